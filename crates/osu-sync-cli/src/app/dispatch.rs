@@ -16,6 +16,7 @@ enum ScreenKey {
     Statistics,
     CollectionConfig { selected: usize },
     CollectionSync,
+    CollectionConflict { selected: usize, apply_to_all: bool },
     CollectionSummary,
     DryRunPreview,
     BackupConfig { selected: usize },
@@ -92,6 +93,14 @@ impl App {
                 selected: *selected,
             }),
             AppState::CollectionSync { .. } => Some(ScreenKey::CollectionSync),
+            AppState::CollectionConflictDialog {
+                selected,
+                apply_to_all,
+                ..
+            } => Some(ScreenKey::CollectionConflict {
+                selected: *selected,
+                apply_to_all: *apply_to_all,
+            }),
             AppState::CollectionSummary { .. } => Some(ScreenKey::CollectionSummary),
             AppState::DryRunPreview { .. } => Some(ScreenKey::DryRunPreview),
             AppState::BackupConfig { selected, .. } => Some(ScreenKey::BackupConfig {
@@ -142,6 +151,10 @@ impl App {
                     self.handle_collection_config_key(key, selected)
                 }
                 ScreenKey::CollectionSync => self.handle_collection_sync_key(key),
+                ScreenKey::CollectionConflict {
+                    selected,
+                    apply_to_all,
+                } => self.handle_collection_conflict_key(key, selected, apply_to_all),
                 ScreenKey::CollectionSummary => self.handle_collection_summary_key(key),
                 ScreenKey::DryRunPreview => self.handle_dry_run_preview_key(key),
                 ScreenKey::BackupConfig { selected } => {
@@ -4,23 +4,56 @@
 //!   osu-sync --cli scan                    Scan installations
 //!   osu-sync --cli dry-run <direction>     Preview sync
 //!   osu-sync --cli sync <direction>        Perform sync
+//!   osu-sync --cli sync --favorites        Sync stable's Favourites collection to lazer
+//!   osu-sync --cli compare                 Diff stable and lazer (exit 1 if different)
+//!   osu-sync --cli list --target <t>       Dump per-difficulty metadata (CSV)
+//!   osu-sync --cli doctor                  Find broken stable beatmap folders
+//!   osu-sync --cli integrity               Find .osu files edited since import
+//!   osu-sync --cli history                 Show recent syncs, backups and restores
+//!   osu-sync --cli export-playlist         Copy a collection's audio plus an .m3u8 playlist
 //!
 //! Directions: stable-to-lazer, lazer-to-stable, bidirectional
 //!
 //! Options:
 //!   --set-ids <ids>    Comma-separated beatmap set IDs to sync
 //!   --json             Output in JSON format
+//!   --target <t>       Installation to list: stable, lazer
+//!   --format <f>       List output format: csv, json (JSON lines)
+//!   --limit <n>        Number of history entries to show (default: 20)
+//!   --collection <c>   Collection to export as a playlist (default: Favourites)
+//!   --output <dir>     Folder for the exported playlist (default: ./<collection>)
+//!   --threads <n>      Worker threads for parallel scanning and syncing
+//!   --max-rate <rate>  Cap sync writes, e.g. 20MB (bytes per second)
+//!   --pause-while-playing  Wait while osu! is running, before and between sets
+//!   --profile <name>   Use the install paths of a named config profile
+//!   -v, -vv            Print every synced set; -vv adds debug logging
+//!   -q, --quiet        Only print summaries and errors, without item lists
 
-use std::collections::HashSet;
-use std::sync::atomic::AtomicBool;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
 use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
 
+use tracing::Level;
+use tracing_subscriber::FmtSubscriber;
+
+use osu_sync_core::audit::{record_operation, AuditLog, AuditOperation, AuditOutcome, AuditRecord};
+use osu_sync_core::beatmap::BeatmapSet;
+use osu_sync_core::collection::{export_collection_audio, Collection, StableCollectionReader};
 use osu_sync_core::config::Config;
-use osu_sync_core::lazer::LazerDatabase;
-use osu_sync_core::stable::StableScanner;
+use osu_sync_core::dedup::{DuplicateIndex, DuplicateStrategy};
+use osu_sync_core::hash_cache::HashCache;
+use osu_sync_core::lazer::{LazerBeatmapSet, LazerDatabase, StableDatabase};
+use osu_sync_core::stable::{BeatmapIndex, LibraryIssue, StableScanner};
+use osu_sync_core::stats::{BeatmapCsvWriter, IntegrityChecker, IntegrityEntry, IntegrityReport};
 use osu_sync_core::sync::{
-    DryRunResult, SyncDirection, SyncEngineBuilder, SyncProgress, SyncResult,
+    format_bytes, DryRunAction, DryRunItem, DryRunResult, ProgressCallback, SyncDirection,
+    SyncEngineBuilder, SyncEvent, SyncProgress, SyncResult, ThrottleConfig,
 };
+use osu_sync_core::utils::{sanitize_filename, set_worker_threads};
 
 /// CLI command to execute
 #[derive(Debug, Clone)]
@@ -34,12 +67,110 @@ pub enum CliCommand {
         direction: SyncDirection,
         set_ids: Option<HashSet<i32>>,
     },
+    /// Sync the sets in stable's Favourites collection to lazer
+    SyncFavorites,
+    Compare,
+    List {
+        target: ListTarget,
+        format: ListFormat,
+    },
+    Doctor,
+    Integrity,
+    /// Show the most recent entries of the audit log
+    History {
+        limit: usize,
+    },
+    /// Copy the audio of a stable collection and write a playlist of it
+    ExportPlaylist {
+        collection: String,
+        output: Option<PathBuf>,
+    },
+}
+
+/// Collection exported by `export-playlist` when `--collection` isn't given
+const DEFAULT_PLAYLIST_COLLECTION: &str = "Favourites";
+
+/// History entries shown when `--limit` isn't given
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Installation to list beatmaps from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListTarget {
+    #[default]
+    Stable,
+    Lazer,
+}
+
+/// Output format for the list command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Json,
+}
+
+/// How much the CLI prints besides the final result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only the final summary and errors
+    Quiet,
+    /// Progress line and summary
+    #[default]
+    Normal,
+    /// Every imported, skipped or failed set with its reason, plus info logs
+    Verbose,
+    /// Like `Verbose`, with debug logs
+    Debug,
+}
+
+impl Verbosity {
+    /// One step more verbose, for repeated `-v`
+    fn more(self) -> Self {
+        match self {
+            Self::Quiet | Self::Normal => Self::Verbose,
+            Self::Verbose | Self::Debug => Self::Debug,
+        }
+    }
+
+    /// Tracing level to log at; `None` keeps logging off
+    fn log_level(self) -> Option<Level> {
+        match self {
+            Self::Quiet | Self::Normal => None,
+            Self::Verbose => Some(Level::INFO),
+            Self::Debug => Some(Level::DEBUG),
+        }
+    }
 }
 
 /// CLI options
 #[derive(Debug, Clone, Default)]
 pub struct CliOptions {
     pub json: bool,
+    pub verbosity: Verbosity,
+    /// Worker threads for parallel work; `None` uses one per logical CPU
+    pub threads: Option<usize>,
+    /// Config profile to use instead of the active one
+    pub profile: Option<String>,
+    /// Cap on sync writes, in bytes per second
+    pub max_rate: Option<u64>,
+    /// Wait between sets while a game is running
+    pub pause_while_playing: bool,
+}
+
+impl CliOptions {
+    /// Throttle for syncs, if any limit was requested
+    fn throttle(&self) -> Option<ThrottleConfig> {
+        (self.max_rate.is_some() || self.pause_while_playing).then_some(ThrottleConfig {
+            max_bytes_per_second: self.max_rate,
+            pause_while_playing: self.pause_while_playing,
+        })
+    }
+
+    /// Whether `--quiet` asked for summaries only, without per-item lists
+    fn quiet(&self) -> bool {
+        self.verbosity == Verbosity::Quiet
+    }
 }
 
 /// Parse CLI arguments and return command + options
@@ -47,12 +178,31 @@ pub fn parse_args(args: &[String]) -> Result<(CliCommand, CliOptions), String> {
     let mut options = CliOptions::default();
     let mut command: Option<CliCommand> = None;
     let mut set_ids: Option<HashSet<i32>> = None;
+    let mut list_target = ListTarget::default();
+    let mut list_format = ListFormat::default();
+    let mut favorites = false;
+    let mut history_limit = DEFAULT_HISTORY_LIMIT;
+    let mut collection_name = DEFAULT_PLAYLIST_COLLECTION.to_string();
+    let mut output: Option<PathBuf> = None;
 
     let mut i = 0;
     while i < args.len() {
         let arg = &args[i];
         match arg.as_str() {
             "--json" => options.json = true,
+            "-q" | "--quiet" => options.verbosity = Verbosity::Quiet,
+            "-v" => options.verbosity = options.verbosity.more(),
+            "-vv" => options.verbosity = Verbosity::Debug,
+            "--favorites" | "--favourites" => favorites = true,
+            "--pause-while-playing" => options.pause_while_playing = true,
+            "--max-rate" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--max-rate requires a value".to_string());
+                }
+                options.max_rate =
+                    Some(ThrottleConfig::parse_rate(&args[i]).map_err(|e| e.to_string())?);
+            }
             "--set-ids" => {
                 i += 1;
                 if i >= args.len() {
@@ -60,7 +210,88 @@ pub fn parse_args(args: &[String]) -> Result<(CliCommand, CliOptions), String> {
                 }
                 set_ids = Some(parse_set_ids(&args[i])?);
             }
+            "--profile" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--profile requires a value".to_string());
+                }
+                options.profile = Some(args[i].clone());
+            }
+            "--threads" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--threads requires a value".to_string());
+                }
+                options.threads = match args[i].parse::<usize>() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => return Err(format!("Invalid thread count '{}'", args[i])),
+                };
+            }
+            "--limit" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--limit requires a value".to_string());
+                }
+                history_limit = match args[i].parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("Invalid limit '{}'", args[i])),
+                };
+            }
+            "--collection" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--collection requires a value".to_string());
+                }
+                collection_name = args[i].clone();
+            }
+            "--output" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--output requires a value".to_string());
+                }
+                output = Some(PathBuf::from(&args[i]));
+            }
+            "--target" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--target requires a value".to_string());
+                }
+                list_target = match args[i].to_lowercase().as_str() {
+                    "stable" => ListTarget::Stable,
+                    "lazer" => ListTarget::Lazer,
+                    other => {
+                        return Err(format!("Invalid target '{}'. Use: stable or lazer", other))
+                    }
+                };
+            }
+            "--format" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("--format requires a value".to_string());
+                }
+                list_format = match args[i].to_lowercase().as_str() {
+                    "csv" => ListFormat::Csv,
+                    "json" => ListFormat::Json,
+                    other => return Err(format!("Invalid format '{}'. Use: csv or json", other)),
+                };
+            }
+            "list" | "export-list" => {
+                command = Some(CliCommand::List {
+                    target: ListTarget::default(),
+                    format: ListFormat::default(),
+                })
+            }
             "scan" => command = Some(CliCommand::Scan),
+            "compare" => command = Some(CliCommand::Compare),
+            "doctor" => command = Some(CliCommand::Doctor),
+            "integrity" => command = Some(CliCommand::Integrity),
+            "history" => command = Some(CliCommand::History { limit: 0 }),
+            "export-playlist" => {
+                command = Some(CliCommand::ExportPlaylist {
+                    collection: String::new(),
+                    output: None,
+                })
+            }
             "dry-run" => {
                 i += 1;
                 if i >= args.len() {
@@ -73,6 +304,15 @@ pub fn parse_args(args: &[String]) -> Result<(CliCommand, CliOptions), String> {
                 });
             }
             "sync" => {
+                // Favourites only exist in stable, so `sync --favorites` needs no direction
+                if args
+                    .get(i + 1)
+                    .is_some_and(|next| matches!(next.as_str(), "--favorites" | "--favourites"))
+                {
+                    command = Some(CliCommand::SyncFavorites);
+                    i += 1;
+                    continue;
+                }
                 i += 1;
                 if i >= args.len() {
                     return Err("sync requires a direction".to_string());
@@ -94,12 +334,35 @@ pub fn parse_args(args: &[String]) -> Result<(CliCommand, CliOptions), String> {
 
     // Apply set_ids to command if present
     let command = match command {
+        Some(ref cmd)
+            if favorites && !matches!(cmd, CliCommand::Sync { .. } | CliCommand::SyncFavorites) =>
+        {
+            return Err("--favorites is only supported by sync".to_string());
+        }
         Some(CliCommand::DryRun { direction, .. }) => CliCommand::DryRun { direction, set_ids },
+        Some(CliCommand::Sync { direction, .. }) if favorites => {
+            if direction != SyncDirection::StableToLazer {
+                return Err("--favorites only supports stable-to-lazer".to_string());
+            }
+            CliCommand::SyncFavorites
+        }
         Some(CliCommand::Sync { direction, .. }) => CliCommand::Sync { direction, set_ids },
+        Some(CliCommand::List { .. }) => CliCommand::List {
+            target: list_target,
+            format: list_format,
+        },
+        Some(CliCommand::History { .. }) => CliCommand::History {
+            limit: history_limit,
+        },
+        Some(CliCommand::ExportPlaylist { .. }) => CliCommand::ExportPlaylist {
+            collection: collection_name,
+            output,
+        },
         Some(cmd) => cmd,
-        None => {
-            return Err("No command specified. Use: scan, dry-run <dir>, or sync <dir>".to_string())
-        }
+        None => return Err(
+            "No command specified. Use: scan, compare, list, doctor, integrity, history, export-playlist, dry-run <dir>, or sync <dir>"
+                .to_string(),
+        ),
     };
 
     Ok((command, options))
@@ -129,16 +392,95 @@ fn parse_set_ids(s: &str) -> Result<HashSet<i32>, String> {
 
 /// Run CLI command
 pub fn run(command: CliCommand, options: CliOptions) -> anyhow::Result<()> {
+    init_logging(options.verbosity);
+    if let Some(threads) = options.threads {
+        set_worker_threads(threads)?;
+    }
+
+    // An explicit --profile wins over the profile saved in the config
+    let config = Config::load();
+    let config = match options
+        .profile
+        .as_deref()
+        .or(config.active_profile.as_deref())
+    {
+        Some(name) => config.with_profile(name)?,
+        None => config,
+    };
+
     match command {
-        CliCommand::Scan => run_scan(options),
-        CliCommand::DryRun { direction, set_ids } => run_dry_run(direction, set_ids, options),
-        CliCommand::Sync { direction, set_ids } => run_sync(direction, set_ids, options),
+        CliCommand::Scan => run_scan(config, options),
+        CliCommand::DryRun { direction, set_ids } => {
+            run_dry_run(config, direction, set_ids, options)
+        }
+        CliCommand::Sync { direction, set_ids } => run_sync(config, direction, set_ids, options),
+        CliCommand::SyncFavorites => run_sync_favorites(config, options),
+        CliCommand::Compare => run_compare(config, options),
+        CliCommand::List { target, format } => run_list(config, target, format),
+        CliCommand::Doctor => run_doctor(config, options),
+        CliCommand::Integrity => run_integrity(config, options),
+        CliCommand::History { limit } => run_history(limit, options),
+        CliCommand::ExportPlaylist { collection, output } => {
+            run_export_playlist(config, &collection, output, options)
+        }
     }
 }
 
-fn run_scan(options: CliOptions) -> anyhow::Result<()> {
-    let config = Config::load();
+/// Log to stderr when running with `-v`
+fn init_logging(verbosity: Verbosity) {
+    if let Some(level) = verbosity.log_level() {
+        let subscriber = FmtSubscriber::builder()
+            .with_max_level(level)
+            .with_target(false)
+            .with_writer(std::io::stderr)
+            .finish();
+        let _ = tracing::subscriber::set_global_default(subscriber);
+    }
+}
+
+/// Progress line for syncs, shown only at normal verbosity without `--json`
+fn sync_progress_callback(options: &CliOptions) -> ProgressCallback {
+    if options.json || options.verbosity != Verbosity::Normal {
+        return Box::new(|_| {});
+    }
+    Box::new(|progress: SyncProgress| {
+        eprint!(
+            "\rSyncing: {}/{} - {}",
+            progress.current, progress.total, progress.current_name
+        );
+    })
+}
+
+/// Print each imported, skipped or failed set as the engine reports it
+///
+/// The thread exits once every clone of the returned sender is dropped.
+fn spawn_event_printer() -> (Sender<SyncEvent>, JoinHandle<()>) {
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        for event in rx {
+            if matches!(
+                event,
+                SyncEvent::SetImported { .. }
+                    | SyncEvent::SetSkipped { .. }
+                    | SyncEvent::SetFailed { .. }
+                    | SyncEvent::WaitingForGameExit { .. }
+            ) {
+                eprintln!("{}", event);
+            }
+        }
+    });
+    (tx, handle)
+}
+
+/// Wait for the event printer once the engine that held its sender is gone
+fn finish_event_printer(printer: Option<(Sender<SyncEvent>, JoinHandle<()>)>) {
+    if let Some((tx, handle)) = printer {
+        drop(tx);
+        let _ = handle.join();
+    }
+}
 
+fn run_scan(config: Config, options: CliOptions) -> anyhow::Result<()> {
     let stable_result = if let Some(ref stable_path) = config.stable_path {
         let songs_path = stable_path.join("Songs");
         if songs_path.exists() {
@@ -212,32 +554,21 @@ fn run_scan(options: CliOptions) -> anyhow::Result<()> {
 }
 
 fn run_dry_run(
+    config: Config,
     direction: SyncDirection,
     set_ids: Option<HashSet<i32>>,
     options: CliOptions,
 ) -> anyhow::Result<()> {
-    let config = Config::load();
-
-    let stable_path = config
-        .stable_path
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("osu!stable path not configured"))?;
-    let lazer_path = config
-        .lazer_path
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("osu!lazer path not configured"))?;
+    let songs_path = config.require_stable_songs_path()?;
+    let lazer_path = config.require_lazer_path()?;
 
-    let songs_path = stable_path.join("Songs");
     let scanner = StableScanner::new(songs_path).skip_hashing();
-    let database = LazerDatabase::open(lazer_path)?;
-
-    let cancelled = Arc::new(AtomicBool::new(false));
+    let database = LazerDatabase::open(&lazer_path)?;
 
     let mut builder = SyncEngineBuilder::new()
         .config(config)
         .stable_scanner(scanner)
-        .lazer_database(database)
-        .cancellation(Arc::clone(&cancelled));
+        .lazer_database(database);
 
     if let Some(ids) = set_ids {
         builder = builder.selected_set_ids(ids);
@@ -252,54 +583,54 @@ fn run_dry_run(
 }
 
 fn run_sync(
+    config: Config,
     direction: SyncDirection,
     set_ids: Option<HashSet<i32>>,
     options: CliOptions,
 ) -> anyhow::Result<()> {
-    let config = Config::load();
-
-    let stable_path = config
-        .stable_path
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("osu!stable path not configured"))?;
-    let lazer_path = config
-        .lazer_path
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("osu!lazer path not configured"))?;
+    let songs_path = config.require_stable_songs_path()?;
+    let lazer_path = config.require_lazer_path()?;
 
-    let songs_path = stable_path.join("Songs");
     let scanner = StableScanner::new(songs_path).skip_hashing();
-    let database = LazerDatabase::open(lazer_path)?;
-
-    let cancelled = Arc::new(AtomicBool::new(false));
-
-    // Progress callback for non-JSON mode
-    let show_progress = !options.json;
-    let progress_callback: Box<dyn Fn(SyncProgress) + Send + Sync> = if show_progress {
-        Box::new(|progress: SyncProgress| {
-            eprint!(
-                "\rSyncing: {}/{} - {}",
-                progress.current, progress.total, progress.current_name
-            );
-        })
-    } else {
-        Box::new(|_| {})
-    };
+    let database = LazerDatabase::open(&lazer_path)?;
 
+    let show_progress = !options.json && options.verbosity == Verbosity::Normal;
     let mut builder = SyncEngineBuilder::new()
         .config(config)
         .stable_scanner(scanner)
         .lazer_database(database)
-        .progress_callback(progress_callback)
-        .cancellation(Arc::clone(&cancelled));
+        .progress_callback(sync_progress_callback(&options));
+
+    if let Some(throttle) = options.throttle() {
+        builder = builder.throttle(throttle);
+    }
 
     if let Some(ids) = set_ids {
         builder = builder.selected_set_ids(ids);
     }
 
+    let printer = (options.verbosity >= Verbosity::Verbose).then(spawn_event_printer);
+    if let Some((tx, _)) = &printer {
+        builder = builder.event_sender(tx.clone());
+    }
+
     let engine = builder.build()?;
     let resolver = osu_sync_core::sync::AutoResolver::skip_all();
-    let result = engine.sync(direction, &resolver)?;
+    let start = Instant::now();
+    let result = engine.sync(direction, &resolver);
+    let elapsed = start.elapsed();
+    drop(engine);
+    finish_event_printer(printer);
+    if let Err(e) = &result {
+        record_operation(&AuditRecord::failed(
+            AuditOperation::Sync,
+            direction.to_string(),
+            elapsed,
+            e.to_string(),
+        ));
+    }
+    let result = result?;
+    record_operation(&AuditRecord::sync(&result, elapsed));
 
     if show_progress {
         eprintln!(); // New line after progress
@@ -310,9 +641,557 @@ fn run_sync(
     Ok(())
 }
 
-fn print_dry_run_result(result: &DryRunResult, options: CliOptions) {
-    use osu_sync_core::sync::DryRunAction;
+fn run_sync_favorites(config: Config, options: CliOptions) -> anyhow::Result<()> {
+    let songs_path = config.require_stable_songs_path()?;
+    let lazer_path = config.require_lazer_path()?;
+
+    let scanner = StableScanner::new(songs_path).skip_hashing();
+    let database = LazerDatabase::open(&lazer_path)?;
+
+    let show_progress = !options.json && options.verbosity == Verbosity::Normal;
+    let mut builder = SyncEngineBuilder::new()
+        .config(config)
+        .stable_scanner(scanner)
+        .lazer_database(database)
+        .progress_callback(sync_progress_callback(&options));
+
+    if let Some(throttle) = options.throttle() {
+        builder = builder.throttle(throttle);
+    }
+
+    let printer = (options.verbosity >= Verbosity::Verbose).then(spawn_event_printer);
+    if let Some((tx, _)) = &printer {
+        builder = builder.event_sender(tx.clone());
+    }
+
+    let engine = builder.build()?;
+    let resolver = osu_sync_core::sync::AutoResolver::skip_all();
+    let start = Instant::now();
+    let favorites = engine.sync_favorites(&resolver);
+    let elapsed = start.elapsed();
+    drop(engine);
+    finish_event_printer(printer);
+    if let Err(e) = &favorites {
+        record_operation(&AuditRecord::failed(
+            AuditOperation::Sync,
+            SyncDirection::StableToLazer.to_string(),
+            elapsed,
+            e.to_string(),
+        ));
+    }
+    let favorites = favorites?;
+    record_operation(&AuditRecord::sync(&favorites.result, elapsed));
+
+    if show_progress {
+        eprintln!(); // New line after progress
+    }
 
+    if options.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "imported": favorites.result.imported,
+                "failed": favorites.result.failed,
+                "skipped": favorites.result.skipped,
+                "missing": favorites.missing,
+            })
+        );
+    } else {
+        let quiet = options.quiet();
+        print_sync_result(&favorites.result, options);
+        if !favorites.missing.is_empty() {
+            println!();
+            println!(
+                "Favourites not in the Songs folder ({}){}",
+                favorites.missing.len(),
+                if quiet { "" } else { ":" }
+            );
+            if !quiet {
+                for md5 in &favorites.missing {
+                    println!("  - {}", md5);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Differences between the stable and lazer installations
+///
+/// Each list is a [`DryRunResult`] so the same item formatting and
+/// summary counts as a dry run can be reused.
+#[derive(Debug, Default)]
+struct CompareReport {
+    /// Sets present only in osu!stable
+    stable_only: DryRunResult,
+    /// Sets present only in osu!lazer
+    lazer_only: DryRunResult,
+    /// Sets present in both (same online ID) whose difficulties differ
+    differing: DryRunResult,
+}
+
+impl CompareReport {
+    fn has_differences(&self) -> bool {
+        self.stable_only.total_items() > 0
+            || self.lazer_only.total_items() > 0
+            || self.differing.total_items() > 0
+    }
+}
+
+/// Match stable and lazer sets using the duplicate index (read-only)
+fn compare_sets(stable_sets: &[BeatmapSet], lazer_sets: &[LazerBeatmapSet]) -> CompareReport {
+    let lazer_as_sets: Vec<BeatmapSet> = lazer_sets.iter().map(lazer_to_beatmap_set).collect();
+    let stable_index = DuplicateIndex::build(stable_sets);
+    let lazer_index = DuplicateIndex::build(&lazer_as_sets);
+    let strategy = DuplicateStrategy::Composite;
+
+    let lazer_by_id: HashMap<i32, &BeatmapSet> = lazer_as_sets
+        .iter()
+        .filter_map(|set| set.id.map(|id| (id, set)))
+        .collect();
+
+    let mut report = CompareReport::default();
+
+    for set in stable_sets {
+        if !lazer_index.is_duplicate(set, strategy) {
+            report
+                .stable_only
+                .add_item(DryRunItem::from_beatmap_set(set, DryRunAction::Import));
+        } else if let Some(lazer_set) = set.id.and_then(|id| lazer_by_id.get(&id)) {
+            if difficulty_hashes(set) != difficulty_hashes(lazer_set) {
+                report
+                    .differing
+                    .add_item(DryRunItem::from_beatmap_set(set, DryRunAction::Duplicate));
+            }
+        }
+    }
+
+    for (lazer_set, as_set) in lazer_sets.iter().zip(&lazer_as_sets) {
+        if !stable_index.is_duplicate(as_set, strategy) {
+            report
+                .lazer_only
+                .add_item(DryRunItem::from_lazer_set(lazer_set, DryRunAction::Import));
+        }
+    }
+
+    report
+}
+
+/// MD5 hashes of all difficulties in a set, ignoring missing hashes
+fn difficulty_hashes(set: &BeatmapSet) -> HashSet<&str> {
+    set.beatmaps
+        .iter()
+        .map(|b| b.md5_hash.as_str())
+        .filter(|h| !h.is_empty())
+        .collect()
+}
+
+/// Convert a lazer set for indexing without needing an open database
+fn lazer_to_beatmap_set(lazer_set: &LazerBeatmapSet) -> BeatmapSet {
+    use osu_sync_core::beatmap::BeatmapInfo;
+
+    BeatmapSet {
+        id: lazer_set.online_id,
+        beatmaps: lazer_set
+            .beatmaps
+            .iter()
+            .map(|lb| BeatmapInfo {
+                metadata: lb.metadata.clone(),
+                md5_hash: lb.md5_hash.clone(),
+                hash: lb.hash.clone(),
+                version: lb.version.clone(),
+                mode: lb.mode,
+                ..Default::default()
+            })
+            .collect(),
+        files: Vec::new(),
+        folder_name: None,
+        missing_files: Vec::new(),
+        folder_name_os: None,
+    }
+}
+
+fn run_compare(config: Config, options: CliOptions) -> anyhow::Result<()> {
+    let songs_path = config.require_stable_songs_path()?;
+    let lazer_path = config.require_lazer_path()?;
+
+    let stable_sets = StableScanner::new(songs_path)
+        .skip_hashing()
+        .scan_parallel()?;
+    let lazer_sets = LazerDatabase::open(&lazer_path)?.get_all_beatmap_sets()?;
+
+    let report = compare_sets(&stable_sets, &lazer_sets);
+    print_compare_report(&report, options);
+
+    // Nonzero exit lets scripts assert the installs are in sync
+    if report.has_differences() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn print_compare_report(report: &CompareReport, options: CliOptions) {
+    let item_json = |item: &DryRunItem| {
+        serde_json::json!({
+            "set_id": item.set_id,
+            "title": item.title,
+            "artist": item.artist,
+            "difficulty_count": item.difficulty_count,
+        })
+    };
+
+    if options.json {
+        let list = |result: &DryRunResult| result.items.iter().map(item_json).collect::<Vec<_>>();
+        println!(
+            "{}",
+            serde_json::json!({
+                "in_sync": !report.has_differences(),
+                "stable_only": list(&report.stable_only),
+                "lazer_only": list(&report.lazer_only),
+                "differing": list(&report.differing),
+            })
+        );
+        return;
+    }
+
+    println!("Compare Results:");
+    println!("  Stable only: {}", report.stable_only.total_items());
+    println!("  Lazer only:  {}", report.lazer_only.total_items());
+    println!("  Differing:   {}", report.differing.total_items());
+
+    let lists = if options.quiet() {
+        Vec::new()
+    } else {
+        vec![
+            ("Only in osu!stable", &report.stable_only),
+            ("Only in osu!lazer", &report.lazer_only),
+            ("In both but differing", &report.differing),
+        ]
+    };
+    for (label, result) in lists {
+        if result.items.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}:", label);
+        for item in &result.items {
+            println!(
+                "  [{}] {} - {}",
+                item.set_id.map(|id| id.to_string()).unwrap_or_default(),
+                item.artist,
+                item.title
+            );
+        }
+    }
+
+    if !report.has_differences() {
+        println!();
+        println!("Installations are in sync.");
+    }
+}
+
+fn run_list(config: Config, target: ListTarget, format: ListFormat) -> anyhow::Result<()> {
+    let sets: Vec<BeatmapSet> = match target {
+        ListTarget::Stable => {
+            let songs_path = config.require_stable_songs_path()?;
+            StableScanner::new(songs_path)
+                .skip_hashing()
+                .scan_parallel()?
+        }
+        ListTarget::Lazer => {
+            let lazer_path = config.require_lazer_path()?;
+            let database = LazerDatabase::open(&lazer_path)?;
+            database
+                .get_all_beatmap_sets()?
+                .iter()
+                .map(|set| database.to_beatmap_set(set))
+                .collect()
+        }
+    };
+
+    // Stream rows straight to stdout rather than building the output in memory
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+
+    match format {
+        ListFormat::Csv => {
+            let mut writer = BeatmapCsvWriter::new(&mut out)?;
+            for set in &sets {
+                writer.write_set(set)?;
+            }
+            writer.finish()?;
+        }
+        ListFormat::Json => {
+            for set in &sets {
+                for beatmap in &set.beatmaps {
+                    serde_json::to_writer(
+                        &mut out,
+                        &serde_json::json!({
+                            "set_id": set.id,
+                            "artist": beatmap.metadata.artist,
+                            "title": beatmap.metadata.title,
+                            "version": beatmap.version,
+                            "mode": format!("{:?}", beatmap.mode),
+                            "star_rating": beatmap.star_rating,
+                            "bpm": beatmap.bpm,
+                            "length_ms": beatmap.length_ms,
+                            "ranked_status": beatmap.ranked_status.map(|s| s.to_string()),
+                        }),
+                    )?;
+                    writeln!(out)?;
+                }
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn run_doctor(config: Config, options: CliOptions) -> anyhow::Result<()> {
+    let songs_path = config.require_stable_songs_path()?;
+
+    let issues = StableScanner::new(songs_path).verify_library()?;
+    print_doctor_report(&issues, options);
+
+    // Nonzero exit lets scripts detect a broken library; warnings alone don't count
+    if issues.iter().any(|issue| !issue.kind.is_warning()) {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn print_doctor_report(issues: &[LibraryIssue], options: CliOptions) {
+    if options.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "issue_count": issues.len(),
+                "issues": issues,
+            })
+        );
+        return;
+    }
+
+    if issues.is_empty() {
+        println!("No problems found in the osu!stable library.");
+        return;
+    }
+
+    if options.quiet() {
+        println!("Library problems: {}", issues.len());
+        return;
+    }
+    println!("Library problems ({}):", issues.len());
+    for issue in issues {
+        let severity = if issue.kind.is_warning() {
+            "warning "
+        } else {
+            ""
+        };
+        println!(
+            "  [{}{}] {}: {}",
+            severity,
+            issue.kind.code(),
+            issue.folder.display(),
+            issue.kind
+        );
+    }
+}
+
+fn run_history(limit: usize, options: CliOptions) -> anyhow::Result<()> {
+    let records = match AuditLog::open_default() {
+        Some(log) => log.recent(limit)?,
+        None => Vec::new(),
+    };
+
+    if options.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "count": records.len(),
+                "records": records,
+            })
+        );
+        return Ok(());
+    }
+
+    if records.is_empty() {
+        println!("No operations recorded yet.");
+        return Ok(());
+    }
+
+    println!("Recent operations ({}):", records.len());
+    for record in &records {
+        let outcome = match record.outcome {
+            AuditOutcome::Success => "ok",
+            AuditOutcome::Partial => "partial",
+            AuditOutcome::Failed => "failed",
+        };
+        println!(
+            "  {}  {:<10} {:<8} {}",
+            record
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            record.operation.display_name(),
+            outcome,
+            record.target
+        );
+        println!(
+            "      {} succeeded, {} skipped, {} failed, {} written in {:.1}s",
+            record.succeeded,
+            record.skipped,
+            record.failed,
+            format_bytes(record.bytes),
+            record.duration_ms as f64 / 1000.0
+        );
+        if let Some(error) = &record.error {
+            println!("      Error: {}", error);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_export_playlist(
+    config: Config,
+    collection_name: &str,
+    output: Option<PathBuf>,
+    options: CliOptions,
+) -> anyhow::Result<()> {
+    let stable_path = config.require_stable_path()?;
+    let songs_path = config.require_stable_songs_path()?;
+
+    let collections = StableCollectionReader::read(stable_path.join("collection.db"))?;
+    let collection = Collection::find(&collections, collection_name)
+        .ok_or_else(|| anyhow::anyhow!("No collection named '{}'", collection_name))?;
+    let output = output.unwrap_or_else(|| PathBuf::from(sanitize_filename(&collection.name)));
+
+    // osu!.db already has every difficulty's MD5, so no Songs scan is needed
+    let db = StableDatabase::open(&stable_path)?;
+    let sets = db
+        .get_all_beatmap_sets()?
+        .iter()
+        .map(|s| db.to_beatmap_set(s))
+        .collect();
+    let index = BeatmapIndex::new(sets);
+
+    let start = Instant::now();
+    let export = export_collection_audio(collection, &index, &songs_path, &output);
+    let elapsed = start.elapsed();
+    let target = output.display().to_string();
+    if let Err(e) = &export {
+        record_operation(&AuditRecord::failed(
+            AuditOperation::Extraction,
+            target.clone(),
+            elapsed,
+            e.to_string(),
+        ));
+    }
+    let export = export?;
+    record_operation(
+        &AuditRecord::new(AuditOperation::Extraction, target, elapsed)
+            .with_counts(export.tracks.len(), 0, 0)
+            .with_bytes(export.bytes_written),
+    );
+
+    if options.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "playlist": export.playlist,
+                "tracks": export.tracks,
+                "bytes_written": export.bytes_written,
+                "missing": export.missing,
+            })
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Exported {} tracks ({}) from '{}'",
+        export.tracks.len(),
+        format_bytes(export.bytes_written),
+        collection.name
+    );
+    println!("Playlist: {}", export.playlist.display());
+    if !export.missing.is_empty() {
+        println!();
+        println!(
+            "Left out of the playlist, not installed ({}){}",
+            export.missing.len(),
+            if options.quiet() { "" } else { ":" }
+        );
+        if !options.quiet() {
+            for md5 in &export.missing {
+                println!("  - {}", md5);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_integrity(config: Config, options: CliOptions) -> anyhow::Result<()> {
+    let stable_path = config.require_stable_path()?;
+
+    let db = StableDatabase::open(&stable_path)?;
+    let hash_cache = Arc::new(HashCache::load());
+    let report = IntegrityChecker::check_database_cached(&db, hash_cache.clone())?;
+    if let Err(e) = hash_cache.save() {
+        eprintln!("Warning: Failed to save hash cache: {}", e);
+    }
+    print_integrity_report(&report, options);
+
+    // Nonzero exit lets scripts flag libraries with edited maps
+    if report.has_findings() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn print_integrity_report(report: &IntegrityReport, options: CliOptions) {
+    if options.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "verified": report.verified,
+                "edited": report.edited,
+                "unknown": report.unknown,
+                "missing": report.missing,
+            })
+        );
+        return;
+    }
+
+    println!("Integrity Results:");
+    println!("  Verified:           {}", report.verified);
+    println!("  Edited:             {}", report.edited.len());
+    println!("  Not in osu!.db:     {}", report.unknown.len());
+    println!("  Missing from disk:  {}", report.missing.len());
+
+    let print_entries = |label: &str, entries: &[IntegrityEntry]| {
+        if entries.is_empty() || options.quiet() {
+            return;
+        }
+        println!();
+        println!("{}:", label);
+        for entry in entries {
+            println!("  {}/{}", entry.folder_name, entry.file_name);
+        }
+    };
+    print_entries("Edited since import", &report.edited);
+    print_entries("On disk but not in osu!.db", &report.unknown);
+    print_entries("In osu!.db but missing from disk", &report.missing);
+}
+
+fn print_dry_run_result(result: &DryRunResult, options: CliOptions) {
+    let summary = result.summary();
     if options.json {
         let items: Vec<_> = result
             .items
@@ -329,56 +1208,36 @@ fn print_dry_run_result(result: &DryRunResult, options: CliOptions) {
             })
             .collect();
 
-        let import_count = result
-            .items
-            .iter()
-            .filter(|i| matches!(i.action, DryRunAction::Import))
-            .count();
-        let skip_count = result
-            .items
-            .iter()
-            .filter(|i| matches!(i.action, DryRunAction::Skip))
-            .count();
-        let duplicate_count = result
-            .items
-            .iter()
-            .filter(|i| matches!(i.action, DryRunAction::Duplicate))
-            .count();
-
         println!(
             "{}",
             serde_json::json!({
                 "summary": {
-                    "total": result.items.len(),
-                    "import": import_count,
-                    "skip": skip_count,
-                    "duplicate": duplicate_count,
+                    "total": summary.total_items(),
+                    "import": summary.import.count,
+                    "skip": summary.skip.count,
+                    "duplicate": summary.duplicate.count,
+                    "import_bytes": summary.import.size_bytes,
+                    "skip_bytes": summary.skip.size_bytes,
+                    "duplicate_bytes": summary.duplicate.size_bytes,
                 },
                 "items": items
             })
         );
     } else {
-        let import_count = result
-            .items
-            .iter()
-            .filter(|i| matches!(i.action, DryRunAction::Import))
-            .count();
-        let skip_count = result
-            .items
-            .iter()
-            .filter(|i| matches!(i.action, DryRunAction::Skip))
-            .count();
-        let duplicate_count = result
-            .items
-            .iter()
-            .filter(|i| matches!(i.action, DryRunAction::Duplicate))
-            .count();
+        let import_count = summary.import.count;
 
         println!("Dry Run Results:");
-        println!("  Total:      {}", result.items.len());
-        println!("  To Import:  {}", import_count);
-        println!("  Skip:       {}", skip_count);
-        println!("  Duplicates: {}", duplicate_count);
+        println!("  Total:      {}", summary.total_items());
+        println!(
+            "  To Import:  {} ({})",
+            import_count,
+            format_bytes(summary.import.size_bytes)
+        );
+        println!("  Skip:       {}", summary.skip.count);
+        println!("  Duplicates: {}", summary.duplicate.count);
+        if options.quiet() {
+            return;
+        }
         println!();
 
         // Show first 20 items to import
@@ -418,6 +1277,16 @@ fn print_sync_result(result: &SyncResult, options: CliOptions) {
                 })
             })
             .collect();
+        let slowest: Vec<_> = result
+            .slowest_sets
+            .iter()
+            .map(|(name, elapsed)| {
+                serde_json::json!({
+                    "beatmap_set": name,
+                    "seconds": elapsed.as_secs_f64(),
+                })
+            })
+            .collect();
 
         println!(
             "{}",
@@ -425,6 +1294,9 @@ fn print_sync_result(result: &SyncResult, options: CliOptions) {
                 "imported": result.imported,
                 "failed": result.failed,
                 "skipped": result.skipped,
+                "bytes_reused": result.bytes_reused,
+                "bytes_written": result.bytes_written,
+                "slowest_sets": slowest,
                 "errors": errors,
             })
         );
@@ -433,6 +1305,23 @@ fn print_sync_result(result: &SyncResult, options: CliOptions) {
         println!("  Imported: {}", result.imported);
         println!("  Failed:   {}", result.failed);
         println!("  Skipped:  {}", result.skipped);
+        if result.bytes_written > 0 {
+            println!("  Written:  {}", format_bytes(result.bytes_written));
+        }
+        if result.bytes_reused > 0 {
+            println!(
+                "  Reused:   {} already in the target",
+                format_bytes(result.bytes_reused)
+            );
+        }
+
+        if !result.slowest_sets.is_empty() && !options.quiet() {
+            println!();
+            println!("Slowest sets:");
+            for (name, elapsed) in &result.slowest_sets {
+                println!("  {:>7.1}s  {}", elapsed.as_secs_f64(), name);
+            }
+        }
 
         if !result.errors.is_empty() {
             println!();
@@ -459,6 +1348,13 @@ pub fn print_help() {
     println!("    scan                        Scan and show installations");
     println!("    dry-run <direction>         Preview what would be synced");
     println!("    sync <direction>            Perform sync");
+    println!("    sync --favorites            Sync stable's Favourites collection to lazer");
+    println!("    compare                     Diff stable and lazer (exit 1 if different)");
+    println!("    list                        Dump per-difficulty metadata to stdout");
+    println!("    doctor                      Find stable sets with missing audio or .osu files");
+    println!("    integrity                   Find .osu files that differ from osu!.db");
+    println!("    history                     Show recent syncs, backups and restores");
+    println!("    export-playlist             Copy a collection's audio plus an .m3u8 playlist");
     println!();
     println!("DIRECTIONS:");
     println!("    stable-to-lazer, s2l        Sync from stable to lazer");
@@ -468,12 +1364,36 @@ pub fn print_help() {
     println!("OPTIONS:");
     println!("    --set-ids <ids>             Comma-separated beatmap set IDs");
     println!("    --json                      Output in JSON format");
+    println!("    --target <stable|lazer>     Installation for list (default: stable)");
+    println!("    --format <csv|json>         Output format for list (default: csv)");
+    println!("    --limit <n>                 Entries shown by history (default: 20)");
+    println!(
+        "    --collection <name>         Collection for export-playlist (default: Favourites)"
+    );
+    println!(
+        "    --output <dir>              Folder for export-playlist (default: ./<collection>)"
+    );
+    println!("    --threads <n>               Worker threads (default: logical CPUs)");
+    println!("    --max-rate <rate>           Cap sync writes per second, e.g. 20MB or 512KB");
+    println!("    --pause-while-playing       Wait while osu! is running, before and between sets");
+    println!("    --profile <name>            Use the paths of a named config profile");
+    println!("    -v, -vv                     Print every synced set; -vv adds debug logs");
+    println!("    -q, --quiet                 Only print summaries and errors, without item lists");
     println!();
     println!("EXAMPLES:");
     println!("    osu-sync --cli scan");
     println!("    osu-sync --cli dry-run stable-to-lazer");
     println!("    osu-sync --cli sync s2l --set-ids 123,456,789");
+    println!("    osu-sync --cli sync --favorites");
+    println!("    osu-sync --cli export-playlist --collection Favourites");
+    println!("    osu-sync --cli sync s2l --threads 2");
+    println!("    osu-sync --cli sync s2l --max-rate 20MB --pause-while-playing");
     println!("    osu-sync --cli dry-run bi --json");
+    println!("    osu-sync --cli compare --json");
+    println!("    osu-sync --cli doctor --json");
+    println!("    osu-sync --cli integrity");
+    println!("    osu-sync --cli history --limit 5");
+    println!("    osu-sync --cli list --format csv --target stable > library.csv");
 }
 
 #[cfg(test)]
@@ -564,10 +1484,285 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_args_sync_favorites() {
+        let args = vec!["sync".to_string(), "--favorites".to_string()];
+        let (cmd, _) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::SyncFavorites));
+
+        let args = vec![
+            "sync".to_string(),
+            "s2l".to_string(),
+            "--favorites".to_string(),
+        ];
+        let (cmd, _) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::SyncFavorites));
+
+        let args = vec![
+            "sync".to_string(),
+            "l2s".to_string(),
+            "--favorites".to_string(),
+        ];
+        assert!(parse_args(&args).is_err());
+
+        for other in [vec!["scan"], vec!["dry-run", "s2l"], vec!["compare"]] {
+            let mut args: Vec<String> = other.iter().map(|a| a.to_string()).collect();
+            args.push("--favorites".to_string());
+            assert!(parse_args(&args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_args_threads() {
+        let args = vec!["scan".to_string(), "--threads".to_string(), "2".to_string()];
+        let (_, options) = parse_args(&args).unwrap();
+        assert_eq!(options.threads, Some(2));
+
+        let (_, options) = parse_args(&["scan".to_string()]).unwrap();
+        assert_eq!(options.threads, None);
+
+        for bad in ["0", "many"] {
+            let args = vec!["scan".to_string(), "--threads".to_string(), bad.to_string()];
+            assert!(parse_args(&args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_args_export_playlist() {
+        let (cmd, _) = parse_args(&["export-playlist".to_string()]).unwrap();
+        assert!(matches!(
+            cmd,
+            CliCommand::ExportPlaylist { ref collection, output: None }
+                if collection == DEFAULT_PLAYLIST_COLLECTION
+        ));
+
+        let args = vec![
+            "export-playlist".to_string(),
+            "--collection".to_string(),
+            "Streams".to_string(),
+            "--output".to_string(),
+            "music".to_string(),
+        ];
+        let (cmd, _) = parse_args(&args).unwrap();
+        match cmd {
+            CliCommand::ExportPlaylist { collection, output } => {
+                assert_eq!(collection, "Streams");
+                assert_eq!(output, Some(PathBuf::from("music")));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_args_history() {
+        let (cmd, _) = parse_args(&["history".to_string()]).unwrap();
+        assert!(matches!(
+            cmd,
+            CliCommand::History {
+                limit: DEFAULT_HISTORY_LIMIT
+            }
+        ));
+
+        let args = vec![
+            "history".to_string(),
+            "--limit".to_string(),
+            "5".to_string(),
+        ];
+        let (cmd, _) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::History { limit: 5 }));
+
+        let args = vec![
+            "history".to_string(),
+            "--limit".to_string(),
+            "0".to_string(),
+        ];
+        assert!(parse_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_profile() {
+        let args = vec![
+            "sync".to_string(),
+            "s2l".to_string(),
+            "--profile".to_string(),
+            "work".to_string(),
+        ];
+        let (cmd, options) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::Sync { .. }));
+        assert_eq!(options.profile.as_deref(), Some("work"));
+
+        let args = vec!["scan".to_string(), "--profile".to_string()];
+        assert!(parse_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_max_rate() {
+        let args: Vec<String> = ["sync", "s2l", "--max-rate", "20MB", "--pause-while-playing"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (cmd, options) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::Sync { .. }));
+        let throttle = options.throttle().unwrap();
+        assert_eq!(throttle.max_bytes_per_second, Some(20 * 1024 * 1024));
+        assert!(throttle.pause_while_playing);
+
+        let (_, options) = parse_args(&["scan".to_string()]).unwrap();
+        assert!(options.throttle().is_none());
+
+        let args = vec![
+            "sync".to_string(),
+            "s2l".to_string(),
+            "--max-rate".to_string(),
+            "fast".to_string(),
+        ];
+        assert!(parse_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_verbosity() {
+        let parse = |flags: &[&str]| {
+            let mut args = vec!["scan".to_string()];
+            args.extend(flags.iter().map(|f| f.to_string()));
+            parse_args(&args).unwrap().1.verbosity
+        };
+        assert_eq!(parse(&[]), Verbosity::Normal);
+        assert_eq!(parse(&["--quiet"]), Verbosity::Quiet);
+        assert_eq!(parse(&["-v"]), Verbosity::Verbose);
+        assert_eq!(parse(&["-v", "-v"]), Verbosity::Debug);
+        assert_eq!(parse(&["-vv"]), Verbosity::Debug);
+    }
+
     #[test]
     fn test_parse_args_json_option() {
         let args = vec!["scan".to_string(), "--json".to_string()];
         let (_, options) = parse_args(&args).unwrap();
         assert!(options.json);
     }
+
+    #[test]
+    fn test_parse_args_compare() {
+        let args = vec!["compare".to_string(), "--json".to_string()];
+        let (cmd, options) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::Compare));
+        assert!(options.json);
+    }
+
+    #[test]
+    fn test_parse_args_integrity() {
+        let args = vec!["integrity".to_string()];
+        let (cmd, options) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::Integrity));
+        assert!(!options.json);
+    }
+
+    #[test]
+    fn test_parse_args_doctor() {
+        let args = vec!["doctor".to_string(), "--json".to_string()];
+        let (cmd, options) = parse_args(&args).unwrap();
+        assert!(matches!(cmd, CliCommand::Doctor));
+        assert!(options.json);
+    }
+
+    fn stable_set(id: i32, hashes: &[&str]) -> BeatmapSet {
+        use osu_sync_core::beatmap::BeatmapInfo;
+
+        let mut set = BeatmapSet::new();
+        set.id = Some(id);
+        set.beatmaps = hashes
+            .iter()
+            .map(|h| BeatmapInfo {
+                md5_hash: h.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        set.beatmaps[0].metadata.title = format!("Song {}", id);
+        set
+    }
+
+    fn lazer_set(id: i32, hashes: &[&str]) -> LazerBeatmapSet {
+        use osu_sync_core::lazer::LazerBeatmapInfo;
+
+        let set = stable_set(id, hashes);
+        LazerBeatmapSet {
+            id: format!("guid-{}", id),
+            online_id: Some(id),
+            beatmaps: set
+                .beatmaps
+                .iter()
+                .map(|b| LazerBeatmapInfo {
+                    id: String::new(),
+                    online_id: None,
+                    hash: String::new(),
+                    md5_hash: b.md5_hash.clone(),
+                    metadata: b.metadata.clone(),
+                    difficulty: b.difficulty.clone(),
+                    version: String::new(),
+                    mode: b.mode,
+                    length_ms: 0,
+                    bpm: 0.0,
+                    star_rating: None,
+                    star_ratings: Default::default(),
+                    ranked_status: None,
+                })
+                .collect(),
+            files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_sets() {
+        let stable = vec![
+            stable_set(1, &["a"]),
+            stable_set(2, &["b", "c"]),
+            stable_set(3, &["d"]),
+        ];
+        let lazer = vec![
+            lazer_set(1, &["a"]),
+            lazer_set(2, &["b"]),
+            lazer_set(4, &["e"]),
+        ];
+
+        let report = compare_sets(&stable, &lazer);
+        assert!(report.has_differences());
+        assert_eq!(report.stable_only.items[0].set_id, Some(3));
+        assert_eq!(report.lazer_only.items[0].set_id, Some(4));
+        assert_eq!(report.differing.total_items(), 1);
+        assert_eq!(report.differing.items[0].set_id, Some(2));
+
+        let in_sync = compare_sets(&stable[..1], &lazer[..1]);
+        assert!(!in_sync.has_differences());
+    }
+
+    #[test]
+    fn test_parse_args_list() {
+        let args: Vec<String> = ["list", "--format", "json", "--target", "lazer"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (cmd, _) = parse_args(&args).unwrap();
+        assert!(matches!(
+            cmd,
+            CliCommand::List {
+                target: ListTarget::Lazer,
+                format: ListFormat::Json
+            }
+        ));
+
+        let (cmd, _) = parse_args(&["export-list".to_string()]).unwrap();
+        assert!(matches!(
+            cmd,
+            CliCommand::List {
+                target: ListTarget::Stable,
+                format: ListFormat::Csv
+            }
+        ));
+
+        let args = vec![
+            "list".to_string(),
+            "--format".to_string(),
+            "xml".to_string(),
+        ];
+        assert!(parse_args(&args).is_err());
+    }
 }
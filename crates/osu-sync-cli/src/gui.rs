@@ -1,40 +1,143 @@
 //! GUI mode for osu-sync (requires `gui` feature)
 
 #[cfg(feature = "gui")]
-use iced::widget::{center, text};
+use iced::widget::{center, column, scrollable, text};
 #[cfg(feature = "gui")]
-use iced::{Element, Task};
+use iced::{Element, Length, Subscription, Task};
+#[cfg(feature = "gui")]
+use osu_sync_core::beatmap::BeatmapSet;
+#[cfg(feature = "gui")]
+use osu_sync_core::config::Config;
+#[cfg(feature = "gui")]
+use osu_sync_core::stable::StableScanner;
+
+/// Frames of the loading spinner shown while scanning
+#[cfg(feature = "gui")]
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 /// Run the GUI application
 #[cfg(feature = "gui")]
 pub fn run() -> iced::Result {
-    iced::application("osu-sync", OsuSyncApp::update, OsuSyncApp::view).run()
+    iced::application("osu-sync", OsuSyncApp::update, OsuSyncApp::view)
+        .subscription(OsuSyncApp::subscription)
+        .run_with(OsuSyncApp::new)
 }
 
 /// Main application state
 #[cfg(feature = "gui")]
-#[derive(Debug, Default)]
-struct OsuSyncApp {}
+#[derive(Debug)]
+enum OsuSyncApp {
+    /// Scanning the osu!stable Songs folder
+    Scanning { frame: usize },
+    /// Scan finished with the detected beatmap sets
+    Loaded { sets: Vec<BeatmapSet> },
+    /// Scan could not run or failed
+    Failed { error: String },
+}
 
 /// Application messages
 #[cfg(feature = "gui")]
 #[derive(Debug, Clone)]
 enum Message {
-    #[allow(dead_code)]
-    Placeholder,
+    /// Advance the loading spinner
+    Tick,
+    /// Stable scan finished
+    ScanComplete(Vec<BeatmapSet>),
+    /// Stable scan failed
+    ScanFailed(String),
 }
 
 #[cfg(feature = "gui")]
 impl OsuSyncApp {
-    fn update(&mut self, _message: Message) -> Task<Message> {
+    fn new() -> (Self, Task<Message>) {
+        let scan = Task::perform(
+            async {
+                tokio::task::spawn_blocking(scan_stable)
+                    .await
+                    .map_err(|e| format!("Scan task failed: {}", e))?
+            },
+            |result| match result {
+                Ok(sets) => Message::ScanComplete(sets),
+                Err(error) => Message::ScanFailed(error),
+            },
+        );
+        (Self::Scanning { frame: 0 }, scan)
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Tick => {
+                if let Self::Scanning { frame } = self {
+                    *frame = (*frame + 1) % SPINNER_FRAMES.len();
+                }
+            }
+            Message::ScanComplete(sets) => *self = Self::Loaded { sets },
+            Message::ScanFailed(error) => *self = Self::Failed { error },
+        }
         Task::none()
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        match self {
+            Self::Scanning { .. } => {
+                iced::time::every(std::time::Duration::from_millis(80)).map(|_| Message::Tick)
+            }
+            _ => Subscription::none(),
+        }
+    }
+
     fn view(&self) -> Element<'_, Message> {
-        center(text("osu-sync GUI - Coming Soon").size(24)).into()
+        match self {
+            Self::Scanning { frame } => center(
+                text(format!(
+                    "{} Scanning osu!stable beatmaps...",
+                    SPINNER_FRAMES[*frame]
+                ))
+                .size(20),
+            )
+            .into(),
+            Self::Failed { error } => {
+                center(text(format!("Scan failed: {}", error)).size(18)).into()
+            }
+            Self::Loaded { sets } => {
+                let rows = sets.iter().map(|set| {
+                    let (artist, title) = set
+                        .metadata()
+                        .map(|m| (m.artist.as_str(), m.title.as_str()))
+                        .unwrap_or(("Unknown", "Unknown"));
+                    text(format!(
+                        "{} - {} ({} difficulties)",
+                        artist,
+                        title,
+                        set.beatmaps.len()
+                    ))
+                    .into()
+                });
+
+                column![
+                    text(format!("{} beatmap sets found in osu!stable", sets.len())).size(22),
+                    scrollable(column(rows).spacing(4)).height(Length::Fill),
+                ]
+                .spacing(12)
+                .padding(16)
+                .into()
+            }
+        }
     }
 }
 
+/// Detect the osu!stable installation and scan its Songs folder
+#[cfg(feature = "gui")]
+fn scan_stable() -> Result<Vec<BeatmapSet>, String> {
+    let songs_path = Config::auto_detect()
+        .require_stable_songs_path()
+        .map_err(|e| e.to_string())?;
+    StableScanner::new(songs_path)
+        .skip_hashing()
+        .scan()
+        .map_err(|e| e.to_string())
+}
+
 /// Stub when GUI feature is not enabled
 #[cfg(not(feature = "gui"))]
 #[allow(dead_code)]
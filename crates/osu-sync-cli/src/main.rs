@@ -156,8 +156,8 @@ fn init_logging() {
 }
 
 fn run() -> anyhow::Result<()> {
-    // Load config and set theme
-    let config = osu_sync_core::config::Config::load();
+    // Load config with the active profile's paths and set theme
+    let config = osu_sync_core::config::Config::load().with_active_profile();
     theme::set_theme(config.theme);
 
     // Initialize terminal
@@ -165,7 +165,7 @@ fn run() -> anyhow::Result<()> {
 
     // Set up worker communication
     let (app_tx, app_rx) = mpsc::channel();
-    let worker = Worker::spawn(app_tx);
+    let worker = Worker::spawn(app_tx, config);
 
     // Create app with channels and cancellation flag
     let mut app = App::new().with_channels(worker.sender(), app_rx, worker.cancellation_flag());
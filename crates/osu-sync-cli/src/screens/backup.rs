@@ -4,7 +4,9 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Gauge, Paragraph};
 
 use crate::app::{PINK, SUBTLE, SUCCESS, TEXT};
-use osu_sync_core::backup::{BackupMode, BackupProgress, BackupTarget, CompressionLevel};
+use osu_sync_core::backup::{
+    BackupFormat, BackupMode, BackupProgress, BackupTarget, CompressionLevel,
+};
 
 /// Menu items for backup selection
 const BACKUP_TARGETS: [BackupTarget; 5] = [
@@ -22,9 +24,11 @@ pub struct BackupUIState {
     pub selected_target: usize,
     /// Current compression level
     pub compression: CompressionLevel,
+    /// Current archive format
+    pub format: BackupFormat,
     /// Current backup mode
     pub mode: BackupMode,
-    /// Which option is focused (0 = target, 1 = compression, 2 = mode)
+    /// Which option is focused (0 = target, 1 = compression, 2 = format, 3 = mode)
     pub focused_option: usize,
 }
 
@@ -33,6 +37,7 @@ impl Default for BackupUIState {
         Self {
             selected_target: 0,
             compression: CompressionLevel::Normal,
+            format: BackupFormat::Zip,
             mode: BackupMode::Full,
             focused_option: 0,
         }
@@ -43,12 +48,12 @@ impl Default for BackupUIState {
 impl BackupUIState {
     /// Move focus to next option
     pub fn focus_next(&mut self) {
-        self.focused_option = (self.focused_option + 1) % 3;
+        self.focused_option = (self.focused_option + 1) % 4;
     }
 
     /// Move focus to previous option
     pub fn focus_prev(&mut self) {
-        self.focused_option = (self.focused_option + 2) % 3;
+        self.focused_option = (self.focused_option + 3) % 4;
     }
 
     /// Handle up arrow
@@ -79,7 +84,8 @@ impl BackupUIState {
     pub fn cycle_option(&mut self) {
         match self.focused_option {
             1 => self.compression = self.compression.next(),
-            2 => self.mode = self.mode.toggle(),
+            2 => self.format = self.format.toggle(),
+            3 => self.mode = self.mode.toggle(),
             _ => {}
         }
     }
@@ -91,9 +97,16 @@ impl BackupUIState {
 }
 
 /// Render the backup screen (target selection) - legacy version
-pub fn render(frame: &mut Frame, area: Rect, selected: usize, status_message: &str) {
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    selected: usize,
+    format: BackupFormat,
+    status_message: &str,
+) {
     let state = BackupUIState {
         selected_target: selected,
+        format,
         ..Default::default()
     };
     render_with_state(frame, area, &state, status_message);
@@ -198,7 +211,7 @@ pub fn render_with_state(
     }
 
     // Options panel - right side
-    let options_area = centered_rect(35, 8, content_chunks[1]);
+    let options_area = centered_rect(35, 10, content_chunks[1]);
 
     let options_block = Block::default()
         .title(Span::styled(
@@ -217,7 +230,7 @@ pub fn render_with_state(
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(2), // Compression
-            Constraint::Length(1), // Spacer
+            Constraint::Length(2), // Format
             Constraint::Length(2), // Mode
         ])
         .split(options_inner);
@@ -241,8 +254,24 @@ pub fn render_with_state(
     ]);
     frame.render_widget(Paragraph::new(compression_line), option_chunks[0]);
 
+    // Archive format option
+    let format_focused = state.focused_option == 2;
+    let format_style = if format_focused {
+        Style::default().fg(PINK).bold()
+    } else {
+        Style::default().fg(TEXT)
+    };
+    let format_prefix = if format_focused { "> " } else { "  " };
+
+    let format_line = Line::from(vec![
+        Span::styled(format_prefix, format_style),
+        Span::styled("Format: ", Style::default().fg(SUBTLE)),
+        Span::styled(format!("[{}]", state.format.short_label()), format_style),
+    ]);
+    frame.render_widget(Paragraph::new(format_line), option_chunks[1]);
+
     // Mode option
-    let mode_focused = state.focused_option == 2;
+    let mode_focused = state.focused_option == 3;
     let mode_style = if mode_focused {
         Style::default().fg(PINK).bold()
     } else {
@@ -340,7 +369,7 @@ pub fn render_progress(
 /// Render the backup complete screen
 #[allow(dead_code)]
 pub fn render_complete(frame: &mut Frame, area: Rect, backup_path: &str, size_bytes: u64) {
-    render_complete_with_type(frame, area, backup_path, size_bytes, false);
+    render_complete_with_type(frame, area, backup_path, size_bytes, false, None);
 }
 
 /// Render the backup complete screen with backup type info
@@ -350,13 +379,14 @@ pub fn render_complete_with_type(
     backup_path: &str,
     size_bytes: u64,
     is_incremental: bool,
+    status_message: Option<&str>,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(5),  // Title + status
             Constraint::Length(12), // Results
-            Constraint::Min(0),     // Spacer
+            Constraint::Min(0),     // Hint
         ])
         .split(area);
 
@@ -409,6 +439,14 @@ pub fn render_complete_with_type(
         ]),
     ]);
     frame.render_widget(results, results_inner);
+
+    // Hint, replaced by the outcome of opening the backup's folder
+    let hint = Paragraph::new(Span::styled(
+        status_message.unwrap_or("Press o to show the backup in your file manager"),
+        Style::default().fg(SUBTLE).italic(),
+    ))
+    .alignment(Alignment::Center);
+    frame.render_widget(hint, chunks[2]);
 }
 
 /// Get icon for backup target
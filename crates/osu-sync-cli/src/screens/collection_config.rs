@@ -6,13 +6,15 @@ use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
 use crate::app::{PINK, SUBTLE, SUCCESS, TEXT, WARNING};
 use crate::widgets::get_spinner_frame;
 use osu_sync_core::collection::{
-    Collection, CollectionSyncDirection, CollectionSyncEngine, CollectionSyncStrategy,
+    Collection, CollectionReport, CollectionSyncDirection, CollectionSyncEngine,
+    CollectionSyncStrategy,
 };
 
 pub fn render(
     frame: &mut Frame,
     area: Rect,
     collections: &[Collection],
+    reports: &[CollectionReport],
     selected: usize,
     strategy: CollectionSyncStrategy,
     loading: bool,
@@ -105,14 +107,15 @@ pub fn render(
                     Style::default().fg(SUBTLE)
                 };
 
-                // Build spans for the list item
+                // Build spans for the list item, with installed counts when known
+                let count_text = match reports.get(i) {
+                    Some(report) => format!("  ({}/{} present)", report.present, report.total),
+                    None => format!("  ({} beatmaps)", preview_item.beatmap_count),
+                };
                 let mut spans = vec![
                     Span::styled(prefix, style),
                     Span::styled(&preview_item.name, style),
-                    Span::styled(
-                        format!("  ({} beatmaps)", preview_item.beatmap_count),
-                        count_style,
-                    ),
+                    Span::styled(count_text, count_style),
                 ];
 
                 // Add duplicate/merge indicators
@@ -224,6 +227,18 @@ pub fn render(
             ),
         ]));
 
+        // Beatmaps referenced by collections but not installed
+        let missing: usize = reports.iter().map(|r| r.missing.len()).sum();
+        if missing > 0 {
+            preview_lines.push(Line::from(vec![
+                Span::styled("Missing: ", Style::default().fg(SUBTLE)),
+                Span::styled(
+                    format!("{} beatmaps are not installed", missing),
+                    Style::default().fg(WARNING),
+                ),
+            ]));
+        }
+
         // Manual steps warning (bidirectional indicator)
         if let Some(ref message) = preview.manual_steps_message {
             preview_lines.push(Line::from(""));
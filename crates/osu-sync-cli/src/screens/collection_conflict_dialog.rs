@@ -0,0 +1,112 @@
+//! Collection conflict resolution modal dialog
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use crate::app::{PINK, SUBTLE, TEXT};
+use osu_sync_core::collection::CollectionConflict;
+
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    conflict: &CollectionConflict,
+    selected: usize,
+    apply_to_all: bool,
+) {
+    // Calculate modal size and position
+    let width = 50;
+    let height = 14;
+    let modal_area = centered_rect(width, height, area);
+
+    // Clear the background
+    frame.render_widget(Clear, modal_area);
+
+    // Modal block
+    let block = Block::default()
+        .title(Span::styled(
+            " Collection Conflict ",
+            Style::default().fg(PINK).bold(),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(PINK));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    // Layout inside modal
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4), // Collection info
+            Constraint::Length(1), // Separator
+            Constraint::Length(3), // Actions
+            Constraint::Length(1), // Separator
+            Constraint::Length(2), // Apply to all checkbox
+        ])
+        .split(inner);
+
+    // Collection info
+    let collection_info = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("Collection: ", Style::default().fg(SUBTLE)),
+            Span::styled(&conflict.name, Style::default().fg(TEXT)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Only in stable: ", Style::default().fg(SUBTLE)),
+            Span::styled(
+                format!("{} beatmaps", conflict.only_in_stable.len()),
+                Style::default().fg(TEXT),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Only in lazer:  ", Style::default().fg(SUBTLE)),
+            Span::styled(
+                format!("{} beatmaps", conflict.only_in_lazer.len()),
+                Style::default().fg(TEXT),
+            ),
+        ]),
+    ]);
+    frame.render_widget(collection_info, chunks[0]);
+
+    // Actions
+    let actions = [
+        "Merge both copies",
+        "Replace stable with lazer",
+        "Replace lazer with stable",
+    ];
+
+    let items: Vec<ListItem> = actions
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            let prefix = if i == selected { "> " } else { "  " };
+            let style = if i == selected {
+                Style::default().fg(PINK).bold()
+            } else {
+                Style::default().fg(TEXT)
+            };
+            ListItem::new(format!("{}{}", prefix, action)).style(style)
+        })
+        .collect();
+
+    let menu = List::new(items);
+    frame.render_widget(menu, chunks[2]);
+
+    // Apply to all checkbox
+    let checkbox = if apply_to_all { "[x]" } else { "[ ]" };
+    let checkbox_line = Paragraph::new(Line::from(vec![
+        Span::styled(checkbox, Style::default().fg(PINK)),
+        Span::styled(
+            " Apply to all remaining conflicts",
+            Style::default().fg(TEXT),
+        ),
+    ]));
+    frame.render_widget(checkbox_line, chunks[4]);
+}
+
+fn centered_rect(width: u16, height: u16, parent: Rect) -> Rect {
+    let x = parent.x + (parent.width.saturating_sub(width)) / 2;
+    let y = parent.y + (parent.height.saturating_sub(height)) / 2;
+    Rect::new(x, y, width.min(parent.width), height.min(parent.height))
+}
@@ -12,13 +12,15 @@ pub fn render(frame: &mut Frame, area: Rect, result: &CollectionSyncResult) {
         .constraints([
             Constraint::Length(5),  // Title + status
             Constraint::Length(10), // Results
-            Constraint::Min(0),     // Missing beatmaps (if any)
+            Constraint::Min(0),     // Missing or added beatmaps (if any)
             Constraint::Length(2),  // Instructions
         ])
         .split(area);
 
     // Title and status
-    let (status_icon, status_text, status_color) = if result.success {
+    let (status_icon, status_text, status_color) = if result.unchanged {
+        ("OK", "Already up to date", SUCCESS)
+    } else if result.success {
         ("OK", "Sync Complete", SUCCESS)
     } else {
         ("!", "Sync Failed", ERROR)
@@ -83,6 +85,16 @@ pub fn render(frame: &mut Frame, area: Rect, result: &CollectionSyncResult) {
         ]));
     }
 
+    if !result.conflicts.is_empty() {
+        result_lines.push(Line::from(vec![
+            Span::styled("  Conflicts:           ", Style::default().fg(SUBTLE)),
+            Span::styled(
+                format!("{}", result.conflicts.len()),
+                Style::default().fg(WARNING),
+            ),
+        ]));
+    }
+
     if let Some(error) = &result.error_message {
         result_lines.push(Line::from(""));
         result_lines.push(Line::from(Span::styled(
@@ -123,6 +135,27 @@ pub fn render(frame: &mut Frame, area: Rect, result: &CollectionSyncResult) {
         );
 
         frame.render_widget(missing_list, chunks[2]);
+    } else if !result.changes.is_empty() {
+        // Beatmaps a merge added, e.g. "Favourites: +12"
+        let change_items: Vec<ListItem> = result
+            .changes
+            .iter()
+            .map(|change| {
+                ListItem::new(Span::styled(
+                    format!("  {} ", truncate(&change.to_string(), 45)),
+                    Style::default().fg(SUCCESS),
+                ))
+            })
+            .collect();
+
+        let changes_list = List::new(change_items).block(
+            Block::default()
+                .title(" Added Beatmaps ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(SUBTLE)),
+        );
+
+        frame.render_widget(changes_list, chunks[2]);
     }
 
     // Instructions
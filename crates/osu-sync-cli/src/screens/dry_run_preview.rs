@@ -8,7 +8,10 @@ use ratatui::widgets::{
 };
 
 use crate::app::{PINK, SUBTLE, SUCCESS, TEXT, WARNING};
-use osu_sync_core::sync::{DryRunAction, DryRunItem, DryRunResult, SyncDirection};
+use osu_sync_core::sync::{format_bytes, DryRunAction, DryRunItem, DryRunResult, SyncDirection};
+
+/// Number of imports marked as the heaviest in the list
+const HEAVIEST_IMPORTS: usize = 5;
 
 /// Filter dry run items by search text, returns indices of matching items
 pub fn filter_items(items: &[DryRunItem], filter_text: &str) -> Vec<usize> {
@@ -94,11 +97,8 @@ pub fn render(
     frame.render_widget(summary_block, summary_area);
 
     // Calculate selection stats
-    let total_importable = result
-        .items
-        .iter()
-        .filter(|i| i.action == DryRunAction::Import)
-        .count();
+    let stats = result.summary();
+    let total_importable = stats.import.count;
     let selected_count = checked_items.len();
 
     let summary = Paragraph::new(vec![Line::from(vec![
@@ -110,10 +110,10 @@ pub fn render(
                 .bold(),
         ),
         Span::styled("    Skip: ", Style::default().fg(SUBTLE)),
-        Span::styled(format!("{}", result.total_skip), Style::default().fg(TEXT)),
+        Span::styled(format!("{}", stats.skip.count), Style::default().fg(TEXT)),
         Span::styled("    Duplicates: ", Style::default().fg(SUBTLE)),
         Span::styled(
-            format!("{}", result.total_duplicate),
+            format!("{}", stats.duplicate.count),
             Style::default().fg(WARNING),
         ),
     ])])
@@ -129,13 +129,20 @@ pub fn render(
     let info_inner = info_block.inner(info_area);
     frame.render_widget(info_block, info_area);
 
-    let info = Paragraph::new(Line::from(vec![
+    let mut info_spans = vec![
         Span::styled("Size: ", Style::default().fg(SUBTLE)),
         Span::styled(result.size_display(), Style::default().fg(TEXT)),
         Span::styled("    Est. Time: ", Style::default().fg(SUBTLE)),
         Span::styled(result.estimated_time_display(), Style::default().fg(TEXT)),
-    ]))
-    .alignment(Alignment::Center);
+    ];
+    if stats.redundant_difficulties > 0 {
+        info_spans.push(Span::styled("    Dup diffs: ", Style::default().fg(SUBTLE)));
+        info_spans.push(Span::styled(
+            format!("{}", stats.redundant_difficulties),
+            Style::default().fg(WARNING),
+        ));
+    }
+    let info = Paragraph::new(Line::from(info_spans)).alignment(Alignment::Center);
     frame.render_widget(info, info_inner);
 
     // Filter input bar (if visible)
@@ -190,6 +197,8 @@ pub fn render(
     // Calculate visible items
     let visible_height = list_inner.height as usize;
 
+    let heaviest = result.largest_items(HEAVIEST_IMPORTS);
+
     // Create list items from filtered indices
     // Note: selected_item is the index in visible_indices (0 to total_visible-1)
     // We render items from scroll_offset to scroll_offset + visible_height
@@ -237,7 +246,7 @@ pub fn render(
 
             let checkbox_color = if is_checked { SUCCESS } else { SUBTLE };
 
-            ListItem::new(Line::from(vec![
+            let mut spans = vec![
                 Span::styled(prefix, style),
                 Span::styled(
                     format!("{} ", checkbox),
@@ -246,7 +255,21 @@ pub fn render(
                 Span::styled(format!("[{}] ", icon), Style::default().fg(action_color)),
                 Span::styled(format!("{} ", set_id_str), Style::default().fg(SUBTLE)),
                 Span::styled(format!("{} - {}", item.artist, item.title), style),
-            ]))
+            ];
+            if item.redundant_difficulties > 0 {
+                spans.push(Span::styled(
+                    format!(" ({} dup diff)", item.redundant_difficulties),
+                    Style::default().fg(WARNING),
+                ));
+            }
+            if item.size_bytes > 0 && heaviest.iter().any(|h| std::ptr::eq(*h, item)) {
+                spans.push(Span::styled(
+                    format!(" [{}]", format_bytes(item.size_bytes)),
+                    Style::default().fg(PINK),
+                ));
+            }
+
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -324,6 +347,7 @@ mod tests {
             action: DryRunAction::Import,
             size_bytes: 1000,
             difficulty_count: 1,
+            redundant_difficulties: 0,
         }
     }
 
@@ -71,14 +71,25 @@ pub fn render(
 
     // Match info
     let confidence_pct = (info.confidence * 100.0) as u8;
-    let match_info = Paragraph::new(Line::from(vec![
+    let mut match_lines = vec![Line::from(vec![
         Span::styled("Match: ", Style::default().fg(SUBTLE)),
         Span::styled(format!("{:?}", info.match_type), Style::default().fg(PINK)),
         Span::styled(
             format!(" ({}% confidence)", confidence_pct),
             Style::default().fg(SUBTLE),
         ),
-    ]));
+    ])];
+    if let Some(overlap) = &info.overlap {
+        match_lines.push(Line::from(Span::styled(
+            format!(
+                "{}% of files shared, {} differ",
+                (overlap.ratio * 100.0) as u8,
+                overlap.only_in_source.len() + overlap.only_in_existing.len()
+            ),
+            Style::default().fg(SUBTLE),
+        )));
+    }
+    let match_info = Paragraph::new(match_lines);
     frame.render_widget(match_info, chunks[1]);
 
     // Actions
@@ -86,6 +97,7 @@ pub fn render(
         "Skip this beatmap",
         "Replace existing",
         "Keep both versions",
+        "Merge missing difficulties",
     ];
 
     let items: Vec<ListItem> = actions
@@ -175,7 +175,12 @@ pub fn render_progress(
 }
 
 /// Render media extraction complete screen
-pub fn render_complete(frame: &mut Frame, area: Rect, result: &ExtractionResult) {
+pub fn render_complete(
+    frame: &mut Frame,
+    area: Rect,
+    result: &ExtractionResult,
+    status_message: Option<&str>,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -220,6 +225,14 @@ pub fn render_complete(frame: &mut Frame, area: Rect, result: &ExtractionResult)
         )),
     ];
 
+    // Show files a previous run already extracted
+    if result.manifest_skipped > 0 {
+        results_text.push(Line::from(Span::styled(
+            format!("Already extracted: {}", result.manifest_skipped),
+            Style::default().fg(SUBTLE),
+        )));
+    }
+
     // Show metadata files created if any
     if result.metadata_files_created > 0 {
         results_text.push(Line::from(Span::styled(
@@ -261,9 +274,9 @@ pub fn render_complete(frame: &mut Frame, area: Rect, result: &ExtractionResult)
     let results_area = centered_rect(50, 12, chunks[1]);
     frame.render_widget(results, results_area);
 
-    // Hint
+    // Hint, replaced by the outcome of opening the output folder
     let hint = Paragraph::new(Span::styled(
-        "Press Enter to return to menu",
+        status_message.unwrap_or("Press Enter to return to menu, o to open the folder"),
         Style::default().fg(SUBTLE).italic(),
     ))
     .alignment(Alignment::Center);
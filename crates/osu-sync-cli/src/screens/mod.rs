@@ -3,6 +3,7 @@
 mod activity_log;
 mod backup;
 mod collection_config;
+mod collection_conflict_dialog;
 mod collection_summary;
 mod collection_sync;
 mod config;
@@ -143,6 +144,7 @@ pub fn render(frame: &mut Frame, app: &App) {
         }
         AppState::CollectionConfig {
             collections,
+            reports,
             selected,
             strategy,
             loading,
@@ -152,6 +154,7 @@ pub fn render(frame: &mut Frame, app: &App) {
                 frame,
                 chunks[1],
                 collections,
+                reports,
                 *selected,
                 *strategy,
                 *loading,
@@ -165,6 +168,16 @@ pub fn render(frame: &mut Frame, app: &App) {
         } => {
             collection_sync::render(frame, chunks[1], *progress, current_collection, logs);
         }
+        AppState::CollectionConflictDialog {
+            conflict,
+            selected,
+            apply_to_all,
+        } => {
+            // Render dimmed collection sync screen behind
+            collection_sync::render(frame, chunks[1], 0.0, &conflict.name, &[]);
+            // Render modal on top
+            collection_conflict_dialog::render(frame, area, conflict, *selected, *apply_to_all);
+        }
         AppState::CollectionSummary { result } => {
             collection_summary::render(frame, chunks[1], result);
         }
@@ -191,9 +204,10 @@ pub fn render(frame: &mut Frame, app: &App) {
         }
         AppState::BackupConfig {
             selected,
+            format,
             status_message,
         } => {
-            backup::render(frame, chunks[1], *selected, status_message);
+            backup::render(frame, chunks[1], *selected, *format, status_message);
         }
         AppState::BackupProgress { target, progress } => {
             backup::render_progress(frame, chunks[1], progress, *target);
@@ -202,6 +216,7 @@ pub fn render(frame: &mut Frame, app: &App) {
             backup_path,
             size_bytes,
             is_incremental,
+            status_message,
         } => {
             backup::render_complete_with_type(
                 frame,
@@ -209,6 +224,7 @@ pub fn render(frame: &mut Frame, app: &App) {
                 &backup_path.display().to_string(),
                 *size_bytes,
                 *is_incremental,
+                status_message.as_deref(),
             );
         }
         AppState::RestoreConfig {
@@ -284,8 +300,12 @@ pub fn render(frame: &mut Frame, app: &App) {
         } => {
             media::render_progress(frame, chunks[1], progress, current_set);
         }
-        AppState::MediaComplete { result } => {
-            media::render_complete(frame, chunks[1], result);
+        AppState::MediaComplete {
+            result,
+            status_message,
+            ..
+        } => {
+            media::render_complete(frame, chunks[1], result, status_message.as_deref());
         }
         AppState::ReplayConfig {
             selected,
@@ -320,8 +340,13 @@ pub fn render(frame: &mut Frame, app: &App) {
         } => {
             replay::render_progress(frame, chunks[1], progress, current_replay);
         }
-        AppState::ReplayComplete { result, stats } => {
-            replay::render_complete(frame, chunks[1], result, stats);
+        AppState::ReplayComplete {
+            result,
+            stats,
+            status_message,
+            ..
+        } => {
+            replay::render_complete(frame, chunks[1], result, stats, status_message.as_deref());
         }
         AppState::UnifiedConfig { screen } => {
             unified_config::render(frame, chunks[1], screen);
@@ -433,6 +458,7 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
         }
         AppState::CollectionConfig {
             collections,
+            reports,
             selected,
             strategy,
             loading,
@@ -442,6 +468,7 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
                 frame,
                 area,
                 collections,
+                reports,
                 *selected,
                 *strategy,
                 *loading,
@@ -455,6 +482,10 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
         } => {
             collection_sync::render(frame, area, *progress, current_collection, logs);
         }
+        AppState::CollectionConflictDialog { conflict, .. } => {
+            // Just render the collection sync screen behind conflicts
+            collection_sync::render(frame, area, 0.0, &conflict.name, &[]);
+        }
         AppState::CollectionSummary { result } => {
             collection_summary::render(frame, area, result);
         }
@@ -481,9 +512,10 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
         }
         AppState::BackupConfig {
             selected,
+            format,
             status_message,
         } => {
-            backup::render(frame, area, *selected, status_message);
+            backup::render(frame, area, *selected, *format, status_message);
         }
         AppState::BackupProgress { target, progress } => {
             backup::render_progress(frame, area, progress, *target);
@@ -492,6 +524,7 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
             backup_path,
             size_bytes,
             is_incremental,
+            status_message,
         } => {
             backup::render_complete_with_type(
                 frame,
@@ -499,6 +532,7 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
                 &backup_path.display().to_string(),
                 *size_bytes,
                 *is_incremental,
+                status_message.as_deref(),
             );
         }
         AppState::RestoreConfig {
@@ -567,8 +601,12 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
         } => {
             media::render_progress(frame, area, progress, current_set);
         }
-        AppState::MediaComplete { result } => {
-            media::render_complete(frame, area, result);
+        AppState::MediaComplete {
+            result,
+            status_message,
+            ..
+        } => {
+            media::render_complete(frame, area, result, status_message.as_deref());
         }
         AppState::ReplayConfig {
             selected,
@@ -603,8 +641,13 @@ fn render_state(frame: &mut Frame, area: Rect, state: &AppState, app: &App) {
         } => {
             replay::render_progress(frame, area, progress, current_replay);
         }
-        AppState::ReplayComplete { result, stats } => {
-            replay::render_complete(frame, area, result, stats);
+        AppState::ReplayComplete {
+            result,
+            stats,
+            status_message,
+            ..
+        } => {
+            replay::render_complete(frame, area, result, stats, status_message.as_deref());
         }
         AppState::UnifiedConfig { screen } => {
             unified_config::render(frame, area, screen);
@@ -685,6 +728,12 @@ fn get_hints(state: &AppState) -> Vec<(&'static str, &'static str)> {
             ("Esc", "Back"),
         ],
         AppState::CollectionSync { .. } => vec![("Esc", "Cancel")],
+        AppState::CollectionConflictDialog { .. } => vec![
+            ("Enter", "Confirm"),
+            ("Space", "Toggle Apply All"),
+            ("j/k", "Navigate"),
+            ("Esc", "Cancel"),
+        ],
         AppState::CollectionSummary { .. } => vec![("Enter", "Back to Menu")],
         AppState::DryRunPreview {
             result,
@@ -722,11 +771,13 @@ fn get_hints(state: &AppState) -> Vec<(&'static str, &'static str)> {
         }
         AppState::BackupConfig { .. } => vec![
             ("Enter", "Start Backup"),
+            ("p", "Preview Size"),
+            ("f", "Format"),
             ("j/k", "Navigate"),
             ("Esc", "Back"),
         ],
         AppState::BackupProgress { .. } => vec![("Esc", "Cancel")],
-        AppState::BackupComplete { .. } => vec![("Enter", "Back to Menu")],
+        AppState::BackupComplete { .. } => vec![("Enter", "Back to Menu"), ("o", "Open Folder")],
         AppState::RestoreConfig { loading: true, .. } => vec![("Esc", "Cancel")],
         AppState::RestoreConfig { loading: false, .. } => {
             vec![("Enter", "Select"), ("j/k", "Navigate"), ("Esc", "Back")]
@@ -744,7 +795,7 @@ fn get_hints(state: &AppState) -> Vec<(&'static str, &'static str)> {
             ("Esc", "Back"),
         ],
         AppState::MediaProgress { .. } => vec![("Esc", "Cancel")],
-        AppState::MediaComplete { .. } => vec![("Enter", "Back to Menu")],
+        AppState::MediaComplete { .. } => vec![("Enter", "Back to Menu"), ("o", "Open Folder")],
         AppState::ReplayConfig { loading: true, .. } => vec![("Esc", "Cancel")],
         AppState::ReplayConfig {
             loading: false,
@@ -761,7 +812,7 @@ fn get_hints(state: &AppState) -> Vec<(&'static str, &'static str)> {
             ("Esc", "Back"),
         ],
         AppState::ReplayProgress { .. } => vec![("Esc", "Cancel")],
-        AppState::ReplayComplete { .. } => vec![("Enter", "Back to Menu")],
+        AppState::ReplayComplete { .. } => vec![("Enter", "Back to Menu"), ("o", "Open Folder")],
         AppState::UnifiedConfig { .. } => vec![
             ("Enter", "Select/Toggle"),
             ("Tab", "Next Section"),
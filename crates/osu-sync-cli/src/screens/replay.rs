@@ -7,7 +7,7 @@ use osu_sync_core::replay::{
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph};
 
-use crate::app::{PINK, SUBTLE, TEXT};
+use crate::app::{PINK, SUBTLE, TEXT, WARNING};
 
 /// Render replay export configuration screen
 #[allow(clippy::too_many_arguments)]
@@ -293,6 +293,7 @@ pub fn render_complete(
     area: Rect,
     result: &ReplayExportResult,
     stats: &Option<ReplayExportStats>,
+    status_message: Option<&str>,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -332,6 +333,23 @@ pub fn render_complete(
         )));
     }
 
+    if result.replays_unmatched > 0 {
+        results_text.push(Line::from(Span::styled(
+            format!(
+                "Replays without a known beatmap: {}",
+                result.replays_unmatched
+            ),
+            Style::default().fg(SUBTLE),
+        )));
+    }
+
+    if !result.corrupt.is_empty() {
+        results_text.push(Line::from(Span::styled(
+            format!("Corrupt replays skipped: {}", result.corrupt.len()),
+            Style::default().fg(WARNING),
+        )));
+    }
+
     results_text.push(Line::from(Span::styled(
         format!(
             "Total data written: {:.1} MB",
@@ -396,9 +414,9 @@ pub fn render_complete(
     let results_area = centered_rect(50, results_height, chunks[1]);
     frame.render_widget(results, results_area);
 
-    // Hint
+    // Hint, replaced by the outcome of opening the output folder
     let hint = Paragraph::new(Span::styled(
-        "Press Enter to return to menu",
+        status_message.unwrap_or("Press Enter to return to menu, o to open the folder"),
         Style::default().fg(SUBTLE).italic(),
     ))
     .alignment(Alignment::Center);
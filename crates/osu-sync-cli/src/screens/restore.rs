@@ -458,6 +458,11 @@ pub fn render_preview(
     } else {
         TEXT
     };
+    let space_color = if preview.has_enough_space {
+        TEXT
+    } else {
+        ERROR
+    };
 
     let stats = Paragraph::new(vec![
         Line::from(vec![
@@ -492,6 +497,17 @@ pub fn render_preview(
                 Style::default().fg(skip_color),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("  Free space:       ", Style::default().fg(SUBTLE)),
+            Span::styled(
+                format!(
+                    "{} ({} needed)",
+                    preview.free_space_display(),
+                    preview.required_space_display()
+                ),
+                Style::default().fg(space_color),
+            ),
+        ]),
     ]);
     frame.render_widget(stats, chunks[1]);
 
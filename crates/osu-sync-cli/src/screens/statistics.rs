@@ -138,7 +138,7 @@ fn render_overview(frame: &mut Frame, area: Rect, stats: &ComparisonStats) {
     frame.render_widget(table, top_chunks[0]);
 
     // Top Right: Summary
-    let summary = vec![
+    let mut summary = vec![
         Line::from(""),
         Line::from(vec![
             Span::styled("Common: ", Style::default().fg(SUBTLE)),
@@ -173,6 +173,25 @@ fn render_overview(frame: &mut Frame, area: Rect, stats: &ComparisonStats) {
         ]),
     ];
 
+    if let Some(integrity) = &stats.integrity {
+        summary.push(Line::from(""));
+        summary.push(Line::from(vec![
+            Span::styled("Edited maps: ", Style::default().fg(SUBTLE)),
+            Span::styled(
+                integrity.edited.len().to_string(),
+                Style::default().fg(if integrity.edited.is_empty() {
+                    SUCCESS
+                } else {
+                    WARNING
+                }),
+            ),
+            Span::styled(
+                format!(" ({} not in osu!.db)", integrity.unknown.len()),
+                Style::default().fg(SUBTLE),
+            ),
+        ]));
+    }
+
     let summary_widget = Paragraph::new(summary).block(
         Block::default()
             .title(" Summary ")
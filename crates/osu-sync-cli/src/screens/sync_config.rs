@@ -63,6 +63,7 @@ pub fn render(
             "Bidirectional",
             format!("{} beatmaps to sync", filtered_stable + filtered_lazer),
         ),
+        ("Favourites only", "Stable -> Lazer".to_string()),
     ];
 
     let items: Vec<ListItem> = options
@@ -108,6 +109,7 @@ pub fn render(
             "Will sync {} beatmap sets in both directions",
             filtered_stable + filtered_lazer
         ),
+        3 => "Will import the sets in osu!stable's Favourites collection to osu!lazer".to_string(),
         _ => String::new(),
     };
 
@@ -101,12 +101,18 @@ pub fn render(
 
         frame.render_widget(gauge, gauge_area[1]);
 
-        // Current item
-        let current = Paragraph::new(Span::styled(
-            truncate(&prog.current_name, 60),
-            Style::default().fg(SUBTLE),
-        ))
-        .alignment(Alignment::Center);
+        // Current item, with the large file being written if any
+        let current_text = match &prog.current_file {
+            Some(file) => format!(
+                "{} - importing {} ({}%)",
+                truncate(&prog.current_name, 30),
+                file,
+                (prog.current_file_progress * 100.0) as u8
+            ),
+            None => truncate(&prog.current_name, 60),
+        };
+        let current = Paragraph::new(Span::styled(current_text, Style::default().fg(SUBTLE)))
+            .alignment(Alignment::Center);
         frame.render_widget(current, chunks[3]);
     } else {
         let spinner = get_spinner_frame();
@@ -37,8 +37,8 @@ struct TestContext {
 
 /// Run automated TUI test from script file or stdin
 pub fn run_test(script_path: &str) -> anyhow::Result<()> {
-    // Load config and set theme
-    let config = osu_sync_core::config::Config::load();
+    // Load config with the active profile's paths and set theme
+    let config = osu_sync_core::config::Config::load().with_active_profile();
     theme::set_theme(config.theme);
 
     // Read script
@@ -63,7 +63,7 @@ pub fn run_test(script_path: &str) -> anyhow::Result<()> {
 
     // Set up worker communication
     let (app_tx, app_rx) = mpsc::channel();
-    let worker = Worker::spawn(app_tx);
+    let worker = Worker::spawn(app_tx, config);
 
     // Create app
     let mut app = App::new().with_channels(worker.sender(), app_rx, worker.cancellation_flag());
@@ -402,6 +402,7 @@ fn get_state_name(state: &AppState) -> &'static str {
         AppState::Statistics { .. } => "Statistics",
         AppState::CollectionConfig { .. } => "CollectionConfig",
         AppState::CollectionSync { .. } => "CollectionSync",
+        AppState::CollectionConflictDialog { .. } => "CollectionConflictDialog",
         AppState::CollectionSummary { .. } => "CollectionSummary",
         AppState::DryRunPreview { .. } => "DryRunPreview",
         AppState::BackupConfig { .. } => "BackupConfig",
@@ -156,6 +156,7 @@ impl TuiTestHarness {
             AppState::Statistics { .. } => "Statistics",
             AppState::CollectionConfig { .. } => "CollectionConfig",
             AppState::CollectionSync { .. } => "CollectionSync",
+            AppState::CollectionConflictDialog { .. } => "CollectionConflictDialog",
             AppState::CollectionSummary { .. } => "CollectionSummary",
             AppState::DryRunPreview { .. } => "DryRunPreview",
             AppState::BackupConfig { .. } => "BackupConfig",
@@ -487,4 +488,41 @@ mod tests {
         // Verify scenario completed - selecting exit should quit
         assert!(harness.should_quit());
     }
+
+    #[test]
+    fn test_dry_run_reuses_cached_result() {
+        use crate::app::{dry_run_key, CachedDryRun, FilterField};
+        use osu_sync_core::filter::FilterCriteria;
+        use osu_sync_core::sync::{DryRunResult, SyncDirection};
+
+        let mut harness = TuiTestHarness::default_size().unwrap();
+        let sync_config = |filter: FilterCriteria| AppState::SyncConfig {
+            selected: 0,
+            stable_count: 0,
+            lazer_count: 0,
+            filter,
+            filter_panel_open: false,
+            filter_field: FilterField::default(),
+        };
+        let filter = FilterCriteria::default();
+        harness.app_mut().cached_dry_run = Some(CachedDryRun {
+            key: dry_run_key(SyncDirection::StableToLazer, &filter),
+            result: DryRunResult::new(),
+        });
+        harness.app_mut().state = sync_config(filter.clone());
+
+        harness.char('d');
+        assert_eq!(harness.state_name(), "DryRunPreview");
+
+        // A different filter misses the cache and starts a new analysis
+        let other = FilterCriteria::default().with_min_stars(5.0);
+        assert_ne!(
+            dry_run_key(SyncDirection::StableToLazer, &other),
+            dry_run_key(SyncDirection::StableToLazer, &filter)
+        );
+        harness.app_mut().state = sync_config(other);
+        harness.char('d');
+        assert_eq!(harness.state_name(), "Syncing");
+        assert!(harness.app().cached_dry_run.is_none());
+    }
 }
@@ -186,6 +186,7 @@ impl TuiSnapshot {
             AppState::Statistics { .. } => "Statistics",
             AppState::CollectionConfig { .. } => "CollectionConfig",
             AppState::CollectionSync { .. } => "CollectionSync",
+            AppState::CollectionConflictDialog { .. } => "CollectionConflictDialog",
             AppState::CollectionSummary { .. } => "CollectionSummary",
             AppState::DryRunPreview { .. } => "DryRunPreview",
             AppState::BackupConfig { .. } => "BackupConfig",
@@ -351,6 +352,50 @@ impl TuiSnapshot {
         )
     }
 
+    /// Output as plain linearized text without box-drawing characters
+    ///
+    /// Border rows become `---` separators (keeping any title, e.g.
+    /// `--- Sync ---`), remaining border glyphs become spaces, trailing
+    /// whitespace is trimmed and runs of blank lines are collapsed.
+    /// Suitable for screen readers, logs, and diffing in tests.
+    pub fn as_plain_text(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for row in self.buffer.lines() {
+            let horizontal = row.chars().filter(|&c| is_horizontal_glyph(c)).count();
+            let stripped: String = row
+                .chars()
+                .map(|c| if is_box_glyph(c) { ' ' } else { c })
+                .collect();
+
+            let line = if horizontal >= 3 {
+                let title = stripped.split_whitespace().collect::<Vec<_>>().join(" ");
+                if title.is_empty() {
+                    "---".to_string()
+                } else {
+                    format!("--- {} ---", title)
+                }
+            } else {
+                stripped.trim_end().to_string()
+            };
+
+            let previous = lines.last().map(String::as_str);
+            let repeated_blank = line.is_empty() && matches!(previous, None | Some(""));
+            let repeated_separator = line == "---" && matches!(previous, Some("---"));
+            if !repeated_blank && !repeated_separator {
+                lines.push(line);
+            }
+        }
+
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+
+        let mut output = lines.join("\n");
+        output.push('\n');
+        output
+    }
+
     /// Output as JSON string
     pub fn as_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
@@ -362,6 +407,17 @@ impl TuiSnapshot {
     }
 }
 
+/// Returns `true` for characters in the Unicode box-drawing block.
+fn is_box_glyph(c: char) -> bool {
+    ('\u{2500}'..='\u{257F}').contains(&c)
+}
+
+/// Returns `true` for box-drawing characters that are not purely vertical.
+fn is_horizontal_glyph(c: char) -> bool {
+    const VERTICAL: [char; 11] = ['│', '┃', '║', '╎', '╏', '┆', '┇', '┊', '┋', '╽', '╿'];
+    is_box_glyph(c) && !VERTICAL.contains(&c)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -391,6 +447,29 @@ mod tests {
         assert!(text.contains("Size: 120x30"));
     }
 
+    #[test]
+    fn test_snapshot_plain_text() {
+        let snapshot = TuiSnapshot {
+            state: "Test".into(),
+            buffer:
+                "┌ Title ──────┐\n│ item   │ x │\n│        │   │\n│        │   │\n└─────────────┘\n"
+                    .into(),
+            width: 15,
+            height: 5,
+            state_data: None,
+            timestamp: String::new(),
+        };
+        assert_eq!(
+            snapshot.as_plain_text(),
+            "--- Title ---\n  item     x\n\n---\n"
+        );
+
+        let text = TuiSnapshot::capture(120, 30).unwrap().as_plain_text();
+        assert!(text.contains("Sync Beatmaps"));
+        assert!(!text.chars().any(is_box_glyph));
+        assert!(text.lines().all(|l| l == l.trim_end()));
+    }
+
     #[test]
     fn test_state_data_mainmenu() {
         let app = App::new();
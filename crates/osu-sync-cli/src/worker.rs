@@ -1,23 +1,28 @@
 //! Background worker thread for sync operations
 
 use std::collections::HashSet;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
+use std::time::Instant;
 
+use osu_sync_core::audit::{record_operation, AuditOperation, AuditRecord};
 use osu_sync_core::backup::{
-    BackupManager, BackupMode, BackupOptions, BackupTarget, CompressionLevel,
+    BackupFormat, BackupManager, BackupMode, BackupOptions, BackupTarget, CompressionLevel,
 };
+use osu_sync_core::cancel::CancellationToken;
 use osu_sync_core::collection::{
-    CollectionSyncEngine, CollectionSyncStrategy, StableCollectionReader,
+    CollectionSyncDirection, CollectionSyncEngine, CollectionSyncState, CollectionSyncStrategy,
+    StableCollectionReader,
 };
 use osu_sync_core::config::Config;
+use osu_sync_core::filter::FilterCriteria;
+use osu_sync_core::hash_cache::HashCache;
 use osu_sync_core::lazer::LazerDatabase;
-use osu_sync_core::stable::StableScanner;
-use osu_sync_core::stats::StatsAnalyzer;
-use osu_sync_core::sync::{SyncDirection, SyncEngineBuilder, SyncProgress};
+use osu_sync_core::stable::{ScanProgress, StableScanner};
+use osu_sync_core::stats::{IntegrityChecker, ModePercentage, StatsAnalyzer};
+use osu_sync_core::sync::{SyncDirection, SyncEngineBuilder, SyncError, SyncEvent, SyncProgress};
 use osu_sync_core::unified::{SharedResourceType, UnifiedStorageMode};
 use osu_sync_core::Error as CoreError;
 
@@ -27,8 +32,8 @@ use crate::app::{AppMessage, ScanResult, WorkerMessage};
 pub struct Worker {
     handle: Option<JoinHandle<()>>,
     tx: Sender<WorkerMessage>,
-    /// Shared cancellation flag
-    cancelled: Arc<AtomicBool>,
+    /// Shared cancellation token
+    cancelled: CancellationToken,
 }
 
 fn config_snapshot(config: &Arc<RwLock<Config>>) -> Config {
@@ -58,15 +63,15 @@ fn format_core_error(error: &CoreError) -> String {
 }
 
 impl Worker {
-    /// Spawn a new worker thread
-    pub fn spawn(app_tx: Sender<AppMessage>) -> Self {
+    /// Spawn a new worker thread using the config loaded at startup
+    pub fn spawn(app_tx: Sender<AppMessage>, config: Config) -> Self {
         let (worker_tx, worker_rx) = mpsc::channel::<WorkerMessage>();
         let (_resolution_tx, resolution_rx) = mpsc::channel();
-        let cancelled = Arc::new(AtomicBool::new(false));
-        let cancelled_clone = Arc::clone(&cancelled);
+        let cancelled = CancellationToken::new();
+        let cancelled_clone = cancelled.clone();
 
         let handle = thread::spawn(move || {
-            run_worker(worker_rx, app_tx, resolution_rx, cancelled_clone);
+            run_worker(worker_rx, app_tx, resolution_rx, cancelled_clone, config);
         });
 
         Self {
@@ -82,8 +87,8 @@ impl Worker {
     }
 
     /// Get a clone of the cancellation flag for sharing with other components
-    pub fn cancellation_flag(&self) -> Arc<AtomicBool> {
-        Arc::clone(&self.cancelled)
+    pub fn cancellation_flag(&self) -> CancellationToken {
+        self.cancelled.clone()
     }
 
     /// Shutdown the worker and wait for it to finish
@@ -99,16 +104,16 @@ fn run_worker(
     rx: Receiver<WorkerMessage>,
     app_tx: Sender<AppMessage>,
     _resolution_rx: Receiver<osu_sync_core::dedup::DuplicateResolution>,
-    cancelled: Arc<AtomicBool>,
+    cancelled: CancellationToken,
+    config: Config,
 ) {
-    // Load config once at session start to avoid repeated disk reads
-    // This is cached for the lifetime of the worker thread
-    let config = Arc::new(RwLock::new(Config::load()));
+    // Kept for the lifetime of the worker thread to avoid repeated disk reads
+    let config = Arc::new(RwLock::new(config));
 
     loop {
         match rx.recv() {
             Ok(WorkerMessage::StartScan { stable, lazer }) => {
-                cancelled.store(false, Ordering::SeqCst);
+                cancelled.reset();
                 handle_scan(&app_tx, &config, stable, lazer);
             }
             Ok(WorkerMessage::StartSync {
@@ -116,19 +121,32 @@ fn run_worker(
                 selected_set_ids,
                 selected_folders,
             }) => {
-                cancelled.store(false, Ordering::SeqCst);
+                cancelled.reset();
                 handle_sync(
                     &app_tx,
                     &config,
                     direction,
-                    Arc::clone(&cancelled),
+                    cancelled.clone(),
                     selected_set_ids,
                     selected_folders,
+                    false,
                 );
             }
-            Ok(WorkerMessage::StartDryRun { direction }) => {
-                cancelled.store(false, Ordering::SeqCst);
-                handle_dry_run(&app_tx, &config, direction, Arc::clone(&cancelled));
+            Ok(WorkerMessage::StartFavoritesSync) => {
+                cancelled.reset();
+                handle_sync(
+                    &app_tx,
+                    &config,
+                    SyncDirection::StableToLazer,
+                    cancelled.clone(),
+                    None,
+                    None,
+                    true,
+                );
+            }
+            Ok(WorkerMessage::StartDryRun { direction, filter }) => {
+                cancelled.reset();
+                handle_dry_run(&app_tx, &config, direction, filter, cancelled.clone());
             }
             Ok(WorkerMessage::CalculateStats) => {
                 handle_calculate_stats(&app_tx, &config);
@@ -140,14 +158,37 @@ fn run_worker(
                 handle_load_collections(&app_tx, &config);
             }
             Ok(WorkerMessage::SyncCollections { strategy }) => {
-                handle_sync_collections(&app_tx, &config, strategy);
+                if !handle_sync_collections(&rx, &app_tx, &config, strategy) {
+                    break;
+                }
+            }
+            Ok(WorkerMessage::ResolveCollectionConflict { .. }) => {
+                // Only meaningful while a collection sync is waiting on it
             }
             Ok(WorkerMessage::CreateBackup {
                 target,
                 compression,
+                format,
+                mode,
+            }) => {
+                cancelled.reset();
+                handle_create_backup(
+                    &app_tx,
+                    &config,
+                    target,
+                    compression,
+                    format,
+                    mode,
+                    cancelled.clone(),
+                );
+            }
+            Ok(WorkerMessage::PreviewBackup {
+                target,
+                compression,
+                format,
                 mode,
             }) => {
-                handle_create_backup(&app_tx, &config, target, compression, mode);
+                handle_preview_backup(&app_tx, &config, target, compression, format, mode);
             }
             Ok(WorkerMessage::LoadBackups) => {
                 handle_load_backups(&app_tx);
@@ -162,6 +203,7 @@ fn run_worker(
                 skip_duplicates,
                 include_metadata,
             }) => {
+                cancelled.reset();
                 handle_media_extraction(
                     &app_tx,
                     &config,
@@ -170,6 +212,7 @@ fn run_worker(
                     output_path,
                     skip_duplicates,
                     include_metadata,
+                    cancelled.clone(),
                 );
             }
             Ok(WorkerMessage::LoadReplays) => {
@@ -181,6 +224,7 @@ fn run_worker(
                 filter,
                 rename_pattern,
             }) => {
+                cancelled.reset();
                 handle_replay_export(
                     &app_tx,
                     &config,
@@ -188,6 +232,7 @@ fn run_worker(
                     output_path,
                     filter,
                     rename_pattern,
+                    cancelled.clone(),
                 );
             }
             Ok(WorkerMessage::StartUnifiedSetup {
@@ -211,11 +256,11 @@ fn run_worker(
             }
             Ok(WorkerMessage::UpdateConfig(new_config)) => {
                 if let Ok(mut guard) = config.write() {
-                    *guard = new_config;
+                    *guard = new_config.with_active_profile();
                 }
             }
             Ok(WorkerMessage::Cancel) => {
-                cancelled.store(true, Ordering::SeqCst);
+                cancelled.cancel();
             }
             Ok(WorkerMessage::Shutdown) | Err(_) => {
                 break;
@@ -224,6 +269,37 @@ fn run_worker(
     }
 }
 
+/// Folders between scan progress messages, to keep the UI channel quiet
+const SCAN_PROGRESS_EVERY: usize = 25;
+
+/// Live scan status, e.g. "1200/3000 folders, 1150 sets, 4800 diffs: 70% osu, 20% mania"
+fn scan_progress_message(progress: &ScanProgress) -> String {
+    let percentages = ModePercentage::from_counts(&progress.mode_counts);
+    let mut modes = [
+        (percentages.osu, "osu"),
+        (percentages.taiko, "taiko"),
+        (percentages.catch, "catch"),
+        (percentages.mania, "mania"),
+    ];
+    modes.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let breakdown = modes
+        .iter()
+        .filter(|(percent, _)| *percent > 0.0)
+        .map(|(percent, mode)| format!("{:.0}% {}", percent, mode))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut message = format!(
+        "{}/{} folders, {} sets, {} diffs",
+        progress.current, progress.total, progress.total_sets, progress.total_difficulties
+    );
+    if !breakdown.is_empty() {
+        message.push_str(": ");
+        message.push_str(&breakdown);
+    }
+    message
+}
+
 fn handle_scan(
     app_tx: &Sender<AppMessage>,
     config: &Arc<RwLock<Config>>,
@@ -255,10 +331,20 @@ fn handle_scan(
                     message: "Scanning osu!stable beatmaps...".to_string(),
                 });
 
+                let progress_tx = app_tx.clone();
+                let on_progress = move |progress: &ScanProgress| {
+                    if progress.current % SCAN_PROGRESS_EVERY == 0 || progress.folder.is_empty() {
+                        let _ = progress_tx.send(AppMessage::ScanProgress {
+                            stable: true,
+                            message: scan_progress_message(progress),
+                        });
+                    }
+                };
+
                 // Use fast mode (skip hashing) for browsing - 5x faster
                 match StableScanner::new(songs_path)
                     .skip_hashing()
-                    .scan_parallel_timed()
+                    .scan_parallel_with_progress(Some(Box::new(on_progress)))
                 {
                     Ok((sets, timing)) => {
                         let total_beatmaps: usize = sets.iter().map(|s| s.beatmaps.len()).sum();
@@ -372,9 +458,10 @@ fn handle_sync(
     app_tx: &Sender<AppMessage>,
     config: &Arc<RwLock<Config>>,
     direction: SyncDirection,
-    cancelled: Arc<AtomicBool>,
+    cancelled: CancellationToken,
     selected_set_ids: Option<HashSet<i32>>,
     selected_folders: Option<HashSet<String>>,
+    favorites_only: bool,
 ) {
     let config = config_snapshot(config);
 
@@ -430,6 +517,15 @@ fn handle_sync(
         let _ = progress_tx.send(AppMessage::SyncProgress(progress));
     });
 
+    // Forward per-set events to the log pane; ends when the engine is dropped
+    let (event_tx, event_rx) = mpsc::channel::<SyncEvent>();
+    let event_app_tx = app_tx.clone();
+    thread::spawn(move || {
+        for event in event_rx {
+            let _ = event_app_tx.send(AppMessage::SyncEvent(event));
+        }
+    });
+
     // Build engine with cancellation support
     // Clone config since SyncEngineBuilder takes ownership
     let mut builder = SyncEngineBuilder::new()
@@ -437,7 +533,8 @@ fn handle_sync(
         .stable_scanner(scanner)
         .lazer_database(database)
         .progress_callback(progress_callback)
-        .cancellation(Arc::clone(&cancelled));
+        .event_sender(event_tx)
+        .cancellation(cancelled.clone());
 
     // Add selected set IDs if provided (for user selection from dry run)
     if let Some(set_ids) = selected_set_ids {
@@ -461,7 +558,7 @@ fn handle_sync(
     };
 
     // Check for cancel before starting
-    if cancelled.load(Ordering::SeqCst) {
+    if cancelled.is_cancelled() {
         let _ = app_tx.send(AppMessage::SyncCancelled);
         return;
     }
@@ -470,17 +567,44 @@ fn handle_sync(
     let resolver = osu_sync_core::sync::AutoResolver::skip_all();
 
     // Run sync - the engine will check is_cancelled() via the shared flag
-    let sync_result = engine.sync(direction, &resolver);
+    let start = Instant::now();
+    let sync_result = if favorites_only {
+        engine.sync_favorites(&resolver).map(|favorites| {
+            // Show favourites missing from Songs alongside import errors
+            let mut result = favorites.result;
+            result
+                .errors
+                .extend(favorites.missing.into_iter().map(|md5| {
+                    SyncError::new(
+                        None,
+                        format!("Favourite {} is not in the Songs folder", md5),
+                    )
+                }));
+            result
+        })
+    } else {
+        engine.sync(direction, &resolver)
+    };
 
     match sync_result {
         Ok(result) => {
-            if cancelled.load(Ordering::SeqCst) {
+            if cancelled.is_cancelled() {
                 let _ = app_tx.send(AppMessage::SyncCancelled);
             } else {
+                record_operation(&AuditRecord::sync(&result, start.elapsed()));
                 let _ = app_tx.send(AppMessage::SyncComplete(result));
             }
         }
+        Err(CoreError::Cancelled) => {
+            let _ = app_tx.send(AppMessage::SyncCancelled);
+        }
         Err(e) => {
+            record_operation(&AuditRecord::failed(
+                AuditOperation::Sync,
+                direction.to_string(),
+                start.elapsed(),
+                e.to_string(),
+            ));
             let _ = app_tx.send(AppMessage::Error(format!(
                 "Sync failed: {}",
                 format_core_error(&e)
@@ -492,6 +616,18 @@ fn handle_sync(
 fn handle_calculate_stats(app_tx: &Sender<AppMessage>, config: &Arc<RwLock<Config>>) {
     let config = config_snapshot(config);
 
+    // Stats need at least one install; otherwise report why instead of empty numbers
+    if let (Err(e), Err(_)) = (
+        config.require_stable_songs_path(),
+        config.require_lazer_path(),
+    ) {
+        let _ = app_tx.send(AppMessage::Error(format!(
+            "Cannot calculate statistics: {}",
+            e
+        )));
+        return;
+    }
+
     let _ = app_tx.send(AppMessage::StatsProgress(
         "Scanning osu!stable...".to_string(),
     ));
@@ -526,7 +662,21 @@ fn handle_calculate_stats(app_tx: &Sender<AppMessage>, config: &Arc<RwLock<Confi
     ));
 
     // Calculate comparison stats
-    let stats = StatsAnalyzer::compare(&stable_sets, &lazer_sets);
+    let mut stats = StatsAnalyzer::compare(&stable_sets, &lazer_sets);
+
+    // Edited-map detection needs osu!.db; skip it quietly when unavailable
+    if let Some(path) = config.stable_path.as_ref() {
+        let _ = app_tx.send(AppMessage::StatsProgress(
+            "Checking beatmap integrity...".to_string(),
+        ));
+        let hash_cache = Arc::new(HashCache::load());
+        stats.integrity = osu_sync_core::lazer::StableDatabase::open(path)
+            .and_then(|db| IntegrityChecker::check_database_cached(&db, hash_cache.clone()))
+            .ok();
+        if let Err(e) = hash_cache.save() {
+            tracing::warn!("Failed to save hash cache: {}", e);
+        }
+    }
 
     let _ = app_tx.send(AppMessage::StatsComplete(stats));
 }
@@ -534,13 +684,13 @@ fn handle_calculate_stats(app_tx: &Sender<AppMessage>, config: &Arc<RwLock<Confi
 fn handle_load_collections(app_tx: &Sender<AppMessage>, config: &Arc<RwLock<Config>>) {
     let config = config_snapshot(config);
 
-    let collections = if let Some(stable_path) = config.stable_path.as_ref() {
+    let (collections, reports) = if let Some(stable_path) = config.stable_path.as_ref() {
         // The collection.db is in the root osu! folder, not in Songs
         // stable_path is the Songs folder, so we need to go up one level
         let osu_root = stable_path.parent().unwrap_or(stable_path);
         let collection_db_path = osu_root.join("collection.db");
 
-        match StableCollectionReader::read(&collection_db_path) {
+        let collections = match StableCollectionReader::read(&collection_db_path) {
             Ok(collections) => collections,
             Err(e) => {
                 let _ = app_tx.send(AppMessage::Error(format!(
@@ -549,31 +699,51 @@ fn handle_load_collections(app_tx: &Sender<AppMessage>, config: &Arc<RwLock<Conf
                 )));
                 Vec::new()
             }
-        }
+        };
+
+        // Installed counts need osu!.db; leave them out when it can't be read
+        let reports = osu_sync_core::lazer::StableDatabase::open(osu_root)
+            .map(|db| {
+                let installed: HashSet<String> = db
+                    .raw_beatmaps()
+                    .iter()
+                    .filter_map(|b| b.hash.as_ref().map(|h| h.to_lowercase()))
+                    .collect();
+                CollectionSyncEngine::analyze(&collections, &installed)
+            })
+            .unwrap_or_default();
+
+        (collections, reports)
     } else {
         let _ = app_tx.send(AppMessage::Error(
             "osu!stable path not configured".to_string(),
         ));
-        Vec::new()
+        (Vec::new(), Vec::new())
     };
 
-    let _ = app_tx.send(AppMessage::CollectionsLoaded(collections));
+    let _ = app_tx.send(AppMessage::CollectionsLoaded {
+        collections,
+        reports,
+    });
 }
 
+/// Returns `false` if the worker was told to shut down while a conflict
+/// was waiting for the user
 fn handle_sync_collections(
+    rx: &Receiver<WorkerMessage>,
     app_tx: &Sender<AppMessage>,
     config: &Arc<RwLock<Config>>,
     strategy: CollectionSyncStrategy,
-) {
+) -> bool {
     let config = config_snapshot(config);
 
     // Load collections from stable
-    let collections = if let Some(stable_path) = config.stable_path.as_ref() {
+    let (collections, collection_db_path) = if let Some(stable_path) = config.stable_path.as_ref() {
         let osu_root = stable_path.parent().unwrap_or(stable_path);
         let collection_db_path = osu_root.join("collection.db");
 
         match StableCollectionReader::read(&collection_db_path) {
-            Ok(collections) => collections,
+            Ok(collections) => (collections, collection_db_path),
             Err(e) => {
                 let _ = app_tx.send(AppMessage::CollectionSyncComplete(
                     osu_sync_core::collection::CollectionSyncResult::failure(format!(
@@ -581,7 +751,7 @@ fn handle_sync_collections(
                         e
                     )),
                 ));
-                return;
+                return true;
             }
         }
     } else {
@@ -590,7 +760,7 @@ fn handle_sync_collections(
                 "osu!stable path not configured",
             ),
         ));
-        return;
+        return true;
     };
 
     // Send progress updates for each collection
@@ -603,9 +773,40 @@ fn handle_sync_collections(
         });
     }
 
-    // Perform the sync
-    match CollectionSyncEngine::sync_to_lazer(&collections, strategy) {
+    // lazer can't be written to directly, so the collections go into a
+    // collection.db in its import folder for the user to import
+    let Some(lazer_path) = config.lazer_path.as_ref() else {
+        let _ = app_tx.send(AppMessage::CollectionSyncComplete(
+            osu_sync_core::collection::CollectionSyncResult::failure(
+                "osu!lazer path not configured",
+            ),
+        ));
+        return true;
+    };
+    let target = lazer_path.join("import").join("collection.db");
+
+    // Collections the lazer copy holds with different beatmaps are settled
+    // one at a time through the conflict dialog
+    let existing = StableCollectionReader::read(&target).unwrap_or_default();
+    if !CollectionSyncEngine::detect_conflicts(&collections, &existing).is_empty() {
+        return sync_collections_resolving(rx, app_tx, &collection_db_path, &target);
+    }
+
+    // Perform the sync, skipping it if nothing changed since the last one
+    let mut state = CollectionSyncState::load();
+    match CollectionSyncEngine::sync_if_changed(
+        &collections,
+        CollectionSyncDirection::StableToLazer,
+        strategy,
+        &target,
+        &mut state,
+    ) {
         Ok(result) => {
+            if result.success && !result.unchanged {
+                if let Err(e) = state.save() {
+                    tracing::warn!("Failed to save collection sync state: {}", e);
+                }
+            }
             let _ = app_tx.send(AppMessage::CollectionSyncComplete(result));
         }
         Err(e) => {
@@ -617,13 +818,73 @@ fn handle_sync_collections(
             ));
         }
     }
+    true
+}
+
+/// Sync collections, asking the UI about each conflict and waiting for a
+/// `ResolveCollectionConflict` reply
+///
+/// Returns `false` if the worker was told to shut down while waiting.
+fn sync_collections_resolving(
+    rx: &Receiver<WorkerMessage>,
+    app_tx: &Sender<AppMessage>,
+    stable_db: &Path,
+    target: &Path,
+) -> bool {
+    let mut shutdown = false;
+    let mut remembered = None;
+
+    let result = CollectionSyncEngine::sync_to_lazer_resolving(stable_db, target, |conflict| {
+        if remembered.is_some() {
+            return remembered;
+        }
+        let _ = app_tx.send(AppMessage::CollectionConflictFound(conflict.clone()));
+        loop {
+            match rx.recv() {
+                Ok(WorkerMessage::ResolveCollectionConflict {
+                    action,
+                    apply_to_all,
+                }) => {
+                    if apply_to_all {
+                        remembered = Some(action);
+                    }
+                    return Some(action);
+                }
+                Ok(WorkerMessage::Cancel) => return None,
+                Ok(WorkerMessage::Shutdown) | Err(_) => {
+                    shutdown = true;
+                    return None;
+                }
+                // Nothing else can start until this sync is settled
+                Ok(_) => {}
+            }
+        }
+    });
+
+    match result {
+        Ok(result) => {
+            let _ = app_tx.send(AppMessage::CollectionSyncComplete(result));
+        }
+        // Cancelled from the dialog, which already went back to the menu
+        Err(CoreError::Cancelled) => {}
+        Err(e) => {
+            let _ = app_tx.send(AppMessage::CollectionSyncComplete(
+                osu_sync_core::collection::CollectionSyncResult::failure(format!(
+                    "Collection sync failed: {}",
+                    e
+                )),
+            ));
+        }
+    }
+    !shutdown
 }
 
 fn handle_dry_run(
     app_tx: &Sender<AppMessage>,
     config: &Arc<RwLock<Config>>,
     direction: SyncDirection,
-    cancelled: Arc<AtomicBool>,
+    filter: FilterCriteria,
+    cancelled: CancellationToken,
 ) {
     let config = config_snapshot(config);
 
@@ -663,7 +924,7 @@ fn handle_dry_run(
     };
 
     // Check for cancel before starting
-    if cancelled.load(Ordering::SeqCst) {
+    if cancelled.is_cancelled() {
         let _ = app_tx.send(AppMessage::SyncCancelled);
         return;
     }
@@ -692,7 +953,7 @@ fn handle_dry_run(
         .stable_scanner(scanner)
         .lazer_database(database)
         .progress_callback(progress_callback)
-        .cancellation(Arc::clone(&cancelled))
+        .cancellation(cancelled.clone())
         .build()
     {
         Ok(e) => e,
@@ -705,15 +966,20 @@ fn handle_dry_run(
         }
     };
 
+    let engine = engine.with_filter(filter);
+
     // Run dry run - the engine will check is_cancelled() via the shared flag
     match engine.dry_run(direction) {
         Ok(result) => {
-            if cancelled.load(Ordering::SeqCst) {
+            if cancelled.is_cancelled() {
                 let _ = app_tx.send(AppMessage::SyncCancelled);
             } else {
                 let _ = app_tx.send(AppMessage::DryRunComplete { result, direction });
             }
         }
+        Err(CoreError::Cancelled) => {
+            let _ = app_tx.send(AppMessage::SyncCancelled);
+        }
         Err(e) => {
             let _ = app_tx.send(AppMessage::Error(format!(
                 "Dry run failed: {}",
@@ -723,70 +989,77 @@ fn handle_dry_run(
     }
 }
 
+/// Path a backup of `target` reads from, or why it cannot be backed up
+fn backup_source(config: &Config, target: BackupTarget) -> Result<PathBuf, String> {
+    let (path, missing) = match target {
+        BackupTarget::StableSongs => (
+            config.stable_path.as_ref().map(|p| p.join("Songs")),
+            "osu!stable Songs folder not found",
+        ),
+        BackupTarget::StableCollections => (
+            config.stable_path.as_ref().map(|p| p.join("collection.db")),
+            "collection.db not found",
+        ),
+        BackupTarget::StableScores => (
+            config.stable_path.as_ref().map(|p| p.join("scores.db")),
+            "scores.db not found",
+        ),
+        BackupTarget::LazerData => (config.lazer_path.clone(), "osu!lazer data folder not found"),
+        // For "All", we backup stable folder (which contains Songs, collection.db, scores.db)
+        BackupTarget::All => (config.stable_path.clone(), "osu!stable folder not found"),
+    };
+    match path {
+        Some(path) if path.exists() => Ok(path),
+        _ => Err(missing.to_string()),
+    }
+}
+
+fn handle_preview_backup(
+    app_tx: &Sender<AppMessage>,
+    config: &Arc<RwLock<Config>>,
+    target: BackupTarget,
+    compression: CompressionLevel,
+    format: BackupFormat,
+    mode: BackupMode,
+) {
+    let config = config_snapshot(config);
+    let options = BackupOptions::new()
+        .with_compression(compression)
+        .with_format(format)
+        .with_mode(mode);
+    let result = backup_source(&config, target).and_then(|source_path| {
+        BackupManager::new(BackupManager::default_backup_dir())
+            .preview_backup(target, &source_path, options)
+            .map_err(|e| e.to_string())
+    });
+    let _ = app_tx.send(AppMessage::BackupPreviewReady { target, result });
+}
+
 fn handle_create_backup(
     app_tx: &Sender<AppMessage>,
     config: &Arc<RwLock<Config>>,
     target: BackupTarget,
     compression: CompressionLevel,
+    format: BackupFormat,
     mode: BackupMode,
+    cancelled: CancellationToken,
 ) {
     let config = config_snapshot(config);
-    let backup_manager = BackupManager::new(BackupManager::default_backup_dir());
+    let backup_manager =
+        BackupManager::new(BackupManager::default_backup_dir()).with_cancellation(cancelled);
 
-    // Determine source path based on target
-    let source_path = match target {
-        BackupTarget::StableSongs => match config.stable_path.as_ref().map(|p| p.join("Songs")) {
-            Some(path) if path.exists() => path,
-            _ => {
-                let _ = app_tx.send(AppMessage::Error(
-                    "osu!stable Songs folder not found".to_string(),
-                ));
-                return;
-            }
-        },
-        BackupTarget::StableCollections => {
-            match config.stable_path.as_ref().map(|p| p.join("collection.db")) {
-                Some(path) if path.exists() => path,
-                _ => {
-                    let _ = app_tx.send(AppMessage::Error("collection.db not found".to_string()));
-                    return;
-                }
-            }
-        }
-        BackupTarget::StableScores => {
-            match config.stable_path.as_ref().map(|p| p.join("scores.db")) {
-                Some(path) if path.exists() => path,
-                _ => {
-                    let _ = app_tx.send(AppMessage::Error("scores.db not found".to_string()));
-                    return;
-                }
-            }
-        }
-        BackupTarget::LazerData => match config.lazer_path.as_ref() {
-            Some(path) if path.exists() => path.clone(),
-            _ => {
-                let _ = app_tx.send(AppMessage::Error(
-                    "osu!lazer data folder not found".to_string(),
-                ));
-                return;
-            }
-        },
-        BackupTarget::All => {
-            // For "All", we backup stable folder (which contains Songs, collection.db, scores.db)
-            match config.stable_path.as_ref() {
-                Some(path) if path.exists() => path.clone(),
-                _ => {
-                    let _ =
-                        app_tx.send(AppMessage::Error("osu!stable folder not found".to_string()));
-                    return;
-                }
-            }
+    let source_path = match backup_source(&config, target) {
+        Ok(path) => path,
+        Err(message) => {
+            let _ = app_tx.send(AppMessage::Error(message));
+            return;
         }
     };
 
     // Create backup options
     let options = BackupOptions::new()
         .with_compression(compression)
+        .with_format(format)
         .with_mode(mode);
 
     let is_incremental = mode == BackupMode::Incremental;
@@ -798,6 +1071,7 @@ fn handle_create_backup(
     });
 
     // Create backup with options
+    let start = Instant::now();
     match backup_manager.create_backup_with_options(
         target,
         &source_path,
@@ -808,13 +1082,25 @@ fn handle_create_backup(
             let size_bytes = std::fs::metadata(&backup_path)
                 .map(|m| m.len())
                 .unwrap_or(0);
+            record_operation(
+                &AuditRecord::new(AuditOperation::Backup, target.label(), start.elapsed())
+                    .with_bytes(size_bytes),
+            );
             let _ = app_tx.send(AppMessage::BackupComplete {
                 path: backup_path,
                 size_bytes,
                 is_incremental,
             });
         }
+        // The app has already left the progress screen
+        Err(CoreError::Cancelled) => {}
         Err(e) => {
+            record_operation(&AuditRecord::failed(
+                AuditOperation::Backup,
+                target.label(),
+                start.elapsed(),
+                e.to_string(),
+            ));
             let _ = app_tx.send(AppMessage::Error(format!("Backup failed: {}", e)));
         }
     }
@@ -900,6 +1186,7 @@ fn handle_restore_backup(
     });
 
     // Restore backup
+    let start = Instant::now();
     match backup_manager.restore_backup_with_progress(
         &backup_path,
         &dest_path,
@@ -908,17 +1195,29 @@ fn handle_restore_backup(
         Ok(()) => {
             // Get file count from last progress or estimate
             let files_restored = 0; // We don't track this currently
+            record_operation(&AuditRecord::new(
+                AuditOperation::Restore,
+                target.label(),
+                start.elapsed(),
+            ));
             let _ = app_tx.send(AppMessage::RestoreComplete {
                 dest_path,
                 files_restored,
             });
         }
         Err(e) => {
+            record_operation(&AuditRecord::failed(
+                AuditOperation::Restore,
+                target.label(),
+                start.elapsed(),
+                e.to_string(),
+            ));
             let _ = app_tx.send(AppMessage::Error(format!("Restore failed: {}", e)));
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_media_extraction(
     app_tx: &Sender<AppMessage>,
     config: &Arc<RwLock<Config>>,
@@ -927,6 +1226,7 @@ fn handle_media_extraction(
     output_path: PathBuf,
     _skip_duplicates: bool,
     include_metadata: bool,
+    cancelled: CancellationToken,
 ) {
     let config = config_snapshot(config);
     use osu_sync_core::media::{ExtractionProgress, MediaExtractor};
@@ -966,13 +1266,30 @@ fn handle_media_extraction(
     let mut extractor = MediaExtractor::new(&output_path)
         .with_media_type(media_type)
         .with_organization(organization)
-        .with_metadata(include_metadata);
+        .with_metadata(include_metadata)
+        .with_cancellation(cancelled);
 
+    let start = Instant::now();
     match extractor.extract_from_stable(&songs_path, &sets, Some(progress_callback)) {
         Ok(result) => {
-            let _ = app_tx.send(AppMessage::MediaComplete(result));
+            record_operation(&AuditRecord::extraction(
+                &result,
+                &output_path,
+                start.elapsed(),
+            ));
+            let _ = app_tx.send(AppMessage::MediaComplete {
+                result,
+                output_path,
+            });
         }
+        Err(CoreError::Cancelled) => {}
         Err(e) => {
+            record_operation(&AuditRecord::failed(
+                AuditOperation::Extraction,
+                output_path.display().to_string(),
+                start.elapsed(),
+                e.to_string(),
+            ));
             let _ = app_tx.send(AppMessage::Error(format!("Media extraction failed: {}", e)));
         }
     }
@@ -1020,6 +1337,7 @@ fn handle_replay_export(
     output_path: PathBuf,
     filter: osu_sync_core::replay::ReplayFilter,
     rename_pattern: Option<String>,
+    cancelled: CancellationToken,
 ) {
     let config = config_snapshot(config);
     use osu_sync_core::replay::{ReplayExporter, ReplayProgress, StableReplayReader};
@@ -1053,11 +1371,28 @@ fn handle_replay_export(
         let _ = progress_tx.send(AppMessage::ReplayProgress(progress));
     });
 
+    // Build a beatmap index from osu!.db so replays can be matched to beatmaps
+    let beatmap_index = osu_sync_core::lazer::StableDatabase::open(&stable_path)
+        .and_then(|db| {
+            let sets = db.get_all_beatmap_sets()?;
+            Ok(sets
+                .iter()
+                .map(|s| db.to_beatmap_set(s))
+                .collect::<Vec<_>>())
+        })
+        .map(osu_sync_core::BeatmapIndex::new)
+        .ok();
+
     // Create exporter with filter and rename pattern
-    let mut exporter = ReplayExporter::new(output_path)
+    let mut exporter = ReplayExporter::new(&output_path)
         .with_organization(organization)
         .with_filter(filter)
-        .with_progress_callback(progress_callback);
+        .with_progress_callback(progress_callback)
+        .with_cancellation(cancelled);
+
+    if let Some(ref index) = beatmap_index {
+        exporter = exporter.with_beatmap_index(index);
+    }
 
     // Add rename pattern if provided
     if let Some(pattern) = rename_pattern {
@@ -1066,8 +1401,12 @@ fn handle_replay_export(
 
     match exporter.export(&replays) {
         Ok(result) => {
-            let _ = app_tx.send(AppMessage::ReplayComplete(result));
+            let _ = app_tx.send(AppMessage::ReplayComplete {
+                result,
+                output_path,
+            });
         }
+        Err(CoreError::Cancelled) => {}
         Err(e) => {
             let _ = app_tx.send(AppMessage::Error(format!("Replay export failed: {}", e)));
         }
@@ -1250,7 +1589,10 @@ fn handle_unified_disable(app_tx: &Sender<AppMessage>, config_lock: &Arc<RwLock<
 
     // Update config to disable unified storage
     config.unified_storage = Some(osu_sync_core::unified::UnifiedStorageConfig::disabled());
-    let _ = config.save();
+    // Save from disk, so the active profile's paths don't become the default
+    let mut saved = Config::load();
+    saved.unified_storage = config.unified_storage.clone();
+    let _ = saved.save();
     if let Ok(mut guard) = config_lock.write() {
         *guard = config.clone();
     }
@@ -1286,12 +1628,34 @@ mod tests {
         assert_eq!(snapshot.theme, ThemeName::Monochrome);
     }
 
+    #[test]
+    fn scan_progress_message_lists_modes_by_share() {
+        let mut progress = ScanProgress {
+            current: 25,
+            total: 100,
+            total_sets: 24,
+            total_difficulties: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            scan_progress_message(&progress),
+            "25/100 folders, 24 sets, 10 diffs"
+        );
+
+        progress.mode_counts.osu = 3;
+        progress.mode_counts.mania = 7;
+        assert_eq!(
+            scan_progress_message(&progress),
+            "25/100 folders, 24 sets, 10 diffs: 70% mania, 30% osu"
+        );
+    }
+
     #[test]
     fn worker_uses_updated_config_for_status() {
         let (app_tx, app_rx) = mpsc::channel::<AppMessage>();
         let (worker_tx, worker_rx) = mpsc::channel::<WorkerMessage>();
         let (_resolution_tx, resolution_rx) = mpsc::channel();
-        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancelled = CancellationToken::new();
 
         let handle = thread::spawn(move || {
             run_worker(worker_rx, app_tx, resolution_rx, cancelled);
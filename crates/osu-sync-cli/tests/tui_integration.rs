@@ -30,6 +30,7 @@ mod test_harness {
             action,
             size_bytes: 1_000_000,
             difficulty_count: 4,
+            redundant_difficulties: 0,
         }
     }
 
@@ -69,6 +70,7 @@ mod test_harness {
             total_skip: 1,
             total_duplicate: 1,
             total_size_bytes: 7_000_000,
+            total_redundant_difficulties: 0,
         }
     }
 
@@ -475,6 +477,7 @@ fn test_empty_result_handling() {
         total_skip: 0,
         total_duplicate: 0,
         total_size_bytes: 0,
+        total_redundant_difficulties: 0,
     };
 
     let visible_indices = filter_items(&result.items, "");
@@ -495,6 +498,7 @@ fn test_all_items_skipped() {
         total_skip: 2,
         total_duplicate: 0,
         total_size_bytes: 0,
+        total_redundant_difficulties: 0,
     };
 
     // Ctrl+A should not select any items
@@ -8,6 +8,8 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
 
+use crate::config::paths;
+
 /// Maximum number of log entries to keep
 pub const MAX_LOG_ENTRIES: usize = 50;
 
@@ -135,9 +137,15 @@ impl ActivityLog {
     }
 
     /// Load activity log from file
+    ///
+    /// Falls back to the log's old location in the config directory until
+    /// the log is next saved.
     pub fn load() -> Self {
         let mut log = Self::new();
-        if let Some(path) = Self::log_path() {
+        let path = Self::log_path()
+            .filter(|path| path.exists())
+            .or_else(Self::legacy_log_path);
+        if let Some(path) = path {
             if let Ok(file) = File::open(&path) {
                 let reader = BufReader::new(file);
                 for line in reader.lines().take(MAX_LOG_ENTRIES).flatten() {
@@ -170,7 +178,12 @@ impl ActivityLog {
 
     /// Get the log file path
     fn log_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|p| p.join("osu-sync").join("activity.log"))
+        paths::app_data_dir().map(|p| p.join("activity.log"))
+    }
+
+    /// Where the log was kept before it moved to the app data directory
+    fn legacy_log_path() -> Option<PathBuf> {
+        paths::config_dir().map(|p| p.join("activity.log"))
     }
 
     /// Add a new entry to the log
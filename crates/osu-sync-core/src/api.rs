@@ -0,0 +1,569 @@
+//! osu! web API client (`online` feature)
+//!
+//! osu!.db and the lazer realm only record what a beatmap's status was when
+//! it was downloaded, so maps ranked since then still show as pending. This
+//! module asks the osu! API v2 for current values. Requests are spaced out to
+//! stay under the API rate limit, and retried with exponential backoff when
+//! the API answers 429 Too Many Requests, a server error, or not at all.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::beatmap::{BeatmapInfo, BeatmapSet};
+use crate::error::{Error, Result};
+use crate::stats::RankedStatus;
+
+/// Base URL of the osu! API v2
+pub const API_BASE_URL: &str = "https://osu.ppy.sh/api/v2";
+
+/// Retries after a rate-limited, failed or unanswered request
+const MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubled for each further retry
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Most difficulty IDs `GET /beatmaps` accepts in one request
+const BEATMAPS_PER_REQUEST: usize = 50;
+
+/// A beatmap set as returned by `GET /beatmapsets/{id}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiBeatmapSet {
+    /// Online beatmap set ID
+    pub id: i32,
+    /// Ranked status name, e.g. `"ranked"` or `"loved"`
+    pub status: String,
+    /// Difficulties in the set
+    #[serde(default)]
+    pub beatmaps: Vec<ApiBeatmap>,
+}
+
+/// A difficulty, as listed in an [`ApiBeatmapSet`] or returned by
+/// `GET /beatmaps` and `GET /beatmaps/lookup`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiBeatmap {
+    /// Online beatmap ID
+    pub id: i32,
+    /// Online ID of the set the difficulty belongs to
+    pub beatmapset_id: i32,
+    /// MD5 of the `.osu` file
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Star rating without mods
+    pub difficulty_rating: f32,
+    /// Ranked status name of the difficulty
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+/// Response of `GET /beatmaps`
+#[derive(Debug, Deserialize)]
+struct ApiBeatmaps {
+    beatmaps: Vec<ApiBeatmap>,
+}
+
+/// Parse an API status name, `None` if it is not recognized
+fn parse_status(status: &str) -> Option<RankedStatus> {
+    Some(match status {
+        "graveyard" => RankedStatus::Graveyard,
+        "wip" => RankedStatus::Wip,
+        "pending" => RankedStatus::Pending,
+        "ranked" => RankedStatus::Ranked,
+        "approved" => RankedStatus::Approved,
+        "qualified" => RankedStatus::Qualified,
+        "loved" => RankedStatus::Loved,
+        _ => return None,
+    })
+}
+
+impl ApiBeatmapSet {
+    /// Parse the API status name, `None` if it is not recognized
+    pub fn ranked_status(&self) -> Option<RankedStatus> {
+        parse_status(&self.status)
+    }
+
+    /// Copy status and star ratings onto a local copy of the set
+    ///
+    /// Difficulties are matched by MD5; ones the API doesn't list, such as
+    /// locally edited difficulties, keep their status and star rating.
+    /// Returns whether any difficulty was updated.
+    fn apply_to(&self, set: &mut BeatmapSet) -> bool {
+        let status = self.ranked_status();
+        let mut updated = false;
+        for beatmap in &mut set.beatmaps {
+            if let Some(online) = self.beatmaps.iter().find(|b| b.matches(beatmap)) {
+                online.apply_to(beatmap, status);
+                updated = true;
+            }
+        }
+        updated
+    }
+}
+
+impl ApiBeatmap {
+    /// Parse the difficulty's status name, `None` if missing or unknown
+    pub fn ranked_status(&self) -> Option<RankedStatus> {
+        self.status.as_deref().and_then(parse_status)
+    }
+
+    /// Whether `beatmap` is an unedited copy of this difficulty
+    fn matches(&self, beatmap: &BeatmapInfo) -> bool {
+        self.checksum
+            .as_deref()
+            .is_some_and(|checksum| checksum.eq_ignore_ascii_case(&beatmap.md5_hash))
+    }
+
+    /// Copy status and star rating onto a local copy of this difficulty
+    ///
+    /// `fallback_status` is used when the difficulty carries no status.
+    fn apply_to(&self, beatmap: &mut BeatmapInfo, fallback_status: Option<RankedStatus>) {
+        if let Some(status) = self.ranked_status().or(fallback_status) {
+            beatmap.ranked_status = Some(status);
+        }
+        beatmap.star_rating = Some(self.difficulty_rating);
+    }
+}
+
+/// Outcome of [`OsuApi::refresh_ranked_status`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RankedStatusRefresh {
+    /// Sets with at least one difficulty updated from the API
+    pub updated: usize,
+    /// Sets the API does not know, or whose difficulties were all edited
+    /// locally
+    pub unknown: usize,
+    /// Sets left untouched because the request failed
+    pub failed: usize,
+}
+
+/// Blocking client for the osu! API v2
+pub struct OsuApi {
+    agent: ureq::Agent,
+    token: String,
+    base_url: String,
+    min_interval: Duration,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl OsuApi {
+    /// Create a client that authenticates with an OAuth access token
+    ///
+    /// A token for public data can be obtained with the client credentials
+    /// grant of an OAuth application registered on the osu! website.
+    pub fn new(access_token: impl Into<String>) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!("osu-sync/", env!("CARGO_PKG_VERSION")))
+            .build();
+        Self {
+            agent,
+            token: access_token.into(),
+            base_url: API_BASE_URL.to_string(),
+            min_interval: Duration::from_secs(1),
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Use a different API base URL, e.g. a development server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the minimum time between requests (default one second)
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Fetch a beatmap set by online ID, `None` if the API doesn't know it
+    pub fn get_beatmapset(&self, id: i32) -> Result<Option<ApiBeatmapSet>> {
+        self.get_json(&format!("/beatmapsets/{}", id))
+    }
+
+    /// Look up a difficulty by the MD5 of its `.osu` file, `None` if the API
+    /// doesn't know it
+    pub fn lookup_beatmap(&self, md5: &str) -> Result<Option<ApiBeatmap>> {
+        self.get_json(&format!("/beatmaps/lookup?checksum={}", md5))
+    }
+
+    /// Fetch up to 50 difficulties by online ID in one request
+    ///
+    /// Difficulties the API doesn't know are left out of the result.
+    pub fn get_beatmaps(&self, ids: &[i32]) -> Result<Vec<ApiBeatmap>> {
+        let query: Vec<String> = ids.iter().map(|id| format!("ids%5B%5D={}", id)).collect();
+        let response: Option<ApiBeatmaps> =
+            self.get_json(&format!("/beatmaps?{}", query.join("&")))?;
+        Ok(response.map(|r| r.beatmaps).unwrap_or_default())
+    }
+
+    /// Update `ranked_status` and `star_rating` of sets from the API
+    ///
+    /// Difficulties with an online ID are fetched 50 at a time through
+    /// `GET /beatmaps`; sets with an online ID but no difficulty IDs (old
+    /// `.osu` formats) are fetched one by one. Only difficulties whose MD5
+    /// matches the API's are updated, so locally edited difficulties keep
+    /// their values, as do sets whose request fails.
+    pub fn refresh_ranked_status(&self, sets: &mut [BeatmapSet]) -> RankedStatusRefresh {
+        // Online difficulty ID -> (set index, difficulty index)
+        let mut by_beatmap: HashMap<i32, Vec<(usize, usize)>> = HashMap::new();
+        let mut by_set: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (set_idx, set) in sets.iter().enumerate() {
+            let mut has_beatmap_ids = false;
+            for (idx, beatmap) in set.beatmaps.iter().enumerate() {
+                if let Some(id) = beatmap.online_id() {
+                    by_beatmap.entry(id).or_default().push((set_idx, idx));
+                    has_beatmap_ids = true;
+                }
+            }
+            if !has_beatmap_ids {
+                if let Some(id) = set.id.filter(|&id| id > 0) {
+                    by_set.entry(id).or_default().push(set_idx);
+                }
+            }
+        }
+
+        let mut updated = HashSet::new();
+        let mut failed = HashSet::new();
+        let mut queried = HashSet::new();
+
+        let mut beatmap_ids: Vec<i32> = by_beatmap.keys().copied().collect();
+        beatmap_ids.sort_unstable();
+        for chunk in beatmap_ids.chunks(BEATMAPS_PER_REQUEST) {
+            let in_chunk = chunk.iter().flat_map(|id| &by_beatmap[id]).map(|&(s, _)| s);
+            match self.get_beatmaps(chunk) {
+                Ok(online) => {
+                    queried.extend(in_chunk);
+                    for beatmap in online {
+                        for &(set_idx, idx) in by_beatmap.get(&beatmap.id).into_iter().flatten() {
+                            let local = &mut sets[set_idx].beatmaps[idx];
+                            if beatmap.matches(local) {
+                                beatmap.apply_to(local, None);
+                                updated.insert(set_idx);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to refresh {} difficulties: {}", chunk.len(), e);
+                    failed.extend(in_chunk);
+                }
+            }
+        }
+
+        let mut set_ids: Vec<i32> = by_set.keys().copied().collect();
+        set_ids.sort_unstable();
+        for id in set_ids {
+            let indices = &by_set[&id];
+            match self.get_beatmapset(id) {
+                Ok(online) => {
+                    queried.extend(indices);
+                    for &idx in indices {
+                        if online.as_ref().is_some_and(|o| o.apply_to(&mut sets[idx])) {
+                            updated.insert(idx);
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to refresh beatmap set {}: {}", id, e);
+                    failed.extend(indices);
+                }
+            }
+        }
+
+        RankedStatusRefresh {
+            updated: updated.len(),
+            unknown: queried
+                .iter()
+                .filter(|idx| !updated.contains(*idx) && !failed.contains(*idx))
+                .count(),
+            failed: failed.difference(&updated).count(),
+        }
+    }
+
+    /// GET `path` and parse the JSON response, `None` on 404
+    fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 0;
+
+        loop {
+            self.throttle();
+            let result = self
+                .agent
+                .get(&url)
+                .set("Authorization", &format!("Bearer {}", self.token))
+                .set("Accept", "application/json")
+                .call();
+
+            let wait = match result {
+                Ok(response) => {
+                    let body = response.into_string()?;
+                    return serde_json::from_str(&body)
+                        .map(Some)
+                        .map_err(|e| Error::Api(format!("Invalid response from {}: {}", url, e)));
+                }
+                Err(ureq::Error::Status(404, _)) => return Ok(None),
+                Err(ureq::Error::Status(code, response))
+                    if (code == 429 || code >= 500) && attempt < MAX_RETRIES =>
+                {
+                    response
+                        .header("Retry-After")
+                        .and_then(|v| v.trim().parse().ok())
+                        .map(Duration::from_secs)
+                        .unwrap_or(backoff)
+                }
+                Err(ureq::Error::Status(code, _)) => {
+                    return Err(Error::Api(format!("{} returned HTTP {}", url, code)));
+                }
+                Err(ureq::Error::Transport(e)) if attempt < MAX_RETRIES => {
+                    tracing::debug!("Request to {} failed: {}", url, e);
+                    backoff
+                }
+                Err(ureq::Error::Transport(e)) => return Err(Error::Api(e.to_string())),
+            };
+
+            tracing::debug!("Retrying {} in {:?}", url, wait);
+            thread::sleep(wait);
+            attempt += 1;
+            backoff *= 2;
+        }
+    }
+
+    /// Sleep until `min_interval` has passed since the previous request
+    fn throttle(&self) {
+        let mut last = self
+            .last_request
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(previous) = *last {
+            let elapsed = previous.elapsed();
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+/// Update `ranked_status` and `star_rating` of sets with an online ID
+///
+/// Shorthand for [`OsuApi::refresh_ranked_status`] with a new client.
+pub fn refresh_ranked_status(sets: &mut [BeatmapSet], api_key: &str) -> RankedStatusRefresh {
+    OsuApi::new(api_key).refresh_ranked_status(sets)
+}
+
+/// Fill in the online ID of a set that has none
+///
+/// The set is looked up by the MD5 of its first difficulty. Returns the ID
+/// that was filled in, or `None` if the set already had one or the API
+/// doesn't know the difficulty (a local or unsubmitted map, or one edited
+/// after download). Only request failures are errors.
+pub fn resolve_online_id(set: &mut BeatmapSet, api: &OsuApi) -> Result<Option<i32>> {
+    if !set.missing_online_id() {
+        return Ok(None);
+    }
+    let Some(md5) = set
+        .beatmaps
+        .first()
+        .map(|b| b.md5_hash.as_str())
+        .filter(|md5| !md5.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let id = api
+        .lookup_beatmap(md5)?
+        .map(|beatmap| beatmap.beatmapset_id)
+        .filter(|&id| id > 0);
+    if id.is_some() {
+        set.id = id;
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_apply_api_set() {
+        let json = r#"{
+            "id": 1,
+            "status": "ranked",
+            "beatmaps": [
+                {"id": 10, "beatmapset_id": 1, "checksum": "AAA", "difficulty_rating": 5.25},
+                {"id": 11, "beatmapset_id": 1, "checksum": null, "difficulty_rating": 3.0}
+            ]
+        }"#;
+        let online: ApiBeatmapSet = serde_json::from_str(json).unwrap();
+        assert_eq!(online.ranked_status(), Some(RankedStatus::Ranked));
+
+        let mut set = BeatmapSet::new();
+        set.id = Some(1);
+        for md5 in ["aaa", "edited"] {
+            set.beatmaps.push(BeatmapInfo {
+                md5_hash: md5.to_string(),
+                star_rating: Some(1.0),
+                ranked_status: Some(RankedStatus::Pending),
+                ..Default::default()
+            });
+        }
+
+        assert!(online.apply_to(&mut set));
+        assert_eq!(set.beatmaps[0].star_rating, Some(5.25));
+        assert_eq!(set.beatmaps[0].ranked_status, Some(RankedStatus::Ranked));
+        // The edited difficulty is not the ranked one
+        assert_eq!(set.beatmaps[1].star_rating, Some(1.0));
+        assert_eq!(set.beatmaps[1].ranked_status, Some(RankedStatus::Pending));
+    }
+
+    /// Answer one request per response on a local port
+    ///
+    /// Returns the base URL and a receiver of the request lines.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for (code, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                tx.send(request_line.trim().to_string()).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nRetry-After: 0\r\nConnection: close\r\n\r\n{}",
+                    code,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    fn difficulty(beatmap_id: Option<i32>, md5: &str) -> BeatmapInfo {
+        let mut beatmap = BeatmapInfo {
+            md5_hash: md5.to_string(),
+            ranked_status: Some(RankedStatus::Pending),
+            ..Default::default()
+        };
+        beatmap.metadata.beatmap_id = beatmap_id;
+        beatmap
+    }
+
+    #[test]
+    fn test_refresh_batches_difficulties_and_skips_edited_ones() {
+        let (url, requests) = serve(vec![(
+            200,
+            r#"{"beatmaps": [
+                {"id": 10, "beatmapset_id": 1, "checksum": "aaa", "difficulty_rating": 5.0, "status": "ranked"},
+                {"id": 11, "beatmapset_id": 1, "checksum": "bbb", "difficulty_rating": 6.0, "status": "ranked"},
+                {"id": 20, "beatmapset_id": 2, "checksum": "ccc", "difficulty_rating": 2.0, "status": "loved"}
+            ]}"#,
+        )]);
+        let api = OsuApi::new("token")
+            .with_base_url(url)
+            .with_min_interval(Duration::ZERO);
+
+        let mut first = BeatmapSet::new();
+        first.id = Some(1);
+        first.beatmaps = vec![difficulty(Some(10), "AAA"), difficulty(Some(11), "edited")];
+        let mut second = BeatmapSet::new();
+        second.id = Some(2);
+        second.beatmaps = vec![difficulty(Some(20), "edited too")];
+        let mut sets = vec![first, second];
+
+        let summary = api.refresh_ranked_status(&mut sets);
+        assert_eq!(
+            summary,
+            RankedStatusRefresh {
+                updated: 1,
+                unknown: 1,
+                failed: 0
+            }
+        );
+        assert_eq!(
+            requests.recv().unwrap(),
+            "GET /beatmaps?ids%5B%5D=10&ids%5B%5D=11&ids%5B%5D=20 HTTP/1.1"
+        );
+        assert_eq!(
+            sets[0].beatmaps[0].ranked_status,
+            Some(RankedStatus::Ranked)
+        );
+        assert_eq!(sets[0].beatmaps[0].star_rating, Some(5.0));
+        assert_eq!(
+            sets[0].beatmaps[1].ranked_status,
+            Some(RankedStatus::Pending)
+        );
+        assert_eq!(
+            sets[1].beatmaps[0].ranked_status,
+            Some(RankedStatus::Pending)
+        );
+    }
+
+    #[test]
+    fn test_refresh_retries_429_and_counts_404_as_unknown() {
+        let (url, requests) = serve(vec![(429, "{}"), (200, r#"{"beatmaps": []}"#), (404, "{}")]);
+        let api = OsuApi::new("token")
+            .with_base_url(url)
+            .with_min_interval(Duration::ZERO);
+
+        // Both sets are unknown: one by difficulty ID, one old set by set ID
+        let mut with_ids = BeatmapSet::new();
+        with_ids.id = Some(1);
+        with_ids.beatmaps = vec![difficulty(Some(10), "aaa")];
+        let mut old = BeatmapSet::new();
+        old.id = Some(2);
+        old.beatmaps = vec![difficulty(None, "bbb")];
+        let mut sets = vec![with_ids, old];
+
+        let summary = api.refresh_ranked_status(&mut sets);
+        assert_eq!(
+            summary,
+            RankedStatusRefresh {
+                updated: 0,
+                unknown: 2,
+                failed: 0
+            }
+        );
+        let requests: Vec<String> = requests.iter().collect();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0], requests[1]);
+        assert!(requests[2].starts_with("GET /beatmapsets/2 "));
+        assert_eq!(
+            sets[1].beatmaps[0].ranked_status,
+            Some(RankedStatus::Pending)
+        );
+    }
+
+    #[test]
+    fn test_resolve_online_id_skips_known_sets() {
+        // Never reached: sets with an ID or without difficulties make no request
+        let api = OsuApi::new("token").with_base_url("http://127.0.0.1:9");
+
+        let mut known = BeatmapSet::new();
+        known.id = Some(42);
+        assert_eq!(resolve_online_id(&mut known, &api).unwrap(), None);
+        assert_eq!(known.id, Some(42));
+
+        let mut empty = BeatmapSet::new();
+        empty.id = Some(-1);
+        assert_eq!(resolve_online_id(&mut empty, &api).unwrap(), None);
+        assert_eq!(empty.id, Some(-1));
+    }
+}
@@ -0,0 +1,263 @@
+//! Persistent audit trail of completed operations
+//!
+//! Unlike the tracing log, the audit log is meant to be read by scripts:
+//! every sync, backup, restore and media extraction appends one JSON line
+//! with a fixed set of fields. New fields may be added, but existing ones
+//! keep their name and meaning.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::paths;
+use crate::error::{Error, Result};
+use crate::media::ExtractionResult;
+use crate::sync::SyncResult;
+
+/// Kind of operation an [`AuditRecord`] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Sync,
+    Backup,
+    Restore,
+    Extraction,
+}
+
+impl AuditOperation {
+    /// Get the display name for this operation
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Sync => "Sync",
+            Self::Backup => "Backup",
+            Self::Restore => "Restore",
+            Self::Extraction => "Extraction",
+        }
+    }
+}
+
+/// How an operation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// Everything succeeded
+    Success,
+    /// Some items failed, others succeeded
+    Partial,
+    /// The operation failed as a whole
+    Failed,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the operation finished
+    pub timestamp: DateTime<Utc>,
+    pub operation: AuditOperation,
+    /// What was operated on, e.g. `stable -> lazer` or a backup target
+    pub target: String,
+    /// Items written: imported sets, restored or extracted files
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Bytes written to the target
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub outcome: AuditOutcome,
+    /// Why the operation failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    /// Create a successful record with no counts
+    pub fn new(operation: AuditOperation, target: impl Into<String>, duration: Duration) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            operation,
+            target: target.into(),
+            succeeded: 0,
+            skipped: 0,
+            failed: 0,
+            bytes: 0,
+            duration_ms: duration.as_millis() as u64,
+            outcome: AuditOutcome::Success,
+            error: None,
+        }
+    }
+
+    /// Record of an operation that failed as a whole
+    pub fn failed(
+        operation: AuditOperation,
+        target: impl Into<String>,
+        duration: Duration,
+        error: impl Into<String>,
+    ) -> Self {
+        Self {
+            outcome: AuditOutcome::Failed,
+            error: Some(error.into()),
+            ..Self::new(operation, target, duration)
+        }
+    }
+
+    /// Record of a finished sync
+    pub fn sync(result: &SyncResult, duration: Duration) -> Self {
+        Self::new(AuditOperation::Sync, result.direction.to_string(), duration)
+            .with_counts(result.imported, result.skipped, result.failed)
+            .with_bytes(result.bytes_written)
+    }
+
+    /// Record of a finished media extraction
+    pub fn extraction(result: &ExtractionResult, output: &Path, duration: Duration) -> Self {
+        Self::new(
+            AuditOperation::Extraction,
+            output.display().to_string(),
+            duration,
+        )
+        .with_counts(
+            result.unique_files,
+            result.duplicates_skipped + result.manifest_skipped,
+            result.errors.len(),
+        )
+        .with_bytes(result.bytes_written)
+    }
+
+    /// Set the item counts, deriving the outcome from them
+    pub fn with_counts(mut self, succeeded: usize, skipped: usize, failed: usize) -> Self {
+        self.succeeded = succeeded;
+        self.skipped = skipped;
+        self.failed = failed;
+        self.outcome = match (succeeded, failed) {
+            (_, 0) => AuditOutcome::Success,
+            (0, _) => AuditOutcome::Failed,
+            _ => AuditOutcome::Partial,
+        };
+        self
+    }
+
+    /// Set the number of bytes written
+    pub fn with_bytes(mut self, bytes: u64) -> Self {
+        self.bytes = bytes;
+        self
+    }
+}
+
+/// Append-only JSON lines file of [`AuditRecord`]s
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Use the audit log stored at `path`
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Where the audit log is stored by default, under the app data directory
+    pub fn default_path() -> Option<PathBuf> {
+        paths::app_data_dir().map(|p| p.join("audit.jsonl"))
+    }
+
+    /// Use the audit log at [`default_path`](Self::default_path)
+    pub fn open_default() -> Option<Self> {
+        Self::default_path().map(Self::open)
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record as one line
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+        }
+        let line = serde_json::to_string(record)
+            .map_err(|e| Error::Other(format!("Failed to encode audit record: {}", e)))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| Error::io_at(e, &self.path))?;
+        writeln!(file, "{}", line).map_err(|e| Error::io_at(e, &self.path))
+    }
+
+    /// The last `count` records, most recent first
+    ///
+    /// Lines that can't be parsed, e.g. from a write cut short, are skipped.
+    pub fn recent(&self, count: usize) -> Result<Vec<AuditRecord>> {
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::io_at(e, &self.path)),
+        };
+        let records: Vec<AuditRecord> = BufReader::new(file)
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+        Ok(records.into_iter().rev().take(count).collect())
+    }
+}
+
+/// Append `record` to the default audit log
+///
+/// Failures are logged rather than returned, so a broken audit log never
+/// fails the operation it describes.
+pub fn record_operation(record: &AuditRecord) {
+    let Some(log) = AuditLog::open_default() else {
+        return;
+    };
+    if let Err(e) = log.append(record) {
+        tracing::warn!("Failed to write audit log: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::SyncDirection;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_log_append_and_recent() {
+        let dir = TempDir::new().unwrap();
+        let log = AuditLog::open(dir.path().join("audit.jsonl"));
+        assert!(log.recent(5).unwrap().is_empty());
+
+        let mut result = SyncResult::new(SyncDirection::StableToLazer);
+        result.imported = 3;
+        result.failed = 1;
+        result.bytes_written = 1024;
+        let sync = AuditRecord::sync(&result, Duration::from_millis(1500));
+        assert_eq!(sync.outcome, AuditOutcome::Partial);
+        log.append(&sync).unwrap();
+
+        let restore = AuditRecord::failed(
+            AuditOperation::Restore,
+            "osu!stable Songs folder",
+            Duration::ZERO,
+            "archive is corrupt",
+        );
+        log.append(&restore).unwrap();
+
+        // A torn line doesn't hide the records around it
+        let mut file = OpenOptions::new().append(true).open(log.path()).unwrap();
+        writeln!(file, "{{\"timestamp\":").unwrap();
+
+        let recent = log.recent(5).unwrap();
+        assert_eq!(recent, vec![restore.clone(), sync]);
+        assert_eq!(log.recent(1).unwrap(), vec![restore]);
+
+        let line = fs::read_to_string(log.path()).unwrap();
+        assert!(line.starts_with("{\"timestamp\":"));
+        assert!(line.contains("\"operation\":\"sync\""));
+        assert!(line.contains("\"duration_ms\":1500"));
+    }
+}
@@ -1,17 +1,22 @@
 //! Archive creation and extraction for backups
 
 use crate::error::{Error, Result};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 use std::time::UNIX_EPOCH;
 use walkdir::WalkDir;
-use zip::write::SimpleFileOptions;
-use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use zip::result::ZipError;
+use zip::write::{FileOptions, SimpleFileOptions};
+use zip::{AesMode, CompressionMethod, ZipArchive, ZipWriter};
 
 use super::{
-    compute_simple_hash, BackupManifest, BackupMetadata, BackupMode, BackupOptions, BackupPhase,
-    BackupProgress, BackupProgressCallback, BackupTarget, CompressionLevel, ManifestEntry,
+    compute_content_hash, compute_simple_hash, BackupFormat, BackupManifest, BackupMetadata,
+    BackupMode, BackupOptions, BackupPhase, BackupPreview, BackupProgress, BackupProgressCallback,
+    BackupTarget, CompressionLevel, ManifestEntry, RenamedFile, BACKUP_INFO_FILENAME,
+    ENCRYPTION_SCHEME_AES256, RENAME_MAP_FILENAME,
 };
 
 /// Result of a backup operation including the generated manifest
@@ -174,58 +179,41 @@ pub fn create_backup_archive(
     Ok(())
 }
 
-/// Create a backup archive with full options support
-pub fn create_backup_archive_with_options(
-    source: &Path,
-    dest: &Path,
-    target: BackupTarget,
-    options: &BackupOptions,
-    previous_manifest: Option<&BackupManifest>,
-    progress: Option<BackupProgressCallback>,
-) -> Result<BackupResult> {
-    // Notify scanning phase
-    if let Some(ref cb) = progress {
-        cb(BackupProgress {
-            phase: BackupPhase::Scanning,
-            files_processed: 0,
-            total_files: None,
-            bytes_written: 0,
-            current_file: None,
-        });
-    }
+/// A file discovered while scanning the backup source
+pub(super) struct SourceFile {
+    /// Absolute path on disk
+    path: PathBuf,
+    /// Path relative to the backup source (forward slashes)
+    relative_path: String,
+    /// Modification time (unix timestamp)
+    modified: u64,
+    /// Size in bytes
+    size: u64,
+    /// Simple hash for change detection
+    hash: String,
+}
 
-    // Check if source is a file or directory
-    let is_file = source.is_file();
+impl SourceFile {
+    fn new(path: &Path, relative_path: String) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            relative_path,
+            modified: modified_secs(path),
+            size: path.metadata().map(|m| m.len()).unwrap_or(0),
+            hash: compute_simple_hash(path).unwrap_or_default(),
+        }
+    }
+}
 
-    // Collect files to backup and compute hashes for incremental comparison
-    let files_to_backup: Vec<(std::path::PathBuf, String, u64, String)> = if is_file {
-        let hash = compute_simple_hash(source).unwrap_or_default();
-        let modified = source
-            .metadata()
-            .ok()
-            .and_then(|m| m.modified().ok())
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+/// Collect all source files with their change-detection hashes
+fn scan_source(source: &Path) -> Result<Vec<SourceFile>> {
+    if source.is_file() {
         let filename = source
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("backup")
             .to_string();
-
-        // Check if file changed for incremental
-        let should_include = match (options.mode, previous_manifest) {
-            (BackupMode::Incremental, Some(manifest)) => {
-                manifest.file_changed(&filename, modified, &hash)
-            }
-            _ => true,
-        };
-
-        if should_include {
-            vec![(source.to_path_buf(), filename, modified, hash)]
-        } else {
-            vec![]
-        }
+        Ok(vec![SourceFile::new(source, filename)])
     } else if source.is_dir() {
         let mut files = Vec::new();
         for entry in WalkDir::new(source) {
@@ -242,56 +230,42 @@ pub fn create_backup_archive_with_options(
                     .to_string_lossy()
                     .replace('\\', "/");
 
-                let hash = compute_simple_hash(path).unwrap_or_default();
-                let modified = path
-                    .metadata()
-                    .ok()
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-
-                // Check if file changed for incremental
-                let should_include = match (options.mode, previous_manifest) {
-                    (BackupMode::Incremental, Some(manifest)) => {
-                        manifest.file_changed(&relative_path, modified, &hash)
-                    }
-                    _ => true,
-                };
-
-                if should_include {
-                    files.push((path.to_path_buf(), relative_path, modified, hash));
-                }
+                files.push(SourceFile::new(path, relative_path));
             }
         }
-        files
+        Ok(files)
     } else {
-        return Err(Error::Other(format!(
+        Err(Error::Other(format!(
             "Source path does not exist: {}",
             source.display()
-        )));
-    };
-
-    let total_files = files_to_backup.len();
-
-    // Create the zip file
-    let file = File::create(dest)?;
-    let mut zip = ZipWriter::new(file);
-
-    // Set compression options based on compression level
-    let compression_method = match options.compression {
-        CompressionLevel::Fast => CompressionMethod::Stored,
-        _ => CompressionMethod::Deflated,
-    };
-
-    let zip_options = SimpleFileOptions::default()
-        .compression_method(compression_method)
-        .compression_level(Some(options.compression.to_zip_level() as i64));
+        )))
+    }
+}
 
-    let mut files_processed = 0usize;
-    let mut bytes_written = 0u64;
-    let mut total_size = 0u64;
+/// What a backup of the scanned source files would archive
+struct BackupPlan<'a> {
+    /// Manifest holding the carried-over and renamed entries
+    manifest: BackupManifest,
+    /// Backup an incremental builds on
+    base_backup: Option<String>,
+    /// Files that need archiving
+    files_to_backup: Vec<&'a SourceFile>,
+    /// Files new, modified or moved since the previous backup; `None`
+    /// without a previous backup to compare against
+    changed_files: Option<usize>,
+}
 
+/// Decide which files need archiving; the rest are carried over or renamed
+///
+/// In incremental mode, unchanged files are carried over from the previous
+/// manifest, and files found at a new path whose content matches a previously
+/// backed up file are recorded as renames.
+fn plan_backup<'a>(
+    source_files: &'a [SourceFile],
+    target: BackupTarget,
+    options: &BackupOptions,
+    previous_manifest: Option<&BackupManifest>,
+) -> BackupPlan<'a> {
     // Create manifest for this backup
     let base_backup = previous_manifest
         .and_then(|m| m.base_backup.clone())
@@ -309,6 +283,492 @@ pub fn create_backup_archive_with_options(
         base_backup.clone(),
     );
 
+    let previous = match options.mode {
+        BackupMode::Incremental => previous_manifest,
+        BackupMode::Full => None,
+    };
+    let current_paths: HashSet<&str> = source_files
+        .iter()
+        .map(|f| f.relative_path.as_str())
+        .collect();
+    let mut files_to_backup = Vec::new();
+    let mut changed_files = 0;
+
+    for file in source_files {
+        let Some(previous) = previous else {
+            files_to_backup.push(file);
+            continue;
+        };
+
+        if !previous.file_changed(&file.relative_path, file.modified, &file.hash) {
+            // Unchanged: keep the previous entry so later incrementals can compare
+            if let Some(entry) = previous.files.get(&file.relative_path) {
+                manifest.add_entry(entry.clone());
+            }
+            continue;
+        }
+        changed_files += 1;
+
+        if !previous.files.contains_key(&file.relative_path) {
+            // New path: check whether the same content was backed up elsewhere
+            let content_hash = compute_content_hash(&file.path).ok();
+            let moved_from = content_hash
+                .as_deref()
+                .and_then(|h| previous.find_by_content_hash(h))
+                .filter(|e| e.hash == file.hash);
+
+            if let Some(old_entry) = moved_from {
+                manifest.renames.push(RenamedFile {
+                    from: old_entry.path.clone(),
+                    to: file.relative_path.clone(),
+                    source_removed: !current_paths.contains(old_entry.path.as_str()),
+                });
+                manifest.add_entry(ManifestEntry {
+                    path: file.relative_path.clone(),
+                    modified: file.modified,
+                    hash: file.hash.clone(),
+                    size: old_entry.size,
+                    content_hash,
+                });
+                continue;
+            }
+        }
+
+        files_to_backup.push(file);
+    }
+
+    BackupPlan {
+        manifest,
+        base_backup,
+        files_to_backup,
+        changed_files: previous.map(|_| changed_files),
+    }
+}
+
+/// Create a backup archive with full options support
+///
+/// In incremental mode, unchanged files are carried over from the previous
+/// manifest, and files found at a new path whose content matches a previously
+/// backed up file are recorded in a rename map instead of being re-archived.
+/// The archive is written in `options.format`.
+pub fn create_backup_archive_with_options(
+    source: &Path,
+    dest: &Path,
+    target: BackupTarget,
+    options: &BackupOptions,
+    previous_manifest: Option<&BackupManifest>,
+    progress: Option<BackupProgressCallback>,
+) -> Result<BackupResult> {
+    let create = || File::create(dest).map_err(|e| Error::io_at(e, dest));
+    match options.format {
+        BackupFormat::Zip => write_backup_archive(
+            source,
+            || ZipArchiveWriter::new(ZipWriter::new(create()?), options, true),
+            target,
+            options,
+            previous_manifest,
+            progress,
+        ),
+        BackupFormat::TarZstd => {
+            write_tar_zstd_backup(source, create, target, options, previous_manifest, progress)
+        }
+    }
+}
+
+/// Stream a backup archive into any writer, such as a pipe or an upload
+///
+/// The writer does not need to be seekable: zip entries are written with
+/// data descriptors and the central directory follows once everything,
+/// including `backup_info.json`, has been written; tar.zst is a stream
+/// anyway. Behaves like [`create_backup_archive_with_options`] otherwise.
+pub fn write_backup_archive_to<W: Write>(
+    source: &Path,
+    writer: W,
+    target: BackupTarget,
+    options: &BackupOptions,
+    previous_manifest: Option<&BackupManifest>,
+    progress: Option<BackupProgressCallback>,
+) -> Result<BackupResult> {
+    match options.format {
+        BackupFormat::Zip => write_backup_archive(
+            source,
+            || ZipArchiveWriter::new(ZipWriter::new_stream(writer), options, false),
+            target,
+            options,
+            previous_manifest,
+            progress,
+        ),
+        BackupFormat::TarZstd => write_tar_zstd_backup(
+            source,
+            || Ok(writer),
+            target,
+            options,
+            previous_manifest,
+            progress,
+        ),
+    }
+}
+
+#[cfg(feature = "tar-zstd")]
+fn write_tar_zstd_backup<W: Write>(
+    source: &Path,
+    open: impl FnOnce() -> Result<W>,
+    target: BackupTarget,
+    options: &BackupOptions,
+    previous_manifest: Option<&BackupManifest>,
+    progress: Option<BackupProgressCallback>,
+) -> Result<BackupResult> {
+    write_backup_archive(
+        source,
+        || super::tar_zstd::TarZstdArchiveWriter::new(open()?, options),
+        target,
+        options,
+        previous_manifest,
+        progress,
+    )
+}
+
+#[cfg(not(feature = "tar-zstd"))]
+fn write_tar_zstd_backup<W: Write>(
+    _source: &Path,
+    _open: impl FnOnce() -> Result<W>,
+    _target: BackupTarget,
+    _options: &BackupOptions,
+    _previous_manifest: Option<&BackupManifest>,
+    _progress: Option<BackupProgressCallback>,
+) -> Result<BackupResult> {
+    Err(Error::Config(
+        "tar.zst backups need osu-sync built with the tar-zstd feature".to_string(),
+    ))
+}
+
+/// Destination of the entries of a backup, in one archive format
+pub(super) trait ArchiveWriter {
+    /// Whether `backup_info.json` and the rename map go before the files, so
+    /// they can be read without decompressing the whole archive
+    const METADATA_FIRST: bool;
+
+    /// Archive a file, returning its size and Blake3 content hash
+    ///
+    /// `store` asks for the file to be stored rather than compressed, for
+    /// formats that decide per entry.
+    fn add_file(&mut self, path: &Path, name: &str, store: bool) -> Result<(u64, String)>;
+
+    /// Archive files in order, passing each one's size and content hash to
+    /// `on_added` once it has been written
+    fn add_files(
+        &mut self,
+        files: &[&SourceFile],
+        options: &BackupOptions,
+        on_added: impl FnMut(&SourceFile, u64, String),
+    ) -> Result<()> {
+        add_files_sequentially(self, files, options, on_added)
+    }
+
+    /// Add the rename map of an incremental backup
+    fn add_rename_map(&mut self, json: &[u8]) -> Result<()>;
+
+    /// Add `backup_info.json`
+    fn add_metadata(&mut self, json: &[u8]) -> Result<()>;
+
+    /// Metadata describing this archive
+    fn metadata(&self, metadata: BackupMetadata) -> BackupMetadata;
+
+    /// Write the end of the archive and flush it
+    fn finish(self) -> Result<()>;
+}
+
+/// Default [`ArchiveWriter::add_files`], archiving one file at a time
+fn add_files_sequentially<A: ArchiveWriter + ?Sized>(
+    archive: &mut A,
+    files: &[&SourceFile],
+    options: &BackupOptions,
+    mut on_added: impl FnMut(&SourceFile, u64, String),
+) -> Result<()> {
+    for file in files {
+        options.check_cancelled()?;
+        let (file_size, content_hash) = archive.add_file(
+            &file.path,
+            &file.relative_path,
+            options.should_store(&file.relative_path),
+        )?;
+        on_added(file, file_size, content_hash);
+    }
+    Ok(())
+}
+
+/// Most source bytes compressed ahead of the entry being written
+const PARALLEL_MAX_BUFFERED_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Larger files are compressed while writing rather than buffered in memory
+const PARALLEL_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// [`ArchiveWriter`] for zip archives
+struct ZipArchiveWriter<'a, W: Write + Seek> {
+    zip: ZipWriter<W>,
+    /// Options for unencrypted metadata entries
+    plain_options: SimpleFileOptions,
+    /// Options for compressed, possibly encrypted, entries
+    entry_options: FileOptions<'a, ()>,
+    /// Options for stored, possibly encrypted, entries
+    stored_entry_options: FileOptions<'a, ()>,
+    encrypted: bool,
+    /// Whether entries may be compressed on worker threads
+    parallel: bool,
+}
+
+impl<'a, W: Write + Seek> ZipArchiveWriter<'a, W> {
+    /// Streamed archives (`parallel` unset) are compressed on the calling
+    /// thread, since their entries are written with data descriptors
+    fn new(zip: ZipWriter<W>, options: &'a BackupOptions, parallel: bool) -> Result<Self> {
+        // Set compression options based on compression level
+        let compression_method = match options.compression {
+            CompressionLevel::Fast => CompressionMethod::Stored,
+            _ => CompressionMethod::Deflated,
+        };
+
+        let zip_options = SimpleFileOptions::default()
+            .compression_method(compression_method)
+            .compression_level(Some(options.compression.to_zip_level() as i64));
+        // Already-compressed media gains nothing from Deflate
+        let stored_options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .compression_level(None);
+
+        // Encrypt file entries when a passphrase is set; backup_info.json stays
+        // readable so restores can detect the encryption scheme
+        let passphrase = options.encryption.as_deref();
+        Ok(Self {
+            zip,
+            plain_options: zip_options,
+            entry_options: encrypted_options(zip_options, passphrase),
+            stored_entry_options: encrypted_options(stored_options, passphrase),
+            encrypted: passphrase.is_some(),
+            parallel,
+        })
+    }
+}
+
+impl<W: Write + Seek> ZipArchiveWriter<'_, W> {
+    /// Body of [`ArchiveWriter::add_files`], spawning compression on `scope`
+    fn add_files_pipelined<'s>(
+        &mut self,
+        scope: &rayon::Scope<'s>,
+        files: &'s [&'s SourceFile],
+        options: &'s BackupOptions,
+        mut on_added: impl FnMut(&SourceFile, u64, String),
+    ) -> Result<()> {
+        let inline = |file: &SourceFile| {
+            options.should_store(&file.relative_path) || file.size > PARALLEL_MAX_FILE_SIZE
+        };
+        let deflate_options = self.plain_options;
+        let (sender, receiver) = mpsc::channel();
+        let mut finished = HashMap::new();
+        // Next file to consider compressing, and source bytes compressing or
+        // compressed but not yet written
+        let mut queued = 0;
+        let mut buffered = 0u64;
+
+        for (i, file) in files.iter().enumerate() {
+            // The file being written is always queued, however large
+            while queued < files.len()
+                && (queued <= i || buffered + files[queued].size <= PARALLEL_MAX_BUFFERED_BYTES)
+            {
+                let ahead = files[queued];
+                if !inline(ahead) {
+                    buffered += ahead.size;
+                    let sender = sender.clone();
+                    let index = queued;
+                    scope.spawn(move |_| {
+                        let entry = options
+                            .check_cancelled()
+                            .and_then(|()| compress_entry(ahead, deflate_options));
+                        // The writer only stops listening after an error
+                        let _ = sender.send((index, entry));
+                    });
+                }
+                queued += 1;
+            }
+
+            options.check_cancelled()?;
+            let (file_size, content_hash) = if inline(file) {
+                self.add_file(
+                    &file.path,
+                    &file.relative_path,
+                    options.should_store(&file.relative_path),
+                )?
+            } else {
+                let entry = loop {
+                    if let Some(entry) = finished.remove(&i) {
+                        break entry;
+                    }
+                    let (index, entry) = receiver
+                        .recv()
+                        .map_err(|_| Error::Other("A compression worker stopped".to_string()))?;
+                    finished.insert(index, entry);
+                };
+                buffered -= file.size;
+                let (buffer, file_size, content_hash) = entry?;
+                let mut entry = ZipArchive::new(Cursor::new(buffer))?;
+                self.zip.raw_copy_file(entry.by_index_raw(0)?)?;
+                (file_size, content_hash)
+            };
+            on_added(file, file_size, content_hash);
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> ArchiveWriter for ZipArchiveWriter<'_, W> {
+    const METADATA_FIRST: bool = false;
+
+    fn add_file(&mut self, path: &Path, name: &str, store: bool) -> Result<(u64, String)> {
+        let options = if store {
+            self.stored_entry_options
+        } else {
+            self.entry_options
+        };
+        add_file_to_zip_hashed(&mut self.zip, path, name, options)
+    }
+
+    /// Deflate files on worker threads, each into an in-memory zip, while
+    /// this thread copies finished entries into the archive in order
+    ///
+    /// Workers stay at most [`PARALLEL_MAX_BUFFERED_BYTES`] of source files
+    /// ahead of the writer. Stored and very large files are written directly
+    /// when their turn comes, so entries and progress follow the input order
+    /// either way. Encrypted archives are compressed on this thread, see
+    /// [`BackupOptions::threads`].
+    fn add_files(
+        &mut self,
+        files: &[&SourceFile],
+        options: &BackupOptions,
+        on_added: impl FnMut(&SourceFile, u64, String),
+    ) -> Result<()> {
+        let threads = options.compression_threads();
+        if !self.parallel || self.encrypted || threads <= 1 {
+            return add_files_sequentially(self, files, options, on_added);
+        }
+        let pool = match options.threads {
+            0 => None,
+            n => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(n)
+                    .build()
+                    .map_err(|e| Error::Other(format!("Failed to create thread pool: {}", e)))?,
+            ),
+        };
+
+        match &pool {
+            Some(pool) => pool
+                .in_place_scope(|scope| self.add_files_pipelined(scope, files, options, on_added)),
+            None => rayon::in_place_scope(|scope| {
+                self.add_files_pipelined(scope, files, options, on_added)
+            }),
+        }
+    }
+
+    fn add_rename_map(&mut self, json: &[u8]) -> Result<()> {
+        self.zip
+            .start_file(RENAME_MAP_FILENAME, self.entry_options)?;
+        self.zip.write_all(json)?;
+        Ok(())
+    }
+
+    fn add_metadata(&mut self, json: &[u8]) -> Result<()> {
+        self.zip
+            .start_file(BACKUP_INFO_FILENAME, self.plain_options)?;
+        self.zip.write_all(json)?;
+        Ok(())
+    }
+
+    fn metadata(&self, metadata: BackupMetadata) -> BackupMetadata {
+        if self.encrypted {
+            metadata.with_encryption(ENCRYPTION_SCHEME_AES256)
+        } else {
+            metadata
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        // Flush buffered writers such as pipes
+        self.zip.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Shared implementation of the backup writers
+///
+/// `open` is only called once the source has been scanned, so a missing
+/// source never leaves an empty archive behind.
+fn write_backup_archive<A: ArchiveWriter>(
+    source: &Path,
+    open: impl FnOnce() -> Result<A>,
+    target: BackupTarget,
+    options: &BackupOptions,
+    previous_manifest: Option<&BackupManifest>,
+    progress: Option<BackupProgressCallback>,
+) -> Result<BackupResult> {
+    options.format.check_supported()?;
+    if options.encryption.is_some() && options.format != BackupFormat::Zip {
+        return Err(Error::Config(format!(
+            "{} backups cannot be encrypted",
+            options.format
+        )));
+    }
+
+    // Notify scanning phase
+    if let Some(ref cb) = progress {
+        cb(BackupProgress {
+            phase: BackupPhase::Scanning,
+            files_processed: 0,
+            total_files: None,
+            bytes_written: 0,
+            current_file: None,
+        });
+    }
+
+    let source_files = scan_source(source)?;
+    let BackupPlan {
+        mut manifest,
+        base_backup,
+        files_to_backup,
+        ..
+    } = plan_backup(&source_files, target, options, previous_manifest);
+
+    let total_files = files_to_backup.len();
+
+    // Create the archive
+    let mut archive = open()?;
+    let metadata_json = |archive: &A, file_count: usize, total_size: u64| {
+        archive
+            .metadata(
+                BackupMetadata::new(
+                    target,
+                    options.mode,
+                    options.compression,
+                    file_count,
+                    total_size,
+                    base_backup.clone(),
+                )
+                .with_format(options.format),
+            )
+            .to_json_bytes()
+    };
+
+    // Up front, the metadata describes the planned files
+    if A::METADATA_FIRST {
+        if let Some(json) = rename_map_json(&manifest.renames)? {
+            archive.add_rename_map(&json)?;
+        }
+        let planned_size = files_to_backup.iter().map(|f| f.size).sum();
+        archive.add_metadata(&metadata_json(&archive, total_files, planned_size)?)?;
+    }
+
+    let mut files_processed = 0usize;
+    let mut bytes_written = 0u64;
+    let mut total_size = 0u64;
+
     // Notify archiving phase
     if let Some(ref cb) = progress {
         cb(BackupProgress {
@@ -321,44 +781,42 @@ pub fn create_backup_archive_with_options(
     }
 
     // Add files to archive
-    for (path, relative_path, modified, hash) in &files_to_backup {
-        let file_size = add_file_to_zip(&mut zip, path, relative_path, zip_options)?;
-        files_processed += 1;
-        bytes_written += file_size;
-        total_size += file_size;
-
-        // Add to manifest
-        manifest.add_entry(ManifestEntry {
-            path: relative_path.clone(),
-            modified: *modified,
-            hash: hash.clone(),
-            size: file_size,
-        });
-
-        if let Some(ref cb) = progress {
-            cb(BackupProgress {
-                phase: BackupPhase::Archiving,
-                files_processed,
-                total_files: Some(total_files),
-                bytes_written,
-                current_file: Some(relative_path.clone()),
+    archive.add_files(
+        &files_to_backup,
+        options,
+        |file, file_size, content_hash| {
+            files_processed += 1;
+            bytes_written += file_size;
+            total_size += file_size;
+
+            // Add to manifest
+            manifest.add_entry(ManifestEntry {
+                path: file.relative_path.clone(),
+                modified: file.modified,
+                hash: file.hash.clone(),
+                size: file_size,
+                content_hash: Some(content_hash),
             });
-        }
-    }
 
-    // Add backup_info.json metadata file
-    let metadata = BackupMetadata::new(
-        target,
-        options.mode,
-        options.compression,
-        files_processed,
-        total_size,
-        base_backup,
-    );
+            if let Some(ref cb) = progress {
+                cb(BackupProgress {
+                    phase: BackupPhase::Archiving,
+                    files_processed,
+                    total_files: Some(total_files),
+                    bytes_written,
+                    current_file: Some(file.relative_path.clone()),
+                });
+            }
+        },
+    )?;
 
-    let metadata_json = metadata.to_json_bytes()?;
-    zip.start_file("backup_info.json", zip_options)?;
-    zip.write_all(&metadata_json)?;
+    if !A::METADATA_FIRST {
+        // Store the rename map so restore can reconstruct the new layout
+        if let Some(json) = rename_map_json(&manifest.renames)? {
+            archive.add_rename_map(&json)?;
+        }
+        archive.add_metadata(&metadata_json(&archive, files_processed, total_size)?)?;
+    }
 
     // Notify finalizing phase
     if let Some(ref cb) = progress {
@@ -371,8 +829,7 @@ pub fn create_backup_archive_with_options(
         });
     }
 
-    // Finish the archive
-    zip.finish()?;
+    archive.finish()?;
 
     // Notify complete
     if let Some(ref cb) = progress {
@@ -392,6 +849,155 @@ pub fn create_backup_archive_with_options(
     })
 }
 
+/// Serialize the rename map of an incremental backup, if it has renames
+fn rename_map_json(renames: &[RenamedFile]) -> Result<Option<Vec<u8>>> {
+    if renames.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_vec_pretty(renames)
+        .map(Some)
+        .map_err(|e| Error::Other(format!("Failed to serialize rename map: {}", e)))
+}
+
+/// Most files compressed to estimate the compression ratio of a backup
+const PREVIEW_SAMPLE_FILES: usize = 32;
+
+/// Bytes read from the start of each sampled file
+const PREVIEW_SAMPLE_BYTES: u64 = 256 * 1024;
+
+/// Estimate what [`create_backup_archive_with_options`] would write, without
+/// writing anything
+///
+/// Files are selected exactly as for a real backup. The compressed size is
+/// extrapolated from compressing the start of up to
+/// [`PREVIEW_SAMPLE_FILES`] files that would be deflated; stored media counts
+/// at full size. tar.zst backups compress every file, so their sample is
+/// drawn from all files and compressed with zstd.
+pub fn preview_backup_archive(
+    source: &Path,
+    target: BackupTarget,
+    options: &BackupOptions,
+    previous_manifest: Option<&BackupManifest>,
+) -> Result<BackupPreview> {
+    let source_files = scan_source(source)?;
+    let plan = plan_backup(&source_files, target, options, previous_manifest);
+
+    Ok(BackupPreview {
+        file_count: plan.files_to_backup.len(),
+        total_size: plan.files_to_backup.iter().map(|f| f.size).sum(),
+        estimated_compressed_size: estimate_compressed_size(&plan.files_to_backup, options)?,
+        changed_files: plan.changed_files,
+    })
+}
+
+/// Estimate the archived size of `files` by compressing a sample of them
+fn estimate_compressed_size(files: &[&SourceFile], options: &BackupOptions) -> Result<u64> {
+    #[cfg(feature = "tar-zstd")]
+    if options.format == BackupFormat::TarZstd {
+        return estimate_zstd_size(files, options);
+    }
+
+    let (stored, deflated): (Vec<&SourceFile>, Vec<&SourceFile>) =
+        files.iter().copied().partition(|f| {
+            options.compression == CompressionLevel::Fast || options.should_store(&f.relative_path)
+        });
+    let stored_size: u64 = stored.iter().map(|f| f.size).sum();
+    let deflated_size: u64 = deflated.iter().map(|f| f.size).sum();
+    if deflated_size == 0 {
+        return Ok(stored_size);
+    }
+
+    let zip_options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .compression_level(Some(options.compression.to_zip_level() as i64));
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let step = deflated.len().div_ceil(PREVIEW_SAMPLE_FILES);
+    for (idx, file) in deflated.iter().step_by(step).enumerate() {
+        options.check_cancelled()?;
+        let sample = read_preview_sample(file)?;
+        zip.start_file(idx.to_string(), zip_options)?;
+        zip.write_all(&sample)?;
+    }
+
+    let mut samples = ZipArchive::new(zip.finish()?)?;
+    let (mut sampled, mut compressed) = (0u64, 0u64);
+    for idx in 0..samples.len() {
+        let entry = samples.by_index(idx)?;
+        sampled += entry.size();
+        compressed += entry.compressed_size();
+    }
+    if sampled == 0 {
+        return Ok(stored_size + deflated_size);
+    }
+
+    let ratio = compressed as f64 / sampled as f64;
+    Ok(stored_size + (deflated_size as f64 * ratio) as u64)
+}
+
+/// Estimate the size of `files` in a tar.zst backup by compressing a sample
+/// of them as one zstd stream, as the backup itself does
+#[cfg(feature = "tar-zstd")]
+fn estimate_zstd_size(files: &[&SourceFile], options: &BackupOptions) -> Result<u64> {
+    let total_size: u64 = files.iter().map(|f| f.size).sum();
+    if total_size == 0 {
+        return Ok(0);
+    }
+
+    let mut encoder =
+        zstd::stream::write::Encoder::new(Vec::new(), options.compression.to_zstd_level())?;
+    let mut sampled = 0u64;
+    let step = files.len().div_ceil(PREVIEW_SAMPLE_FILES);
+    for file in files.iter().step_by(step) {
+        options.check_cancelled()?;
+        let sample = read_preview_sample(file)?;
+        sampled += sample.len() as u64;
+        encoder.write_all(&sample)?;
+    }
+    let compressed = encoder.finish()?.len() as u64;
+    if sampled == 0 {
+        return Ok(total_size);
+    }
+
+    let ratio = compressed as f64 / sampled as f64;
+    Ok((total_size as f64 * ratio) as u64)
+}
+
+/// Read the start of a file for compression sampling
+fn read_preview_sample(file: &SourceFile) -> Result<Vec<u8>> {
+    let mut sample = Vec::new();
+    File::open(&file.path)
+        .and_then(|f| f.take(PREVIEW_SAMPLE_BYTES).read_to_end(&mut sample))
+        .map_err(|e| Error::io_at(e, &file.path))?;
+    Ok(sample)
+}
+
+/// Compress a file into a single-entry in-memory zip, returning the zip along
+/// with the file's size and Blake3 content hash
+fn compress_entry(file: &SourceFile, options: SimpleFileOptions) -> Result<(Vec<u8>, u64, String)> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let (file_size, content_hash) =
+        add_file_to_zip_hashed(&mut zip, &file.path, &file.relative_path, options)?;
+    Ok((zip.finish()?.into_inner(), file_size, content_hash))
+}
+
+/// Apply AES-256 encryption to entry options when a passphrase is set
+fn encrypted_options(base: SimpleFileOptions, passphrase: Option<&str>) -> FileOptions<'_, ()> {
+    match passphrase {
+        Some(passphrase) => base.with_aes_encryption(AesMode::Aes256, passphrase),
+        None => base,
+    }
+}
+
+/// Get a file's modification time as a unix timestamp (0 if unavailable)
+pub(super) fn modified_secs(path: &Path) -> u64 {
+    path.metadata()
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Add a file to a zip archive
 fn add_file_to_zip<W: Write + std::io::Seek>(
     zip: &mut ZipWriter<W>,
@@ -399,23 +1005,149 @@ fn add_file_to_zip<W: Write + std::io::Seek>(
     archive_path: &str,
     options: SimpleFileOptions,
 ) -> Result<u64> {
+    add_file_to_zip_hashed(zip, file_path, archive_path, options).map(|(size, _)| size)
+}
+
+/// Add a file to a zip archive, returning its size and Blake3 content hash
+fn add_file_to_zip_hashed<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    file_path: &Path,
+    archive_path: &str,
+    options: FileOptions<'_, ()>,
+) -> Result<(u64, String)> {
     let mut file = File::open(file_path)?;
     let metadata = file.metadata()?;
     let file_size = metadata.len();
 
     zip.start_file(archive_path, options)?;
 
-    // Read and write in chunks
+    // Read and write in chunks, hashing as we go
+    let mut hasher = blake3::Hasher::new();
     let mut buffer = vec![0u8; 64 * 1024]; // 64KB buffer
     loop {
         let bytes_read = file.read(&mut buffer)?;
         if bytes_read == 0 {
             break;
         }
+        hasher.update(&buffer[..bytes_read]);
         zip.write_all(&buffer[..bytes_read])?;
     }
 
-    Ok(file_size)
+    Ok((file_size, hasher.finalize().to_hex().to_string()))
+}
+
+/// Convert a zip error into a passphrase error where applicable
+pub(crate) fn map_passphrase_error(err: ZipError) -> Error {
+    match err {
+        ZipError::InvalidPassword => Error::WrongPassphrase,
+        ZipError::UnsupportedArchive(msg) if msg == ZipError::PASSWORD_REQUIRED => {
+            Error::PassphraseRequired
+        }
+        other => Error::Zip(other),
+    }
+}
+
+/// Read the rename map from a backup archive, if it has one
+pub fn read_rename_map<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    passphrase: Option<&str>,
+) -> Result<Vec<RenamedFile>> {
+    let entry = match passphrase {
+        Some(p) => archive.by_name_decrypt(RENAME_MAP_FILENAME, p.as_bytes()),
+        None => archive.by_name(RENAME_MAP_FILENAME),
+    };
+    let mut file = match entry {
+        Ok(f) => f,
+        Err(ZipError::FileNotFound) => return Ok(Vec::new()),
+        Err(e) => return Err(map_passphrase_error(e)),
+    };
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    serde_json::from_str(&content).map_err(|e| Error::Other(format!("Invalid rename map: {}", e)))
+}
+
+/// Apply a rename map to a restore destination
+///
+/// Each renamed file is copied from its old path to its new path. Old paths
+/// that no longer exist in the backed up layout are removed afterwards, along
+/// with any directories left empty. Returns the number of files placed.
+pub fn apply_rename_map(
+    dest: &Path,
+    renames: &[RenamedFile],
+    should_apply: impl Fn(&RenamedFile) -> bool,
+) -> Result<usize> {
+    let mut applied = 0usize;
+    let mut to_remove = Vec::new();
+
+    for rename in renames.iter().filter(|r| should_apply(r)) {
+        // Never follow paths that could escape the destination
+        if !is_safe_relative_path(&rename.from) || !is_safe_relative_path(&rename.to) {
+            continue;
+        }
+
+        let from = dest.join(&rename.from);
+        let to = dest.join(&rename.to);
+
+        if !from.is_file() {
+            continue;
+        }
+
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&from, &to)?;
+        applied += 1;
+
+        if rename.source_removed {
+            to_remove.push(from);
+        }
+    }
+
+    for path in to_remove {
+        if path.is_file() {
+            std::fs::remove_file(&path)?;
+        }
+
+        // Clean up directories emptied by the move
+        let mut dir = path.parent();
+        while let Some(d) = dir {
+            if d == dest || std::fs::remove_dir(d).is_err() {
+                break;
+            }
+            dir = d.parent();
+        }
+    }
+
+    Ok(applied)
+}
+
+/// Check that an archive-relative path stays within its destination
+pub(super) fn is_safe_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+/// A backup file opened for reading, by format
+pub(super) enum OpenedBackup {
+    Zip(File),
+    #[cfg(feature = "tar-zstd")]
+    TarZstd,
+}
+
+/// Open a backup file, detecting its format
+///
+/// tar.zst backups fail with [`Error::Config`] when this build cannot read
+/// them.
+pub(super) fn open_backup(path: &Path) -> Result<OpenedBackup> {
+    if BackupFormat::detect(path) == Some(BackupFormat::TarZstd) {
+        BackupFormat::TarZstd.check_supported()?;
+        #[cfg(feature = "tar-zstd")]
+        return Ok(OpenedBackup::TarZstd);
+    }
+    Ok(OpenedBackup::Zip(File::open(path)?))
 }
 
 /// Extract a backup archive to a destination directory
@@ -424,7 +1156,11 @@ pub fn extract_backup_archive(
     dest: &Path,
     progress: Option<BackupProgressCallback>,
 ) -> Result<()> {
-    let file = File::open(archive_path)?;
+    let file = match open_backup(archive_path)? {
+        OpenedBackup::Zip(file) => file,
+        #[cfg(feature = "tar-zstd")]
+        OpenedBackup::TarZstd => return super::tar_zstd::extract(archive_path, dest, progress),
+    };
     let mut archive = ZipArchive::new(file)?;
 
     let total_files = archive.len();
@@ -447,13 +1183,16 @@ pub fn extract_backup_archive(
     let mut bytes_written = 0u64;
 
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
+        let mut file = archive.by_index(i).map_err(map_passphrase_error)?;
         let outpath = match file.enclosed_name() {
             Some(path) => dest.join(path),
             None => continue,
         };
 
         let filename = file.name().to_string();
+        if filename == RENAME_MAP_FILENAME {
+            continue;
+        }
 
         // Notify progress
         if let Some(ref cb) = progress {
@@ -485,6 +1224,10 @@ pub fn extract_backup_archive(
         files_processed += 1;
     }
 
+    // Reconstruct renamed files recorded by incremental backups
+    let renames = read_rename_map(&mut archive, None)?;
+    apply_rename_map(dest, &renames, |_| true)?;
+
     // Notify complete
     if let Some(ref cb) = progress {
         cb(BackupProgress {
@@ -530,4 +1273,146 @@ mod tests {
         let content = std::fs::read_to_string(restored_file).unwrap();
         assert_eq!(content, "Hello, World!");
     }
+
+    #[test]
+    fn test_media_files_are_stored() {
+        let temp_dir = tempdir().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        let backup_file = temp_dir.path().join("backup.zip");
+        std::fs::create_dir_all(&source_dir).unwrap();
+        std::fs::write(source_dir.join("audio.mp3"), vec![7u8; 4096]).unwrap();
+        std::fs::write(source_dir.join("map.osu"), vec![b'a'; 4096]).unwrap();
+
+        create_backup_archive_with_options(
+            &source_dir,
+            &backup_file,
+            BackupTarget::StableSongs,
+            &BackupOptions::new(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&backup_file).unwrap()).unwrap();
+        assert_eq!(
+            archive.by_name("audio.mp3").unwrap().compression(),
+            CompressionMethod::Stored
+        );
+        assert_eq!(
+            archive.by_name("map.osu").unwrap().compression(),
+            CompressionMethod::Deflated
+        );
+    }
+
+    #[test]
+    fn test_preview_estimates_compressed_size() {
+        let temp_dir = tempdir().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        std::fs::create_dir_all(&source_dir).unwrap();
+        std::fs::write(source_dir.join("audio.mp3"), vec![7u8; 4096]).unwrap();
+        std::fs::write(source_dir.join("map.osu"), vec![b'a'; 64 * 1024]).unwrap();
+
+        let options = BackupOptions::new();
+        let preview =
+            preview_backup_archive(&source_dir, BackupTarget::StableSongs, &options, None).unwrap();
+        assert_eq!(preview.file_count, 2);
+        assert_eq!(preview.total_size, 4096 + 64 * 1024);
+        assert_eq!(preview.changed_files, None);
+        // The mp3 is stored as-is; the repetitive .osu deflates to almost nothing
+        assert!(preview.estimated_compressed_size >= 4096);
+        assert!(preview.estimated_compressed_size < 8192);
+
+        // An incremental after a backup of the same files only picks up changes
+        let backup_file = temp_dir.path().join("backup.zip");
+        let result = create_backup_archive_with_options(
+            &source_dir,
+            &backup_file,
+            BackupTarget::StableSongs,
+            &options,
+            None,
+            None,
+        )
+        .unwrap();
+        std::fs::write(source_dir.join("new.osu"), b"new").unwrap();
+
+        let incremental = BackupOptions::new().with_mode(BackupMode::Incremental);
+        let preview = preview_backup_archive(
+            &source_dir,
+            BackupTarget::StableSongs,
+            &incremental,
+            Some(&result.manifest),
+        )
+        .unwrap();
+        assert_eq!(preview.file_count, 1);
+        assert_eq!(preview.total_size, 3);
+        assert_eq!(preview.changed_files, Some(1));
+    }
+
+    #[cfg(feature = "tar-zstd")]
+    #[test]
+    fn test_preview_estimates_tar_zstd_size() {
+        let temp_dir = tempdir().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        std::fs::create_dir_all(&source_dir).unwrap();
+        std::fs::write(source_dir.join("audio.mp3"), vec![7u8; 64 * 1024]).unwrap();
+        std::fs::write(source_dir.join("map.osu"), vec![b'a'; 64 * 1024]).unwrap();
+
+        // zstd compresses media too, so the repetitive mp3 no longer counts
+        // at full size
+        let options = BackupOptions::new().with_format(BackupFormat::TarZstd);
+        let preview =
+            preview_backup_archive(&source_dir, BackupTarget::StableSongs, &options, None).unwrap();
+        assert_eq!(preview.total_size, 128 * 1024);
+        assert!(preview.estimated_compressed_size < 8192);
+    }
+
+    #[test]
+    fn test_parallel_backup_keeps_order_and_progress() {
+        let temp_dir = tempdir().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        std::fs::create_dir_all(&source_dir).unwrap();
+        for i in 0..40 {
+            let content = format!("[Metadata]\nTitle:{}\n", i).repeat(100 + i);
+            std::fs::write(source_dir.join(format!("{:02}.osu", i)), content).unwrap();
+        }
+        std::fs::write(source_dir.join("20.mp3"), vec![7u8; 4096]).unwrap();
+
+        let updates = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = updates.clone();
+        let backup_file = temp_dir.path().join("backup.zip");
+        let result = create_backup_archive_with_options(
+            &source_dir,
+            &backup_file,
+            BackupTarget::StableSongs,
+            &BackupOptions::new().with_threads(4),
+            None,
+            Some(Box::new(move |p| sink.lock().unwrap().push(p))),
+        )
+        .unwrap();
+        assert_eq!(result.files_included, 41);
+
+        let updates = updates.lock().unwrap();
+        assert!(updates.windows(2).all(|w| {
+            w[0].files_processed <= w[1].files_processed && w[0].bytes_written <= w[1].bytes_written
+        }));
+
+        // Entries follow the scan order and carry the right content
+        let order: Vec<_> = updates
+            .iter()
+            .filter_map(|p| p.current_file.clone())
+            .collect();
+        let mut archive = ZipArchive::new(File::open(&backup_file).unwrap()).unwrap();
+        let names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        assert!(names
+            .iter()
+            .filter(|n| *n != "backup_info.json")
+            .eq(order.iter()));
+        let mut content = String::new();
+        archive
+            .by_name("07.osu")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "[Metadata]\nTitle:7\n".repeat(107));
+    }
 }
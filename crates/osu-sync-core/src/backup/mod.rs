@@ -8,14 +8,19 @@
 
 mod archive;
 mod options;
+#[cfg(feature = "tar-zstd")]
+mod tar_zstd;
 
 pub use archive::*;
 pub use options::*;
 
+use crate::cancel::CancellationToken;
+use crate::config::paths;
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fs::File;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use zip::ZipArchive;
@@ -281,6 +286,27 @@ impl BackupVerificationResult {
         self.issues.push(issue);
     }
 
+    /// Flag archive entry names that are unsafe or empty
+    fn check_entry_name(&mut self, name: &str) {
+        // Check for path traversal attacks
+        if name.contains("..") {
+            self.add_issue(VerificationIssue {
+                severity: IssueSeverity::Error,
+                message: "Path traversal detected".to_string(),
+                file_path: Some(name.to_string()),
+            });
+        }
+
+        // Check for empty file names
+        if name.is_empty() {
+            self.add_issue(VerificationIssue {
+                severity: IssueSeverity::Warning,
+                message: "Empty file name detected".to_string(),
+                file_path: None,
+            });
+        }
+    }
+
     /// Check if the backup is restorable
     pub fn is_restorable(&self) -> bool {
         self.can_open && self.status != VerificationStatus::Invalid
@@ -333,6 +359,10 @@ pub enum RestoreMode {
     Skip,
     /// Rename existing files with a backup suffix
     Rename,
+    /// Continue an interrupted restore, skipping entries that a previous run
+    /// completed and that still match the archive; everything else is
+    /// overwritten
+    Resume,
 }
 
 impl RestoreMode {
@@ -341,7 +371,8 @@ impl RestoreMode {
         match self {
             RestoreMode::Overwrite => RestoreMode::Skip,
             RestoreMode::Skip => RestoreMode::Rename,
-            RestoreMode::Rename => RestoreMode::Overwrite,
+            RestoreMode::Rename => RestoreMode::Resume,
+            RestoreMode::Resume => RestoreMode::Overwrite,
         }
     }
 }
@@ -352,6 +383,7 @@ impl fmt::Display for RestoreMode {
             RestoreMode::Overwrite => write!(f, "Overwrite existing"),
             RestoreMode::Skip => write!(f, "Skip existing"),
             RestoreMode::Rename => write!(f, "Rename existing"),
+            RestoreMode::Resume => write!(f, "Resume interrupted restore"),
         }
     }
 }
@@ -365,6 +397,10 @@ pub struct RestoreOptions {
     pub restore_mode: RestoreMode,
     /// Whether to verify files after restore
     pub verify_after_restore: bool,
+    /// Whether to re-extract a file once if it fails verification
+    pub reextract_on_mismatch: bool,
+    /// Passphrase for encrypted backups
+    pub passphrase: Option<String>,
 }
 
 impl RestoreOptions {
@@ -393,6 +429,18 @@ impl RestoreOptions {
         self
     }
 
+    /// Re-extract files that fail verification once before reporting them
+    pub fn with_reextract_on_mismatch(mut self) -> Self {
+        self.reextract_on_mismatch = true;
+        self
+    }
+
+    /// Set the passphrase used to decrypt an encrypted backup
+    pub fn with_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+
     /// Check if a file should be restored based on these options
     pub fn should_restore(&self, file_path: &str) -> bool {
         match &self.files_to_restore {
@@ -404,6 +452,22 @@ impl RestoreOptions {
     }
 }
 
+/// Result of a restore operation
+#[derive(Debug, Clone, Default)]
+pub struct RestoreResult {
+    /// Number of files restored
+    pub files_restored: usize,
+    /// Files that failed post-restore verification
+    pub verification_issues: Vec<VerificationIssue>,
+}
+
+impl RestoreResult {
+    /// Check if every verified file matched its checksum
+    pub fn is_verified(&self) -> bool {
+        self.verification_issues.is_empty()
+    }
+}
+
 /// Preview of what will happen during restore
 #[derive(Debug, Clone)]
 pub struct RestorePreview {
@@ -419,6 +483,12 @@ pub struct RestorePreview {
     pub skipped: Vec<String>,
     /// Files that would be renamed
     pub renames: Vec<(String, String)>,
+    /// Additional disk space the restore needs on the destination volume
+    pub required_space: u64,
+    /// Free space on the destination volume (`u64::MAX` if unknown)
+    pub free_space: u64,
+    /// Whether the destination has room for the restore
+    pub has_enough_space: bool,
 }
 
 impl RestorePreview {
@@ -431,6 +501,9 @@ impl RestorePreview {
             new_files: Vec::new(),
             skipped: Vec::new(),
             renames: Vec::new(),
+            required_space: 0,
+            free_space: u64::MAX,
+            has_enough_space: true,
         }
     }
 
@@ -443,6 +516,20 @@ impl RestorePreview {
     pub fn has_overwrites(&self) -> bool {
         !self.overwrites.is_empty()
     }
+
+    /// Get human-readable required space
+    pub fn required_space_display(&self) -> String {
+        format_size(self.required_space)
+    }
+
+    /// Get human-readable free space
+    pub fn free_space_display(&self) -> String {
+        if self.free_space == u64::MAX {
+            "Unknown".to_string()
+        } else {
+            format_size(self.free_space)
+        }
+    }
 }
 
 impl Default for RestorePreview {
@@ -451,16 +538,69 @@ impl Default for RestorePreview {
     }
 }
 
+/// Estimate of what a backup would write, from [`BackupManager::preview_backup`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackupPreview {
+    /// Files that would be archived
+    pub file_count: usize,
+    /// Uncompressed size of those files
+    pub total_size: u64,
+    /// Estimated size of the archive, extrapolated from compressing a sample
+    pub estimated_compressed_size: u64,
+    /// Files new, modified or moved since the parent backup; `None` for full
+    /// backups and incrementals without a parent
+    pub changed_files: Option<usize>,
+}
+
+impl BackupPreview {
+    /// Get human-readable uncompressed size
+    pub fn size_display(&self) -> String {
+        format_size(self.total_size)
+    }
+
+    /// Get human-readable estimated archive size
+    pub fn compressed_size_display(&self) -> String {
+        format_size(self.estimated_compressed_size)
+    }
+}
+
 /// Manages backup operations
+#[derive(Clone)]
 pub struct BackupManager {
     /// Directory to store backups
     backup_dir: PathBuf,
+    /// Optional cancellation token for aborting backups and restores
+    cancellation: Option<CancellationToken>,
+    /// Free space on the volume holding a path, checked by restore previews
+    free_space: fn(&Path) -> Option<u64>,
 }
 
 impl BackupManager {
     /// Create a new backup manager
     pub fn new(backup_dir: PathBuf) -> Self {
-        Self { backup_dir }
+        Self {
+            backup_dir,
+            cancellation: None,
+            free_space: crate::utils::available_space,
+        }
+    }
+
+    /// Set a cancellation token for aborting long operations
+    ///
+    /// Checked before each file is archived and before each entry restored
+    /// by [`Self::restore_backup_with_options`]. Used for backups whose
+    /// [`BackupOptions`] carry no token of their own.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Fill in the manager's cancellation token if the options have none
+    fn with_manager_cancellation(&self, mut options: BackupOptions) -> BackupOptions {
+        if options.cancellation.is_none() {
+            options.cancellation = self.cancellation.clone();
+        }
+        options
     }
 
     /// Get the backup directory
@@ -500,6 +640,7 @@ impl BackupManager {
         progress: Option<BackupProgressCallback>,
     ) -> Result<PathBuf> {
         self.ensure_backup_dir()?;
+        let options = self.with_manager_cancellation(options);
 
         // Generate backup filename with timestamp
         let timestamp = chrono_timestamp();
@@ -508,7 +649,13 @@ impl BackupManager {
         } else {
             ""
         };
-        let filename = format!("{}-{}{}.zip", target.file_prefix(), timestamp, mode_suffix);
+        let filename = format!(
+            "{}-{}{}.{}",
+            target.file_prefix(),
+            timestamp,
+            mode_suffix,
+            options.format.extension()
+        );
         let backup_path = self.backup_dir.join(&filename);
 
         // For incremental backups, try to find the previous manifest
@@ -519,14 +666,21 @@ impl BackupManager {
         };
 
         // Create the archive with options
-        let result = create_backup_archive_with_options(
+        let result = match create_backup_archive_with_options(
             source_path,
             &backup_path,
             target,
             &options,
             previous_manifest.as_ref().map(|(_, m)| m),
             progress,
-        )?;
+        ) {
+            Ok(result) => result,
+            Err(e) => {
+                // Don't leave a truncated archive among the backups
+                let _ = std::fs::remove_file(&backup_path);
+                return Err(e);
+            }
+        };
 
         // Save the new manifest for future incremental backups
         let manifest_filename = BackupManifest::manifest_filename(&filename);
@@ -536,6 +690,81 @@ impl BackupManager {
         Ok(backup_path)
     }
 
+    /// Stream a backup into a writer instead of a file in the backup directory
+    ///
+    /// Useful for piping straight into `rclone` or an upload without a
+    /// temporary file. See [`Self::create_backup_to_writer_with_options`].
+    pub fn create_backup_to_writer<W: Write>(
+        &self,
+        target: BackupTarget,
+        source_path: &Path,
+        writer: W,
+        progress: Option<BackupProgressCallback>,
+    ) -> Result<BackupResult> {
+        self.create_backup_to_writer_with_options(
+            target,
+            source_path,
+            writer,
+            BackupOptions::default(),
+            progress,
+        )
+    }
+
+    /// Stream a backup with full options into a writer
+    ///
+    /// Incremental backups compare against the latest manifest in the backup
+    /// directory. No manifest is saved, since the archive does not live here;
+    /// the returned [`BackupResult`] carries it for callers that want to keep it.
+    pub fn create_backup_to_writer_with_options<W: Write>(
+        &self,
+        target: BackupTarget,
+        source_path: &Path,
+        writer: W,
+        options: BackupOptions,
+        progress: Option<BackupProgressCallback>,
+    ) -> Result<BackupResult> {
+        let options = self.with_manager_cancellation(options);
+        let previous_manifest = if options.mode == BackupMode::Incremental {
+            self.find_latest_manifest(target)
+        } else {
+            None
+        };
+
+        write_backup_archive_to(
+            source_path,
+            writer,
+            target,
+            &options,
+            previous_manifest.as_ref().map(|(_, m)| m),
+            progress,
+        )
+    }
+
+    /// Estimate the size of a backup without writing it
+    ///
+    /// Incremental backups compare against the latest manifest in the backup
+    /// directory, like [`Self::create_backup_with_options`] does.
+    pub fn preview_backup(
+        &self,
+        target: BackupTarget,
+        source_path: &Path,
+        options: BackupOptions,
+    ) -> Result<BackupPreview> {
+        let options = self.with_manager_cancellation(options);
+        let previous_manifest = if options.mode == BackupMode::Incremental {
+            self.find_latest_manifest(target)
+        } else {
+            None
+        };
+
+        preview_backup_archive(
+            source_path,
+            target,
+            &options,
+            previous_manifest.as_ref().map(|(_, m)| m),
+        )
+    }
+
     /// Find the latest manifest for a given target
     fn find_latest_manifest(&self, target: BackupTarget) -> Option<(PathBuf, BackupManifest)> {
         if !self.backup_dir.exists() {
@@ -583,7 +812,7 @@ impl BackupManager {
             let entry = entry?;
             let path = entry.path();
 
-            if path.extension().map(|e| e == "zip").unwrap_or(false) {
+            if BackupFormat::from_path(&path).is_some() {
                 if let Some(info) = self.parse_backup_info(&path) {
                     backups.push(info);
                 }
@@ -640,17 +869,42 @@ impl BackupManager {
 
     /// Read backup_info.json from inside a backup archive
     fn read_backup_metadata(&self, path: &Path) -> Option<BackupMetadata> {
-        let file = File::open(path).ok()?;
+        let file = match open_backup(path).ok()? {
+            OpenedBackup::Zip(file) => file,
+            #[cfg(feature = "tar-zstd")]
+            OpenedBackup::TarZstd => return tar_zstd::read_metadata(path),
+        };
         let mut archive = ZipArchive::new(file).ok()?;
 
         // Try to find backup_info.json
-        let mut info_file = archive.by_name("backup_info.json").ok()?;
+        let mut info_file = archive.by_name(BACKUP_INFO_FILENAME).ok()?;
         let mut content = String::new();
         std::io::Read::read_to_string(&mut info_file, &mut content).ok()?;
 
         serde_json::from_str(&content).ok()
     }
 
+    /// Check if a backup's file entries are encrypted
+    pub fn is_backup_encrypted(&self, backup_path: &Path) -> bool {
+        if let Some(metadata) = self.read_backup_metadata(backup_path) {
+            return metadata.is_encrypted();
+        }
+
+        // Fall back to inspecting the entries for backups without metadata
+        let Ok(file) = File::open(backup_path) else {
+            return false;
+        };
+        let Ok(mut archive) = ZipArchive::new(file) else {
+            return false;
+        };
+        (0..archive.len()).any(|i| {
+            archive
+                .by_index_raw(i)
+                .map(|f| f.encrypted())
+                .unwrap_or(false)
+        })
+    }
+
     /// Restore a backup to the specified destination
     pub fn restore_backup(&self, backup_path: &Path, dest_path: &Path) -> Result<()> {
         self.restore_backup_with_progress(backup_path, dest_path, None)
@@ -685,8 +939,8 @@ impl BackupManager {
 
     /// Verify backup integrity
     ///
-    /// Checks that the ZIP file can be opened, lists all files,
-    /// and optionally verifies file checksums.
+    /// Checks that the archive can be opened and lists all files. tar.zst
+    /// backups are decompressed in full, checking the zstd checksum.
     pub fn verify_backup(&self, backup_path: &Path) -> Result<BackupVerificationResult> {
         if !backup_path.exists() {
             return Ok(BackupVerificationResult::cannot_open(format!(
@@ -695,9 +949,11 @@ impl BackupManager {
             )));
         }
 
-        // Try to open the ZIP file
-        let file = match File::open(backup_path) {
-            Ok(f) => f,
+        // Try to open the archive
+        let file = match open_backup(backup_path) {
+            Ok(OpenedBackup::Zip(f)) => f,
+            #[cfg(feature = "tar-zstd")]
+            Ok(OpenedBackup::TarZstd) => return Ok(tar_zstd::verify_backup(backup_path)),
             Err(e) => {
                 return Ok(BackupVerificationResult::cannot_open(format!(
                     "Cannot open backup file: {}",
@@ -722,30 +978,14 @@ impl BackupManager {
 
         // Check each file in the archive
         for i in 0..archive.len() {
-            match archive.by_index(i) {
+            match archive.by_index_raw(i) {
                 Ok(file) => {
                     if !file.is_dir() {
                         file_count += 1;
                         total_size += file.size();
                     }
 
-                    // Check for path traversal attacks
-                    if file.name().contains("..") {
-                        result.add_issue(VerificationIssue {
-                            severity: IssueSeverity::Error,
-                            message: "Path traversal detected".to_string(),
-                            file_path: Some(file.name().to_string()),
-                        });
-                    }
-
-                    // Check for empty file names
-                    if file.name().is_empty() {
-                        result.add_issue(VerificationIssue {
-                            severity: IssueSeverity::Warning,
-                            message: "Empty file name detected".to_string(),
-                            file_path: None,
-                        });
-                    }
+                    result.check_entry_name(file.name());
                 }
                 Err(e) => {
                     result.all_files_readable = false;
@@ -784,12 +1024,16 @@ impl BackupManager {
             )));
         }
 
-        let file = File::open(backup_path)?;
+        let file = match open_backup(backup_path)? {
+            OpenedBackup::Zip(file) => file,
+            #[cfg(feature = "tar-zstd")]
+            OpenedBackup::TarZstd => return tar_zstd::list_contents(backup_path),
+        };
         let mut archive = ZipArchive::new(file)?;
         let mut contents = Vec::with_capacity(archive.len());
 
         for i in 0..archive.len() {
-            let file = archive.by_index(i)?;
+            let file = archive.by_index_raw(i)?;
             contents.push(BackupFileInfo {
                 path: file.name().to_string(),
                 size: file.size(),
@@ -818,17 +1062,14 @@ impl BackupManager {
             )));
         }
 
-        let file = File::open(backup_path)?;
-        let mut archive = ZipArchive::new(file)?;
         let mut preview = RestorePreview::new();
 
-        for i in 0..archive.len() {
-            let file = archive.by_index(i)?;
-            if file.is_dir() {
+        for file in self.list_backup_contents(backup_path)? {
+            if file.is_directory {
                 continue;
             }
 
-            let file_name = file.name().to_string();
+            let file_name = file.path;
 
             // Check if this file should be restored
             if !options.should_restore(&file_name) {
@@ -836,11 +1077,15 @@ impl BackupManager {
             }
 
             let dest_file = dest_path.join(&file_name);
-            let file_size = file.size();
+            let file_size = file.size;
 
             if dest_file.exists() {
                 match options.restore_mode {
-                    RestoreMode::Overwrite => {
+                    RestoreMode::Overwrite | RestoreMode::Resume => {
+                        // Overwriting only needs the size difference
+                        let existing_size =
+                            std::fs::metadata(&dest_file).map(|m| m.len()).unwrap_or(0);
+                        preview.required_space += file_size.saturating_sub(existing_size);
                         preview.overwrites.push(file_name);
                         preview.files_to_restore += 1;
                         preview.total_size += file_size;
@@ -854,30 +1099,42 @@ impl BackupManager {
                         preview
                             .renames
                             .push((dest_file.display().to_string(), new_name));
+                        // The renamed original is kept, so the full size is needed
+                        preview.required_space += file_size;
                         preview.files_to_restore += 1;
                         preview.total_size += file_size;
                     }
                 }
             } else {
+                preview.required_space += file_size;
                 preview.new_files.push(file_name);
                 preview.files_to_restore += 1;
                 preview.total_size += file_size;
             }
         }
 
+        if let Some(free_space) = (self.free_space)(dest_path) {
+            preview.free_space = free_space;
+            preview.has_enough_space = free_space >= preview.required_space;
+        }
+
         Ok(preview)
     }
 
     /// Restore backup with options
     ///
-    /// Restores selected files from backup with specified mode.
+    /// Restores selected files from backup with specified mode. When
+    /// `verify_after_restore` is set, each extracted file is re-read and
+    /// checked against the CRC32 from the archive and the manifest's content
+    /// hash (if the backup's manifest is available). tar.zst entries carry no
+    /// CRC32, so they are checked against the content hash alone.
     pub fn restore_backup_with_options(
         &self,
         backup_path: &Path,
         dest_path: &Path,
         options: &RestoreOptions,
         progress: Option<BackupProgressCallback>,
-    ) -> Result<usize> {
+    ) -> Result<RestoreResult> {
         if !backup_path.exists() {
             return Err(Error::Other(format!(
                 "Backup file not found: {}",
@@ -885,15 +1142,35 @@ impl BackupManager {
             )));
         }
 
-        let file = File::open(backup_path)?;
+        let file = match open_backup(backup_path)? {
+            OpenedBackup::Zip(file) => file,
+            #[cfg(feature = "tar-zstd")]
+            OpenedBackup::TarZstd => {
+                return self.restore_tar_zstd_backup(backup_path, dest_path, options, progress)
+            }
+        };
+
+        // Encrypted backups need a passphrase before anything is written
+        let passphrase = options.passphrase.as_deref();
+        if passphrase.is_none() && self.is_backup_encrypted(backup_path) {
+            return Err(Error::PassphraseRequired);
+        }
+
         let mut archive = ZipArchive::new(file)?;
         let total_files = archive.len();
         let mut files_restored = 0usize;
         let mut bytes_written = 0u64;
+        let mut extracted = Vec::new();
 
         // Create destination directory
         std::fs::create_dir_all(dest_path)?;
 
+        // Resumable restores track finished entries in a sidecar file
+        let mut progress_log = match options.restore_mode {
+            RestoreMode::Resume => Some(RestoreProgressLog::open(dest_path, backup_path)?),
+            _ => None,
+        };
+
         // Notify scanning phase
         if let Some(ref cb) = progress {
             cb(BackupProgress {
@@ -906,7 +1183,14 @@ impl BackupManager {
         }
 
         for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
+            if let Some(token) = &self.cancellation {
+                token.check()?;
+            }
+            let entry = match passphrase {
+                Some(p) => archive.by_index_decrypt(i, p.as_bytes()),
+                None => archive.by_index(i),
+            };
+            let mut file = entry.map_err(map_passphrase_error)?;
             let outpath = match file.enclosed_name() {
                 Some(path) => dest_path.join(path),
                 None => continue,
@@ -914,6 +1198,11 @@ impl BackupManager {
 
             let filename = file.name().to_string();
 
+            // The rename map is applied after extraction, not restored as a file
+            if filename == RENAME_MAP_FILENAME {
+                continue;
+            }
+
             // Check if this file should be restored
             if !options.should_restore(&filename) {
                 continue;
@@ -930,30 +1219,26 @@ impl BackupManager {
                 });
             }
 
+            // AES (AE-2) entries store a zero CRC32; the manifest hash covers them
+            let expected_crc = (!file.encrypted()).then(|| file.crc32());
+
             if file.is_dir() {
                 std::fs::create_dir_all(&outpath)?;
             } else {
-                // Handle existing files based on mode
-                if outpath.exists() {
-                    match options.restore_mode {
-                        RestoreMode::Overwrite => {
-                            // Will overwrite below
-                        }
-                        RestoreMode::Skip => {
-                            continue;
-                        }
-                        RestoreMode::Rename => {
-                            let new_name = Self::generate_backup_name(&outpath);
-                            std::fs::rename(&outpath, &new_name)?;
-                        }
+                // Entries finished by an interrupted run are kept if still intact
+                if let Some(log) = &progress_log {
+                    if log.is_completed(i)
+                        && std::fs::metadata(&outpath).is_ok_and(|m| m.len() == file.size())
+                        && verify_restored_file(&outpath, expected_crc, None)?.is_none()
+                    {
+                        files_restored += 1;
+                        continue;
                     }
                 }
 
-                // Create parent directories if needed
-                if let Some(parent) = outpath.parent() {
-                    if !parent.exists() {
-                        std::fs::create_dir_all(parent)?;
-                    }
+                // Handle existing files based on mode
+                if !Self::prepare_restore_path(&outpath, options.restore_mode)? {
+                    continue;
                 }
 
                 // Extract file
@@ -961,9 +1246,60 @@ impl BackupManager {
                 let bytes = std::io::copy(&mut file, &mut outfile)?;
                 bytes_written += bytes;
                 files_restored += 1;
+
+                if let Some(log) = progress_log.as_mut() {
+                    outfile.sync_all()?;
+                    log.record(i)?;
+                }
+
+                if options.verify_after_restore {
+                    extracted.push((i, outpath, filename, expected_crc));
+                }
             }
         }
 
+        // Verify extracted files against the archive (and manifest, if present)
+        let mut verification_issues = Vec::new();
+        if options.verify_after_restore {
+            let manifest = Self::load_backup_manifest(backup_path);
+
+            for (index, outpath, filename, expected_crc) in extracted {
+                let expected_hash = manifest
+                    .as_ref()
+                    .and_then(|m| m.files.get(&filename))
+                    .and_then(|e| e.content_hash.as_deref());
+
+                let mut mismatch = verify_restored_file(&outpath, expected_crc, expected_hash)?;
+                if mismatch.is_some() && options.reextract_on_mismatch {
+                    let entry = match passphrase {
+                        Some(p) => archive.by_index_decrypt(index, p.as_bytes()),
+                        None => archive.by_index(index),
+                    };
+                    let mut file = entry.map_err(map_passphrase_error)?;
+                    let mut outfile = std::fs::File::create(&outpath)?;
+                    std::io::copy(&mut file, &mut outfile)?;
+                    mismatch = verify_restored_file(&outpath, expected_crc, expected_hash)?;
+                }
+
+                if let Some(message) = mismatch {
+                    verification_issues.push(VerificationIssue {
+                        severity: IssueSeverity::Error,
+                        message,
+                        file_path: Some(filename),
+                    });
+                }
+            }
+        }
+
+        // Reconstruct files recorded as renames by incremental backups
+        let renames = read_rename_map(&mut archive, passphrase)?;
+        files_restored += Self::restore_renames(dest_path, &renames, options)?;
+
+        // The restore completed, so there is nothing left to resume
+        if progress_log.take().is_some() {
+            RestoreProgressLog::remove(dest_path)?;
+        }
+
         // Notify complete
         if let Some(ref cb) = progress {
             cb(BackupProgress {
@@ -975,7 +1311,67 @@ impl BackupManager {
             });
         }
 
-        Ok(files_restored)
+        Ok(RestoreResult {
+            files_restored,
+            verification_issues,
+        })
+    }
+
+    /// Make way for a restored file at `outpath` according to `mode`
+    ///
+    /// Returns `false` if an existing file is to be kept instead.
+    fn prepare_restore_path(outpath: &Path, mode: RestoreMode) -> Result<bool> {
+        if outpath.exists() {
+            match mode {
+                RestoreMode::Overwrite | RestoreMode::Resume => {
+                    // Overwritten by the caller
+                }
+                RestoreMode::Skip => {
+                    return Ok(false);
+                }
+                RestoreMode::Rename => {
+                    let new_name = Self::generate_backup_name(outpath);
+                    std::fs::rename(outpath, &new_name)?;
+                }
+            }
+        }
+
+        // Create parent directories if needed
+        if let Some(parent) = outpath.parent() {
+            if !parent.exists() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Load the manifest saved next to a backup, if there is one
+    fn load_backup_manifest(backup_path: &Path) -> Option<BackupManifest> {
+        let name = backup_path.file_name()?.to_string_lossy();
+        let path = backup_path.with_file_name(BackupManifest::manifest_filename(&name));
+        BackupManifest::load(&path).ok()
+    }
+
+    /// Reconstruct files recorded as renames, honouring the restore options
+    ///
+    /// Returns the number of files placed.
+    fn restore_renames(
+        dest_path: &Path,
+        renames: &[RenamedFile],
+        options: &RestoreOptions,
+    ) -> Result<usize> {
+        if options.restore_mode == RestoreMode::Rename {
+            for rename in renames.iter().filter(|r| options.should_restore(&r.to)) {
+                let target = dest_path.join(&rename.to);
+                if target.exists() {
+                    std::fs::rename(&target, Self::generate_backup_name(&target))?;
+                }
+            }
+        }
+        apply_rename_map(dest_path, renames, |r| {
+            options.should_restore(&r.to)
+                && !(options.restore_mode == RestoreMode::Skip && dest_path.join(&r.to).exists())
+        })
     }
 
     /// Generate a backup name for an existing file (e.g., "file.txt" -> "file.txt.bak")
@@ -997,13 +1393,121 @@ impl BackupManager {
 
     /// Get the default backup directory
     pub fn default_backup_dir() -> PathBuf {
-        dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("osu-sync")
+        paths::app_data_dir()
+            .unwrap_or_else(|| PathBuf::from("osu-sync"))
             .join("backups")
     }
 }
 
+/// Sidecar file in the restore destination listing finished archive entries
+pub const RESTORE_PROGRESS_FILENAME: &str = ".restore-progress";
+
+/// Progress of a resumable restore, persisted after every extracted entry
+///
+/// The first line identifies the backup (file name and size) so progress from
+/// a different archive is never reused; each following line is the index of
+/// an entry that was fully extracted.
+struct RestoreProgressLog {
+    file: File,
+    completed: std::collections::HashSet<usize>,
+}
+
+impl RestoreProgressLog {
+    /// Open the sidecar in `dest`, keeping earlier progress for the same backup
+    fn open(dest: &Path, backup_path: &Path) -> Result<Self> {
+        let path = dest.join(RESTORE_PROGRESS_FILENAME);
+        let header = format!(
+            "{}:{}",
+            backup_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            std::fs::metadata(backup_path)?.len()
+        );
+
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut lines = existing.lines();
+        if lines.next() == Some(header.as_str()) {
+            let completed = lines.filter_map(|l| l.trim().parse().ok()).collect();
+            let file = std::fs::OpenOptions::new().append(true).open(&path)?;
+            return Ok(Self { file, completed });
+        }
+
+        let mut file = File::create(&path)?;
+        writeln!(file, "{}", header)?;
+        Ok(Self {
+            file,
+            completed: Default::default(),
+        })
+    }
+
+    /// Whether a previous run finished extracting this entry
+    fn is_completed(&self, index: usize) -> bool {
+        self.completed.contains(&index)
+    }
+
+    /// Record an entry as fully extracted
+    fn record(&mut self, index: usize) -> Result<()> {
+        writeln!(self.file, "{}", index)?;
+        self.file.sync_data()?;
+        self.completed.insert(index);
+        Ok(())
+    }
+
+    /// Delete the sidecar after a successful restore
+    fn remove(dest: &Path) -> Result<()> {
+        match std::fs::remove_file(dest.join(RESTORE_PROGRESS_FILENAME)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check a restored file against its expected checksums
+///
+/// Returns a description of the mismatch, or `None` if the file matches.
+fn verify_restored_file(
+    path: &Path,
+    expected_crc: Option<u32>,
+    expected_hash: Option<&str>,
+) -> Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let mut crc = crc32fast::Hasher::new();
+    let mut blake = blake3::Hasher::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let bytes_read = std::io::Read::read(&mut file, &mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        crc.update(&buffer[..bytes_read]);
+        if expected_hash.is_some() {
+            blake.update(&buffer[..bytes_read]);
+        }
+    }
+
+    let actual_crc = crc.finalize();
+    if let Some(expected_crc) = expected_crc.filter(|&c| c != actual_crc) {
+        return Ok(Some(format!(
+            "CRC32 mismatch: expected {:08x}, got {:08x}",
+            expected_crc, actual_crc
+        )));
+    }
+
+    if let Some(expected) = expected_hash {
+        let actual = blake.finalize().to_hex().to_string();
+        if actual != expected {
+            return Ok(Some(format!(
+                "Content hash mismatch: expected {}, got {}",
+                expected, actual
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
 /// Generate a timestamp string for filenames
 fn chrono_timestamp() -> String {
     use std::time::{Duration, UNIX_EPOCH};
@@ -1079,6 +1583,30 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
+#[cfg(feature = "tokio")]
+impl BackupManager {
+    /// Create a backup on tokio's blocking pool, see
+    /// [`BackupManager::create_backup_with_options`]
+    pub async fn create_backup_async(
+        &self,
+        target: BackupTarget,
+        source_path: PathBuf,
+        options: BackupOptions,
+        progress: Option<tokio::sync::mpsc::UnboundedSender<BackupProgress>>,
+    ) -> Result<PathBuf> {
+        let manager = self.clone();
+        let callback = progress.map(|tx| -> BackupProgressCallback {
+            Box::new(move |update| {
+                let _ = tx.send(update);
+            })
+        });
+        tokio::task::spawn_blocking(move || {
+            manager.create_backup_with_options(target, &source_path, options, callback)
+        })
+        .await?
+    }
+}
+
 /// Format age relative to now
 fn format_age(time: SystemTime) -> String {
     let now = SystemTime::now();
@@ -1111,6 +1639,326 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_incremental_backup_detects_renamed_folder() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("Songs");
+        let backup_dir = temp_dir.path().join("backups");
+        let restore_dir = temp_dir.path().join("restore");
+
+        let data = vec![0x5au8; 256 * 1024];
+        let old_folder = source.join("123 Artist - Title");
+        std::fs::create_dir_all(&old_folder).unwrap();
+        std::fs::write(old_folder.join("audio.mp3"), &data).unwrap();
+        std::fs::write(old_folder.join("map.osu"), b"osu file format v14").unwrap();
+
+        let manager = BackupManager::new(backup_dir);
+        let full = manager
+            .create_backup_with_options(
+                BackupTarget::StableSongs,
+                &source,
+                BackupOptions::new().with_compression(CompressionLevel::Fast),
+                None,
+            )
+            .unwrap();
+
+        // Rename the folder without touching its contents
+        let new_folder = source.join("123 Artist - Title (renamed)");
+        std::fs::rename(&old_folder, &new_folder).unwrap();
+
+        let incremental = manager
+            .create_backup_with_options(
+                BackupTarget::StableSongs,
+                &source,
+                BackupOptions::new()
+                    .with_compression(CompressionLevel::Fast)
+                    .with_mode(BackupMode::Incremental),
+                None,
+            )
+            .unwrap();
+
+        let full_size = std::fs::metadata(&full).unwrap().len();
+        let incremental_size = std::fs::metadata(&incremental).unwrap().len();
+        assert!(incremental_size < full_size / 4);
+
+        // Restoring full then incremental reconstructs the new layout
+        manager
+            .restore_backup_with_options(&full, &restore_dir, &RestoreOptions::all(), None)
+            .unwrap();
+        manager
+            .restore_backup_with_options(&incremental, &restore_dir, &RestoreOptions::all(), None)
+            .unwrap();
+
+        let restored = restore_dir.join("123 Artist - Title (renamed)");
+        assert_eq!(std::fs::read(restored.join("audio.mp3")).unwrap(), data);
+        assert!(restored.join("map.osu").exists());
+        assert!(!restore_dir.join("123 Artist - Title").exists());
+        assert!(!restore_dir.join(RENAME_MAP_FILENAME).exists());
+    }
+
+    #[test]
+    fn test_encrypted_backup_requires_passphrase() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("Songs");
+        let restore_dir = temp_dir.path().join("restore");
+        std::fs::create_dir_all(source.join("1 Artist - Title")).unwrap();
+        std::fs::write(source.join("1 Artist - Title/map.osu"), b"secret").unwrap();
+
+        let manager = BackupManager::new(temp_dir.path().join("backups"));
+        let backup = manager
+            .create_backup_with_options(
+                BackupTarget::StableSongs,
+                &source,
+                BackupOptions::new().with_encryption("hunter2"),
+                None,
+            )
+            .unwrap();
+
+        assert!(manager.is_backup_encrypted(&backup));
+        let metadata = manager.read_backup_metadata(&backup).unwrap();
+        assert_eq!(
+            metadata.encryption.as_deref(),
+            Some(ENCRYPTION_SCHEME_AES256)
+        );
+
+        let result = manager.restore_backup_with_options(
+            &backup,
+            &restore_dir,
+            &RestoreOptions::all(),
+            None,
+        );
+        assert!(matches!(result, Err(Error::PassphraseRequired)));
+
+        let wrong = RestoreOptions::all().with_passphrase("wrong");
+        let result = manager.restore_backup_with_options(&backup, &restore_dir, &wrong, None);
+        assert!(matches!(result, Err(Error::WrongPassphrase)));
+
+        let right = RestoreOptions::all().with_passphrase("hunter2");
+        manager
+            .restore_backup_with_options(&backup, &restore_dir, &right, None)
+            .unwrap();
+        assert_eq!(
+            std::fs::read(restore_dir.join("1 Artist - Title/map.osu")).unwrap(),
+            b"secret"
+        );
+    }
+
+    #[test]
+    fn test_encrypted_restore_passes_verification() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("Songs");
+        let restore_dir = temp_dir.path().join("restore");
+        std::fs::create_dir_all(source.join("1 Artist - Title")).unwrap();
+        std::fs::write(source.join("1 Artist - Title/map.osu"), b"secret").unwrap();
+        std::fs::write(source.join("1 Artist - Title/audio.mp3"), vec![7u8; 4096]).unwrap();
+
+        let manager = BackupManager::new(temp_dir.path().join("backups"));
+        let backup = manager
+            .create_backup_with_options(
+                BackupTarget::StableSongs,
+                &source,
+                BackupOptions::new().with_encryption("hunter2"),
+                None,
+            )
+            .unwrap();
+
+        let options = RestoreOptions::all()
+            .with_passphrase("hunter2")
+            .with_verification();
+        let result = manager
+            .restore_backup_with_options(&backup, &restore_dir, &options, None)
+            .unwrap();
+        assert!(result.is_verified(), "{:?}", result.verification_issues);
+        assert_eq!(
+            std::fs::read(restore_dir.join("1 Artist - Title/audio.mp3")).unwrap(),
+            vec![7u8; 4096]
+        );
+    }
+
+    #[test]
+    fn test_cancelled_backup_leaves_no_archive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("Songs");
+        std::fs::create_dir_all(source.join("1 Artist - Title")).unwrap();
+        std::fs::write(source.join("1 Artist - Title/map.osu"), b"map").unwrap();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let backup_dir = temp_dir.path().join("backups");
+        let manager = BackupManager::new(backup_dir.clone()).with_cancellation(token);
+
+        let result = manager.create_backup(BackupTarget::StableSongs, &source);
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(std::fs::read_dir(&backup_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_backup_to_writer_streams_complete_archive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("Songs");
+        let restore_dir = temp_dir.path().join("restore");
+        std::fs::create_dir_all(source.join("1 Artist - Title")).unwrap();
+        std::fs::write(source.join("1 Artist - Title/map.osu"), b"streamed").unwrap();
+
+        let progress_calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = progress_calls.clone();
+        let progress: BackupProgressCallback = Box::new(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+
+        let manager = BackupManager::new(temp_dir.path().join("backups"));
+        let mut stream = Vec::new();
+        let result = manager
+            .create_backup_to_writer(
+                BackupTarget::StableSongs,
+                &source,
+                &mut stream,
+                Some(progress),
+            )
+            .unwrap();
+        assert_eq!(result.files_included, 1);
+        assert!(progress_calls.load(std::sync::atomic::Ordering::SeqCst) > 0);
+
+        // Nothing is written to the backup directory
+        assert!(!manager.backup_dir().exists());
+
+        let backup = temp_dir.path().join("streamed.zip");
+        std::fs::write(&backup, &stream).unwrap();
+        let metadata = manager.read_backup_metadata(&backup).unwrap();
+        assert_eq!(metadata.file_count, 1);
+
+        manager.restore_backup(&backup, &restore_dir).unwrap();
+        assert_eq!(
+            std::fs::read(restore_dir.join("1 Artist - Title/map.osu")).unwrap(),
+            b"streamed"
+        );
+    }
+
+    #[test]
+    fn test_preview_restore_space_accounting() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("source");
+        let dest = temp_dir.path().join("dest");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(source.join("existing.db"), vec![0u8; 1000]).unwrap();
+        std::fs::write(source.join("new.db"), vec![0u8; 500]).unwrap();
+        std::fs::write(dest.join("existing.db"), vec![0u8; 800]).unwrap();
+
+        let mut manager = BackupManager::new(temp_dir.path().join("backups"));
+        let backup = manager
+            .create_backup(BackupTarget::StableSongs, &source)
+            .unwrap();
+        // Enough for overwriting, not for keeping renamed originals
+        manager.free_space = |_| Some(1000);
+
+        let files = vec!["existing.db".to_string(), "new.db".to_string()];
+        let overwrite = manager
+            .preview_restore(&backup, &dest, &RestoreOptions::selective(files.clone()))
+            .unwrap();
+        assert_eq!(overwrite.required_space, 200 + 500);
+        assert_eq!(overwrite.free_space, 1000);
+        assert!(overwrite.has_enough_space);
+
+        let rename = manager
+            .preview_restore(
+                &backup,
+                &dest,
+                &RestoreOptions::selective(files).with_mode(RestoreMode::Rename),
+            )
+            .unwrap();
+        assert_eq!(rename.required_space, 1000 + 500);
+        assert!(!rename.has_enough_space);
+    }
+
+    #[test]
+    fn test_verify_restored_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("file.bin");
+        std::fs::write(&path, b"hello").unwrap();
+
+        let crc = crc32fast::hash(b"hello");
+        let hash = blake3::hash(b"hello").to_hex().to_string();
+        assert!(verify_restored_file(&path, Some(crc), Some(&hash))
+            .unwrap()
+            .is_none());
+        assert!(verify_restored_file(&path, Some(crc ^ 1), None)
+            .unwrap()
+            .is_some());
+        assert!(verify_restored_file(&path, Some(crc), Some("deadbeef"))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_restore_with_verification() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("source");
+        let restore_dir = temp_dir.path().join("restore");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("scores.db"), b"scores").unwrap();
+
+        let manager = BackupManager::new(temp_dir.path().join("backups"));
+        let backup = manager
+            .create_backup(BackupTarget::StableSongs, &source)
+            .unwrap();
+
+        let options = RestoreOptions::all()
+            .with_verification()
+            .with_reextract_on_mismatch();
+        let result = manager
+            .restore_backup_with_options(&backup, &restore_dir, &options, None)
+            .unwrap();
+        assert!(result.files_restored >= 1);
+        assert!(result.is_verified());
+    }
+
+    #[test]
+    fn test_resume_restore_skips_completed_entries() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("source");
+        let restore_dir = temp_dir.path().join("restore");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("a.osu"), b"first").unwrap();
+        std::fs::write(source.join("b.osu"), b"second").unwrap();
+
+        let manager = BackupManager::new(temp_dir.path().join("backups"));
+        let backup = manager
+            .create_backup(BackupTarget::StableSongs, &source)
+            .unwrap();
+
+        // Simulate an interrupted run: every entry was marked done, but one
+        // file on disk was left truncated
+        std::fs::create_dir_all(&restore_dir).unwrap();
+        let mut log = RestoreProgressLog::open(&restore_dir, &backup).unwrap();
+        let archive = ZipArchive::new(File::open(&backup).unwrap()).unwrap();
+        for index in 0..archive.len() {
+            log.record(index).unwrap();
+        }
+        drop(log);
+        std::fs::write(restore_dir.join("a.osu"), b"first").unwrap();
+        std::fs::write(restore_dir.join("b.osu"), b"sec").unwrap();
+        // An mtime no extraction would produce, to show a.osu is not rewritten
+        let old_mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        File::options()
+            .write(true)
+            .open(restore_dir.join("a.osu"))
+            .unwrap()
+            .set_modified(old_mtime)
+            .unwrap();
+
+        let options = RestoreOptions::all().with_mode(RestoreMode::Resume);
+        manager
+            .restore_backup_with_options(&backup, &restore_dir, &options, None)
+            .unwrap();
+
+        assert_eq!(std::fs::read(restore_dir.join("a.osu")).unwrap(), b"first");
+        let a_meta = std::fs::metadata(restore_dir.join("a.osu")).unwrap();
+        assert_eq!(a_meta.modified().unwrap(), old_mtime);
+        assert_eq!(std::fs::read(restore_dir.join("b.osu")).unwrap(), b"second");
+        assert!(!restore_dir.join(RESTORE_PROGRESS_FILENAME).exists());
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(500), "500 B");
@@ -1,8 +1,9 @@
 //! Backup options for compression and incremental backups
 
+use crate::cancel::CancellationToken;
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs::File;
 use std::io::Read;
@@ -33,6 +34,15 @@ impl CompressionLevel {
         }
     }
 
+    /// Get the zstd compression level value, for tar.zst backups
+    pub fn to_zstd_level(&self) -> i32 {
+        match self {
+            CompressionLevel::Fast => 1,
+            CompressionLevel::Normal => 3,
+            CompressionLevel::Best => 19,
+        }
+    }
+
     /// Get user-friendly label
     pub fn label(&self) -> &'static str {
         match self {
@@ -109,13 +119,129 @@ impl fmt::Display for BackupMode {
     }
 }
 
+/// Archive format of a backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BackupFormat {
+    /// Zip with Deflate; supports encryption
+    #[default]
+    Zip,
+    /// A tar stream compressed with zstd, usually smaller and faster on
+    /// folders of many small files; needs the `tar-zstd` feature
+    TarZstd,
+}
+
+/// Magic number at the start of every zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+impl BackupFormat {
+    /// File extension of backups in this format, without the leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BackupFormat::Zip => "zip",
+            BackupFormat::TarZstd => "tar.zst",
+        }
+    }
+
+    /// Get short label for display
+    pub fn short_label(&self) -> &'static str {
+        match self {
+            BackupFormat::Zip => "Zip",
+            BackupFormat::TarZstd => "tar.zst",
+        }
+    }
+
+    /// Toggle between formats
+    pub fn toggle(&self) -> Self {
+        match self {
+            BackupFormat::Zip => BackupFormat::TarZstd,
+            BackupFormat::TarZstd => BackupFormat::Zip,
+        }
+    }
+
+    /// Format of a backup file, judged by its name
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(BackupFormat::Zip)
+        } else if name.ends_with(".tar.zst") {
+            Some(BackupFormat::TarZstd)
+        } else {
+            None
+        }
+    }
+
+    /// Format of a backup file, judged by its first bytes and falling back
+    /// to its name
+    pub fn detect(path: &Path) -> Option<Self> {
+        let mut magic = [0u8; 4];
+        let read = File::open(path).and_then(|mut f| f.read_exact(&mut magic));
+        match read {
+            Ok(()) if magic.starts_with(b"PK") => Some(BackupFormat::Zip),
+            Ok(()) if magic == ZSTD_MAGIC => Some(BackupFormat::TarZstd),
+            _ => Self::from_path(path),
+        }
+    }
+
+    /// Fail unless this build can read and write the format
+    pub(crate) fn check_supported(&self) -> Result<()> {
+        match self {
+            BackupFormat::TarZstd if !cfg!(feature = "tar-zstd") => Err(Error::Config(
+                "tar.zst backups need osu-sync built with the tar-zstd feature".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for BackupFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.short_label())
+    }
+}
+
+/// Extensions of already-compressed media that are stored without compression
+pub const DEFAULT_STORED_EXTENSIONS: &[&str] = &[
+    "mp3", "ogg", "wav", "flac", "m4a", "jpg", "jpeg", "png", "webp", "gif", "mp4", "avi", "flv",
+    "webm", "mkv", "zip", "osz", "osk", "7z",
+];
+
 /// Options for creating a backup
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct BackupOptions {
     /// Compression level
     pub compression: CompressionLevel,
     /// Backup mode (full or incremental)
     pub mode: BackupMode,
+    /// Archive format
+    pub format: BackupFormat,
+    /// Passphrase to encrypt the archive with (AES-256), if any
+    pub encryption: Option<String>,
+    /// Lowercase extensions (without the dot) archived with Store instead of
+    /// Deflate; recompressing media costs CPU for next to no size gain
+    pub stored_extensions: HashSet<String>,
+    /// Threads to compress on (`0` uses the global rayon pool, `1` runs
+    /// sequentially). Ignored for encrypted zip backups, which always
+    /// compress on one thread.
+    pub threads: usize,
+    /// Checked before each file is archived
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for BackupOptions {
+    fn default() -> Self {
+        Self {
+            compression: CompressionLevel::default(),
+            mode: BackupMode::default(),
+            format: BackupFormat::default(),
+            encryption: None,
+            stored_extensions: DEFAULT_STORED_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            threads: 0,
+            cancellation: None,
+        }
+    }
 }
 
 impl BackupOptions {
@@ -124,6 +250,36 @@ impl BackupOptions {
         Self::default()
     }
 
+    /// Replace the set of extensions that are stored without compression
+    pub fn with_stored_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.stored_extensions = extensions
+            .into_iter()
+            .map(|ext| ext.as_ref().trim_start_matches('.').to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Whether a file should be stored rather than compressed
+    ///
+    /// Always true for [`CompressionLevel::Fast`]; otherwise decided by the
+    /// file's extension.
+    pub fn should_store(&self, path: &str) -> bool {
+        if self.compression == CompressionLevel::Fast {
+            return true;
+        }
+        Path::new(path)
+            .extension()
+            .map(|ext| {
+                self.stored_extensions
+                    .contains(&ext.to_string_lossy().to_ascii_lowercase())
+            })
+            .unwrap_or(false)
+    }
+
     /// Set compression level
     pub fn with_compression(mut self, level: CompressionLevel) -> Self {
         self.compression = level;
@@ -135,8 +291,58 @@ impl BackupOptions {
         self.mode = mode;
         self
     }
+
+    /// Set archive format
+    pub fn with_format(mut self, format: BackupFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Encrypt the backup with a passphrase
+    ///
+    /// Only [`BackupFormat::Zip`] supports encryption.
+    pub fn with_encryption(mut self, passphrase: impl Into<String>) -> Self {
+        self.encryption = Some(passphrase.into());
+        self
+    }
+
+    /// Set how many threads compress the backup
+    ///
+    /// Zip entries are compressed in parallel and written in order; tar.zst
+    /// uses zstd's own worker threads. Encrypted and streamed zip archives
+    /// are always compressed on the calling thread.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Stop with [`Error::Cancelled`] once the token is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Number of threads compression runs on, with `0` resolved to the size
+    /// of the global rayon pool
+    pub(crate) fn compression_threads(&self) -> usize {
+        match self.threads {
+            0 => rayon::current_num_threads(),
+            n => n,
+        }
+    }
+
+    /// Return [`Error::Cancelled`] if cancellation has been requested
+    pub(crate) fn check_cancelled(&self) -> Result<()> {
+        match &self.cancellation {
+            Some(token) => token.check(),
+            None => Ok(()),
+        }
+    }
 }
 
+/// Encryption scheme recorded in `backup_info.json` for AES-encrypted archives
+pub const ENCRYPTION_SCHEME_AES256: &str = "zip-aes-256";
+
 /// Manifest entry for tracking file state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ManifestEntry {
@@ -148,6 +354,26 @@ pub struct ManifestEntry {
     pub hash: String,
     /// File size in bytes
     pub size: u64,
+    /// Full Blake3 hash of the file content, used to detect moved files
+    #[serde(default)]
+    pub content_hash: Option<String>,
+}
+
+/// Name of the metadata entry stored inside every backup archive
+pub const BACKUP_INFO_FILENAME: &str = "backup_info.json";
+
+/// Name of the rename map stored inside incremental backup archives
+pub const RENAME_MAP_FILENAME: &str = "backup_renames.json";
+
+/// A file that was recorded as moved/copied instead of being re-archived
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenamedFile {
+    /// Path the content was previously backed up under
+    pub from: String,
+    /// Path the content now lives at
+    pub to: String,
+    /// Whether the old path no longer exists (a move rather than a copy)
+    pub source_removed: bool,
 }
 
 /// Manifest file stored alongside backups for incremental tracking
@@ -163,6 +389,12 @@ pub struct BackupManifest {
     pub incremental: bool,
     /// Base backup filename this incremental is based on (if incremental)
     pub base_backup: Option<String>,
+    /// Files recorded as renames of previously backed up content
+    #[serde(default)]
+    pub renames: Vec<RenamedFile>,
+    /// Index of file paths by content hash (rebuilt on load)
+    #[serde(skip)]
+    by_content_hash: HashMap<String, String>,
 }
 
 impl BackupManifest {
@@ -179,14 +411,36 @@ impl BackupManifest {
             files: HashMap::new(),
             incremental,
             base_backup,
+            renames: Vec::new(),
+            by_content_hash: HashMap::new(),
         }
     }
 
     /// Add a file entry to the manifest
     pub fn add_entry(&mut self, entry: ManifestEntry) {
+        if let Some(ref content_hash) = entry.content_hash {
+            self.by_content_hash
+                .insert(content_hash.clone(), entry.path.clone());
+        }
         self.files.insert(entry.path.clone(), entry);
     }
 
+    /// Find an entry with the given full content hash
+    pub fn find_by_content_hash(&self, content_hash: &str) -> Option<&ManifestEntry> {
+        self.by_content_hash
+            .get(content_hash)
+            .and_then(|path| self.files.get(path))
+    }
+
+    /// Rebuild the content hash index from the file entries
+    fn rebuild_index(&mut self) {
+        self.by_content_hash = self
+            .files
+            .values()
+            .filter_map(|e| e.content_hash.clone().map(|h| (h, e.path.clone())))
+            .collect();
+    }
+
     /// Check if a file has changed compared to a previous manifest
     pub fn file_changed(&self, path: &str, modified: u64, hash: &str) -> bool {
         match self.files.get(path) {
@@ -197,17 +451,20 @@ impl BackupManifest {
 
     /// Get manifest filename for a backup
     pub fn manifest_filename(backup_filename: &str) -> String {
-        if let Some(base) = backup_filename.strip_suffix(".zip") {
-            format!("{}.manifest.json", base)
-        } else {
-            format!("{}.manifest.json", backup_filename)
-        }
+        let base = backup_filename
+            .strip_suffix(".zip")
+            .or_else(|| backup_filename.strip_suffix(".tar.zst"))
+            .unwrap_or(backup_filename);
+        format!("{}.manifest.json", base)
     }
 
     /// Load manifest from file
     pub fn load(path: &Path) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        serde_json::from_str(&content).map_err(|e| Error::Other(format!("Invalid manifest: {}", e)))
+        let mut manifest: Self = serde_json::from_str(&content)
+            .map_err(|e| Error::Other(format!("Invalid manifest: {}", e)))?;
+        manifest.rebuild_index();
+        Ok(manifest)
     }
 
     /// Save manifest to file
@@ -232,6 +489,9 @@ pub struct BackupMetadata {
     pub mode: BackupMode,
     /// Compression level used
     pub compression: CompressionLevel,
+    /// Archive format
+    #[serde(default)]
+    pub format: BackupFormat,
     /// Number of files in backup
     pub file_count: usize,
     /// Total uncompressed size in bytes
@@ -242,6 +502,9 @@ pub struct BackupMetadata {
     pub base_backup: Option<String>,
     /// osu-sync version that created this backup
     pub osu_sync_version: String,
+    /// Encryption scheme used for the archive entries (None if unencrypted)
+    #[serde(default)]
+    pub encryption: Option<String>,
 }
 
 impl BackupMetadata {
@@ -265,14 +528,33 @@ impl BackupMetadata {
             target,
             mode,
             compression,
+            format: BackupFormat::default(),
             file_count,
             total_size,
             is_incremental: mode == BackupMode::Incremental,
             base_backup,
             osu_sync_version: env!("CARGO_PKG_VERSION").to_string(),
+            encryption: None,
         }
     }
 
+    /// Record the archive format
+    pub fn with_format(mut self, format: BackupFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Record the encryption scheme used for the archive
+    pub fn with_encryption(mut self, scheme: impl Into<String>) -> Self {
+        self.encryption = Some(scheme.into());
+        self
+    }
+
+    /// Check if the archive entries are encrypted
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
     /// Serialize to JSON bytes for inclusion in archive
     pub fn to_json_bytes(&self) -> Result<Vec<u8>> {
         serde_json::to_vec_pretty(self)
@@ -300,6 +582,23 @@ pub fn compute_simple_hash(path: &Path) -> Result<String> {
     Ok(format!("{:016x}", hasher.finish()))
 }
 
+/// Compute a full Blake3 hash of a file's content
+pub fn compute_content_hash(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
 /// Format a unix timestamp to a human-readable date string
 fn format_timestamp(secs: u64) -> String {
     let days_since_epoch = secs / 86400;
@@ -364,9 +663,71 @@ mod tests {
         assert_eq!(CompressionLevel::Best.next(), CompressionLevel::Fast);
     }
 
+    #[test]
+    fn test_manifest_find_by_content_hash() {
+        let mut manifest = BackupManifest::new(BackupTarget::StableSongs, false, None);
+        manifest.add_entry(ManifestEntry {
+            path: "123 Artist - Title/audio.mp3".to_string(),
+            modified: 0,
+            hash: "simple".to_string(),
+            size: 4,
+            content_hash: Some("full".to_string()),
+        });
+
+        let entry = manifest.find_by_content_hash("full").unwrap();
+        assert_eq!(entry.path, "123 Artist - Title/audio.mp3");
+        assert!(manifest.find_by_content_hash("other").is_none());
+
+        // Index survives a save/load round trip
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.manifest.json");
+        manifest.save(&path).unwrap();
+        let loaded = BackupManifest::load(&path).unwrap();
+        assert!(loaded.find_by_content_hash("full").is_some());
+    }
+
+    #[test]
+    fn test_should_store_by_extension() {
+        let options = BackupOptions::new();
+        assert!(options.should_store("123 Artist - Title/audio.MP3"));
+        assert!(options.should_store("123 Artist - Title/bg.jpg"));
+        assert!(!options.should_store("123 Artist - Title/map.osu"));
+        assert!(!options.should_store("osu!.db"));
+
+        let options = options.with_stored_extensions([".osb"]);
+        assert!(options.should_store("storyboard.osb"));
+        assert!(!options.should_store("audio.mp3"));
+
+        let fast = BackupOptions::new().with_compression(CompressionLevel::Fast);
+        assert!(fast.should_store("map.osu"));
+    }
+
     #[test]
     fn test_backup_mode() {
         assert_eq!(BackupMode::Full.toggle(), BackupMode::Incremental);
         assert_eq!(BackupMode::Incremental.toggle(), BackupMode::Full);
     }
+
+    #[test]
+    fn test_backup_format_detection() {
+        assert_eq!(
+            BackupFormat::from_path(Path::new("stable-songs-1.tar.zst")),
+            Some(BackupFormat::TarZstd)
+        );
+        assert_eq!(
+            BackupFormat::from_path(Path::new("stable-songs-1-inc.zip")),
+            Some(BackupFormat::Zip)
+        );
+        assert_eq!(BackupFormat::from_path(Path::new("notes.txt")), None);
+        assert_eq!(
+            BackupManifest::manifest_filename("all-1.tar.zst"),
+            "all-1.manifest.json"
+        );
+
+        // Magic bytes win over a misleading extension
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("renamed.zip");
+        std::fs::write(&path, [0x28, 0xB5, 0x2F, 0xFD, 0]).unwrap();
+        assert_eq!(BackupFormat::detect(&path), Some(BackupFormat::TarZstd));
+    }
 }
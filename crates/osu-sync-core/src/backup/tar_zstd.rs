@@ -0,0 +1,577 @@
+//! tar.zst backup archives
+//!
+//! `backup_info.json` and the rename map are the first entries, so reading a
+//! backup's metadata only decompresses the start of the stream. zstd frame
+//! checksums stand in for the per-entry CRC32 of zip archives.
+
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{
+    is_safe_relative_path, modified_secs, verify_restored_file, ArchiveWriter, BackupFileInfo,
+    BackupManager, BackupMetadata, BackupOptions, BackupPhase, BackupProgress,
+    BackupProgressCallback, BackupVerificationResult, IssueSeverity, RenamedFile, RestoreMode,
+    RestoreOptions, RestoreProgressLog, RestoreResult, VerificationIssue, BACKUP_INFO_FILENAME,
+    RENAME_MAP_FILENAME,
+};
+
+type TarZstdReader = tar::Archive<zstd::stream::read::Decoder<'static, BufReader<File>>>;
+
+/// [`ArchiveWriter`] for tar.zst archives
+pub(super) struct TarZstdArchiveWriter<W: Write> {
+    tar: tar::Builder<zstd::stream::write::Encoder<'static, W>>,
+}
+
+impl<W: Write> TarZstdArchiveWriter<W> {
+    pub(super) fn new(writer: W, options: &BackupOptions) -> Result<Self> {
+        let mut encoder =
+            zstd::stream::write::Encoder::new(writer, options.compression.to_zstd_level())?;
+        encoder.include_checksum(true)?;
+        let threads = options.compression_threads();
+        if threads > 1 {
+            // zstd splits the stream into jobs and writes them in order
+            encoder.multithread(threads as u32)?;
+        }
+        Ok(Self {
+            tar: tar::Builder::new(encoder),
+        })
+    }
+
+    fn append(&mut self, name: &str, size: u64, mtime: u64, data: impl Read) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        self.tar.append_data(&mut header, name, data)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> ArchiveWriter for TarZstdArchiveWriter<W> {
+    const METADATA_FIRST: bool = true;
+
+    fn add_file(&mut self, path: &Path, name: &str, _store: bool) -> Result<(u64, String)> {
+        // zstd passes over incompressible media quickly, so nothing is stored
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut reader = HashingReader::new(file.take(size));
+        self.append(name, size, modified_secs(path), &mut reader)?;
+
+        // The header already promised `size` bytes
+        if reader.bytes_read != size {
+            return Err(Error::Other(format!(
+                "{} changed while it was being backed up",
+                path.display()
+            )));
+        }
+        Ok((size, reader.finalize()))
+    }
+
+    fn add_rename_map(&mut self, json: &[u8]) -> Result<()> {
+        self.append(RENAME_MAP_FILENAME, json.len() as u64, 0, json)
+    }
+
+    fn add_metadata(&mut self, json: &[u8]) -> Result<()> {
+        self.append(BACKUP_INFO_FILENAME, json.len() as u64, 0, json)
+    }
+
+    fn metadata(&self, metadata: BackupMetadata) -> BackupMetadata {
+        metadata
+    }
+
+    fn finish(self) -> Result<()> {
+        self.tar.into_inner()?.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Reader that Blake3-hashes everything read through it
+struct HashingReader<R> {
+    inner: R,
+    hasher: blake3::Hasher,
+    bytes_read: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: blake3::Hasher::new(),
+            bytes_read: 0,
+        }
+    }
+
+    fn finalize(&self) -> String {
+        self.hasher.finalize().to_hex().to_string()
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.bytes_read += n as u64;
+        Ok(n)
+    }
+}
+
+fn open_archive(path: &Path) -> Result<TarZstdReader> {
+    let file = File::open(path).map_err(|e| Error::io_at(e, path))?;
+    Ok(tar::Archive::new(zstd::stream::read::Decoder::new(file)?))
+}
+
+fn entry_name<R: Read>(entry: &tar::Entry<'_, R>) -> String {
+    String::from_utf8_lossy(&entry.path_bytes()).into_owned()
+}
+
+fn parse_rename_map(entry: impl Read) -> Result<Vec<RenamedFile>> {
+    serde_json::from_reader(entry).map_err(|e| Error::Other(format!("Invalid rename map: {}", e)))
+}
+
+/// Read `backup_info.json` from the start of a tar.zst backup
+pub(super) fn read_metadata(path: &Path) -> Option<BackupMetadata> {
+    let mut archive = open_archive(path).ok()?;
+    for entry in archive.entries().ok()? {
+        let entry = entry.ok()?;
+        match entry_name(&entry).as_str() {
+            BACKUP_INFO_FILENAME => return serde_json::from_reader(entry).ok(),
+            RENAME_MAP_FILENAME => continue,
+            // Metadata precedes the files, so this backup has none
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// List the entries of a tar.zst backup
+///
+/// The archive is compressed as one stream, so `compressed_size` is the
+/// uncompressed size and no CRC32 is available.
+pub(super) fn list_contents(path: &Path) -> Result<Vec<BackupFileInfo>> {
+    let mut archive = open_archive(path)?;
+    let mut contents = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        contents.push(BackupFileInfo {
+            path: entry_name(&entry),
+            size: entry.size(),
+            compressed_size: entry.size(),
+            is_directory: entry.header().entry_type().is_dir(),
+            crc32: None,
+        });
+    }
+    Ok(contents)
+}
+
+/// Verify a tar.zst backup by decompressing all of it
+///
+/// Reading to the end checks the zstd frame checksum.
+pub(super) fn verify_backup(path: &Path) -> BackupVerificationResult {
+    let mut archive = match open_archive(path) {
+        Ok(a) => a,
+        Err(e) => {
+            return BackupVerificationResult::cannot_open(format!(
+                "Cannot open backup file: {}",
+                e
+            ));
+        }
+    };
+
+    let mut file_count = 0usize;
+    let mut total_size = 0u64;
+    let mut result = BackupVerificationResult::valid(0, 0);
+    fn read_error(result: &mut BackupVerificationResult, message: String) {
+        result.all_files_readable = false;
+        result.add_issue(VerificationIssue {
+            severity: IssueSeverity::Error,
+            message,
+            file_path: None,
+        });
+    }
+
+    match archive.entries() {
+        Ok(entries) => {
+            for (i, entry) in entries.enumerate() {
+                let mut entry = match entry {
+                    Ok(e) => e,
+                    Err(e) => {
+                        read_error(
+                            &mut result,
+                            format!("Cannot read file at index {}: {}", i, e),
+                        );
+                        break;
+                    }
+                };
+                let name = entry_name(&entry);
+                if !entry.header().entry_type().is_dir() {
+                    file_count += 1;
+                    total_size += entry.size();
+                }
+                result.check_entry_name(&name);
+
+                if let Err(e) = std::io::copy(&mut entry, &mut std::io::sink()) {
+                    read_error(&mut result, format!("Cannot read {}: {}", name, e));
+                    break;
+                }
+            }
+        }
+        Err(e) => read_error(&mut result, format!("Invalid tar archive: {}", e)),
+    }
+
+    // tar stops at its end-of-archive marker, before the frame checksum
+    if result.all_files_readable {
+        if let Err(e) = std::io::copy(&mut archive.into_inner(), &mut std::io::sink()) {
+            read_error(&mut result, format!("Corrupt zstd stream: {}", e));
+        }
+    }
+
+    result.file_count = file_count;
+    result.total_size = total_size;
+    if file_count == 0 {
+        result.add_issue(VerificationIssue {
+            severity: IssueSeverity::Warning,
+            message: "Backup contains no files".to_string(),
+            file_path: None,
+        });
+    }
+
+    result
+}
+
+/// Extract a tar.zst backup to a destination directory
+pub(super) fn extract(
+    archive_path: &Path,
+    dest: &Path,
+    progress: Option<BackupProgressCallback>,
+) -> Result<()> {
+    let total_files = read_metadata(archive_path).map(|m| m.file_count);
+    let mut archive = open_archive(archive_path)?;
+
+    // Notify scanning phase
+    if let Some(ref cb) = progress {
+        cb(BackupProgress {
+            phase: BackupPhase::Scanning,
+            files_processed: 0,
+            total_files,
+            bytes_written: 0,
+            current_file: None,
+        });
+    }
+
+    std::fs::create_dir_all(dest)?;
+
+    let mut files_processed = 0usize;
+    let mut bytes_written = 0u64;
+    let mut renames = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let filename = entry_name(&entry);
+        if !is_safe_relative_path(filename.trim_end_matches('/')) {
+            continue;
+        }
+        if filename == RENAME_MAP_FILENAME {
+            renames = parse_rename_map(entry)?;
+            continue;
+        }
+
+        if let Some(ref cb) = progress {
+            cb(BackupProgress {
+                phase: BackupPhase::Archiving, // Reusing for extraction
+                files_processed,
+                total_files,
+                bytes_written,
+                current_file: Some(filename.clone()),
+            });
+        }
+
+        let outpath = dest.join(&filename);
+        if entry.header().entry_type().is_dir() {
+            std::fs::create_dir_all(&outpath)?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut outfile = File::create(&outpath)?;
+            bytes_written += std::io::copy(&mut entry, &mut outfile)?;
+        }
+
+        files_processed += 1;
+    }
+
+    // Reconstruct renamed files recorded by incremental backups
+    super::apply_rename_map(dest, &renames, |_| true)?;
+
+    // Notify complete
+    if let Some(ref cb) = progress {
+        cb(BackupProgress {
+            phase: BackupPhase::Complete,
+            files_processed,
+            total_files,
+            bytes_written,
+            current_file: None,
+        });
+    }
+
+    Ok(())
+}
+
+impl BackupManager {
+    /// [`Self::restore_backup_with_options`] for tar.zst backups
+    ///
+    /// The archive is read in a single pass, hashing each file as it is
+    /// written. Verification checks the restored files against the manifest's
+    /// content hash, or else the hash taken while extracting; mismatches are
+    /// extracted again in a second pass when requested. Without a manifest,
+    /// resumed entries are only checked by size.
+    pub(super) fn restore_tar_zstd_backup(
+        &self,
+        backup_path: &Path,
+        dest_path: &Path,
+        options: &RestoreOptions,
+        progress: Option<BackupProgressCallback>,
+    ) -> Result<RestoreResult> {
+        let total_files = read_metadata(backup_path).map(|m| m.file_count);
+        let manifest = Self::load_backup_manifest(backup_path);
+        let manifest_hash = |filename: &str| {
+            manifest
+                .as_ref()
+                .and_then(|m| m.files.get(filename))
+                .and_then(|e| e.content_hash.clone())
+        };
+
+        let mut archive = open_archive(backup_path)?;
+        let mut files_restored = 0usize;
+        let mut bytes_written = 0u64;
+        let mut extracted = Vec::new();
+        let mut renames = Vec::new();
+
+        // Create destination directory
+        std::fs::create_dir_all(dest_path)?;
+
+        // Resumable restores track finished entries in a sidecar file
+        let mut progress_log = match options.restore_mode {
+            RestoreMode::Resume => Some(RestoreProgressLog::open(dest_path, backup_path)?),
+            _ => None,
+        };
+
+        // Notify scanning phase
+        if let Some(ref cb) = progress {
+            cb(BackupProgress {
+                phase: BackupPhase::Scanning,
+                files_processed: 0,
+                total_files,
+                bytes_written: 0,
+                current_file: None,
+            });
+        }
+
+        for (i, entry) in archive.entries()?.enumerate() {
+            if let Some(token) = &self.cancellation {
+                token.check()?;
+            }
+            let mut entry = entry?;
+            let filename = entry_name(&entry);
+            if !is_safe_relative_path(filename.trim_end_matches('/')) {
+                continue;
+            }
+
+            // The rename map is applied after extraction, not restored as a file
+            if filename == RENAME_MAP_FILENAME {
+                renames = parse_rename_map(entry)?;
+                continue;
+            }
+
+            // Check if this file should be restored
+            if !options.should_restore(&filename) {
+                continue;
+            }
+
+            // Notify progress
+            if let Some(ref cb) = progress {
+                cb(BackupProgress {
+                    phase: BackupPhase::Archiving,
+                    files_processed: files_restored,
+                    total_files,
+                    bytes_written,
+                    current_file: Some(filename.clone()),
+                });
+            }
+
+            let outpath = dest_path.join(&filename);
+            if entry.header().entry_type().is_dir() {
+                std::fs::create_dir_all(&outpath)?;
+                continue;
+            }
+
+            // Entries finished by an interrupted run are kept if still intact
+            if let Some(log) = &progress_log {
+                if log.is_completed(i)
+                    && std::fs::metadata(&outpath).is_ok_and(|m| m.len() == entry.size())
+                    && verify_restored_file(&outpath, None, manifest_hash(&filename).as_deref())?
+                        .is_none()
+                {
+                    files_restored += 1;
+                    continue;
+                }
+            }
+
+            // Handle existing files based on mode
+            if !Self::prepare_restore_path(&outpath, options.restore_mode)? {
+                continue;
+            }
+
+            // Extract file
+            let mut outfile = File::create(&outpath)?;
+            let mut reader = HashingReader::new(&mut entry);
+            bytes_written += std::io::copy(&mut reader, &mut outfile)?;
+            files_restored += 1;
+
+            if let Some(log) = progress_log.as_mut() {
+                outfile.sync_all()?;
+                log.record(i)?;
+            }
+
+            if options.verify_after_restore {
+                let expected = manifest_hash(&filename).unwrap_or_else(|| reader.finalize());
+                extracted.push((outpath, filename, expected));
+            }
+        }
+
+        // Verify extracted files, extracting mismatches again if requested
+        let mut mismatches = HashMap::new();
+        for (outpath, filename, expected) in extracted {
+            if let Some(message) = verify_restored_file(&outpath, None, Some(expected.as_str()))? {
+                mismatches.insert(filename, (outpath, expected, message));
+            }
+        }
+        if !mismatches.is_empty() && options.reextract_on_mismatch {
+            reextract(backup_path, &mut mismatches)?;
+        }
+        let mut verification_issues: Vec<_> = mismatches
+            .into_iter()
+            .map(|(filename, (_, _, message))| VerificationIssue {
+                severity: IssueSeverity::Error,
+                message,
+                file_path: Some(filename),
+            })
+            .collect();
+        verification_issues.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        // Reconstruct files recorded as renames by incremental backups
+        files_restored += Self::restore_renames(dest_path, &renames, options)?;
+
+        // The restore completed, so there is nothing left to resume
+        if progress_log.take().is_some() {
+            RestoreProgressLog::remove(dest_path)?;
+        }
+
+        // Notify complete
+        if let Some(ref cb) = progress {
+            cb(BackupProgress {
+                phase: BackupPhase::Complete,
+                files_processed: files_restored,
+                total_files,
+                bytes_written,
+                current_file: None,
+            });
+        }
+
+        Ok(RestoreResult {
+            files_restored,
+            verification_issues,
+        })
+    }
+}
+
+/// Extract the mismatched files again, dropping those that now verify
+fn reextract(
+    backup_path: &Path,
+    mismatches: &mut HashMap<String, (PathBuf, String, String)>,
+) -> Result<()> {
+    let mut archive = open_archive(backup_path)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let filename = entry_name(&entry);
+        let Some((outpath, expected, _)) = mismatches.get(&filename) else {
+            continue;
+        };
+
+        let mut outfile = File::create(outpath)?;
+        std::io::copy(&mut entry, &mut outfile)?;
+        match verify_restored_file(outpath, None, Some(expected.as_str()))? {
+            Some(message) => mismatches.get_mut(&filename).unwrap().2 = message,
+            None => {
+                mismatches.remove(&filename);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{BackupFormat, BackupTarget};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_tar_zstd_backup_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let source = temp_dir.path().join("source");
+        std::fs::create_dir_all(source.join("set")).unwrap();
+        std::fs::write(source.join("set/map.osu"), vec![b'a'; 8192]).unwrap();
+        std::fs::write(source.join("set/audio.mp3"), b"audio").unwrap();
+
+        let manager = BackupManager::new(temp_dir.path().join("backups"));
+        let options = BackupOptions::new().with_format(BackupFormat::TarZstd);
+        let backup = manager
+            .create_backup_with_options(BackupTarget::StableSongs, &source, options, None)
+            .unwrap();
+        assert!(backup.to_string_lossy().ends_with(".tar.zst"));
+        assert_eq!(BackupFormat::detect(&backup), Some(BackupFormat::TarZstd));
+
+        let listed = manager.list_backups().unwrap();
+        let metadata = listed[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata.format, BackupFormat::TarZstd);
+        assert_eq!(metadata.file_count, 2);
+
+        let verification = manager.verify_backup(&backup).unwrap();
+        assert!(verification.is_restorable());
+        assert_eq!(verification.file_count, 3);
+
+        let restore_dir = temp_dir.path().join("restore");
+        let result = manager
+            .restore_backup_with_options(
+                &backup,
+                &restore_dir,
+                &RestoreOptions::all().with_verification(),
+                None,
+            )
+            .unwrap();
+        assert!(result.is_verified());
+        assert_eq!(
+            std::fs::read(restore_dir.join("set/audio.mp3")).unwrap(),
+            b"audio"
+        );
+        assert_eq!(
+            std::fs::read(restore_dir.join("set/map.osu"))
+                .unwrap()
+                .len(),
+            8192
+        );
+
+        // Encryption is zip-only
+        let encrypted = BackupOptions::new()
+            .with_format(BackupFormat::TarZstd)
+            .with_encryption("secret");
+        assert!(matches!(
+            manager.create_backup_with_options(BackupTarget::StableSongs, &source, encrypted, None),
+            Err(Error::Config(_))
+        ));
+    }
+}
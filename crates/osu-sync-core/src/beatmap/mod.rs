@@ -5,8 +5,11 @@ mod metadata;
 pub use metadata::*;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
 
 use crate::stats::RankedStatus;
+use crate::utils::SanitizeOptions;
 
 /// Represents a game mode in osu!
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -75,8 +78,72 @@ pub struct BeatmapInfo {
     pub version: String,
     /// Star rating for this difficulty (from osu! database)
     pub star_rating: Option<f32>,
+    /// Star ratings keyed by mod bitmask (from osu! database, 0 = no mods)
+    #[serde(default)]
+    pub star_ratings: HashMap<u32, f32>,
     /// Ranked status of this beatmap
     pub ranked_status: Option<RankedStatus>,
+    /// `osu file format` version of the .osu file (0 if unknown)
+    #[serde(default)]
+    pub format_version: u8,
+}
+
+impl BeatmapInfo {
+    /// Star rating with the given mods applied, if known
+    pub fn star_rating_with_mods(&self, mods: u32) -> Option<f32> {
+        star_rating_for_mods(self.star_rating, &self.star_ratings, mods)
+    }
+
+    /// Online beatmap (difficulty) ID, `None` for unsubmitted difficulties
+    pub fn online_id(&self) -> Option<i32> {
+        self.metadata.beatmap_id.filter(|&id| id > 0)
+    }
+
+    /// Link to this difficulty on the osu! website, within the set `set_id`
+    ///
+    /// Returns `None` unless both the set and the difficulty have an online
+    /// ID.
+    pub fn online_url(&self, set_id: Option<i32>) -> Option<String> {
+        let set_id = set_id.filter(|&id| id > 0)?;
+        let beatmap_id = self.online_id()?;
+        let ruleset = match self.mode {
+            GameMode::Osu => "osu",
+            GameMode::Taiko => "taiko",
+            GameMode::Catch => "fruits",
+            GameMode::Mania => "mania",
+        };
+        Some(format!(
+            "{}/{}#{}/{}",
+            BEATMAPSETS_URL, set_id, ruleset, beatmap_id
+        ))
+    }
+}
+
+/// Base URL of beatmap set pages on the osu! website
+pub const BEATMAPSETS_URL: &str = "https://osu.ppy.sh/beatmapsets";
+
+/// Mod bits that affect star rating in osu!.db (EZ, HR, DT, HT)
+pub const DIFFICULTY_MOD_MASK: u32 = 2 | 16 | 64 | 256;
+
+/// Look up a star rating for a mod combination
+///
+/// Nightcore is treated as Double Time and mods that don't change difficulty
+/// are ignored, matching the combinations osu!.db stores. Falls back to
+/// `nomod` when no mods apply.
+pub fn star_rating_for_mods(
+    nomod: Option<f32>,
+    ratings: &HashMap<u32, f32>,
+    mods: u32,
+) -> Option<f32> {
+    // Nightcore (512) implies Double Time (64)
+    let mods = if mods & 512 != 0 { mods | 64 } else { mods };
+    let mods = mods & DIFFICULTY_MOD_MASK;
+
+    if mods == 0 {
+        nomod.or_else(|| ratings.get(&0).copied())
+    } else {
+        ratings.get(&mods).copied()
+    }
 }
 
 /// A beatmap set containing multiple difficulties
@@ -88,8 +155,16 @@ pub struct BeatmapSet {
     pub beatmaps: Vec<BeatmapInfo>,
     /// All files (audio, backgrounds, videos, storyboards, etc.)
     pub files: Vec<BeatmapFile>,
-    /// Folder name in osu!stable
+    /// Folder name in osu!stable, lossily converted to UTF-8 for display
     pub folder_name: Option<String>,
+    /// Exact folder name on disk, kept only when it is not valid UTF-8
+    /// (e.g. Shift-JIS names from old installs) and `folder_name` is lossy
+    #[serde(default)]
+    pub folder_name_os: Option<OsString>,
+    /// Storyboard assets and custom hitsounds that are referenced but not
+    /// present on disk
+    #[serde(default)]
+    pub missing_files: Vec<String>,
 }
 
 impl BeatmapSet {
@@ -100,6 +175,8 @@ impl BeatmapSet {
             beatmaps: Vec::new(),
             files: Vec::new(),
             folder_name: None,
+            missing_files: Vec::new(),
+            folder_name_os: None,
         }
     }
 
@@ -108,21 +185,201 @@ impl BeatmapSet {
         self.beatmaps.first().map(|b| &b.metadata)
     }
 
+    /// Record the name of the set's folder as read from disk
+    ///
+    /// Sets `folder_name`, plus `folder_name_os` if the name is not valid
+    /// UTF-8 and the display name had to be converted lossily.
+    pub fn set_folder_name(&mut self, name: &OsStr) {
+        self.folder_name = Some(name.to_string_lossy().into_owned());
+        self.folder_name_os = match name.to_str() {
+            Some(_) => None,
+            None => Some(name.to_os_string()),
+        };
+    }
+
+    /// Folder name to use for filesystem paths
+    ///
+    /// Prefers the exact on-disk name over the lossy `folder_name`, so
+    /// folders with non-UTF-8 names can still be found.
+    pub fn disk_folder_name(&self) -> Option<&OsStr> {
+        self.folder_name_os
+            .as_deref()
+            .or_else(|| self.folder_name.as_deref().map(OsStr::new))
+    }
+
+    /// Whether the set has no online ID, e.g. an unsubmitted or local map
+    ///
+    /// Lazer stores `-1` for sets without an ID, so non-positive IDs count
+    /// as missing.
+    pub fn missing_online_id(&self) -> bool {
+        !self.id.is_some_and(|id| id > 0)
+    }
+
+    /// Link to the set's page on the osu! website, if it has an online ID
+    pub fn online_url(&self) -> Option<String> {
+        self.id
+            .filter(|&id| id > 0)
+            .map(|id| format!("{}/{}", BEATMAPSETS_URL, id))
+    }
+
     /// Generate a folder name in osu!stable format: "{SetID} {Artist} - {Title}"
     pub fn generate_folder_name(&self) -> String {
+        self.generate_folder_name_with(&SanitizeOptions::default())
+    }
+
+    /// Generate a folder name like [`generate_folder_name`](Self::generate_folder_name),
+    /// sanitized with the given rules
+    pub fn generate_folder_name_with(&self, options: &SanitizeOptions) -> String {
         if let Some(meta) = self.metadata() {
             let id_prefix = self.id.map(|id| format!("{} ", id)).unwrap_or_default();
-            let artist = meta
-                .artist
-                .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
-            let title = meta
-                .title
-                .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
-            format!("{}{} - {}", id_prefix, artist, title)
+            options.sanitize(&format!("{}{} - {}", id_prefix, meta.artist, meta.title))
         } else {
             "Unknown Beatmap".to_string()
         }
     }
+
+    /// Remove difficulties whose .osu content hash duplicates an earlier one
+    ///
+    /// Keeps the first occurrence of each hash; difficulties without a hash
+    /// are never removed. Returns the number of difficulties removed.
+    pub fn dedup_difficulties(&mut self) -> usize {
+        let before = self.beatmaps.len();
+        let mut seen = std::collections::HashSet::new();
+        self.beatmaps
+            .retain(|b| b.hash.is_empty() || seen.insert(b.hash.clone()));
+        before - self.beatmaps.len()
+    }
+
+    /// Difficulties ordered by game mode, then ascending star rating
+    ///
+    /// Difficulties without a star rating sort after rated ones in the same
+    /// mode and are ordered by overall difficulty, then circle size.
+    pub fn sorted_difficulties(&self) -> Vec<&BeatmapInfo> {
+        let mut sorted: Vec<&BeatmapInfo> = self.beatmaps.iter().collect();
+        sorted.sort_by(|a, b| {
+            (a.mode as u8)
+                .cmp(&(b.mode as u8))
+                .then_with(|| a.star_rating.is_none().cmp(&b.star_rating.is_none()))
+                .then_with(|| {
+                    a.star_rating
+                        .unwrap_or_default()
+                        .total_cmp(&b.star_rating.unwrap_or_default())
+                })
+                .then_with(|| {
+                    a.difficulty
+                        .overall_difficulty
+                        .total_cmp(&b.difficulty.overall_difficulty)
+                })
+                .then_with(|| {
+                    a.difficulty
+                        .circle_size
+                        .total_cmp(&b.difficulty.circle_size)
+                })
+        });
+        sorted
+    }
+
+    /// Minimum and maximum star rating across all rated difficulties
+    pub fn difficulty_range(&self) -> Option<(f32, f32)> {
+        self.beatmaps
+            .iter()
+            .filter_map(|b| b.star_rating)
+            .fold(None, |range, stars| match range {
+                None => Some((stars, stars)),
+                Some((min, max)) => Some((min.min(stars), max.max(stars))),
+            })
+    }
+
+    /// Combine the difficulties and files of two copies of the same set
+    ///
+    /// The copy with an online ID supplies the set ID, folder name and the
+    /// order of difficulties; `self` wins if both or neither have one.
+    /// Difficulties are deduplicated by MD5, which stable and lazer agree on,
+    /// falling back to the content hash. Files are deduplicated by content
+    /// hash, then by file name so no two files share a name. A difficulty
+    /// whose .osu file name is already taken by a different file of the
+    /// preferred copy is dropped along with its file.
+    pub fn merge(&self, other: &BeatmapSet) -> BeatmapSet {
+        let (base, extra) = if self.merge_prefers(other) {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        let mut merged = base.clone();
+        merged.id = base.id.or(extra.id);
+        if base.folder_name.is_none() {
+            merged.folder_name = extra.folder_name.clone();
+            merged.folder_name_os = extra.folder_name_os.clone();
+        }
+
+        let difficulty_key = |b: &BeatmapInfo| {
+            if b.md5_hash.is_empty() {
+                b.hash.clone()
+            } else {
+                b.md5_hash.clone()
+            }
+        };
+        let mut hashes: std::collections::HashSet<String> = merged
+            .files
+            .iter()
+            .map(|f| f.hash.clone())
+            .filter(|h| !h.is_empty())
+            .collect();
+        let mut names: std::collections::HashSet<String> = merged
+            .files
+            .iter()
+            .map(|f| f.filename.to_lowercase())
+            .collect();
+
+        // Each copy hashes its difficulties the same way as its own files
+        let name_taken = |beatmap: &BeatmapInfo| {
+            !beatmap.hash.is_empty()
+                && extra
+                    .files
+                    .iter()
+                    .find(|f| f.hash == beatmap.hash)
+                    .is_some_and(|f| {
+                        !hashes.contains(&f.hash) && names.contains(&f.filename.to_lowercase())
+                    })
+        };
+        let mut seen: std::collections::HashSet<String> = merged
+            .beatmaps
+            .iter()
+            .map(difficulty_key)
+            .filter(|k| !k.is_empty())
+            .collect();
+        for beatmap in &extra.beatmaps {
+            let key = difficulty_key(beatmap);
+            if (key.is_empty() || seen.insert(key)) && !name_taken(beatmap) {
+                merged.beatmaps.push(beatmap.clone());
+            }
+        }
+
+        for file in &extra.files {
+            if !file.hash.is_empty() && hashes.contains(&file.hash) {
+                continue;
+            }
+            if names.insert(file.filename.to_lowercase()) {
+                hashes.insert(file.hash.clone());
+                merged.files.push(file.clone());
+            }
+        }
+
+        let mut missing = merged.missing_files.clone();
+        missing.extend(extra.missing_files.iter().cloned());
+        missing.sort();
+        missing.dedup();
+        missing.retain(|m| !names.contains(&m.to_lowercase()));
+        merged.missing_files = missing;
+
+        merged
+    }
+
+    /// Whether [`merge`](Self::merge) prefers `other` over `self`, i.e. only
+    /// `other` has an online ID
+    pub fn merge_prefers(&self, other: &BeatmapSet) -> bool {
+        self.id.is_none() && other.id.is_some()
+    }
 }
 
 impl Default for BeatmapSet {
@@ -130,3 +387,180 @@ impl Default for BeatmapSet {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(mode: GameMode, stars: Option<f32>, od: f32) -> BeatmapInfo {
+        BeatmapInfo {
+            mode,
+            star_rating: stars,
+            difficulty: BeatmapDifficulty {
+                overall_difficulty: od,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn difficulty(md5: &str, version: &str) -> BeatmapInfo {
+        BeatmapInfo {
+            md5_hash: md5.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn file(name: &str, hash: &str) -> BeatmapFile {
+        BeatmapFile {
+            filename: name.to_string(),
+            hash: hash.to_string(),
+            size: 1,
+        }
+    }
+
+    #[test]
+    fn test_merge_sets() {
+        let mut local = BeatmapSet::new();
+        local.folder_name = Some("Artist - Title".to_string());
+        local.beatmaps = vec![
+            difficulty("easy", "Easy"),
+            difficulty("guest", "Guest's Hard"),
+        ];
+        local.files = vec![
+            file("audio.mp3", "a"),
+            file("easy.osu", "easy"),
+            file("guest.osu", "guest"),
+        ];
+
+        let mut online = BeatmapSet::new();
+        online.id = Some(42);
+        online.folder_name = Some("42 Artist - Title".to_string());
+        online.beatmaps = vec![difficulty("easy", "Easy"), difficulty("insane", "Insane")];
+        online.files = vec![
+            file("audio.mp3", "a"),
+            file("Easy.osu", "easy"),
+            file("insane.osu", "insane"),
+            file("bg.jpg", "bg"),
+        ];
+
+        let merged = local.merge(&online);
+        assert_eq!(merged.id, Some(42));
+        assert_eq!(merged.folder_name.as_deref(), Some("42 Artist - Title"));
+
+        let versions: Vec<&str> = merged.beatmaps.iter().map(|b| b.version.as_str()).collect();
+        assert_eq!(versions, vec!["Easy", "Insane", "Guest's Hard"]);
+
+        let names: Vec<&str> = merged.files.iter().map(|f| f.filename.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["audio.mp3", "Easy.osu", "insane.osu", "bg.jpg", "guest.osu"]
+        );
+    }
+
+    #[test]
+    fn test_merge_drops_difficulty_with_colliding_filename() {
+        let mut local = BeatmapSet::new();
+        local.beatmaps = vec![BeatmapInfo {
+            hash: "hard-local".to_string(),
+            ..difficulty("hard-local", "Hard")
+        }];
+        local.files = vec![file("hard.osu", "hard-local")];
+
+        let mut other = BeatmapSet::new();
+        other.beatmaps = vec![BeatmapInfo {
+            hash: "hard-edited".to_string(),
+            ..difficulty("hard-edited", "Hard (edited)")
+        }];
+        other.files = vec![file("Hard.osu", "hard-edited")];
+
+        let merged = local.merge(&other);
+        let versions: Vec<&str> = merged.beatmaps.iter().map(|b| b.version.as_str()).collect();
+        assert_eq!(versions, vec!["Hard"]);
+        let names: Vec<&str> = merged.files.iter().map(|f| f.filename.as_str()).collect();
+        assert_eq!(names, vec!["hard.osu"]);
+    }
+
+    #[test]
+    fn test_sorted_difficulties_and_range() {
+        let mut set = BeatmapSet::new();
+        set.beatmaps = vec![
+            diff(GameMode::Taiko, Some(2.0), 5.0),
+            diff(GameMode::Osu, None, 4.0),
+            diff(GameMode::Osu, Some(6.8), 9.0),
+            diff(GameMode::Osu, None, 2.0),
+            diff(GameMode::Osu, Some(3.2), 6.0),
+        ];
+
+        let order: Vec<(GameMode, Option<f32>, f32)> = set
+            .sorted_difficulties()
+            .iter()
+            .map(|b| (b.mode, b.star_rating, b.difficulty.overall_difficulty))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (GameMode::Osu, Some(3.2), 6.0),
+                (GameMode::Osu, Some(6.8), 9.0),
+                (GameMode::Osu, None, 2.0),
+                (GameMode::Osu, None, 4.0),
+                (GameMode::Taiko, Some(2.0), 5.0),
+            ]
+        );
+        assert_eq!(set.difficulty_range(), Some((2.0, 6.8)));
+        assert_eq!(BeatmapSet::new().difficulty_range(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_folder_name() {
+        use std::os::unix::ffi::OsStrExt;
+
+        // "曲" in Shift-JIS
+        let raw = OsStr::from_bytes(b"1 \x8b\xc8");
+        let mut set = BeatmapSet::new();
+        set.set_folder_name(raw);
+        assert_eq!(set.folder_name.as_deref(), Some("1 \u{FFFD}\u{FFFD}"));
+        assert_eq!(set.disk_folder_name(), Some(raw));
+
+        set.set_folder_name(OsStr::new("1 Artist - Title"));
+        assert!(set.folder_name_os.is_none());
+        assert_eq!(set.disk_folder_name(), Some(OsStr::new("1 Artist - Title")));
+    }
+
+    #[test]
+    fn test_missing_online_id() {
+        let mut set = BeatmapSet::new();
+        assert!(set.missing_online_id());
+        set.id = Some(-1);
+        assert!(set.missing_online_id());
+        set.id = Some(1);
+        assert!(!set.missing_online_id());
+    }
+
+    #[test]
+    fn test_online_url() {
+        let mut set = BeatmapSet::new();
+        let mut beatmap = diff(GameMode::Catch, None, 5.0);
+        beatmap.metadata.beatmap_id = Some(75);
+        assert_eq!(set.online_url(), None);
+        assert_eq!(beatmap.online_url(set.id), None);
+
+        set.id = Some(-1);
+        assert_eq!(set.online_url(), None);
+
+        set.id = Some(1);
+        assert_eq!(
+            set.online_url().as_deref(),
+            Some("https://osu.ppy.sh/beatmapsets/1")
+        );
+        assert_eq!(
+            beatmap.online_url(set.id).as_deref(),
+            Some("https://osu.ppy.sh/beatmapsets/1#fruits/75")
+        );
+
+        beatmap.metadata.beatmap_id = None;
+        assert_eq!(beatmap.online_url(set.id), None);
+    }
+}
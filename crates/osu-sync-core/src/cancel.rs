@@ -0,0 +1,83 @@
+//! Cooperative cancellation for long-running operations
+//!
+//! A [`CancellationToken`] is shared between the caller and an operation; the
+//! operation polls it at safe points and stops with [`Error::Cancelled`].
+//! Cancellation is checked:
+//!
+//! - by [`SyncEngine`](crate::sync::SyncEngine) before each beatmap set during
+//!   syncs and dry runs (watch mode returns normally instead)
+//! - by [`BackupManager`](crate::backup::BackupManager) before each file is
+//!   archived or restored; a partially written backup file is removed
+//! - by [`MediaExtractor`](crate::media::MediaExtractor) before each beatmap set
+//! - by [`ReplayExporter`](crate::replay::ReplayExporter) before each replay
+//!
+//! Files already written when cancellation is noticed are left in place.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+
+/// Shared flag for requesting that an operation stop
+///
+/// Clones share the same flag, so one clone can be handed to an operation
+/// while another is kept to cancel it from a different thread. Operations
+/// that notice the cancellation return [`Error::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Clear a previous cancellation so the token can be reused
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Return [`Error::Cancelled`] if cancellation has been requested
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_state() {
+        let token = CancellationToken::new();
+        let shared = token.clone();
+        assert!(shared.check().is_ok());
+
+        token.cancel();
+        assert!(shared.is_cancelled());
+        assert!(matches!(shared.check(), Err(Error::Cancelled)));
+
+        shared.reset();
+        assert!(!token.is_cancelled());
+    }
+}
@@ -0,0 +1,289 @@
+//! Export of collections to playlist files
+//!
+//! Collections only store beatmap MD5s, which mean nothing outside osu!. An
+//! extended M3U playlist resolves each hash to its audio file so a collection
+//! can be played in a media player, while keeping the hashes as comments so
+//! the collection can be rebuilt from the playlist on another machine.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::Collection;
+use crate::error::{Error, Result};
+use crate::media::{ExtractionResult, MediaExtractor, MediaType};
+use crate::stable::BeatmapIndex;
+use crate::utils::sanitize_filename;
+
+/// Comment prefix recording a beatmap MD5 in an exported playlist
+pub const M3U_MD5_PREFIX: &str = "#OSU-MD5:";
+
+/// Write one `.m3u8` playlist per collection into `dir`
+///
+/// Hashes are resolved through `index` to audio files inside `songs_dir`.
+/// Every hash is written as a [`M3U_MD5_PREFIX`] comment; an audio file is
+/// listed once even if several difficulties of a collection share it, and
+/// hashes that are not installed only get the comment. Collections with the
+/// same name get numbered file names. Returns the paths written.
+pub fn collections_to_m3u(
+    collections: &[Collection],
+    index: &BeatmapIndex,
+    songs_dir: &Path,
+    dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).map_err(|e| Error::io_at(e, dir))?;
+
+    let mut used = HashSet::new();
+    let mut written = Vec::with_capacity(collections.len());
+
+    for collection in collections {
+        let base = sanitize_filename(&collection.name);
+        let mut name = base.clone();
+        let mut n = 1;
+        while !used.insert(name.to_lowercase()) {
+            name = format!("{} ({})", base, n);
+            n += 1;
+        }
+
+        let path = dir.join(format!("{}.m3u8", name));
+        let file = File::create(&path).map_err(|e| Error::io_at(e, &path))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "#EXTM3U")?;
+        writeln!(writer, "#PLAYLIST:{}", collection.name)?;
+
+        let mut listed = HashSet::new();
+        for hash in &collection.beatmap_hashes {
+            writeln!(writer, "{}{}", M3U_MD5_PREFIX, hash)?;
+
+            let Some((set, beatmap)) = index.get_by_md5(&hash.to_lowercase()) else {
+                continue;
+            };
+            let Some(folder) = set.disk_folder_name() else {
+                continue;
+            };
+            if beatmap.audio_file.is_empty() {
+                continue;
+            }
+            let audio = songs_dir.join(folder).join(&beatmap.audio_file);
+            if !listed.insert(audio.clone()) {
+                continue;
+            }
+            writeln!(
+                writer,
+                "#EXTINF:{},{} - {}",
+                beatmap.length_ms / 1000,
+                beatmap.metadata.artist,
+                beatmap.metadata.title
+            )?;
+            writeln!(writer, "{}", audio.display())?;
+        }
+
+        writer.flush()?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Audio and playlist written by [`export_collection_audio`]
+#[derive(Debug, Clone, Default)]
+pub struct PlaylistExport {
+    /// The `.m3u8` playlist
+    pub playlist: PathBuf,
+    /// Audio files copied into the output folder, in play order
+    pub tracks: Vec<PathBuf>,
+    /// Total bytes copied
+    pub bytes_written: u64,
+    /// MD5s with no installed beatmap or audio file, left out of the playlist
+    pub missing: Vec<String>,
+}
+
+/// Copy a collection's audio into `dir` and write a playlist of it there
+///
+/// Hashes are resolved through `index` in collection order. Each audio file
+/// is copied once by [`MediaExtractor`], as `Artist - Title.ext`, even if
+/// several difficulties share it, so exporting into the same folder again
+/// skips unchanged tracks. The playlist refers to the copies by relative path
+/// so the folder can be moved as a whole, and is only written once every
+/// track is copied. Like [`collections_to_m3u`], every hash is kept as a
+/// [`M3U_MD5_PREFIX`] comment; hashes that can't be resolved to an audio file
+/// are only listed in [`PlaylistExport::missing`].
+pub fn export_collection_audio(
+    collection: &Collection,
+    index: &BeatmapIndex,
+    songs_dir: &Path,
+    dir: &Path,
+) -> Result<PlaylistExport> {
+    fs::create_dir_all(dir).map_err(|e| Error::io_at(e, dir))?;
+
+    // Each hash with the audio file it is the first to list, if any
+    let mut entries = Vec::with_capacity(collection.beatmap_hashes.len());
+    let mut audio_files = Vec::new();
+    let mut listed = HashSet::new();
+    let mut missing = Vec::new();
+
+    for hash in &collection.beatmap_hashes {
+        let resolved = index
+            .get_by_md5(&hash.to_lowercase())
+            .and_then(|(set, beatmap)| {
+                let folder = set.disk_folder_name()?;
+                let audio = songs_dir.join(folder).join(&beatmap.audio_file);
+                (!beatmap.audio_file.is_empty() && audio.is_file()).then_some((audio, beatmap))
+            });
+        let track = match resolved {
+            Some((audio, beatmap)) if listed.insert(audio.clone()) => {
+                audio_files.push((audio, beatmap));
+                Some(audio_files.len() - 1)
+            }
+            Some(_) => None,
+            None => {
+                missing.push(hash.clone());
+                None
+            }
+        };
+        entries.push((hash, track));
+    }
+
+    // Shared audio files are listed once, so the extractor must not skip
+    // any as duplicates
+    let mut result = ExtractionResult::new();
+    let tracks = MediaExtractor::new(dir)
+        .with_media_type(MediaType::Audio)
+        .with_skip_duplicates(false)
+        .extract_audio_files(&audio_files, &mut result)?;
+
+    let mut content = format!("#EXTM3U\n#PLAYLIST:{}\n", collection.name);
+    for (hash, track) in entries {
+        content.push_str(&format!("{}{}\n", M3U_MD5_PREFIX, hash));
+        let Some(i) = track else {
+            continue;
+        };
+        let beatmap = audio_files[i].1;
+        let name = tracks[i]
+            .strip_prefix(dir)
+            .unwrap_or(&tracks[i])
+            .to_string_lossy();
+        content.push_str(&format!(
+            "#EXTINF:{},{} - {}\n{}\n",
+            beatmap.length_ms / 1000,
+            beatmap.metadata.artist,
+            beatmap.metadata.title,
+            name
+        ));
+    }
+
+    // Written next to the playlist and renamed over it, so a failed write
+    // leaves no partial playlist behind
+    let playlist = dir.join(format!("{}.m3u8", sanitize_filename(&collection.name)));
+    let tmp_path = playlist.with_extension("m3u8.tmp");
+    fs::write(&tmp_path, content).map_err(|e| Error::io_at(e, &tmp_path))?;
+    fs::rename(&tmp_path, &playlist).map_err(|e| Error::io_at(e, &playlist))?;
+
+    Ok(PlaylistExport {
+        playlist,
+        tracks,
+        bytes_written: result.bytes_written,
+        missing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beatmap::{BeatmapInfo, BeatmapMetadata, BeatmapSet};
+    use tempfile::TempDir;
+
+    fn beatmap(md5: &str, version: &str) -> BeatmapInfo {
+        BeatmapInfo {
+            metadata: BeatmapMetadata {
+                artist: "Artist".to_string(),
+                title: "Title".to_string(),
+                ..Default::default()
+            },
+            md5_hash: md5.to_string(),
+            audio_file: "audio.mp3".to_string(),
+            length_ms: 90_500,
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collections_to_m3u() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs = temp_dir.path().join("Songs");
+
+        let mut set = BeatmapSet::new();
+        set.folder_name = Some("1 Artist - Title".to_string());
+        set.beatmaps.push(beatmap("aaa", "Easy"));
+        set.beatmaps.push(beatmap("bbb", "Hard"));
+        let index = BeatmapIndex::new(vec![set]);
+
+        let collections = vec![
+            Collection::with_hashes(
+                "Fav/orites",
+                vec!["AAA".to_string(), "bbb".to_string(), "ccc".to_string()],
+            ),
+            Collection::new("Fav/orites"),
+        ];
+
+        let out = temp_dir.path().join("playlists");
+        let written = collections_to_m3u(&collections, &index, &songs, &out).unwrap();
+        assert_eq!(
+            written,
+            vec![out.join("Fav_orites.m3u8"), out.join("Fav_orites (1).m3u8")]
+        );
+
+        let audio = songs.join("1 Artist - Title").join("audio.mp3");
+        let content = std::fs::read_to_string(&written[0]).unwrap();
+        let expected = format!(
+            "#EXTM3U\n#PLAYLIST:Fav/orites\n#OSU-MD5:AAA\n#EXTINF:90,Artist - Title\n{}\n#OSU-MD5:bbb\n#OSU-MD5:ccc\n",
+            audio.display()
+        );
+        assert_eq!(content, expected);
+    }
+
+    #[test]
+    fn test_export_collection_audio() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs = temp_dir.path().join("Songs");
+        let folder = songs.join("1 Artist - Title");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("audio.mp3"), b"mp3 data").unwrap();
+
+        let mut set = BeatmapSet::new();
+        set.folder_name = Some("1 Artist - Title".to_string());
+        set.beatmaps.push(beatmap("aaa", "Easy"));
+        set.beatmaps.push(beatmap("bbb", "Hard"));
+        let index = BeatmapIndex::new(vec![set]);
+
+        let favourites = Collection::with_hashes(
+            "Favourites",
+            vec!["ccc".to_string(), "AAA".to_string(), "bbb".to_string()],
+        );
+        let out = temp_dir.path().join("export");
+        let export = export_collection_audio(&favourites, &index, &songs, &out).unwrap();
+
+        assert_eq!(export.tracks, vec![out.join("Artist - Title.mp3")]);
+        assert_eq!(export.missing, vec!["ccc".to_string()]);
+        assert_eq!(export.bytes_written, 8);
+        assert_eq!(fs::read(&export.tracks[0]).unwrap(), b"mp3 data");
+
+        let content = fs::read_to_string(out.join("Favourites.m3u8")).unwrap();
+        assert_eq!(
+            content,
+            "#EXTM3U\n#PLAYLIST:Favourites\n#OSU-MD5:ccc\n#OSU-MD5:AAA\n#EXTINF:90,Artist - Title\nArtist - Title.mp3\n#OSU-MD5:bbb\n"
+        );
+        assert!(!out.join("Favourites.m3u8.tmp").exists());
+
+        // Exporting again keeps the copied track
+        let again = export_collection_audio(&favourites, &index, &songs, &out).unwrap();
+        assert_eq!(again.tracks, export.tracks);
+        assert_eq!(again.bytes_written, 0);
+        assert_eq!(
+            fs::read_to_string(out.join("Favourites.m3u8")).unwrap(),
+            content
+        );
+    }
+}
@@ -3,10 +3,16 @@
 //! Provides functionality for reading and syncing beatmap collections between
 //! osu!stable and osu!lazer installations.
 
+pub mod export;
 pub mod model;
 pub mod stable_reader;
+pub mod stable_writer;
+pub mod state;
 pub mod sync;
 
+pub use export::{collections_to_m3u, export_collection_audio, PlaylistExport, M3U_MD5_PREFIX};
 pub use model::*;
 pub use stable_reader::StableCollectionReader;
+pub use stable_writer::StableCollectionWriter;
+pub use state::CollectionSyncState;
 pub use sync::CollectionSyncEngine;
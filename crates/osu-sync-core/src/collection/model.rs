@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::error::{Error, Result};
+
 /// A beatmap collection containing a name and list of beatmap MD5 hashes
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Collection {
@@ -38,6 +40,32 @@ impl Collection {
     pub fn is_empty(&self) -> bool {
         self.beatmap_hashes.is_empty()
     }
+
+    /// Find a collection by name, ignoring case and surrounding whitespace
+    ///
+    /// "Favourites" and "Favorites" match each other, since the collection
+    /// is spelled both ways.
+    pub fn find<'a>(collections: &'a [Collection], name: &str) -> Option<&'a Collection> {
+        let is_favorites =
+            |n: &str| n.eq_ignore_ascii_case("Favourites") || n.eq_ignore_ascii_case("Favorites");
+        let name = name.trim();
+        collections.iter().find(|c| {
+            let candidate = c.name.trim();
+            candidate.eq_ignore_ascii_case(name) || (is_favorites(name) && is_favorites(candidate))
+        })
+    }
+
+    /// Serialize to pretty-printed JSON for sharing without collection.db
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize collection: {}", e)))
+    }
+
+    /// Parse a collection written by [`Collection::to_json`]
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| Error::Other(format!("Failed to parse collection JSON: {}", e)))
+    }
 }
 
 /// Strategy for syncing collections
@@ -93,6 +121,17 @@ pub struct CollectionSyncResult {
     pub success: bool,
     /// Error message if sync failed
     pub error_message: Option<String>,
+    /// Beatmaps a merge added, for each collection that gained any
+    #[serde(default)]
+    pub changes: Vec<CollectionChange>,
+    /// Whether the write was skipped because the source collections are
+    /// the same as at the last sync
+    #[serde(default)]
+    pub unchanged: bool,
+    /// Collections the target already had with different beatmaps, found
+    /// before writing
+    #[serde(default)]
+    pub conflicts: Vec<CollectionConflict>,
 }
 
 impl CollectionSyncResult {
@@ -110,6 +149,18 @@ impl CollectionSyncResult {
             missing_beatmaps,
             success: true,
             error_message: None,
+            changes: Vec::new(),
+            unchanged: false,
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// Create a result for a sync skipped because nothing changed
+    pub fn unchanged() -> Self {
+        Self {
+            success: true,
+            unchanged: true,
+            ..Default::default()
         }
     }
 
@@ -128,6 +179,86 @@ impl CollectionSyncResult {
     }
 }
 
+/// Beatmaps a merge added to one collection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionChange {
+    /// Name of the collection
+    pub name: String,
+    /// MD5 hashes added to it, in source order
+    pub added: Vec<String>,
+}
+
+impl fmt::Display for CollectionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: +{}", self.name, self.added.len())
+    }
+}
+
+/// A collection with the same name in stable and lazer but different beatmaps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionConflict {
+    /// Name shared by both copies
+    pub name: String,
+    /// Beatmap MD5s of the stable copy
+    pub stable: Vec<String>,
+    /// Beatmap MD5s of the lazer copy
+    pub lazer: Vec<String>,
+    /// MD5s only the stable copy has, in stable order
+    pub only_in_stable: Vec<String>,
+    /// MD5s only the lazer copy has, in lazer order
+    pub only_in_lazer: Vec<String>,
+}
+
+impl CollectionConflict {
+    /// The collection both sides end up with after `action`
+    pub fn resolve(&self, action: CollectionConflictAction) -> Collection {
+        let hashes = match action {
+            CollectionConflictAction::Merge => {
+                let mut hashes = self.stable.clone();
+                hashes.extend(self.only_in_lazer.iter().cloned());
+                hashes
+            }
+            CollectionConflictAction::ReplaceStable => self.lazer.clone(),
+            CollectionConflictAction::ReplaceLazer => self.stable.clone(),
+        };
+        Collection::with_hashes(self.name.clone(), hashes)
+    }
+}
+
+impl fmt::Display for CollectionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} only in stable, {} only in lazer",
+            self.name,
+            self.only_in_stable.len(),
+            self.only_in_lazer.len()
+        )
+    }
+}
+
+/// How to settle a [`CollectionConflict`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CollectionConflictAction {
+    /// Give both sides every beatmap either of them has
+    #[default]
+    Merge,
+    /// Overwrite the stable copy with the lazer one
+    ReplaceStable,
+    /// Overwrite the lazer copy with the stable one
+    ReplaceLazer,
+}
+
+impl fmt::Display for CollectionConflictAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionConflictAction::Merge => write!(f, "Merge"),
+            CollectionConflictAction::ReplaceStable => write!(f, "Replace stable"),
+            CollectionConflictAction::ReplaceLazer => write!(f, "Replace lazer"),
+        }
+    }
+}
+
 /// Progress information during collection sync
 #[derive(Debug, Clone, Default)]
 pub struct CollectionSyncProgress {
@@ -162,6 +293,32 @@ impl CollectionSyncProgress {
     }
 }
 
+/// How much of a collection is installed locally
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionReport {
+    /// Name of the collection
+    pub name: String,
+    /// Number of beatmap hashes in the collection
+    pub total: usize,
+    /// Number of hashes that resolve to an installed beatmap
+    pub present: usize,
+    /// Hashes with no installed beatmap, in collection order
+    pub missing: Vec<String>,
+}
+
+impl CollectionReport {
+    /// Check if every beatmap in the collection is installed
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for CollectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}/{} present", self.name, self.present, self.total)
+    }
+}
+
 /// Preview information for a single collection before sync
 #[derive(Debug, Clone, Default)]
 pub struct CollectionPreviewItem {
@@ -197,3 +354,21 @@ pub struct CollectionSyncPreview {
     /// Message explaining manual steps if required
     pub manual_steps_message: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_json_round_trip() {
+        let collection =
+            Collection::with_hashes("Favorites", vec!["h1".to_string(), "h2".to_string()]);
+
+        let json = collection.to_json().unwrap();
+        let parsed = Collection::from_json(&json).unwrap();
+
+        assert_eq!(parsed.name, "Favorites");
+        assert_eq!(parsed.beatmap_hashes, collection.beatmap_hashes);
+        assert!(Collection::from_json("not json").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Writer for osu!stable's collection.db binary format
+//!
+//! Produces the layout read by [`StableCollectionReader`](super::StableCollectionReader).
+//! osu!lazer imports the same format.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use super::Collection;
+use crate::error::{Error, Result};
+
+/// Format version written to new files, as used by current osu!stable builds
+const COLLECTION_DB_VERSION: i32 = 20150203;
+
+/// Writer for osu!stable collection.db files
+pub struct StableCollectionWriter;
+
+impl StableCollectionWriter {
+    /// Write `collections` to a collection.db at `path`
+    ///
+    /// The file is written next to `path` first and renamed over it, so a
+    /// failed write leaves the previous file intact.
+    pub fn write<P: AsRef<Path>>(path: P, collections: &[Collection]) -> Result<()> {
+        let path = path.as_ref();
+        let mut data = Vec::new();
+        Self::serialize(&mut data, collections)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+        }
+        let tmp_path = path.with_extension("db.tmp");
+        fs::write(&tmp_path, &data).map_err(|e| Error::io_at(e, &tmp_path))?;
+        fs::rename(&tmp_path, path).map_err(|e| Error::io_at(e, path))
+    }
+
+    /// Serialize collections in the collection.db binary format
+    fn serialize<W: Write>(writer: &mut W, collections: &[Collection]) -> Result<()> {
+        writer.write_all(&COLLECTION_DB_VERSION.to_le_bytes())?;
+        Self::write_i32(writer, collections.len())?;
+        for collection in collections {
+            Self::write_string(writer, &collection.name)?;
+            Self::write_i32(writer, collection.beatmap_hashes.len())?;
+            for hash in &collection.beatmap_hashes {
+                Self::write_string(writer, hash)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a count as a little-endian i32
+    fn write_i32<W: Write>(writer: &mut W, value: usize) -> Result<()> {
+        let value = i32::try_from(value)
+            .map_err(|_| Error::Other("Too many entries for collection.db".to_string()))?;
+        writer.write_all(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Write an osu! format string (0x0b, ULEB128 length, UTF-8 bytes)
+    fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+        if value.is_empty() {
+            writer.write_all(&[0x00])?;
+            return Ok(());
+        }
+        writer.write_all(&[0x0b])?;
+        let mut length = value.len();
+        loop {
+            let mut byte = (length & 0x7F) as u8;
+            length >>= 7;
+            if length != 0 {
+                byte |= 0x80;
+            }
+            writer.write_all(&[byte])?;
+            if length == 0 {
+                break;
+            }
+        }
+        writer.write_all(value.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::StableCollectionReader;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_round_trips_through_reader() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("collection.db");
+        let long_name = "x".repeat(200);
+        let collections = vec![
+            Collection::with_hashes("Favourites", vec!["a".repeat(32), "b".repeat(32)]),
+            Collection::with_hashes(long_name, vec!["c".repeat(32)]),
+            Collection::new("Empty"),
+        ];
+
+        StableCollectionWriter::write(&path, &collections).unwrap();
+
+        let entries = |collections: &[Collection]| -> Vec<(String, Vec<String>)> {
+            collections
+                .iter()
+                .map(|c| (c.name.clone(), c.beatmap_hashes.clone()))
+                .collect()
+        };
+        let read = StableCollectionReader::read(&path).unwrap();
+        assert_eq!(entries(&read), entries(&collections));
+        assert!(!path.with_extension("db.tmp").exists());
+    }
+}
@@ -0,0 +1,92 @@
+//! Checksums of the collections at the last successful sync
+//!
+//! Lets [`CollectionSyncEngine::sync_if_changed`](super::CollectionSyncEngine::sync_if_changed)
+//! skip writing the target when the source collections are the same as last
+//! time.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::CollectionSyncDirection;
+use crate::config::paths;
+use crate::error::{Error, Result};
+
+/// Last synced checksum per sync direction
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CollectionSyncState {
+    /// Checksum by direction key
+    checksums: HashMap<String, String>,
+    /// File the state is saved to; `None` keeps it in memory only
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl CollectionSyncState {
+    /// Create an empty state that is never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Where the state is stored by default, under the app data directory
+    pub fn default_path() -> Option<PathBuf> {
+        paths::app_data_dir().map(|p| p.join("collection_sync.json"))
+    }
+
+    /// Load the state from [`default_path`](Self::default_path)
+    pub fn load() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::open(path),
+            None => Self::new(),
+        }
+    }
+
+    /// Load the state stored at `path`
+    ///
+    /// A missing or unreadable file gives an empty state, so the next sync
+    /// always writes.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut state: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        state.path = Some(path);
+        state
+    }
+
+    /// Checksum recorded by the last successful sync in `direction`
+    pub fn last_checksum(&self, direction: CollectionSyncDirection) -> Option<&str> {
+        self.checksums
+            .get(direction_key(direction))
+            .map(String::as_str)
+    }
+
+    /// Remember `checksum` as synced in `direction`
+    pub fn record(&mut self, direction: CollectionSyncDirection, checksum: String) {
+        self.checksums
+            .insert(direction_key(direction).to_string(), checksum);
+    }
+
+    /// Write the state to its file
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to save collection sync state: {}", e)))?;
+        fs::write(path, content).map_err(|e| Error::io_at(e, path))
+    }
+}
+
+fn direction_key(direction: CollectionSyncDirection) -> &'static str {
+    match direction {
+        CollectionSyncDirection::StableToLazer => "stable_to_lazer",
+        CollectionSyncDirection::LazerToStable => "lazer_to_stable",
+    }
+}
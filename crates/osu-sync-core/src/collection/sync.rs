@@ -1,16 +1,19 @@
 //! Collection synchronization engine
 //!
 //! Handles syncing collections between osu!stable and osu!lazer.
-//! Note: osu!lazer uses a Realm database which requires special handling.
-//! Currently, lazer sync is not implemented and returns a placeholder message.
+//! Note: osu!lazer keeps collections in a Realm database that can't be
+//! written from outside, so syncing to lazer produces a collection.db for the
+//! user to import. Syncing from lazer is not implemented yet.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use super::{
-    Collection, CollectionPreviewItem, CollectionSyncDirection, CollectionSyncResult,
-    CollectionSyncStrategy,
+    Collection, CollectionChange, CollectionConflict, CollectionConflictAction,
+    CollectionPreviewItem, CollectionReport, CollectionSyncDirection, CollectionSyncResult,
+    CollectionSyncState, CollectionSyncStrategy, StableCollectionReader, StableCollectionWriter,
 };
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// Engine for synchronizing beatmap collections between installations
 pub struct CollectionSyncEngine;
@@ -18,28 +21,109 @@ pub struct CollectionSyncEngine;
 impl CollectionSyncEngine {
     /// Sync collections to osu!lazer
     ///
-    /// Currently returns a placeholder result as lazer uses Realm database
-    /// which requires special handling not yet implemented.
+    /// Writes the collection.db at `target` for lazer to import (File >
+    /// Import, or drag it into the game). With
+    /// [`Merge`](CollectionSyncStrategy::Merge) the collections are merged
+    /// into what `target` already holds from earlier syncs and the result
+    /// lists the beatmaps added to each collection; with
+    /// [`Replace`](CollectionSyncStrategy::Replace) same-named collections in
+    /// `target` are overwritten.
+    ///
+    /// Collections that `target` holds with different beatmaps are reported
+    /// in [`CollectionSyncResult::conflicts`];
+    /// [`sync_to_lazer_resolving`](Self::sync_to_lazer_resolving) asks about
+    /// each instead.
     pub fn sync_to_lazer(
         collections: &[Collection],
-        _strategy: CollectionSyncStrategy,
+        strategy: CollectionSyncStrategy,
+        target: &Path,
     ) -> Result<CollectionSyncResult> {
-        // Count total beatmaps for the placeholder result
+        let existing = StableCollectionReader::read(target)?;
+        let conflicts = Self::detect_conflicts(collections, &existing);
         let total_beatmaps: usize = collections.iter().map(|c| c.len()).sum();
+        let source = Self::merge_duplicates(collections);
+
+        let (written, changes) = match strategy {
+            CollectionSyncStrategy::Merge => Self::merge(&existing, &source),
+            CollectionSyncStrategy::Replace => {
+                let mut written: Vec<Collection> = existing
+                    .into_iter()
+                    .filter(|c| !source.iter().any(|s| s.name == c.name))
+                    .collect();
+                written.extend(source.iter().cloned());
+                (written, Vec::new())
+            }
+        };
+        StableCollectionWriter::write(target, &written)?;
 
-        // For now, return a placeholder result indicating this isn't implemented
-        Ok(CollectionSyncResult {
-            collections_synced: 0,
-            beatmaps_added: 0,
-            beatmaps_skipped: total_beatmaps,
-            missing_beatmaps: Vec::new(),
-            success: false,
-            error_message: Some(
-                "Lazer collection sync not yet implemented. \
-                 osu!lazer uses a Realm database which requires special handling."
-                    .to_string(),
-            ),
-        })
+        let beatmaps_added = match strategy {
+            CollectionSyncStrategy::Merge => changes.iter().map(|c| c.added.len()).sum(),
+            CollectionSyncStrategy::Replace => source.iter().map(|c| c.len()).sum(),
+        };
+        let mut result = CollectionSyncResult::success(
+            source.len(),
+            beatmaps_added,
+            total_beatmaps.saturating_sub(beatmaps_added),
+            Vec::new(),
+        );
+        result.changes = changes;
+        result.conflicts = conflicts;
+        Ok(result)
+    }
+
+    /// Sync stable's collection.db at `stable_db` to osu!lazer, asking
+    /// `choose` how to settle each conflict
+    ///
+    /// Collections that `target` holds with different beatmaps are passed to
+    /// `choose` one at a time, the way the beatmap duplicate dialog asks about
+    /// each duplicate set, and both copies become the chosen result. Every
+    /// other collection is merged into `target` as with
+    /// [`Merge`](CollectionSyncStrategy::Merge). If a choice changes the
+    /// stable copy, `stable_db` is rewritten too, so osu!stable must not be
+    /// running.
+    ///
+    /// Returning `None` from `choose` stops the sync with
+    /// [`Error::Cancelled`] before anything is written.
+    pub fn sync_to_lazer_resolving(
+        stable_db: &Path,
+        target: &Path,
+        mut choose: impl FnMut(&CollectionConflict) -> Option<CollectionConflictAction>,
+    ) -> Result<CollectionSyncResult> {
+        let stable = StableCollectionReader::read(stable_db)?;
+        let existing = StableCollectionReader::read(target)?;
+        let conflicts = Self::detect_conflicts(&stable, &existing);
+
+        let mut actions = Vec::with_capacity(conflicts.len());
+        for conflict in &conflicts {
+            actions.push(choose(conflict).ok_or(Error::Cancelled)?);
+        }
+        let mut actions = actions.into_iter();
+        let (new_stable, new_lazer) =
+            Self::resolve_conflicts(&stable, &existing, |_| actions.next().unwrap_or_default());
+
+        let source = Self::merge_duplicates(&new_stable);
+        let (written, changes) = Self::merge(&new_lazer, &source);
+        let stable_changed = new_stable.len() != stable.len()
+            || new_stable
+                .iter()
+                .zip(&stable)
+                .any(|(a, b)| a.name != b.name || a.beatmap_hashes != b.beatmap_hashes);
+        StableCollectionWriter::write(target, &written)?;
+        if stable_changed {
+            StableCollectionWriter::write(stable_db, &new_stable)?;
+        }
+
+        let total_beatmaps: usize = source.iter().map(|c| c.len()).sum();
+        let beatmaps_added: usize = changes.iter().map(|c| c.added.len()).sum();
+        let mut result = CollectionSyncResult::success(
+            source.len(),
+            beatmaps_added,
+            total_beatmaps.saturating_sub(beatmaps_added),
+            Vec::new(),
+        );
+        result.changes = changes;
+        result.conflicts = conflicts;
+        Ok(result)
     }
 
     /// Sync collections from osu!lazer to osu!stable
@@ -56,21 +140,238 @@ impl CollectionSyncEngine {
             missing_beatmaps: Vec::new(),
             success: false,
             error_message: Some("Lazer to Stable collection sync not yet implemented.".to_string()),
+            changes: Vec::new(),
+            unchanged: false,
+            conflicts: Vec::new(),
         })
     }
 
     /// Sync collections based on direction and strategy
+    ///
+    /// `target` is the collection.db written by [`sync_to_lazer`](Self::sync_to_lazer).
     pub fn sync(
         collections: &[Collection],
         direction: CollectionSyncDirection,
         strategy: CollectionSyncStrategy,
+        target: &Path,
     ) -> Result<CollectionSyncResult> {
         match direction {
-            CollectionSyncDirection::StableToLazer => Self::sync_to_lazer(collections, strategy),
+            CollectionSyncDirection::StableToLazer => {
+                Self::sync_to_lazer(collections, strategy, target)
+            }
             CollectionSyncDirection::LazerToStable => Self::sync_to_stable(collections, strategy),
         }
     }
 
+    /// Like [`sync`](Self::sync), but skip the write when the collections
+    /// and strategy are the same as at the last successful sync in
+    /// `direction`
+    ///
+    /// A successful sync records the new checksum in `state`; the caller
+    /// saves it.
+    pub fn sync_if_changed(
+        collections: &[Collection],
+        direction: CollectionSyncDirection,
+        strategy: CollectionSyncStrategy,
+        target: &Path,
+        state: &mut CollectionSyncState,
+    ) -> Result<CollectionSyncResult> {
+        let checksum = Self::checksum(collections, strategy);
+        if state.last_checksum(direction) == Some(checksum.as_str()) && target.exists() {
+            return Ok(CollectionSyncResult::unchanged());
+        }
+
+        let result = Self::sync(collections, direction, strategy, target)?;
+        if result.success {
+            state.record(direction, checksum);
+        }
+        Ok(result)
+    }
+
+    /// Hash of the collections' names and beatmaps plus the strategy
+    ///
+    /// The order of collections and of the beatmaps in them doesn't affect
+    /// the result, and neither does the case of the MD5 hashes.
+    pub fn checksum(collections: &[Collection], strategy: CollectionSyncStrategy) -> String {
+        let mut sorted: Vec<(&str, Vec<String>)> = collections
+            .iter()
+            .map(|collection| {
+                let mut hashes: Vec<String> = collection
+                    .beatmap_hashes
+                    .iter()
+                    .map(|hash| hash.to_lowercase())
+                    .collect();
+                hashes.sort_unstable();
+                (collection.name.as_str(), hashes)
+            })
+            .collect();
+        sorted.sort();
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(strategy.to_string().as_bytes());
+        for (name, hashes) in sorted {
+            hasher.update(b"\n");
+            hasher.update(name.as_bytes());
+            for hash in hashes {
+                hasher.update(b"\0");
+                hasher.update(hash.as_bytes());
+            }
+        }
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Merge `source` into `target` by collection name
+    ///
+    /// Beatmaps already in the target collection are compared
+    /// case-insensitively and not added again; collections missing from the
+    /// target are appended. Returns the merged collections and what was
+    /// added to each.
+    pub fn merge(
+        target: &[Collection],
+        source: &[Collection],
+    ) -> (Vec<Collection>, Vec<CollectionChange>) {
+        let mut merged = target.to_vec();
+        let mut changes: Vec<CollectionChange> = Vec::new();
+
+        for collection in source {
+            let idx = match merged.iter().position(|c| c.name == collection.name) {
+                Some(idx) => idx,
+                None => {
+                    merged.push(Collection::new(collection.name.clone()));
+                    merged.len() - 1
+                }
+            };
+            let mut present: HashSet<String> = merged[idx]
+                .beatmap_hashes
+                .iter()
+                .map(|hash| hash.to_lowercase())
+                .collect();
+
+            let mut added = Vec::new();
+            for hash in &collection.beatmap_hashes {
+                if present.insert(hash.to_lowercase()) {
+                    added.push(hash.clone());
+                }
+            }
+            if added.is_empty() {
+                continue;
+            }
+
+            merged[idx].beatmap_hashes.extend(added.iter().cloned());
+            match changes.iter_mut().find(|c| c.name == collection.name) {
+                Some(change) => change.added.extend(added),
+                None => changes.push(CollectionChange {
+                    name: collection.name.clone(),
+                    added,
+                }),
+            }
+        }
+
+        (merged, changes)
+    }
+
+    /// Find collections that exist in both installations with different
+    /// beatmaps
+    ///
+    /// Collections are matched by name; duplicate names on one side are
+    /// merged first. MD5s are compared case-insensitively and order is
+    /// ignored. Conflicts are returned in stable's collection order.
+    pub fn detect_conflicts(
+        stable: &[Collection],
+        lazer: &[Collection],
+    ) -> Vec<CollectionConflict> {
+        let lazer = Self::merge_duplicates(lazer);
+        let lazer_by_name: HashMap<&str, &Collection> =
+            lazer.iter().map(|c| (c.name.as_str(), c)).collect();
+
+        let mut seen = HashSet::new();
+        let mut conflicts = Vec::new();
+        for collection in stable {
+            if !seen.insert(collection.name.as_str()) {
+                continue;
+            }
+            let Some(lazer_collection) = lazer_by_name.get(collection.name.as_str()) else {
+                continue;
+            };
+            let stable_hashes: Vec<String> = stable
+                .iter()
+                .filter(|c| c.name == collection.name)
+                .flat_map(|c| c.beatmap_hashes.iter().cloned())
+                .collect();
+
+            let in_stable: HashSet<String> =
+                stable_hashes.iter().map(|h| h.to_lowercase()).collect();
+            let in_lazer: HashSet<String> = lazer_collection
+                .beatmap_hashes
+                .iter()
+                .map(|h| h.to_lowercase())
+                .collect();
+            if in_stable == in_lazer {
+                continue;
+            }
+
+            let mut reported = HashSet::new();
+            let only_in_stable = stable_hashes
+                .iter()
+                .filter(|h| {
+                    !in_lazer.contains(&h.to_lowercase()) && reported.insert(h.to_lowercase())
+                })
+                .cloned()
+                .collect();
+            let only_in_lazer = lazer_collection
+                .beatmap_hashes
+                .iter()
+                .filter(|h| {
+                    !in_stable.contains(&h.to_lowercase()) && reported.insert(h.to_lowercase())
+                })
+                .cloned()
+                .collect();
+            conflicts.push(CollectionConflict {
+                name: collection.name.clone(),
+                stable: stable_hashes,
+                lazer: lazer_collection.beatmap_hashes.clone(),
+                only_in_stable,
+                only_in_lazer,
+            });
+        }
+        conflicts
+    }
+
+    /// Settle every conflict between `stable` and `lazer` with the action
+    /// `choose` picks for it
+    ///
+    /// `choose` is called once per conflict, in the order of
+    /// [`detect_conflicts`](Self::detect_conflicts), so it can prompt the
+    /// user the way the beatmap duplicate dialog does. Returns the updated
+    /// stable and lazer collections; collections without a conflict are
+    /// left as they are.
+    pub fn resolve_conflicts(
+        stable: &[Collection],
+        lazer: &[Collection],
+        mut choose: impl FnMut(&CollectionConflict) -> CollectionConflictAction,
+    ) -> (Vec<Collection>, Vec<Collection>) {
+        let resolved: HashMap<String, Collection> = Self::detect_conflicts(stable, lazer)
+            .into_iter()
+            .map(|conflict| {
+                let collection = conflict.resolve(choose(&conflict));
+                (conflict.name, collection)
+            })
+            .collect();
+
+        let apply = |collections: &[Collection]| -> Vec<Collection> {
+            let mut written = HashSet::new();
+            collections
+                .iter()
+                .filter_map(|c| match resolved.get(&c.name) {
+                    // Same-name duplicates collapse into the resolved collection
+                    Some(r) => written.insert(c.name.as_str()).then(|| r.clone()),
+                    None => Some(c.clone()),
+                })
+                .collect()
+        };
+        (apply(stable), apply(lazer))
+    }
+
     /// Get a detailed summary of what would be synced (dry run)
     ///
     /// This includes per-collection details, duplicate detection, and
@@ -121,7 +422,7 @@ impl CollectionSyncEngine {
             CollectionSyncDirection::StableToLazer => (
                 true,
                 Some(
-                    "After sync, drag collection.db into osu!lazer or use File > Import"
+                    "After sync, drag collection.db from lazer's import folder into osu!lazer"
                         .to_string(),
                 ),
             ),
@@ -148,6 +449,34 @@ impl CollectionSyncEngine {
         }
     }
 
+    /// Check which beatmaps of each collection are installed
+    ///
+    /// `installed_md5s` holds the lowercase MD5 of every installed difficulty,
+    /// e.g. from osu!.db. Collection hashes are compared case-insensitively.
+    /// Returns one report per collection, in the same order.
+    pub fn analyze(
+        collections: &[Collection],
+        installed_md5s: &HashSet<String>,
+    ) -> Vec<CollectionReport> {
+        collections
+            .iter()
+            .map(|collection| {
+                let missing: Vec<String> = collection
+                    .beatmap_hashes
+                    .iter()
+                    .filter(|hash| !installed_md5s.contains(&hash.to_lowercase()))
+                    .cloned()
+                    .collect();
+                CollectionReport {
+                    name: collection.name.clone(),
+                    total: collection.len(),
+                    present: collection.len() - missing.len(),
+                    missing,
+                }
+            })
+            .collect()
+    }
+
     /// Merge collections with duplicate names
     ///
     /// Takes a list of collections and merges any that share the same name,
@@ -193,19 +522,56 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_sync_to_lazer_placeholder() {
-        let collections = vec![Collection::with_hashes(
+    fn test_sync_to_lazer_merges_into_previous_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("collection.db");
+        let merge = CollectionSyncStrategy::Merge;
+
+        let first = vec![Collection::with_hashes(
             "Test",
             vec!["hash1".to_string(), "hash2".to_string()],
         )];
+        let result = CollectionSyncEngine::sync_to_lazer(&first, merge, &target).unwrap();
+        assert!(result.success);
+        assert_eq!(result.beatmaps_added, 2);
 
-        let result =
-            CollectionSyncEngine::sync_to_lazer(&collections, CollectionSyncStrategy::Merge)
-                .unwrap();
-
-        assert!(!result.success);
-        assert!(result.error_message.is_some());
-        assert_eq!(result.beatmaps_skipped, 2);
+        let second = vec![
+            Collection::with_hashes("Test", vec!["hash2".to_string(), "hash3".to_string()]),
+            Collection::with_hashes("New", vec!["hash4".to_string()]),
+        ];
+        let result = CollectionSyncEngine::sync_to_lazer(&second, merge, &target).unwrap();
+        assert_eq!(result.beatmaps_added, 2);
+        assert_eq!(result.beatmaps_skipped, 1);
+        assert_eq!(
+            result.changes,
+            vec![
+                CollectionChange {
+                    name: "New".to_string(),
+                    added: vec!["hash4".to_string()],
+                },
+                CollectionChange {
+                    name: "Test".to_string(),
+                    added: vec!["hash3".to_string()],
+                },
+            ]
+        );
+        let written = StableCollectionReader::read(&target).unwrap();
+        assert_eq!(written[0].beatmap_hashes, vec!["hash1", "hash2", "hash3"]);
+
+        // Replace overwrites the collection instead
+        let result = CollectionSyncEngine::sync_to_lazer(
+            &second[..1],
+            CollectionSyncStrategy::Replace,
+            &target,
+        )
+        .unwrap();
+        assert!(result.changes.is_empty());
+        let written = StableCollectionReader::read(&target).unwrap();
+        assert_eq!(written.len(), 2);
+        assert_eq!(
+            Collection::find(&written, "Test").unwrap().beatmap_hashes,
+            vec!["hash2", "hash3"]
+        );
     }
 
     #[test]
@@ -266,6 +632,28 @@ mod tests {
             .contains("Realm"));
     }
 
+    #[test]
+    fn test_analyze() {
+        let collections = vec![
+            Collection::with_hashes(
+                "Favorites",
+                vec!["AAA".to_string(), "bbb".to_string(), "ccc".to_string()],
+            ),
+            Collection::new("Empty"),
+        ];
+        let installed: HashSet<String> = ["aaa", "ccc"].iter().map(|s| s.to_string()).collect();
+
+        let reports = CollectionSyncEngine::analyze(&collections, &installed);
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].total, 3);
+        assert_eq!(reports[0].present, 2);
+        assert_eq!(reports[0].missing, vec!["bbb".to_string()]);
+        assert_eq!(reports[0].to_string(), "Favorites: 2/3 present");
+        assert!(!reports[0].is_complete());
+        assert!(reports[1].is_complete());
+    }
+
     #[test]
     fn test_merge_duplicates() {
         let collections = vec![
@@ -290,6 +678,184 @@ mod tests {
         assert_eq!(training.beatmap_hashes.len(), 1);
     }
 
+    #[test]
+    fn test_merge_reports_added_beatmaps() {
+        let target = vec![Collection::with_hashes(
+            "Favourites",
+            vec!["AA".to_string(), "bb".to_string()],
+        )];
+        let source = vec![
+            Collection::with_hashes(
+                "Favourites",
+                vec!["aa".to_string(), "cc".to_string(), "CC".to_string()],
+            ),
+            Collection::with_hashes("Training", vec!["dd".to_string()]),
+            Collection::with_hashes("Empty", Vec::new()),
+        ];
+
+        let (merged, changes) = CollectionSyncEngine::merge(&target, &source);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].beatmap_hashes, vec!["AA", "bb", "cc"]);
+        assert_eq!(
+            changes,
+            vec![
+                CollectionChange {
+                    name: "Favourites".to_string(),
+                    added: vec!["cc".to_string()],
+                },
+                CollectionChange {
+                    name: "Training".to_string(),
+                    added: vec!["dd".to_string()],
+                },
+            ]
+        );
+        assert_eq!(changes[0].to_string(), "Favourites: +1");
+    }
+
+    #[test]
+    fn test_checksum_ignores_order_and_case() {
+        let a = vec![
+            Collection::with_hashes("A", vec!["h1".to_string(), "H2".to_string()]),
+            Collection::with_hashes("B", vec!["h3".to_string()]),
+        ];
+        let b = vec![
+            Collection::with_hashes("B", vec!["h3".to_string()]),
+            Collection::with_hashes("A", vec!["h2".to_string(), "h1".to_string()]),
+        ];
+        let merge = CollectionSyncStrategy::Merge;
+        assert_eq!(
+            CollectionSyncEngine::checksum(&a, merge),
+            CollectionSyncEngine::checksum(&b, merge)
+        );
+        assert_ne!(
+            CollectionSyncEngine::checksum(&a, merge),
+            CollectionSyncEngine::checksum(&a, CollectionSyncStrategy::Replace)
+        );
+
+        // A successful sync records the checksum, which skips the next one
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("collection.db");
+        let mut state = CollectionSyncState::new();
+        let direction = CollectionSyncDirection::StableToLazer;
+        let result =
+            CollectionSyncEngine::sync_if_changed(&a, direction, merge, &target, &mut state)
+                .unwrap();
+        assert!(result.success && !result.unchanged);
+        assert_eq!(
+            state.last_checksum(direction),
+            Some(CollectionSyncEngine::checksum(&b, merge).as_str())
+        );
+
+        let result =
+            CollectionSyncEngine::sync_if_changed(&b, direction, merge, &target, &mut state)
+                .unwrap();
+        assert!(result.unchanged && result.success);
+
+        // A failed sync records nothing
+        let direction = CollectionSyncDirection::LazerToStable;
+        let result =
+            CollectionSyncEngine::sync_if_changed(&a, direction, merge, &target, &mut state)
+                .unwrap();
+        assert!(!result.success);
+        assert_eq!(state.last_checksum(direction), None);
+    }
+
+    #[test]
+    fn test_detect_and_resolve_conflicts() {
+        let hashes = |h: &[&str]| h.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let stable = vec![
+            Collection::with_hashes("Favourites", hashes(&["aa", "bb"])),
+            Collection::with_hashes("Same", hashes(&["cc", "dd"])),
+            Collection::with_hashes("Stable only", hashes(&["ee"])),
+        ];
+        let lazer = vec![
+            Collection::with_hashes("Same", hashes(&["DD", "cc"])),
+            Collection::with_hashes("Favourites", hashes(&["bb", "ff"])),
+        ];
+
+        let conflicts = CollectionSyncEngine::detect_conflicts(&stable, &lazer);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].name, "Favourites");
+        assert_eq!(conflicts[0].only_in_stable, vec!["aa"]);
+        assert_eq!(conflicts[0].only_in_lazer, vec!["ff"]);
+        assert_eq!(
+            conflicts[0]
+                .resolve(CollectionConflictAction::Merge)
+                .beatmap_hashes,
+            vec!["aa", "bb", "ff"]
+        );
+
+        let (new_stable, new_lazer) =
+            CollectionSyncEngine::resolve_conflicts(&stable, &lazer, |_| {
+                CollectionConflictAction::ReplaceStable
+            });
+        assert_eq!(new_stable[0].beatmap_hashes, vec!["bb", "ff"]);
+        assert_eq!(new_stable[2].name, "Stable only");
+        assert_eq!(new_lazer[1].beatmap_hashes, vec!["bb", "ff"]);
+    }
+
+    #[test]
+    fn test_sync_to_lazer_resolving() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let stable_db = dir.path().join("stable.db");
+        let target = dir.path().join("collection.db");
+        let hashes = |h: &[&str]| h.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let stable = vec![
+            Collection::with_hashes("Favourites", hashes(&["aa", "bb"])),
+            Collection::with_hashes("Stable only", hashes(&["ee"])),
+        ];
+        let lazer = vec![
+            Collection::with_hashes("Favourites", hashes(&["bb", "ff"])),
+            Collection::with_hashes("Lazer only", hashes(&["gg"])),
+        ];
+        StableCollectionWriter::write(&stable_db, &stable).unwrap();
+        StableCollectionWriter::write(&target, &lazer).unwrap();
+        let read = |path: &Path, name: &str| {
+            Collection::find(&StableCollectionReader::read(path).unwrap(), name)
+                .map(|c| c.beatmap_hashes.clone())
+        };
+
+        // The plain sync reports the conflict it overwrites
+        let other = dir.path().join("other.db");
+        StableCollectionWriter::write(&other, &lazer).unwrap();
+        let result =
+            CollectionSyncEngine::sync_to_lazer(&stable, CollectionSyncStrategy::Replace, &other)
+                .unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].only_in_lazer, vec!["ff"]);
+
+        // Stopping at the prompt writes nothing
+        let stopped = CollectionSyncEngine::sync_to_lazer_resolving(&stable_db, &target, |_| None);
+        assert!(matches!(stopped, Err(Error::Cancelled)));
+        assert_eq!(read(&target, "Favourites").unwrap(), vec!["bb", "ff"]);
+
+        let mut asked = Vec::new();
+        let result = CollectionSyncEngine::sync_to_lazer_resolving(&stable_db, &target, |c| {
+            asked.push(c.name.clone());
+            Some(CollectionConflictAction::ReplaceLazer)
+        })
+        .unwrap();
+        assert_eq!(asked, vec!["Favourites"]);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(read(&target, "Favourites").unwrap(), vec!["aa", "bb"]);
+        assert_eq!(read(&target, "Stable only").unwrap(), vec!["ee"]);
+        assert_eq!(read(&target, "Lazer only").unwrap(), vec!["gg"]);
+        assert_eq!(read(&stable_db, "Favourites").unwrap(), vec!["aa", "bb"]);
+
+        // Merging gives both sides every beatmap
+        StableCollectionWriter::write(&target, &lazer).unwrap();
+        CollectionSyncEngine::sync_to_lazer_resolving(&stable_db, &target, |_| {
+            Some(CollectionConflictAction::Merge)
+        })
+        .unwrap();
+        assert_eq!(read(&target, "Favourites").unwrap(), vec!["aa", "bb", "ff"]);
+        assert_eq!(
+            read(&stable_db, "Favourites").unwrap(),
+            vec!["aa", "bb", "ff"]
+        );
+        assert!(read(&stable_db, "Lazer only").is_none());
+    }
+
     #[test]
     fn test_find_duplicates() {
         let collections = vec![
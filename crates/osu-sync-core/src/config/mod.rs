@@ -1,12 +1,15 @@
 //! Configuration and path detection
 
-mod paths;
+pub mod paths;
 
 pub use paths::*;
 
+use crate::error::{Error, Result};
 use crate::unified::UnifiedStorageConfig;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// Theme name for UI customization
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -46,11 +49,32 @@ impl std::fmt::Display for ThemeName {
     }
 }
 
+/// Name of the profile backed by the top-level path fields
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Installation paths of one named sync setup
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfilePaths {
+    /// Path to osu!stable installation (Songs folder parent)
+    pub stable_path: Option<PathBuf>,
+    /// Path to osu!lazer data directory
+    pub lazer_path: Option<PathBuf>,
+}
+
+/// Current config schema version, bumped whenever a field changes meaning
+pub const CONFIG_VERSION: u32 = 1;
+
 /// Configuration for osu-sync
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Schema version of this config; configs written before versioning are 0
+    #[serde(default)]
+    pub version: u32,
     /// Path to osu!stable installation (Songs folder parent)
     pub stable_path: Option<PathBuf>,
+    /// All detected osu!stable installations, for users with several installs
+    #[serde(default)]
+    pub stable_paths: Vec<PathBuf>,
     /// Path to osu!lazer data directory
     pub lazer_path: Option<PathBuf>,
     /// Default duplicate handling strategy
@@ -61,6 +85,35 @@ pub struct Config {
     /// Unified storage configuration
     #[serde(default)]
     pub unified_storage: Option<UnifiedStorageConfig>,
+    /// Named install pairs for machines with several setups
+    ///
+    /// The [`DEFAULT_PROFILE`] entry mirrors `stable_path` and `lazer_path`,
+    /// which remain the source of truth for it.
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfilePaths>,
+    /// Profile used when none is given explicitly; `None` means the default
+    #[serde(default)]
+    pub active_profile: Option<String>,
+}
+
+/// Outcome of loading the config file from disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLoadStatus {
+    /// The config file was read and parsed successfully
+    Loaded,
+    /// No config file exists yet; defaults were auto-detected
+    NotFound,
+    /// The config file was corrupt and the backup copy was used instead
+    RecoveredFromBackup,
+    /// The config file and its backup were unusable; defaults were auto-detected
+    Corrupt,
+}
+
+impl ConfigLoadStatus {
+    /// Whether the primary config file failed to parse
+    pub fn primary_was_corrupt(&self) -> bool {
+        matches!(self, Self::RecoveredFromBackup | Self::Corrupt)
+    }
 }
 
 /// Strategy for handling duplicate beatmaps
@@ -79,13 +132,21 @@ pub enum DuplicateStrategy {
 
 impl Default for Config {
     fn default() -> Self {
-        Self {
-            stable_path: detect_stable_path(),
+        // Scans the disk once for both the preferred and the full list
+        let (stable_path, stable_paths) = paths::detect_stable_paths();
+        let mut config = Self {
+            version: CONFIG_VERSION,
+            stable_path,
+            stable_paths,
             lazer_path: detect_lazer_path(),
             duplicate_strategy: DuplicateStrategy::Ask,
             theme: ThemeName::Default,
             unified_storage: None,
-        }
+            profiles: HashMap::new(),
+            active_profile: None,
+        };
+        config.sync_default_profile();
+        config
     }
 }
 
@@ -97,29 +158,187 @@ impl Config {
 
     /// Get the config file path
     fn config_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|p| p.join("osu-sync").join("config.json"))
+        paths::config_dir().map(|p| p.join("config.json"))
     }
 
     /// Load config from disk, falling back to auto-detection if not found
     pub fn load() -> Self {
-        Self::config_path()
-            .and_then(|path| std::fs::read_to_string(&path).ok())
-            .and_then(|content| serde_json::from_str(&content).ok())
-            .unwrap_or_default()
+        Self::load_with_status().0
+    }
+
+    /// Load config from disk, reporting whether the primary file was corrupt
+    ///
+    /// If `config.json` fails to parse, the `config.json.bak` written by the
+    /// previous successful save is tried before falling back to defaults.
+    pub fn load_with_status() -> (Self, ConfigLoadStatus) {
+        match Self::config_path() {
+            Some(path) => Self::load_from(&path),
+            None => (Self::default(), ConfigLoadStatus::NotFound),
+        }
     }
 
     /// Save config to disk
+    ///
+    /// The config is written to a sibling temp file and renamed over the
+    /// target so an interrupted save never leaves a truncated file. The
+    /// previous config is kept as `config.json.bak`.
     pub fn save(&self) -> std::io::Result<()> {
         if let Some(path) = Self::config_path() {
-            if let Some(parent) = path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            let content = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
-            std::fs::write(&path, content)?;
+            self.save_to(&path)?;
         }
         Ok(())
     }
 
+    fn load_from(path: &Path) -> (Self, ConfigLoadStatus) {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return (Self::default(), ConfigLoadStatus::NotFound),
+        };
+
+        if let Ok(mut config) = serde_json::from_str::<Config>(&content) {
+            config.migrate();
+            config.sync_default_profile();
+            return (config, ConfigLoadStatus::Loaded);
+        }
+
+        tracing::warn!("Config file {} is corrupt, trying backup", path.display());
+        std::fs::read_to_string(Self::backup_path(path))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Config>(&content).ok())
+            .map(|mut config| {
+                config.migrate();
+                config.sync_default_profile();
+                (config, ConfigLoadStatus::RecoveredFromBackup)
+            })
+            .unwrap_or_else(|| (Self::default(), ConfigLoadStatus::Corrupt))
+    }
+
+    /// Upgrade a config written by an older version, one step at a time
+    ///
+    /// Returns `true` if anything was changed. Each step handles exactly one
+    /// version bump so new migrations can be appended without touching old ones.
+    pub fn migrate(&mut self) -> bool {
+        let original = self.version;
+
+        while self.version < CONFIG_VERSION {
+            // v0 -> v1: introduce the list of stable installs
+            if self.version == 0 && self.stable_paths.is_empty() {
+                self.stable_paths.extend(self.stable_path.clone());
+            }
+            self.version += 1;
+        }
+
+        if self.version != original {
+            tracing::info!(
+                "Migrated config from version {} to {}",
+                original,
+                self.version
+            );
+        }
+        self.version != original
+    }
+
+    fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut config = self.clone();
+        config.sync_default_profile();
+        let content = serde_json::to_string_pretty(&config).map_err(std::io::Error::other)?;
+
+        let tmp_path = path.with_extension("json.tmp");
+        {
+            let mut file = std::fs::File::create(&tmp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+
+        // Keep the last good config around in case the next one gets corrupted
+        if path.exists() {
+            let current_is_valid = std::fs::read_to_string(path)
+                .ok()
+                .is_some_and(|c| serde_json::from_str::<Config>(&c).is_ok());
+            if current_is_valid {
+                let _ = std::fs::copy(path, Self::backup_path(path));
+            }
+        }
+
+        std::fs::rename(&tmp_path, path)
+    }
+
+    fn backup_path(path: &Path) -> PathBuf {
+        path.with_extension("json.bak")
+    }
+
+    /// Point the default profile at the top-level paths
+    fn sync_default_profile(&mut self) {
+        self.profiles.insert(
+            DEFAULT_PROFILE.to_string(),
+            ProfilePaths {
+                stable_path: self.stable_path.clone(),
+                lazer_path: self.lazer_path.clone(),
+            },
+        );
+    }
+
+    /// The config with the paths of the named profile in effect
+    ///
+    /// [`DEFAULT_PROFILE`] gives the top-level paths. The result is meant
+    /// for running a sync, not for saving: saving it would make the
+    /// profile's paths the new default.
+    pub fn with_profile(&self, name: &str) -> Result<Config> {
+        let mut config = self.clone();
+        config.active_profile = Some(name.to_string());
+        if name == DEFAULT_PROFILE {
+            return Ok(config);
+        }
+
+        let paths = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| Error::Config(format!("Unknown profile '{}'", name)))?;
+        config.stable_path = paths.stable_path.clone();
+        config.lazer_path = paths.lazer_path.clone();
+        Ok(config)
+    }
+
+    /// The config with the paths of `active_profile` in effect
+    ///
+    /// Like [`Self::with_profile`], but falls back to the top-level paths
+    /// with a warning when the active profile no longer exists.
+    pub fn with_active_profile(&self) -> Config {
+        match self.active_profile.as_deref() {
+            Some(name) => self.with_profile(name).unwrap_or_else(|e| {
+                tracing::warn!("{}, using the default paths", e);
+                self.clone()
+            }),
+            None => self.clone(),
+        }
+    }
+
+    /// Re-scan for osu!stable installations, keeping the current selection
+    /// if it is still among them
+    pub fn refresh_stable_paths(&mut self) {
+        self.stable_paths = detect_all_stable_paths();
+        if self.stable_path.is_none() {
+            self.stable_path = self.stable_paths.first().cloned();
+        }
+    }
+
+    /// Select one of the detected osu!stable installations by index
+    ///
+    /// Returns `false` if the index is out of range. Scanning and stats use
+    /// `stable_path`, so they follow the selection.
+    pub fn select_stable_path(&mut self, index: usize) -> bool {
+        match self.stable_paths.get(index) {
+            Some(path) => {
+                self.stable_path = Some(path.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get the Songs folder path for osu!stable
     pub fn stable_songs_path(&self) -> Option<PathBuf> {
         self.stable_path.as_ref().map(|p| p.join("Songs"))
@@ -139,4 +358,197 @@ impl Config {
     pub fn lazer_realm_path(&self) -> Option<PathBuf> {
         self.lazer_path.as_ref().map(|p| p.join("client.realm"))
     }
+
+    /// Get the osu!stable installation path, or an error naming what is missing
+    ///
+    /// Returns [`Error::MissingPath`] when no path is configured and
+    /// [`Error::OsuNotFound`] when the configured directory does not exist.
+    pub fn require_stable_path(&self) -> Result<PathBuf> {
+        require_dir(self.stable_path.clone(), "osu!stable")
+    }
+
+    /// Get the osu!stable Songs folder, or an error naming what is missing
+    pub fn require_stable_songs_path(&self) -> Result<PathBuf> {
+        let songs = self.require_stable_path()?.join("Songs");
+        require_dir(Some(songs), "osu!stable")
+    }
+
+    /// Get the osu!lazer data path, or an error naming what is missing
+    pub fn require_lazer_path(&self) -> Result<PathBuf> {
+        require_dir(self.lazer_path.clone(), "osu!lazer")
+    }
+
+    /// Get the osu!lazer files directory, or an error naming what is missing
+    pub fn require_lazer_files_path(&self) -> Result<PathBuf> {
+        Ok(self.require_lazer_path()?.join("files"))
+    }
+
+    /// Get the osu!lazer import directory, or an error naming what is missing
+    pub fn require_lazer_import_path(&self) -> Result<PathBuf> {
+        Ok(self.require_lazer_path()?.join("import"))
+    }
+}
+
+/// Check that an optional configured directory is set and exists
+fn require_dir(path: Option<PathBuf>, path_type: &'static str) -> Result<PathBuf> {
+    match path {
+        Some(path) if path.is_dir() => Ok(path),
+        Some(path) => Err(Error::OsuNotFound(path)),
+        None => Err(Error::MissingPath { path_type }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_stable_path() {
+        let mut config = Config {
+            version: CONFIG_VERSION,
+            stable_path: None,
+            stable_paths: vec![PathBuf::from("/osu/a"), PathBuf::from("/osu/b")],
+            lazer_path: None,
+            duplicate_strategy: DuplicateStrategy::Ask,
+            theme: ThemeName::Default,
+            unified_storage: None,
+            profiles: HashMap::new(),
+            active_profile: None,
+        };
+
+        assert!(config.select_stable_path(1));
+        assert_eq!(config.stable_path, Some(PathBuf::from("/osu/b")));
+        assert_eq!(
+            config.stable_songs_path(),
+            Some(PathBuf::from("/osu/b/Songs"))
+        );
+        assert!(!config.select_stable_path(5));
+        assert_eq!(config.stable_path, Some(PathBuf::from("/osu/b")));
+    }
+
+    #[test]
+    fn test_save_is_atomic_and_keeps_backup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("osu-sync").join("config.json");
+
+        let mut config = Config {
+            theme: ThemeName::Ocean,
+            ..Config::default()
+        };
+        config.save_to(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        config.theme = ThemeName::Monochrome;
+        config.save_to(&path).unwrap();
+
+        let (loaded, status) = Config::load_from(&path);
+        assert_eq!(status, ConfigLoadStatus::Loaded);
+        assert_eq!(loaded.theme, ThemeName::Monochrome);
+
+        let backup: Config =
+            serde_json::from_str(&std::fs::read_to_string(Config::backup_path(&path)).unwrap())
+                .unwrap();
+        assert_eq!(backup.theme, ThemeName::Ocean);
+    }
+
+    #[test]
+    fn test_load_recovers_from_backup_when_corrupt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.json");
+
+        let config = Config {
+            theme: ThemeName::Ocean,
+            ..Config::default()
+        };
+        config.save_to(&path).unwrap();
+        config.save_to(&path).unwrap();
+
+        // Simulate a truncated write
+        std::fs::write(&path, "{\"stable_path\": ").unwrap();
+
+        let (loaded, status) = Config::load_from(&path);
+        assert_eq!(status, ConfigLoadStatus::RecoveredFromBackup);
+        assert!(status.primary_was_corrupt());
+        assert_eq!(loaded.theme, ThemeName::Ocean);
+
+        std::fs::remove_file(Config::backup_path(&path)).unwrap();
+        let (_, status) = Config::load_from(&path);
+        assert_eq!(status, ConfigLoadStatus::Corrupt);
+    }
+
+    #[test]
+    fn test_config_without_stable_paths_deserializes() {
+        let json = r#"{"stable_path":"/osu","lazer_path":null,"duplicate_strategy":"Skip"}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.version, 0);
+        assert_eq!(config.stable_path, Some(PathBuf::from("/osu")));
+        assert!(config.stable_paths.is_empty());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.json");
+
+        // A config from before profiles gets a default profile on load
+        let json = r#"{"version":1,"stable_path":"/osu","lazer_path":"/lazer","duplicate_strategy":"Skip"}"#;
+        std::fs::write(&path, json).unwrap();
+        let (mut config, _) = Config::load_from(&path);
+        assert_eq!(
+            config.profiles[DEFAULT_PROFILE],
+            ProfilePaths {
+                stable_path: Some(PathBuf::from("/osu")),
+                lazer_path: Some(PathBuf::from("/lazer")),
+            }
+        );
+
+        config.profiles.insert(
+            "work".to_string(),
+            ProfilePaths {
+                stable_path: Some(PathBuf::from("/work/osu")),
+                lazer_path: None,
+            },
+        );
+        let work = config.with_profile("work").unwrap();
+        assert_eq!(work.stable_path, Some(PathBuf::from("/work/osu")));
+        assert_eq!(work.lazer_path, None);
+        assert_eq!(work.active_profile.as_deref(), Some("work"));
+
+        let default = config.with_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.stable_path, Some(PathBuf::from("/osu")));
+        assert!(matches!(config.with_profile("home"), Err(Error::Config(_))));
+
+        config.active_profile = Some("work".to_string());
+        let active = config.with_active_profile();
+        assert_eq!(active.stable_path, Some(PathBuf::from("/work/osu")));
+        config.active_profile = Some("home".to_string());
+        assert_eq!(
+            config.with_active_profile().stable_path,
+            Some(PathBuf::from("/osu"))
+        );
+        config.active_profile = None;
+
+        // The default profile follows the top-level paths when saved
+        config.lazer_path = Some(PathBuf::from("/new-lazer"));
+        config.save_to(&path).unwrap();
+        let (loaded, _) = Config::load_from(&path);
+        assert_eq!(
+            loaded.profiles[DEFAULT_PROFILE].lazer_path,
+            Some(PathBuf::from("/new-lazer"))
+        );
+        assert!(loaded.profiles.contains_key("work"));
+    }
+
+    #[test]
+    fn test_migrate_legacy_config() {
+        let json = r#"{"stable_path":"/osu","lazer_path":null,"duplicate_strategy":"Skip"}"#;
+        let mut config: Config = serde_json::from_str(json).unwrap();
+
+        assert!(config.migrate());
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.stable_paths, vec![PathBuf::from("/osu")]);
+
+        // Already current: nothing to do
+        assert!(!config.migrate());
+    }
 }
@@ -1,7 +1,57 @@
-//! Platform-specific path detection for osu! installations
+//! Platform-specific path detection for osu! installations, and the
+//! directories osu-sync keeps its own files in
 
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
+/// Environment variable that moves every osu-sync directory under one root,
+/// with `config`, `data` and `cache` subfolders; used by tests
+pub const APP_DIR_ENV: &str = "OSU_SYNC_HOME";
+
+/// Name of the osu-sync folder inside the platform directories
+const APP_DIR_NAME: &str = "osu-sync";
+
+/// Directory for settings: the config file, skip list and unified storage
+/// manifest
+///
+/// `%APPDATA%\osu-sync` on Windows, `~/.config/osu-sync` on Linux.
+pub fn config_dir() -> Option<PathBuf> {
+    app_dir(std::env::var_os(APP_DIR_ENV), dirs::config_dir(), "config")
+}
+
+/// Directory for data osu-sync accumulates: backups, the audit and
+/// activity logs, the trash and collection sync state
+///
+/// `%LOCALAPPDATA%\osu-sync` on Windows, `~/.local/share/osu-sync` on Linux.
+pub fn app_data_dir() -> Option<PathBuf> {
+    app_dir(
+        std::env::var_os(APP_DIR_ENV),
+        dirs::data_local_dir(),
+        "data",
+    )
+}
+
+/// Directory for files that can be rebuilt at any time, such as the hash
+/// cache
+///
+/// `%LOCALAPPDATA%\osu-sync` on Windows, `~/.cache/osu-sync` on Linux.
+pub fn cache_dir() -> Option<PathBuf> {
+    app_dir(std::env::var_os(APP_DIR_ENV), dirs::cache_dir(), "cache")
+}
+
+/// `override_root/subfolder` if the override is set, else the osu-sync
+/// folder inside `platform_dir`
+fn app_dir(
+    override_root: Option<OsString>,
+    platform_dir: Option<PathBuf>,
+    subfolder: &str,
+) -> Option<PathBuf> {
+    match override_root.filter(|root| !root.is_empty()) {
+        Some(root) => Some(PathBuf::from(root).join(subfolder)),
+        None => platform_dir.map(|dir| dir.join(APP_DIR_NAME)),
+    }
+}
+
 /// Get all available drive letters on Windows
 #[cfg(target_os = "windows")]
 fn get_available_drives() -> Vec<PathBuf> {
@@ -46,16 +96,16 @@ fn is_lazer_installation(path: &Path) -> bool {
     path.join("client.realm").exists()
 }
 
-/// Scan a directory for osu! installations (non-recursive, checks immediate children)
+/// Collect osu!stable installations in a directory (non-recursive, checks immediate children)
 #[cfg(target_os = "windows")]
-fn scan_directory_for_stable(dir: &Path) -> Option<PathBuf> {
+fn scan_directory_for_stable(dir: &Path, found: &mut Vec<PathBuf>) {
     if !dir.exists() || !dir.is_dir() {
-        return None;
+        return;
     }
 
     // First check if this directory itself is osu!
     if is_stable_installation(dir) {
-        return Some(dir.to_path_buf());
+        found.push(dir.to_path_buf());
     }
 
     // Then check immediate children
@@ -63,12 +113,10 @@ fn scan_directory_for_stable(dir: &Path) -> Option<PathBuf> {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() && is_stable_installation(&path) {
-                return Some(path);
+                found.push(path);
             }
         }
     }
-
-    None
 }
 
 /// Scan a directory for osu!lazer installations (non-recursive, checks immediate children)
@@ -96,6 +144,60 @@ fn scan_directory_for_lazer(dir: &Path) -> Option<PathBuf> {
     None
 }
 
+/// Candidate osu!lazer data directories on Linux, in priority order
+///
+/// Covers the standard XDG location plus Flatpak and Snap sandboxes, which
+/// keep their data under per-app home directories.
+#[cfg(target_os = "linux")]
+fn linux_lazer_candidates(home: &Path, data_local: Option<&Path>) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(data) = data_local {
+        candidates.push(data.join("osu"));
+    }
+    candidates.push(home.join(".local/share/osu"));
+    candidates.push(home.join(".var/app/sh.ppy.osu/data/osu"));
+    candidates.push(home.join("snap/osu/current/.local/share/osu"));
+    candidates.push(home.join("snap/osu/common/.local/share/osu"));
+    candidates
+}
+
+/// Candidate osu!stable directories inside Wine/Lutris prefixes on Linux
+#[cfg(target_os = "linux")]
+fn linux_stable_candidates(home: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![
+        home.join(".wine/drive_c/osu!"),
+        home.join(".local/share/osu-wine/osu!"),
+        home.join("Games/osu!"),
+    ];
+
+    // Common Wine prefixes (default, Lutris game dirs, osu-winello)
+    let prefixes = [
+        home.join(".wine"),
+        home.join("Games/osu"),
+        home.join("Games/osu-stable"),
+        home.join("Games/osu!"),
+        home.join(".local/share/wineprefixes/osu"),
+        home.join(".local/share/osu-wine/WINE.osu"),
+    ];
+
+    for prefix in &prefixes {
+        let drive_c = prefix.join("drive_c");
+        candidates.push(drive_c.join("osu!"));
+        candidates.push(drive_c.join("Program Files/osu!"));
+        candidates.push(drive_c.join("Program Files (x86)/osu!"));
+
+        // The official installer puts osu! in the user's local AppData
+        if let Ok(users) = std::fs::read_dir(drive_c.join("users")) {
+            for user in users.flatten() {
+                candidates.push(user.path().join("AppData/Local/osu!"));
+                candidates.push(user.path().join("Local Settings/Application Data/osu!"));
+            }
+        }
+    }
+
+    candidates
+}
+
 /// Detect osu!lazer data directory
 pub fn detect_lazer_path() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
@@ -134,10 +236,12 @@ pub fn detect_lazer_path() -> Option<PathBuf> {
 
     #[cfg(target_os = "linux")]
     {
-        if let Some(data) = dirs::data_local_dir() {
-            let path = data.join("osu");
-            if is_lazer_installation(&path) {
-                return Some(path);
+        if let Some(home) = dirs::home_dir() {
+            let data_local = dirs::data_local_dir();
+            for path in linux_lazer_candidates(&home, data_local.as_deref()) {
+                if is_lazer_installation(&path) {
+                    return Some(path);
+                }
             }
         }
     }
@@ -155,15 +259,18 @@ pub fn detect_lazer_path() -> Option<PathBuf> {
     None
 }
 
-/// Detect osu!stable installation directory
-pub fn detect_stable_path() -> Option<PathBuf> {
+/// Collect every osu!stable installation found on this system, in priority order
+fn find_stable_installations() -> Vec<PathBuf> {
+    #[allow(unused_mut)]
+    let mut found: Vec<PathBuf> = Vec::new();
+
     #[cfg(target_os = "windows")]
     {
         // Priority 1: Standard location
         if let Some(local) = dirs::data_local_dir() {
             let osu_path = local.join("osu!");
             if is_stable_installation(&osu_path) {
-                return Some(osu_path);
+                found.push(osu_path);
             }
         }
 
@@ -179,9 +286,7 @@ pub fn detect_stable_path() -> Option<PathBuf> {
             ];
 
             for dir in &scan_dirs {
-                if let Some(path) = scan_directory_for_stable(dir) {
-                    return Some(path);
-                }
+                scan_directory_for_stable(dir, &mut found);
             }
         }
     }
@@ -189,17 +294,11 @@ pub fn detect_stable_path() -> Option<PathBuf> {
     #[cfg(target_os = "linux")]
     {
         if let Some(home) = dirs::home_dir() {
-            let wine_paths = [
-                home.join(".wine/drive_c/osu!"),
-                home.join(".local/share/osu-wine/osu!"),
-                home.join("Games/osu!"),
-            ];
-
-            for path in wine_paths {
-                if is_stable_installation(&path) {
-                    return Some(path);
-                }
-            }
+            found.extend(
+                linux_stable_candidates(&home)
+                    .into_iter()
+                    .filter(|path| is_stable_installation(path)),
+            );
         }
     }
 
@@ -211,15 +310,56 @@ pub fn detect_stable_path() -> Option<PathBuf> {
                 home.join(".wine/drive_c/osu!"),
             ];
 
-            for path in candidates {
-                if is_stable_installation(&path) {
-                    return Some(path);
-                }
-            }
+            found.extend(
+                candidates
+                    .into_iter()
+                    .filter(|path| is_stable_installation(path)),
+            );
         }
     }
 
-    None
+    dedup_paths(found)
+}
+
+/// Remove duplicate paths while preserving order
+///
+/// Paths are compared by their canonical form so the same folder reached
+/// through different routes (symlinks, drive roots) is only reported once.
+fn dedup_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = std::collections::HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())))
+        .collect()
+}
+
+/// Detect osu!stable installation directory
+///
+/// Returns the highest-priority installation; see [`detect_all_stable_paths`]
+/// to list every install when more than one is present.
+pub fn detect_stable_path() -> Option<PathBuf> {
+    detect_stable_paths().0
+}
+
+/// Detect every osu!stable installation directory containing an `osu!.db`
+///
+/// Useful when several stable folders are kept side by side (e.g. one per
+/// account). The first entry matches [`detect_stable_path`].
+pub fn detect_all_stable_paths() -> Vec<PathBuf> {
+    detect_stable_paths().1
+}
+
+/// [`detect_stable_path`] and [`detect_all_stable_paths`] from a single scan
+pub(super) fn detect_stable_paths() -> (Option<PathBuf>, Vec<PathBuf>) {
+    let found = find_stable_installations();
+    let with_db: Vec<PathBuf> = found
+        .iter()
+        .filter(|path| path.join("osu!.db").is_file())
+        .cloned()
+        .collect();
+    // Prefer installs with a beatmap database
+    let preferred = with_db.first().or_else(|| found.first()).cloned();
+    (preferred, with_db)
 }
 
 /// Validate that a path is a valid osu!stable installation
@@ -228,18 +368,100 @@ pub fn validate_stable_path(path: &Path) -> bool {
 }
 
 /// Validate that a path is a valid osu!lazer data directory
+///
+/// Requires both the Realm database and the file store, so sandbox
+/// directories (Flatpak/Snap) that exist without game data are rejected.
 pub fn validate_lazer_path(path: &Path) -> bool {
-    path.exists() && path.join("client.realm").is_file() && path.join("files").is_dir()
+    path.is_dir() && path.join("client.realm").is_file() && path.join("files").is_dir()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_app_dir_override() {
+        let platform = Some(PathBuf::from("/home/user/.local/share"));
+        assert_eq!(
+            app_dir(None, platform.clone(), "data"),
+            Some(PathBuf::from("/home/user/.local/share/osu-sync"))
+        );
+        assert_eq!(
+            app_dir(Some("/tmp/osu-sync-test".into()), platform.clone(), "data"),
+            Some(PathBuf::from("/tmp/osu-sync-test/data"))
+        );
+        // An empty override is treated as unset
+        assert_eq!(
+            app_dir(Some(OsString::new()), platform, "data"),
+            Some(PathBuf::from("/home/user/.local/share/osu-sync"))
+        );
+        assert_eq!(app_dir(None, None, "data"), None);
+    }
+
     #[test]
     fn test_detect_paths() {
         // These tests just verify the functions run without panicking
         let _ = detect_lazer_path();
         let _ = detect_stable_path();
     }
+
+    #[test]
+    fn test_validate_lazer_path_rejects_missing_realm() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let flatpak = temp_dir.path().join(".var/app/sh.ppy.osu/data/osu");
+        std::fs::create_dir_all(flatpak.join("files")).unwrap();
+        assert!(!validate_lazer_path(&flatpak));
+
+        std::fs::write(flatpak.join("client.realm"), b"").unwrap();
+        assert!(validate_lazer_path(&flatpak));
+    }
+
+    #[test]
+    fn test_detect_all_stable_paths_first_matches_single() {
+        let all = detect_all_stable_paths();
+        if let Some(first) = all.first() {
+            assert_eq!(detect_stable_path().as_ref(), Some(first));
+        }
+        assert!(all.iter().all(|p| p.join("osu!.db").is_file()));
+    }
+
+    #[test]
+    fn test_dedup_paths_preserves_order() {
+        let paths = vec![
+            PathBuf::from("/nonexistent/b"),
+            PathBuf::from("/nonexistent/a"),
+            PathBuf::from("/nonexistent/b"),
+        ];
+        assert_eq!(
+            dedup_paths(paths),
+            vec![
+                PathBuf::from("/nonexistent/b"),
+                PathBuf::from("/nonexistent/a")
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_lazer_candidates_include_sandboxes() {
+        let home = Path::new("/home/user");
+        let candidates = linux_lazer_candidates(home, None);
+        assert!(candidates.contains(&home.join(".var/app/sh.ppy.osu/data/osu")));
+        assert!(candidates.contains(&home.join("snap/osu/current/.local/share/osu")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_stable_candidates_find_wine_user_appdata() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path();
+        let osu = home.join(".wine/drive_c/users/player/AppData/Local/osu!");
+        std::fs::create_dir_all(osu.join("Songs")).unwrap();
+        std::fs::write(osu.join("osu!.db"), b"").unwrap();
+
+        let found = linux_stable_candidates(home)
+            .into_iter()
+            .find(|p| is_stable_installation(p));
+        assert_eq!(found, Some(osu));
+    }
 }
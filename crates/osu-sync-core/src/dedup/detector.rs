@@ -1,8 +1,9 @@
 //! Duplicate detection logic
 
 use crate::beatmap::BeatmapSet;
-use crate::dedup::DuplicateStrategy;
-use std::collections::HashSet;
+use crate::dedup::{DuplicateStrategy, QualityScore};
+use std::collections::{HashMap, HashSet};
+use unicode_normalization::UnicodeNormalization;
 
 /// Information about a detected duplicate
 #[derive(Debug, Clone)]
@@ -15,6 +16,143 @@ pub struct DuplicateInfo {
     pub match_type: MatchType,
     /// Confidence score (0.0 - 1.0)
     pub confidence: f32,
+    /// File-level overlap, set for [`MatchType::FileHashSubset`] matches
+    pub overlap: Option<FileOverlap>,
+}
+
+/// How much two beatmap sets' files overlap, compared by content hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileOverlap {
+    /// Shared files divided by the file count of the larger set (0.0 - 1.0)
+    pub ratio: f32,
+    /// Files only the source set has
+    pub only_in_source: Vec<String>,
+    /// Files only the existing set has
+    pub only_in_existing: Vec<String>,
+}
+
+impl FileOverlap {
+    /// Compare the files of two sets
+    ///
+    /// Returns `None` if either set has no hashed files or neither set's
+    /// files are a subset of the other's.
+    pub fn between(source: &BeatmapSet, existing: &BeatmapSet) -> Option<Self> {
+        Self::from_hashes(
+            source,
+            &Self::hashes(source),
+            existing,
+            &Self::hashes(existing),
+        )
+    }
+
+    /// Like [`between`](Self::between), with each set's hashes already collected
+    fn from_hashes(
+        source: &BeatmapSet,
+        source_hashes: &HashSet<&str>,
+        existing: &BeatmapSet,
+        existing_hashes: &HashSet<&str>,
+    ) -> Option<Self> {
+        if source_hashes.is_empty() || existing_hashes.is_empty() {
+            return None;
+        }
+        if !source_hashes.is_subset(existing_hashes) && !existing_hashes.is_subset(source_hashes) {
+            return None;
+        }
+
+        let shared = source_hashes.intersection(existing_hashes).count();
+        let larger = source_hashes.len().max(existing_hashes.len());
+        let only_in = |set: &BeatmapSet, other: &HashSet<&str>| -> Vec<String> {
+            set.files
+                .iter()
+                .filter(|f| !f.hash.is_empty() && !other.contains(f.hash.as_str()))
+                .map(|f| f.filename.clone())
+                .collect()
+        };
+
+        Some(Self {
+            ratio: shared as f32 / larger as f32,
+            only_in_source: only_in(source, existing_hashes),
+            only_in_existing: only_in(existing, source_hashes),
+        })
+    }
+
+    /// Whether both sets have exactly the same files
+    pub fn is_identical(&self) -> bool {
+        self.only_in_source.is_empty() && self.only_in_existing.is_empty()
+    }
+
+    fn hashes(set: &BeatmapSet) -> HashSet<&str> {
+        set.files
+            .iter()
+            .filter(|f| !f.hash.is_empty())
+            .map(|f| f.hash.as_str())
+            .collect()
+    }
+}
+
+/// Content hashes of the files of each set in a list
+///
+/// Build one for the existing sets and reuse it for every source looked up
+/// with [`DuplicateDetector::find_duplicate_indexed`], so each set's hashes
+/// are collected once rather than once per lookup. The sets' normalized
+/// metadata is kept too, grouped by creator, for fuzzy metadata matches.
+pub struct FileHashIndex<'a> {
+    hashes: Vec<HashSet<&'a str>>,
+    /// Normalized creator -> (set index, normalized metadata)
+    by_creator: HashMap<String, Vec<(usize, FuzzyMetadata)>>,
+}
+
+impl<'a> FileHashIndex<'a> {
+    /// Collect the file hashes of every set in `sets`
+    pub fn new(sets: &'a [BeatmapSet]) -> Self {
+        let mut by_creator: HashMap<String, Vec<(usize, FuzzyMetadata)>> = HashMap::new();
+        for (idx, set) in sets.iter().enumerate() {
+            if let Some(metadata) = FuzzyMetadata::new(set) {
+                by_creator
+                    .entry(metadata.creator.clone())
+                    .or_default()
+                    .push((idx, metadata));
+            }
+        }
+        Self {
+            hashes: sets.iter().map(FileOverlap::hashes).collect(),
+            by_creator,
+        }
+    }
+}
+
+/// A set's normalized title, artist and creator, for fuzzy matching
+struct FuzzyMetadata {
+    creator: String,
+    /// Normalized romanized and unicode titles
+    titles: Vec<String>,
+    /// Normalized romanized and unicode artists
+    artists: Vec<String>,
+}
+
+impl FuzzyMetadata {
+    /// `None` if the set has no metadata or no creator to match on
+    fn new(set: &BeatmapSet) -> Option<Self> {
+        let metadata = set.metadata()?;
+        let creator = normalize_metadata_text(&metadata.creator);
+        if creator.is_empty() {
+            return None;
+        }
+        Some(Self {
+            creator,
+            titles: Self::variants(&metadata.title, &metadata.title_unicode),
+            artists: Self::variants(&metadata.artist, &metadata.artist_unicode),
+        })
+    }
+
+    /// Normalized romanized and unicode variants of a metadata field
+    fn variants(romanized: &str, unicode: &Option<String>) -> Vec<String> {
+        std::iter::once(romanized)
+            .chain(unicode.as_deref())
+            .map(normalize_metadata_text)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
 }
 
 /// Reference to a beatmap set
@@ -25,6 +163,10 @@ pub struct BeatmapSetRef {
     pub artist: String,
     pub creator: String,
     pub hash: Option<String>,
+    /// Number of difficulties in the set
+    pub difficulty_count: usize,
+    /// Quality score used to pick the better copy of a duplicate
+    pub quality: QualityScore,
 }
 
 impl From<&BeatmapSet> for BeatmapSetRef {
@@ -36,6 +178,8 @@ impl From<&BeatmapSet> for BeatmapSetRef {
             artist: metadata.map(|m| m.artist.clone()).unwrap_or_default(),
             creator: metadata.map(|m| m.creator.clone()).unwrap_or_default(),
             hash: set.beatmaps.first().map(|b| b.md5_hash.clone()),
+            difficulty_count: set.beatmaps.len(),
+            quality: QualityScore::from_set(set),
         }
     }
 }
@@ -51,37 +195,145 @@ pub enum MatchType {
     SameBeatmapId,
     /// Title + Artist + Creator match
     Metadata,
+    /// Normalized title + artist match (unicode/romanization variants)
+    FuzzyMetadata,
+    /// One set's files are a strict subset of the other's (by content hash)
+    FileHashSubset,
     /// Partial/fuzzy match
     Similar(u8), // Similarity percentage
 }
 
+/// Default maximum edit distance for fuzzy metadata matches
+pub const DEFAULT_FUZZY_THRESHOLD: usize = 2;
+
+/// Normalize a title or artist for fuzzy comparison
+///
+/// Applies NFKC (folding full-width forms), lowercases, folds long-vowel
+/// romanization markers (macrons/circumflexes, "ou"/"uu"/"oo") and strips
+/// whitespace and punctuation.
+pub fn normalize_metadata_text(text: &str) -> String {
+    let folded: String = text
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'ā' | 'â' => 'a',
+            'ē' | 'ê' => 'e',
+            'ī' | 'î' => 'i',
+            'ō' | 'ô' => 'o',
+            'ū' | 'û' => 'u',
+            other => other,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+
+    folded
+        .replace("ou", "o")
+        .replace("oo", "o")
+        .replace("uu", "u")
+}
+
+/// Check whether two normalized strings are equal or within `threshold` edits
+///
+/// Strings too short to absorb the threshold must match exactly, so short
+/// titles like "Ai" and "Bi" never collide.
+fn fuzzy_eq(a: &str, b: &str, threshold: usize) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    if a == b {
+        return true;
+    }
+    let shortest = a.chars().count().min(b.chars().count());
+    shortest > threshold * 2 && strsim::levenshtein(a, b) <= threshold
+}
+
 /// Detector for finding duplicate beatmaps
 pub struct DuplicateDetector {
     strategy: DuplicateStrategy,
+    /// Maximum edit distance for fuzzy metadata matches
+    fuzzy_threshold: usize,
 }
 
 impl DuplicateDetector {
     /// Create a new detector with the given strategy
     pub fn new(strategy: DuplicateStrategy) -> Self {
-        Self { strategy }
+        Self {
+            strategy,
+            fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD,
+        }
+    }
+
+    /// Set the maximum edit distance for fuzzy metadata matches (0 = exact only)
+    pub fn with_fuzzy_threshold(mut self, threshold: usize) -> Self {
+        self.fuzzy_threshold = threshold;
+        self
+    }
+
+    /// Find difficulties within a set whose .osu files are byte-identical
+    ///
+    /// Returns `(first, duplicate)` index pairs into `set.beatmaps`, where
+    /// `first` is the earliest difficulty with that hash. Difficulties
+    /// without a hash are ignored.
+    pub fn find_intra_set_duplicates(set: &BeatmapSet) -> Vec<(usize, usize)> {
+        let mut first_by_hash: HashMap<&str, usize> = HashMap::new();
+        let mut pairs = Vec::new();
+
+        for (idx, beatmap) in set.beatmaps.iter().enumerate() {
+            if beatmap.hash.is_empty() {
+                continue;
+            }
+            match first_by_hash.get(beatmap.hash.as_str()) {
+                Some(&first) => pairs.push((first, idx)),
+                None => {
+                    first_by_hash.insert(&beatmap.hash, idx);
+                }
+            }
+        }
+
+        pairs
     }
 
     /// Check if a beatmap set already exists in the target index
+    ///
+    /// When looking up many sources against the same sets, use
+    /// [`find_duplicate_indexed`](Self::find_duplicate_indexed) instead.
     pub fn find_duplicate(
         &self,
         source: &BeatmapSet,
         existing_sets: &[BeatmapSet],
     ) -> Option<DuplicateInfo> {
         match self.strategy {
-            DuplicateStrategy::ByHash => self.find_by_hash(source, existing_sets),
             DuplicateStrategy::BySetId => self.find_by_set_id(source, existing_sets),
             DuplicateStrategy::ByMetadata => self.find_by_metadata(source, existing_sets),
-            DuplicateStrategy::Composite => self.find_composite(source, existing_sets),
+            DuplicateStrategy::ByHash | DuplicateStrategy::Composite => self
+                .find_duplicate_indexed(source, existing_sets, &FileHashIndex::new(existing_sets)),
         }
     }
 
-    /// Find duplicates by MD5 hash
-    fn find_by_hash(&self, source: &BeatmapSet, existing: &[BeatmapSet]) -> Option<DuplicateInfo> {
+    /// Like [`find_duplicate`](Self::find_duplicate), with the file hashes of
+    /// `existing_sets` collected up front
+    pub fn find_duplicate_indexed(
+        &self,
+        source: &BeatmapSet,
+        existing_sets: &[BeatmapSet],
+        file_hashes: &FileHashIndex,
+    ) -> Option<DuplicateInfo> {
+        debug_assert_eq!(file_hashes.hashes.len(), existing_sets.len());
+        match self.strategy {
+            DuplicateStrategy::ByHash => self.find_by_hash(source, existing_sets, file_hashes),
+            DuplicateStrategy::BySetId => self.find_by_set_id(source, existing_sets),
+            DuplicateStrategy::ByMetadata => self.find_by_metadata(source, existing_sets),
+            DuplicateStrategy::Composite => self.find_composite(source, existing_sets, file_hashes),
+        }
+    }
+
+    /// Find duplicates by MD5 hash, then by file hash subset
+    fn find_by_hash(
+        &self,
+        source: &BeatmapSet,
+        existing: &[BeatmapSet],
+        file_hashes: &FileHashIndex,
+    ) -> Option<DuplicateInfo> {
         for source_beatmap in &source.beatmaps {
             for existing_set in existing {
                 for existing_beatmap in &existing_set.beatmaps {
@@ -91,12 +343,50 @@ impl DuplicateDetector {
                             existing: existing_set.into(),
                             match_type: MatchType::ExactHash,
                             confidence: 1.0,
+                            overlap: None,
                         });
                     }
                 }
             }
         }
-        None
+        self.find_by_file_subset(source, existing, file_hashes)
+    }
+
+    /// Find sets whose files are a strict subset of the other's
+    ///
+    /// Catches partial copies, such as a set missing a difficulty or one with
+    /// an extra storyboard. Identical file lists are left to the MD5 check.
+    fn find_by_file_subset(
+        &self,
+        source: &BeatmapSet,
+        existing: &[BeatmapSet],
+        file_hashes: &FileHashIndex,
+    ) -> Option<DuplicateInfo> {
+        let source_hashes = FileOverlap::hashes(source);
+        if source_hashes.is_empty() {
+            return None;
+        }
+        existing
+            .iter()
+            .zip(&file_hashes.hashes)
+            .find_map(|(existing_set, existing_hashes)| {
+                let overlap = FileOverlap::from_hashes(
+                    source,
+                    &source_hashes,
+                    existing_set,
+                    existing_hashes,
+                )?;
+                if overlap.is_identical() {
+                    return None;
+                }
+                Some(DuplicateInfo {
+                    source: source.into(),
+                    existing: existing_set.into(),
+                    match_type: MatchType::FileHashSubset,
+                    confidence: 0.9,
+                    overlap: Some(overlap),
+                })
+            })
     }
 
     /// Find duplicates by beatmap set ID
@@ -113,6 +403,7 @@ impl DuplicateDetector {
                         existing: existing_set.into(),
                         match_type: MatchType::SameSetId,
                         confidence: 0.95,
+                        overlap: None,
                     });
                 }
             }
@@ -136,6 +427,7 @@ impl DuplicateDetector {
                         existing: existing_set.into(),
                         match_type: MatchType::Metadata,
                         confidence: 0.8,
+                        overlap: None,
                     });
                 }
             }
@@ -143,16 +435,62 @@ impl DuplicateDetector {
         None
     }
 
+    /// Find duplicates by normalized title + artist from the same creator
+    ///
+    /// Compares both romanized and unicode variants, so a set imported with
+    /// romanized metadata matches the same set with its original title.
+    /// Only sets whose normalized creator is equal are compared, so the same
+    /// song mapped by someone else is not a duplicate. Sets with different
+    /// online IDs are never matched.
+    fn find_by_fuzzy_metadata(
+        &self,
+        source: &BeatmapSet,
+        existing: &[BeatmapSet],
+        index: &FileHashIndex,
+    ) -> Option<DuplicateInfo> {
+        let source_meta = FuzzyMetadata::new(source)?;
+        let candidates = index.by_creator.get(&source_meta.creator)?;
+
+        for (idx, existing_meta) in candidates {
+            let existing_set = &existing[*idx];
+            if let (Some(a), Some(b)) = (source.id, existing_set.id) {
+                if a > 0 && b > 0 && a != b {
+                    continue;
+                }
+            }
+
+            let any_match = |left: &[String], right: &[String]| {
+                left.iter()
+                    .any(|l| right.iter().any(|r| fuzzy_eq(l, r, self.fuzzy_threshold)))
+            };
+
+            if any_match(&source_meta.titles, &existing_meta.titles)
+                && any_match(&source_meta.artists, &existing_meta.artists)
+            {
+                return Some(DuplicateInfo {
+                    source: source.into(),
+                    existing: existing_set.into(),
+                    match_type: MatchType::FuzzyMetadata,
+                    confidence: 0.7,
+                    overlap: None,
+                });
+            }
+        }
+        None
+    }
+
     /// Composite detection: try all methods
     fn find_composite(
         &self,
         source: &BeatmapSet,
         existing: &[BeatmapSet],
+        file_hashes: &FileHashIndex,
     ) -> Option<DuplicateInfo> {
         // Try in order of confidence
-        self.find_by_hash(source, existing)
+        self.find_by_hash(source, existing, file_hashes)
             .or_else(|| self.find_by_set_id(source, existing))
             .or_else(|| self.find_by_metadata(source, existing))
+            .or_else(|| self.find_by_fuzzy_metadata(source, existing, file_hashes))
     }
 
     /// Find all duplicates in a list of beatmaps to import
@@ -161,9 +499,10 @@ impl DuplicateDetector {
         sources: &[BeatmapSet],
         existing: &[BeatmapSet],
     ) -> Vec<DuplicateInfo> {
+        let file_hashes = FileHashIndex::new(existing);
         sources
             .iter()
-            .filter_map(|source| self.find_duplicate(source, existing))
+            .filter_map(|source| self.find_duplicate_indexed(source, existing, &file_hashes))
             .collect()
     }
 }
@@ -276,7 +615,7 @@ impl DuplicateIndex {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::beatmap::{BeatmapInfo, BeatmapMetadata};
+    use crate::beatmap::{BeatmapFile, BeatmapInfo, BeatmapMetadata};
 
     fn make_set(id: Option<i32>, title: &str, artist: &str, creator: &str) -> BeatmapSet {
         let mut set = BeatmapSet::new();
@@ -318,4 +657,158 @@ mod tests {
         assert!(dup.is_some());
         assert_eq!(dup.unwrap().match_type, MatchType::Metadata);
     }
+
+    fn with_files(mut set: BeatmapSet, files: &[&str]) -> BeatmapSet {
+        set.files = files
+            .iter()
+            .map(|name| BeatmapFile {
+                filename: name.to_string(),
+                hash: format!("sha_{}", name),
+                size: 1,
+            })
+            .collect();
+        set
+    }
+
+    #[test]
+    fn test_find_by_file_subset() {
+        let detector = DuplicateDetector::new(DuplicateStrategy::Composite);
+
+        let source = with_files(
+            make_set(None, "Song", "Artist", "Mapper"),
+            &["audio.mp3", "easy.osu"],
+        );
+        let existing = vec![with_files(
+            make_set(Some(1), "Other", "Name", "Someone"),
+            &["audio.mp3", "easy.osu", "hard.osu", "bg.jpg"],
+        )];
+
+        let dup = detector.find_duplicate(&source, &existing).unwrap();
+        assert_eq!(dup.match_type, MatchType::FileHashSubset);
+        let overlap = dup.overlap.unwrap();
+        assert_eq!(overlap.ratio, 0.5);
+        assert!(overlap.only_in_source.is_empty());
+        assert_eq!(overlap.only_in_existing, vec!["hard.osu", "bg.jpg"]);
+
+        // Partial overlap in both directions is not a subset
+        let source = with_files(
+            make_set(None, "Song", "Artist", "Mapper"),
+            &["audio.mp3", "insane.osu"],
+        );
+        assert!(detector.find_duplicate(&source, &existing).is_none());
+    }
+
+    #[test]
+    fn test_exact_hash_beats_file_subset() {
+        let detector = DuplicateDetector::new(DuplicateStrategy::ByHash);
+
+        // Same difficulty, but the existing copy has an extra background
+        let source = with_files(
+            make_set(None, "Song", "Artist", "Mapper"),
+            &["audio.mp3", "easy.osu"],
+        );
+        let existing = vec![with_files(
+            make_set(None, "Song", "Artist", "Mapper"),
+            &["audio.mp3", "easy.osu", "bg.jpg"],
+        )];
+
+        let file_hashes = FileHashIndex::new(&existing);
+        let dup = detector
+            .find_duplicate_indexed(&source, &existing, &file_hashes)
+            .unwrap();
+        assert_eq!(dup.match_type, MatchType::ExactHash);
+        assert!(dup.overlap.is_none());
+    }
+
+    #[test]
+    fn test_find_intra_set_duplicates() {
+        let mut set = BeatmapSet::new();
+        for hash in ["a", "b", "a", "", "", "b", "a"] {
+            set.beatmaps.push(BeatmapInfo {
+                hash: hash.to_string(),
+                ..Default::default()
+            });
+        }
+
+        assert_eq!(
+            DuplicateDetector::find_intra_set_duplicates(&set),
+            vec![(0, 2), (1, 5), (0, 6)]
+        );
+
+        assert_eq!(set.dedup_difficulties(), 3);
+        let hashes: Vec<_> = set.beatmaps.iter().map(|b| b.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["a", "b", "", ""]);
+        assert!(DuplicateDetector::find_intra_set_duplicates(&set).is_empty());
+    }
+
+    fn make_unicode_set(
+        id: Option<i32>,
+        title: &str,
+        title_unicode: Option<&str>,
+        artist: &str,
+        artist_unicode: Option<&str>,
+    ) -> BeatmapSet {
+        let mut set = make_set(id, title, artist, "Mapper");
+        set.beatmaps[0].metadata.title_unicode = title_unicode.map(str::to_string);
+        set.beatmaps[0].metadata.artist_unicode = artist_unicode.map(str::to_string);
+        set.beatmaps[0].md5_hash = format!("{}-{:?}", title, id);
+        set
+    }
+
+    #[test]
+    fn test_normalize_metadata_text() {
+        assert_eq!(normalize_metadata_text("Tōkyō  Ｔｅｉｎ!"), "tokyotein");
+        assert_eq!(normalize_metadata_text("Yuuki - Kousoku"), "yukikosoku");
+    }
+
+    #[test]
+    fn test_fuzzy_matches_romanized_and_unicode() {
+        let detector = DuplicateDetector::new(DuplicateStrategy::Composite);
+        let romanized = make_unicode_set(None, "Yoru ni Kakeru", None, "YOASOBI", None);
+        let original = make_unicode_set(
+            None,
+            "Yoru ni Kakeru",
+            Some("夜に駆ける"),
+            "YOASOBI",
+            Some("YOASOBI"),
+        );
+        let japanese_only = make_unicode_set(None, "夜に駆ける", None, "ＹＯＡＳＯＢＩ", None);
+
+        let dup = detector
+            .find_duplicate(&japanese_only, &[original])
+            .unwrap();
+        assert_eq!(dup.match_type, MatchType::FuzzyMetadata);
+
+        // Small typo within threshold
+        let typo = make_unicode_set(None, "Yoru ni Kakeruu", None, "YOASOBl", None);
+        assert!(detector
+            .find_duplicate(&typo, &[romanized.clone()])
+            .is_some());
+
+        // Threshold 0 requires exact normalized equality
+        let strict = DuplicateDetector::new(DuplicateStrategy::Composite).with_fuzzy_threshold(0);
+        assert!(strict.find_duplicate(&typo, &[romanized]).is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_never_merges_different_online_ids() {
+        let detector = DuplicateDetector::new(DuplicateStrategy::Composite);
+        let a = make_unicode_set(Some(1), "Same Song", None, "Artist", None);
+        let b = make_unicode_set(Some(2), "Same Song", None, "Artist", None);
+        assert!(detector.find_duplicate(&a, &[b]).is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_requires_the_same_creator() {
+        let detector = DuplicateDetector::new(DuplicateStrategy::Composite);
+        let mine = make_unicode_set(None, "Yoru ni Kakeru", Some("夜に駆ける"), "YOASOBI", None);
+        let mut theirs = make_unicode_set(None, "夜に駆ける", None, "YOASOBI", None);
+        theirs.beatmaps[0].metadata.creator = "Someone Else".to_string();
+        assert!(detector.find_duplicate(&mine, &[theirs.clone()]).is_none());
+
+        // Creators are compared normalized
+        theirs.beatmaps[0].metadata.creator = "mapper".to_string();
+        let dup = detector.find_duplicate(&mine, &[theirs]).unwrap();
+        assert_eq!(dup.match_type, MatchType::FuzzyMetadata);
+    }
 }
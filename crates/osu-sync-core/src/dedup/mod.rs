@@ -1,7 +1,9 @@
 //! Duplicate detection for beatmaps
 
 mod detector;
+mod quality;
 mod strategy;
 
 pub use detector::*;
+pub use quality::*;
 pub use strategy::*;
@@ -0,0 +1,146 @@
+//! Quality scoring for choosing between duplicate beatmap sets
+
+use crate::beatmap::BeatmapSet;
+use crate::lazer::LazerFileStore;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Comparable quality score for a beatmap set
+///
+/// Scores compare field by field, in declaration order:
+/// 1. a set with an online ID beats one without
+/// 2. more difficulties beats fewer
+/// 3. larger total file size beats smaller
+/// 4. newer folder modification time beats older (unknown sorts lowest)
+///
+/// The ordering is total, so comparing two scores is deterministic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct QualityScore {
+    /// Whether the set has an online beatmap set ID
+    pub has_online_id: bool,
+    /// Number of difficulties in the set
+    pub difficulty_count: usize,
+    /// Total size of all files in the set, in bytes
+    pub total_size: u64,
+    /// Last modification time of the set's folder, if known
+    pub modified: Option<SystemTime>,
+}
+
+impl QualityScore {
+    /// Compute a score from a beatmap set (without folder mtime)
+    pub fn from_set(set: &BeatmapSet) -> Self {
+        Self {
+            has_online_id: set.id.is_some_and(|id| id > 0),
+            difficulty_count: set.beatmaps.len(),
+            total_size: set.files.iter().map(|f| f.size).sum(),
+            modified: None,
+        }
+    }
+
+    /// Compute a score from a beatmap set stored under `songs_dir`
+    ///
+    /// The folder mtime is read from `songs_dir/<folder_name>` when available.
+    pub fn from_set_in(set: &BeatmapSet, songs_dir: &Path) -> Self {
+        let modified = set
+            .disk_folder_name()
+            .and_then(|name| std::fs::metadata(songs_dir.join(name)).ok())
+            .and_then(|m| m.modified().ok());
+        Self::from_set(set).with_modified(modified)
+    }
+
+    /// Compute a score from a lazer set whose files are in `store`
+    ///
+    /// lazer sets have no folder, so the size is summed from the set's blobs
+    /// and the newest blob's mtime stands in for the folder's.
+    pub fn from_stored_set(set: &BeatmapSet, store: &LazerFileStore) -> Self {
+        let mut score = Self::from_set(set);
+        let blobs: Vec<std::fs::Metadata> = set
+            .files
+            .iter()
+            .filter_map(|file| std::fs::metadata(store.hash_to_path(&file.hash)).ok())
+            .collect();
+        score.total_size = blobs.iter().map(|m| m.len()).sum();
+        score.modified = blobs.iter().filter_map(|m| m.modified().ok()).max();
+        score
+    }
+
+    /// Set the folder modification time
+    pub fn with_modified(mut self, modified: Option<SystemTime>) -> Self {
+        self.modified = modified;
+        self
+    }
+}
+
+impl From<&BeatmapSet> for QualityScore {
+    fn from(set: &BeatmapSet) -> Self {
+        Self::from_set(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beatmap::{BeatmapFile, BeatmapInfo};
+    use std::time::Duration;
+
+    fn make_set(id: Option<i32>, difficulties: usize, size: u64) -> BeatmapSet {
+        let mut set = BeatmapSet::new();
+        set.id = id;
+        set.beatmaps = vec![BeatmapInfo::default(); difficulties];
+        set.files = vec![BeatmapFile {
+            filename: "audio.mp3".to_string(),
+            hash: "abc".to_string(),
+            size,
+        }];
+        set
+    }
+
+    #[test]
+    fn test_online_id_wins_first() {
+        let with_id = QualityScore::from_set(&make_set(Some(1), 1, 10));
+        let without_id = QualityScore::from_set(&make_set(None, 5, 1000));
+        assert!(with_id > without_id);
+    }
+
+    #[test]
+    fn test_difficulties_then_size_then_mtime() {
+        let more_diffs = QualityScore::from_set(&make_set(Some(1), 3, 10));
+        let fewer_diffs = QualityScore::from_set(&make_set(Some(1), 2, 1000));
+        assert!(more_diffs > fewer_diffs);
+
+        let larger = QualityScore::from_set(&make_set(Some(1), 2, 2000));
+        assert!(larger > fewer_diffs);
+
+        let now = SystemTime::now();
+        let newer = fewer_diffs.with_modified(Some(now));
+        let older = fewer_diffs.with_modified(Some(now - Duration::from_secs(60)));
+        assert!(newer > older);
+        assert!(older > fewer_diffs);
+    }
+
+    #[test]
+    fn test_scores_read_mtime_and_size_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut set = make_set(Some(1), 1, 0);
+        set.folder_name = Some("1 Artist - Title".to_string());
+        std::fs::create_dir(dir.path().join("1 Artist - Title")).unwrap();
+        assert!(QualityScore::from_set_in(&set, dir.path())
+            .modified
+            .is_some());
+
+        let store = LazerFileStore::new(dir.path());
+        let path = store.hash_to_path("abc");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"audio").unwrap();
+        let stored = QualityScore::from_stored_set(&set, &store);
+        assert_eq!(stored.total_size, 5);
+        assert!(stored.modified.is_some());
+    }
+
+    #[test]
+    fn test_identical_sets_tie() {
+        let a = QualityScore::from_set(&make_set(Some(1), 2, 10));
+        let b = QualityScore::from_set(&make_set(Some(1), 2, 10));
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+    }
+}
@@ -25,6 +25,8 @@ pub enum DuplicateAction {
     Replace,
     /// Keep both versions (import with different folder name)
     KeepBoth,
+    /// Combine both copies into one set, see [`BeatmapSet::merge`](crate::beatmap::BeatmapSet::merge)
+    Merge,
 }
 
 /// Resolution for a duplicate detection
@@ -67,6 +69,13 @@ impl DuplicateResolution {
         }
     }
 
+    pub fn merge() -> Self {
+        Self {
+            action: DuplicateAction::Merge,
+            apply_to_all: false,
+        }
+    }
+
     pub fn with_apply_to_all(mut self) -> Self {
         self.apply_to_all = true;
         self
@@ -27,12 +27,21 @@ pub enum Error {
     #[error("Realm database error: {0}")]
     Realm(String),
 
+    #[error("osu! API error: {0}")]
+    Api(String),
+
+    #[error("osu!.db at {path} is unreadable: {reason}")]
+    DatabasePartial { path: PathBuf, reason: String },
+
     #[error("File hash mismatch: expected {expected}, got {actual}")]
     HashMismatch { expected: String, actual: String },
 
     #[error("Sync aborted by user")]
     Aborted,
 
+    #[error("Operation cancelled")]
+    Cancelled,
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -72,7 +81,78 @@ pub enum Error {
 
     #[error("Manifest error: {0}")]
     ManifestError(String),
+
+    #[error("Backup is encrypted; a passphrase is required to restore it")]
+    PassphraseRequired,
+
+    #[error("Wrong passphrase for encrypted backup")]
+    WrongPassphrase,
+
+    #[error(
+        "Permission denied writing to {path}. Check that this folder is owned by your user, \
+         or run osu-sync as the user that owns the osu! installation"
+    )]
+    PermissionDenied { path: PathBuf },
+
+    #[error(
+        "Not enough disk space: {} needed, {} available",
+        crate::sync::format_bytes(*needed),
+        crate::sync::format_bytes(*available)
+    )]
+    InsufficientSpace { needed: u64, available: u64 },
+
+    #[error("No file manager available ({launcher}): {reason}")]
+    FileManagerUnavailable {
+        launcher: &'static str,
+        reason: String,
+    },
+}
+
+impl Error {
+    /// Wrap an I/O error that occurred while writing to `path`
+    ///
+    /// Permission errors become [`Error::PermissionDenied`] so the message
+    /// can tell the user what to fix; anything else stays [`Error::Io`].
+    pub fn io_at(err: std::io::Error, path: impl Into<PathBuf>) -> Self {
+        Error::Io(err).with_path(path)
+    }
+
+    /// Attach `path` to a permission error, leaving other errors unchanged
+    pub fn with_path(self, path: impl Into<PathBuf>) -> Self {
+        match self {
+            Error::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                Error::PermissionDenied { path: path.into() }
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl From<tokio::task::JoinError> for Error {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Error::Other(format!("Background task failed: {}", e))
+    }
 }
 
 /// Result type alias for osu-sync operations
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_io_at_maps_permission_denied() {
+        let err = Error::io_at(
+            io::Error::from(io::ErrorKind::PermissionDenied),
+            "/home/other/.local/share/osu",
+        );
+        assert!(matches!(err, Error::PermissionDenied { ref path } if path.ends_with("osu")));
+        assert!(err.to_string().contains("owned by your user"));
+
+        let err = Error::io_at(io::Error::from(io::ErrorKind::NotFound), "/missing");
+        assert!(matches!(err, Error::Io(_)));
+    }
+}
@@ -11,6 +11,9 @@ pub struct FilterCriteria {
     pub star_rating_min: Option<f32>,
     /// Maximum star rating (inclusive)
     pub star_rating_max: Option<f32>,
+    /// Mod bitmask the star rating range applies to (0 = no mods)
+    #[serde(default)]
+    pub star_rating_mods: u32,
     /// Game modes to include (empty = all modes)
     pub modes: Vec<GameMode>,
     /// Ranked statuses to include (empty = all statuses)
@@ -52,6 +55,12 @@ impl FilterCriteria {
         self
     }
 
+    /// Apply the star rating range to ratings with these mods (e.g. 64 for DT)
+    pub fn with_star_mods(mut self, mods: u32) -> Self {
+        self.star_rating_mods = mods;
+        self
+    }
+
     /// Set star rating range
     pub fn with_star_range(mut self, min: f32, max: f32) -> Self {
         self.star_rating_min = Some(min);
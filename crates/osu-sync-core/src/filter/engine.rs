@@ -1,8 +1,64 @@
 //! Filter engine for matching beatmaps against criteria
 
-use super::FilterCriteria;
-use crate::beatmap::BeatmapSet;
+use super::{BeatmapSearchIndex, FilterCriteria};
+use crate::beatmap::{BeatmapSet, GameMode};
 use crate::lazer::LazerBeatmapSet;
+use serde::Serialize;
+
+/// Summary of a beatmap set matched by [`FilterEngine::search_stable`]
+///
+/// Serializes to a compact JSON object for tools that answer questions
+/// about the user's library, such as the `search_beatmaps` tool of
+/// [`OsuSyncTools`](crate::tools::OsuSyncTools). Field names are the JSON
+/// keys.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BeatmapSearchHit {
+    /// Online beatmap set ID (if known)
+    pub set_id: Option<i32>,
+    /// Romanized artist of the set's first difficulty
+    pub artist: String,
+    /// Romanized title of the set's first difficulty
+    pub title: String,
+    /// Mapper of the set's first difficulty
+    pub creator: String,
+    /// Distinct game modes across the set's difficulties, in difficulty
+    /// order (`"Osu"`, `"Taiko"`, `"Catch"` or `"Mania"`)
+    pub modes: Vec<GameMode>,
+    /// Lowest rated difficulty in stars, `None` without star ratings
+    pub star_min: Option<f32>,
+    /// Highest rated difficulty in stars, `None` without star ratings
+    pub star_max: Option<f32>,
+    /// Number of difficulties in the set
+    pub difficulty_count: usize,
+    /// Folder name in osu!stable
+    pub folder_name: Option<String>,
+}
+
+impl BeatmapSearchHit {
+    /// Summarize a beatmap set
+    pub fn from_set(set: &BeatmapSet) -> Self {
+        let metadata = set.metadata();
+        let mut modes: Vec<GameMode> = Vec::new();
+        for beatmap in &set.beatmaps {
+            if !modes.contains(&beatmap.mode) {
+                modes.push(beatmap.mode);
+            }
+        }
+        let range = set.difficulty_range();
+
+        Self {
+            set_id: set.id,
+            artist: metadata.map(|m| m.artist.clone()).unwrap_or_default(),
+            title: metadata.map(|m| m.title.clone()).unwrap_or_default(),
+            creator: metadata.map(|m| m.creator.clone()).unwrap_or_default(),
+            modes,
+            star_min: range.map(|(min, _)| min),
+            star_max: range.map(|(_, max)| max),
+            difficulty_count: set.beatmaps.len(),
+            folder_name: set.folder_name.clone(),
+        }
+    }
+}
 
 /// Engine for filtering beatmap sets against criteria
 pub struct FilterEngine;
@@ -21,6 +77,30 @@ impl FilterEngine {
             .collect()
     }
 
+    /// Filter stable beatmap sets, using `index` for the search query
+    ///
+    /// `index` must have been built from `sets`. The search query is matched
+    /// by word prefix as described on [`BeatmapSearchIndex`] instead of by
+    /// substring; all other criteria are checked as in
+    /// [`filter_stable`](Self::filter_stable).
+    pub fn filter_stable_indexed<'a>(
+        sets: &'a [BeatmapSet],
+        index: &BeatmapSearchIndex,
+        criteria: &FilterCriteria,
+    ) -> Vec<&'a BeatmapSet> {
+        let Some(query) = criteria.search_query.as_deref().filter(|q| !q.is_empty()) else {
+            return Self::filter_stable(sets, criteria);
+        };
+        let mut rest = criteria.clone();
+        rest.clear_search();
+        index
+            .search(query)
+            .into_iter()
+            .filter_map(|idx| sets.get(idx))
+            .filter(|set| Self::matches_stable(set, &rest))
+            .collect()
+    }
+
     /// Filter lazer beatmap sets, returning references to matching sets
     pub fn filter_lazer<'a>(
         sets: &'a [LazerBeatmapSet],
@@ -48,8 +128,9 @@ impl FilterEngine {
             }
 
             // Star rating filter
+            let star_rating = beatmap.star_rating_with_mods(criteria.star_rating_mods);
             if let Some(min_stars) = criteria.star_rating_min {
-                if let Some(sr) = beatmap.star_rating {
+                if let Some(sr) = star_rating {
                     if sr < min_stars {
                         return false;
                     }
@@ -60,7 +141,7 @@ impl FilterEngine {
             }
 
             if let Some(max_stars) = criteria.star_rating_max {
-                if let Some(sr) = beatmap.star_rating {
+                if let Some(sr) = star_rating {
                     if sr > max_stars {
                         return false;
                     }
@@ -173,8 +254,9 @@ impl FilterEngine {
             }
 
             // Star rating filter
+            let star_rating = beatmap.star_rating_with_mods(criteria.star_rating_mods);
             if let Some(min_stars) = criteria.star_rating_min {
-                if let Some(sr) = beatmap.star_rating {
+                if let Some(sr) = star_rating {
                     if sr < min_stars {
                         return false;
                     }
@@ -184,7 +266,7 @@ impl FilterEngine {
             }
 
             if let Some(max_stars) = criteria.star_rating_max {
-                if let Some(sr) = beatmap.star_rating {
+                if let Some(sr) = star_rating {
                     if sr > max_stars {
                         return false;
                     }
@@ -279,6 +361,23 @@ impl FilterEngine {
         true
     }
 
+    /// Search stable beatmap sets, returning at most `limit` summaries
+    ///
+    /// Matching uses the same rules as [`matches_stable`](Self::matches_stable),
+    /// so a query such as "Camellia" combined with `with_min_stars(7.0)` finds
+    /// sets with at least one difficulty above 7 stars.
+    pub fn search_stable(
+        sets: &[BeatmapSet],
+        criteria: &FilterCriteria,
+        limit: usize,
+    ) -> Vec<BeatmapSearchHit> {
+        sets.iter()
+            .filter(|set| Self::matches_stable(set, criteria))
+            .take(limit)
+            .map(BeatmapSearchHit::from_set)
+            .collect()
+    }
+
     /// Count matching stable beatmap sets
     pub fn count_stable(sets: &[BeatmapSet], criteria: &FilterCriteria) -> usize {
         if criteria.is_empty() {
@@ -326,10 +425,14 @@ mod tests {
                 mode,
                 version: "Normal".to_string(),
                 star_rating: None,
+                star_ratings: Default::default(),
                 ranked_status: None,
+                format_version: 0,
             }],
             files: vec![],
             folder_name: Some("1 TestArtist - TestTitle".to_string()),
+            missing_files: Vec::new(),
+            folder_name_os: None,
         }
     }
 
@@ -360,10 +463,14 @@ mod tests {
                 mode,
                 version: "Normal".to_string(),
                 star_rating,
+                star_ratings: Default::default(),
                 ranked_status,
+                format_version: 0,
             }],
             files: vec![],
             folder_name: Some("1 TestArtist - TestTitle".to_string()),
+            missing_files: Vec::new(),
+            folder_name_os: None,
         }
     }
 
@@ -639,4 +746,87 @@ mod tests {
         let criteria = FilterCriteria::new().with_search("TestArtist");
         assert!(FilterEngine::matches_stable(&set, &criteria));
     }
+
+    #[test]
+    fn test_star_rating_filter_with_mods() {
+        let mut set = create_test_set_with_details(
+            "Song",
+            "Artist",
+            "Mapper",
+            GameMode::Osu,
+            Some(4.5),
+            None,
+        );
+        set.beatmaps[0].star_ratings = [(0, 4.5), (64, 6.2)].into_iter().collect();
+
+        let nomod = FilterCriteria::new().with_min_stars(6.0);
+        assert!(!FilterEngine::matches_stable(&set, &nomod));
+
+        let with_dt = FilterCriteria::new().with_min_stars(6.0).with_star_mods(64);
+        assert!(FilterEngine::matches_stable(&set, &with_dt));
+
+        // No rating stored for HR
+        let with_hr = FilterCriteria::new().with_min_stars(1.0).with_star_mods(16);
+        assert!(!FilterEngine::matches_stable(&set, &with_hr));
+    }
+
+    #[test]
+    fn test_search_stable_caps_results() {
+        let sets = vec![
+            create_test_set_with_details("Ghost", "Camellia", "A", GameMode::Osu, Some(7.5), None),
+            create_test_set_with_details(
+                "Feelin Sky",
+                "Camellia",
+                "B",
+                GameMode::Osu,
+                Some(5.0),
+                None,
+            ),
+            create_test_set_with_details(
+                "Exit This Earth",
+                "Camellia",
+                "C",
+                GameMode::Taiko,
+                Some(8.1),
+                None,
+            ),
+            create_test_set_with_details("Other", "Someone", "D", GameMode::Osu, Some(9.0), None),
+        ];
+        let criteria = FilterCriteria::new()
+            .with_search("camellia")
+            .with_min_stars(7.0);
+
+        let hits = FilterEngine::search_stable(&sets, &criteria, 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].title, "Ghost");
+        assert_eq!(hits[1].modes, vec![GameMode::Taiko]);
+        assert_eq!(hits[1].star_min, Some(8.1));
+
+        assert_eq!(FilterEngine::search_stable(&sets, &criteria, 1).len(), 1);
+        let json = serde_json::to_string(&hits[0]).unwrap();
+        assert!(json.contains("\"artist\":\"Camellia\""));
+    }
+
+    #[test]
+    fn test_filter_stable_indexed() {
+        let sets = vec![
+            create_test_set_with_details("Ghost", "Camellia", "A", GameMode::Osu, Some(7.5), None),
+            create_test_set_with_details("Ghost", "Camellia", "B", GameMode::Osu, Some(5.0), None),
+            create_test_set_with_details("Other", "Someone", "C", GameMode::Osu, Some(9.0), None),
+        ];
+        let index = BeatmapSearchIndex::build(&sets);
+        let criteria = FilterCriteria::new()
+            .with_search("came gho")
+            .with_min_stars(7.0);
+
+        let matched = FilterEngine::filter_stable_indexed(&sets, &index, &criteria);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].metadata().unwrap().creator, "A");
+
+        let no_query = FilterCriteria::new().with_min_stars(7.0);
+        assert_eq!(
+            FilterEngine::filter_stable_indexed(&sets, &index, &no_query).len(),
+            2
+        );
+    }
 }
@@ -0,0 +1,161 @@
+//! In-memory search index over scanned beatmap sets
+
+use std::collections::BTreeMap;
+
+use crate::beatmap::BeatmapSet;
+
+/// Word index for fast text search over a slice of beatmap sets
+///
+/// Artist, title (romanized and unicode), tags and mapper are split into
+/// lowercase words. A query matches a set when every word of the query is a
+/// prefix of one of the set's words, so "cam fre" finds "Camellia - Freedom
+/// Dive". The index refers to sets by position and must be rebuilt when the
+/// slice it was built from changes, e.g. after a rescan.
+#[derive(Debug, Clone, Default)]
+pub struct BeatmapSearchIndex {
+    /// Word -> sorted, deduplicated set indices
+    words: BTreeMap<String, Vec<usize>>,
+    /// Number of sets indexed
+    len: usize,
+}
+
+impl BeatmapSearchIndex {
+    /// Index the given sets
+    pub fn build(sets: &[BeatmapSet]) -> Self {
+        let mut words: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (idx, set) in sets.iter().enumerate() {
+            let Some(meta) = set.metadata() else {
+                continue;
+            };
+            let fields = [
+                Some(meta.artist.as_str()),
+                meta.artist_unicode.as_deref(),
+                Some(meta.title.as_str()),
+                meta.title_unicode.as_deref(),
+                Some(meta.creator.as_str()),
+            ];
+            let text = fields
+                .into_iter()
+                .flatten()
+                .chain(meta.tags.iter().map(String::as_str));
+            for field in text {
+                for word in tokenize(field) {
+                    let entry = words.entry(word).or_default();
+                    // Sets are visited in order, so checking the last entry dedups
+                    if entry.last() != Some(&idx) {
+                        entry.push(idx);
+                    }
+                }
+            }
+        }
+        Self {
+            words,
+            len: sets.len(),
+        }
+    }
+
+    /// Number of sets the index was built from
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the index was built from no sets
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Indices of sets matching every word of `query`, in ascending order
+    ///
+    /// An empty query matches every set.
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        let mut result: Option<Vec<usize>> = None;
+        for word in tokenize(query) {
+            let matches = self.prefix_matches(&word);
+            result = Some(match result {
+                Some(current) => intersect(&current, &matches),
+                None => matches,
+            });
+            if result.as_ref().is_some_and(Vec::is_empty) {
+                break;
+            }
+        }
+        result.unwrap_or_else(|| (0..self.len).collect())
+    }
+
+    /// Sets with any word starting with `prefix`, sorted and deduplicated
+    fn prefix_matches(&self, prefix: &str) -> Vec<usize> {
+        let mut matches: Vec<usize> = self
+            .words
+            .range(prefix.to_string()..)
+            .take_while(|(word, _)| word.starts_with(prefix))
+            .flat_map(|(_, sets)| sets.iter().copied())
+            .collect();
+        matches.sort_unstable();
+        matches.dedup();
+        matches
+    }
+}
+
+/// Split text into lowercase alphanumeric words
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Intersect two sorted index lists
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beatmap::{BeatmapInfo, BeatmapMetadata};
+
+    fn set(artist: &str, title: &str, creator: &str, tags: &[&str]) -> BeatmapSet {
+        let mut set = BeatmapSet::new();
+        set.beatmaps.push(BeatmapInfo {
+            metadata: BeatmapMetadata {
+                artist: artist.to_string(),
+                title: title.to_string(),
+                creator: creator.to_string(),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        set
+    }
+
+    #[test]
+    fn test_search_index() {
+        let sets = vec![
+            set("Camellia", "Freedom Dive", "Someone", &["electronic"]),
+            set("xi", "FREEDOM DiVE", "Nakagawa-Kanon", &["bms"]),
+            set("Camellia", "Exit This Earth's Atomosphere", "Mapper", &[]),
+        ];
+        let index = BeatmapSearchIndex::build(&sets);
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.search("freedom"), vec![0, 1]);
+        assert_eq!(index.search("cam fre"), vec![0]);
+        assert_eq!(index.search("KANON"), vec![1]);
+        assert_eq!(index.search("elec"), vec![0]);
+        assert_eq!(index.search("  "), vec![0, 1, 2]);
+        assert!(index.search("camellia bms").is_empty());
+    }
+}
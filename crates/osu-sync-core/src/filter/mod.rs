@@ -5,6 +5,8 @@
 
 mod criteria;
 mod engine;
+mod index;
 
 pub use criteria::FilterCriteria;
-pub use engine::FilterEngine;
+pub use engine::{BeatmapSearchHit, FilterEngine};
+pub use index::BeatmapSearchIndex;
@@ -0,0 +1,359 @@
+//! On-disk cache of file hashes
+//!
+//! Hashing a large Songs folder reads every byte of every file. A
+//! [`HashCache`] remembers each file's hashes together with its size and
+//! modification time, so files that haven't changed since the last run are
+//! not read again. An entry is recomputed as soon as either value differs.
+//!
+//! The cache is consulted by [`IntegrityChecker`](crate::stats::IntegrityChecker)
+//! for the MD5s it compares against osu!.db. Songs folder scans, whose file
+//! hashes are what duplicate detection compares, reuse the hashes kept in the
+//! scanner's own cache instead.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::config::paths;
+use crate::error::{Error, Result};
+
+/// Bumped whenever the on-disk layout changes; older files are discarded
+const CACHE_VERSION: u32 = 1;
+
+/// Hash function a cached hash was computed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    Md5,
+    Blake3,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Hash `content` as a lowercase hex string
+    fn digest(self, content: &[u8]) -> String {
+        match self {
+            Self::Md5 => format!("{:x}", Md5::digest(content)),
+            Self::Blake3 => blake3::hash(content).to_hex().to_string(),
+            Self::Sha256 => format!("{:x}", <Sha256 as sha2::Digest>::digest(content)),
+        }
+    }
+}
+
+/// A file hash returned by [`HashCache::hash_file`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashedFile {
+    /// Lowercase hex hash
+    pub hash: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Modification time since the UNIX epoch
+    pub modified: Duration,
+    /// Whether the hash came from the cache rather than reading the file
+    pub cached: bool,
+}
+
+/// Hashes of one file, valid while its size and mtime are unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    size: u64,
+    /// Modification time since the UNIX epoch
+    mtime: Duration,
+    hashes: HashMap<HashAlgorithm, String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+/// File hashes keyed by path, size and modification time
+///
+/// Lookups are thread-safe, so one cache can be shared by parallel scans
+/// through an `Arc`. Changes are only written by [`save`](Self::save).
+#[derive(Debug, Default)]
+pub struct HashCache {
+    /// File the cache is saved to; `None` keeps it in memory only
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<PathBuf, CacheEntry>>,
+    dirty: AtomicBool,
+}
+
+impl HashCache {
+    /// Create an empty cache that is never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Where the cache is stored by default, under the cache directory
+    pub fn default_path() -> Option<PathBuf> {
+        paths::cache_dir().map(|p| p.join("hash_cache.bin"))
+    }
+
+    /// Where the cache was kept before it moved to the cache directory
+    fn legacy_path() -> Option<PathBuf> {
+        paths::app_data_dir().map(|p| p.join("hash_cache.bin"))
+    }
+
+    /// Load the cache from [`default_path`](Self::default_path)
+    ///
+    /// A cache left in the app data directory by older versions is moved
+    /// there first.
+    pub fn load() -> Self {
+        match Self::default_path() {
+            Some(path) => {
+                if let Some(legacy) = Self::legacy_path() {
+                    migrate_legacy_cache(&legacy, &path);
+                }
+                Self::open(path)
+            }
+            None => Self::new(),
+        }
+    }
+
+    /// Load the cache stored at `path`
+    ///
+    /// A missing, unreadable or outdated file gives an empty cache that will
+    /// be written to `path` on [`save`](Self::save).
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<CacheFile>(&bytes).ok())
+            .filter(|file| file.version == CACHE_VERSION)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Number of files with cached hashes
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether no file has a cached hash
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash a file, reading it only if it changed since it was last hashed
+    pub fn hash_file(&self, path: &Path, algorithm: HashAlgorithm) -> io::Result<HashedFile> {
+        let metadata = fs::metadata(path)?;
+        let size = metadata.len();
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .unwrap_or_default();
+
+        {
+            let entries = self.entries.lock().unwrap();
+            if let Some(entry) = entries.get(path) {
+                if entry.size == size && entry.mtime == mtime {
+                    if let Some(hash) = entry.hashes.get(&algorithm) {
+                        return Ok(HashedFile {
+                            hash: hash.clone(),
+                            size,
+                            modified: mtime,
+                            cached: true,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Hash outside the lock so parallel callers don't wait on each other
+        let hash = if size > 1024 * 1024 {
+            let file = fs::File::open(path)?;
+            let mmap = unsafe { memmap2::Mmap::map(&file)? };
+            algorithm.digest(&mmap)
+        } else {
+            algorithm.digest(&fs::read(path)?)
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries
+            .entry(path.to_path_buf())
+            .or_insert_with(|| CacheEntry {
+                size,
+                mtime,
+                hashes: HashMap::new(),
+            });
+        if entry.size != size || entry.mtime != mtime {
+            // The file changed, so hashes from other algorithms are stale too
+            entry.size = size;
+            entry.mtime = mtime;
+            entry.hashes.clear();
+        }
+        entry.hashes.insert(algorithm, hash.clone());
+        self.dirty.store(true, Ordering::Relaxed);
+
+        Ok(HashedFile {
+            hash,
+            size,
+            modified: mtime,
+            cached: false,
+        })
+    }
+
+    /// Drop entries for files that no longer exist
+    pub fn prune(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|path, _| path.exists());
+        if entries.len() != before {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Write the cache to its file if anything changed since it was loaded
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let entries = self.entries.lock().unwrap().clone();
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            entries,
+        };
+        let bytes = bincode::serialize(&file)
+            .map_err(|e| Error::Other(format!("Failed to save hash cache: {}", e)))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+        }
+        fs::write(path, bytes).map_err(|e| Error::io_at(e, path))?;
+        self.dirty.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Move a cache from its old location `legacy` to `path`
+///
+/// If `path` already holds a cache, or the move fails, the old file is
+/// deleted instead; it can always be rebuilt. Where the data and cache
+/// directories are the same, as on Windows, there is nothing to do.
+fn migrate_legacy_cache(legacy: &Path, path: &Path) {
+    if legacy == path || !legacy.exists() {
+        return;
+    }
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if fs::rename(legacy, path).is_ok() {
+            return;
+        }
+    }
+    if let Err(e) = fs::remove_file(legacy) {
+        tracing::warn!(
+            "Failed to remove old hash cache {}: {}",
+            legacy.display(),
+            e
+        );
+    }
+}
+
+/// Delete the hash cache at [`HashCache::default_path`]
+///
+/// Every file is hashed again on the next run. Useful when cached hashes are
+/// suspected to be wrong, e.g. after a tool rewrote files while preserving
+/// their modification times.
+pub fn clear_hash_cache() -> Result<()> {
+    let Some(path) = HashCache::default_path() else {
+        return Ok(());
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(Error::io_at(e, path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hash_cache_reuses_and_invalidates() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("audio.mp3");
+        fs::write(&file, b"first").unwrap();
+
+        let cache_path = dir.path().join("cache.bin");
+        let cache = HashCache::open(&cache_path);
+        let first = cache.hash_file(&file, HashAlgorithm::Md5).unwrap();
+        assert!(!first.cached);
+        assert_eq!(first.hash, format!("{:x}", Md5::digest(b"first")));
+        assert!(cache.hash_file(&file, HashAlgorithm::Md5).unwrap().cached);
+        assert!(
+            !cache
+                .hash_file(&file, HashAlgorithm::Blake3)
+                .unwrap()
+                .cached
+        );
+        cache.save().unwrap();
+
+        let reloaded = HashCache::open(&cache_path);
+        assert_eq!(reloaded.len(), 1);
+        assert!(
+            reloaded
+                .hash_file(&file, HashAlgorithm::Md5)
+                .unwrap()
+                .cached
+        );
+
+        // A different size invalidates the entry even if mtime is unchanged
+        fs::write(&file, b"second, longer").unwrap();
+        let changed = reloaded.hash_file(&file, HashAlgorithm::Md5).unwrap();
+        assert!(!changed.cached);
+        assert_eq!(
+            changed.hash,
+            format!("{:x}", Md5::digest(b"second, longer"))
+        );
+
+        fs::remove_file(&file).unwrap();
+        reloaded.prune();
+        assert!(reloaded.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_legacy_cache() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("audio.mp3");
+        fs::write(&file, b"audio").unwrap();
+        let legacy = dir.path().join("data").join("hash_cache.bin");
+        let path = dir.path().join("cache").join("hash_cache.bin");
+
+        let cache = HashCache::open(&legacy);
+        cache.hash_file(&file, HashAlgorithm::Md5).unwrap();
+        cache.save().unwrap();
+
+        migrate_legacy_cache(&legacy, &path);
+        assert!(!legacy.exists());
+        assert_eq!(HashCache::open(&path).len(), 1);
+
+        // With a cache already in place, the old one is only deleted
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, b"old").unwrap();
+        migrate_legacy_cache(&legacy, &path);
+        assert!(!legacy.exists());
+        assert_eq!(HashCache::open(&path).len(), 1);
+    }
+}
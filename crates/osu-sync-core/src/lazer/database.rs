@@ -152,10 +152,20 @@ pub struct LazerBeatmapInfo {
     pub bpm: f64,
     /// Star rating for this difficulty (from osu! database)
     pub star_rating: Option<f32>,
+    /// Star ratings keyed by mod bitmask (from osu! database, 0 = no mods)
+    #[serde(default)]
+    pub star_ratings: HashMap<u32, f32>,
     /// Ranked status of this beatmap
     pub ranked_status: Option<RankedStatus>,
 }
 
+impl LazerBeatmapInfo {
+    /// Star rating with the given mods applied, if known
+    pub fn star_rating_with_mods(&self, mods: u32) -> Option<f32> {
+        crate::beatmap::star_rating_for_mods(self.star_rating, &self.star_ratings, mods)
+    }
+}
+
 /// Beatmap set as stored in lazer's Realm database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LazerBeatmapSet {
@@ -218,6 +228,16 @@ impl LazerDatabase {
         })
     }
 
+    /// A database without a Realm file, for tests that supply the sets
+    #[cfg(test)]
+    pub(crate) fn without_realm(data_path: &Path) -> Self {
+        Self {
+            data_path: data_path.to_path_buf(),
+            file_store: LazerFileStore::new(data_path),
+            realm_group: None,
+        }
+    }
+
     /// Check if the Realm database is available for reading
     pub fn is_realm_available(&self) -> bool {
         self.realm_group.is_some()
@@ -595,6 +615,7 @@ impl LazerDatabase {
             length_ms,
             bpm,
             star_rating: None, // Not available from .osu file
+            star_ratings: HashMap::new(),
             ranked_status: None,
         }
     }
@@ -836,6 +857,7 @@ impl LazerDatabase {
             length_ms,
             bpm,
             star_rating,
+            star_ratings: HashMap::new(),
             ranked_status,
         })
     }
@@ -1114,7 +1136,9 @@ impl LazerDatabase {
                 mode: lb.mode,
                 version: lb.version.clone(),
                 star_rating: lb.star_rating,
+                star_ratings: lb.star_ratings.clone(),
                 ranked_status: lb.ranked_status,
+                format_version: 0,
             })
             .collect();
 
@@ -1133,6 +1157,8 @@ impl LazerDatabase {
             beatmaps,
             files,
             folder_name: None,
+            missing_files: Vec::new(),
+            folder_name_os: None,
         }
     }
 }
@@ -1174,6 +1200,65 @@ mod tests {
             "Cache should invalidate when signature changes"
         );
     }
+
+    #[test]
+    fn star_ratings_keyed_by_mods() {
+        // NoMod, DT, HR+DT, plus an unprocessed NaN entry
+        let ratings =
+            star_ratings_from_pairs(vec![(0, 5.25), (64, 7.5), (80, 8.125), (256, f64::NAN)]);
+
+        assert_eq!(ratings.len(), 3);
+        assert_eq!(ratings.get(&64), Some(&7.5));
+
+        let beatmap = LazerBeatmapInfo {
+            id: String::new(),
+            online_id: None,
+            hash: String::new(),
+            md5_hash: String::new(),
+            metadata: BeatmapMetadata::default(),
+            difficulty: BeatmapDifficulty::default(),
+            version: String::new(),
+            mode: GameMode::Osu,
+            length_ms: 0,
+            bpm: 0.0,
+            star_rating: ratings.get(&0).copied(),
+            star_ratings: ratings,
+            ranked_status: None,
+        };
+        assert_eq!(beatmap.star_rating_with_mods(0), Some(5.25));
+        // Nightcore counts as DT; Hidden doesn't change stars
+        assert_eq!(beatmap.star_rating_with_mods(512 | 64 | 8), Some(7.5));
+        assert_eq!(beatmap.star_rating_with_mods(64 | 16), Some(8.125));
+        assert_eq!(beatmap.star_rating_with_mods(256), None);
+    }
+
+    #[test]
+    fn stable_db_header_offset_skips_player_name() {
+        let mut bytes = vec![0u8; 17];
+        bytes.extend_from_slice(&[0x0b, 3]);
+        bytes.extend_from_slice(b"abc");
+        bytes.extend_from_slice(&42u32.to_le_bytes());
+        assert_eq!(stable_db_count_offset(&bytes), Some(22));
+
+        let mut anonymous = vec![0u8; 17];
+        anonymous.push(0x00);
+        anonymous.extend_from_slice(&7u32.to_le_bytes());
+        assert_eq!(stable_db_count_offset(&anonymous), Some(18));
+    }
+
+    #[test]
+    fn truncated_stable_db_header_is_unreadable() {
+        let bytes = vec![0u8; 10];
+        assert_eq!(stable_db_count_offset(&bytes), None);
+
+        let err = recover_listing(Path::new("osu!.db"), &bytes).unwrap_err();
+        assert!(matches!(err, Error::DatabasePartial { .. }));
+
+        let mut bad_marker = vec![0u8; 17];
+        bad_marker.push(0x42);
+        bad_marker.extend_from_slice(&[0u8; 4]);
+        assert_eq!(stable_db_count_offset(&bad_marker), None);
+    }
 }
 
 /// Build an index of lazer beatmaps for fast lookup
@@ -1253,6 +1338,18 @@ impl LazerIndex {
 // osu!stable database reader using osu-db crate
 // =============================================================================
 
+/// Collect (mod bitmask, stars) pairs into a lookup map
+///
+/// Non-finite ratings are dropped. If the same mod combination appears
+/// twice, the last entry wins.
+fn star_ratings_from_pairs(pairs: impl IntoIterator<Item = (u32, f64)>) -> HashMap<u32, f32> {
+    pairs
+        .into_iter()
+        .filter(|(_, sr)| sr.is_finite())
+        .map(|(mods, sr)| (mods, sr as f32))
+        .collect()
+}
+
 /// Reader for osu!stable's osu!.db file using the osu-db crate
 ///
 /// This provides full support for reading the osu!.db binary format
@@ -1262,6 +1359,10 @@ pub struct StableDatabase {
     data_path: PathBuf,
     /// Parsed listing from osu!.db
     listing: osu_db::Listing,
+    /// Whether the listing was recovered from a damaged file
+    partial: bool,
+    /// Number of beatmap records that could not be read
+    records_skipped: usize,
 }
 
 impl StableDatabase {
@@ -1286,15 +1387,36 @@ impl StableDatabase {
             return Err(Error::OsuNotFound(osu_path.to_path_buf()));
         }
 
-        let listing = osu_db::Listing::from_file(&db_path)
-            .map_err(|e| Error::Realm(format!("Failed to parse osu!.db: {}", e)))?;
+        let bytes = std::fs::read(&db_path)?;
+        let (listing, records_skipped) = match osu_db::Listing::from_bytes(&bytes) {
+            Ok(listing) => (listing, 0),
+            Err(e) => {
+                tracing::warn!(
+                    "osu!.db failed to parse ({}), attempting partial recovery",
+                    e
+                );
+                recover_listing(&db_path, &bytes)?
+            }
+        };
 
         Ok(Self {
             data_path: osu_path.to_path_buf(),
             listing,
+            partial: records_skipped > 0,
+            records_skipped,
         })
     }
 
+    /// Whether the listing was only partially recovered from a damaged osu!.db
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    /// Number of beatmap records skipped because they could not be parsed
+    pub fn records_skipped(&self) -> usize {
+        self.records_skipped
+    }
+
     /// Get the osu! data path
     pub fn data_path(&self) -> &Path {
         &self.data_path
@@ -1432,8 +1554,9 @@ impl StableDatabase {
         // Calculate approximate BPM from timing points
         let bpm = self.calculate_bpm(beatmap);
 
-        // Extract star rating for the beatmap's mode (no-mods, key 0)
-        let star_rating = Self::extract_star_rating(beatmap, &mode);
+        // Extract star ratings for the beatmap's mode (no-mods, key 0)
+        let star_ratings = Self::extract_star_ratings(beatmap, &mode);
+        let star_rating = star_ratings.get(&0).copied();
 
         // Convert ranked status
         let ranked_status = Self::convert_ranked_status(beatmap.status);
@@ -1454,14 +1577,18 @@ impl StableDatabase {
             length_ms: beatmap.total_time as u64,
             bpm,
             star_rating,
+            star_ratings,
             ranked_status,
         }
     }
 
-    /// Extract star rating from osu-db beatmap for the given mode (no-mods)
-    fn extract_star_rating(beatmap: &osu_db::listing::Beatmap, mode: &GameMode) -> Option<f32> {
-        // Star ratings are stored per mode as Vec<(ModSet, f64)>
-        // ModSet with raw value 0 = no mods
+    /// Extract star ratings from osu-db beatmap for the given mode, keyed by mod bitmask
+    fn extract_star_ratings(
+        beatmap: &osu_db::listing::Beatmap,
+        mode: &GameMode,
+    ) -> HashMap<u32, f32> {
+        // Star ratings are stored per mode as an int-prefixed list of
+        // (ModSet, f64) pairs; ModSet with raw value 0 = no mods
         let ratings = match mode {
             GameMode::Osu => &beatmap.std_ratings,
             GameMode::Taiko => &beatmap.taiko_ratings,
@@ -1469,11 +1596,7 @@ impl StableDatabase {
             GameMode::Mania => &beatmap.mania_ratings,
         };
 
-        // Find no-mod star rating (mods with bits value 0)
-        ratings
-            .iter()
-            .find(|(mods, _)| mods.bits() == 0)
-            .map(|(_, sr)| *sr as f32)
+        star_ratings_from_pairs(ratings.iter().map(|(mods, sr)| (mods.bits(), *sr)))
     }
 
     /// Convert osu-db ranked status to our RankedStatus enum
@@ -1595,7 +1718,9 @@ impl StableDatabase {
                 mode: lb.mode,
                 version: lb.version.clone(),
                 star_rating: lb.star_rating,
+                star_ratings: lb.star_ratings.clone(),
                 ranked_status: lb.ranked_status,
+                format_version: 0,
             })
             .collect();
 
@@ -1614,6 +1739,8 @@ impl StableDatabase {
             beatmaps,
             files,
             folder_name: None,
+            missing_files: Vec::new(),
+            folder_name_os: None,
         }
     }
 
@@ -1705,3 +1832,89 @@ impl StableIndex {
         self.sets.iter().map(|s| s.beatmaps.len()).sum()
     }
 }
+
+/// Offset of the beatmap count field in an osu!.db header.
+///
+/// The header is: version (u32), folder count (u32), account unlocked (bool),
+/// unlock date (u64), player name (osu! string), beatmap count (u32).
+fn stable_db_count_offset(bytes: &[u8]) -> Option<usize> {
+    let mut pos = 4 + 4 + 1 + 8;
+    match *bytes.get(pos)? {
+        0x00 => pos += 1,
+        0x0b => {
+            pos += 1;
+            let mut len: usize = 0;
+            let mut shift = 0;
+            loop {
+                let byte = *bytes.get(pos)?;
+                pos += 1;
+                len |= ((byte & 0x7f) as usize) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+                if shift > 63 {
+                    return None;
+                }
+            }
+            pos = pos.checked_add(len)?;
+        }
+        _ => return None,
+    }
+    if pos + 4 > bytes.len() {
+        return None;
+    }
+    Some(pos)
+}
+
+/// Recover as many leading beatmap records as possible from a damaged osu!.db.
+///
+/// The declared beatmap count is rewritten and the largest prefix of records
+/// that still parses is found by binary search. Returns the listing together
+/// with the number of records that had to be skipped.
+fn recover_listing(db_path: &Path, bytes: &[u8]) -> Result<(osu_db::Listing, usize)> {
+    let unreadable = |reason: &str| Error::DatabasePartial {
+        path: db_path.to_path_buf(),
+        reason: reason.to_string(),
+    };
+
+    let offset = stable_db_count_offset(bytes).ok_or_else(|| unreadable("header is truncated"))?;
+    let declared = u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ]);
+
+    let mut patched = bytes.to_vec();
+    // Guarantee the trailing permissions field is present even when the file
+    // was cut off mid-record.
+    patched.extend_from_slice(&[0u8; 4]);
+    let mut parse_with = |count: u32| {
+        patched[offset..offset + 4].copy_from_slice(&count.to_le_bytes());
+        osu_db::Listing::from_bytes(&patched).ok()
+    };
+
+    let mut best = parse_with(0).ok_or_else(|| unreadable("header could not be parsed"))?;
+    let (mut lo, mut hi) = (0u32, declared);
+    while lo < hi {
+        let mid = lo + (hi - lo).div_ceil(2);
+        match parse_with(mid) {
+            Some(listing) => {
+                best = listing;
+                lo = mid;
+            }
+            None => hi = mid - 1,
+        }
+    }
+
+    let recovered = best.beatmaps.len();
+    let skipped = (declared as usize).saturating_sub(recovered).max(1);
+    tracing::warn!(
+        "Recovered {} of {} beatmap records from {}",
+        recovered,
+        declared,
+        db_path.display()
+    );
+    Ok((best, skipped))
+}
@@ -1,19 +1,58 @@
 //! Export beatmaps from osu!lazer
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::lazer::{LazerBeatmapSet, LazerDatabase};
 use crate::parser::create_osz_from_set;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+/// Progress of reading a set's files out of the lazer file store
+#[derive(Debug, Clone, Default)]
+pub struct LazerExportProgress {
+    /// Original name of the file being read
+    pub current_file: String,
+    /// Files fully read so far
+    pub files_done: usize,
+    /// Number of files in the set
+    pub total_files: usize,
+    /// Bytes read so far, across all files
+    pub bytes_done: u64,
+    /// Size of all files in the set
+    pub total_bytes: u64,
+}
+
+impl LazerExportProgress {
+    /// Calculate progress as a ratio (0.0 to 1.0), by bytes
+    pub fn ratio(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.bytes_done as f64 / self.total_bytes as f64
+    }
+}
+
+/// Progress callback for lazer exports
+pub type LazerExportProgressCallback = Box<dyn Fn(LazerExportProgress) + Send + Sync>;
 
 /// Exporter for extracting beatmaps from osu!lazer
 pub struct LazerExporter {
     database: LazerDatabase,
+    progress_callback: Option<LazerExportProgressCallback>,
 }
 
 impl LazerExporter {
     /// Create a new exporter for the given lazer database
     pub fn new(database: LazerDatabase) -> Self {
-        Self { database }
+        Self {
+            database,
+            progress_callback: None,
+        }
+    }
+
+    /// Set a callback reporting per-file and per-byte progress while a
+    /// set's files are read from the file store
+    pub fn with_progress_callback(mut self, callback: LazerExportProgressCallback) -> Self {
+        self.progress_callback = Some(callback);
+        self
     }
 
     /// Export a beatmap set to an .osz file
@@ -35,13 +74,59 @@ impl LazerExporter {
     }
 
     /// Read all files for a beatmap set from the file store
+    ///
+    /// Files are named after the set's named-file records, with `/` as the
+    /// separator for files in subfolders (e.g. storyboard images). Records
+    /// without a usable name are skipped, so no file ends up named after its
+    /// content hash.
     pub fn read_set_files(&self, lazer_set: &LazerBeatmapSet) -> Result<Vec<(String, Vec<u8>)>> {
         let file_store = self.database.file_store();
-        let mut files = Vec::new();
+        let named_files: Vec<_> = lazer_set
+            .files
+            .iter()
+            .filter_map(|named_file| match original_name(&named_file.filename) {
+                Some(name) => Some((name, named_file)),
+                None => {
+                    tracing::warn!(
+                        "Skipping lazer file {} with unusable name '{}'",
+                        named_file.hash,
+                        named_file.filename
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        let mut progress = LazerExportProgress {
+            total_files: named_files.len(),
+            total_bytes: named_files
+                .iter()
+                .filter_map(|(_, f)| file_store.size(&f.hash))
+                .sum(),
+            ..Default::default()
+        };
+        let mut files = Vec::with_capacity(named_files.len());
+
+        for (name, named_file) in named_files {
+            let content = match &self.progress_callback {
+                Some(callback) => {
+                    progress.current_file = name.clone();
+                    callback(progress.clone());
+                    let start = progress.bytes_done;
+                    file_store.read_with_progress(&named_file.hash, |read| {
+                        progress.bytes_done = start + read;
+                        callback(progress.clone());
+                    })?
+                }
+                None => file_store.read(&named_file.hash)?,
+            };
+            progress.files_done += 1;
+            files.push((name, content));
+        }
 
-        for named_file in &lazer_set.files {
-            let content = file_store.read(&named_file.hash)?;
-            files.push((named_file.filename.clone(), content));
+        if let Some(callback) = &self.progress_callback {
+            progress.current_file.clear();
+            callback(progress);
         }
 
         Ok(files)
@@ -62,10 +147,13 @@ impl LazerExporter {
 
         std::fs::create_dir_all(&folder_path)?;
 
-        // Write all files
+        // Write all files, recreating subfolders
         for (filename, content) in files {
             let file_path = folder_path.join(&filename);
-            std::fs::write(file_path, content)?;
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+            }
+            std::fs::write(&file_path, content).map_err(|e| Error::io_at(e, &file_path))?;
         }
 
         Ok(folder_path)
@@ -82,3 +170,80 @@ impl LazerExporter {
             .collect()
     }
 }
+
+/// Name of a lazer file inside its set folder, with `/` separators
+///
+/// Returns `None` for empty names and names that would escape the folder.
+pub(crate) fn original_name(filename: &str) -> Option<String> {
+    let normalized = filename.replace('\\', "/");
+    let mut parts = Vec::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lazer::{LazerFileStore, LazerNamedFile};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_original_name() {
+        assert_eq!(original_name("audio.mp3").as_deref(), Some("audio.mp3"));
+        assert_eq!(original_name("sb\\bg.png").as_deref(), Some("sb/bg.png"));
+        assert_eq!(original_name("./sb//bg.png").as_deref(), Some("sb/bg.png"));
+        assert_eq!(original_name(""), None);
+        assert_eq!(original_name("../escape.png"), None);
+        assert_eq!(original_name("/abs.png"), None);
+    }
+
+    #[test]
+    fn test_export_to_stable_folder_reports_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = temp_dir.path().join("lazer");
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(data.join("client.realm"), b"").unwrap();
+        let store = LazerFileStore::new(&data);
+
+        let mut files = Vec::new();
+        for (name, content) in [("audio.mp3", &b"audio"[..]), ("sb\\bg.png", &b"image!"[..])] {
+            let hash = LazerFileStore::calculate_hash(content);
+            let path = store.hash_to_path(&hash);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            files.push(LazerNamedFile {
+                filename: name.to_string(),
+                hash,
+            });
+        }
+        let set = LazerBeatmapSet {
+            id: "set".to_string(),
+            online_id: Some(1),
+            beatmaps: Vec::new(),
+            files,
+        };
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = updates.clone();
+        let exporter = LazerExporter::new(LazerDatabase::open(&data).unwrap())
+            .with_progress_callback(Box::new(move |p| sink.lock().unwrap().push(p)));
+
+        let songs = temp_dir.path().join("Songs");
+        let folder = exporter.export_to_stable_folder(&set, &songs).unwrap();
+        assert_eq!(std::fs::read(folder.join("audio.mp3")).unwrap(), b"audio");
+        assert_eq!(std::fs::read(folder.join("sb/bg.png")).unwrap(), b"image!");
+
+        let updates = updates.lock().unwrap();
+        let last = updates.last().unwrap();
+        assert_eq!((last.files_done, last.total_files), (2, 2));
+        assert_eq!((last.bytes_done, last.total_bytes), (11, 11));
+        assert!(updates.iter().any(|p| p.current_file == "sb/bg.png"));
+    }
+}
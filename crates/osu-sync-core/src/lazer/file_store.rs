@@ -2,10 +2,14 @@
 
 use crate::error::{Error, Result};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// Chunk size for [`LazerFileStore::read_with_progress`]
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
 /// Handler for osu!lazer's hash-based file storage
 ///
 /// Files are stored at: `files/{hash[0]}/{hash[0..2]}/{hash}`
@@ -45,26 +49,56 @@ impl LazerFileStore {
     /// Read a file by its hash
     pub fn read(&self, hash: &str) -> Result<Vec<u8>> {
         let path = self.hash_to_path(hash);
-        fs::read(&path).map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Error::BeatmapNotFound(format!("File with hash {} not found", hash))
-            } else {
-                Error::Io(e)
+        fs::read(&path).map_err(|e| read_error(hash, e))
+    }
+
+    /// Read a file by its hash in chunks, calling `on_progress` with the
+    /// number of bytes read so far after each chunk
+    pub fn read_with_progress(
+        &self,
+        hash: &str,
+        mut on_progress: impl FnMut(u64),
+    ) -> Result<Vec<u8>> {
+        use std::io::Read;
+        let path = self.hash_to_path(hash);
+        let mut file = fs::File::open(&path).map_err(|e| read_error(hash, e))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        let mut content = Vec::with_capacity(size as usize);
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+        loop {
+            let bytes_read = file.read(&mut chunk)?;
+            if bytes_read == 0 {
+                break;
             }
-        })
+            content.extend_from_slice(&chunk[..bytes_read]);
+            on_progress(content.len() as u64);
+        }
+        Ok(content)
+    }
+
+    /// Delete a file from the store; a missing file is not an error
+    ///
+    /// Only remove blobs no beatmap set in lazer's database references.
+    pub fn remove(&self, hash: &str) -> Result<()> {
+        let path = self.hash_to_path(hash);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::io_at(e, &path)),
+        }
+    }
+
+    /// Size in bytes of a stored file, if it exists
+    pub fn size(&self, hash: &str) -> Option<u64> {
+        fs::metadata(self.hash_to_path(hash)).ok().map(|m| m.len())
     }
 
     /// Read just the first N bytes of a file (for header detection)
     pub fn read_prefix(&self, hash: &str, len: usize) -> Result<Vec<u8>> {
         use std::io::Read;
         let path = self.hash_to_path(hash);
-        let mut file = fs::File::open(&path).map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                Error::BeatmapNotFound(format!("File with hash {} not found", hash))
-            } else {
-                Error::Io(e)
-            }
-        })?;
+        let mut file = fs::File::open(&path).map_err(|e| read_error(hash, e))?;
         let mut buffer = vec![0u8; len];
         let bytes_read = file.read(&mut buffer)?;
         buffer.truncate(bytes_read);
@@ -120,6 +154,20 @@ impl LazerFileStore {
         Ok(hashes)
     }
 
+    /// Distinct sizes of the files in the store
+    ///
+    /// Only needs file metadata, so it's much cheaper than hashing: a file
+    /// whose size isn't in the set can't already be stored.
+    pub fn file_sizes(&self) -> Result<HashSet<u64>> {
+        use rayon::prelude::*;
+
+        Ok(self
+            .list_all()?
+            .par_iter()
+            .filter_map(|hash| self.size(hash))
+            .collect())
+    }
+
     /// Get the last-modified time of the file store directory (seconds since UNIX epoch)
     pub fn store_mtime_secs(&self) -> Option<u64> {
         let metadata = fs::metadata(&self.files_path).ok()?;
@@ -134,6 +182,23 @@ impl LazerFileStore {
     pub fn calculate_hash(content: &[u8]) -> String {
         format!("{:x}", Sha256::digest(content))
     }
+
+    /// Calculate the SHA-256 hash of a file on disk, streaming its content
+    pub fn calculate_file_hash(path: &Path) -> Result<String> {
+        let mut file = fs::File::open(path).map_err(|e| Error::io_at(e, path))?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher).map_err(|e| Error::io_at(e, path))?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
+
+/// Report a missing store file as [`Error::BeatmapNotFound`]
+fn read_error(hash: &str, e: std::io::Error) -> Error {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        Error::BeatmapNotFound(format!("File with hash {} not found", hash))
+    } else {
+        Error::Io(e)
+    }
 }
 
 #[cfg(test)]
@@ -158,4 +223,18 @@ mod tests {
         let hash = LazerFileStore::calculate_hash(content);
         assert_eq!(hash.len(), 64); // SHA-256 produces 64 hex characters
     }
+
+    #[test]
+    fn test_file_sizes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = LazerFileStore::new(temp_dir.path());
+        assert!(store.file_sizes().unwrap().is_empty());
+
+        for content in [&b"abc"[..], b"xyz", b"hello"] {
+            let path = store.hash_to_path(&LazerFileStore::calculate_hash(content));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        assert_eq!(store.file_sizes().unwrap(), HashSet::from([3, 5]));
+    }
 }
@@ -9,11 +9,16 @@
 
 use crate::beatmap::BeatmapSet;
 use crate::error::{Error, Result};
-use crate::parser::create_osz_from_set;
-use crate::utils::sanitize_filename;
+use crate::lazer::{LazerFileStore, LazerNamedFile};
+use crate::parser::{validate_osz, write_osz_entries};
+use crate::sync::{SetDiff, Throttle};
+use crate::utils::{sanitize_filename, RetryPolicy};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -31,6 +36,165 @@ pub struct ImportResult {
     pub lazer_triggered: bool,
 }
 
+/// Name of the staging folder used by transactional imports
+const STAGING_DIR: &str = "osu-sync-staging";
+
+/// A set of blobs staged for the lazer file store
+///
+/// Files are written to a private staging folder first and only moved into
+/// the content-addressed store on [`commit`](Self::commit). Dropping an
+/// uncommitted transaction removes everything it staged.
+pub struct FileStoreTransaction {
+    store: LazerFileStore,
+    staging_path: PathBuf,
+    staged: Vec<(LazerNamedFile, PathBuf)>,
+    committed: bool,
+    retry: RetryPolicy,
+    /// Bytes not written because the blob was already in the store
+    reused_bytes: u64,
+}
+
+impl FileStoreTransaction {
+    /// Begin a transaction against the lazer data directory
+    pub fn begin(lazer_data_path: &Path) -> Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let staging_path =
+            lazer_data_path
+                .join(STAGING_DIR)
+                .join(format!("{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&staging_path).map_err(|e| Error::io_at(e, &staging_path))?;
+
+        Ok(Self {
+            store: LazerFileStore::new(lazer_data_path),
+            staging_path,
+            staged: Vec::new(),
+            committed: false,
+            retry: RetryPolicy::default(),
+            reused_bytes: 0,
+        })
+    }
+
+    /// Set how transient errors while writing blobs are retried
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Stage a file's content, returning its content hash
+    ///
+    /// Blobs already in the store are not written again; see
+    /// [`reused_bytes`](Self::reused_bytes).
+    pub fn stage(&mut self, filename: &str, content: &[u8]) -> Result<String> {
+        let hash = LazerFileStore::calculate_hash(content);
+        let staged_path = self.staging_path.join(&hash);
+        if self.store.exists(&hash) {
+            self.reused_bytes += content.len() as u64;
+        } else if !staged_path.exists() {
+            self.retry
+                .run(|| fs::write(&staged_path, content))
+                .map_err(|e| Error::io_at(e, &staged_path))?;
+        }
+        self.staged.push((
+            LazerNamedFile {
+                filename: filename.to_string(),
+                hash: hash.clone(),
+            },
+            staged_path,
+        ));
+        Ok(hash)
+    }
+
+    /// Bytes skipped so far because their blob was already in the store
+    pub fn reused_bytes(&self) -> u64 {
+        self.reused_bytes
+    }
+
+    /// Files staged so far
+    pub fn files(&self) -> Vec<LazerNamedFile> {
+        self.staged.iter().map(|(file, _)| file.clone()).collect()
+    }
+
+    /// Move staged blobs into the file store, then run `finalize`
+    ///
+    /// If moving any blob or `finalize` fails, blobs added to the store by
+    /// this transaction are removed again; blobs that already existed are
+    /// left untouched. Returns the number of new blobs written.
+    pub fn commit<F>(mut self, finalize: F) -> Result<usize>
+    where
+        F: FnOnce(&[LazerNamedFile]) -> Result<()>,
+    {
+        let mut written: Vec<PathBuf> = Vec::new();
+        let result = self.move_into_store(&mut written).and_then(|()| {
+            let files = self.files();
+            finalize(&files)
+        });
+
+        match result {
+            Ok(()) => {
+                self.committed = true;
+                self.cleanup();
+                Ok(written.len())
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Rolling back import: removing {} new blobs ({})",
+                    written.len(),
+                    e
+                );
+                for path in &written {
+                    let _ = fs::remove_file(path);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Discard everything staged by this transaction
+    pub fn rollback(self) {
+        // Drop removes the staging folder
+    }
+
+    fn move_into_store(&self, written: &mut Vec<PathBuf>) -> Result<()> {
+        for (file, staged_path) in &self.staged {
+            let dest = self.store.hash_to_path(&file.hash);
+            // Reused blobs were never staged
+            if dest.exists() || written.contains(&dest) || !staged_path.exists() {
+                continue;
+            }
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+            }
+            // Fall back to copy when staging and store are on different volumes
+            if fs::rename(staged_path, &dest).is_err() {
+                self.retry
+                    .run(|| fs::copy(staged_path, &dest))
+                    .map_err(|e| Error::io_at(e, &dest))?;
+            }
+            written.push(dest);
+        }
+        Ok(())
+    }
+
+    fn cleanup(&self) {
+        let _ = fs::remove_dir_all(&self.staging_path);
+        if let Some(parent) = self.staging_path.parent() {
+            // Only succeeds once no other transaction is using it
+            let _ = fs::remove_dir(parent);
+        }
+    }
+}
+
+impl Drop for FileStoreTransaction {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.cleanup();
+        }
+    }
+}
+
 /// Importer for adding beatmaps to osu!lazer
 pub struct LazerImporter {
     /// Path to lazer data directory
@@ -43,6 +207,12 @@ pub struct LazerImporter {
     trigger_import: bool,
     /// Accumulated .osz files for batch import
     pending_imports: Vec<PathBuf>,
+    /// How transient write errors are retried
+    retry: RetryPolicy,
+    /// Bytes of imported files whose blob was already in the file store
+    reused_bytes: AtomicU64,
+    /// Write rate cap shared with the rest of the sync
+    throttle: Option<Arc<Throttle>>,
 }
 
 impl LazerImporter {
@@ -61,9 +231,24 @@ impl LazerImporter {
             lazer_exe,
             trigger_import: true,
             pending_imports: Vec::new(),
+            retry: RetryPolicy::default(),
+            reused_bytes: AtomicU64::new(0),
+            throttle: None,
         }
     }
 
+    /// Set how transient write errors (e.g. files locked by antivirus) are retried
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Pace writing .osz files through a sync's write rate cap
+    pub(crate) fn with_throttle(mut self, throttle: Option<Arc<Throttle>>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
     /// Disable automatic import triggering (for batch mode)
     pub fn batch_mode(mut self) -> Self {
         self.trigger_import = false;
@@ -182,10 +367,17 @@ impl LazerImporter {
         self
     }
 
+    /// Use a custom import folder instead of `<data>/import`
+    pub fn with_import_path(mut self, path: PathBuf) -> Self {
+        self.import_path = path;
+        self
+    }
+
     /// Ensure the import directory exists
     pub fn ensure_import_dir(&self) -> Result<()> {
         if !self.import_path.exists() {
-            fs::create_dir_all(&self.import_path)?;
+            fs::create_dir_all(&self.import_path)
+                .map_err(|e| Error::io_at(e, &self.import_path))?;
         }
         Ok(())
     }
@@ -197,6 +389,100 @@ impl LazerImporter {
         &mut self,
         beatmap_set: &BeatmapSet,
         files: &[(String, Vec<u8>)],
+    ) -> Result<ImportResult> {
+        self.import_beatmap_set_with_progress(beatmap_set, files, |_, _| {})
+    }
+
+    /// Like [`import_beatmap_set`](Self::import_beatmap_set), reporting the
+    /// name and written fraction of large files as the .osz is created
+    pub fn import_beatmap_set_with_progress(
+        &mut self,
+        beatmap_set: &BeatmapSet,
+        files: &[(String, Vec<u8>)],
+        on_progress: impl FnMut(&str, f32),
+    ) -> Result<ImportResult> {
+        self.import_beatmap_set_with_hashes(beatmap_set, files, &HashMap::new(), on_progress)
+    }
+
+    /// Like [`import_beatmap_set_with_progress`](Self::import_beatmap_set_with_progress),
+    /// reusing SHA-256 hashes the caller already computed
+    ///
+    /// `known_hashes` maps filenames to their lazer content hash; other media
+    /// files are hashed here. Media whose blob is already in the file store is
+    /// left out of the .osz and counted in [`reused_bytes`](Self::reused_bytes)
+    /// instead of being written again. `.osu` and `.osb` files are always
+    /// written.
+    pub fn import_beatmap_set_with_hashes(
+        &mut self,
+        beatmap_set: &BeatmapSet,
+        files: &[(String, Vec<u8>)],
+        known_hashes: &HashMap<String, String>,
+        mut on_progress: impl FnMut(&str, f32),
+    ) -> Result<ImportResult> {
+        let store = LazerFileStore::new(&self.data_path);
+        let mut reused = 0u64;
+        let archived: Vec<&(String, Vec<u8>)> = files
+            .iter()
+            .filter(|(filename, content)| {
+                if !is_immutable_media(filename) {
+                    return true;
+                }
+                let stored = match known_hashes.get(filename) {
+                    Some(hash) => store.exists(hash),
+                    None => store.exists(&LazerFileStore::calculate_hash(content)),
+                };
+                if stored {
+                    reused += content.len() as u64;
+                }
+                !stored
+            })
+            .collect();
+
+        self.write_import(beatmap_set, archived, reused, on_progress)
+    }
+
+    /// Import a new version of a set lazer already has, archiving only what
+    /// changed
+    ///
+    /// Media files that `diff` reports as neither changed nor renamed keep
+    /// the blobs lazer's copy already references and are left out of the
+    /// .osz. `.osu` and `.osb` files are always archived so lazer can rebuild
+    /// the set, but unchanged ones are counted in
+    /// [`reused_bytes`](Self::reused_bytes) since lazer already stores them.
+    /// Files in `diff.removed` are simply not part of the new version.
+    pub fn import_set_diff(
+        &mut self,
+        beatmap_set: &BeatmapSet,
+        files: &[(String, Vec<u8>)],
+        diff: &SetDiff,
+        on_progress: impl FnMut(&str, f32),
+    ) -> Result<ImportResult> {
+        let changed: HashSet<&str> = diff.changed.iter().map(String::as_str).collect();
+        // Renamed files are already stored, but must be archived under their
+        // new name
+        let renamed: HashSet<&str> = diff.renamed.iter().map(|(_, to)| to.as_str()).collect();
+        let mut reused = 0u64;
+        let archived: Vec<&(String, Vec<u8>)> = files
+            .iter()
+            .filter(|(filename, content)| {
+                if changed.contains(filename.as_str()) {
+                    return true;
+                }
+                reused += content.len() as u64;
+                renamed.contains(filename.as_str()) || !is_immutable_media(filename)
+            })
+            .collect();
+
+        self.write_import(beatmap_set, archived, reused, on_progress)
+    }
+
+    /// Write `archived` to an .osz in the import folder and queue it
+    fn write_import(
+        &mut self,
+        beatmap_set: &BeatmapSet,
+        archived: Vec<&(String, Vec<u8>)>,
+        reused: u64,
+        mut on_progress: impl FnMut(&str, f32),
     ) -> Result<ImportResult> {
         self.ensure_import_dir()?;
 
@@ -211,7 +497,17 @@ impl LazerImporter {
         let osz_path = self.import_path.join(&filename);
 
         // Create the .osz file
-        create_osz_from_set(beatmap_set, files, &osz_path)?;
+        self.retry
+            .run(|| {
+                write_osz_entries(
+                    archived.iter().copied(),
+                    &osz_path,
+                    self.throttle.as_deref(),
+                    &mut on_progress,
+                )
+            })
+            .map_err(|e| e.with_path(&osz_path))?;
+        self.reused_bytes.fetch_add(reused, Ordering::Relaxed);
         tracing::info!("Created {} for lazer import", osz_path.display());
 
         // Track for batch import
@@ -408,7 +704,9 @@ impl LazerImporter {
             .ok_or_else(|| Error::Other("Invalid .osz path".to_string()))?;
 
         let dest_path = self.import_path.join(filename);
-        fs::copy(osz_path, &dest_path)?;
+        self.retry
+            .run(|| fs::copy(osz_path, &dest_path))
+            .map_err(|e| Error::io_at(e, &dest_path))?;
 
         tracing::info!("Copied {} to lazer import directory", dest_path.display());
 
@@ -426,6 +724,113 @@ impl LazerImporter {
         })
     }
 
+    /// Stage an existing .osz in lazer's import folder without launching lazer
+    ///
+    /// Lazer picks staged archives up on its next launch, so nothing is written
+    /// to the realm database or file store directly. The archive is validated
+    /// first and broken archives are refused. An existing file with the same
+    /// name is never overwritten; a numbered suffix is added instead.
+    pub fn stage_osz(&self, osz_path: &Path) -> Result<PathBuf> {
+        validate_osz(osz_path)?;
+        self.ensure_import_dir()?;
+
+        let stem = osz_path
+            .file_stem()
+            .map(|s| sanitize_filename(&s.to_string_lossy()))
+            .ok_or_else(|| Error::Other("Invalid .osz path".to_string()))?;
+
+        let mut dest_path = self.import_path.join(format!("{}.osz", stem));
+        let mut n = 1;
+        while dest_path.exists() {
+            dest_path = self.import_path.join(format!("{} ({}).osz", stem, n));
+            n += 1;
+        }
+
+        self.retry
+            .run(|| fs::copy(osz_path, &dest_path))
+            .map_err(|e| Error::io_at(e, &dest_path))?;
+        tracing::info!("Staged {} for lazer import", dest_path.display());
+
+        Ok(dest_path)
+    }
+
+    /// Import a set directly into the file store as a single transaction
+    ///
+    /// All files are staged first; they are only moved into the
+    /// content-addressed store once every file was staged, after which
+    /// `commit_record` is called to write the database record. If staging,
+    /// moving, or `commit_record` fails, the blobs added by this import are
+    /// removed so a killed or failed import leaves no phantom files behind.
+    ///
+    /// Returns the set's files with their content hashes.
+    pub fn import_set_transactional<F>(
+        &self,
+        files: &[(String, Vec<u8>)],
+        commit_record: F,
+    ) -> Result<Vec<LazerNamedFile>>
+    where
+        F: FnOnce(&[LazerNamedFile]) -> Result<()>,
+    {
+        let mut transaction = FileStoreTransaction::begin(&self.data_path)?.with_retry(self.retry);
+        for (filename, content) in files {
+            transaction.stage(filename, content)?;
+        }
+
+        let named = transaction.files();
+        let reused = transaction.reused_bytes();
+        let written = transaction.commit(commit_record)?;
+        self.reused_bytes.fetch_add(reused, Ordering::Relaxed);
+        tracing::debug!(
+            "Transactional import wrote {} new blobs for {} files",
+            written,
+            named.len()
+        );
+        Ok(named)
+    }
+
+    /// Hardlink files of a stable set folder into the lazer file store
+    ///
+    /// Each file's content hash is computed from `files`, which must hold
+    /// the contents of the same-named files in `source_dir`. Blobs already in
+    /// the store are left alone; lazer skips writing blobs that exist with a
+    /// matching hash when it later imports the set, so linked files take no
+    /// extra disk space. Files that cannot be linked are left for lazer to
+    /// copy. Returns the number of files linked.
+    ///
+    /// `.osu` and `.osb` files are never linked: they are edited in place by
+    /// stable's editor, which would change the blob under lazer's hash.
+    ///
+    /// Only call this when `source_dir` and the lazer data directory are on
+    /// the same filesystem, see [`same_device`](crate::utils::same_device),
+    /// and only after the set was imported, so no unreferenced blobs are left.
+    pub fn link_into_store(&self, source_dir: &Path, files: &[(String, Vec<u8>)]) -> usize {
+        let store = LazerFileStore::new(&self.data_path);
+        let mut linked = 0;
+        for (filename, content) in files.iter().filter(|(name, _)| is_immutable_media(name)) {
+            let source = source_dir.join(filename);
+            let dest = store.hash_to_path(&LazerFileStore::calculate_hash(content));
+            if dest.exists() || !source.is_file() {
+                continue;
+            }
+            if let Some(parent) = dest.parent() {
+                if fs::create_dir_all(parent).is_err() {
+                    continue;
+                }
+            }
+            match fs::hard_link(&source, &dest) {
+                Ok(()) => linked += 1,
+                Err(e) => tracing::debug!("Could not link {}: {}", source.display(), e),
+            }
+        }
+        linked
+    }
+
+    /// Total size of imported files that did not need to be written because
+    /// the lazer file store already had them
+    pub fn reused_bytes(&self) -> u64 {
+        self.reused_bytes.load(Ordering::Relaxed)
+    }
+
     /// Get the import directory path
     pub fn import_dir(&self) -> &Path {
         &self.import_path
@@ -466,3 +871,146 @@ impl LazerImporter {
         self.pending_imports.clear();
     }
 }
+
+/// Whether a set file is never modified in place, so it can share an inode
+/// with lazer's store
+fn is_immutable_media(filename: &str) -> bool {
+    !Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("osu") || ext.eq_ignore_ascii_case("osb"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("map.osu".to_string(), b"osu file format v14".to_vec()),
+            ("audio.mp3".to_string(), vec![1u8; 32]),
+        ]
+    }
+
+    #[test]
+    fn test_link_into_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("Songs").join("1 Artist - Title");
+        let lazer = temp_dir.path().join("lazer");
+        fs::create_dir_all(&source).unwrap();
+        for (name, content) in files() {
+            fs::write(source.join(name), content).unwrap();
+        }
+
+        let importer = LazerImporter::new(&lazer);
+        assert_eq!(importer.link_into_store(&source, &files()), 1);
+        // Already linked blobs are skipped
+        assert_eq!(importer.link_into_store(&source, &files()), 0);
+
+        let store = LazerFileStore::new(&lazer);
+        let hash = LazerFileStore::calculate_hash(&files()[1].1);
+        assert_eq!(fs::read(store.hash_to_path(&hash)).unwrap(), files()[1].1);
+        // Beatmap files stay editable in stable without touching lazer's copy
+        let osu_hash = LazerFileStore::calculate_hash(&files()[0].1);
+        assert!(!store.hash_to_path(&osu_hash).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let meta = fs::metadata(source.join("audio.mp3")).unwrap();
+            assert_eq!(meta.nlink(), 2);
+        }
+    }
+
+    #[test]
+    fn test_transactional_import_commits_blobs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let importer = LazerImporter::new(temp_dir.path());
+        let store = LazerFileStore::new(temp_dir.path());
+
+        let named = importer
+            .import_set_transactional(&files(), |files| {
+                assert_eq!(files.len(), 2);
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(named.iter().all(|f| store.exists(&f.hash)));
+        assert!(!temp_dir.path().join(STAGING_DIR).exists());
+    }
+
+    #[test]
+    fn test_transactional_import_reuses_existing_blobs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let importer = LazerImporter::new(temp_dir.path());
+        let store = LazerFileStore::new(temp_dir.path());
+
+        // The audio is shared with a set lazer already has
+        let shared = &files()[1].1;
+        let shared_path = store.hash_to_path(&LazerFileStore::calculate_hash(shared));
+        fs::create_dir_all(shared_path.parent().unwrap()).unwrap();
+        fs::write(&shared_path, shared).unwrap();
+        let modified = fs::metadata(&shared_path).unwrap().modified().unwrap();
+
+        let named = importer
+            .import_set_transactional(&files(), |_| Ok(()))
+            .unwrap();
+
+        assert!(named.iter().all(|f| store.exists(&f.hash)));
+        assert_eq!(importer.reused_bytes(), shared.len() as u64);
+        assert_eq!(
+            fs::metadata(&shared_path).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+
+    #[test]
+    fn test_import_leaves_stored_media_out_of_osz() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut importer = LazerImporter::new(temp_dir.path()).batch_mode();
+        let store = LazerFileStore::new(temp_dir.path());
+
+        // The audio is shared with a set lazer already has
+        let shared = &files()[1].1;
+        let shared_path = store.hash_to_path(&LazerFileStore::calculate_hash(shared));
+        fs::create_dir_all(shared_path.parent().unwrap()).unwrap();
+        fs::write(&shared_path, shared).unwrap();
+        let modified = fs::metadata(&shared_path).unwrap().modified().unwrap();
+
+        let result = importer
+            .import_beatmap_set(&BeatmapSet::new(), &files())
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&result.osz_path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.by_index(0).unwrap().name(), "map.osu");
+        assert_eq!(importer.reused_bytes(), shared.len() as u64);
+        assert_eq!(
+            fs::metadata(&shared_path).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+
+    #[test]
+    fn test_transactional_import_rolls_back_on_failure() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let importer = LazerImporter::new(temp_dir.path());
+        let store = LazerFileStore::new(temp_dir.path());
+
+        // A blob that was already in the store must survive the rollback
+        let existing = &files()[0].1;
+        let existing_hash = LazerFileStore::calculate_hash(existing);
+        let existing_path = store.hash_to_path(&existing_hash);
+        fs::create_dir_all(existing_path.parent().unwrap()).unwrap();
+        fs::write(&existing_path, existing).unwrap();
+
+        let result = importer.import_set_transactional(&files(), |_| {
+            Err(Error::Other("record write failed".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert!(store.exists(&existing_hash));
+        let new_hash = LazerFileStore::calculate_hash(&files()[1].1);
+        assert!(!store.exists(&new_hash));
+        assert!(!temp_dir.path().join(STAGING_DIR).exists());
+    }
+}
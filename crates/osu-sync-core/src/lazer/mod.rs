@@ -4,6 +4,7 @@
 //!
 //! - [`LazerDatabase`] - Reader for osu!lazer's Realm database (placeholder)
 //! - [`StableDatabase`] - Reader for osu!stable's osu!.db file (fully implemented)
+//! - [`StableScoresReader`] - Reader for osu!stable's scores.db file
 //! - [`LazerFileStore`] - Access to lazer's content-addressed file store
 //!
 //! ## Example
@@ -25,8 +26,10 @@ mod database;
 mod exporter;
 mod file_store;
 mod importer;
+mod scores;
 
 pub use database::*;
 pub use exporter::*;
 pub use file_store::*;
 pub use importer::*;
+pub use scores::*;
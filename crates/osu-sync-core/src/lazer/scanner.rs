@@ -292,6 +292,7 @@ impl LazerScanner {
                     length_ms: b.length_ms,
                     bpm: b.bpm,
                     star_rating: None, // Would need to compute
+                    star_ratings: Default::default(),
                     ranked_status: None, // Would need to check online
                 })
                 .collect();
@@ -321,6 +322,7 @@ impl LazerScanner {
                     length_ms: beatmap.length_ms,
                     bpm: beatmap.bpm,
                     star_rating: None,
+                    star_ratings: Default::default(),
                     ranked_status: None,
                 }],
                 files: Vec::new(),
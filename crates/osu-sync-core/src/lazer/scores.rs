@@ -0,0 +1,178 @@
+//! Reader for osu!stable's scores.db file
+//!
+//! scores.db is a map of beatmap MD5 hash to the list of local scores set on
+//! that difficulty. [`StableScoresReader`] flattens it into [`ScoreRecord`]s
+//! keyed by beatmap hash.
+
+use crate::beatmap::GameMode;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A single local score read from scores.db
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    /// MD5 hash of the beatmap the score was set on
+    pub beatmap_hash: String,
+    /// Game mode the score was set in
+    pub mode: GameMode,
+    /// Player name
+    pub player_name: String,
+    /// Total score
+    pub score: u32,
+    /// Max combo
+    pub max_combo: u16,
+    /// Whether the play was a full combo
+    pub perfect: bool,
+    /// Mods bitmask
+    pub mods: u32,
+    /// Number of 300s
+    pub count_300: u16,
+    /// Number of 100s
+    pub count_100: u16,
+    /// Number of 50s
+    pub count_50: u16,
+    /// Number of gekis
+    pub count_geki: u16,
+    /// Number of katus
+    pub count_katu: u16,
+    /// Number of misses
+    pub count_miss: u16,
+    /// Unix timestamp of when the score was set
+    pub timestamp: i64,
+    /// MD5 hash of the replay, if one was saved
+    pub replay_hash: Option<String>,
+}
+
+/// Reader for osu!stable's scores.db
+#[derive(Debug, Clone, Default)]
+pub struct StableScoresReader {
+    /// Scores grouped by beatmap MD5 hash
+    scores: HashMap<String, Vec<ScoreRecord>>,
+}
+
+impl StableScoresReader {
+    /// Open and parse scores.db in the given osu!stable directory
+    pub fn open(osu_path: &Path) -> Result<Self> {
+        let scores_path = osu_path.join("scores.db");
+        if !scores_path.exists() {
+            return Err(Error::OsuNotFound(scores_path));
+        }
+
+        let list = osu_db::score::ScoreList::from_file(&scores_path)
+            .map_err(|e| Error::Other(format!("Failed to parse scores.db: {}", e)))?;
+
+        let mut records = Vec::new();
+        for beatmap_scores in list.beatmaps {
+            let Some(hash) = beatmap_scores.hash else {
+                continue;
+            };
+            for score in beatmap_scores.scores {
+                records.push(ScoreRecord {
+                    beatmap_hash: hash.clone(),
+                    mode: match score.mode {
+                        osu_db::Mode::Standard => GameMode::Osu,
+                        osu_db::Mode::Taiko => GameMode::Taiko,
+                        osu_db::Mode::CatchTheBeat => GameMode::Catch,
+                        osu_db::Mode::Mania => GameMode::Mania,
+                    },
+                    player_name: score.player_name.unwrap_or_default(),
+                    score: score.score,
+                    max_combo: score.max_combo,
+                    perfect: score.perfect_combo,
+                    mods: score.mods.bits(),
+                    count_300: score.count_300,
+                    count_100: score.count_100,
+                    count_50: score.count_50,
+                    count_geki: score.count_geki,
+                    count_katu: score.count_katsu,
+                    count_miss: score.count_miss,
+                    timestamp: score.timestamp.timestamp(),
+                    replay_hash: score.replay_hash,
+                });
+            }
+        }
+
+        Ok(Self::from_records(records))
+    }
+
+    /// Build a reader from already-parsed score records
+    pub fn from_records(records: impl IntoIterator<Item = ScoreRecord>) -> Self {
+        let mut scores: HashMap<String, Vec<ScoreRecord>> = HashMap::new();
+        for record in records {
+            scores
+                .entry(record.beatmap_hash.clone())
+                .or_default()
+                .push(record);
+        }
+        Self { scores }
+    }
+
+    /// Get all scores set on the beatmap with the given MD5 hash
+    pub fn get_scores_for(&self, md5: &str) -> Vec<ScoreRecord> {
+        self.scores.get(md5).cloned().unwrap_or_default()
+    }
+
+    /// Total number of scores across all beatmaps
+    pub fn total_count(&self) -> usize {
+        self.scores.values().map(Vec::len).sum()
+    }
+
+    /// Number of beatmaps with at least one score
+    pub fn beatmap_count(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Beatmap hashes ordered by number of scores, most played first
+    pub fn most_played(&self, limit: usize) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = self
+            .scores
+            .iter()
+            .map(|(hash, scores)| (hash.clone(), scores.len()))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(limit);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(hash: &str, score: u32) -> ScoreRecord {
+        ScoreRecord {
+            beatmap_hash: hash.to_string(),
+            mode: GameMode::Osu,
+            player_name: "Player".to_string(),
+            score,
+            max_combo: 100,
+            perfect: false,
+            mods: 0,
+            count_300: 90,
+            count_100: 5,
+            count_50: 0,
+            count_geki: 0,
+            count_katu: 0,
+            count_miss: 1,
+            timestamp: 0,
+            replay_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_scores_grouped_by_hash() {
+        let reader = StableScoresReader::from_records(vec![
+            record("aaa", 1),
+            record("bbb", 2),
+            record("aaa", 3),
+        ]);
+
+        assert_eq!(reader.total_count(), 3);
+        assert_eq!(reader.beatmap_count(), 2);
+        assert_eq!(reader.get_scores_for("aaa").len(), 2);
+        assert!(reader.get_scores_for("missing").is_empty());
+        assert_eq!(reader.most_played(1), vec![("aaa".to_string(), 2)]);
+    }
+}
@@ -11,14 +11,29 @@
 //!
 //! ## Modules
 //!
+//! - [`audit`] - Audit log of completed operations
 //! - [`beatmap`] - Beatmap data structures (metadata, difficulty, files)
+//! - [`cancel`] - Cancellation of long-running operations
 //! - [`config`] - Configuration and path detection
 //! - [`dedup`] - Duplicate detection and resolution
 //! - [`error`] - Error types and Result alias
+//! - [`hash_cache`] - On-disk cache of file hashes
 //! - [`lazer`] - osu!lazer file store and database integration
 //! - [`parser`] - .osu file and .osz archive parsing
 //! - [`stable`] - osu!stable Songs folder integration
 //! - [`sync`] - Synchronization engine and conflict resolution
+//! - [`tools`] - Library queries for AI assistants, shaped as MCP tools
+//!
+//! ## Features
+//!
+//! - `tokio` - `async` variants of long-running operations
+//!   ([`SyncEngine::sync_async`], [`StableScanner::scan_async`],
+//!   [`BackupManager::create_backup_async`]) that run on the blocking pool
+//!   and report progress through a channel
+//! - `online` - an osu! API v2 client (`api` module) for refreshing ranked
+//!   status and star ratings of sets with an online ID
+//! - `tar-zstd` - tar.zst as an alternative backup format
+//!   ([`BackupFormat::TarZstd`](backup::BackupFormat::TarZstd))
 //!
 //! ## Example
 //!
@@ -38,13 +53,18 @@
 
 // Module declarations
 pub mod activity;
+#[cfg(feature = "online")]
+pub mod api;
+pub mod audit;
 pub mod backup;
 pub mod beatmap;
+pub mod cancel;
 pub mod collection;
 pub mod config;
 pub mod dedup;
 pub mod error;
 pub mod filter;
+pub mod hash_cache;
 pub mod lazer;
 pub mod media;
 pub mod parser;
@@ -52,6 +72,7 @@ pub mod replay;
 pub mod stable;
 pub mod stats;
 pub mod sync;
+pub mod tools;
 pub mod unified;
 pub mod utils;
 #[cfg(feature = "vision")]
@@ -62,6 +83,12 @@ pub mod vision;
 // Error types
 pub use error::{Error, Result};
 
+// Cancellation
+pub use cancel::CancellationToken;
+
+// File naming
+pub use utils::{SanitizeOptions, SanitizeTarget};
+
 // Beatmap types
 pub use beatmap::{
     BeatmapDifficulty, BeatmapFile, BeatmapInfo, BeatmapMetadata, BeatmapSet, GameMode,
@@ -69,57 +96,78 @@ pub use beatmap::{
 
 // Configuration
 pub use config::{
-    detect_lazer_path, detect_stable_path, validate_lazer_path, validate_stable_path, Config,
-    DuplicateStrategy as DuplicateHandling,
+    detect_all_stable_paths, detect_lazer_path, detect_stable_path, validate_lazer_path,
+    validate_stable_path, Config, ConfigLoadStatus, DuplicateStrategy as DuplicateHandling,
+    ProfilePaths, DEFAULT_PROFILE,
 };
 
 // Parsing
-pub use parser::{create_osz, create_osz_from_set, extract_osz, parse_osu_file};
+pub use parser::{
+    create_collection_archive, create_osz, create_osz_batch, create_osz_from_set,
+    extract_collection_archive, extract_osz, extract_osz_with_progress, parse_osu_file,
+    parse_osu_file_with_options, validate_osz, OszBatchResult, ParseOptions, ParseWarning,
+    LATEST_FORMAT_VERSION,
+};
 
 // osu!stable integration
 pub use stable::{
-    BeatmapIndex, ImportResult, ScanProgress, StableExporter, StableImporter, StableScanner,
+    BeatmapIndex, ImportResult, LibraryIssue, LibraryIssueKind, ScanProgress, ScanProgressCallback,
+    StableExporter, StableImporter, StableScanner,
 };
 
 // osu!lazer integration
 pub use lazer::{
-    LazerBeatmapInfo, LazerBeatmapSet, LazerDatabase, LazerExporter, LazerFileStore, LazerImporter,
-    LazerIndex, LazerNamedFile,
+    FileStoreTransaction, LazerBeatmapInfo, LazerBeatmapSet, LazerDatabase, LazerExportProgress,
+    LazerExportProgressCallback, LazerExporter, LazerFileStore, LazerImporter, LazerIndex,
+    LazerNamedFile, ScoreRecord, StableScoresReader,
 };
 
 // Duplicate detection
 pub use dedup::{
     BeatmapSetRef, DuplicateAction, DuplicateDetector, DuplicateInfo, DuplicateResolution,
-    DuplicateStrategy, MatchType,
+    DuplicateStrategy, MatchType, QualityScore,
 };
 
 // Sync engine
 pub use sync::{
-    format_bytes, AutoResolver, ConfigBasedResolver, ConflictResolver, DryRunAction, DryRunItem,
-    DryRunResult, InteractiveResolver, ProgressCallback, SkipList, SmartResolver, SyncDirection,
-    SyncEngine, SyncEngineBuilder, SyncError, SyncPhase, SyncProgress, SyncResult,
+    diff_set_files, format_bytes, restore_from_trash, select_difficulties, ActionSummary,
+    AutoResolver, ConfigBasedResolver, ConflictResolver, DecisionSummary, DryRunAction, DryRunItem,
+    DryRunResult, DryRunSummary, FavoritesSyncResult, GameGuard, InteractiveResolver,
+    ObserveResolver, ProgressCallback, SetDiff, SkipList, SmartResolver, SyncDirection, SyncEngine,
+    SyncEngineBuilder, SyncError, SyncEvent, SyncPhase, SyncProgress, SyncResult, ThrottleConfig,
+    Trash, TrashSource, DEFAULT_TRASH_MAX_AGE, SLOWEST_SETS_TRACKED,
 };
 
 // Statistics
 pub use stats::{
-    export_csv, export_html, export_json, ComparisonStats, DuplicateStats, ExportFormat,
-    HtmlExport, InstallationStats, RankedStatus, StarRatingBucket, StatsAnalyzer,
+    export_csv, export_html, export_json, export_library_json, import_library_json,
+    ComparisonStats, DuplicateStats, ExportFormat, HtmlExport, InstallationStats, IntegrityChecker,
+    IntegrityReport, RankedStatus, StarRatingBucket, StatsAnalyzer,
 };
 
+// File hash cache
+pub use hash_cache::{clear_hash_cache, HashAlgorithm, HashCache, HashedFile};
+
 // Filtering
-pub use filter::{FilterCriteria, FilterEngine};
+pub use filter::{BeatmapSearchHit, BeatmapSearchIndex, FilterCriteria, FilterEngine};
+
+// Assistant tools
+pub use tools::{OsuSyncTools, SearchBeatmapsArgs, ToolDefinition};
 
 // Collections
 pub use collection::{
-    Collection, CollectionSyncDirection, CollectionSyncEngine, CollectionSyncProgress,
-    CollectionSyncResult, CollectionSyncStrategy, StableCollectionReader,
+    collections_to_m3u, export_collection_audio, Collection, CollectionChange, CollectionConflict,
+    CollectionConflictAction, CollectionReport, CollectionSyncDirection, CollectionSyncEngine,
+    CollectionSyncProgress, CollectionSyncResult, CollectionSyncState, CollectionSyncStrategy,
+    PlaylistExport, StableCollectionReader, StableCollectionWriter,
 };
 
 // Backup
 pub use backup::{
-    BackupFileInfo, BackupInfo, BackupManager, BackupPhase, BackupProgress, BackupProgressCallback,
-    BackupTarget, BackupVerificationResult, IssueSeverity, RestoreMode, RestoreOptions,
-    RestorePreview, VerificationIssue, VerificationStatus,
+    BackupFileInfo, BackupFormat, BackupInfo, BackupManager, BackupPhase, BackupPreview,
+    BackupProgress, BackupProgressCallback, BackupTarget, BackupVerificationResult, IssueSeverity,
+    RestoreMode, RestoreOptions, RestorePreview, RestoreResult, VerificationIssue,
+    VerificationStatus,
 };
 
 // Media extraction
@@ -137,6 +185,13 @@ pub use replay::{
 // Activity log
 pub use activity::{ActivityEntry, ActivityLog, ActivityType, MAX_LOG_ENTRIES};
 
+// Audit log
+pub use audit::{record_operation, AuditLog, AuditOperation, AuditOutcome, AuditRecord};
+
+// osu! web API
+#[cfg(feature = "online")]
+pub use api::{refresh_ranked_status, resolve_online_id, OsuApi, RankedStatusRefresh};
+
 // Vision/capture
 #[cfg(feature = "vision")]
 pub use vision::{capture_game_window, list_osu_windows, CaptureTarget, CapturedFrame, WindowInfo};
@@ -1,25 +1,44 @@
 //! Media extraction implementation
 
-use std::collections::HashSet;
+#[cfg(feature = "image")]
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 
 use md5::{Digest, Md5};
 
-use crate::beatmap::BeatmapSet;
+use crate::beatmap::{BeatmapInfo, BeatmapSet};
+use crate::cancel::CancellationToken;
+#[cfg(feature = "image")]
+use crate::error::Error;
 use crate::error::Result;
 use crate::lazer::{LazerBeatmapSet, LazerFileStore};
-use crate::utils::sanitize_filename;
+use crate::utils::SanitizeOptions;
 
+use super::manifest::ExtractionManifest;
+#[cfg(feature = "image")]
+use super::types::ImageFormat;
 use super::types::{
-    AudioFormat, AudioInfo, AudioMetadata, ExtractionProgress, ExtractionProgressCallback,
-    ExtractionResult, ImageSizeCategory, MediaType, OutputOrganization,
+    AudioFormat, AudioInfo, AudioMetadata, BackgroundMetadata, ExtractionProgress,
+    ExtractionProgressCallback, ExtractionResult, ImageSizeCategory, MediaType, OutputOrganization,
 };
 
 /// Size of the sample to read for fast hashing (first 1KB)
 const FAST_HASH_SAMPLE_SIZE: usize = 1024;
 
+/// What happened to a single media file
+enum Extracted {
+    /// Written to the output, with the number of bytes
+    Written(u64),
+    /// Skipped as a duplicate of another file
+    Duplicate,
+    /// Skipped because the manifest shows it is already extracted
+    Unchanged,
+}
+
 /// Extractor for audio and background files from beatmaps
 pub struct MediaExtractor {
     output_dir: PathBuf,
@@ -36,6 +55,23 @@ pub struct MediaExtractor {
     extracted_hashes: HashSet<String>,
     /// Track hashes of files already in output directory
     existing_hashes: HashSet<String>,
+    /// Optional cancellation token, checked before each beatmap set
+    cancellation: Option<CancellationToken>,
+    /// Rules for output folder and file names
+    sanitize: SanitizeOptions,
+    /// Whether to keep a manifest of extracted files in the output directory
+    use_manifest: bool,
+    /// Manifest of the output directory, loaded when extraction starts
+    manifest: Option<ExtractionManifest>,
+    /// Source key of the file each output path was given to in this run,
+    /// keyed by the lowercased path
+    claimed_outputs: HashMap<String, String>,
+    /// Format to convert background images to
+    #[cfg(feature = "image")]
+    image_format: Option<ImageFormat>,
+    /// Largest width or height of extracted background images
+    #[cfg(feature = "image")]
+    max_image_dimension: Option<u32>,
 }
 
 impl MediaExtractor {
@@ -51,6 +87,15 @@ impl MediaExtractor {
             embed_id3_tags: false,
             extracted_hashes: HashSet::new(),
             existing_hashes: HashSet::new(),
+            cancellation: None,
+            sanitize: SanitizeOptions::default(),
+            use_manifest: true,
+            manifest: None,
+            claimed_outputs: HashMap::new(),
+            #[cfg(feature = "image")]
+            image_format: None,
+            #[cfg(feature = "image")]
+            max_image_dimension: None,
         }
     }
 
@@ -72,6 +117,9 @@ impl MediaExtractor {
     /// - Artist, Title, Source from beatmap
     /// - Beatmap set ID, difficulty name, mapper
     /// - Audio format, duration, bitrate info
+    ///
+    /// Each background gets a `.json` sidecar with its artist, title, set ID
+    /// and the difficulty it was taken from.
     pub fn with_metadata(mut self, create_metadata: bool) -> Self {
         self.create_metadata = create_metadata;
         self
@@ -99,6 +147,210 @@ impl MediaExtractor {
         self
     }
 
+    /// Set a cancellation token; a cancelled extraction returns [`crate::Error::Cancelled`]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Set the rules used to sanitize output folder and file names
+    pub fn with_sanitize_options(mut self, options: SanitizeOptions) -> Self {
+        self.sanitize = options;
+        self
+    }
+
+    /// Enable/disable the extraction manifest
+    ///
+    /// When enabled (the default), the extractor records every file it writes
+    /// in [`MANIFEST_FILE_NAME`](super::MANIFEST_FILE_NAME) in the output
+    /// directory. Later runs into the same directory skip files whose source
+    /// is unchanged and whose output is still there, counting them in
+    /// [`ExtractionResult::manifest_skipped`].
+    pub fn with_manifest(mut self, enabled: bool) -> Self {
+        self.use_manifest = enabled;
+        self
+    }
+
+    /// Convert extracted background images to `format`
+    ///
+    /// Images already in that format are copied unchanged unless they exceed
+    /// [`with_max_image_dimension`](Self::with_max_image_dimension).
+    #[cfg(feature = "image")]
+    pub fn with_image_format(mut self, format: ImageFormat) -> Self {
+        self.image_format = Some(format);
+        self
+    }
+
+    /// Scale down background images wider or taller than `max` pixels
+    ///
+    /// The aspect ratio is kept; smaller images keep their dimensions.
+    #[cfg(feature = "image")]
+    pub fn with_max_image_dimension(mut self, max: u32) -> Self {
+        self.max_image_dimension = Some(max);
+        self
+    }
+
+    /// Output file name of a background after format conversion
+    #[cfg(feature = "image")]
+    fn converted_name<'a>(&self, filename: &'a str, is_audio: bool) -> Cow<'a, str> {
+        match self.image_format {
+            Some(format) if !is_audio && !format.matches_filename(filename) => Cow::Owned(
+                Path::new(filename)
+                    .with_extension(format.extension())
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            _ => Cow::Borrowed(filename),
+        }
+    }
+
+    /// Re-encode a background to the configured format and size cap
+    #[cfg(feature = "image")]
+    fn convert_image(&self, content: Vec<u8>, is_audio: bool) -> Result<Vec<u8>> {
+        if is_audio || (self.image_format.is_none() && self.max_image_dimension.is_none()) {
+            return Ok(content);
+        }
+
+        let invalid =
+            |e: image::ImageError| Error::Other(format!("Failed to convert image: {}", e));
+        let source_format = image::guess_format(&content).map_err(invalid)?;
+        let target_format = match self.image_format {
+            Some(ImageFormat::Png) => image::ImageFormat::Png,
+            Some(ImageFormat::Jpeg) => image::ImageFormat::Jpeg,
+            None => source_format,
+        };
+        if source_format == target_format && self.max_image_dimension.is_none() {
+            return Ok(content);
+        }
+
+        let mut img =
+            image::load_from_memory_with_format(&content, source_format).map_err(invalid)?;
+        match self.max_image_dimension {
+            Some(max) if img.width() > max || img.height() > max => {
+                img = img.resize(max, max, image::imageops::FilterType::Lanczos3);
+            }
+            _ if source_format == target_format => return Ok(content),
+            _ => {}
+        }
+        if target_format == image::ImageFormat::Jpeg {
+            // JPEG has no alpha channel
+            img = image::DynamicImage::ImageRgb8(img.to_rgb8());
+        }
+
+        let mut output = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut output, target_format).map_err(invalid)?;
+        Ok(output.into_inner())
+    }
+
+    /// Manifest key of a background, with the conversion settings added so
+    /// that changing them extracts it again
+    #[cfg(feature = "image")]
+    fn converted_source_key(&self, source_key: String, is_audio: bool) -> String {
+        if is_audio || (self.image_format.is_none() && self.max_image_dimension.is_none()) {
+            return source_key;
+        }
+        let format = self.image_format.map_or("original", |f| f.extension());
+        let max = self
+            .max_image_dimension
+            .map_or_else(|| "any".to_string(), |max| max.to_string());
+        format!("{}|{}:{}", source_key, format, max)
+    }
+
+    /// Load the output directory's manifest, unless already loaded
+    fn load_manifest(&mut self) {
+        if self.use_manifest && self.manifest.is_none() {
+            self.manifest = Some(ExtractionManifest::open(&self.output_dir));
+        }
+    }
+
+    /// Write the manifest back to the output directory
+    fn save_manifest(&self) {
+        if let Some(manifest) = &self.manifest {
+            if let Err(e) = manifest.save() {
+                tracing::warn!("Failed to save extraction manifest: {}", e);
+            }
+        }
+    }
+
+    /// Whether the manifest shows `output_path` was already extracted from `source_key`
+    fn is_unchanged(&self, output_path: &Path, source_key: &str) -> bool {
+        self.manifest
+            .as_ref()
+            .is_some_and(|m| m.is_unchanged(output_path, source_key))
+    }
+
+    /// Record a written file in the manifest
+    fn record_extracted(
+        &mut self,
+        output_path: &Path,
+        set_name: &str,
+        source_key: String,
+        size: u64,
+    ) {
+        if let Some(manifest) = &mut self.manifest {
+            manifest.record(output_path, set_name, source_key, size);
+        }
+    }
+
+    /// Manifest key of a Songs file, from its size and modification time
+    ///
+    /// Checking a file against the manifest then needs no read of its content.
+    fn stable_source_key(path: &Path) -> Result<String> {
+        let metadata = fs::metadata(path)?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        Ok(format!("{}:{}", metadata.len(), mtime))
+    }
+
+    /// Manifest key of a lazer file; store files are named by content hash
+    fn lazer_source_key(lazer_hash: &str) -> String {
+        format!("lazer:{}", lazer_hash)
+    }
+
+    /// Give `path` to the file with `source_key` for the rest of this run
+    ///
+    /// Different sets can map to the same output name, such as two mapsets of
+    /// one song in flat mode. Later ones get a numbered name instead of
+    /// overwriting the first, so each keeps its own manifest entry.
+    fn claim_output_path(&mut self, path: PathBuf, source_key: &str) -> PathBuf {
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ext = path.extension().map(|e| e.to_string_lossy().into_owned());
+        let mut candidate = path.clone();
+        let mut n = 2;
+        loop {
+            let key = candidate.to_string_lossy().to_lowercase();
+            match self.claimed_outputs.get(&key) {
+                Some(owner) if owner != source_key => {
+                    let name = match &ext {
+                        Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                        None => format!("{} ({})", stem, n),
+                    };
+                    candidate = path.with_file_name(name);
+                    n += 1;
+                }
+                Some(_) => return candidate,
+                None => {
+                    self.claimed_outputs.insert(key, source_key.to_string());
+                    return candidate;
+                }
+            }
+        }
+    }
+
+    /// Return [`crate::Error::Cancelled`] if cancellation has been requested
+    fn check_cancelled(&self) -> Result<()> {
+        match &self.cancellation {
+            Some(token) => token.check(),
+            None => Ok(()),
+        }
+    }
+
     /// Compute a fast hash for duplicate detection (first 1KB + file size)
     /// This is much faster than full MD5 for large files while still being effective
     fn compute_fast_hash(content: &[u8]) -> String {
@@ -170,18 +422,34 @@ impl MediaExtractor {
         songs_path: &Path,
         sets: &[BeatmapSet],
         progress_callback: Option<ExtractionProgressCallback>,
+    ) -> Result<ExtractionResult> {
+        self.load_manifest();
+        self.claimed_outputs.clear();
+        let result = self.extract_stable_sets(songs_path, sets, progress_callback);
+        // Saved even when cancelled, so finished files aren't copied again
+        self.save_manifest();
+        result
+    }
+
+    fn extract_stable_sets(
+        &mut self,
+        songs_path: &Path,
+        sets: &[BeatmapSet],
+        progress_callback: Option<ExtractionProgressCallback>,
     ) -> Result<ExtractionResult> {
         let mut result = ExtractionResult::new();
         let total_sets = sets.len();
 
         for (idx, set) in sets.iter().enumerate() {
+            self.check_cancelled()?;
+
             let metadata = set.metadata();
             let set_name = metadata
                 .map(|m| format!("{} - {}", m.artist, m.title))
                 .unwrap_or_else(|| "Unknown".to_string());
 
             // Get the beatmap folder path
-            let folder_path = if let Some(ref folder_name) = set.folder_name {
+            let folder_path = if let Some(folder_name) = set.disk_folder_name() {
                 songs_path.join(folder_name)
             } else {
                 continue;
@@ -214,34 +482,26 @@ impl MediaExtractor {
                         });
                     }
 
-                    // Build audio metadata from beatmap info
-                    let audio_metadata = AudioMetadata {
-                        artist: beatmap.metadata.artist.clone(),
-                        title: beatmap.metadata.title.clone(),
-                        source: beatmap.metadata.source.clone().unwrap_or_default(),
-                        beatmap_set_id: beatmap.metadata.beatmap_set_id,
-                        difficulty: Some(beatmap.version.clone()),
-                        mapper: Some(beatmap.metadata.creator.clone()),
-                        audio_info: None, // Will be filled during extraction
-                    };
-
                     match self.extract_file_with_metadata(
                         &audio_path,
                         &set_name,
                         &beatmap.audio_file,
                         true,
                         Some(beatmap.length_ms),
-                        audio_metadata,
+                        Self::audio_metadata(beatmap),
                         &mut result,
                     ) {
-                        Ok(Some(bytes)) => {
+                        Ok(Extracted::Written(bytes)) => {
                             result.audio_extracted += 1;
                             result.unique_files += 1;
                             result.bytes_written += bytes;
                         }
-                        Ok(None) => {
+                        Ok(Extracted::Duplicate) => {
                             result.duplicates_skipped += 1;
                         }
+                        Ok(Extracted::Unchanged) => {
+                            result.manifest_skipped += 1;
+                        }
                         Err(e) => {
                             result
                                 .errors
@@ -274,15 +534,34 @@ impl MediaExtractor {
                             });
                         }
 
-                        match self.extract_file(&bg_path, &set_name, bg_file, false) {
-                            Ok(Some(bytes)) => {
+                        let bg_metadata = BackgroundMetadata {
+                            artist: beatmap.metadata.artist.clone(),
+                            title: beatmap.metadata.title.clone(),
+                            beatmap_set_id: beatmap.metadata.beatmap_set_id,
+                            difficulty: Some(beatmap.version.clone()),
+                            mapper: Some(beatmap.metadata.creator.clone()),
+                            source_file: bg_file.clone(),
+                        };
+
+                        match self.extract_file(
+                            &bg_path,
+                            &set_name,
+                            bg_file,
+                            false,
+                            &bg_metadata,
+                            &mut result,
+                        ) {
+                            Ok(Extracted::Written(bytes)) => {
                                 result.backgrounds_extracted += 1;
                                 result.unique_files += 1;
                                 result.bytes_written += bytes;
                             }
-                            Ok(None) => {
+                            Ok(Extracted::Duplicate) => {
                                 result.duplicates_skipped += 1;
                             }
+                            Ok(Extracted::Unchanged) => {
+                                result.manifest_skipped += 1;
+                            }
                             Err(e) => {
                                 result
                                     .errors
@@ -300,17 +579,99 @@ impl MediaExtractor {
         Ok(result)
     }
 
+    /// Extract the audio file of each beatmap in `files`, in order
+    ///
+    /// Files are named, tagged and checked against the manifest as in
+    /// [`extract_from_stable`](Self::extract_from_stable), but the first error
+    /// stops the run. Returns the output path of each file.
+    pub(crate) fn extract_audio_files(
+        &mut self,
+        files: &[(PathBuf, &BeatmapInfo)],
+        result: &mut ExtractionResult,
+    ) -> Result<Vec<PathBuf>> {
+        self.load_manifest();
+        self.claimed_outputs.clear();
+        let outputs = self.extract_audio_list(files, result);
+        self.save_manifest();
+        outputs
+    }
+
+    fn extract_audio_list(
+        &mut self,
+        files: &[(PathBuf, &BeatmapInfo)],
+        result: &mut ExtractionResult,
+    ) -> Result<Vec<PathBuf>> {
+        let mut outputs = Vec::with_capacity(files.len());
+        for (audio_path, beatmap) in files {
+            self.check_cancelled()?;
+
+            let set_name = format!("{} - {}", beatmap.metadata.artist, beatmap.metadata.title);
+            let source_key = Self::stable_source_key(audio_path)?;
+            outputs.push(self.claim_output_path(
+                self.get_output_path(&set_name, &beatmap.audio_file, true),
+                &source_key,
+            ));
+
+            match self.extract_file_with_metadata(
+                audio_path,
+                &set_name,
+                &beatmap.audio_file,
+                true,
+                Some(beatmap.length_ms),
+                Self::audio_metadata(beatmap),
+                result,
+            )? {
+                Extracted::Written(bytes) => {
+                    result.audio_extracted += 1;
+                    result.unique_files += 1;
+                    result.bytes_written += bytes;
+                }
+                Extracted::Duplicate => result.duplicates_skipped += 1,
+                Extracted::Unchanged => result.manifest_skipped += 1,
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Audio tags and sidecar fields of a stable beatmap
+    fn audio_metadata(beatmap: &BeatmapInfo) -> AudioMetadata {
+        AudioMetadata {
+            artist: beatmap.metadata.artist.clone(),
+            title: beatmap.metadata.title.clone(),
+            source: beatmap.metadata.source.clone().unwrap_or_default(),
+            beatmap_set_id: beatmap.metadata.beatmap_set_id,
+            difficulty: Some(beatmap.version.clone()),
+            mapper: Some(beatmap.metadata.creator.clone()),
+            audio_info: None, // Will be filled during extraction
+        }
+    }
+
     /// Extract media from osu!lazer beatmap sets
     pub fn extract_from_lazer(
         &mut self,
         file_store: &LazerFileStore,
         sets: &[LazerBeatmapSet],
         progress_callback: Option<ExtractionProgressCallback>,
+    ) -> Result<ExtractionResult> {
+        self.load_manifest();
+        self.claimed_outputs.clear();
+        let result = self.extract_lazer_sets(file_store, sets, progress_callback);
+        self.save_manifest();
+        result
+    }
+
+    fn extract_lazer_sets(
+        &mut self,
+        file_store: &LazerFileStore,
+        sets: &[LazerBeatmapSet],
+        progress_callback: Option<ExtractionProgressCallback>,
     ) -> Result<ExtractionResult> {
         let mut result = ExtractionResult::new();
         let total_sets = sets.len();
 
         for (idx, set) in sets.iter().enumerate() {
+            self.check_cancelled()?;
+
             let first_beatmap = set.beatmaps.first();
             let set_name = first_beatmap
                 .map(|b| format!("{} - {}", b.metadata.artist, b.metadata.title))
@@ -361,15 +722,19 @@ impl MediaExtractor {
                         audio_metadata,
                         &mut result,
                     ) {
-                        Ok(Some(bytes)) => {
+                        Ok(Extracted::Written(bytes)) => {
                             result.audio_extracted += 1;
                             result.unique_files += 1;
                             result.bytes_written += bytes;
                             audio_extracted_flag = true;
                         }
-                        Ok(None) => {
+                        Ok(Extracted::Duplicate) => {
                             result.duplicates_skipped += 1;
                         }
+                        Ok(Extracted::Unchanged) => {
+                            result.manifest_skipped += 1;
+                            audio_extracted_flag = true;
+                        }
                         Err(e) => {
                             result.errors.push((file.filename.clone(), e.to_string()));
                         }
@@ -388,22 +753,41 @@ impl MediaExtractor {
                         });
                     }
 
+                    let bg_metadata = BackgroundMetadata {
+                        artist: first_beatmap
+                            .map(|b| b.metadata.artist.clone())
+                            .unwrap_or_default(),
+                        title: first_beatmap
+                            .map(|b| b.metadata.title.clone())
+                            .unwrap_or_default(),
+                        beatmap_set_id: first_beatmap.and_then(|b| b.metadata.beatmap_set_id),
+                        difficulty: first_beatmap.map(|b| b.version.clone()),
+                        mapper: first_beatmap.map(|b| b.metadata.creator.clone()),
+                        source_file: file.filename.clone(),
+                    };
+
                     match self.extract_lazer_file(
                         file_store,
                         &file.hash,
                         &set_name,
                         &file.filename,
                         false,
+                        &bg_metadata,
+                        &mut result,
                     ) {
-                        Ok(Some(bytes)) => {
+                        Ok(Extracted::Written(bytes)) => {
                             result.backgrounds_extracted += 1;
                             result.unique_files += 1;
                             result.bytes_written += bytes;
                             bg_extracted = true;
                         }
-                        Ok(None) => {
+                        Ok(Extracted::Duplicate) => {
                             result.duplicates_skipped += 1;
                         }
+                        Ok(Extracted::Unchanged) => {
+                            result.manifest_skipped += 1;
+                            bg_extracted = true;
+                        }
                         Err(e) => {
                             result.errors.push((file.filename.clone(), e.to_string()));
                         }
@@ -415,14 +799,34 @@ impl MediaExtractor {
         Ok(result)
     }
 
-    /// Extract a file from stable (filesystem) without metadata
+    /// Extract a file from stable (filesystem) without audio metadata
+    #[allow(clippy::too_many_arguments)]
     fn extract_file(
         &mut self,
         source_path: &Path,
         set_name: &str,
         filename: &str,
         is_audio: bool,
-    ) -> Result<Option<u64>> {
+        metadata: &BackgroundMetadata,
+        result: &mut ExtractionResult,
+    ) -> Result<Extracted> {
+        #[cfg(feature = "image")]
+        let filename: &str = &self.converted_name(filename, is_audio);
+
+        // Skip files a previous run already extracted from the same source
+        let source_key = Self::stable_source_key(source_path)?;
+        #[cfg(feature = "image")]
+        let source_key = self.converted_source_key(source_key, is_audio);
+        let output_path = self.claim_output_path(
+            self.get_output_path(set_name, filename, is_audio),
+            &source_key,
+        );
+        if self.is_unchanged(&output_path, &source_key) {
+            let hash = Self::compute_fast_hash_from_file(source_path)?;
+            self.extracted_hashes.insert(hash);
+            return Ok(Extracted::Unchanged);
+        }
+
         // Read file and compute hash for deduplication
         let mut file = File::open(source_path)?;
         let mut content = Vec::new();
@@ -435,23 +839,29 @@ impl MediaExtractor {
         if self.skip_duplicates
             && (self.extracted_hashes.contains(&hash) || self.existing_hashes.contains(&hash))
         {
-            return Ok(None);
+            return Ok(Extracted::Duplicate);
         }
 
-        // Determine output path
-        let output_path = self.get_output_path(set_name, filename, is_audio);
-
         // Ensure parent directory exists
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        #[cfg(feature = "image")]
+        let content = self.convert_image(content, is_audio)?;
+
         // Write file
         let mut output_file = File::create(&output_path)?;
         output_file.write_all(&content)?;
 
+        if self.create_metadata && !is_audio {
+            Self::write_background_sidecar(&output_path, metadata, result);
+        }
+
+        let size = content.len() as u64;
+        self.record_extracted(&output_path, set_name, source_key, size);
         self.extracted_hashes.insert(hash);
-        Ok(Some(content.len() as u64))
+        Ok(Extracted::Written(size))
     }
 
     /// Extract a file from stable with metadata support
@@ -465,7 +875,19 @@ impl MediaExtractor {
         duration_ms: Option<u64>,
         mut metadata: AudioMetadata,
         result: &mut ExtractionResult,
-    ) -> Result<Option<u64>> {
+    ) -> Result<Extracted> {
+        // Skip files a previous run already extracted from the same source
+        let source_key = Self::stable_source_key(source_path)?;
+        let output_path = self.claim_output_path(
+            self.get_output_path(set_name, filename, is_audio),
+            &source_key,
+        );
+        if self.is_unchanged(&output_path, &source_key) {
+            let hash = Self::compute_fast_hash_from_file(source_path)?;
+            self.extracted_hashes.insert(hash);
+            return Ok(Extracted::Unchanged);
+        }
+
         // Read file and compute hash for deduplication
         let mut file = File::open(source_path)?;
         let mut content = Vec::new();
@@ -478,7 +900,7 @@ impl MediaExtractor {
         if self.skip_duplicates
             && (self.extracted_hashes.contains(&hash) || self.existing_hashes.contains(&hash))
         {
-            return Ok(None);
+            return Ok(Extracted::Duplicate);
         }
 
         // Build audio info
@@ -486,9 +908,6 @@ impl MediaExtractor {
         result.record_audio_info(&audio_info);
         metadata.audio_info = Some(audio_info.clone());
 
-        // Determine output path
-        let output_path = self.get_output_path(set_name, filename, is_audio);
-
         // Ensure parent directory exists
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)?;
@@ -519,11 +938,14 @@ impl MediaExtractor {
             }
         }
 
+        let size = final_content.len() as u64;
+        self.record_extracted(&output_path, set_name, source_key, size);
         self.extracted_hashes.insert(hash);
-        Ok(Some(final_content.len() as u64))
+        Ok(Extracted::Written(size))
     }
 
-    /// Extract a file from lazer (file store) without metadata
+    /// Extract a file from lazer (file store) without audio metadata
+    #[allow(clippy::too_many_arguments)]
     fn extract_lazer_file(
         &mut self,
         file_store: &LazerFileStore,
@@ -531,7 +953,26 @@ impl MediaExtractor {
         set_name: &str,
         filename: &str,
         is_audio: bool,
-    ) -> Result<Option<u64>> {
+        metadata: &BackgroundMetadata,
+        result: &mut ExtractionResult,
+    ) -> Result<Extracted> {
+        #[cfg(feature = "image")]
+        let filename: &str = &self.converted_name(filename, is_audio);
+
+        // Skip files a previous run already extracted from the same source
+        let source_key = Self::lazer_source_key(lazer_hash);
+        #[cfg(feature = "image")]
+        let source_key = self.converted_source_key(source_key, is_audio);
+        let output_path = self.claim_output_path(
+            self.get_output_path(set_name, filename, is_audio),
+            &source_key,
+        );
+        if self.is_unchanged(&output_path, &source_key) {
+            let hash = Self::compute_fast_hash_from_file(&file_store.hash_to_path(lazer_hash))?;
+            self.extracted_hashes.insert(hash);
+            return Ok(Extracted::Unchanged);
+        }
+
         // Read from file store first to compute fast hash
         let content = file_store.read(lazer_hash)?;
 
@@ -542,23 +983,29 @@ impl MediaExtractor {
         if self.skip_duplicates
             && (self.extracted_hashes.contains(&hash) || self.existing_hashes.contains(&hash))
         {
-            return Ok(None);
+            return Ok(Extracted::Duplicate);
         }
 
-        // Determine output path
-        let output_path = self.get_output_path(set_name, filename, is_audio);
-
         // Ensure parent directory exists
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        #[cfg(feature = "image")]
+        let content = self.convert_image(content, is_audio)?;
+
         // Write file
         let mut output_file = File::create(&output_path)?;
         output_file.write_all(&content)?;
 
+        if self.create_metadata && !is_audio {
+            Self::write_background_sidecar(&output_path, metadata, result);
+        }
+
+        let size = content.len() as u64;
+        self.record_extracted(&output_path, set_name, source_key, size);
         self.extracted_hashes.insert(hash);
-        Ok(Some(content.len() as u64))
+        Ok(Extracted::Written(size))
     }
 
     /// Extract a file from lazer with metadata support
@@ -573,7 +1020,19 @@ impl MediaExtractor {
         duration_ms: Option<u64>,
         mut metadata: AudioMetadata,
         result: &mut ExtractionResult,
-    ) -> Result<Option<u64>> {
+    ) -> Result<Extracted> {
+        // Skip files a previous run already extracted from the same source
+        let source_key = Self::lazer_source_key(lazer_hash);
+        let output_path = self.claim_output_path(
+            self.get_output_path(set_name, filename, is_audio),
+            &source_key,
+        );
+        if self.is_unchanged(&output_path, &source_key) {
+            let hash = Self::compute_fast_hash_from_file(&file_store.hash_to_path(lazer_hash))?;
+            self.extracted_hashes.insert(hash);
+            return Ok(Extracted::Unchanged);
+        }
+
         // Read from file store first to compute fast hash
         let content = file_store.read(lazer_hash)?;
 
@@ -584,7 +1043,7 @@ impl MediaExtractor {
         if self.skip_duplicates
             && (self.extracted_hashes.contains(&hash) || self.existing_hashes.contains(&hash))
         {
-            return Ok(None);
+            return Ok(Extracted::Duplicate);
         }
 
         // Build audio info
@@ -592,9 +1051,6 @@ impl MediaExtractor {
         result.record_audio_info(&audio_info);
         metadata.audio_info = Some(audio_info.clone());
 
-        // Determine output path
-        let output_path = self.get_output_path(set_name, filename, is_audio);
-
         // Ensure parent directory exists
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)?;
@@ -625,42 +1081,54 @@ impl MediaExtractor {
             }
         }
 
+        let size = final_content.len() as u64;
+        self.record_extracted(&output_path, set_name, source_key, size);
         self.extracted_hashes.insert(hash);
-        Ok(Some(final_content.len() as u64))
+        Ok(Extracted::Written(size))
+    }
+
+    /// Write a JSON sidecar with the beatmap a background came from
+    ///
+    /// The sidecar keeps the full file name, e.g. `bg.jpg.json`, so gallery
+    /// apps can pair it with the image.
+    fn write_background_sidecar(
+        output_path: &Path,
+        metadata: &BackgroundMetadata,
+        result: &mut ExtractionResult,
+    ) {
+        let mut sidecar_path = output_path.as_os_str().to_owned();
+        sidecar_path.push(".json");
+        if fs::write(&sidecar_path, metadata.to_sidecar_json()).is_ok() {
+            result.metadata_files_created += 1;
+        }
     }
 
     /// Get the output path based on organization mode
     fn get_output_path(&self, set_name: &str, filename: &str, is_audio: bool) -> PathBuf {
-        let sanitized_name = sanitize_filename(set_name);
         let ext = Path::new(filename)
             .extension()
             .and_then(|e| e.to_str())
             .unwrap_or("");
+        // Use set name as filename for audio
+        let media_name = || {
+            let set_name = set_name.trim();
+            if is_audio {
+                self.sanitize.sanitize(&format!("{}.{}", set_name, ext))
+            } else {
+                self.sanitize.sanitize(&format!("{}_bg.{}", set_name, ext))
+            }
+        };
 
         match self.organization {
-            OutputOrganization::Flat => {
-                // Use set name as filename for audio
-                if is_audio {
-                    self.output_dir.join(format!("{}.{}", sanitized_name, ext))
-                } else {
-                    self.output_dir
-                        .join(format!("{}_bg.{}", sanitized_name, ext))
-                }
-            }
+            OutputOrganization::Flat => self.output_dir.join(media_name()),
             OutputOrganization::ByArtist => {
                 // Extract artist from set name (format: "Artist - Title")
                 let artist = set_name.split(" - ").next().unwrap_or("Unknown");
-                let sanitized_artist = sanitize_filename(artist);
-                let subdir = self.output_dir.join(&sanitized_artist);
-
-                if is_audio {
-                    subdir.join(format!("{}.{}", sanitized_name, ext))
-                } else {
-                    subdir.join(format!("{}_bg.{}", sanitized_name, ext))
-                }
+                let subdir = self.output_dir.join(self.sanitize.sanitize(artist));
+                subdir.join(media_name())
             }
             OutputOrganization::ByBeatmap => {
-                let subdir = self.output_dir.join(&sanitized_name);
+                let subdir = self.output_dir.join(self.sanitize.sanitize(set_name));
                 subdir.join(filename)
             }
         }
@@ -786,6 +1254,247 @@ mod tests {
         assert_eq!(result.duplicates_skipped, 0);
     }
 
+    #[test]
+    fn test_extract_from_stable_skips_via_manifest() {
+        use crate::beatmap::{BeatmapInfo, BeatmapMetadata};
+
+        let songs = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let folder = songs.path().join("1 Artist - Song");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("audio.mp3"), b"first version").unwrap();
+
+        let mut set = BeatmapSet::new();
+        set.folder_name = Some("1 Artist - Song".to_string());
+        set.beatmaps.push(BeatmapInfo {
+            metadata: BeatmapMetadata {
+                artist: "Artist".to_string(),
+                title: "Song".to_string(),
+                ..Default::default()
+            },
+            audio_file: "audio.mp3".to_string(),
+            ..Default::default()
+        });
+        let sets = vec![set];
+        let extract = || {
+            MediaExtractor::new(output.path())
+                .with_media_type(MediaType::Audio)
+                .extract_from_stable(songs.path(), &sets, None)
+                .unwrap()
+        };
+
+        let first = extract();
+        assert_eq!(first.audio_extracted, 1);
+        assert!(output
+            .path()
+            .join(crate::media::MANIFEST_FILE_NAME)
+            .is_file());
+
+        let second = extract();
+        assert_eq!(second.audio_extracted, 0);
+        assert_eq!(second.manifest_skipped, 1);
+        assert_eq!(second.duplicates_skipped, 0);
+
+        // Sources are compared by size and mtime, without reading them
+        let audio = folder.join("audio.mp3");
+        let mtime = fs::metadata(&audio).unwrap().modified().unwrap();
+        fs::write(&audio, b"FIRST VERSION").unwrap();
+        File::options()
+            .write(true)
+            .open(&audio)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        assert_eq!(extract().manifest_skipped, 1);
+
+        // A changed source is extracted again
+        fs::write(folder.join("audio.mp3"), b"second version").unwrap();
+        let third = extract();
+        assert_eq!(third.audio_extracted, 1);
+        assert_eq!(
+            fs::read(output.path().join("Artist - Song.mp3")).unwrap(),
+            b"second version"
+        );
+    }
+
+    #[test]
+    fn test_cancelled_extraction_is_cancelled() {
+        let songs = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let mut set = BeatmapSet::new();
+        set.folder_name = Some("1 Artist - Song".to_string());
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = MediaExtractor::new(output.path())
+            .with_cancellation(token)
+            .extract_from_stable(songs.path(), &[set], None);
+        assert!(matches!(result, Err(crate::Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_flat_output_names_do_not_collide() {
+        use crate::beatmap::{BeatmapInfo, BeatmapMetadata};
+
+        let songs = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        // Two mapsets of the same song, with different audio files
+        let sets: Vec<_> = ["1 Artist - Song", "2 Artist - Song"]
+            .into_iter()
+            .map(|folder| {
+                fs::create_dir_all(songs.path().join(folder)).unwrap();
+                fs::write(songs.path().join(folder).join("audio.mp3"), folder).unwrap();
+                let mut set = BeatmapSet::new();
+                set.folder_name = Some(folder.to_string());
+                set.beatmaps.push(BeatmapInfo {
+                    metadata: BeatmapMetadata {
+                        artist: "Artist".to_string(),
+                        title: "Song".to_string(),
+                        ..Default::default()
+                    },
+                    audio_file: "audio.mp3".to_string(),
+                    ..Default::default()
+                });
+                set
+            })
+            .collect();
+        let extract = || {
+            MediaExtractor::new(output.path())
+                .with_media_type(MediaType::Audio)
+                .extract_from_stable(songs.path(), &sets, None)
+                .unwrap()
+        };
+
+        assert_eq!(extract().audio_extracted, 2);
+        assert_eq!(
+            fs::read(output.path().join("Artist - Song.mp3")).unwrap(),
+            b"1 Artist - Song"
+        );
+        assert_eq!(
+            fs::read(output.path().join("Artist - Song (2).mp3")).unwrap(),
+            b"2 Artist - Song"
+        );
+
+        let second = extract();
+        assert_eq!(second.audio_extracted, 0);
+        assert_eq!(second.manifest_skipped, 2);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_extract_converts_backgrounds() {
+        let source = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let bg_path = source.path().join("bg.png");
+        image::RgbaImage::from_pixel(8, 4, image::Rgba([200, 40, 40, 255]))
+            .save(&bg_path)
+            .unwrap();
+
+        let mut extractor = MediaExtractor::new(output.path())
+            .with_image_format(ImageFormat::Jpeg)
+            .with_max_image_dimension(4);
+        let extracted = extractor
+            .extract_file(
+                &bg_path,
+                "Artist - Song",
+                "bg.png",
+                false,
+                &BackgroundMetadata::default(),
+                &mut ExtractionResult::new(),
+            )
+            .unwrap();
+        assert!(matches!(extracted, Extracted::Written(_)));
+
+        let converted = output.path().join("Artist - Song_bg.jpg");
+        let img = image::open(&converted).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 2));
+
+        // Already the target format and small enough: copied as is
+        let mut extractor = MediaExtractor::new(output.path()).with_image_format(ImageFormat::Png);
+        extractor
+            .extract_file(
+                &bg_path,
+                "Artist - Copy",
+                "bg.png",
+                false,
+                &BackgroundMetadata::default(),
+                &mut ExtractionResult::new(),
+            )
+            .unwrap();
+        assert_eq!(
+            fs::read(output.path().join("Artist - Copy_bg.png")).unwrap(),
+            fs::read(&bg_path).unwrap()
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_conversion_settings_change_extracts_again() {
+        let source = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let bg_path = source.path().join("bg.png");
+        image::RgbaImage::from_pixel(8, 4, image::Rgba([200, 40, 40, 255]))
+            .save(&bg_path)
+            .unwrap();
+
+        let extract = |max: u32| {
+            let mut extractor = MediaExtractor::new(output.path()).with_max_image_dimension(max);
+            extractor.load_manifest();
+            let extracted = extractor
+                .extract_file(
+                    &bg_path,
+                    "Artist - Song",
+                    "bg.png",
+                    false,
+                    &BackgroundMetadata::default(),
+                    &mut ExtractionResult::new(),
+                )
+                .unwrap();
+            extractor.save_manifest();
+            extracted
+        };
+
+        assert!(matches!(extract(4), Extracted::Written(_)));
+        assert!(matches!(extract(4), Extracted::Unchanged));
+        assert!(matches!(extract(2), Extracted::Written(_)));
+        let img = image::open(output.path().join("Artist - Song_bg.png")).unwrap();
+        assert_eq!((img.width(), img.height()), (2, 1));
+    }
+
+    #[test]
+    fn test_background_sidecar() {
+        let source = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let bg_path = source.path().join("bg.jpg");
+        fs::write(&bg_path, b"jpeg data").unwrap();
+
+        let metadata = BackgroundMetadata {
+            artist: "Artist".to_string(),
+            title: "Song".to_string(),
+            beatmap_set_id: Some(42),
+            difficulty: Some("Insane".to_string()),
+            mapper: Some("Mapper".to_string()),
+            source_file: "bg.jpg".to_string(),
+        };
+        let mut result = ExtractionResult::new();
+        let mut extractor = MediaExtractor::new(output.path()).with_metadata(true);
+        extractor
+            .extract_file(
+                &bg_path,
+                "Artist - Song",
+                "bg.jpg",
+                false,
+                &metadata,
+                &mut result,
+            )
+            .unwrap();
+
+        assert_eq!(result.metadata_files_created, 1);
+        let sidecar = fs::read_to_string(output.path().join("Artist - Song_bg.jpg.json")).unwrap();
+        let parsed: BackgroundMetadata = serde_json::from_str(&sidecar).unwrap();
+        assert_eq!(parsed, metadata);
+    }
+
     #[test]
     fn test_metadata_builder_defaults() {
         let extractor = MediaExtractor::new("/output");
@@ -0,0 +1,142 @@
+//! Record of the files a previous extraction wrote
+//!
+//! The manifest is stored in the output directory, so re-running an
+//! extraction into the same folder only copies media that is new or whose
+//! source changed. Sources are compared by size and modification time, or
+//! by store hash for lazer files, so unchanged files are never read.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Name of the manifest file inside the output directory
+pub const MANIFEST_FILE_NAME: &str = ".osu-sync-manifest.json";
+
+/// One extracted file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Beatmap set the file came from, as "Artist - Title"
+    pub source_set: String,
+    /// Source the file was written from: "size:mtime" of a Songs file, or
+    /// "lazer:<hash>" of a lazer store file
+    pub source: String,
+    /// Size of the written file in bytes
+    pub size: u64,
+}
+
+/// Extracted files by output path
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExtractionManifest {
+    /// Entries keyed by output path relative to the output directory,
+    /// always with `/` separators
+    files: BTreeMap<String, ManifestEntry>,
+    #[serde(skip)]
+    output_dir: PathBuf,
+}
+
+impl ExtractionManifest {
+    /// Load the manifest of `output_dir`
+    ///
+    /// A missing or unreadable manifest gives an empty one, so every file
+    /// is extracted again.
+    pub fn open(output_dir: impl Into<PathBuf>) -> Self {
+        let output_dir = output_dir.into();
+        let mut manifest: Self = fs::read_to_string(output_dir.join(MANIFEST_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        manifest.output_dir = output_dir;
+        manifest
+    }
+
+    /// Number of files in the manifest
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Check if the manifest has no files
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Entry for the file written to `output_path`
+    pub fn get(&self, output_path: &Path) -> Option<&ManifestEntry> {
+        self.files.get(&self.key(output_path))
+    }
+
+    /// Whether `output_path` was extracted from `source` and is still on
+    /// disk with the size that was written
+    pub fn is_unchanged(&self, output_path: &Path, source: &str) -> bool {
+        self.get(output_path).is_some_and(|entry| {
+            entry.source == source
+                && fs::metadata(output_path).is_ok_and(|meta| meta.len() == entry.size)
+        })
+    }
+
+    /// Remember that `output_path` was written from `source`
+    pub fn record(
+        &mut self,
+        output_path: &Path,
+        source_set: impl Into<String>,
+        source: impl Into<String>,
+        size: u64,
+    ) {
+        let entry = ManifestEntry {
+            source_set: source_set.into(),
+            source: source.into(),
+            size,
+        };
+        self.files.insert(self.key(output_path), entry);
+    }
+
+    /// Write the manifest to the output directory
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.output_dir).map_err(|e| Error::io_at(e, &self.output_dir))?;
+        let path = self.output_dir.join(MANIFEST_FILE_NAME);
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to save extraction manifest: {}", e)))?;
+        fs::write(&path, content).map_err(|e| Error::io_at(e, &path))
+    }
+
+    fn key(&self, output_path: &Path) -> String {
+        output_path
+            .strip_prefix(&self.output_dir)
+            .unwrap_or(output_path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("Artist - Song.mp3");
+        fs::write(&output, b"audio").unwrap();
+
+        let mut manifest = ExtractionManifest::open(dir.path());
+        assert!(manifest.is_empty());
+        manifest.record(&output, "Artist - Song", "abc", 5);
+        manifest.save().unwrap();
+
+        let manifest = ExtractionManifest::open(dir.path());
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest.get(&output).unwrap().source_set, "Artist - Song");
+        assert!(manifest.is_unchanged(&output, "abc"));
+        assert!(!manifest.is_unchanged(&output, "def"));
+
+        // Edited or deleted outputs are extracted again
+        fs::write(&output, b"edited audio").unwrap();
+        assert!(!manifest.is_unchanged(&output, "abc"));
+        fs::remove_file(&output).unwrap();
+        assert!(!manifest.is_unchanged(&output, "abc"));
+    }
+}
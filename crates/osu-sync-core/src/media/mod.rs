@@ -1,10 +1,13 @@
 //! Media extraction module for extracting audio and background files from beatmaps
 
 mod extractor;
+mod manifest;
 mod types;
 
 pub use extractor::MediaExtractor;
+pub use manifest::{ExtractionManifest, ManifestEntry, MANIFEST_FILE_NAME};
 pub use types::{
-    AudioFormat, AudioInfo, AudioMetadata, ExtractionProgress, ExtractionProgressCallback,
-    ExtractionResult, ExtractionSource, ImageSizeCategory, MediaType, OutputOrganization,
+    AudioFormat, AudioInfo, AudioMetadata, BackgroundMetadata, ExtractionProgress,
+    ExtractionProgressCallback, ExtractionResult, ExtractionSource, ImageFormat, ImageSizeCategory,
+    MediaType, OutputOrganization,
 };
@@ -64,6 +64,32 @@ impl ImageSizeCategory {
     }
 }
 
+/// Format to convert extracted background images to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    /// File extension used for converted images
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+        }
+    }
+
+    /// Check if a filename's extension already denotes this format
+    pub fn matches_filename(&self, filename: &str) -> bool {
+        let lower = filename.to_lowercase();
+        match self {
+            ImageFormat::Png => lower.ends_with(".png"),
+            ImageFormat::Jpeg => lower.ends_with(".jpg") || lower.ends_with(".jpeg"),
+        }
+    }
+}
+
 /// Audio file format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum AudioFormat {
@@ -222,6 +248,30 @@ impl AudioMetadata {
     }
 }
 
+/// Where an extracted background came from, written as a JSON sidecar
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackgroundMetadata {
+    /// Artist name
+    pub artist: String,
+    /// Song title
+    pub title: String,
+    /// Beatmap set ID
+    pub beatmap_set_id: Option<i32>,
+    /// Difficulty the background was taken from
+    pub difficulty: Option<String>,
+    /// Mapper name
+    pub mapper: Option<String>,
+    /// Original file name inside the beatmap set
+    pub source_file: String,
+}
+
+impl BackgroundMetadata {
+    /// Generate sidecar JSON file content
+    pub fn to_sidecar_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
 /// Result of a media extraction operation
 #[derive(Debug, Clone, Default)]
 pub struct ExtractionResult {
@@ -231,11 +281,14 @@ pub struct ExtractionResult {
     pub backgrounds_extracted: usize,
     /// Number of files skipped due to duplicates
     pub duplicates_skipped: usize,
+    /// Number of files skipped because the manifest shows a previous run
+    /// already extracted them and their source is unchanged
+    pub manifest_skipped: usize,
     /// Number of unique files extracted
     pub unique_files: usize,
     /// Total bytes written
     pub bytes_written: u64,
-    /// Number of metadata sidecar files created
+    /// Number of metadata sidecar files created, for audio and backgrounds
     pub metadata_files_created: usize,
     /// Audio format breakdown
     pub audio_by_format: std::collections::HashMap<String, usize>,
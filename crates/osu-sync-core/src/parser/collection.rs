@@ -0,0 +1,257 @@
+//! Multi-set collection archives
+//!
+//! A collection archive packs several beatmap sets into one zip, each in its
+//! own subfolder, alongside a manifest describing the sets. It is a separate
+//! format from a single-set .osz and [`extract_osz`](super::extract_osz)
+//! refuses to open one.
+
+use crate::beatmap::{BeatmapFile, BeatmapSet};
+use crate::error::{Error, Result};
+use crate::parser::parse_osu_file;
+use crate::utils::sanitize_filename;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Name of the manifest stored at the root of a collection archive
+pub const COLLECTION_MANIFEST: &str = "manifest.json";
+
+/// Current collection manifest format version
+const COLLECTION_VERSION: u32 = 1;
+
+/// One set listed in a collection manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectionEntry {
+    /// Subfolder inside the archive holding the set's files
+    pub folder: String,
+    /// Online beatmap set ID (if available)
+    pub id: Option<i32>,
+    /// Artist of the set
+    pub artist: String,
+    /// Title of the set
+    pub title: String,
+}
+
+/// Manifest describing the contents of a collection archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectionManifest {
+    /// Manifest format version
+    pub version: u32,
+    /// Sets contained in the archive
+    pub sets: Vec<CollectionEntry>,
+}
+
+/// Pack several beatmap sets from `songs_dir` into a single collection archive
+pub fn create_collection_archive(
+    sets: &[BeatmapSet],
+    songs_dir: &Path,
+    out_path: &Path,
+) -> Result<()> {
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut zip = ZipWriter::new(File::create(out_path)?);
+    let options = FileOptions::<()>::default().compression_method(zip::CompressionMethod::Stored);
+
+    let mut used = HashSet::new();
+    let mut entries = Vec::with_capacity(sets.len());
+
+    for set in sets {
+        let source_folder = set
+            .disk_folder_name()
+            .ok_or_else(|| Error::Other("Beatmap set has no folder name".to_string()))?;
+        let source_dir = songs_dir.join(source_folder);
+        if !source_dir.is_dir() {
+            return Err(Error::BeatmapNotFound(
+                source_folder.to_string_lossy().into_owned(),
+            ));
+        }
+
+        let base = sanitize_filename(&set.generate_folder_name());
+        let mut folder = base.clone();
+        let mut n = 1;
+        while !used.insert(folder.to_lowercase()) {
+            folder = format!("{} ({})", base, n);
+            n += 1;
+        }
+
+        for entry in walkdir::WalkDir::new(&source_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let relative = entry
+                .path()
+                .strip_prefix(&source_dir)
+                .map_err(|_| Error::Other("Failed to get relative path".to_string()))?;
+            let name = format!(
+                "{}/{}",
+                folder,
+                relative.to_string_lossy().replace('\\', "/")
+            );
+            zip.start_file(name, options)?;
+            zip.write_all(&fs::read(entry.path())?)?;
+        }
+
+        let meta = set.metadata();
+        entries.push(CollectionEntry {
+            folder,
+            id: set.id,
+            artist: meta.map(|m| m.artist.clone()).unwrap_or_default(),
+            title: meta.map(|m| m.title.clone()).unwrap_or_default(),
+        });
+    }
+
+    let manifest = CollectionManifest {
+        version: COLLECTION_VERSION,
+        sets: entries,
+    };
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| Error::Other(format!("Failed to serialize manifest: {}", e)))?;
+    zip.start_file(COLLECTION_MANIFEST, options)?;
+    zip.write_all(&json)?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Check whether an open archive is a collection archive
+pub(crate) fn is_collection_archive<R: Read + std::io::Seek>(archive: &ZipArchive<R>) -> bool {
+    archive.file_names().any(|name| name == COLLECTION_MANIFEST)
+}
+
+/// Unpack a collection archive into `dest`, returning one [`BeatmapSet`] per set
+///
+/// Each set is extracted to `dest/<folder>` as listed in the manifest.
+pub fn extract_collection_archive(archive_path: &Path, dest: &Path) -> Result<Vec<BeatmapSet>> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+
+    let manifest: CollectionManifest = {
+        let mut file = archive
+            .by_name(COLLECTION_MANIFEST)
+            .map_err(|_| Error::InvalidOsz {
+                reason: "Not a collection archive (missing manifest)".to_string(),
+            })?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        serde_json::from_slice(&content).map_err(|e| Error::InvalidOsz {
+            reason: format!("Invalid collection manifest: {}", e),
+        })?
+    };
+
+    fs::create_dir_all(dest)?;
+    let mut sets: Vec<BeatmapSet> = manifest
+        .sets
+        .iter()
+        .map(|entry| {
+            let mut set = BeatmapSet::new();
+            set.id = entry.id;
+            set.folder_name = Some(entry.folder.clone());
+            set
+        })
+        .collect();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() || file.name() == COLLECTION_MANIFEST {
+            continue;
+        }
+        let path = file
+            .enclosed_name()
+            .ok_or_else(|| Error::InvalidOsz {
+                reason: "Invalid file path in archive".to_string(),
+            })?
+            .to_path_buf();
+
+        let mut components = path.components();
+        let folder = components
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+        let relative = components.as_path().to_string_lossy().replace('\\', "/");
+        let Some(set_index) = manifest.sets.iter().position(|e| e.folder == folder) else {
+            tracing::warn!("Skipping {} not listed in manifest", path.display());
+            continue;
+        };
+
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+
+        let dest_path = dest.join(&path);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&dest_path, &content)?;
+
+        let set = &mut sets[set_index];
+        set.files.push(BeatmapFile {
+            filename: relative.clone(),
+            hash: format!("{:x}", Sha256::digest(&content)),
+            size: content.len() as u64,
+        });
+
+        if relative.to_lowercase().ends_with(".osu") {
+            match parse_osu_file(&dest_path) {
+                Ok(info) => {
+                    if set.id.is_none() {
+                        set.id = info.metadata.beatmap_set_id;
+                    }
+                    set.beatmaps.push(info);
+                }
+                Err(e) => tracing::warn!("Failed to parse {}: {}", dest_path.display(), e),
+            }
+        }
+    }
+
+    Ok(sets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_osz;
+
+    #[test]
+    fn test_collection_archive_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let songs = temp_dir.path().join("Songs");
+        for (folder, title) in [("1 A - One", "One"), ("2 B - Two", "Two")] {
+            fs::create_dir_all(songs.join(folder)).unwrap();
+            let osu = format!("osu file format v14\n\n[Metadata]\nTitle:{}\n", title);
+            fs::write(songs.join(folder).join("map.osu"), osu).unwrap();
+            fs::write(songs.join(folder).join("audio.mp3"), [0u8; 8]).unwrap();
+        }
+
+        let sets: Vec<BeatmapSet> = [(1, "1 A - One"), (2, "2 B - Two")]
+            .iter()
+            .map(|(id, folder)| {
+                let mut set = BeatmapSet::new();
+                set.id = Some(*id);
+                set.folder_name = Some(folder.to_string());
+                set
+            })
+            .collect();
+
+        let archive = temp_dir.path().join("collection.zip");
+        create_collection_archive(&sets, &songs, &archive).unwrap();
+
+        // A collection is not a single-set .osz
+        assert!(matches!(
+            extract_osz(&archive, &temp_dir.path().join("osz")),
+            Err(Error::InvalidOsz { .. })
+        ));
+
+        let extracted = extract_collection_archive(&archive, &temp_dir.path().join("out")).unwrap();
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(extracted[0].id, Some(1));
+        assert_eq!(extracted[0].files.len(), 2);
+        assert_eq!(extracted[0].beatmaps.len(), 1);
+        assert_eq!(extracted[1].beatmaps[0].metadata.title, "Two");
+    }
+}
@@ -0,0 +1,200 @@
+//! Custom hitsound reference parsing
+//!
+//! Custom samples are referenced in two ways: explicitly, through the
+//! `filename` field of a hit object's hit sample, and implicitly, through a
+//! sample set and custom index (`soft-hitclap2.wav`) picked by timing points
+//! or hit objects. Implicit samples fall back to the skin when absent, so only
+//! explicit references can be considered missing.
+
+/// Sample sets as numbered in `.osu` files (0 means "inherit")
+const SAMPLE_SETS: [&str; 3] = ["normal", "soft", "drum"];
+
+/// Sample names that can be customized per sample set and index
+const SAMPLE_KINDS: [&str; 7] = [
+    "hitnormal",
+    "hitwhistle",
+    "hitfinish",
+    "hitclap",
+    "slidertick",
+    "sliderslide",
+    "sliderwhistle",
+];
+
+/// File extensions osu! looks for when resolving an implicit sample
+pub const SAMPLE_EXTENSIONS: [&str; 3] = ["wav", "ogg", "mp3"];
+
+/// Custom hitsound files referenced by a single `.osu` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HitsoundReferences {
+    /// Files named explicitly by hit objects; these must exist
+    pub files: Vec<String>,
+    /// Sample names without extension (e.g. `soft-hitclap2`) that the map
+    /// would use if present; see [`SAMPLE_EXTENSIONS`]
+    pub samples: Vec<String>,
+}
+
+/// Collect the custom hitsound files referenced by `.osu` content
+pub fn parse_hitsound_references(content: &str) -> HitsoundReferences {
+    let mut refs = HitsoundReferences::default();
+    let mut default_set = 1;
+    // (sample set, custom index) pairs in use
+    let mut banks: Vec<(usize, u32)> = Vec::new();
+    let mut section = String::new();
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line[1..line.len() - 1].to_string();
+            continue;
+        }
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        match section.as_str() {
+            "General" => {
+                if let Some((key, value)) = line.split_once(':') {
+                    if key.trim() == "SampleSet" {
+                        default_set = match value.trim().to_ascii_lowercase().as_str() {
+                            "soft" => 2,
+                            "drum" => 3,
+                            _ => 1,
+                        };
+                    }
+                }
+            }
+            "TimingPoints" => {
+                let fields: Vec<&str> = line.split(',').collect();
+                let set = fields.get(3).and_then(|f| f.trim().parse::<usize>().ok());
+                let index = fields.get(4).and_then(|f| f.trim().parse::<u32>().ok());
+                if let (Some(set), Some(index)) = (set, index) {
+                    push_bank(&mut banks, resolve_set(set, default_set), index);
+                }
+            }
+            "HitObjects" => {
+                let fields: Vec<&str> = line.split(',').collect();
+                // Slider edge sets look like `0:0|1:0` and are not hit samples
+                let Some(hit_sample) = fields
+                    .last()
+                    .filter(|f| f.contains(':') && !f.contains('|'))
+                else {
+                    continue;
+                };
+                // Mania hold notes (type bit 128) put `endTime:` in front
+                let is_hold = fields
+                    .get(3)
+                    .and_then(|f| f.trim().parse::<u32>().ok())
+                    .is_some_and(|kind| kind & 128 != 0);
+                let hit_sample = match hit_sample.split_once(':') {
+                    Some((_, rest)) if is_hold => rest,
+                    _ => hit_sample,
+                };
+                // normalSet:additionSet:index:volume:filename
+                let parts: Vec<&str> = hit_sample.split(':').collect();
+                if parts.len() < 3 {
+                    continue;
+                }
+                if let Some(filename) = parts.get(4).map(|f| f.trim()).filter(|f| !f.is_empty()) {
+                    let filename = filename.replace('\\', "/");
+                    if !refs.files.contains(&filename) {
+                        refs.files.push(filename);
+                    }
+                }
+                let index = parts[2].trim().parse::<u32>().unwrap_or(0);
+                for set in &parts[..2] {
+                    if let Ok(set) = set.trim().parse::<usize>() {
+                        if set != 0 {
+                            push_bank(&mut banks, set, index);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (set, index) in banks {
+        let suffix = if index == 1 {
+            String::new()
+        } else {
+            index.to_string()
+        };
+        for kind in SAMPLE_KINDS {
+            refs.samples
+                .push(format!("{}-{}{}", SAMPLE_SETS[set - 1], kind, suffix));
+        }
+    }
+
+    refs
+}
+
+/// Map a sample set number to 1..=3, using `default_set` for "inherit"
+fn resolve_set(set: usize, default_set: usize) -> usize {
+    if (1..=3).contains(&set) {
+        set
+    } else {
+        default_set
+    }
+}
+
+/// Record a custom bank; index 0 means "use the skin" and is skipped
+fn push_bank(banks: &mut Vec<(usize, u32)>, set: usize, index: u32) {
+    if index == 0 || !(1..=3).contains(&set) {
+        return;
+    }
+    if !banks.contains(&(set, index)) {
+        banks.push((set, index));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hitsound_references() {
+        let osu = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+SampleSet: Soft
+
+[TimingPoints]
+0,500,4,0,1,60,1,0
+1000,-100,4,3,2,60,0,0
+2000,500,4,1,0,60,1,0
+
+[HitObjects]
+256,192,100,1,0,0:0:0:0:
+256,192,200,1,2,0:0:0:0:kick.wav
+256,192,300,2,0,L|300:200,1,100,2|0,0:0|0:0,0:0:0:0:
+";
+        let refs = parse_hitsound_references(osu);
+        assert_eq!(refs.files, vec!["kick.wav"]);
+        assert!(refs.samples.contains(&"soft-hitnormal".to_string()));
+        assert!(refs.samples.contains(&"drum-hitclap2".to_string()));
+        assert!(!refs.samples.iter().any(|s| s.starts_with("normal-")));
+        assert_eq!(refs.samples.len(), 2 * SAMPLE_KINDS.len());
+    }
+
+    #[test]
+    fn test_parse_mania_hold_note_references() {
+        let osu = "osu file format v14
+
+[General]
+Mode: 3
+
+[HitObjects]
+64,192,100,128,0,600:2:0:3:0:hold.wav
+192,192,200,1,0,0:0:0:0:tap.wav
+";
+        let refs = parse_hitsound_references(osu);
+        assert_eq!(refs.files, vec!["hold.wav", "tap.wav"]);
+        assert_eq!(
+            refs.samples,
+            SAMPLE_KINDS
+                .iter()
+                .map(|kind| format!("soft-{}3", kind))
+                .collect::<Vec<_>>()
+        );
+    }
+}
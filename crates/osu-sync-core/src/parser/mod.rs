@@ -1,7 +1,16 @@
 //! Beatmap file parsing
 
+mod collection;
+mod hitsound;
 mod osu_file;
 mod osz;
+mod storyboard;
 
+pub use collection::{
+    create_collection_archive, extract_collection_archive, CollectionEntry, CollectionManifest,
+    COLLECTION_MANIFEST,
+};
+pub use hitsound::{parse_hitsound_references, HitsoundReferences, SAMPLE_EXTENSIONS};
 pub use osu_file::*;
 pub use osz::*;
+pub use storyboard::parse_storyboard_references;
@@ -3,12 +3,78 @@
 use crate::beatmap::{BeatmapDifficulty, BeatmapInfo, BeatmapMetadata, GameMode};
 use crate::error::{Error, Result};
 use md5::{Digest as Md5Digest, Md5};
+use std::fmt;
 use std::fs;
+use std::ops::RangeInclusive;
 use std::path::Path;
 
+/// `osu file format` versions written by osu!stable and osu!lazer
+///
+/// v128 marks maps saved by lazer with features stable can't read.
+const KNOWN_FORMAT_VERSIONS: [RangeInclusive<u32>; 2] = [3..=14, 128..=128];
+
+/// Newest `osu file format` version the parser has been tested against
+///
+/// This is the version osu!stable currently writes.
+pub const LATEST_FORMAT_VERSION: u8 = 14;
+
+/// Sections every .osu file is expected to have
+const REQUIRED_SECTIONS: [&str; 2] = ["General", "Metadata"];
+
+/// How strictly .osu files are checked while parsing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail on any [`ParseWarning`] instead of returning it
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    /// Options that reject files with any [`ParseWarning`]
+    pub fn strict() -> Self {
+        Self { strict: true }
+    }
+}
+
+/// A problem found in a .osu file that the parser could work around
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWarning {
+    /// The file doesn't start with an `osu file format vN` line
+    MissingFormatVersion,
+    /// The file format version is not one osu! has written
+    UnknownFormatVersion(u32),
+    /// A required section such as `[Metadata]` is absent
+    MissingSection(&'static str),
+    /// A difficulty setting is outside the range the editor allows
+    DifficultyOutOfRange { field: &'static str, value: f32 },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFormatVersion => write!(f, "missing 'osu file format' header"),
+            Self::UnknownFormatVersion(v) => write!(f, "unknown file format version v{}", v),
+            Self::MissingSection(name) => write!(f, "missing [{}] section", name),
+            Self::DifficultyOutOfRange { field, value } => {
+                write!(f, "{} {} is out of range", field, value)
+            }
+        }
+    }
+}
+
 /// Parse a .osu file and extract beatmap information
 /// Uses Blake3 for fast hashing (5-10x faster than SHA-256)
 pub fn parse_osu_file(path: &Path) -> Result<BeatmapInfo> {
+    parse_osu_file_with_options(path, &ParseOptions::default()).map(|(info, _)| info)
+}
+
+/// Parse a .osu file, checking it against `options`
+///
+/// Problems the parser works around are returned as warnings. In strict
+/// mode they fail the parse instead, with every warning in the message.
+pub fn parse_osu_file_with_options(
+    path: &Path,
+    options: &ParseOptions,
+) -> Result<(BeatmapInfo, Vec<ParseWarning>)> {
     let content = fs::read(path)?;
 
     // Calculate hashes - use Blake3 instead of SHA-256 (5-10x faster)
@@ -54,12 +120,22 @@ pub fn parse_osu_file(path: &Path) -> Result<BeatmapInfo> {
         },
     };
 
+    let text = String::from_utf8_lossy(&content);
+    let version = format_version(&text);
+
+    // Files before v8 have no ApproachRate; osu! uses OD in its place
+    let approach_rate = if has_key(&text, "Difficulty", "ApproachRate") {
+        beatmap.approach_rate
+    } else {
+        beatmap.overall_difficulty
+    };
+
     // Extract difficulty settings
     let difficulty = BeatmapDifficulty {
         hp_drain: beatmap.hp_drain_rate,
         circle_size: beatmap.circle_size,
         overall_difficulty: beatmap.overall_difficulty,
-        approach_rate: beatmap.approach_rate,
+        approach_rate,
         slider_multiplier: beatmap.slider_multiplier,
         slider_tick_rate: beatmap.slider_tick_rate,
     };
@@ -70,7 +146,25 @@ pub fn parse_osu_file(path: &Path) -> Result<BeatmapInfo> {
     // Calculate main BPM
     let bpm = calculate_bpm(&beatmap);
 
-    Ok(BeatmapInfo {
+    let warnings = check_content(
+        &text,
+        version,
+        &difficulty,
+        GameMode::from(beatmap.mode as u8),
+    );
+    if options.strict && !warnings.is_empty() {
+        let message = warnings
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(Error::BeatmapParse {
+            path: path.to_path_buf(),
+            message,
+        });
+    }
+
+    let info = BeatmapInfo {
         metadata,
         difficulty,
         hash: blake3_hash, // Use Blake3 (5-10x faster than SHA-256)
@@ -82,8 +176,86 @@ pub fn parse_osu_file(path: &Path) -> Result<BeatmapInfo> {
         mode: GameMode::from(beatmap.mode as u8),
         version: beatmap.version.clone(),
         star_rating: None, // Not available from .osu file, populated from database
+        star_ratings: Default::default(),
         ranked_status: None, // Not available from .osu file, populated from database
-    })
+        format_version: version.map_or(0, |v| u8::try_from(v).unwrap_or(u8::MAX)),
+    };
+    Ok((info, warnings))
+}
+
+/// Check the raw file for problems rosu-map silently accepts
+fn check_content(
+    text: &str,
+    version: Option<u32>,
+    difficulty: &BeatmapDifficulty,
+    mode: GameMode,
+) -> Vec<ParseWarning> {
+    let mut warnings = Vec::new();
+
+    match version {
+        None => warnings.push(ParseWarning::MissingFormatVersion),
+        Some(v) if !KNOWN_FORMAT_VERSIONS.iter().any(|r| r.contains(&v)) => {
+            warnings.push(ParseWarning::UnknownFormatVersion(v))
+        }
+        Some(_) => {}
+    }
+
+    for section in REQUIRED_SECTIONS {
+        let header = format!("[{}]", section);
+        if !text.lines().any(|line| line.trim() == header) {
+            warnings.push(ParseWarning::MissingSection(section));
+        }
+    }
+
+    // Mania stores the key count in CircleSize
+    let max_cs = if mode == GameMode::Mania { 18.0 } else { 10.0 };
+    let ranges = [
+        ("HPDrainRate", difficulty.hp_drain, 0.0..=10.0),
+        ("CircleSize", difficulty.circle_size, 0.0..=max_cs),
+        (
+            "OverallDifficulty",
+            difficulty.overall_difficulty,
+            0.0..=10.0,
+        ),
+        ("ApproachRate", difficulty.approach_rate, 0.0..=10.0),
+    ];
+    for (field, value, range) in ranges {
+        if !range.contains(&value) {
+            warnings.push(ParseWarning::DifficultyOutOfRange { field, value });
+        }
+    }
+
+    warnings
+}
+
+/// The N of the `osu file format vN` header on the first non-empty line
+fn format_version(text: &str) -> Option<u32> {
+    text.trim_start_matches('\u{feff}')
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?
+        .strip_prefix("osu file format v")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Whether `key` is set in the given `[section]`
+fn has_key(text: &str, section: &str, key: &str) -> bool {
+    let header = format!("[{}]", section);
+    let mut in_section = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = line == header;
+        } else if in_section
+            && line
+                .split_once(':')
+                .is_some_and(|(name, _)| name.trim() == key)
+        {
+            return true;
+        }
+    }
+    false
 }
 
 /// Calculate the length of the beatmap in milliseconds
@@ -132,6 +304,74 @@ fn extract_background(beatmap: &rosu_map::Beatmap) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_osu(dir: &TempDir, content: &str) -> std::path::PathBuf {
+        let path = dir.path().join("test.osu");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_warnings_and_strict_mode() {
+        let dir = TempDir::new().unwrap();
+
+        let valid = write_osu(
+            &dir,
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nMode: 0\n\n[Metadata]\nTitle:Title\nVersion:Hard\n\n[Difficulty]\nHPDrainRate:5\nCircleSize:4\nOverallDifficulty:8\nApproachRate:9\n",
+        );
+        let (info, warnings) =
+            parse_osu_file_with_options(&valid, &ParseOptions::strict()).unwrap();
+        assert_eq!(info.version, "Hard");
+        assert_eq!(info.format_version, 14);
+        assert!(warnings.is_empty());
+
+        let broken = write_osu(
+            &dir,
+            "osu file format v99\n\n[General]\nAudioFilename: audio.mp3\n\n[Difficulty]\nApproachRate:11\n",
+        );
+        let (_, warnings) = parse_osu_file_with_options(&broken, &ParseOptions::default()).unwrap();
+        assert!(warnings.contains(&ParseWarning::UnknownFormatVersion(99)));
+        assert!(warnings.contains(&ParseWarning::MissingSection("Metadata")));
+        assert!(warnings.contains(&ParseWarning::DifficultyOutOfRange {
+            field: "ApproachRate",
+            value: 11.0
+        }));
+        assert!(parse_osu_file(&broken).is_ok());
+
+        let err = parse_osu_file_with_options(&broken, &ParseOptions::strict()).unwrap_err();
+        assert!(err.to_string().contains("missing [Metadata] section"));
+    }
+
+    #[test]
+    fn test_old_format_approach_rate() {
+        let dir = TempDir::new().unwrap();
+        let path = write_osu(
+            &dir,
+            "osu file format v5\n\n[General]\nAudioFilename: audio.mp3\n\n[Metadata]\nTitle:Old\n\n[Difficulty]\nHPDrainRate:4\nCircleSize:4\nOverallDifficulty:6\n",
+        );
+        let info = parse_osu_file(&path).unwrap();
+        assert_eq!(info.format_version, 5);
+        assert_eq!(info.difficulty.approach_rate, 6.0);
+    }
+
+    #[test]
+    fn test_online_id() {
+        let dir = TempDir::new().unwrap();
+        let path = write_osu(
+            &dir,
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\n\n[Metadata]\nTitle:Title\nBeatmapID:75\nBeatmapSetID:1\n",
+        );
+        assert_eq!(parse_osu_file(&path).unwrap().online_id(), Some(75));
+
+        let path = write_osu(
+            &dir,
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\n\n[Metadata]\nTitle:Title\nBeatmapID:-1\nBeatmapSetID:-1\n",
+        );
+        assert_eq!(parse_osu_file(&path).unwrap().online_id(), None);
+    }
+
     #[test]
     fn test_bpm_calculation() {
         // BPM = 60000 / beat_len
@@ -2,19 +2,56 @@
 
 use crate::beatmap::{BeatmapFile, BeatmapSet};
 use crate::error::{Error, Result};
+use crate::parser::collection::is_collection_archive;
 use crate::parser::parse_osu_file;
+use crate::sync::{Throttle, ThrottledWriter};
+use crate::utils::{sanitize_filename, write_with_progress};
+use rayon::prelude::*;
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
 
 /// Extract an .osz archive to a destination directory
 pub fn extract_osz(osz_path: &Path, dest: &Path) -> Result<BeatmapSet> {
+    extract_osz_with_progress(osz_path, dest, |_, _, _, _| {})
+}
+
+/// Extract an .osz archive, reporting progress after each file
+///
+/// The callback receives `(files_done, total_files, bytes_done, bytes_total)`.
+/// Totals are read from the zip central directory before extraction starts,
+/// so large archives can show an accurate progress bar.
+pub fn extract_osz_with_progress<F>(
+    osz_path: &Path,
+    dest: &Path,
+    mut callback: F,
+) -> Result<BeatmapSet>
+where
+    F: FnMut(usize, usize, u64, u64),
+{
     let file = File::open(osz_path)?;
     let mut archive = ZipArchive::new(file)?;
 
+    if is_collection_archive(&archive) {
+        return Err(Error::InvalidOsz {
+            reason: "Archive is a multi-set collection; use extract_collection_archive".to_string(),
+        });
+    }
+
+    let total_files = archive.len();
+    let mut bytes_total = 0u64;
+    for i in 0..total_files {
+        bytes_total += archive.by_index_raw(i)?.size();
+    }
+    let mut bytes_done = 0u64;
+    callback(0, total_files, 0, bytes_total);
+
     // Create destination directory
     fs::create_dir_all(dest)?;
 
@@ -22,7 +59,7 @@ pub fn extract_osz(osz_path: &Path, dest: &Path) -> Result<BeatmapSet> {
     let mut osu_files = Vec::new();
 
     // Extract all files
-    for i in 0..archive.len() {
+    for i in 0..total_files {
         let mut file = archive.by_index(i)?;
         let filename = file
             .enclosed_name()
@@ -61,6 +98,9 @@ pub fn extract_osz(osz_path: &Path, dest: &Path) -> Result<BeatmapSet> {
         if filename_str.to_lowercase().ends_with(".osu") {
             osu_files.push(dest_path);
         }
+
+        bytes_done += content.len() as u64;
+        callback(i + 1, total_files, bytes_done, bytes_total);
     }
 
     // Parse all .osu files
@@ -91,6 +131,38 @@ pub fn extract_osz(osz_path: &Path, dest: &Path) -> Result<BeatmapSet> {
     Ok(beatmap_set)
 }
 
+/// Check that an .osz archive is readable and contains at least one valid .osu file
+///
+/// Parses the difficulties in memory without extracting anything to disk.
+/// Returns the number of difficulties that parsed successfully.
+pub fn validate_osz(osz_path: &Path) -> Result<usize> {
+    let file = File::open(osz_path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    let mut valid = 0;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if !file.name().to_lowercase().ends_with(".osu") {
+            continue;
+        }
+
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        match rosu_map::Beatmap::from_bytes(&content) {
+            Ok(_) => valid += 1,
+            Err(e) => tracing::warn!("Invalid {} in {}: {}", file.name(), osz_path.display(), e),
+        }
+    }
+
+    if valid == 0 {
+        return Err(Error::InvalidOsz {
+            reason: "No valid .osu files found in archive".to_string(),
+        });
+    }
+
+    Ok(valid)
+}
+
 /// Create an .osz archive from a beatmap set
 pub fn create_osz(source_dir: &Path, dest_path: &Path) -> Result<PathBuf> {
     let file = File::create(dest_path)?;
@@ -123,25 +195,230 @@ pub fn create_osz(source_dir: &Path, dest_path: &Path) -> Result<PathBuf> {
 
 /// Create an .osz archive from a BeatmapSet with files already loaded
 pub fn create_osz_from_set(
+    beatmap_set: &BeatmapSet,
+    files: &[(String, Vec<u8>)],
+    dest_path: &Path,
+) -> Result<PathBuf> {
+    create_osz_from_set_with_progress(beatmap_set, files, dest_path, |_, _| {})
+}
+
+/// Like [`create_osz_from_set`], reporting progress through large files
+///
+/// `on_progress` receives the file name and the fraction of it written, see
+/// [`write_with_progress`] for how often.
+pub fn create_osz_from_set_with_progress(
     _beatmap_set: &BeatmapSet,
     files: &[(String, Vec<u8>)],
     dest_path: &Path,
+    on_progress: impl FnMut(&str, f32),
+) -> Result<PathBuf> {
+    write_osz_entries(files, dest_path, None, on_progress)
+}
+
+/// Write `files` into a new .osz archive at `dest_path`
+///
+/// Writes are paced by `throttle` when given.
+pub(crate) fn write_osz_entries<'a>(
+    files: impl IntoIterator<Item = &'a (String, Vec<u8>)>,
+    dest_path: &Path,
+    throttle: Option<&Throttle>,
+    mut on_progress: impl FnMut(&str, f32),
 ) -> Result<PathBuf> {
     let file = File::create(dest_path)?;
-    let mut zip = ZipWriter::new(file);
+    let mut zip = ZipWriter::new(ThrottledWriter::new(file, throttle));
 
     let options = FileOptions::<()>::default().compression_method(zip::CompressionMethod::Stored);
 
     for (filename, content) in files {
         zip.start_file(filename.as_str(), options)?;
-        zip.write_all(content)?;
+        write_with_progress(&mut zip, content, |fraction| {
+            on_progress(filename, fraction)
+        })?;
     }
 
     zip.finish()?;
     Ok(dest_path.to_path_buf())
 }
 
+/// Outcome of [`create_osz_batch`]
+#[derive(Debug, Default)]
+pub struct OszBatchResult {
+    /// Archives that were written, in input order
+    pub created: Vec<PathBuf>,
+    /// Sets that could not be exported, with the reason
+    pub failed: Vec<(String, Error)>,
+}
+
+/// Export many beatmap sets as individual .osz archives
+///
+/// Each set is read from `songs_dir/<folder_name>` and written to
+/// `out_dir/<generated folder name>.osz`. Failures are collected rather than
+/// aborting the batch. `threads` controls how many archives are created in
+/// parallel (`0` uses the global rayon pool, `1` runs sequentially).
+///
+/// The callback receives `(sets_done, total_sets, set_name)` after each set.
+pub fn create_osz_batch<F>(
+    sets: &[BeatmapSet],
+    songs_dir: &Path,
+    out_dir: &Path,
+    threads: usize,
+    callback: F,
+) -> Result<OszBatchResult>
+where
+    F: Fn(usize, usize, &str) + Sync,
+{
+    fs::create_dir_all(out_dir)?;
+
+    // Assign unique archive names up front so parallel writers never collide
+    let mut used = HashSet::new();
+    let jobs: Vec<(String, PathBuf)> = sets
+        .iter()
+        .map(|set| {
+            let base = sanitize_filename(&set.generate_folder_name());
+            let mut name = base.clone();
+            let mut n = 1;
+            while !used.insert(name.to_lowercase()) {
+                name = format!("{} ({})", base, n);
+                n += 1;
+            }
+            (name.clone(), out_dir.join(format!("{}.osz", name)))
+        })
+        .collect();
+
+    let total = sets.len();
+    let done = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(total));
+
+    let export = |index: usize| {
+        let set = &sets[index];
+        let (name, dest) = &jobs[index];
+        let outcome = match set.disk_folder_name() {
+            Some(folder) if songs_dir.join(folder).is_dir() => {
+                create_osz(&songs_dir.join(folder), dest)
+            }
+            Some(folder) => Err(Error::BeatmapNotFound(
+                folder.to_string_lossy().into_owned(),
+            )),
+            None => Err(Error::Other("Beatmap set has no folder name".to_string())),
+        };
+        if outcome.is_err() {
+            let _ = fs::remove_file(dest);
+        }
+        results.lock().unwrap().push((index, outcome));
+        callback(done.fetch_add(1, Ordering::SeqCst) + 1, total, name);
+    };
+
+    match threads {
+        1 => (0..total).for_each(export),
+        0 => (0..total).into_par_iter().for_each(export),
+        n => rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build()
+            .map_err(|e| Error::Other(format!("Failed to create thread pool: {}", e)))?
+            .install(|| (0..total).into_par_iter().for_each(export)),
+    }
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+
+    let mut batch = OszBatchResult::default();
+    for (index, outcome) in results {
+        match outcome {
+            Ok(path) => batch.created.push(path),
+            Err(e) => {
+                tracing::warn!("Failed to export {}: {}", jobs[index].0, e);
+                batch.failed.push((jobs[index].0.clone(), e));
+            }
+        }
+    }
+
+    Ok(batch)
+}
+
 #[cfg(test)]
 mod tests {
-    // Integration tests would go here with actual .osz files
+    use super::*;
+
+    #[test]
+    fn test_extract_osz_reports_progress() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let osz_path = temp_dir.path().join("set.osz");
+        let files = vec![
+            (
+                "map.osu".to_string(),
+                b"osu file format v14\n\n[Metadata]\nTitle:Test\nBeatmapSetID:1\n".to_vec(),
+            ),
+            ("audio.mp3".to_string(), vec![0u8; 1024]),
+        ];
+        create_osz_from_set(&BeatmapSet::new(), &files, &osz_path).unwrap();
+        let bytes_total: u64 = files.iter().map(|(_, c)| c.len() as u64).sum();
+
+        let mut calls = Vec::new();
+        let set =
+            extract_osz_with_progress(&osz_path, &temp_dir.path().join("out"), |f, t, b, bt| {
+                calls.push((f, t, b, bt))
+            })
+            .unwrap();
+
+        assert_eq!(set.files.len(), 2);
+        assert_eq!(calls.first(), Some(&(0, 2, 0, bytes_total)));
+        assert_eq!(calls.last(), Some(&(2, 2, bytes_total, bytes_total)));
+        assert_eq!(calls.len(), 3);
+    }
+
+    #[test]
+    fn test_validate_osz_rejects_archive_without_difficulties() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let broken = temp_dir.path().join("broken.osz");
+        let files = vec![("audio.mp3".to_string(), vec![0u8; 16])];
+        create_osz_from_set(&BeatmapSet::new(), &files, &broken).unwrap();
+        assert!(matches!(
+            validate_osz(&broken),
+            Err(Error::InvalidOsz { .. })
+        ));
+
+        let not_zip = temp_dir.path().join("not_zip.osz");
+        fs::write(&not_zip, b"definitely not a zip").unwrap();
+        assert!(validate_osz(&not_zip).is_err());
+
+        let valid = temp_dir.path().join("valid.osz");
+        let files = vec![(
+            "map.osu".to_string(),
+            b"osu file format v14\n\n[Metadata]\nTitle:Test\n".to_vec(),
+        )];
+        create_osz_from_set(&BeatmapSet::new(), &files, &valid).unwrap();
+        assert_eq!(validate_osz(&valid).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_create_osz_batch_collects_failures() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let songs = temp_dir.path().join("Songs");
+        fs::create_dir_all(songs.join("1 A - B")).unwrap();
+        fs::write(
+            songs.join("1 A - B").join("map.osu"),
+            b"osu file format v14",
+        )
+        .unwrap();
+
+        let mut present = BeatmapSet::new();
+        present.id = Some(1);
+        present.folder_name = Some("1 A - B".to_string());
+        let mut missing = BeatmapSet::new();
+        missing.id = Some(2);
+        missing.folder_name = Some("2 C - D".to_string());
+
+        let out = temp_dir.path().join("out");
+        let progress = AtomicUsize::new(0);
+        let result = create_osz_batch(&[present, missing], &songs, &out, 2, |_, total, _| {
+            assert_eq!(total, 2);
+            progress.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        assert_eq!(progress.load(Ordering::SeqCst), 2);
+        assert_eq!(result.created.len(), 1);
+        assert!(result.created[0].exists());
+        assert_eq!(result.failed.len(), 1);
+    }
 }
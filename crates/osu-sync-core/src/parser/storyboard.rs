@@ -0,0 +1,187 @@
+//! Storyboard asset reference parsing
+//!
+//! Storyboards (the set-wide `.osb` file and the `[Events]` section of each
+//! `.osu` file) reference images and sounds that often live in subfolders
+//! such as `sb/`. These are not visible from a flat directory listing, so
+//! they are extracted here to make sure exports carry them along.
+
+use std::collections::HashMap;
+
+/// Extract all asset paths referenced by storyboard commands
+///
+/// Handles `Sprite`, `Animation` and `Sample` commands, including
+/// `[Variables]` substitution. Animation references are expanded into their
+/// individual frame files (`name0.png`, `name1.png`, ...). Paths are returned
+/// with forward slashes, deduplicated, in order of first appearance.
+pub fn parse_storyboard_references(content: &str) -> Vec<String> {
+    let variables = parse_variables(content);
+    let mut references = Vec::new();
+    let mut in_events = false;
+
+    for raw_line in content.lines() {
+        let line = raw_line.trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            in_events = line.eq_ignore_ascii_case("[Events]");
+            continue;
+        }
+        if !in_events || line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        // Commands are indented with spaces or underscores; only objects
+        // start at column zero
+        if raw_line.starts_with([' ', '_']) {
+            continue;
+        }
+
+        let line = substitute_variables(line, &variables);
+        let fields = split_fields(&line);
+        let Some(kind) = fields.first() else {
+            continue;
+        };
+
+        let path = match kind.as_str() {
+            "Sprite" | "4" | "Animation" | "6" | "Sample" | "5" => fields.get(3),
+            _ => None,
+        };
+        let Some(path) = path.map(|p| normalize_path(p)).filter(|p| !p.is_empty()) else {
+            continue;
+        };
+
+        if matches!(kind.as_str(), "Animation" | "6") {
+            let frame_count = fields
+                .get(6)
+                .and_then(|c| c.trim().parse::<u32>().ok())
+                .unwrap_or(1);
+            for frame in 0..frame_count {
+                push_unique(&mut references, animation_frame(&path, frame));
+            }
+        } else {
+            push_unique(&mut references, path);
+        }
+    }
+
+    references
+}
+
+/// Parse `$name=value` definitions from the `[Variables]` section
+fn parse_variables(content: &str) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    let mut in_variables = false;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            in_variables = line.eq_ignore_ascii_case("[Variables]");
+            continue;
+        }
+        if !in_variables {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=') {
+            if name.starts_with('$') {
+                variables.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    variables
+}
+
+/// Replace variable names with their values, longest names first so that
+/// `$ab` is not clobbered by `$a`
+fn substitute_variables(line: &str, variables: &HashMap<String, String>) -> String {
+    if variables.is_empty() || !line.contains('$') {
+        return line.to_string();
+    }
+    let mut names: Vec<_> = variables.keys().collect();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+    let mut line = line.to_string();
+    for name in names {
+        line = line.replace(name.as_str(), &variables[name]);
+    }
+    line
+}
+
+/// Split a command line on commas, keeping quoted fields intact
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+/// Normalize a referenced path to forward slashes without leading `./`
+fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+/// Build the file name of a single animation frame
+fn animation_frame(path: &str, frame: u32) -> String {
+    let file_start = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match path[file_start..].rfind('.') {
+        Some(dot) => {
+            let dot = file_start + dot;
+            format!("{}{}{}", &path[..dot], frame, &path[dot..])
+        }
+        None => format!("{}{}", path, frame),
+    }
+}
+
+fn push_unique(references: &mut Vec<String>, path: String) {
+    if !references
+        .iter()
+        .any(|existing| existing.eq_ignore_ascii_case(&path))
+    {
+        references.push(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_storyboard_references() {
+        let osb = r#"[Events]
+//Background and Video events
+//Storyboard Layer 0 (Background)
+Sprite,Background,Centre,"sb\bg.jpg",320,240
+ F,0,0,1000,0,1
+Animation,Foreground,Centre,"sb/star.png",320,240,3,100,LoopForever
+$spr,Pass,Centre,"sb/$name.png",0,0
+Sample,500,0,"sfx/whoosh.wav",70
+Sprite,Overlay,Centre,"sb/bg.jpg",0,0
+[Variables]
+$spr=Sprite
+$name=glow
+"#;
+        let refs = parse_storyboard_references(osb);
+        assert_eq!(
+            refs,
+            vec![
+                "sb/bg.jpg",
+                "sb/star0.png",
+                "sb/star1.png",
+                "sb/star2.png",
+                "sb/glow.png",
+                "sfx/whoosh.wav",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_references_outside_events_ignored() {
+        let osu = "[General]\nAudioFilename: audio.mp3\n\n[Events]\n0,0,\"bg.jpg\",0,0\nSprite,Foreground,Centre,\"sb/x.png\",0,0\n\n[HitObjects]\n256,192,1000,1,0\n";
+        assert_eq!(parse_storyboard_references(osu), vec!["sb/x.png"]);
+    }
+}
@@ -4,17 +4,23 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::beatmap::GameMode;
-use crate::error::Result;
-use crate::utils::sanitize_filename;
+use crate::cancel::CancellationToken;
+use crate::error::{Error, Result};
+use crate::stable::BeatmapIndex;
+use crate::utils::SanitizeOptions;
 
 use super::filter::ReplayFilter;
 use super::model::{
     ExportOrganization, ReplayExportResult, ReplayExportStats, ReplayInfo, ReplayProgress,
     ReplayProgressCallback,
 };
+use super::reader::check_osr_data;
+
+/// Folder used by [`ExportOrganization::ByBeatmap`] for replays whose beatmap is unknown
+pub const UNMATCHED_FOLDER: &str = "_unmatched";
 
 /// Exporter for replay files
-pub struct ReplayExporter {
+pub struct ReplayExporter<'a> {
     /// Output directory
     output_path: PathBuf,
     /// How to organize exported replays
@@ -25,9 +31,17 @@ pub struct ReplayExporter {
     filter: Option<ReplayFilter>,
     /// Optional rename pattern for output files
     rename_pattern: Option<String>,
+    /// Optional beatmap index for resolving replay beatmap hashes
+    beatmap_index: Option<&'a BeatmapIndex>,
+    /// Optional cancellation token, checked before each replay
+    cancellation: Option<CancellationToken>,
+    /// Rules for folder and file names
+    sanitize: SanitizeOptions,
+    /// Decompress replay data before copying, see [`validate_osr_deep`](super::validate_osr_deep)
+    deep_validation: bool,
 }
 
-impl ReplayExporter {
+impl<'a> ReplayExporter<'a> {
     /// Create a new replay exporter
     pub fn new(output_path: impl AsRef<Path>) -> Self {
         Self {
@@ -36,6 +50,10 @@ impl ReplayExporter {
             progress_callback: None,
             filter: None,
             rename_pattern: None,
+            beatmap_index: None,
+            cancellation: None,
+            sanitize: SanitizeOptions::default(),
+            deep_validation: false,
         }
     }
 
@@ -51,6 +69,12 @@ impl ReplayExporter {
         self
     }
 
+    /// Set a cancellation token; a cancelled export returns [`crate::Error::Cancelled`]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
     /// Set filter to apply before export
     pub fn with_filter(mut self, filter: ReplayFilter) -> Self {
         self.filter = Some(filter);
@@ -69,11 +93,41 @@ impl ReplayExporter {
     /// - {score} - Score achieved
     /// - {mode} - Game mode (osu, taiko, catch, mania)
     /// - {hash} - Beatmap hash (first 8 chars)
+    /// - {mods} - Enabled mods (e.g. HDDT, or NoMod)
+    /// - {accuracy} - Accuracy with two decimals (e.g. 98.52)
+    ///
+    /// Placeholders without a value render as empty strings, and the
+    /// resulting filename is always sanitized.
     pub fn with_rename_pattern(mut self, pattern: impl Into<String>) -> Self {
         self.rename_pattern = Some(pattern.into());
         self
     }
 
+    /// Set a beatmap index used to resolve each replay's beatmap MD5
+    ///
+    /// Resolved beatmaps provide the artist, title and difficulty used for
+    /// folder and file names. Replays whose beatmap is not in the index are
+    /// still exported, but counted as unmatched.
+    pub fn with_beatmap_index(mut self, index: &'a BeatmapIndex) -> Self {
+        self.beatmap_index = Some(index);
+        self
+    }
+
+    /// Set the rules used to sanitize folder and file names
+    pub fn with_sanitize_options(mut self, options: SanitizeOptions) -> Self {
+        self.sanitize = options;
+        self
+    }
+
+    /// Also check that each replay's compressed frame data decompresses
+    ///
+    /// Off by default since it decodes every replay; replays that fail are
+    /// skipped and listed in [`ReplayExportResult::corrupt`].
+    pub fn with_deep_validation(mut self, enabled: bool) -> Self {
+        self.deep_validation = enabled;
+        self
+    }
+
     /// Export replays
     pub fn export(&self, replays: &[ReplayInfo]) -> Result<ReplayExportResult> {
         // Apply filter if set
@@ -93,6 +147,10 @@ impl ReplayExporter {
         let total = filtered_replays.len();
 
         for (i, replay) in filtered_replays.iter().enumerate() {
+            if let Some(token) = &self.cancellation {
+                token.check()?;
+            }
+
             // Report progress
             if let Some(ref callback) = self.progress_callback {
                 let display_name = replay
@@ -126,6 +184,28 @@ impl ReplayExporter {
                 continue;
             }
 
+            // Don't copy truncated or otherwise unreadable replays; the file
+            // is read once, for both the check and the copy
+            let validation = fs::read(&source_path)
+                .map_err(|e| Error::io_at(e, &source_path))
+                .and_then(|data| {
+                    check_osr_data(&source_path, &data, self.deep_validation).map(|_| data)
+                });
+            let data = match validation {
+                Ok(data) => data,
+                Err(e) => {
+                    tracing::warn!("Skipping corrupt replay: {}", e);
+                    result
+                        .corrupt
+                        .push(source_path.to_string_lossy().to_string());
+                    continue;
+                }
+            };
+
+            // Resolve beatmap metadata before naming the output
+            let (replay, matched) = self.resolve_beatmap(replay);
+            let replay = &replay;
+
             // Determine output path based on organization
             let dest_path = self.get_output_path(replay)?;
 
@@ -135,10 +215,13 @@ impl ReplayExporter {
             }
 
             // Copy the replay file
-            match fs::copy(&source_path, &dest_path) {
-                Ok(bytes) => {
+            match fs::write(&dest_path, &data) {
+                Ok(()) => {
                     result.replays_exported += 1;
-                    result.bytes_written += bytes;
+                    result.bytes_written += data.len() as u64;
+                    if !matched {
+                        result.replays_unmatched += 1;
+                    }
                 }
                 Err(e) => {
                     result.errors.push((
@@ -165,6 +248,33 @@ impl ReplayExporter {
         Ok(result)
     }
 
+    /// Fill in beatmap metadata from the index, returning whether the beatmap is known
+    ///
+    /// Without an index, a replay counts as matched if it already carries a title.
+    fn resolve_beatmap(&self, replay: &ReplayInfo) -> (ReplayInfo, bool) {
+        let mut resolved = replay.clone();
+
+        let Some(index) = self.beatmap_index else {
+            let matched = replay.beatmap_title.is_some();
+            return (resolved, matched);
+        };
+
+        match index.get_by_md5(&replay.beatmap_hash) {
+            Some((_, beatmap)) => {
+                resolved.beatmap_title = Some(beatmap.metadata.title.clone());
+                resolved.beatmap_artist = Some(beatmap.metadata.artist.clone());
+                resolved.beatmap_version = Some(beatmap.version.clone());
+                (resolved, true)
+            }
+            None => {
+                resolved.beatmap_title = None;
+                resolved.beatmap_artist = None;
+                resolved.beatmap_version = None;
+                (resolved, false)
+            }
+        }
+    }
+
     /// Get the output path for a replay based on organization settings
     fn get_output_path(&self, replay: &ReplayInfo) -> Result<PathBuf> {
         let filename = self.generate_filename(replay);
@@ -173,11 +283,13 @@ impl ReplayExporter {
             ExportOrganization::Flat => self.output_path.join(&filename),
 
             ExportOrganization::ByBeatmap => {
-                let beatmap_folder = replay
-                    .beatmap_title
-                    .as_ref()
-                    .map(|t| sanitize_filename(t))
-                    .unwrap_or_else(|| replay.beatmap_hash.clone());
+                let beatmap_folder = match (&replay.beatmap_artist, &replay.beatmap_title) {
+                    (Some(artist), Some(title)) => {
+                        self.sanitize.sanitize(&format!("{} - {}", artist, title))
+                    }
+                    (None, Some(title)) => self.sanitize.sanitize(title),
+                    _ => UNMATCHED_FOLDER.to_string(),
+                };
                 self.output_path.join(beatmap_folder).join(&filename)
             }
 
@@ -187,7 +299,7 @@ impl ReplayExporter {
             }
 
             ExportOrganization::ByPlayer => {
-                let player = sanitize_filename(&replay.player_name);
+                let player = self.sanitize.sanitize(&replay.player_name);
                 self.output_path.join(player).join(&filename)
             }
 
@@ -210,13 +322,13 @@ impl ReplayExporter {
         // Default naming
         let base_name = if let Some(ref title) = replay.beatmap_title {
             let artist = replay.beatmap_artist.as_deref().unwrap_or("Unknown");
-            format!(
+            self.sanitize.sanitize(&format!(
                 "{} - {} [{}] ({}).osr",
-                sanitize_filename(artist),
-                sanitize_filename(title),
+                artist,
+                title,
                 replay.grade.as_str(),
                 replay.score
-            )
+            ))
         } else {
             // Fallback to hash-based naming
             format!(
@@ -247,24 +359,34 @@ impl ReplayExporter {
         } else {
             &replay.beatmap_hash
         };
+        // `accuracy` comes from calculate_accuracy, which also counts gekis
+        // and katus (e.g. mania MAX judgements) and is 0 without any hits
+        let accuracy = if replay.accuracy > 0.0 {
+            format!("{:.2}", replay.accuracy)
+        } else {
+            String::new()
+        };
 
         let mut result = pattern.to_string();
-        result = result.replace("{artist}", &sanitize_filename(artist));
-        result = result.replace("{title}", &sanitize_filename(title));
-        result = result.replace("{diff}", &sanitize_filename(diff));
+        result = result.replace("{artist}", &self.sanitize.sanitize(artist));
+        result = result.replace("{title}", &self.sanitize.sanitize(title));
+        result = result.replace("{diff}", &self.sanitize.sanitize(diff));
         result = result.replace("{grade}", replay.grade.as_str());
         result = result.replace("{date}", &date);
-        result = result.replace("{player}", &sanitize_filename(&replay.player_name));
+        result = result.replace("{player}", &self.sanitize.sanitize(&replay.player_name));
         result = result.replace("{score}", &replay.score.to_string());
         result = result.replace("{mode}", mode);
         result = result.replace("{hash}", hash_short);
+        result = result.replace("{mods}", &replay.mods_string());
+        result = result.replace("{accuracy}", &accuracy);
 
-        // Ensure .osr extension
+        // Ensure .osr extension before sanitizing, so truncation keeps it
+        let mut result = result.trim().to_string();
         if !result.ends_with(".osr") {
             result.push_str(".osr");
         }
 
-        result
+        self.sanitize.sanitize(&result)
     }
 }
 
@@ -308,6 +430,8 @@ fn days_to_ymd(days: u64) -> (u32, u32, u32) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::replay::model::calculate_accuracy;
+    use crate::replay::reader::sample_osr;
     use crate::replay::Grade;
 
     #[test]
@@ -357,6 +481,8 @@ mod tests {
             timestamp: 1704024000,
             mode: GameMode::Osu,
             grade,
+            accuracy: 93.33,
+            mods: Vec::new(),
             has_replay_file: true,
             replay_path: Some("/path/to/replay.osr".to_string()),
             beatmap_title: title.map(String::from),
@@ -425,6 +551,55 @@ mod tests {
         assert!(filename.contains("12345"));
     }
 
+    #[test]
+    fn test_rename_pattern_mods_and_accuracy() {
+        let exporter =
+            ReplayExporter::new("/output").with_rename_pattern("{title} +{mods} {accuracy}%");
+        let mut replay = make_test_replay(Some("Test Song"), Some("Test Artist"), Grade::S, 100);
+
+        let filename = exporter.generate_filename(&replay);
+        assert_eq!(filename, "Test Song +NoMod 93.33%.osr");
+
+        replay.mods = vec!["HD".to_string(), "DT".to_string()];
+        let filename = exporter.generate_filename(&replay);
+        assert_eq!(filename, "Test Song +HDDT 93.33%.osr");
+    }
+
+    #[test]
+    fn test_rename_pattern_unresolved_accuracy_is_empty() {
+        let exporter = ReplayExporter::new("/output").with_rename_pattern("{title}_{accuracy}");
+        let mut replay = make_test_replay(Some("Song"), Some("Artist"), Grade::D, 0);
+        replay.count_300 = 0;
+        replay.count_100 = 0;
+        replay.accuracy = 0.0;
+
+        let filename = exporter.generate_filename(&replay);
+        assert_eq!(filename, "Song_.osr");
+    }
+
+    #[test]
+    fn test_rename_pattern_accuracy_of_geki_only_play() {
+        // A mania play judged entirely as MAX only counts gekis
+        let exporter = ReplayExporter::new("/output").with_rename_pattern("{title}_{accuracy}");
+        let mut replay = make_test_replay(Some("Song"), Some("Artist"), Grade::SS, 0);
+        replay.mode = GameMode::Mania;
+        replay.count_300 = 0;
+        replay.count_100 = 0;
+        replay.accuracy = calculate_accuracy(GameMode::Mania, 0, 0, 0, 500, 0, 0);
+
+        let filename = exporter.generate_filename(&replay);
+        assert_eq!(filename, "Song_100.00.osr");
+    }
+
+    #[test]
+    fn test_rename_pattern_result_is_sanitized() {
+        let exporter = ReplayExporter::new("/output").with_rename_pattern("{player}: {title}?");
+        let replay = make_test_replay(Some("Song"), Some("Artist"), Grade::A, 100);
+
+        let filename = exporter.generate_filename(&replay);
+        assert_eq!(filename, "TestPlayer_ Song_.osr");
+    }
+
     #[test]
     fn test_get_output_path_flat() {
         let exporter = ReplayExporter::new("/output").with_organization(ExportOrganization::Flat);
@@ -466,6 +641,104 @@ mod tests {
         assert!(path.to_string_lossy().contains("TestSong"));
     }
 
+    #[test]
+    fn test_get_output_path_by_beatmap_unmatched() {
+        let exporter =
+            ReplayExporter::new("/output").with_organization(ExportOrganization::ByBeatmap);
+        let replay = make_test_replay(None, None, Grade::A, 100);
+
+        let path = exporter.get_output_path(&replay).unwrap();
+        assert!(path.starts_with(Path::new("/output").join(UNMATCHED_FOLDER)));
+    }
+
+    fn make_index(md5: &str, artist: &str, title: &str) -> BeatmapIndex {
+        use crate::beatmap::{BeatmapInfo, BeatmapSet};
+
+        let mut beatmap = BeatmapInfo {
+            md5_hash: md5.to_string(),
+            version: "Insane".to_string(),
+            ..Default::default()
+        };
+        beatmap.metadata.artist = artist.to_string();
+        beatmap.metadata.title = title.to_string();
+
+        let mut set = BeatmapSet::new();
+        set.beatmaps.push(beatmap);
+        BeatmapIndex::new(vec![set])
+    }
+
+    #[test]
+    fn test_resolve_beatmap_from_index() {
+        let index = make_index("abc123", "Indexed Artist", "Indexed Title");
+        let exporter = ReplayExporter::new("/output")
+            .with_organization(ExportOrganization::ByBeatmap)
+            .with_beatmap_index(&index);
+        let replay = make_test_replay(None, None, Grade::A, 100);
+
+        let (resolved, matched) = exporter.resolve_beatmap(&replay);
+        assert!(matched);
+        assert_eq!(resolved.beatmap_title.as_deref(), Some("Indexed Title"));
+        assert_eq!(resolved.beatmap_version.as_deref(), Some("Insane"));
+
+        let path = exporter.get_output_path(&resolved).unwrap();
+        assert!(path
+            .to_string_lossy()
+            .contains("Indexed Artist - Indexed Title"));
+    }
+
+    #[test]
+    fn test_export_counts_unmatched_replays() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source_dir = tempfile::tempdir().unwrap();
+        let index = make_index("abc123", "Artist", "Known");
+
+        let known_path = source_dir.path().join("known.osr");
+        let unknown_path = source_dir.path().join("unknown.osr");
+        fs::write(&known_path, sample_osr(16)).unwrap();
+        fs::write(&unknown_path, sample_osr(32)).unwrap();
+
+        let mut known = make_test_replay(None, None, Grade::A, 100);
+        known.replay_path = Some(known_path.to_string_lossy().to_string());
+        let mut unknown = make_test_replay(Some("Stale"), None, Grade::A, 200);
+        unknown.beatmap_hash = "def456".to_string();
+        unknown.replay_path = Some(unknown_path.to_string_lossy().to_string());
+
+        let exporter = ReplayExporter::new(temp_dir.path())
+            .with_organization(ExportOrganization::ByBeatmap)
+            .with_beatmap_index(&index);
+        let result = exporter.export(&[known, unknown]).unwrap();
+
+        assert_eq!(result.replays_exported, 2);
+        assert_eq!(result.replays_unmatched, 1);
+        assert_eq!(result.stats.unwrap().total_unmatched, 1);
+        assert!(temp_dir.path().join("Artist - Known").is_dir());
+        assert!(temp_dir.path().join(UNMATCHED_FOLDER).is_dir());
+    }
+
+    #[test]
+    fn test_export_skips_corrupt_replays() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source_dir = tempfile::tempdir().unwrap();
+
+        let good_path = source_dir.path().join("good.osr");
+        let bad_path = source_dir.path().join("bad.osr");
+        fs::write(&good_path, sample_osr(16)).unwrap();
+        let truncated = sample_osr(64);
+        fs::write(&bad_path, &truncated[..truncated.len() / 2]).unwrap();
+
+        let mut good = make_test_replay(Some("Song"), None, Grade::A, 100);
+        good.replay_path = Some(good_path.to_string_lossy().to_string());
+        let mut bad = make_test_replay(Some("Song"), None, Grade::B, 200);
+        bad.replay_path = Some(bad_path.to_string_lossy().to_string());
+
+        let exporter = ReplayExporter::new(temp_dir.path());
+        let result = exporter.export(&[good, bad]).unwrap();
+
+        assert_eq!(result.replays_exported, 1);
+        assert_eq!(result.replays_skipped, 0);
+        assert_eq!(result.corrupt, vec![bad_path.to_string_lossy().to_string()]);
+    }
+
     #[test]
     fn test_export_empty_replays() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -504,6 +777,20 @@ mod tests {
         assert_eq!(result.replays_skipped, 1);
     }
 
+    #[test]
+    fn test_cancelled_export_is_cancelled() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let token = CancellationToken::new();
+        token.cancel();
+        let exporter = ReplayExporter::new(temp_dir.path()).with_cancellation(token);
+
+        let replay = make_test_replay(Some("Song"), Some("Artist"), Grade::A, 100);
+        assert!(matches!(
+            exporter.export(&[replay]),
+            Err(crate::Error::Cancelled)
+        ));
+    }
+
     #[test]
     fn test_export_with_filter() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -264,6 +264,8 @@ mod tests {
             timestamp,
             mode,
             grade,
+            accuracy: 93.33,
+            mods: Vec::new(),
             has_replay_file: true,
             replay_path: Some("/path/to/replay.osr".to_string()),
             beatmap_title: title.map(String::from),
@@ -6,10 +6,12 @@ mod model;
 mod reader;
 
 pub use crate::utils::sanitize_filename;
-pub use exporter::ReplayExporter;
+pub use exporter::{ReplayExporter, UNMATCHED_FOLDER};
 pub use filter::ReplayFilter;
 pub use model::{
-    ExportOrganization, Grade, ReplayExportResult, ReplayExportStats, ReplayInfo, ReplayProgress,
-    ReplayProgressCallback,
+    calculate_accuracy, decode_mods, ExportOrganization, Grade, ReplayExportResult,
+    ReplayExportStats, ReplayInfo, ReplayProgress, ReplayProgressCallback,
+};
+pub use reader::{
+    read_osr_header, validate_osr, validate_osr_deep, OsrHeader, ReplayStats, StableReplayReader,
 };
-pub use reader::{ReplayStats, StableReplayReader};
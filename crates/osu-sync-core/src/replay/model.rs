@@ -32,6 +32,12 @@ pub struct ReplayInfo {
     pub mode: GameMode,
     /// Grade achieved
     pub grade: Grade,
+    /// Accuracy as a percentage (0.0 to 100.0)
+    #[serde(default)]
+    pub accuracy: f64,
+    /// Enabled mods as short names (e.g. "HD", "DT")
+    #[serde(default)]
+    pub mods: Vec<String>,
     /// Whether the .osr file exists
     pub has_replay_file: bool,
     /// Path to the .osr file (if available)
@@ -44,6 +50,120 @@ pub struct ReplayInfo {
     pub beatmap_version: Option<String>,
 }
 
+impl ReplayInfo {
+    /// Check if the replay was played with the given mod (short name, case-insensitive)
+    pub fn has_mod(&self, name: &str) -> bool {
+        self.mods.iter().any(|m| m.eq_ignore_ascii_case(name))
+    }
+
+    /// Get the mods as a compact string (e.g. "HDDT"), or "NoMod" if none
+    pub fn mods_string(&self) -> String {
+        if self.mods.is_empty() {
+            "NoMod".to_string()
+        } else {
+            self.mods.concat()
+        }
+    }
+}
+
+/// Short names for each bit of the osu!stable mods bitmask
+const MOD_NAMES: [(u32, &str); 31] = [
+    (1 << 0, "NF"),
+    (1 << 1, "EZ"),
+    (1 << 2, "TD"),
+    (1 << 3, "HD"),
+    (1 << 4, "HR"),
+    (1 << 5, "SD"),
+    (1 << 6, "DT"),
+    (1 << 7, "RX"),
+    (1 << 8, "HT"),
+    (1 << 9, "NC"),
+    (1 << 10, "FL"),
+    (1 << 11, "AT"),
+    (1 << 12, "SO"),
+    (1 << 13, "AP"),
+    (1 << 14, "PF"),
+    (1 << 15, "4K"),
+    (1 << 16, "5K"),
+    (1 << 17, "6K"),
+    (1 << 18, "7K"),
+    (1 << 19, "8K"),
+    (1 << 20, "FI"),
+    (1 << 21, "RD"),
+    (1 << 22, "CN"),
+    (1 << 23, "TP"),
+    (1 << 24, "9K"),
+    (1 << 25, "CO"),
+    (1 << 26, "1K"),
+    (1 << 27, "3K"),
+    (1 << 28, "2K"),
+    (1 << 29, "V2"),
+    (1 << 30, "MR"),
+];
+
+/// Decode an osu!stable mods bitmask into short mod names
+///
+/// Implied mods are collapsed: NC hides DT and PF hides SD, matching how
+/// the game displays them. Unknown bits are rendered as `Mod{n}`.
+pub fn decode_mods(bits: u32) -> Vec<String> {
+    let mut mods = Vec::new();
+
+    for (bit, name) in MOD_NAMES {
+        if bits & bit == 0 {
+            continue;
+        }
+        if name == "DT" && bits & (1 << 9) != 0 {
+            continue;
+        }
+        if name == "SD" && bits & (1 << 14) != 0 {
+            continue;
+        }
+        mods.push(name.to_string());
+    }
+
+    if bits & (1 << 31) != 0 {
+        mods.push("Mod31".to_string());
+    }
+
+    mods
+}
+
+/// Calculate accuracy (0.0 to 100.0) from hit counts, as shown by osu!stable
+///
+/// Each mode weighs judgements differently: taiko has no 50s, catch counts
+/// missed droplets as katus, and mania scores gekis (MAX) as 300s and katus
+/// as 200s.
+pub fn calculate_accuracy(
+    mode: GameMode,
+    count_300: u32,
+    count_100: u32,
+    count_50: u32,
+    count_geki: u32,
+    count_katu: u32,
+    count_miss: u32,
+) -> f64 {
+    let [n300, n100, n50, geki, katu, miss] = [
+        count_300, count_100, count_50, count_geki, count_katu, count_miss,
+    ]
+    .map(f64::from);
+    let (points, max_points) = match mode {
+        GameMode::Osu => (
+            n300 * 300.0 + n100 * 100.0 + n50 * 50.0,
+            (n300 + n100 + n50 + miss) * 300.0,
+        ),
+        GameMode::Taiko => (n300 + n100 * 0.5, n300 + n100 + miss),
+        GameMode::Catch => (n300 + n100 + n50, n300 + n100 + n50 + katu + miss),
+        GameMode::Mania => (
+            (geki + n300) * 300.0 + katu * 200.0 + n100 * 100.0 + n50 * 50.0,
+            (geki + n300 + katu + n100 + n50 + miss) * 300.0,
+        ),
+    };
+    if max_points == 0.0 {
+        return 0.0;
+    }
+    points / max_points * 100.0
+}
+
 /// Grade/rank achieved on a play
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Grade {
@@ -170,6 +290,10 @@ pub struct ReplayExportResult {
     pub replays_skipped: usize,
     /// Number of replays filtered out
     pub replays_filtered: usize,
+    /// Number of exported replays whose beatmap could not be resolved
+    pub replays_unmatched: usize,
+    /// Paths of .osr files skipped because they failed validation
+    pub corrupt: Vec<String>,
     /// Total bytes written
     pub bytes_written: u64,
     /// Errors encountered
@@ -199,6 +323,8 @@ pub struct ReplayExportStats {
     pub total_skipped: usize,
     /// Total replays filtered out
     pub total_filtered: usize,
+    /// Total exported replays whose beatmap could not be resolved
+    pub total_unmatched: usize,
     /// Breakdown by grade (using simplified grade as key)
     pub by_grade: HashMap<String, usize>,
     /// Breakdown by game mode
@@ -223,6 +349,7 @@ impl ReplayExportStats {
             total_exported: result.replays_exported,
             total_skipped: result.replays_skipped,
             total_filtered: result.replays_filtered,
+            total_unmatched: result.replays_unmatched,
             total_bytes: result.bytes_written,
             ..Default::default()
         };
@@ -445,6 +572,34 @@ mod tests {
         assert!(result.has_errors());
     }
 
+    #[test]
+    fn test_decode_mods() {
+        assert!(decode_mods(0).is_empty());
+        assert_eq!(decode_mods(8 | 64), vec!["HD", "DT"]);
+        assert_eq!(decode_mods(16 | 1024), vec!["HR", "FL"]);
+        // NC implies DT, PF implies SD
+        assert_eq!(decode_mods(64 | 512), vec!["NC"]);
+        assert_eq!(decode_mods(32 | 16384), vec!["PF"]);
+        assert_eq!(decode_mods(1 << 31), vec!["Mod31"]);
+    }
+
+    #[test]
+    fn test_calculate_accuracy() {
+        let accuracy = |mode, counts: [u32; 6]| {
+            let [n300, n100, n50, geki, katu, miss] = counts;
+            calculate_accuracy(mode, n300, n100, n50, geki, katu, miss)
+        };
+        assert!((accuracy(GameMode::Osu, [100, 0, 0, 0, 0, 0]) - 100.0).abs() < 0.001);
+        assert!((accuracy(GameMode::Osu, [0, 0, 0, 0, 0, 0]) - 0.0).abs() < 0.001);
+        assert!((accuracy(GameMode::Osu, [90, 10, 0, 0, 0, 0]) - 93.333).abs() < 0.01);
+        // A taiko GOOD is worth half a GREAT
+        assert!((accuracy(GameMode::Taiko, [90, 10, 0, 0, 0, 0]) - 95.0).abs() < 0.001);
+        // Catch: every caught fruit, drop and droplet counts fully
+        assert!((accuracy(GameMode::Catch, [80, 10, 5, 0, 5, 0]) - 95.0).abs() < 0.001);
+        // Mania: MAX and 300 are both 300 points, 200s are katus
+        assert!((accuracy(GameMode::Mania, [50, 0, 0, 40, 10, 0]) - 96.667).abs() < 0.01);
+    }
+
     #[test]
     fn test_export_organization_default() {
         assert_eq!(ExportOrganization::default(), ExportOrganization::Flat);
@@ -1,13 +1,15 @@
 //! Stable replay reader for reading scores.db and finding .osr files
 
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
 
 use crate::beatmap::GameMode;
 use crate::error::{Error, Result};
 use crate::lazer::StableDatabase;
 
-use super::model::{Grade, ReplayInfo};
+use super::model::{calculate_accuracy, decode_mods, Grade, ReplayInfo};
 
 /// Reader for osu!stable replay data
 pub struct StableReplayReader {
@@ -54,6 +56,19 @@ impl StableReplayReader {
 
     /// Read all replays from scores.db
     pub fn read_replays(&self) -> Result<Vec<ReplayInfo>> {
+        Ok(self
+            .scan_replays(false)?
+            .into_iter()
+            .map(|(replay, _)| replay)
+            .collect())
+    }
+
+    /// Read all replays from scores.db, reading each .osr file once
+    ///
+    /// With `validate`, each file is checked with [`validate_osr`] rather
+    /// than only having its header read, and the flag beside each replay
+    /// says whether it passed. Replays without a file always pass.
+    fn scan_replays(&self, validate: bool) -> Result<Vec<(ReplayInfo, bool)>> {
         let scores_path = self.osu_path.join("scores.db");
         if !scores_path.exists() {
             return Err(Error::OsuNotFound(scores_path));
@@ -122,19 +137,42 @@ impl StableReplayReader {
                     osu_db::Mode::Mania => GameMode::Mania,
                 };
 
+                // Prefer hit counts and mods from the .osr header when the file exists
+                let header = replay_path.as_ref().map(|p| {
+                    if validate {
+                        validate_osr(Path::new(p))
+                    } else {
+                        read_osr_header(Path::new(p))
+                    }
+                });
+                let valid = !matches!(header, Some(Err(_)));
+                let (count_300, count_100, count_50, count_geki, count_katu, count_miss, mods_bits) =
+                    match header.and_then(Result::ok) {
+                        Some(h) => (
+                            h.count_300 as u32,
+                            h.count_100 as u32,
+                            h.count_50 as u32,
+                            h.count_geki as u32,
+                            h.count_katu as u32,
+                            h.count_miss as u32,
+                            h.mods,
+                        ),
+                        None => (
+                            score.count_300 as u32,
+                            score.count_100 as u32,
+                            score.count_50 as u32,
+                            score.count_geki as u32,
+                            score.count_katsu as u32,
+                            score.count_miss as u32,
+                            score.mods.bits(),
+                        ),
+                    };
+
                 // Calculate grade from accuracy (Replay struct doesn't have grade field)
-                let total_hits =
-                    score.count_300 + score.count_100 + score.count_50 + score.count_miss;
-                let accuracy = if total_hits > 0 {
-                    (score.count_300 as f32 * 300.0
-                        + score.count_100 as f32 * 100.0
-                        + score.count_50 as f32 * 50.0)
-                        / (total_hits as f32 * 300.0)
-                        * 100.0
-                } else {
-                    0.0
-                };
-                let grade = if score.count_miss == 0 && accuracy >= 100.0 {
+                let accuracy = calculate_accuracy(
+                    mode, count_300, count_100, count_50, count_geki, count_katu, count_miss,
+                );
+                let grade = if count_miss == 0 && accuracy >= 100.0 {
                     Grade::SS
                 } else if accuracy >= 93.0 {
                     Grade::S
@@ -157,13 +195,15 @@ impl StableReplayReader {
                     replay_hash,
                     score: score.score as u64,
                     max_combo: score.max_combo as u32,
-                    count_300: score.count_300 as u32,
-                    count_100: score.count_100 as u32,
-                    count_50: score.count_50 as u32,
-                    count_miss: score.count_miss as u32,
+                    count_300,
+                    count_100,
+                    count_50,
+                    count_miss,
                     timestamp,
                     mode,
                     grade,
+                    accuracy,
+                    mods: decode_mods(mods_bits),
                     has_replay_file,
                     replay_path,
                     beatmap_title: if beatmap_title.is_empty() {
@@ -179,7 +219,7 @@ impl StableReplayReader {
                     beatmap_version: None, // Not available from scores.db
                 };
 
-                replays.push(replay_info);
+                replays.push((replay_info, valid));
             }
         }
 
@@ -194,16 +234,216 @@ impl StableReplayReader {
 
     /// Get replay count statistics
     pub fn get_stats(&self) -> Result<ReplayStats> {
-        let replays = self.read_replays()?;
+        let replays = self.scan_replays(true)?;
         let total = replays.len();
-        let with_files = replays.iter().filter(|r| r.has_replay_file).count();
+        let with_files = replays.iter().filter(|(r, _)| r.has_replay_file).count();
+        let corrupt = replays.iter().filter(|(_, valid)| !valid).count();
 
         Ok(ReplayStats {
             total_scores: total,
             with_replay_files: with_files,
             without_replay_files: total - with_files,
+            corrupt_count: corrupt,
         })
     }
+
+    /// Check that an .osr file is a complete replay, see [`validate_osr`]
+    pub fn validate_replay(path: &Path) -> Result<OsrHeader> {
+        validate_osr(path)
+    }
+}
+
+/// Fields read from the header of an .osr replay file
+#[derive(Debug, Clone, Default)]
+pub struct OsrHeader {
+    /// Game mode (0 = osu!, 1 = taiko, 2 = catch, 3 = mania)
+    pub mode: u8,
+    /// Game version the replay was recorded with
+    pub version: i32,
+    /// MD5 hash of the beatmap
+    pub beatmap_hash: String,
+    /// Player name
+    pub player_name: String,
+    /// MD5 hash of the replay
+    pub replay_hash: String,
+    /// Number of 300s
+    pub count_300: u16,
+    /// Number of 100s
+    pub count_100: u16,
+    /// Number of 50s
+    pub count_50: u16,
+    /// Number of gekis (max 300s in mania)
+    pub count_geki: u16,
+    /// Number of katus (200s in mania)
+    pub count_katu: u16,
+    /// Number of misses
+    pub count_miss: u16,
+    /// Total score
+    pub score: u32,
+    /// Max combo
+    pub max_combo: u16,
+    /// Whether the play was a full combo
+    pub perfect: bool,
+    /// Mods bitmask
+    pub mods: u32,
+}
+
+/// Read the header of an .osr file without decoding the replay data
+pub fn read_osr_header(path: &Path) -> Result<OsrHeader> {
+    read_header(&mut BufReader::new(File::open(path)?))
+}
+
+/// Replay versions [`validate_osr`] accepts: osu!stable writes its build
+/// date (yyyymmdd), osu!lazer exports count up from 30000000
+const REPLAY_VERSIONS: std::ops::Range<i32> = 20_000_000..40_000_000;
+
+/// Check that an .osr file holds a complete replay
+///
+/// Besides parsing the header, this checks the game mode, that the version
+/// looks like an osu!stable build date or an osu!lazer export version, that
+/// the beatmap hash and player name are present, and that the file contains
+/// all of the compressed replay data its header announces, which catches
+/// truncated files.
+pub fn validate_osr(path: &Path) -> Result<OsrHeader> {
+    check_osr(path, false)
+}
+
+/// Like [`validate_osr`], but also decompress the replay frame data
+///
+/// Catches corruption inside the LZMA stream that leaves the header and
+/// lengths intact. The decompressed frames are discarded, but this is much
+/// slower than the header check.
+pub fn validate_osr_deep(path: &Path) -> Result<OsrHeader> {
+    check_osr(path, true)
+}
+
+fn check_osr(path: &Path, decompress: bool) -> Result<OsrHeader> {
+    let data = std::fs::read(path).map_err(|e| Error::io_at(e, path))?;
+    check_osr_data(path, &data, decompress)
+}
+
+/// [`validate_osr`] or [`validate_osr_deep`] on the already read content of `path`
+pub(super) fn check_osr_data(path: &Path, data: &[u8], decompress: bool) -> Result<OsrHeader> {
+    let invalid =
+        |reason: String| Error::Other(format!("Invalid replay {}: {}", path.display(), reason));
+
+    let mut reader = Cursor::new(data);
+    let header = read_header(&mut reader).map_err(|e| invalid(e.to_string()))?;
+    if header.mode > 3 {
+        return Err(invalid(format!("unknown game mode {}", header.mode)));
+    }
+    if !REPLAY_VERSIONS.contains(&header.version) {
+        return Err(invalid(format!("unexpected version {}", header.version)));
+    }
+    if header.beatmap_hash.len() != 32
+        || !header.beatmap_hash.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(invalid("missing beatmap hash".to_string()));
+    }
+    if header.player_name.is_empty() {
+        return Err(invalid("missing player name".to_string()));
+    }
+
+    // Life bar graph, timestamp, then the length-prefixed LZMA replay data
+    read_osr_string(&mut reader).map_err(|e| invalid(e.to_string()))?;
+    let mut timestamp = [0u8; 8];
+    reader
+        .read_exact(&mut timestamp)
+        .map_err(|e| invalid(e.to_string()))?;
+    let length = read_u32(&mut reader).map_err(|e| invalid(e.to_string()))? as i32;
+    let remaining = data.len() as u64 - reader.position();
+    if length < 0 || length as u64 > remaining {
+        return Err(invalid(format!(
+            "replay data is truncated ({} of {} bytes)",
+            remaining, length
+        )));
+    }
+
+    if decompress {
+        let start = reader.position() as usize;
+        let mut frames = &data[start..start + length as usize];
+        lzma_rs::lzma_decompress(&mut frames, &mut std::io::sink())
+            .map_err(|e| invalid(format!("replay data is corrupt ({})", e)))?;
+    }
+
+    Ok(header)
+}
+
+fn read_header(reader: &mut impl Read) -> Result<OsrHeader> {
+    Ok(OsrHeader {
+        mode: read_u8(reader)?,
+        version: read_u32(reader)? as i32,
+        beatmap_hash: read_osr_string(reader)?,
+        player_name: read_osr_string(reader)?,
+        replay_hash: read_osr_string(reader)?,
+        count_300: read_u16(reader)?,
+        count_100: read_u16(reader)?,
+        count_50: read_u16(reader)?,
+        count_geki: read_u16(reader)?,
+        count_katu: read_u16(reader)?,
+        count_miss: read_u16(reader)?,
+        score: read_u32(reader)?,
+        max_combo: read_u16(reader)?,
+        perfect: read_u8(reader)? != 0,
+        mods: read_u32(reader)?,
+    })
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> Result<u16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Read an osu! string (0x00 for empty, or 0x0b + ULEB128 length + UTF-8 bytes)
+fn read_osr_string(reader: &mut impl Read) -> Result<String> {
+    match read_u8(reader)? {
+        0x00 => Ok(String::new()),
+        0x0b => {
+            let mut len: u64 = 0;
+            let mut shift = 0;
+            loop {
+                let byte = read_u8(reader)?;
+                len |= ((byte & 0x7f) as u64) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+                if shift > 63 {
+                    return Err(Error::Other("Invalid string length in .osr".to_string()));
+                }
+            }
+            // The length comes from the file, so read at most that much
+            // instead of allocating it up front
+            let mut buf = Vec::new();
+            reader.by_ref().take(len).read_to_end(&mut buf)?;
+            if (buf.len() as u64) < len {
+                return Err(Error::Other(format!(
+                    "String in .osr is cut off: {} of {} bytes",
+                    buf.len(),
+                    len
+                )));
+            }
+            String::from_utf8(buf)
+                .map_err(|e| Error::Other(format!("Invalid UTF-8 string in .osr: {}", e)))
+        }
+        other => Err(Error::Other(format!(
+            "Invalid string marker in .osr: 0x{:02x}",
+            other
+        ))),
+    }
 }
 
 /// Statistics about available replays
@@ -215,4 +455,156 @@ pub struct ReplayStats {
     pub with_replay_files: usize,
     /// Number of scores without .osr files
     pub without_replay_files: usize,
+    /// Number of .osr files that fail [`validate_osr`]
+    pub corrupt_count: usize,
+}
+
+/// A complete replay with `data_len` bytes of replay data
+#[cfg(test)]
+pub(super) fn sample_osr(data_len: usize) -> Vec<u8> {
+    sample_osr_with_data(&vec![0xaa; data_len])
+}
+
+/// A complete replay whose replay data is `replay_data`
+#[cfg(test)]
+pub(super) fn sample_osr_with_data(replay_data: &[u8]) -> Vec<u8> {
+    let mut data = vec![0u8];
+    data.extend_from_slice(&20240101i32.to_le_bytes());
+    tests::write_osr_string(&mut data, "0123456789abcdef0123456789abcdef");
+    tests::write_osr_string(&mut data, "Player");
+    tests::write_osr_string(&mut data, "");
+    for count in [95u16, 4, 1, 10, 2, 0] {
+        data.extend_from_slice(&count.to_le_bytes());
+    }
+    data.extend_from_slice(&1234567u32.to_le_bytes());
+    data.extend_from_slice(&321u16.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(&0u32.to_le_bytes());
+    tests::write_osr_string(&mut data, "");
+    data.extend_from_slice(&0i64.to_le_bytes());
+    data.extend_from_slice(&(replay_data.len() as i32).to_le_bytes());
+    data.extend_from_slice(replay_data);
+    data.extend_from_slice(&0i64.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn write_osr_string(buf: &mut Vec<u8>, s: &str) {
+        if s.is_empty() {
+            buf.push(0x00);
+        } else {
+            buf.push(0x0b);
+            buf.push(s.len() as u8);
+            buf.extend_from_slice(s.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_read_osr_header() {
+        let mut data = Vec::new();
+        data.push(0u8);
+        data.extend_from_slice(&20240101i32.to_le_bytes());
+        write_osr_string(&mut data, "beatmaphash");
+        write_osr_string(&mut data, "Player");
+        write_osr_string(&mut data, "");
+        for count in [95u16, 4, 1, 10, 2, 0] {
+            data.extend_from_slice(&count.to_le_bytes());
+        }
+        data.extend_from_slice(&1234567u32.to_le_bytes());
+        data.extend_from_slice(&321u16.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&(8u32 | 64).to_le_bytes());
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.osr");
+        std::fs::write(&path, &data).unwrap();
+
+        let header = read_osr_header(&path).unwrap();
+        assert_eq!(header.mode, 0);
+        assert_eq!(header.beatmap_hash, "beatmaphash");
+        assert_eq!(header.player_name, "Player");
+        assert!(header.replay_hash.is_empty());
+        assert_eq!(header.count_300, 95);
+        assert_eq!(header.count_100, 4);
+        assert_eq!(header.count_50, 1);
+        assert_eq!(header.count_miss, 0);
+        assert_eq!(header.score, 1234567);
+        assert_eq!(header.max_combo, 321);
+        assert!(header.perfect);
+        assert_eq!(decode_mods(header.mods), vec!["HD", "DT"]);
+    }
+
+    #[test]
+    fn test_validate_osr() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("replay.osr");
+
+        let complete = sample_osr(64);
+        std::fs::write(&path, &complete).unwrap();
+        assert_eq!(validate_osr(&path).unwrap().player_name, "Player");
+
+        // Cut off in the middle of the replay data
+        std::fs::write(&path, &complete[..complete.len() - 40]).unwrap();
+        let err = validate_osr(&path).unwrap_err().to_string();
+        assert!(err.contains("truncated"), "{}", err);
+
+        // Exported by osu!lazer
+        let mut lazer = complete.clone();
+        lazer[1..5].copy_from_slice(&30000016i32.to_le_bytes());
+        std::fs::write(&path, &lazer).unwrap();
+        assert!(validate_osr(&path).is_ok());
+
+        let mut bad_version = complete.clone();
+        bad_version[1..5].copy_from_slice(&99999999i32.to_le_bytes());
+        std::fs::write(&path, &bad_version).unwrap();
+        assert!(validate_osr(&path).is_err());
+
+        let mut bad_mode = complete.clone();
+        bad_mode[0] = 7;
+        std::fs::write(&path, &bad_mode).unwrap();
+        assert!(StableReplayReader::validate_replay(&path).is_err());
+    }
+
+    #[test]
+    fn test_validate_osr_deep() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("replay.osr");
+
+        let frames = b"0|256|192|0,16|260|190|1,16|264|188|1,".repeat(20);
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut frames.as_slice(), &mut compressed).unwrap();
+
+        std::fs::write(&path, sample_osr_with_data(&compressed)).unwrap();
+        assert!(validate_osr_deep(&path).is_ok());
+
+        // The LZMA stream ends early, but the length prefix still matches
+        compressed.truncate(compressed.len() / 2);
+        std::fs::write(&path, sample_osr_with_data(&compressed)).unwrap();
+        assert!(validate_osr(&path).is_ok());
+        let err = validate_osr_deep(&path).unwrap_err().to_string();
+        assert!(err.contains("corrupt"), "{}", err);
+    }
+
+    #[test]
+    fn test_read_osr_header_truncated() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("truncated.osr");
+        std::fs::write(&path, [0u8, 1, 2]).unwrap();
+
+        assert!(read_osr_header(&path).is_err());
+    }
+
+    #[test]
+    fn test_read_osr_string_huge_length() {
+        // Claims a 2^62-byte string, followed by only a few bytes
+        let mut data = vec![0x0b];
+        data.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x40]);
+        data.extend_from_slice(b"abc");
+
+        let err = read_osr_string(&mut data.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("cut off"));
+    }
 }
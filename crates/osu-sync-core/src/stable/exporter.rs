@@ -1,7 +1,7 @@
 //! Export beatmaps from osu!stable
 
 use crate::beatmap::BeatmapSet;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::parser::create_osz;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -23,21 +23,23 @@ impl StableExporter {
             crate::error::Error::Other("Beatmap set has no folder name".to_string())
         })?;
 
-        let source_dir = self.songs_path.join(folder_name);
+        let source_dir = self
+            .songs_path
+            .join(beatmap_set.disk_folder_name().unwrap_or_default());
 
         if !source_dir.exists() {
             return Err(crate::error::Error::BeatmapNotFound(folder_name.clone()));
         }
 
         // Create output directory if needed
-        fs::create_dir_all(output_dir)?;
+        fs::create_dir_all(output_dir).map_err(|e| Error::io_at(e, output_dir))?;
 
         // Generate output filename
         let output_name = format!("{}.osz", folder_name);
         let output_path = output_dir.join(&output_name);
 
         // Create the .osz archive
-        create_osz(&source_dir, &output_path)
+        create_osz(&source_dir, &output_path).map_err(|e| e.with_path(&output_path))
     }
 
     /// Export multiple beatmap sets to .osz files
@@ -58,7 +60,9 @@ impl StableExporter {
             crate::error::Error::Other("Beatmap set has no folder name".to_string())
         })?;
 
-        let source_dir = self.songs_path.join(folder_name);
+        let source_dir = self
+            .songs_path
+            .join(beatmap_set.disk_folder_name().unwrap_or_default());
 
         if !source_dir.exists() {
             return Err(crate::error::Error::BeatmapNotFound(folder_name.clone()));
@@ -1,15 +1,25 @@
 //! Import beatmaps into osu!stable
 
 use crate::beatmap::BeatmapSet;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::parser::extract_osz;
+use crate::sync::{SetDiff, Throttle, ThrottledWriter, Trash};
 use crate::unified::copy_dir_recursive;
-use std::fs;
+use crate::utils::{write_with_progress, RetryPolicy};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use walkdir::WalkDir;
 
 /// Importer for adding beatmaps to osu!stable
 pub struct StableImporter {
     songs_path: PathBuf,
+    /// How transient write errors are retried
+    retry: RetryPolicy,
+    /// Write rate cap shared with the rest of the sync
+    throttle: Option<Arc<Throttle>>,
 }
 
 /// Result of an import operation
@@ -24,7 +34,35 @@ pub struct ImportResult {
 impl StableImporter {
     /// Create a new importer for the given Songs folder
     pub fn new(songs_path: PathBuf) -> Self {
-        Self { songs_path }
+        Self {
+            songs_path,
+            retry: RetryPolicy::default(),
+            throttle: None,
+        }
+    }
+
+    /// Set how transient write errors (e.g. files locked by antivirus) are retried
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Pace every file write through a sync's write rate cap
+    pub(crate) fn with_throttle(mut self, throttle: Option<Arc<Throttle>>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Write one file, paced by the throttle if there is one
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &[u8],
+        on_progress: impl FnMut(f32),
+    ) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer = ThrottledWriter::new(file, self.throttle.as_deref());
+        write_with_progress(&mut writer, content, on_progress)
     }
 
     /// Import a beatmap set from an .osz file
@@ -55,7 +93,7 @@ impl StableImporter {
             .clone()
             .unwrap_or_else(|| beatmap_set.generate_folder_name());
 
-        let dest_path = self.songs_path.join(&folder_name);
+        let dest_path = self.dest_path(beatmap_set, &folder_name);
 
         // Check if folder already exists
         if dest_path.exists() {
@@ -78,18 +116,149 @@ impl StableImporter {
         })
     }
 
+    /// Add files to an existing beatmap set folder
+    ///
+    /// Files whose name already exists in the folder are left untouched, so
+    /// only difficulties and assets the folder lacks are written.
+    pub fn merge_files(
+        &self,
+        files: &[(String, Vec<u8>)],
+        folder_name: &OsStr,
+    ) -> Result<ImportResult> {
+        let dest_path = self.songs_path.join(folder_name);
+        fs::create_dir_all(&dest_path).map_err(|e| Error::io_at(e, &dest_path))?;
+
+        for (filename, content) in files {
+            let file_path = dest_path.join(filename);
+            if file_path.exists() {
+                continue;
+            }
+            if let Some(parent) = file_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+            }
+            self.retry
+                .run(|| self.write_file(&file_path, content, |_| {}))
+                .map_err(|e| Error::io_at(e, &file_path))?;
+        }
+
+        Ok(ImportResult {
+            success: true,
+            folder_name: folder_name.to_string_lossy().into_owned(),
+            path: dest_path,
+            error: None,
+        })
+    }
+
+    /// Blake3 hash of every file in an existing set folder
+    ///
+    /// Names are relative to the folder and use `/` as separator.
+    pub fn folder_hashes(&self, folder_name: &OsStr) -> Result<Vec<(String, String)>> {
+        let folder = self.songs_path.join(folder_name);
+        let mut hashes = Vec::new();
+        for entry in WalkDir::new(&folder).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let content = fs::read(path).map_err(|e| Error::io_at(e, path))?;
+            let name = path
+                .strip_prefix(&folder)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            hashes.push((name, blake3::hash(&content).to_hex().to_string()));
+        }
+        Ok(hashes)
+    }
+
+    /// Patch an existing set folder according to a [`SetDiff`]
+    ///
+    /// The files in `diff.removed` are deleted first, then `diff.renamed`
+    /// files are renamed and the files in `diff.changed` written; everything
+    /// else is left untouched. Removing before writing keeps a file whose
+    /// name only changed case from being deleted again on case-insensitive
+    /// filesystems. With a `trash`, overwritten and removed files are moved
+    /// there first. Returns the number of bytes written.
+    pub fn apply_diff(
+        &self,
+        files: &[(String, Vec<u8>)],
+        folder_name: &OsStr,
+        diff: &SetDiff,
+        trash: Option<&Trash>,
+    ) -> Result<u64> {
+        let dest_path = self.songs_path.join(folder_name);
+
+        for filename in &diff.removed {
+            if let Some(trash) = trash {
+                trash.trash_file(&dest_path, filename)?;
+                continue;
+            }
+            let file_path = dest_path.join(filename);
+            match fs::remove_file(&file_path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(Error::io_at(e, &file_path)),
+            }
+        }
+
+        for (from, to) in &diff.renamed {
+            let from_path = dest_path.join(from);
+            let to_path = dest_path.join(to);
+            if let Some(parent) = to_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+            }
+            // Going through a temporary name makes case-only renames work on
+            // case-insensitive filesystems too
+            let temp_path = dest_path.join(format!("{}.osu-sync-rename", to));
+            fs::rename(&from_path, &temp_path).map_err(|e| Error::io_at(e, &from_path))?;
+            fs::rename(&temp_path, &to_path).map_err(|e| Error::io_at(e, &to_path))?;
+        }
+
+        let changed: HashSet<&str> = diff.changed.iter().map(String::as_str).collect();
+        let mut written = 0;
+        for (filename, content) in files {
+            if !changed.contains(filename.as_str()) {
+                continue;
+            }
+            if let Some(trash) = trash {
+                trash.trash_file(&dest_path, filename)?;
+            }
+            let file_path = dest_path.join(filename);
+            if let Some(parent) = file_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+            }
+            self.retry
+                .run(|| self.write_file(&file_path, content, |_| {}))
+                .map_err(|e| Error::io_at(e, &file_path))?;
+            written += content.len() as u64;
+        }
+
+        Ok(written)
+    }
+
     /// Import a beatmap set by copying files
     pub fn import_files(
         &self,
         files: &[(String, Vec<u8>)],
         beatmap_set: &BeatmapSet,
+    ) -> Result<ImportResult> {
+        self.import_files_with_progress(files, beatmap_set, |_, _| {})
+    }
+
+    /// Like [`import_files`](Self::import_files), reporting the name and
+    /// written fraction of large files
+    pub fn import_files_with_progress(
+        &self,
+        files: &[(String, Vec<u8>)],
+        beatmap_set: &BeatmapSet,
+        mut on_progress: impl FnMut(&str, f32),
     ) -> Result<ImportResult> {
         let folder_name = beatmap_set
             .folder_name
             .clone()
             .unwrap_or_else(|| beatmap_set.generate_folder_name());
 
-        let dest_path = self.songs_path.join(&folder_name);
+        let dest_path = self.dest_path(beatmap_set, &folder_name);
 
         // Check if folder already exists
         if dest_path.exists() {
@@ -102,15 +271,21 @@ impl StableImporter {
         }
 
         // Create directory
-        fs::create_dir_all(&dest_path)?;
+        fs::create_dir_all(&dest_path).map_err(|e| Error::io_at(e, &dest_path))?;
 
         // Write all files
         for (filename, content) in files {
             let file_path = dest_path.join(filename);
             if let Some(parent) = file_path.parent() {
-                fs::create_dir_all(parent)?;
+                fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
             }
-            fs::write(&file_path, content)?;
+            self.retry
+                .run(|| {
+                    self.write_file(&file_path, content, |fraction| {
+                        on_progress(filename, fraction)
+                    })
+                })
+                .map_err(|e| Error::io_at(e, &file_path))?;
         }
 
         Ok(ImportResult {
@@ -120,6 +295,14 @@ impl StableImporter {
             error: None,
         })
     }
+
+    /// Destination folder for a set, using its exact on-disk name if known
+    fn dest_path(&self, beatmap_set: &BeatmapSet, folder_name: &str) -> PathBuf {
+        match &beatmap_set.folder_name_os {
+            Some(name) => self.songs_path.join(name),
+            None => self.songs_path.join(folder_name),
+        }
+    }
 }
 
 /// Generate a simple UUID-like string
@@ -3,7 +3,9 @@
 mod exporter;
 mod importer;
 mod scanner;
+mod verify;
 
 pub use exporter::*;
 pub use importer::*;
 pub use scanner::*;
+pub use verify::*;
@@ -2,13 +2,15 @@
 
 use crate::beatmap::{BeatmapInfo, BeatmapSet};
 use crate::error::{Error, Result};
-use crate::parser::parse_osu_file;
+use crate::parser::{
+    parse_hitsound_references, parse_osu_file, parse_storyboard_references, SAMPLE_EXTENSIONS,
+};
+use crate::stats::ModeCount;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime};
 use walkdir::WalkDir;
@@ -141,7 +143,7 @@ struct StableScanCache {
 impl Default for StableScanCache {
     fn default() -> Self {
         Self {
-            version: 3, // Bump version for new osu_cache field
+            version: 7, // Bump version after dropping the BeatmapInfo::online_id field
             dir_count: 0,
             beatmaps_parsed: 0,
             sets: Vec::new(),
@@ -194,7 +196,6 @@ fn hash_file_blake3(path: &Path) -> std::io::Result<FileHashResult> {
 }
 
 /// Check if a file needs rehashing based on mtime/size
-#[cfg(test)]
 fn needs_rehash(path: &Path, cached: Option<&CachedFileInfo>) -> bool {
     let Some(cached) = cached else {
         return true; // Not in cache
@@ -215,14 +216,50 @@ fn needs_rehash(path: &Path, cached: Option<&CachedFileInfo>) -> bool {
 }
 
 /// Scanner for osu!stable Songs folder
+#[derive(Clone)]
 pub struct StableScanner {
     songs_path: PathBuf,
     /// Skip file hashing for faster scans (hashes won't be available)
     skip_hashing: bool,
+    /// File hashes from the previous scan's cache, reused for files whose
+    /// size and mtime are unchanged
+    previous_hashes: Arc<HashMap<String, CachedFileInfo>>,
+}
+
+/// Progress of a Songs folder scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    /// Folders started so far, including the current one
+    pub current: usize,
+    /// Total number of folders
+    pub total: usize,
+    /// Folder currently being scanned, empty once the scan is done
+    pub folder: String,
+    /// Beatmap sets found in the folders finished so far
+    pub total_sets: usize,
+    /// Difficulties in those sets
+    pub total_difficulties: usize,
+    /// Difficulties in those sets, per game mode
+    pub mode_counts: ModeCount,
+}
+
+impl ScanProgress {
+    fn start_folder(&mut self, folder: &std::ffi::OsStr) {
+        self.current += 1;
+        self.folder = folder.to_string_lossy().into_owned();
+    }
+
+    fn add_set(&mut self, set: &BeatmapSet) {
+        self.total_sets += 1;
+        self.total_difficulties += set.beatmaps.len();
+        for beatmap in &set.beatmaps {
+            self.mode_counts.add(beatmap.mode);
+        }
+    }
 }
 
 /// Progress callback for scanning (must be Sync for parallel scanning)
-pub type ScanProgress = Box<dyn Fn(usize, usize, &str) + Send + Sync>;
+pub type ScanProgressCallback = Box<dyn Fn(&ScanProgress) + Send + Sync>;
 
 impl StableScanner {
     /// Create a new scanner for the given Songs folder
@@ -230,6 +267,7 @@ impl StableScanner {
         Self {
             songs_path,
             skip_hashing: false,
+            previous_hashes: Arc::default(),
         }
     }
 
@@ -269,8 +307,8 @@ impl StableScanner {
         let content = fs::read(&cache_path).ok()?;
         let cache: StableScanCache = bincode::deserialize(&content).ok()?;
 
-        // Check cache version (3 = with osu_cache)
-        if cache.version < 3 {
+        // Check cache version (7 = difficulty online IDs read from metadata)
+        if cache.version < 7 {
             tracing::info!(
                 "Stable cache version mismatch ({}), rebuilding",
                 cache.version
@@ -333,7 +371,7 @@ impl StableScanner {
         osu_cache: HashMap<String, CachedOsuFile>,
     ) {
         let cache = StableScanCache {
-            version: 3,
+            version: 7,
             dir_count,
             beatmaps_parsed,
             sets: sets.to_vec(),
@@ -370,7 +408,10 @@ impl StableScanner {
     }
 
     /// Scan all beatmap sets with progress callback
-    pub fn scan_with_progress(&self, progress: Option<ScanProgress>) -> Result<Vec<BeatmapSet>> {
+    pub fn scan_with_progress(
+        &self,
+        progress: Option<ScanProgressCallback>,
+    ) -> Result<Vec<BeatmapSet>> {
         let (sets, _timing) = self.scan_timed_with_progress(progress)?;
         Ok(sets)
     }
@@ -378,7 +419,7 @@ impl StableScanner {
     /// Scan all beatmap sets with timing and progress callback
     pub fn scan_timed_with_progress(
         &self,
-        progress: Option<ScanProgress>,
+        progress: Option<ScanProgressCallback>,
     ) -> Result<(Vec<BeatmapSet>, ScanTiming)> {
         let total_start = Instant::now();
         let mut timing = ScanTiming::default();
@@ -398,22 +439,24 @@ impl StableScanner {
         timing.dir_enumeration = dir_start.elapsed();
         timing.dirs_scanned = entries.len();
 
-        let total = entries.len();
+        let mut scan_progress = ScanProgress {
+            total: entries.len(),
+            ..Default::default()
+        };
 
-        for (idx, entry) in entries.into_iter().enumerate() {
+        for entry in entries {
             let dir_path = entry.path();
-            let folder_name = dir_path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_default();
+            let folder_name = dir_path.file_name().unwrap_or_default();
 
             if let Some(ref cb) = progress {
-                cb(idx + 1, total, &folder_name);
+                scan_progress.start_folder(folder_name);
+                cb(&scan_progress);
             }
 
             match self.scan_beatmap_set_timed(&dir_path, &mut timing) {
                 Ok(mut set) => {
-                    set.folder_name = Some(folder_name);
+                    set.set_folder_name(folder_name);
+                    scan_progress.add_set(&set);
                     beatmap_sets.push(set);
                 }
                 Err(e) => {
@@ -422,6 +465,11 @@ impl StableScanner {
             }
         }
 
+        if let Some(ref cb) = progress {
+            scan_progress.folder.clear();
+            cb(&scan_progress);
+        }
+
         timing.total = total_start.elapsed();
         Ok((beatmap_sets, timing))
     }
@@ -440,7 +488,7 @@ impl StableScanner {
     /// Scan all beatmap sets in parallel with progress callback
     pub fn scan_parallel_with_progress(
         &self,
-        progress: Option<ScanProgress>,
+        progress: Option<ScanProgressCallback>,
     ) -> Result<(Vec<BeatmapSet>, ScanTiming)> {
         let total_start = Instant::now();
 
@@ -461,9 +509,11 @@ impl StableScanner {
         // Try to load from cache (includes file hash cache for incremental updates)
         // Load osu_cache for incremental parsing even if full cache is invalid
         let osu_cache = self.load_osu_cache();
-        if let Some((cached_sets, beatmaps_parsed, _file_hashes, _osu_cache)) =
+        let mut previous_hashes = HashMap::new();
+        if let Some((cached_sets, beatmaps_parsed, file_hashes, _osu_cache)) =
             self.load_from_cache(total)
         {
+            previous_hashes = file_hashes;
             if !cached_sets.is_empty() {
                 let timing = ScanTiming {
                     total: total_start.elapsed(),
@@ -479,8 +529,16 @@ impl StableScanner {
             }
         }
         let osu_cache = Arc::new(Mutex::new(osu_cache));
+        // Files unchanged since the cached scan keep their hashes
+        let scanner = Self {
+            previous_hashes: Arc::new(previous_hashes),
+            ..self.clone()
+        };
 
-        let processed = AtomicUsize::new(0);
+        let scan_progress = Mutex::new(ScanProgress {
+            total,
+            ..Default::default()
+        });
         let timing = Mutex::new(ScanTiming {
             dir_enumeration,
             dirs_scanned: total,
@@ -498,24 +556,29 @@ impl StableScanner {
             .par_iter()
             .filter_map(|entry| {
                 let dir_path = entry.path();
-                let folder_name = dir_path.file_name()?.to_string_lossy().to_string();
+                let folder_name = dir_path.file_name()?;
 
-                // Update progress
-                let current = processed.fetch_add(1, Ordering::SeqCst);
+                // Update progress, calling back outside the lock
                 if let Some(ref cb) = progress {
-                    cb(current + 1, total, &folder_name);
+                    let snapshot = {
+                        let mut p = scan_progress.lock().unwrap();
+                        p.start_folder(folder_name);
+                        p.clone()
+                    };
+                    cb(&snapshot);
                 }
 
                 // Scan with local timing and file hash collection
                 let mut local_timing = ScanTiming::default();
                 let mut local_hashes = HashMap::new();
-                match self.scan_beatmap_set_timed_with_cache(
+                match scanner.scan_beatmap_set_timed_with_cache(
                     &dir_path,
                     &mut local_timing,
                     &mut local_hashes,
                 ) {
                     Ok(mut set) => {
-                        set.folder_name = Some(folder_name);
+                        set.set_folder_name(folder_name);
+                        scan_progress.lock().unwrap().add_set(&set);
 
                         // Merge timing (aggregate across threads)
                         let mut t = timing.lock().unwrap();
@@ -539,6 +602,12 @@ impl StableScanner {
             })
             .collect();
 
+        if let Some(ref cb) = progress {
+            let mut scan_progress = scan_progress.into_inner().unwrap();
+            scan_progress.folder.clear();
+            cb(&scan_progress);
+        }
+
         let mut final_timing = timing.into_inner().unwrap();
         final_timing.total = total_start.elapsed();
         let final_hashes = file_hashes.into_inner().unwrap();
@@ -557,10 +626,20 @@ impl StableScanner {
     }
 
     /// Scan a single beatmap set directory
-    #[allow(dead_code)]
-    fn scan_beatmap_set(&self, dir: &Path) -> Result<BeatmapSet> {
+    ///
+    /// The set's `folder_name` is taken from the directory name.
+    pub fn scan_beatmap_set(&self, dir: &Path) -> Result<BeatmapSet> {
         let mut timing = ScanTiming::default();
-        self.scan_beatmap_set_timed(dir, &mut timing)
+        let mut set = self.scan_beatmap_set_timed(dir, &mut timing)?;
+        if let Some(name) = dir.file_name() {
+            set.set_folder_name(name);
+        }
+        Ok(set)
+    }
+
+    /// Get the Songs folder this scanner reads from
+    pub fn songs_path(&self) -> &Path {
+        &self.songs_path
     }
 
     /// Scan a single beatmap set directory with timing
@@ -596,10 +675,10 @@ impl StableScanner {
         }
 
         // Parse all .osu files
-        for entry in osu_files {
-            let path = entry.path();
+        let osu_paths: Vec<PathBuf> = osu_files.iter().map(|e| e.path()).collect();
+        for path in &osu_paths {
             let parse_start = Instant::now();
-            match parse_osu_file(&path) {
+            match parse_osu_file(path) {
                 Ok(info) => {
                     timing.osu_parsing += parse_start.elapsed();
                     timing.osu_files_parsed += 1;
@@ -624,43 +703,25 @@ impl StableScanner {
             {
                 let path = entry.path();
                 if path.is_file() {
-                    let hash_start = Instant::now();
-
-                    // Use Blake3 for hashing (5-10x faster than SHA-256)
-                    // hash_file_blake3 returns hash + metadata to avoid redundant fs::metadata calls
-                    if let Ok(result) = hash_file_blake3(path) {
-                        timing.file_hashing += hash_start.elapsed();
-                        timing.files_hashed += 1;
-                        timing.bytes_hashed += result.size;
-
-                        let filename = path
-                            .file_name()
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_default();
-
-                        // Cache the file info for incremental updates
-                        let relative_path = path
-                            .strip_prefix(&self.songs_path)
-                            .map(|p| p.to_string_lossy().to_string())
-                            .unwrap_or_else(|_| filename.clone());
-
-                        file_hash_cache.insert(
-                            relative_path,
-                            CachedFileInfo {
-                                mtime_secs: result.mtime_secs,
-                                size: result.size,
-                                hash: result.hash.clone(),
-                            },
-                        );
-
-                        beatmap_set.files.push(crate::beatmap::BeatmapFile {
-                            filename,
-                            hash: result.hash,
-                            size: result.size,
-                        });
+                    let filename = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    if let Some(file) = self.hash_set_file(path, filename, timing, file_hash_cache)
+                    {
+                        beatmap_set.files.push(file);
                     }
                 }
             }
+
+            // Storyboard assets commonly live in subfolders the flat walk misses
+            self.collect_referenced_assets(
+                dir,
+                &osu_paths,
+                &mut beatmap_set,
+                timing,
+                file_hash_cache,
+            );
         }
 
         if beatmap_set.beatmaps.is_empty() {
@@ -672,6 +733,159 @@ impl StableScanner {
         Ok(beatmap_set)
     }
 
+    /// Hash a single file of a beatmap set and record it in the file hash cache
+    fn hash_set_file(
+        &self,
+        path: &Path,
+        filename: String,
+        timing: &mut ScanTiming,
+        file_hash_cache: &mut HashMap<String, CachedFileInfo>,
+    ) -> Option<crate::beatmap::BeatmapFile> {
+        let hash_start = Instant::now();
+
+        let relative_path = path
+            .strip_prefix(&self.songs_path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| filename.clone());
+
+        // Reuse the previous scan's hash unless the file changed, otherwise
+        // use Blake3 (5-10x faster than SHA-256); hash_file_blake3 returns
+        // hash + metadata to avoid redundant fs::metadata calls
+        let previous = self
+            .previous_hashes
+            .get(&relative_path)
+            .filter(|cached| !needs_rehash(path, Some(cached)));
+        let (result, cached) = match previous {
+            Some(cached) => {
+                let result = FileHashResult {
+                    hash: cached.hash.clone(),
+                    size: cached.size,
+                    mtime_secs: cached.mtime_secs,
+                };
+                (result, true)
+            }
+            None => (hash_file_blake3(path).ok()?, false),
+        };
+        timing.file_hashing += hash_start.elapsed();
+        if !cached {
+            timing.files_hashed += 1;
+            timing.bytes_hashed += result.size;
+        }
+
+        // Cache the file info for incremental updates
+        file_hash_cache.insert(
+            relative_path,
+            CachedFileInfo {
+                mtime_secs: result.mtime_secs,
+                size: result.size,
+                hash: result.hash.clone(),
+            },
+        );
+
+        Some(crate::beatmap::BeatmapFile {
+            filename,
+            hash: result.hash,
+            size: result.size,
+        })
+    }
+
+    /// Add assets referenced by storyboards and hitsounds to `beatmap_set.files`
+    ///
+    /// Storyboard references come from any `.osb` file in the set folder and
+    /// from the `[Events]` section of each difficulty; hitsound references
+    /// come from timing points and hit objects. Explicitly referenced files
+    /// that do not exist are recorded in `missing_files` instead of failing
+    /// the scan. Custom sample-set samples are optional, since osu! falls back
+    /// to the skin, and are only added when present.
+    fn collect_referenced_assets(
+        &self,
+        dir: &Path,
+        osu_paths: &[PathBuf],
+        beatmap_set: &mut BeatmapSet,
+        timing: &mut ScanTiming,
+        file_hash_cache: &mut HashMap<String, CachedFileInfo>,
+    ) {
+        let osb_paths = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension()
+                    .map(|ext| ext.eq_ignore_ascii_case("osb"))
+                    .unwrap_or(false)
+            });
+
+        let mut references = Vec::new();
+        let mut samples = Vec::new();
+        for path in osb_paths.chain(osu_paths.iter().cloned()) {
+            let content = match fs::read(&path) {
+                Ok(content) => String::from_utf8_lossy(&content).into_owned(),
+                Err(e) => {
+                    tracing::warn!("Failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let mut found = parse_storyboard_references(&content);
+            if osu_paths.contains(&path) {
+                let hitsounds = parse_hitsound_references(&content);
+                found.extend(hitsounds.files);
+                for sample in hitsounds.samples {
+                    if !samples.contains(&sample) {
+                        samples.push(sample);
+                    }
+                }
+            }
+            for reference in found {
+                if !references.contains(&reference) {
+                    references.push(reference);
+                }
+            }
+        }
+
+        for reference in references {
+            match resolve_set_asset(dir, &reference) {
+                Some((path, filename)) => {
+                    self.add_set_asset(&path, filename, beatmap_set, timing, file_hash_cache)
+                }
+                None => {
+                    tracing::debug!("Asset {} missing from {}", reference, dir.display());
+                    beatmap_set.missing_files.push(reference);
+                }
+            }
+        }
+
+        for sample in samples {
+            let found = SAMPLE_EXTENSIONS
+                .iter()
+                .find_map(|ext| resolve_set_asset(dir, &format!("{}.{}", sample, ext)));
+            if let Some((path, filename)) = found {
+                self.add_set_asset(&path, filename, beatmap_set, timing, file_hash_cache);
+            }
+        }
+    }
+
+    /// Hash and add an asset unless the set already lists it
+    fn add_set_asset(
+        &self,
+        path: &Path,
+        filename: String,
+        beatmap_set: &mut BeatmapSet,
+        timing: &mut ScanTiming,
+        file_hash_cache: &mut HashMap<String, CachedFileInfo>,
+    ) {
+        if beatmap_set
+            .files
+            .iter()
+            .any(|f| f.filename.eq_ignore_ascii_case(&filename))
+        {
+            return;
+        }
+        if let Some(file) = self.hash_set_file(path, filename, timing, file_hash_cache) {
+            beatmap_set.files.push(file);
+        }
+    }
+
     /// Find a beatmap set by its online ID
     pub fn find_by_set_id(&self, set_id: i32) -> Result<Option<BeatmapSet>> {
         // Scan all sets and find matching one
@@ -700,6 +914,60 @@ impl StableScanner {
     }
 }
 
+#[cfg(feature = "tokio")]
+impl StableScanner {
+    /// Scan all beatmap sets in parallel on tokio's blocking pool
+    ///
+    /// Progress is sent to `progress` if given; a dropped receiver is ignored.
+    pub async fn scan_async(
+        &self,
+        progress: Option<tokio::sync::mpsc::UnboundedSender<ScanProgress>>,
+    ) -> Result<Vec<BeatmapSet>> {
+        let scanner = self.clone();
+        let callback = progress.map(|tx| -> ScanProgressCallback {
+            Box::new(move |progress| {
+                let _ = tx.send(progress.clone());
+            })
+        });
+        let (sets, _timing) =
+            tokio::task::spawn_blocking(move || scanner.scan_parallel_with_progress(callback))
+                .await??;
+        Ok(sets)
+    }
+}
+
+/// Resolve an asset path referenced from a beatmap set folder
+///
+/// osu! resolves paths case-insensitively, so each component is matched
+/// ignoring ASCII case when the exact path does not exist. Returns the file
+/// path and its folder-relative name using the on-disk casing, or `None` if
+/// the asset is missing or escapes the folder.
+pub(super) fn resolve_set_asset(dir: &Path, reference: &str) -> Option<(PathBuf, String)> {
+    let components: Vec<&str> = reference.split('/').filter(|c| !c.is_empty()).collect();
+    if components.is_empty() || components.iter().any(|c| *c == ".." || c.contains(':')) {
+        return None;
+    }
+
+    let exact = components.iter().fold(dir.to_path_buf(), |p, c| p.join(c));
+    if exact.is_file() {
+        return Some((exact, components.join("/")));
+    }
+
+    let mut current = dir.to_path_buf();
+    let mut names = Vec::with_capacity(components.len());
+    for component in components {
+        let name = fs::read_dir(&current)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .find(|name| name.eq_ignore_ascii_case(component))?;
+        current.push(&name);
+        names.push(name);
+    }
+
+    current.is_file().then(|| (current, names.join("/")))
+}
+
 /// Index for fast beatmap lookups
 pub struct BeatmapIndex {
     /// All beatmap sets
@@ -1063,6 +1331,73 @@ mod tests {
         assert!(scanner.skip_hashing);
     }
 
+    #[test]
+    fn test_scan_includes_referenced_assets() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs_path = temp_dir.path().join("Songs");
+        let set_dir = songs_path.join("1 Artist - Title");
+        fs::create_dir_all(set_dir.join("SB")).unwrap();
+
+        fs::write(
+            set_dir.join("Artist - Title (Mapper) [Normal].osu"),
+            "osu file format v14\n\n[Metadata]\nTitle:Title\nArtist:Artist\nBeatmapSetID:1\n\n[Events]\nSample,0,0,\"hit.wav\",100\n\n[TimingPoints]\n0,500,4,2,1,60,1,0\n\n[HitObjects]\n256,192,100,1,2,0:0:0:0:kick.wav\n256,192,200,1,2,0:0:0:0:snare.wav\n",
+        )
+        .unwrap();
+        fs::write(
+            set_dir.join("Artist - Title (Mapper).osb"),
+            "[Events]\nSprite,Background,Centre,\"sb\\bg.png\",320,240\nAnimation,Foreground,Centre,\"sb/star.png\",0,0,2,100\n",
+        )
+        .unwrap();
+        fs::write(set_dir.join("SB").join("bg.png"), b"bg").unwrap();
+        fs::write(set_dir.join("SB").join("star0.png"), b"star").unwrap();
+        fs::write(set_dir.join("hit.wav"), b"hit").unwrap();
+        fs::write(set_dir.join("kick.wav"), b"kick").unwrap();
+        fs::write(set_dir.join("soft-hitclap.ogg"), b"clap").unwrap();
+
+        let scanner = StableScanner::new(songs_path);
+        let set = scanner.scan_beatmap_set(&set_dir).unwrap();
+
+        let names: Vec<_> = set.files.iter().map(|f| f.filename.as_str()).collect();
+        assert!(names.contains(&"SB/bg.png"));
+        assert!(names.contains(&"SB/star0.png"));
+        assert_eq!(names.iter().filter(|n| **n == "hit.wav").count(), 1);
+        assert!(names.contains(&"kick.wav"));
+        assert!(names.contains(&"soft-hitclap.ogg"));
+        assert_eq!(
+            set.missing_files,
+            vec!["sb/star1.png".to_string(), "snare.wav".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rescan_reuses_cached_file_hashes() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs_path = temp_dir.path().join("Songs");
+        let add_set = |id: i32| {
+            let set_dir = songs_path.join(format!("{} Artist - Title", id));
+            fs::create_dir_all(&set_dir).unwrap();
+            fs::write(
+                set_dir.join("map.osu"),
+                format!("osu file format v14\n\n[Metadata]\nBeatmapSetID:{}\n", id),
+            )
+            .unwrap();
+            fs::write(set_dir.join("audio.mp3"), b"audio").unwrap();
+        };
+        add_set(1);
+
+        let scanner = StableScanner::new(songs_path.clone());
+        let (_, timing) = scanner.scan_parallel_timed().unwrap();
+        assert_eq!(timing.files_hashed, 2);
+
+        // A new folder invalidates the cached sets, but not the hashes of
+        // files that didn't change
+        add_set(2);
+        let (sets, timing) = scanner.scan_parallel_timed().unwrap();
+        assert_eq!(sets.len(), 2);
+        assert_eq!(timing.files_hashed, 2);
+        assert!(sets.iter().all(|set| set.files.len() == 2));
+    }
+
     #[test]
     fn test_scan_timing_report_cached() {
         let timing = ScanTiming {
@@ -1116,4 +1451,66 @@ mod tests {
         assert_eq!(deserialized.size, info.size);
         assert_eq!(deserialized.hash, info.hash);
     }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_scan_async_reports_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs_path = temp_dir.path().join("Songs");
+        let set_dir = songs_path.join("1 Artist - Title");
+        fs::create_dir_all(&set_dir).unwrap();
+        fs::write(
+            set_dir.join("Artist - Title (Mapper) [Normal].osu"),
+            "osu file format v14\n\n[Metadata]\nTitle:Title\nArtist:Artist\nBeatmapSetID:1\n",
+        )
+        .unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let sets = StableScanner::new(songs_path)
+            .scan_async(Some(tx))
+            .await
+            .unwrap();
+        assert_eq!(sets.len(), 1);
+
+        let update = rx.recv().await.unwrap();
+        assert_eq!(update.total, 1);
+    }
+
+    #[test]
+    fn test_scan_progress_counts_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs_path = temp_dir.path().join("Songs");
+        for (id, modes) in [(1, &[0, 3][..]), (2, &[3][..])] {
+            let set_dir = songs_path.join(format!("{} Artist - Title", id));
+            fs::create_dir_all(&set_dir).unwrap();
+            for (idx, mode) in modes.iter().enumerate() {
+                fs::write(
+                    set_dir.join(format!("Artist - Title (Mapper) [{}].osu", idx)),
+                    format!(
+                        "osu file format v14\n\n[General]\nMode: {}\n\n[Metadata]\nTitle:Title\nVersion:{}\nBeatmapSetID:{}\n",
+                        mode, idx, id
+                    ),
+                )
+                .unwrap();
+            }
+        }
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = updates.clone();
+        let sets = StableScanner::new(songs_path)
+            .skip_hashing()
+            .scan_with_progress(Some(Box::new(move |p: &ScanProgress| {
+                sink.lock().unwrap().push(p.clone())
+            })))
+            .unwrap();
+        assert_eq!(sets.len(), 2);
+
+        let updates = updates.lock().unwrap();
+        assert_eq!(updates.len(), 3);
+        let last = updates.last().unwrap();
+        assert_eq!((last.current, last.total), (2, 2));
+        assert!(last.folder.is_empty());
+        assert_eq!((last.total_sets, last.total_difficulties), (2, 3));
+        assert_eq!((last.mode_counts.osu, last.mode_counts.mania), (1, 2));
+    }
 }
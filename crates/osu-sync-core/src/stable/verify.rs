@@ -0,0 +1,255 @@
+//! Library health checks for osu!stable Songs folders
+
+use super::scanner::{resolve_set_asset, StableScanner};
+use crate::error::{Error, Result};
+use crate::parser::{parse_osu_file, LATEST_FORMAT_VERSION};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A problem found in a beatmap set folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LibraryIssue {
+    /// The beatmap set folder the issue was found in
+    pub folder: PathBuf,
+    /// What is wrong with it
+    #[serde(flatten)]
+    pub kind: LibraryIssueKind,
+}
+
+/// Machine-readable kind of a [`LibraryIssue`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LibraryIssueKind {
+    /// The folder contains no `.osu` files at all
+    NoBeatmapFiles,
+    /// A `.osu` file could not be parsed
+    UnparseableBeatmap { file: String, message: String },
+    /// The audio file declared by a difficulty does not exist
+    MissingAudio { file: String },
+    /// The background image referenced by a difficulty does not exist
+    MissingBackground { file: String },
+    /// A `.osu` file uses a format version newer than [`LATEST_FORMAT_VERSION`]
+    UntestedFormatVersion { file: String, version: u8 },
+}
+
+impl LibraryIssueKind {
+    /// Stable identifier for scripts, matching the serialized `kind` tag
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoBeatmapFiles => "no_beatmap_files",
+            Self::UnparseableBeatmap { .. } => "unparseable_beatmap",
+            Self::MissingAudio { .. } => "missing_audio",
+            Self::MissingBackground { .. } => "missing_background",
+            Self::UntestedFormatVersion { .. } => "untested_format_version",
+        }
+    }
+
+    /// Whether the set still works and the issue is only worth a look
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Self::UntestedFormatVersion { .. })
+    }
+}
+
+impl std::fmt::Display for LibraryIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoBeatmapFiles => write!(f, "no .osu files"),
+            Self::UnparseableBeatmap { file, message } => {
+                write!(f, "cannot parse {}: {}", file, message)
+            }
+            Self::MissingAudio { file } => write!(f, "missing audio file {}", file),
+            Self::MissingBackground { file } => write!(f, "missing background {}", file),
+            Self::UntestedFormatVersion { file, version } => write!(
+                f,
+                "{} uses file format v{}, newer than the tested v{}",
+                file, version, LATEST_FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+impl StableScanner {
+    /// Check every beatmap set folder for missing audio, missing backgrounds
+    /// and folders without `.osu` files
+    ///
+    /// Unlike a scan this never skips broken folders; each problem is
+    /// reported as a [`LibraryIssue`], ordered by folder. Difficulties in a
+    /// file format newer than the parser was tested against are reported as
+    /// warnings (see [`LibraryIssueKind::is_warning`]).
+    pub fn verify_library(&self) -> Result<Vec<LibraryIssue>> {
+        let songs_path = self.songs_path();
+        if !songs_path.exists() {
+            return Err(Error::OsuNotFound(songs_path.to_path_buf()));
+        }
+
+        let mut dirs: Vec<PathBuf> = fs::read_dir(songs_path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        dirs.sort();
+
+        Ok(dirs
+            .par_iter()
+            .flat_map_iter(|dir| verify_set_folder(dir))
+            .collect())
+    }
+}
+
+/// Check a single beatmap set folder
+pub fn verify_set_folder(dir: &Path) -> Vec<LibraryIssue> {
+    let issue = |kind| LibraryIssue {
+        folder: dir.to_path_buf(),
+        kind,
+    };
+
+    let mut osu_files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension()
+                    .map(|ext| ext.eq_ignore_ascii_case("osu"))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    osu_files.sort();
+
+    if osu_files.is_empty() {
+        return vec![issue(LibraryIssueKind::NoBeatmapFiles)];
+    }
+
+    let mut issues = Vec::new();
+    for path in osu_files {
+        let info = match parse_osu_file(&path) {
+            Ok(info) => info,
+            Err(e) => {
+                let message = match e {
+                    Error::BeatmapParse { message, .. } => message,
+                    other => other.to_string(),
+                };
+                issues.push(issue(LibraryIssueKind::UnparseableBeatmap {
+                    file: file_name(&path),
+                    message,
+                }));
+                continue;
+            }
+        };
+
+        if info.format_version > LATEST_FORMAT_VERSION {
+            issues.push(issue(LibraryIssueKind::UntestedFormatVersion {
+                file: file_name(&path),
+                version: info.format_version,
+            }));
+        }
+
+        let audio = info.audio_file.replace('\\', "/");
+        if !audio.is_empty() && resolve_set_asset(dir, &audio).is_none() {
+            push_unique(
+                &mut issues,
+                issue(LibraryIssueKind::MissingAudio { file: audio }),
+            );
+        }
+        if let Some(background) = info.background_file.map(|b| b.replace('\\', "/")) {
+            if resolve_set_asset(dir, &background).is_none() {
+                push_unique(
+                    &mut issues,
+                    issue(LibraryIssueKind::MissingBackground { file: background }),
+                );
+            }
+        }
+    }
+
+    issues
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Difficulties usually share audio and backgrounds; report each once
+fn push_unique(issues: &mut Vec<LibraryIssue>, issue: LibraryIssue) {
+    if !issues.contains(&issue) {
+        issues.push(issue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_map(dir: &Path, name: &str, audio: &str, background: &str) {
+        write_versioned_map(dir, name, 14, audio, background);
+    }
+
+    fn write_versioned_map(dir: &Path, name: &str, version: u32, audio: &str, background: &str) {
+        fs::write(
+            dir.join(name),
+            format!(
+                "osu file format v{}\n\n[General]\nAudioFilename: {}\n\n[Metadata]\nTitle:T\n\n[Events]\n0,0,\"{}\",0,0\n",
+                version, audio, background
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_verify_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs = temp_dir.path().join("Songs");
+
+        let healthy = songs.join("1 Healthy");
+        fs::create_dir_all(&healthy).unwrap();
+        write_map(&healthy, "a.osu", "audio.mp3", "BG.jpg");
+        fs::write(healthy.join("audio.mp3"), b"a").unwrap();
+        fs::write(healthy.join("bg.jpg"), b"b").unwrap();
+
+        let broken = songs.join("2 Broken");
+        fs::create_dir_all(&broken).unwrap();
+        write_map(&broken, "easy.osu", "song.mp3", "bg.png");
+        write_map(&broken, "hard.osu", "song.mp3", "bg.png");
+
+        let empty = songs.join("3 Empty");
+        fs::create_dir_all(&empty).unwrap();
+        fs::write(empty.join("song.mp3"), b"a").unwrap();
+
+        let newer = songs.join("4 Newer");
+        fs::create_dir_all(&newer).unwrap();
+        write_versioned_map(&newer, "a.osu", 128, "audio.mp3", "bg.jpg");
+        fs::write(newer.join("audio.mp3"), b"a").unwrap();
+        fs::write(newer.join("bg.jpg"), b"b").unwrap();
+
+        let issues = StableScanner::new(songs).verify_library().unwrap();
+        let kinds: Vec<_> = issues
+            .iter()
+            .map(|i| {
+                (
+                    i.folder.file_name().unwrap().to_string_lossy().to_string(),
+                    i.kind.code(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("2 Broken".to_string(), "missing_audio"),
+                ("2 Broken".to_string(), "missing_background"),
+                ("3 Empty".to_string(), "no_beatmap_files"),
+                ("4 Newer".to_string(), "untested_format_version"),
+            ]
+        );
+        assert!(issues[3].kind.is_warning());
+        assert!(!issues[0].kind.is_warning());
+
+        let json = serde_json::to_value(&issues[0]).unwrap();
+        assert_eq!(json["kind"], "missing_audio");
+        assert_eq!(json["file"], "song.mp3");
+    }
+}
@@ -3,7 +3,7 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::beatmap::{BeatmapFile, BeatmapSet, GameMode};
-use crate::dedup::{DuplicateDetector, DuplicateStrategy, MatchType};
+use crate::dedup::{DuplicateDetector, DuplicateStrategy, FileHashIndex, MatchType};
 use crate::lazer::LazerBeatmapSet;
 
 use super::model::{
@@ -80,6 +80,7 @@ impl StatsAnalyzer {
             common_beatmaps: common.len(),
             mode_breakdown,
             recommendations,
+            integrity: None,
         }
     }
 
@@ -95,8 +96,11 @@ impl StatsAnalyzer {
         let lazer_as_sets: Vec<BeatmapSet> =
             lazer_sets.iter().map(Self::lazer_to_beatmap_set).collect();
 
+        let file_hashes = FileHashIndex::new(&lazer_as_sets);
         for stable_set in stable_sets {
-            if let Some(dup_info) = detector.find_duplicate(stable_set, &lazer_as_sets) {
+            if let Some(dup_info) =
+                detector.find_duplicate_indexed(stable_set, &lazer_as_sets, &file_hashes)
+            {
                 stats.count += 1;
 
                 // Estimate wasted space (size of duplicate)
@@ -109,6 +113,8 @@ impl StatsAnalyzer {
                     MatchType::SameSetId => "Same Set ID",
                     MatchType::SameBeatmapId => "Same Beatmap ID",
                     MatchType::Metadata => "Metadata Match",
+                    MatchType::FuzzyMetadata => "Fuzzy Metadata",
+                    MatchType::FileHashSubset => "File Subset",
                     MatchType::Similar(_) => "Similar",
                 };
                 *stats
@@ -140,7 +146,9 @@ impl StatsAnalyzer {
                 mode: lb.mode,
                 version: lb.version.clone(),
                 star_rating: lb.star_rating,
+                star_ratings: lb.star_ratings.clone(),
                 ranked_status: lb.ranked_status,
+                format_version: 0,
             })
             .collect();
 
@@ -159,6 +167,8 @@ impl StatsAnalyzer {
             beatmaps,
             files,
             folder_name: None,
+            missing_files: Vec::new(),
+            folder_name_os: None,
         }
     }
 
@@ -189,12 +199,7 @@ impl StatsAnalyzer {
 
         for set in sets {
             for beatmap in &set.beatmaps {
-                match beatmap.mode {
-                    GameMode::Osu => counts.osu += 1,
-                    GameMode::Taiko => counts.taiko += 1,
-                    GameMode::Catch => counts.catch += 1,
-                    GameMode::Mania => counts.mania += 1,
-                }
+                counts.add(beatmap.mode);
             }
         }
 
@@ -207,12 +212,7 @@ impl StatsAnalyzer {
 
         for set in sets {
             for beatmap in &set.beatmaps {
-                match beatmap.mode {
-                    GameMode::Osu => counts.osu += 1,
-                    GameMode::Taiko => counts.taiko += 1,
-                    GameMode::Catch => counts.catch += 1,
-                    GameMode::Mania => counts.mania += 1,
-                }
+                counts.add(beatmap.mode);
             }
         }
 
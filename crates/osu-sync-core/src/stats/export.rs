@@ -1,12 +1,13 @@
 //! Export functionality for statistics data
 
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::model::{ComparisonStats, DuplicateStats, InstallationStats};
+use crate::beatmap::{BeatmapInfo, BeatmapSet};
 use crate::error::{Error, Result};
 
 /// Serializable version of ComparisonStats for JSON export
@@ -47,6 +48,56 @@ pub fn export_json(stats: &ComparisonStats, path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Schema version written by [`export_library_json`]
+///
+/// Bumped whenever [`BeatmapSet`] changes in a way older snapshots cannot
+/// be read into.
+pub const LIBRARY_JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct LibraryExport<'a> {
+    schema_version: u32,
+    sets: &'a [BeatmapSet],
+}
+
+#[derive(Deserialize)]
+struct LibraryImport {
+    schema_version: u32,
+    sets: Vec<BeatmapSet>,
+}
+
+/// Export the full list of beatmap sets, with every difficulty and file, to JSON
+///
+/// Unlike [`export_json`] this writes the raw scan data, so a library can be
+/// reloaded with [`import_library_json`] instead of being scanned again.
+pub fn export_library_json(sets: &[BeatmapSet], path: &Path) -> Result<()> {
+    let export = LibraryExport {
+        schema_version: LIBRARY_JSON_VERSION,
+        sets,
+    };
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, &export)
+        .map_err(|e| Error::Other(format!("Failed to serialize library: {}", e)))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Load beatmap sets written by [`export_library_json`]
+///
+/// Fails if the file was written with a newer schema version.
+pub fn import_library_json(path: &Path) -> Result<Vec<BeatmapSet>> {
+    let reader = BufReader::new(File::open(path)?);
+    let import: LibraryImport = serde_json::from_reader(reader)
+        .map_err(|e| Error::Other(format!("Failed to parse library JSON: {}", e)))?;
+    if import.schema_version > LIBRARY_JSON_VERSION {
+        return Err(Error::Other(format!(
+            "Library JSON schema version {} is newer than supported version {}",
+            import.schema_version, LIBRARY_JSON_VERSION
+        )));
+    }
+    Ok(import.sets)
+}
+
 /// Export statistics to CSV format
 pub fn export_csv(stats: &ComparisonStats, path: &Path) -> Result<()> {
     let mut writer = csv::Writer::from_path(path)
@@ -218,6 +269,83 @@ pub fn export_csv(stats: &ComparisonStats, path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Column headers for per-difficulty beatmap CSV exports
+pub const BEATMAP_CSV_HEADERS: [&str; 9] = [
+    "set_id",
+    "artist",
+    "title",
+    "version",
+    "mode",
+    "star_rating",
+    "bpm",
+    "length_ms",
+    "ranked_status",
+];
+
+/// Streaming CSV writer that emits one row per beatmap difficulty
+///
+/// Rows are written as they are added, so large libraries can be exported
+/// to stdout without buffering the whole file in memory.
+pub struct BeatmapCsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    rows: usize,
+}
+
+impl<W: Write> BeatmapCsvWriter<W> {
+    /// Create a writer and emit the header row
+    pub fn new(output: W) -> Result<Self> {
+        let mut writer = csv::Writer::from_writer(output);
+        writer
+            .write_record(BEATMAP_CSV_HEADERS)
+            .map_err(|e| Error::Other(format!("CSV write error: {}", e)))?;
+        Ok(Self { writer, rows: 0 })
+    }
+
+    /// Write a single difficulty row
+    pub fn write_beatmap(&mut self, set_id: Option<i32>, beatmap: &BeatmapInfo) -> Result<()> {
+        self.writer
+            .write_record([
+                set_id.map(|id| id.to_string()).unwrap_or_default(),
+                beatmap.metadata.artist.clone(),
+                beatmap.metadata.title.clone(),
+                beatmap.version.clone(),
+                format!("{:?}", beatmap.mode),
+                beatmap
+                    .star_rating
+                    .map(|sr| format!("{:.2}", sr))
+                    .unwrap_or_default(),
+                format!("{:.0}", beatmap.bpm),
+                beatmap.length_ms.to_string(),
+                beatmap
+                    .ranked_status
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+            ])
+            .map_err(|e| Error::Other(format!("CSV write error: {}", e)))?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Write one row for every difficulty in a set
+    pub fn write_set(&mut self, set: &BeatmapSet) -> Result<()> {
+        let set_id = set
+            .id
+            .or_else(|| set.metadata().and_then(|m| m.beatmap_set_id));
+        for beatmap in &set.beatmaps {
+            self.write_beatmap(set_id, beatmap)?;
+        }
+        Ok(())
+    }
+
+    /// Flush remaining output and return the number of rows written
+    pub fn finish(mut self) -> Result<usize> {
+        self.writer
+            .flush()
+            .map_err(|e| Error::Other(format!("CSV flush error: {}", e)))?;
+        Ok(self.rows)
+    }
+}
+
 /// Export format options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ExportFormat {
@@ -552,6 +680,47 @@ pub fn export_html(stats: &ComparisonStats, path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::beatmap::{BeatmapFile, BeatmapMetadata};
+
+    #[test]
+    fn test_library_json_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("library.json");
+
+        let mut set = BeatmapSet::new();
+        set.id = Some(42);
+        set.folder_name = Some("42 Artist - Title".to_string());
+        set.beatmaps.push(BeatmapInfo {
+            metadata: BeatmapMetadata {
+                title: "Title".to_string(),
+                artist: "Artist".to_string(),
+                ..Default::default()
+            },
+            version: "Hard".to_string(),
+            star_rating: Some(4.2),
+            ..Default::default()
+        });
+        set.files.push(BeatmapFile {
+            filename: "audio.mp3".to_string(),
+            hash: "abc".to_string(),
+            size: 3,
+        });
+
+        export_library_json(&[set], &path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["schema_version"], LIBRARY_JSON_VERSION);
+
+        let sets = import_library_json(&path).unwrap();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].id, Some(42));
+        assert_eq!(sets[0].beatmaps[0].version, "Hard");
+        assert_eq!(sets[0].beatmaps[0].star_rating, Some(4.2));
+        assert_eq!(sets[0].files[0].filename, "audio.mp3");
+
+        std::fs::write(&path, r#"{"schema_version": 99, "sets": []}"#).unwrap();
+        assert!(import_library_json(&path).is_err());
+    }
 
     #[allow(dead_code)]
     fn create_test_stats() -> ComparisonStats {
@@ -593,4 +762,41 @@ mod tests {
         assert_eq!(format!("{}", ExportFormat::Csv), "CSV");
         assert_eq!(format!("{}", ExportFormat::Html), "HTML");
     }
+
+    #[test]
+    fn test_beatmap_csv_writer_rows() {
+        use crate::beatmap::GameMode;
+        use crate::stats::RankedStatus;
+
+        let mut set = BeatmapSet::new();
+        set.id = Some(42);
+        for (version, stars) in [("Easy", 1.5), ("Insane, Extra", 5.25)] {
+            let mut beatmap = BeatmapInfo {
+                version: version.to_string(),
+                mode: GameMode::Taiko,
+                star_rating: Some(stars),
+                bpm: 180.0,
+                length_ms: 90_000,
+                ranked_status: Some(RankedStatus::Ranked),
+                ..Default::default()
+            };
+            beatmap.metadata.artist = "Artist".to_string();
+            beatmap.metadata.title = "Title".to_string();
+            set.beatmaps.push(beatmap);
+        }
+
+        let mut output = Vec::new();
+        let mut writer = BeatmapCsvWriter::new(&mut output).unwrap();
+        writer.write_set(&set).unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], BEATMAP_CSV_HEADERS.join(","));
+        assert_eq!(lines[1], "42,Artist,Title,Easy,Taiko,1.50,180,90000,Ranked");
+        assert_eq!(
+            lines[2],
+            "42,Artist,Title,\"Insane, Extra\",Taiko,5.25,180,90000,Ranked"
+        );
+    }
 }
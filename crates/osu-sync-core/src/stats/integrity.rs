@@ -0,0 +1,292 @@
+//! Detection of locally edited beatmaps
+//!
+//! osu!.db records the MD5 of every `.osu` file it knows about. Recomputing
+//! those hashes from the Songs folder reveals difficulties that were edited
+//! after import, as well as files osu! has not indexed yet.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use md5::{Digest, Md5};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::hash_cache::{HashAlgorithm, HashCache};
+use crate::lazer::StableDatabase;
+
+/// A `.osu` file as recorded in osu!.db
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbBeatmapRecord {
+    /// Folder name inside Songs
+    pub folder_name: String,
+    /// `.osu` file name inside the folder
+    pub file_name: String,
+    /// MD5 recorded in the database
+    pub md5: String,
+}
+
+/// A single difficulty flagged by the integrity check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrityEntry {
+    /// Folder name inside Songs
+    pub folder_name: String,
+    /// `.osu` file name inside the folder
+    pub file_name: String,
+    /// MD5 recorded in osu!.db, if the file is known to it
+    pub db_md5: Option<String>,
+    /// MD5 of the file on disk, if it exists
+    pub disk_md5: Option<String>,
+}
+
+/// Result of comparing the Songs folder against osu!.db
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Difficulties whose content no longer matches the recorded MD5
+    pub edited: Vec<IntegrityEntry>,
+    /// `.osu` files on disk that osu!.db has no record of
+    pub unknown: Vec<IntegrityEntry>,
+    /// Database records whose `.osu` file no longer exists
+    pub missing: Vec<IntegrityEntry>,
+    /// Number of difficulties whose MD5 matched
+    pub verified: usize,
+}
+
+impl IntegrityReport {
+    /// Whether any edited, unknown or missing beatmap was found
+    pub fn has_findings(&self) -> bool {
+        !self.edited.is_empty() || !self.unknown.is_empty() || !self.missing.is_empty()
+    }
+}
+
+/// Cross-references `.osu` files in Songs with osu!.db hashes
+pub struct IntegrityChecker {
+    songs_path: PathBuf,
+    /// MD5s of unchanged files are taken from here instead of rehashing
+    hash_cache: Option<Arc<HashCache>>,
+}
+
+impl IntegrityChecker {
+    /// Create a checker for the given Songs folder
+    pub fn new(songs_path: impl Into<PathBuf>) -> Self {
+        Self {
+            songs_path: songs_path.into(),
+            hash_cache: None,
+        }
+    }
+
+    /// Reuse MD5s of files unchanged since they were last hashed
+    ///
+    /// The cache is not saved by the checker; call [`HashCache::save`] afterwards.
+    pub fn with_hash_cache(mut self, cache: Arc<HashCache>) -> Self {
+        self.hash_cache = Some(cache);
+        self
+    }
+
+    /// Check the Songs folder against every record in an opened osu!.db
+    pub fn check_database(db: &StableDatabase) -> Result<IntegrityReport> {
+        Self::new(db.songs_path()).check(Self::database_records(db))
+    }
+
+    /// Like [`check_database`](Self::check_database), reusing cached MD5s
+    ///
+    /// Entries for files that were deleted since are dropped from the cache.
+    pub fn check_database_cached(
+        db: &StableDatabase,
+        cache: Arc<HashCache>,
+    ) -> Result<IntegrityReport> {
+        cache.prune();
+        Self::new(db.songs_path())
+            .with_hash_cache(cache)
+            .check(Self::database_records(db))
+    }
+
+    fn database_records(db: &StableDatabase) -> impl Iterator<Item = DbBeatmapRecord> + '_ {
+        let records = db.raw_beatmaps().iter().filter_map(|b| {
+            Some(DbBeatmapRecord {
+                folder_name: b.folder_name.clone()?,
+                file_name: b.file_name.clone()?,
+                md5: b.hash.clone()?,
+            })
+        });
+        records
+    }
+
+    /// Check the Songs folder against the given database records
+    ///
+    /// File names are matched case-insensitively, as osu! does on Windows.
+    pub fn check(
+        &self,
+        records: impl IntoIterator<Item = DbBeatmapRecord>,
+    ) -> Result<IntegrityReport> {
+        if !self.songs_path.exists() {
+            return Err(Error::OsuNotFound(self.songs_path.clone()));
+        }
+
+        let mut on_disk: HashMap<String, (String, String, PathBuf)> = self
+            .osu_files()?
+            .into_iter()
+            .map(|(folder, file, path)| (record_key(&folder, &file), (folder, file, path)))
+            .collect();
+
+        let mut known = Vec::new();
+        let mut report = IntegrityReport::default();
+        for record in records {
+            match on_disk.remove(&record_key(&record.folder_name, &record.file_name)) {
+                Some((_, _, path)) => known.push((record, path)),
+                None => report.missing.push(IntegrityEntry {
+                    folder_name: record.folder_name,
+                    file_name: record.file_name,
+                    db_md5: Some(record.md5),
+                    disk_md5: None,
+                }),
+            }
+        }
+
+        let hashed: Vec<(DbBeatmapRecord, Option<String>)> = known
+            .into_par_iter()
+            .map(|(record, path)| {
+                let md5 = self.md5_file(&path);
+                (record, md5)
+            })
+            .collect();
+        for (record, disk_md5) in hashed {
+            if disk_md5
+                .as_deref()
+                .is_some_and(|md5| md5.eq_ignore_ascii_case(&record.md5))
+            {
+                report.verified += 1;
+            } else {
+                report.edited.push(IntegrityEntry {
+                    folder_name: record.folder_name,
+                    file_name: record.file_name,
+                    db_md5: Some(record.md5),
+                    disk_md5,
+                });
+            }
+        }
+
+        report.unknown = on_disk
+            .into_values()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(folder_name, file_name, path)| IntegrityEntry {
+                folder_name,
+                file_name,
+                db_md5: None,
+                disk_md5: self.md5_file(&path),
+            })
+            .collect();
+
+        for list in [&mut report.edited, &mut report.unknown, &mut report.missing] {
+            list.sort_by(|a, b| {
+                (&a.folder_name, &a.file_name).cmp(&(&b.folder_name, &b.file_name))
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// List every `.osu` file directly inside a Songs subfolder
+    fn osu_files(&self) -> Result<Vec<(String, String, PathBuf)>> {
+        let mut files = Vec::new();
+        for dir in fs::read_dir(&self.songs_path)?.filter_map(|e| e.ok()) {
+            let dir_path = dir.path();
+            if !dir_path.is_dir() {
+                continue;
+            }
+            let folder = dir.file_name().to_string_lossy().to_string();
+            let Ok(entries) = fs::read_dir(&dir_path) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let is_osu = path
+                    .extension()
+                    .map(|ext| ext.eq_ignore_ascii_case("osu"))
+                    .unwrap_or(false);
+                if is_osu && path.is_file() {
+                    let file = entry.file_name().to_string_lossy().to_string();
+                    files.push((folder.clone(), file, path));
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn md5_file(&self, path: &Path) -> Option<String> {
+        match &self.hash_cache {
+            Some(cache) => cache
+                .hash_file(path, HashAlgorithm::Md5)
+                .ok()
+                .map(|h| h.hash),
+            None => md5_file(path),
+        }
+    }
+}
+
+fn record_key(folder: &str, file: &str) -> String {
+    format!("{}/{}", folder, file).to_lowercase()
+}
+
+fn md5_file(path: &Path) -> Option<String> {
+    fs::read(path)
+        .ok()
+        .map(|content| format!("{:x}", Md5::digest(&content)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn md5_of(content: &[u8]) -> String {
+        format!("{:x}", Md5::digest(content))
+    }
+
+    fn record(folder: &str, file: &str, md5: String) -> DbBeatmapRecord {
+        DbBeatmapRecord {
+            folder_name: folder.to_string(),
+            file_name: file.to_string(),
+            md5,
+        }
+    }
+
+    #[test]
+    fn test_integrity_check_categories() {
+        let temp_dir = TempDir::new().unwrap();
+        let songs = temp_dir.path().join("Songs");
+        let set = songs.join("1 Artist - Title");
+        fs::create_dir_all(&set).unwrap();
+
+        fs::write(set.join("Easy.osu"), b"original easy").unwrap();
+        fs::write(set.join("Hard.osu"), b"edited hard").unwrap();
+        fs::write(set.join("New.osu"), b"not imported").unwrap();
+
+        let records = vec![
+            record("1 Artist - Title", "easy.osu", md5_of(b"original easy")),
+            record("1 Artist - Title", "Hard.osu", md5_of(b"original hard")),
+            record("1 Artist - Title", "Gone.osu", md5_of(b"gone")),
+        ];
+
+        let report = IntegrityChecker::new(&songs).check(records).unwrap();
+        assert_eq!(report.verified, 1);
+        assert!(report.has_findings());
+
+        assert_eq!(report.edited.len(), 1);
+        assert_eq!(report.edited[0].file_name, "Hard.osu");
+        assert_eq!(
+            report.edited[0].disk_md5.as_deref(),
+            Some(md5_of(b"edited hard").as_str())
+        );
+
+        assert_eq!(report.unknown.len(), 1);
+        assert_eq!(report.unknown[0].file_name, "New.osu");
+        assert!(report.unknown[0].db_md5.is_none());
+
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].file_name, "Gone.osu");
+    }
+}
@@ -5,8 +5,13 @@
 
 mod analyzer;
 mod export;
+mod integrity;
 mod model;
 
 pub use analyzer::StatsAnalyzer;
-pub use export::{export_csv, export_html, export_json, ExportFormat, HtmlExport};
+pub use export::{
+    export_csv, export_html, export_json, export_library_json, import_library_json,
+    BeatmapCsvWriter, ExportFormat, HtmlExport, BEATMAP_CSV_HEADERS, LIBRARY_JSON_VERSION,
+};
+pub use integrity::{DbBeatmapRecord, IntegrityChecker, IntegrityEntry, IntegrityReport};
 pub use model::*;
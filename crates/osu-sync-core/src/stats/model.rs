@@ -5,6 +5,7 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use super::integrity::IntegrityReport;
 use crate::beatmap::GameMode;
 
 /// Ranked status of a beatmap (matches osu! API values)
@@ -69,6 +70,16 @@ impl ModeCount {
     pub fn total(&self) -> usize {
         self.osu + self.taiko + self.catch + self.mania
     }
+
+    /// Count one beatmap of the given mode
+    pub fn add(&mut self, mode: GameMode) {
+        match mode {
+            GameMode::Osu => self.osu += 1,
+            GameMode::Taiko => self.taiko += 1,
+            GameMode::Catch => self.catch += 1,
+            GameMode::Mania => self.mania += 1,
+        }
+    }
 }
 
 /// Percentage breakdown by game mode
@@ -196,6 +207,8 @@ pub struct ComparisonStats {
     pub mode_breakdown: ModeBreakdown,
     /// Sync recommendations
     pub recommendations: Recommendations,
+    /// Comparison of stable's Songs folder against osu!.db, when available
+    pub integrity: Option<IntegrityReport>,
 }
 
 impl ComparisonStats {
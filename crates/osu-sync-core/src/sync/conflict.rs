@@ -1,16 +1,58 @@
 //! Conflict resolution for beatmap synchronization
 
 use crate::dedup::{DuplicateAction, DuplicateInfo, DuplicateResolution};
+use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
 
 /// Trait for resolving conflicts when duplicate beatmaps are detected
+///
+/// Implement this to plug custom resolution logic into a sync, either by
+/// passing it to [`SyncEngine::sync`](crate::sync::SyncEngine::sync) or by
+/// boxing it for [`SyncEngineBuilder::with_resolver`](crate::sync::SyncEngineBuilder::with_resolver).
+/// Resolvers must be `Send + Sync` because syncs run on a worker thread.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use osu_sync_core::dedup::{DuplicateInfo, DuplicateResolution};
+/// use osu_sync_core::sync::ConflictResolver;
+///
+/// /// Keep whichever copy has more difficulties
+/// struct PreferMoreDifficulties;
+///
+/// impl ConflictResolver for PreferMoreDifficulties {
+///     fn resolve(&self, duplicate: &DuplicateInfo) -> DuplicateResolution {
+///         if duplicate.source.difficulty_count > duplicate.existing.difficulty_count {
+///             DuplicateResolution::replace()
+///         } else {
+///             DuplicateResolution::skip()
+///         }
+///     }
+///
+///     fn name(&self) -> &'static str {
+///         "prefer-more-difficulties"
+///     }
+/// }
+///
+/// let engine = SyncEngineBuilder::new()
+///     .config(config)
+///     .stable_scanner(scanner)
+///     .lazer_database(database)
+///     .with_resolver(Box::new(PreferMoreDifficulties))
+///     .build()?;
+/// let result = engine.run(SyncDirection::StableToLazer)?;
+/// ```
 pub trait ConflictResolver: Send + Sync {
     /// Resolve a conflict for a detected duplicate
     ///
-    /// Returns the resolution action to take for this duplicate.
+    /// `duplicate.source` is the set being imported and `duplicate.existing`
+    /// is the copy already in the target. Returns the resolution action to
+    /// take for this duplicate.
     fn resolve(&self, duplicate: &DuplicateInfo) -> DuplicateResolution;
 
     /// Called when a batch of duplicates needs resolution
     ///
+    /// Returns one resolution per duplicate, in the same order.
     /// Default implementation calls `resolve` for each duplicate.
     fn resolve_batch(&self, duplicates: &[DuplicateInfo]) -> Vec<DuplicateResolution> {
         duplicates.iter().map(|d| self.resolve(d)).collect()
@@ -20,6 +62,20 @@ pub trait ConflictResolver: Send + Sync {
     fn name(&self) -> &'static str;
 }
 
+impl<R: ConflictResolver + ?Sized> ConflictResolver for Box<R> {
+    fn resolve(&self, duplicate: &DuplicateInfo) -> DuplicateResolution {
+        (**self).resolve(duplicate)
+    }
+
+    fn resolve_batch(&self, duplicates: &[DuplicateInfo]) -> Vec<DuplicateResolution> {
+        (**self).resolve_batch(duplicates)
+    }
+
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+}
+
 /// Interactive conflict resolver that prompts the user for each conflict
 ///
 /// This resolver uses a callback function to get user input for each duplicate.
@@ -78,6 +134,11 @@ impl AutoResolver {
     pub fn keep_both() -> Self {
         Self::new(DuplicateAction::KeepBoth)
     }
+
+    /// Create an auto resolver that merges both versions
+    pub fn merge_all() -> Self {
+        Self::new(DuplicateAction::Merge)
+    }
 }
 
 impl ConflictResolver for AutoResolver {
@@ -93,11 +154,20 @@ impl ConflictResolver for AutoResolver {
             DuplicateAction::Skip => "auto-skip",
             DuplicateAction::Replace => "auto-replace",
             DuplicateAction::KeepBoth => "auto-keep-both",
+            DuplicateAction::Merge => "auto-merge",
         }
     }
 }
 
-/// A resolver that remembers decisions and applies them to similar conflicts
+/// A resolver that prefers the higher-quality copy of a duplicate
+///
+/// Decisions are made in this order:
+/// 1. a remembered "apply to all" decision from the callback, if any
+/// 2. the copy with the higher [`QualityScore`](crate::dedup::QualityScore) wins: the source replaces the
+///    existing set if it scores higher, and is skipped if it scores lower.
+///    Scores compare online ID presence, then difficulty count, then total
+///    file size, then folder mtime
+/// 3. on an exact tie, the callback decides
 pub struct SmartResolver<F>
 where
     F: Fn(&DuplicateInfo) -> DuplicateResolution + Send + Sync,
@@ -134,6 +204,13 @@ where
             }
         }
 
+        // Prefer the higher-quality copy
+        match duplicate.source.quality.cmp(&duplicate.existing.quality) {
+            Ordering::Greater => return DuplicateResolution::replace(),
+            Ordering::Less => return DuplicateResolution::skip(),
+            Ordering::Equal => {}
+        }
+
         // Get new resolution from callback
         let resolution = (self.callback)(duplicate);
 
@@ -152,6 +229,125 @@ where
     }
 }
 
+/// Shared log of resolver decisions recorded by [`ObserveResolver`]
+pub type DecisionLog = Arc<Mutex<Vec<(DuplicateInfo, DuplicateAction)>>>;
+
+/// Counts of each action decided by a resolver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecisionSummary {
+    /// Number of duplicates that would be skipped
+    pub skip: usize,
+    /// Number of duplicates that would be replaced
+    pub replace: usize,
+    /// Number of duplicates where both copies would be kept
+    pub keep_both: usize,
+    /// Number of duplicates that would be merged
+    pub merge: usize,
+}
+
+impl DecisionSummary {
+    /// Total number of decisions
+    pub fn total(&self) -> usize {
+        self.skip + self.replace + self.keep_both + self.merge
+    }
+}
+
+/// Resolver wrapper that records every decision made by an inner resolver
+///
+/// The inner resolver still makes the actual decision; this only logs
+/// `(DuplicateInfo, DuplicateAction)` pairs so a dry run can report what a
+/// resolver would do, e.g. "smart would skip 12, replace 3".
+pub struct ObserveResolver<R: ConflictResolver> {
+    inner: R,
+    log: DecisionLog,
+}
+
+impl<R: ConflictResolver> ObserveResolver<R> {
+    /// Wrap a resolver, recording its decisions into a new log
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            log: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Get a handle to the shared decision log
+    ///
+    /// The handle stays valid after the resolver is moved into a sync.
+    pub fn log(&self) -> DecisionLog {
+        Arc::clone(&self.log)
+    }
+
+    /// Get a copy of all recorded decisions
+    pub fn decisions(&self) -> Vec<(DuplicateInfo, DuplicateAction)> {
+        self.log.lock().map(|log| log.clone()).unwrap_or_default()
+    }
+
+    /// Count recorded decisions by action
+    pub fn summary(&self) -> DecisionSummary {
+        summarize_decisions(&self.log)
+    }
+
+    /// Describe recorded decisions, e.g. "smart would skip 12, replace 3"
+    pub fn describe(&self) -> String {
+        let summary = self.summary();
+        let mut parts = Vec::new();
+        if summary.skip > 0 {
+            parts.push(format!("skip {}", summary.skip));
+        }
+        if summary.replace > 0 {
+            parts.push(format!("replace {}", summary.replace));
+        }
+        if summary.keep_both > 0 {
+            parts.push(format!("keep both {}", summary.keep_both));
+        }
+        if summary.merge > 0 {
+            parts.push(format!("merge {}", summary.merge));
+        }
+
+        if parts.is_empty() {
+            format!("{} made no decisions", self.inner.name())
+        } else {
+            format!("{} would {}", self.inner.name(), parts.join(", "))
+        }
+    }
+
+    /// Unwrap the inner resolver
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Count the decisions in a shared log by action
+pub fn summarize_decisions(log: &DecisionLog) -> DecisionSummary {
+    let mut summary = DecisionSummary::default();
+    if let Ok(log) = log.lock() {
+        for (_, action) in log.iter() {
+            match action {
+                DuplicateAction::Skip => summary.skip += 1,
+                DuplicateAction::Replace => summary.replace += 1,
+                DuplicateAction::KeepBoth => summary.keep_both += 1,
+                DuplicateAction::Merge => summary.merge += 1,
+            }
+        }
+    }
+    summary
+}
+
+impl<R: ConflictResolver> ConflictResolver for ObserveResolver<R> {
+    fn resolve(&self, duplicate: &DuplicateInfo) -> DuplicateResolution {
+        let resolution = self.inner.resolve(duplicate);
+        if let Ok(mut log) = self.log.lock() {
+            log.push((duplicate.clone(), resolution.action));
+        }
+        resolution
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
 /// Default resolver that uses configuration to determine action
 pub struct ConfigBasedResolver {
     strategy: crate::config::DuplicateStrategy,
@@ -190,7 +386,7 @@ impl ConflictResolver for ConfigBasedResolver {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dedup::{BeatmapSetRef, MatchType};
+    use crate::dedup::{BeatmapSetRef, MatchType, QualityScore};
 
     fn make_duplicate() -> DuplicateInfo {
         DuplicateInfo {
@@ -200,6 +396,8 @@ mod tests {
                 artist: "Test Artist".to_string(),
                 creator: "Mapper".to_string(),
                 hash: Some("abc123".to_string()),
+                difficulty_count: 4,
+                quality: QualityScore::default(),
             },
             existing: BeatmapSetRef {
                 set_id: Some(123),
@@ -207,9 +405,12 @@ mod tests {
                 artist: "Test Artist".to_string(),
                 creator: "Mapper".to_string(),
                 hash: Some("abc123".to_string()),
+                difficulty_count: 2,
+                quality: QualityScore::default(),
             },
             match_type: MatchType::ExactHash,
             confidence: 1.0,
+            overlap: None,
         }
     }
 
@@ -241,4 +442,122 @@ mod tests {
         let resolution = resolver.resolve(&make_duplicate());
         assert_eq!(resolution.action, DuplicateAction::Replace);
     }
+
+    fn quality(has_online_id: bool, difficulties: usize, size: u64, age_secs: u64) -> QualityScore {
+        QualityScore {
+            has_online_id,
+            difficulty_count: difficulties,
+            total_size: size,
+            modified: Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1000 - age_secs)),
+        }
+    }
+
+    fn smart_action(source: QualityScore, existing: QualityScore) -> DuplicateAction {
+        let resolver = SmartResolver::new(|_| DuplicateResolution::keep_both());
+        let mut duplicate = make_duplicate();
+        duplicate.source.quality = source;
+        duplicate.existing.quality = existing;
+        resolver.resolve(&duplicate).action
+    }
+
+    #[test]
+    fn test_smart_resolver_tie_break_chain() {
+        // Online ID outranks everything else
+        assert_eq!(
+            smart_action(quality(true, 1, 1, 500), quality(false, 9, 999, 0)),
+            DuplicateAction::Replace
+        );
+        // Then difficulty count
+        assert_eq!(
+            smart_action(quality(true, 2, 999, 0), quality(true, 3, 1, 500)),
+            DuplicateAction::Skip
+        );
+        // Then total size
+        assert_eq!(
+            smart_action(quality(true, 3, 200, 500), quality(true, 3, 100, 0)),
+            DuplicateAction::Replace
+        );
+        // Then folder mtime (newer wins)
+        assert_eq!(
+            smart_action(quality(true, 3, 100, 500), quality(true, 3, 100, 0)),
+            DuplicateAction::Skip
+        );
+        // Exact tie defers to the callback
+        assert_eq!(
+            smart_action(quality(true, 3, 100, 0), quality(true, 3, 100, 0)),
+            DuplicateAction::KeepBoth
+        );
+    }
+
+    #[test]
+    fn test_smart_resolver_remembered_decision_wins() {
+        let resolver = SmartResolver::new(|_| DuplicateResolution::keep_both().with_apply_to_all());
+        // First tie asks the callback, which says "apply to all"
+        assert_eq!(
+            resolver.resolve(&make_duplicate()).action,
+            DuplicateAction::KeepBoth
+        );
+
+        let mut duplicate = make_duplicate();
+        duplicate.source.quality = quality(true, 9, 999, 0);
+        assert_eq!(
+            resolver.resolve(&duplicate).action,
+            DuplicateAction::KeepBoth
+        );
+    }
+
+    #[test]
+    fn test_observe_resolver_records_decisions() {
+        let observer = ObserveResolver::new(SmartResolver::new(|_| DuplicateResolution::skip()));
+        let log = observer.log();
+
+        let mut better = make_duplicate();
+        better.source.quality.has_online_id = true;
+
+        observer.resolve(&make_duplicate());
+        observer.resolve(&make_duplicate());
+        let resolution = observer.resolve(&better);
+        assert_eq!(resolution.action, DuplicateAction::Replace);
+
+        assert_eq!(log.lock().unwrap().len(), 3);
+        assert_eq!(
+            observer.summary(),
+            DecisionSummary {
+                skip: 2,
+                replace: 1,
+                keep_both: 0,
+                merge: 0
+            }
+        );
+        assert_eq!(observer.describe(), "smart would skip 2, replace 1");
+        assert_eq!(summarize_decisions(&log).total(), 3);
+    }
+
+    struct PreferMoreDifficulties;
+
+    impl ConflictResolver for PreferMoreDifficulties {
+        fn resolve(&self, duplicate: &DuplicateInfo) -> DuplicateResolution {
+            if duplicate.source.difficulty_count > duplicate.existing.difficulty_count {
+                DuplicateResolution::replace()
+            } else {
+                DuplicateResolution::skip()
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            "prefer-more-difficulties"
+        }
+    }
+
+    #[test]
+    fn test_custom_boxed_resolver() {
+        let resolver: Box<dyn ConflictResolver> = Box::new(PreferMoreDifficulties);
+        let resolution = resolver.resolve(&make_duplicate());
+        assert_eq!(resolution.action, DuplicateAction::Replace);
+        assert_eq!(resolver.name(), "prefer-more-difficulties");
+
+        // Boxed resolvers can be moved to a worker thread
+        let handle = std::thread::spawn(move || resolver.resolve(&make_duplicate()).action);
+        assert_eq!(handle.join().unwrap(), DuplicateAction::Replace);
+    }
 }
@@ -0,0 +1,223 @@
+//! Syncing only some difficulties of a beatmap set
+//!
+//! Each `.osu` file names the media it uses: the audio track, background,
+//! video, storyboard sprites and custom hitsounds. When only some
+//! difficulties are synced, media used solely by the others is left behind.
+
+use std::collections::HashSet;
+
+use crate::beatmap::BeatmapSet;
+use crate::parser::{parse_hitsound_references, parse_storyboard_references, SAMPLE_EXTENSIONS};
+
+/// Reduce a set and its file contents to the given difficulty versions
+///
+/// Versions are matched case-insensitively against each `.osu` file's
+/// `Version`. Files referenced only by excluded difficulties are dropped;
+/// files no difficulty references (such as the `.osb` storyboard or skin
+/// elements) are kept. Returns `None` if no difficulty matched.
+pub fn select_difficulties(
+    set: &BeatmapSet,
+    files: Vec<(String, Vec<u8>)>,
+    versions: &[String],
+) -> Option<(BeatmapSet, Vec<(String, Vec<u8>)>)> {
+    let wanted: HashSet<String> = versions.iter().map(|v| v.to_lowercase()).collect();
+
+    let mut kept_refs = References::default();
+    let mut excluded_refs = References::default();
+    let mut excluded_osu = HashSet::new();
+    let mut any_kept = false;
+
+    for (name, content) in &files {
+        if !is_osu_file(name) {
+            continue;
+        }
+        let content = String::from_utf8_lossy(content);
+        let version = osu_version(&content).unwrap_or_default();
+        if wanted.contains(&version.to_lowercase()) {
+            kept_refs.add(&content);
+            any_kept = true;
+        } else {
+            excluded_refs.add(&content);
+            excluded_osu.insert(normalize(name));
+        }
+    }
+
+    if !any_kept {
+        return None;
+    }
+
+    let files: Vec<(String, Vec<u8>)> = files
+        .into_iter()
+        .filter(|(name, _)| {
+            let name = normalize(name);
+            !excluded_osu.contains(&name)
+                && (kept_refs.contains(&name) || !excluded_refs.contains(&name))
+        })
+        .collect();
+
+    let names: HashSet<String> = files.iter().map(|(name, _)| normalize(name)).collect();
+    let mut selected = set.clone();
+    selected
+        .beatmaps
+        .retain(|b| wanted.contains(&b.version.to_lowercase()));
+    selected
+        .files
+        .retain(|f| names.contains(&normalize(&f.filename)));
+
+    Some((selected, files))
+}
+
+/// Media referenced by a group of `.osu` files
+#[derive(Default)]
+struct References {
+    /// Normalized file paths
+    files: HashSet<String>,
+    /// Implicit hitsound sample names without extension
+    samples: HashSet<String>,
+}
+
+impl References {
+    fn add(&mut self, content: &str) {
+        self.files.extend(media_references(content));
+        self.files.extend(
+            parse_storyboard_references(content)
+                .iter()
+                .map(|p| normalize(p)),
+        );
+
+        let hitsounds = parse_hitsound_references(content);
+        self.files
+            .extend(hitsounds.files.iter().map(|p| normalize(p)));
+        self.samples
+            .extend(hitsounds.samples.iter().map(|s| s.to_lowercase()));
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        if self.files.contains(name) {
+            return true;
+        }
+        match name.rsplit_once('.') {
+            Some((stem, ext)) if SAMPLE_EXTENSIONS.contains(&ext) => self.samples.contains(stem),
+            _ => false,
+        }
+    }
+}
+
+/// The difficulty name from a `.osu` file's `[Metadata]` section
+fn osu_version(content: &str) -> Option<String> {
+    let mut in_metadata = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            in_metadata = line == "[Metadata]";
+            continue;
+        }
+        if in_metadata {
+            if let Some((key, value)) = line.split_once(':') {
+                if key.trim() == "Version" {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Audio, background and video files named by a `.osu` file
+fn media_references(content: &str) -> Vec<String> {
+    let mut refs = Vec::new();
+    let mut section = "";
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line;
+            continue;
+        }
+        match section {
+            "[General]" => {
+                if let Some((key, value)) = line.split_once(':') {
+                    if key.trim() == "AudioFilename" && !value.trim().is_empty() {
+                        refs.push(normalize(value.trim()));
+                    }
+                }
+            }
+            "[Events]" => {
+                // Background: `0,0,"bg.jpg",...`; video: `Video,0,"video.mp4"` or `1,0,...`
+                let mut fields = line.splitn(4, ',');
+                let kind = fields.next().unwrap_or_default().trim();
+                if matches!(kind, "0" | "1" | "Video") {
+                    if let Some(file) = fields.nth(1) {
+                        let file = file.trim().trim_matches('"');
+                        if !file.is_empty() {
+                            refs.push(normalize(file));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    refs
+}
+
+fn is_osu_file(name: &str) -> bool {
+    name.to_lowercase().ends_with(".osu")
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beatmap::BeatmapInfo;
+
+    fn osu(version: &str, video: &str, hitsound: &str) -> Vec<u8> {
+        format!(
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\n\n[Metadata]\nVersion:{}\n\n[Events]\n0,0,\"bg.jpg\",0,0\nVideo,0,\"{}\"\n\n[HitObjects]\n256,192,100,1,0,0:0:0:0:{}\n",
+            version, video, hitsound
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_select_difficulties() {
+        let mut set = BeatmapSet::new();
+        for version in ["Easy", "Expert"] {
+            set.beatmaps.push(BeatmapInfo {
+                version: version.to_string(),
+                ..Default::default()
+            });
+        }
+        let files = vec![
+            ("easy.osu".to_string(), osu("Easy", "intro.mp4", "")),
+            (
+                "expert.osu".to_string(),
+                osu("Expert", "video.mp4", "kick.wav"),
+            ),
+            ("audio.mp3".to_string(), vec![1]),
+            ("bg.jpg".to_string(), vec![2]),
+            ("intro.mp4".to_string(), vec![3]),
+            ("video.mp4".to_string(), vec![4]),
+            ("kick.wav".to_string(), vec![5]),
+            ("storyboard.osb".to_string(), vec![6]),
+        ];
+
+        let (selected, files) = select_difficulties(&set, files, &["expert".to_string()]).unwrap();
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "expert.osu",
+                "audio.mp3",
+                "bg.jpg",
+                "video.mp4",
+                "kick.wav",
+                "storyboard.osb"
+            ]
+        );
+        assert_eq!(selected.beatmaps.len(), 1);
+        assert_eq!(selected.beatmaps[0].version, "Expert");
+
+        assert!(select_difficulties(&set, Vec::new(), &["Insane".to_string()]).is_none());
+    }
+}
@@ -1,10 +1,17 @@
 //! Dry run mode for previewing sync operations without making changes
 
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
 use crate::beatmap::BeatmapSet;
+use crate::dedup::DuplicateDetector;
+use crate::error::{Error, Result};
 use crate::lazer::LazerBeatmapSet;
 
 /// Action that would be taken for a beatmap set during sync
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DryRunAction {
     /// Will be imported to target
     Import,
@@ -25,7 +32,7 @@ impl std::fmt::Display for DryRunAction {
 }
 
 /// A single item in the dry run preview
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DryRunItem {
     /// Online beatmap set ID (if available)
     pub set_id: Option<i32>,
@@ -41,6 +48,8 @@ pub struct DryRunItem {
     pub size_bytes: u64,
     /// Number of difficulties in this set
     pub difficulty_count: usize,
+    /// Number of difficulties that duplicate another .osu in the same set
+    pub redundant_difficulties: usize,
 }
 
 impl DryRunItem {
@@ -54,6 +63,7 @@ impl DryRunItem {
 
         let size_bytes: u64 = set.files.iter().map(|f| f.size).sum();
         let difficulty_count = set.beatmaps.len();
+        let redundant_difficulties = DuplicateDetector::find_intra_set_duplicates(set).len();
 
         Self {
             set_id: set.id,
@@ -63,6 +73,7 @@ impl DryRunItem {
             action,
             size_bytes,
             difficulty_count,
+            redundant_difficulties,
         }
     }
 
@@ -86,6 +97,7 @@ impl DryRunItem {
             action,
             size_bytes,
             difficulty_count,
+            redundant_difficulties: 0,
         }
     }
 
@@ -99,8 +111,58 @@ impl DryRunItem {
     }
 }
 
+/// Number and total size of dry run items sharing an action
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ActionSummary {
+    /// Number of items
+    pub count: usize,
+    /// Combined estimated size in bytes
+    pub size_bytes: u64,
+}
+
+impl ActionSummary {
+    fn add(&mut self, item: &DryRunItem) {
+        self.count += 1;
+        self.size_bytes += item.size_bytes;
+    }
+}
+
+/// Per-action breakdown of a [`DryRunResult`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DryRunSummary {
+    /// Sets that would be imported
+    pub import: ActionSummary,
+    /// Sets already present in the target
+    pub skip: ActionSummary,
+    /// Sets detected as duplicates
+    pub duplicate: ActionSummary,
+    /// Difficulties that duplicate another .osu in the same set
+    pub redundant_difficulties: usize,
+}
+
+impl DryRunSummary {
+    /// Counts for a single action
+    pub fn get(&self, action: DryRunAction) -> ActionSummary {
+        match action {
+            DryRunAction::Import => self.import,
+            DryRunAction::Skip => self.skip,
+            DryRunAction::Duplicate => self.duplicate,
+        }
+    }
+
+    /// Number of items across all actions
+    pub fn total_items(&self) -> usize {
+        self.import.count + self.skip.count + self.duplicate.count
+    }
+
+    /// Size of items across all actions
+    pub fn total_bytes(&self) -> u64 {
+        self.import.size_bytes + self.skip.size_bytes + self.duplicate.size_bytes
+    }
+}
+
 /// Result of a dry run analysis
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DryRunResult {
     /// All items that would be processed
     pub items: Vec<DryRunItem>,
@@ -112,6 +174,8 @@ pub struct DryRunResult {
     pub total_duplicate: usize,
     /// Total size in bytes of items to import
     pub total_size_bytes: u64,
+    /// Total difficulties that duplicate another .osu in the same set
+    pub total_redundant_difficulties: usize,
 }
 
 impl DryRunResult {
@@ -134,6 +198,7 @@ impl DryRunResult {
                 self.total_duplicate += 1;
             }
         }
+        self.total_redundant_difficulties += item.redundant_difficulties;
         self.items.push(item);
     }
 
@@ -142,6 +207,34 @@ impl DryRunResult {
         self.items.len()
     }
 
+    /// Count items and bytes per action in a single pass over the items
+    pub fn summary(&self) -> DryRunSummary {
+        let mut summary = DryRunSummary::default();
+        for item in &self.items {
+            match item.action {
+                DryRunAction::Import => summary.import.add(item),
+                DryRunAction::Skip => summary.skip.add(item),
+                DryRunAction::Duplicate => summary.duplicate.add(item),
+            }
+            summary.redundant_difficulties += item.redundant_difficulties;
+        }
+        summary
+    }
+
+    /// The `n` largest items to import, heaviest first
+    ///
+    /// Items of equal size keep their order in the result.
+    pub fn largest_items(&self, n: usize) -> Vec<&DryRunItem> {
+        let mut imports: Vec<&DryRunItem> = self
+            .items
+            .iter()
+            .filter(|item| item.action == DryRunAction::Import)
+            .collect();
+        imports.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        imports.truncate(n);
+        imports
+    }
+
     /// Format the total size as a human-readable string
     pub fn size_display(&self) -> String {
         format_bytes(self.total_size_bytes)
@@ -164,6 +257,20 @@ impl DryRunResult {
     pub fn has_imports(&self) -> bool {
         self.total_import > 0
     }
+
+    /// Write the result to a JSON file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| Error::Other(format!("Failed to save dry run: {}", e)))?;
+        fs::write(path, json).map_err(|e| Error::io_at(e, path))
+    }
+
+    /// Read a result written by [`save`](Self::save)
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|e| Error::io_at(e, path))?;
+        serde_json::from_str(&json)
+            .map_err(|e| Error::Other(format!("Failed to load dry run: {}", e)))
+    }
 }
 
 /// Format bytes as a human-readable string
@@ -209,6 +316,7 @@ mod tests {
             action: DryRunAction::Import,
             size_bytes: 1024 * 1024, // 1 MB
             difficulty_count: 3,
+            redundant_difficulties: 1,
         });
 
         result.add_item(DryRunItem {
@@ -219,12 +327,72 @@ mod tests {
             action: DryRunAction::Skip,
             size_bytes: 512 * 1024,
             difficulty_count: 1,
+            redundant_difficulties: 0,
         });
 
         assert_eq!(result.total_import, 1);
         assert_eq!(result.total_skip, 1);
         assert_eq!(result.total_duplicate, 0);
         assert_eq!(result.total_size_bytes, 1024 * 1024);
+        assert_eq!(result.total_redundant_difficulties, 1);
         assert!(result.has_imports());
     }
+
+    #[test]
+    fn test_summary_and_largest_items() {
+        let mut result = DryRunResult::new();
+        result.add_item(test_item(1, DryRunAction::Import, 100));
+        result.add_item(test_item(2, DryRunAction::Import, 300));
+        result.add_item(test_item(3, DryRunAction::Skip, 1000));
+        result.add_item(test_item(4, DryRunAction::Import, 200));
+        result.add_item(test_item(5, DryRunAction::Duplicate, 50));
+
+        let summary = result.summary();
+        assert_eq!(
+            summary.import,
+            ActionSummary {
+                count: 3,
+                size_bytes: 600
+            }
+        );
+        assert_eq!(summary.get(DryRunAction::Skip).size_bytes, 1000);
+        assert_eq!(summary.duplicate.count, 1);
+        assert_eq!(summary.total_items(), 5);
+        assert_eq!(summary.total_bytes(), 1650);
+
+        let largest: Vec<_> = result
+            .largest_items(2)
+            .iter()
+            .map(|item| item.set_id)
+            .collect();
+        assert_eq!(largest, vec![Some(2), Some(4)]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut result = DryRunResult::new();
+        result.add_item(test_item(1, DryRunAction::Skip, 1000));
+        result.add_item(test_item(2, DryRunAction::Import, 300));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("dry_run.json");
+        result.save(&path).unwrap();
+        let loaded = DryRunResult::load(&path).unwrap();
+        assert_eq!(loaded.total_items(), 2);
+        assert_eq!(loaded.summary(), result.summary());
+        assert_eq!(loaded.items[1].action, DryRunAction::Import);
+    }
+
+    fn test_item(id: i32, action: DryRunAction, size_bytes: u64) -> DryRunItem {
+        DryRunItem {
+            set_id: Some(id),
+            folder_name: None,
+            title: format!("Title {}", id),
+            artist: "Artist".to_string(),
+            action,
+            size_bytes,
+            difficulty_count: 1,
+            redundant_difficulties: 0,
+        }
+    }
 }
@@ -1,21 +1,47 @@
 //! Main synchronization engine
 
 use rayon::prelude::*;
-use std::collections::HashSet;
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex, OnceLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::beatmap::BeatmapSet;
+use crate::cancel::CancellationToken;
+use crate::collection::{Collection, StableCollectionReader};
 use crate::config::Config;
-use crate::dedup::{DuplicateAction, DuplicateDetector, DuplicateIndex, DuplicateStrategy};
+use crate::dedup::{
+    BeatmapSetRef, DuplicateAction, DuplicateDetector, DuplicateIndex, DuplicateInfo,
+    DuplicateStrategy, FileHashIndex, QualityScore,
+};
 use crate::error::{Error, Result};
 use crate::filter::{FilterCriteria, FilterEngine};
-use crate::lazer::{LazerBeatmapSet, LazerDatabase, LazerImporter};
+use crate::lazer::{original_name, LazerBeatmapSet, LazerDatabase, LazerFileStore, LazerImporter};
 use crate::stable::{StableImporter, StableScanner};
-use crate::sync::conflict::ConflictResolver;
+use crate::sync::conflict::{AutoResolver, ConflictResolver};
+use crate::sync::difficulty_selection::select_difficulties;
 use crate::sync::direction::SyncDirection;
 use crate::sync::dry_run::{DryRunAction, DryRunItem, DryRunResult};
+use crate::sync::event::SyncEvent;
+use crate::sync::game_guard::{running_game, DetectGame, GameGuard, GameWatch};
+use crate::sync::replace::{self, diff_set_files, SetDiff};
+use crate::sync::throttle::{sleep_unless_cancelled, Throttle, ThrottleConfig};
+use crate::sync::trash::{Trash, TrashSource, DEFAULT_TRASH_MAX_AGE};
+use crate::unified::{FileChangeEvent, GameLaunchDetector, OsuGame, UnifiedWatcher};
+use crate::utils::{available_space, same_device, RetryPolicy};
+
+/// How long a new Songs folder must stay quiet before watch mode syncs it
+///
+/// osu! writes many files per import, so a folder is only picked up once
+/// events for it have stopped for this long.
+const WATCH_SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// Number of sets kept in [`SyncResult::slowest_sets`]
+pub const SLOWEST_SETS_TRACKED: usize = 10;
 
 /// Result of a sync operation
 #[derive(Debug, Clone, Default)]
@@ -30,6 +56,14 @@ pub struct SyncResult {
     pub errors: Vec<SyncError>,
     /// Direction of the sync
     pub direction: SyncDirection,
+    /// Bytes that did not need writing because the target already had them
+    pub bytes_reused: u64,
+    /// Bytes of set files written to the target; a replaced set only counts
+    /// the files that changed
+    pub bytes_written: u64,
+    /// Sets that took longest to import, slowest first, at most
+    /// [`SLOWEST_SETS_TRACKED`]
+    pub slowest_sets: Vec<(String, Duration)>,
 }
 
 impl SyncResult {
@@ -56,7 +90,24 @@ impl SyncResult {
         self.imported += other.imported;
         self.skipped += other.skipped;
         self.failed += other.failed;
+        self.bytes_reused += other.bytes_reused;
+        self.bytes_written += other.bytes_written;
         self.errors.extend(other.errors);
+        for (name, elapsed) in other.slowest_sets {
+            self.record_set_time(&name, elapsed);
+        }
+    }
+
+    /// Record how long a set took, keeping only the slowest ones
+    pub fn record_set_time(&mut self, name: &str, elapsed: Duration) {
+        if self.slowest_sets.len() >= SLOWEST_SETS_TRACKED
+            && self.slowest_sets.last().is_some_and(|(_, d)| *d >= elapsed)
+        {
+            return;
+        }
+        let pos = self.slowest_sets.partition_point(|(_, d)| *d >= elapsed);
+        self.slowest_sets.insert(pos, (name.to_string(), elapsed));
+        self.slowest_sets.truncate(SLOWEST_SETS_TRACKED);
     }
 }
 
@@ -79,6 +130,15 @@ impl SyncError {
     }
 }
 
+/// Result of [`SyncEngine::sync_favorites`]
+#[derive(Debug, Clone, Default)]
+pub struct FavoritesSyncResult {
+    /// Outcome of importing the favourite sets
+    pub result: SyncResult,
+    /// MD5s in the Favourites collection with no matching difficulty in Songs
+    pub missing: Vec<String>,
+}
+
 /// Progress information for sync callbacks
 #[derive(Debug, Clone, Default)]
 pub struct SyncProgress {
@@ -96,6 +156,10 @@ pub struct SyncProgress {
     pub elapsed_seconds: u64,
     /// Estimated remaining time in seconds
     pub estimated_remaining_seconds: Option<u64>,
+    /// Large file currently being written within the set, if any
+    pub current_file: Option<String>,
+    /// Fraction of `current_file` written so far (0.0 - 1.0)
+    pub current_file_progress: f32,
 }
 
 /// Phase of the sync operation
@@ -134,13 +198,33 @@ pub struct SyncEngine {
     duplicate_detector: DuplicateDetector,
     duplicate_strategy: DuplicateStrategy,
     progress_callback: Option<ProgressCallback>,
+    /// Optional channel for per-set [`SyncEvent`]s
+    events: Option<Sender<SyncEvent>>,
     filter: Option<FilterCriteria>,
     /// Optional set of beatmap set IDs to sync (for user selection)
     selected_set_ids: Option<HashSet<i32>>,
     /// Optional set of folder names to sync (fallback for sets without IDs)
     selected_folders: Option<HashSet<String>>,
+    /// Optional difficulty versions to sync, by beatmap set ID
+    selected_difficulties: Option<HashMap<i32, Vec<String>>>,
     /// Optional cancellation token for aborting sync
-    cancellation: Option<Arc<AtomicBool>>,
+    cancellation: Option<CancellationToken>,
+    /// Optional conflict resolver used by [`SyncEngine::run`]
+    resolver: Option<Box<dyn ConflictResolver>>,
+    /// How transient errors while writing beatmap files are retried
+    retry: RetryPolicy,
+    /// Hardlink stable files into the lazer store when on the same volume
+    link_files: bool,
+    /// Where files removed by a Replace go; `None` deletes them
+    trash: Option<Trash>,
+    /// Write rate cap for the importers and game pause between sets
+    throttle: Option<ThrottleConfig>,
+    /// What to do while osu! is running; `None` ignores running games
+    game_guard: Option<GameGuard>,
+    /// The running game, checked by the game guard
+    detect_game: DetectGame,
+    /// Free space on the volume holding a path, checked before writing
+    free_space: fn(&Path) -> Option<u64>,
     /// Session-level cache for lazer beatmap sets to avoid repeated database queries
     /// Each query can take 1-3 minutes, so caching provides significant speedup
     lazer_sets_cache: OnceLock<Vec<LazerBeatmapSet>>,
@@ -163,10 +247,20 @@ impl SyncEngine {
             duplicate_detector,
             duplicate_strategy: strategy,
             progress_callback: None,
+            events: None,
             filter: None,
             selected_set_ids: None,
             selected_folders: None,
+            selected_difficulties: None,
             cancellation: None,
+            resolver: None,
+            retry: RetryPolicy::default(),
+            link_files: false,
+            trash: None,
+            throttle: None,
+            game_guard: None,
+            detect_game: running_game,
+            free_space: available_space,
             lazer_sets_cache: OnceLock::new(),
         }
     }
@@ -189,6 +283,15 @@ impl SyncEngine {
         self
     }
 
+    /// Send a [`SyncEvent`] for each beatmap set processed, plus one when a
+    /// sync starts and finishes
+    ///
+    /// Send errors are ignored, so dropping the receiver is harmless.
+    pub fn with_event_sender(mut self, sender: Sender<SyncEvent>) -> Self {
+        self.events = Some(sender);
+        self
+    }
+
     /// Set the filter criteria for syncing
     pub fn with_filter(mut self, filter: FilterCriteria) -> Self {
         if filter.is_empty() {
@@ -229,18 +332,152 @@ impl SyncEngine {
         self
     }
 
+    /// Only sync some difficulties of the given sets
+    ///
+    /// Maps a beatmap set ID to the difficulty versions to sync. Media used
+    /// only by the other difficulties is left out, see
+    /// [`select_difficulties`]. Sets not in the map are synced whole.
+    pub fn with_selected_difficulties(mut self, selection: HashMap<i32, Vec<String>>) -> Self {
+        if selection.is_empty() {
+            self.selected_difficulties = None;
+        } else {
+            self.selected_difficulties = Some(selection);
+        }
+        self
+    }
+
     /// Set a cancellation token for aborting sync operations
-    pub fn with_cancellation(mut self, token: Arc<AtomicBool>) -> Self {
-        self.cancellation = Some(token);
+    ///
+    /// Checked before each beatmap set; a cancelled sync or dry run returns
+    /// [`Error::Cancelled`], while watch mode simply stops.
+    pub fn with_cancellation(mut self, token: impl Into<CancellationToken>) -> Self {
+        self.cancellation = Some(token.into());
+        self
+    }
+
+    /// Set the conflict resolver used by [`SyncEngine::run`]
+    pub fn with_resolver(mut self, resolver: Box<dyn ConflictResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Set how transient errors while writing beatmap files are retried
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Hardlink stable files into the lazer file store instead of letting
+    /// lazer copy them, when both live on the same filesystem
+    ///
+    /// Only affects stable to lazer syncs. Across filesystems files are
+    /// copied as usual.
+    pub fn with_link_instead_of_copy(mut self, link: bool) -> Self {
+        self.link_files = link;
+        self
+    }
+
+    /// Keep the files a Replace overwrites or removes in `trash` instead of
+    /// deleting them
+    ///
+    /// For stable the files are moved out of the set folder. lazer's copies
+    /// stay in its file store, so they are copied out before re-importing.
+    pub fn with_trash(mut self, trash: Trash) -> Self {
+        self.trash = Some(trash);
         self
     }
 
+    /// Limit how fast sets are written, for syncing while playing
+    ///
+    /// The importers sleep between chunks of each file they write so writes
+    /// stay under the cap, large sets included. If configured, the engine
+    /// also waits for running games to close before each set.
+    pub fn with_throttle(mut self, throttle: ThrottleConfig) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Refuse to sync, or wait, while osu!stable or osu!lazer is running
+    ///
+    /// A running game keeps its files open, so writing into them fails with
+    /// lock errors on Windows.
+    pub fn with_game_guard(mut self, guard: GameGuard) -> Self {
+        self.game_guard = Some(guard);
+        self
+    }
+
+    /// Throttle shared by the importers of one sync
+    fn shared_throttle(&self) -> Option<Arc<Throttle>> {
+        self.throttle
+            .map(|config| Arc::new(Throttle::new(config, self.cancellation.clone())))
+    }
+
+    /// The game guard in effect; a throttle that pauses while playing
+    /// implies [`GameGuard::Wait`]
+    fn effective_game_guard(&self) -> Option<GameGuard> {
+        self.game_guard.or_else(|| {
+            self.throttle
+                .filter(|t| t.pause_while_playing)
+                .map(|_| GameGuard::Wait)
+        })
+    }
+
+    /// Apply the game guard before a sync starts
+    fn guard_game_at_start(&self) -> Result<()> {
+        let Some(guard) = self.effective_game_guard() else {
+            return Ok(());
+        };
+        let watch = GameWatch::new(self.detect_game);
+        match (guard, watch.running()) {
+            (_, None) => Ok(()),
+            (GameGuard::Refuse, Some(game)) => Err(Error::GameRunning {
+                game: game.to_string(),
+            }),
+            (GameGuard::Wait, Some(game)) => self.wait_for_game_exit(&watch, game),
+        }
+    }
+
+    /// Watch for games between sets, if the guard waits for them
+    fn game_watch(&self) -> Option<GameWatch> {
+        (self.effective_game_guard() == Some(GameGuard::Wait))
+            .then(|| GameWatch::new(self.detect_game))
+    }
+
+    /// Pause before the next set if a game was started since the last check
+    fn wait_if_game_running(&self, watch: &mut Option<GameWatch>) -> Result<()> {
+        if let Some(watch) = watch {
+            if let Some(game) = watch.poll() {
+                self.wait_for_game_exit(watch, game)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn wait_for_game_exit(&self, watch: &GameWatch, game: OsuGame) -> Result<()> {
+        tracing::info!("{} is running, waiting for it to exit", game);
+        self.emit(SyncEvent::WaitingForGameExit { game });
+        while watch.running().is_some() {
+            sleep_unless_cancelled(
+                GameLaunchDetector::DEFAULT_POLL_INTERVAL,
+                self.cancellation.as_ref(),
+            )?;
+        }
+        tracing::info!("{} exited, resuming sync", game);
+        Ok(())
+    }
+
+    /// Send an event if an event channel is set
+    fn emit(&self, event: SyncEvent) {
+        if let Some(ref events) = self.events {
+            let _ = events.send(event);
+        }
+    }
+
     /// Check if cancellation has been requested
     fn is_cancelled(&self) -> bool {
         self.cancellation
             .as_ref()
-            .map(|c| c.load(Ordering::SeqCst))
-            .unwrap_or(false)
+            .is_some_and(CancellationToken::is_cancelled)
     }
 
     /// Get lazer beatmap sets with session-level caching
@@ -347,6 +584,107 @@ impl SyncEngine {
         }
     }
 
+    /// Apply the difficulty selection for this set, if it has one
+    ///
+    /// Returns `None` when the set has a selection but none of the selected
+    /// difficulties exist in it.
+    fn select_set_difficulties<'a>(
+        &self,
+        set: Cow<'a, BeatmapSet>,
+        files: Vec<(String, Vec<u8>)>,
+    ) -> Option<(Cow<'a, BeatmapSet>, Vec<(String, Vec<u8>)>)> {
+        let versions = set
+            .id
+            .and_then(|id| self.selected_difficulties.as_ref()?.get(&id));
+        match versions {
+            Some(versions) => select_difficulties(&set, files, versions)
+                .map(|(selected, files)| (Cow::Owned(selected), files)),
+            None => Some((set, files)),
+        }
+    }
+
+    /// Report progress through a large file of the set described by `set`
+    fn report_file_progress(&self, set: &SyncProgress, file: &str, fraction: f32) {
+        if self.progress_callback.is_some() {
+            self.report_progress(SyncProgress {
+                current_file: Some(file.to_string()),
+                current_file_progress: fraction,
+                ..set.clone()
+            });
+        }
+    }
+
+    /// Run duplicate detection for every source set before importing
+    ///
+    /// Each source is compared against the whole target library, which takes
+    /// seconds on large libraries, so [`SyncPhase::Deduplicating`] progress is
+    /// reported with processed/total set counts. Returns one entry per source,
+    /// in order.
+    ///
+    /// `sources_on_stable` tells which side the sources are from, so both
+    /// copies of a duplicate are scored with their modification time and
+    /// size on disk.
+    fn detect_duplicates(
+        &self,
+        sources: &[&BeatmapSet],
+        existing: &[BeatmapSet],
+        sources_on_stable: bool,
+    ) -> Result<Vec<Option<DuplicateInfo>>> {
+        let songs_path = self.config.require_stable_songs_path().ok();
+        let file_store = self.lazer_database.file_store();
+        let score = |set: &BeatmapSet, on_stable: bool| match &songs_path {
+            Some(songs_path) if on_stable => QualityScore::from_set_in(set, songs_path),
+            _ if on_stable => QualityScore::from_set(set),
+            _ => QualityScore::from_stored_set(set, file_store),
+        };
+        let total = sources.len();
+        let processed = AtomicUsize::new(0);
+        let start_time = Instant::now();
+        let last_report_millis = AtomicU64::new(0);
+        let file_hashes = FileHashIndex::new(existing);
+
+        let duplicates = sources
+            .par_iter()
+            .map(|source| {
+                if self.is_cancelled() {
+                    return None;
+                }
+                let duplicate = self
+                    .duplicate_detector
+                    .find_duplicate_indexed(source, existing, &file_hashes)
+                    .map(|mut duplicate| {
+                        duplicate.source.quality = score(source, sources_on_stable);
+                        if let Some(idx) = find_existing_set(existing, &duplicate.existing) {
+                            duplicate.existing.quality = score(&existing[idx], !sources_on_stable);
+                        }
+                        duplicate
+                    });
+
+                // Report every 50ms or at completion
+                let current = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                let elapsed_millis = start_time.elapsed().as_millis() as u64;
+                let last = last_report_millis.load(Ordering::Relaxed);
+                if elapsed_millis >= last + 50 || current == total {
+                    last_report_millis.store(elapsed_millis, Ordering::Relaxed);
+                    self.report_progress(SyncProgress {
+                        current,
+                        total,
+                        current_name: "Checking duplicates...".to_string(),
+                        phase: SyncPhase::Deduplicating,
+                        elapsed_seconds: start_time.elapsed().as_secs(),
+                        ..Default::default()
+                    });
+                }
+                duplicate
+            })
+            .collect();
+
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(duplicates)
+    }
+
     /// Perform a dry run to preview what would happen during sync
     ///
     /// This analyzes the source and target installations and determines
@@ -470,6 +808,8 @@ impl SyncEngine {
                 action,
                 size_bytes,
                 difficulty_count: stable_set.beatmaps.len(),
+                redundant_difficulties: DuplicateDetector::find_intra_set_duplicates(stable_set)
+                    .len(),
             };
 
             // Add to results
@@ -506,10 +846,15 @@ impl SyncEngine {
                     items_per_second: items_per_sec,
                     elapsed_seconds: elapsed_secs,
                     estimated_remaining_seconds: estimated_remaining,
+                    ..Default::default()
                 });
             }
         });
 
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
         // Add all items to result
         let items = results_mutex.into_inner().unwrap();
         for item in items {
@@ -540,6 +885,8 @@ impl SyncEngine {
         let stable_sets = self.stable_scanner.scan_parallel()?;
         let stable_index = crate::stable::BeatmapIndex::new(stable_sets);
 
+        let file_hashes = FileHashIndex::new(&stable_index.sets);
+
         // Analyze each lazer set
         for (progress_idx, set_idx) in filtered_indices.iter().enumerate() {
             // Check for cancellation
@@ -549,7 +896,7 @@ impl SyncEngine {
                     progress_idx,
                     total
                 );
-                break;
+                return Err(Error::Cancelled);
             }
 
             let lazer_set = &lazer_sets[*set_idx];
@@ -564,10 +911,11 @@ impl SyncEngine {
             });
 
             // Check for duplicates
-            let action = if let Some(_duplicate) = self
-                .duplicate_detector
-                .find_duplicate(&beatmap_set, &stable_index.sets)
-            {
+            let action = if let Some(_duplicate) = self.duplicate_detector.find_duplicate_indexed(
+                &beatmap_set,
+                &stable_index.sets,
+                &file_hashes,
+            ) {
                 DryRunAction::Duplicate
             } else {
                 // Check if it already exists in stable by ID
@@ -591,7 +939,7 @@ impl SyncEngine {
 
     /// Calculate the total size of files in a stable beatmap set folder
     fn calculate_stable_set_size(&self, beatmap_set: &BeatmapSet) -> u64 {
-        let folder_name = match &beatmap_set.folder_name {
+        let folder_name = match beatmap_set.disk_folder_name() {
             Some(name) => name,
             None => return 0,
         };
@@ -620,13 +968,108 @@ impl SyncEngine {
             .unwrap_or(0)
     }
 
+    /// Perform synchronization using the configured resolver
+    ///
+    /// Falls back to skipping all duplicates when no resolver was set.
+    pub fn run(&self, direction: SyncDirection) -> Result<SyncResult> {
+        match self.resolver.as_deref() {
+            Some(resolver) => self.sync(direction, resolver),
+            None => self.sync(direction, &AutoResolver::skip_all()),
+        }
+    }
+
+    /// Watch stable's Songs folder and sync newly added sets to lazer
+    ///
+    /// Only folders that appear after watching starts are synced, each one
+    /// once it has settled for [`WATCH_SETTLE_TIME`] (see
+    /// [`UnifiedWatcher::new_debounced`]). Filters, selection and
+    /// the configured resolver apply as in [`SyncEngine::run`]. `callback` is
+    /// called with the folder name and result after each set is synced; a
+    /// set that fails to import is reported there as failed and watching
+    /// goes on. Runs until the cancellation token is set.
+    pub fn watch<F>(&self, direction: SyncDirection, mut callback: F) -> Result<()>
+    where
+        F: FnMut(&str, &SyncResult),
+    {
+        if direction != SyncDirection::StableToLazer {
+            return Err(Error::Other(format!(
+                "Watch mode only supports {}",
+                SyncDirection::StableToLazer
+            )));
+        }
+
+        let songs_path = self.stable_scanner.songs_path().to_path_buf();
+        let (mut watcher, rx) = UnifiedWatcher::new_debounced(WATCH_SETTLE_TIME)?;
+        watcher.watch(&songs_path)?;
+
+        let skip_all = AutoResolver::skip_all();
+        let resolver = self.resolver.as_deref().unwrap_or(&skip_all);
+
+        // Existing folders are not "new"; modifying them shouldn't trigger a sync
+        let mut known: HashSet<String> = std::fs::read_dir(&songs_path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+
+        tracing::info!("Watching {} for new beatmap sets", songs_path.display());
+
+        while !self.is_cancelled() {
+            let event = match rx.recv_timeout(Duration::from_millis(250)) {
+                Ok(event) => event,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            // The watcher only reports a folder once it has settled
+            let Some(folder) = new_set_folder(&songs_path, &event) else {
+                continue;
+            };
+            let dir = songs_path.join(&folder);
+            if known.contains(&folder) || !dir.is_dir() {
+                continue;
+            }
+
+            // A failed scan leaves the folder unknown, so a later event for
+            // it (e.g. once an extraction finishes) tries again
+            let set = match self.stable_scanner.scan_beatmap_set(&dir) {
+                Ok(set) => set,
+                Err(e) => {
+                    tracing::warn!("Failed to scan new set {}: {}", folder, e);
+                    continue;
+                }
+            };
+            known.insert(folder.clone());
+
+            let sets = [set];
+            let indices = self.filter_stable_sets(&sets);
+            let result = match self.import_stable_sets_to_lazer(&sets, &indices, resolver) {
+                Ok(result) => result,
+                Err(_) if self.is_cancelled() => break,
+                Err(e) => {
+                    tracing::warn!("Failed to sync new set {}: {}", folder, e);
+                    let mut result = SyncResult::new(direction);
+                    result.failed = 1;
+                    result
+                        .errors
+                        .push(SyncError::new(Some(folder.clone()), e.to_string()));
+                    result
+                }
+            };
+            callback(&folder, &result);
+        }
+
+        Ok(())
+    }
+
     /// Perform synchronization in the specified direction
     pub fn sync(
         &self,
         direction: SyncDirection,
         resolver: &dyn ConflictResolver,
     ) -> Result<SyncResult> {
+        self.guard_game_at_start()?;
         tracing::info!("Starting sync: {}", direction);
+        self.emit(SyncEvent::Started { direction });
 
         let mut result = SyncResult::new(direction);
 
@@ -657,10 +1100,71 @@ impl SyncEngine {
             result.skipped,
             result.failed
         );
+        self.emit(SyncEvent::Finished {
+            imported: result.imported,
+            skipped: result.skipped,
+            failed: result.failed,
+        });
 
         Ok(result)
     }
 
+    /// Sync only the sets in osu!stable's Favourites collection to lazer
+    ///
+    /// The collection is read from `collection.db` in the stable folder and
+    /// matched by MD5 against the scanned Songs folder. Filters and
+    /// selections are ignored: every favourite set that is installed is
+    /// synced, and favourites not found on disk are listed in
+    /// [`FavoritesSyncResult::missing`].
+    pub fn sync_favorites(&self, resolver: &dyn ConflictResolver) -> Result<FavoritesSyncResult> {
+        let collection_db = self.config.require_stable_path()?.join("collection.db");
+        let collections = StableCollectionReader::read(&collection_db)?;
+        let favorites = find_favorites(&collections).ok_or_else(|| {
+            Error::Other(format!(
+                "No Favourites collection in {}",
+                collection_db.display()
+            ))
+        })?;
+
+        tracing::info!(
+            "Syncing {} favourite beatmaps from osu!stable",
+            favorites.beatmap_hashes.len()
+        );
+        let direction = SyncDirection::StableToLazer;
+        self.guard_game_at_start()?;
+        self.emit(SyncEvent::Started { direction });
+
+        self.report_progress(SyncProgress {
+            current: 0,
+            total: 0,
+            current_name: "Scanning osu!stable...".to_string(),
+            phase: SyncPhase::Scanning,
+            ..Default::default()
+        });
+        let stable_sets = self.stable_scanner.scan_parallel()?;
+        let (indices, missing) = match_favorites(&stable_sets, &favorites.beatmap_hashes);
+        for md5 in &missing {
+            tracing::warn!("Favourite beatmap {} is not in the Songs folder", md5);
+        }
+
+        let result = self.import_stable_sets_to_lazer(&stable_sets, &indices, resolver)?;
+
+        self.report_progress(SyncProgress {
+            current: result.total(),
+            total: result.total(),
+            current_name: String::new(),
+            phase: SyncPhase::Complete,
+            ..Default::default()
+        });
+        self.emit(SyncEvent::Finished {
+            imported: result.imported,
+            skipped: result.skipped,
+            failed: result.failed,
+        });
+
+        Ok(FavoritesSyncResult { result, missing })
+    }
+
     /// Sync beatmaps from osu!stable to osu!lazer
     fn sync_stable_to_lazer(&self, resolver: &dyn ConflictResolver) -> Result<SyncResult> {
         let mut result = SyncResult::new(SyncDirection::StableToLazer);
@@ -700,12 +1204,36 @@ impl SyncEngine {
             ..Default::default()
         });
 
+        result.merge(self.import_stable_sets_to_lazer(
+            &stable_sets,
+            &filtered_indices,
+            resolver,
+        )?);
+        Ok(result)
+    }
+
+    /// Import the given stable sets into lazer, resolving duplicates with `resolver`
+    fn import_stable_sets_to_lazer(
+        &self,
+        stable_sets: &[BeatmapSet],
+        filtered_indices: &[usize],
+        resolver: &dyn ConflictResolver,
+    ) -> Result<SyncResult> {
+        let mut result = SyncResult::new(SyncDirection::StableToLazer);
+        let total = filtered_indices.len();
+
         let lazer_sets = self.get_lazer_sets_cached()?;
         let lazer_beatmap_sets: Vec<BeatmapSet> = lazer_sets
             .iter()
             .map(|ls| self.lazer_database.to_beatmap_set(ls))
             .collect();
 
+        let sources: Vec<&BeatmapSet> = filtered_indices
+            .iter()
+            .map(|&idx| &stable_sets[idx])
+            .collect();
+        let duplicates = self.detect_duplicates(&sources, &lazer_beatmap_sets, true)?;
+
         // Phase 3: Import to lazer
         // Use batch mode - create all .osz files first, then trigger lazer once at the end
         let mut lazer_importer = LazerImporter::new(
@@ -714,63 +1242,243 @@ impl SyncEngine {
                 .as_ref()
                 .ok_or(Error::MissingPath { path_type: "Lazer" })?,
         )
-        .batch_mode(); // Don't launch lazer for each beatmap
+        .batch_mode() // Don't launch lazer for each beatmap
+        .with_retry(self.retry)
+        .with_throttle(self.shared_throttle());
+
+        // Fail before writing anything if the sets won't fit. Duplicates
+        // count too, since merging or replacing them writes files as well;
+        // their unchanged files are already in the store and cost nothing.
+        let source_files: Vec<PathBuf> = sources
+            .iter()
+            .flat_map(|set| self.stable_set_file_paths(set))
+            .collect();
+        let needed = estimate_import_size(
+            &source_files,
+            self.lazer_database.file_store(),
+            self.cancellation.as_ref(),
+        )?;
+        self.check_free_space(lazer_importer.data_dir(), needed)?;
+
+        // Hardlinks only work within one filesystem; otherwise lazer copies
+        let link_from = if self.link_files {
+            let songs_path = self.config.require_stable_songs_path()?;
+            if same_device(&songs_path, lazer_importer.data_dir()) {
+                Some(songs_path)
+            } else {
+                tracing::info!("Stable and lazer are on different volumes, copying files");
+                None
+            }
+        } else {
+            None
+        };
+
+        let mut game_watch = self.game_watch();
+        // How many lazer sets reference each blob, counted on the first Replace
+        let mut blob_refs = None;
+        for (progress_idx, (stable_set, duplicate)) in
+            sources.into_iter().zip(duplicates).enumerate()
+        {
+            self.wait_if_game_running(&mut game_watch)?;
 
-        for (progress_idx, set_idx) in filtered_indices.iter().enumerate() {
             // Check for cancellation
             if self.is_cancelled() {
                 tracing::info!("Sync cancelled by user at item {}/{}", progress_idx, total);
-                break;
+                return Err(Error::Cancelled);
             }
 
-            let stable_set = &stable_sets[*set_idx];
             let set_name = stable_set
                 .folder_name
                 .clone()
                 .unwrap_or_else(|| stable_set.generate_folder_name());
 
-            self.report_progress(SyncProgress {
+            let set_progress = SyncProgress {
                 current: progress_idx + 1,
                 total,
                 current_name: set_name.clone(),
                 phase: SyncPhase::Importing,
                 ..Default::default()
-            });
+            };
+            self.report_progress(set_progress.clone());
 
             // Check for duplicates
-            if let Some(duplicate) = self
-                .duplicate_detector
-                .find_duplicate(stable_set, &lazer_beatmap_sets)
-            {
+            let mut merge_with = None;
+            let mut replace = None;
+            if let Some(duplicate) = duplicate {
                 let resolution = resolver.resolve(&duplicate);
 
                 match resolution.action {
                     DuplicateAction::Skip => {
                         tracing::debug!("Skipping duplicate: {}", set_name);
                         result.skipped += 1;
+                        self.emit(SyncEvent::SetSkipped {
+                            name: set_name,
+                            reason: "duplicate".to_string(),
+                        });
                         continue;
                     }
                     DuplicateAction::Replace => {
                         tracing::debug!("Replacing duplicate: {}", set_name);
-                        // For lazer, we just import and let lazer handle the replacement
+                        replace = find_existing_set(&lazer_beatmap_sets, &duplicate.existing);
                     }
                     DuplicateAction::KeepBoth => {
                         tracing::debug!("Keeping both versions: {}", set_name);
                     }
+                    DuplicateAction::Merge => {
+                        tracing::debug!("Merging duplicate: {}", set_name);
+                        // lazer marks the set sharing the imported set's online
+                        // ID for deletion, which is what replaces the old copy;
+                        // without an online ID both copies would stay
+                        merge_with = find_existing_set(&lazer_beatmap_sets, &duplicate.existing)
+                            .filter(|&idx| {
+                                stable_set
+                                    .id
+                                    .or(lazer_beatmap_sets[idx].id)
+                                    .is_some_and(|id| id > 0)
+                            });
+                        if merge_with.is_none() {
+                            result.skipped += 1;
+                            self.emit(SyncEvent::SetSkipped {
+                                name: set_name,
+                                reason: "merge needs an online set ID".to_string(),
+                            });
+                            continue;
+                        }
+                    }
                 }
             }
 
-            // Collect files from the stable folder
-            let files = self.collect_stable_files(stable_set)?;
+            // Time spent waiting on the resolver is not counted
+            let set_start = Instant::now();
+
+            // Collect files from the stable folder, plus the lazer copy's when merging
+            let stable_files = self.collect_stable_files(stable_set)?;
+            // Merged sets may carry lazer's content under a stable filename
+            let link_dir = match (&link_from, stable_set.disk_folder_name()) {
+                (Some(songs_path), Some(folder)) if merge_with.is_none() => {
+                    Some(songs_path.join(folder))
+                }
+                _ => None,
+            };
+            let (import_set, files) = match merge_with {
+                Some(idx) => {
+                    let lazer_set = &lazer_beatmap_sets[idx];
+                    let merged = stable_set.merge(lazer_set);
+                    let lazer_files = self.collect_lazer_files(&lazer_sets[idx], &set_progress)?;
+                    // Contents come from the copy whose difficulties the merge kept
+                    let files = if stable_set.merge_prefers(lazer_set) {
+                        merged_file_contents(&merged, lazer_files, stable_files)
+                    } else {
+                        merged_file_contents(&merged, stable_files, lazer_files)
+                    };
+                    (Cow::Owned(merged), files)
+                }
+                None => (Cow::Borrowed(stable_set), stable_files),
+            };
+            let Some((import_set, files)) = self.select_set_difficulties(import_set, files) else {
+                result.skipped += 1;
+                self.emit(SyncEvent::SetSkipped {
+                    name: set_name,
+                    reason: "no selected difficulties".to_string(),
+                });
+                continue;
+            };
+
+            // A replaced set only archives the files that changed; lazer keeps
+            // the blobs of everything else
+            let mut replaced = None;
+            if let Some(idx) = replace {
+                let existing: Vec<(String, String)> = lazer_sets[idx]
+                    .files
+                    .iter()
+                    .map(|f| (f.filename.clone(), f.hash.clone()))
+                    .collect();
+                let incoming: Vec<(String, String)> = files
+                    .iter()
+                    .map(|(name, content)| (name.clone(), LazerFileStore::calculate_hash(content)))
+                    .collect();
+                let diff = diff_set_files(&existing, &incoming);
+                if diff.is_empty() {
+                    result.skipped += 1;
+                    self.emit(SyncEvent::SetSkipped {
+                        name: set_name,
+                        reason: "unchanged".to_string(),
+                    });
+                    continue;
+                }
+                tracing::debug!(
+                    "Replacing {}: {} changed, {} removed, {} unchanged",
+                    set_name,
+                    diff.changed.len(),
+                    diff.removed.len(),
+                    diff.unchanged
+                );
+
+                if let Some(ref trash) = self.trash {
+                    let folder = lazer_beatmap_sets[idx].generate_folder_name();
+                    if let Err(e) = self.trash_lazer_files(trash, &lazer_sets[idx], &folder, &diff)
+                    {
+                        tracing::error!("Failed to trash old files of {}: {}", set_name, e);
+                        result.failed += 1;
+                        result.record_set_time(&set_name, set_start.elapsed());
+                        self.emit(SyncEvent::SetFailed {
+                            name: set_name.clone(),
+                            error: e.to_string(),
+                        });
+                        result
+                            .errors
+                            .push(SyncError::new(Some(set_name), e.to_string()));
+                        continue;
+                    }
+                }
+                replaced = Some((idx, diff, incoming));
+            }
 
             // Import to lazer
-            match lazer_importer.import_beatmap_set(stable_set, &files) {
+            let total_bytes: u64 = files.iter().map(|(_, c)| c.len() as u64).sum();
+            let reused_before = lazer_importer.reused_bytes();
+            let on_progress =
+                |file: &str, fraction| self.report_file_progress(&set_progress, file, fraction);
+            let import = match replaced {
+                Some((_, ref diff, _)) => {
+                    lazer_importer.import_set_diff(&import_set, &files, diff, on_progress)
+                }
+                None => lazer_importer.import_beatmap_set_with_progress(
+                    &import_set,
+                    &files,
+                    on_progress,
+                ),
+            };
+            match import {
                 Ok(_) => {
+                    if let Some((idx, ref diff, ref incoming)) = replaced {
+                        let refs = blob_refs.get_or_insert_with(|| count_blob_refs(lazer_sets));
+                        let dropped =
+                            self.drop_removed_blobs(&lazer_sets[idx], diff, incoming, refs);
+                        tracing::debug!("Dropped {} unused files of {}", dropped, set_name);
+                    }
+                    if let Some(ref dir) = link_dir {
+                        let linked = lazer_importer.link_into_store(dir, &files);
+                        tracing::debug!(
+                            "Linked {} files of {} into the lazer store",
+                            linked,
+                            set_name
+                        );
+                    }
                     result.imported += 1;
+                    result.bytes_written +=
+                        total_bytes - (lazer_importer.reused_bytes() - reused_before);
+                    result.record_set_time(&set_name, set_start.elapsed());
+                    self.emit(SyncEvent::SetImported { name: set_name });
                 }
                 Err(e) => {
                     tracing::error!("Failed to import {}: {}", set_name, e);
                     result.failed += 1;
+                    result.record_set_time(&set_name, set_start.elapsed());
+                    self.emit(SyncEvent::SetFailed {
+                        name: set_name.clone(),
+                        error: e.to_string(),
+                    });
                     result
                         .errors
                         .push(SyncError::new(Some(set_name), e.to_string()));
@@ -778,6 +1486,8 @@ impl SyncEngine {
             }
         }
 
+        result.bytes_reused = lazer_importer.reused_bytes();
+
         // Trigger lazer to process all pending imports
         if result.imported > 0 {
             match lazer_importer.trigger_batch_import() {
@@ -847,72 +1557,211 @@ impl SyncEngine {
         let stable_sets = self.stable_scanner.scan_parallel()?;
         let stable_index = crate::stable::BeatmapIndex::new(stable_sets);
 
+        let beatmap_sets: Vec<BeatmapSet> = filtered_indices
+            .iter()
+            .map(|&idx| self.lazer_database.to_beatmap_set(&lazer_sets[idx]))
+            .collect();
+        let duplicates = {
+            let sources: Vec<&BeatmapSet> = beatmap_sets.iter().collect();
+            self.detect_duplicates(&sources, &stable_index.sets, false)?
+        };
+
         // Phase 3: Import to stable
-        let stable_importer =
-            StableImporter::new(self.config.stable_songs_path().ok_or(Error::MissingPath {
-                path_type: "Stable",
-            })?);
+        let songs_path = self.config.require_stable_songs_path()?;
+        let stable_importer = StableImporter::new(songs_path.clone())
+            .with_retry(self.retry)
+            .with_throttle(self.shared_throttle());
+
+        // Fail before writing anything if the sets won't fit; stable keeps a
+        // full copy of every set, so there is nothing to dedup. Duplicates
+        // count at full size, the most a merge or replace can write.
+        let file_store = self.lazer_database.file_store();
+        let needed: u64 = filtered_indices
+            .iter()
+            .flat_map(|&idx| &lazer_sets[idx].files)
+            .filter_map(|file| file_store.size(&file.hash))
+            .sum();
+        self.check_free_space(&songs_path, needed)?;
+
+        let sets = filtered_indices.iter().zip(beatmap_sets).zip(duplicates);
+        let mut game_watch = self.game_watch();
+        for (progress_idx, ((set_idx, beatmap_set), duplicate)) in sets.enumerate() {
+            self.wait_if_game_running(&mut game_watch)?;
 
-        for (progress_idx, set_idx) in filtered_indices.iter().enumerate() {
             // Check for cancellation
             if self.is_cancelled() {
                 tracing::info!("Sync cancelled by user at item {}/{}", progress_idx, total);
-                break;
+                return Err(Error::Cancelled);
             }
 
             let lazer_set = &lazer_sets[*set_idx];
-            let beatmap_set = self.lazer_database.to_beatmap_set(lazer_set);
             let set_name = beatmap_set.generate_folder_name();
 
-            self.report_progress(SyncProgress {
+            let set_progress = SyncProgress {
                 current: progress_idx + 1,
                 total,
                 current_name: set_name.clone(),
                 phase: SyncPhase::Importing,
                 ..Default::default()
-            });
+            };
+            self.report_progress(set_progress.clone());
 
             // Check for duplicates
-            if let Some(duplicate) = self
-                .duplicate_detector
-                .find_duplicate(&beatmap_set, &stable_index.sets)
-            {
+            let mut merge_into = None;
+            let mut replace = None;
+            if let Some(duplicate) = duplicate {
                 let resolution = resolver.resolve(&duplicate);
 
                 match resolution.action {
                     DuplicateAction::Skip => {
                         tracing::debug!("Skipping duplicate: {}", set_name);
                         result.skipped += 1;
+                        self.emit(SyncEvent::SetSkipped {
+                            name: set_name,
+                            reason: "duplicate".to_string(),
+                        });
                         continue;
                     }
                     DuplicateAction::Replace => {
                         tracing::debug!("Replacing duplicate: {}", set_name);
-                        // Would need to delete existing folder first
+                        replace = find_existing_set(&stable_index.sets, &duplicate.existing)
+                            .map(|idx| &stable_index.sets[idx]);
                     }
                     DuplicateAction::KeepBoth => {
                         tracing::debug!("Keeping both versions: {}", set_name);
                     }
+                    DuplicateAction::Merge => {
+                        tracing::debug!("Merging duplicate: {}", set_name);
+                        merge_into = find_existing_set(&stable_index.sets, &duplicate.existing)
+                            .map(|idx| &stable_index.sets[idx]);
+                    }
                 }
             }
 
+            // Time spent waiting on the resolver is not counted
+            let set_start = Instant::now();
+
             // Collect files from lazer file store
-            let files = self.collect_lazer_files(lazer_set)?;
+            let files = self.collect_lazer_files(lazer_set, &set_progress)?;
+            let Some((beatmap_set, files)) =
+                self.select_set_difficulties(Cow::Owned(beatmap_set), files)
+            else {
+                result.skipped += 1;
+                self.emit(SyncEvent::SetSkipped {
+                    name: set_name,
+                    reason: "no selected difficulties".to_string(),
+                });
+                continue;
+            };
 
-            // Import to stable
-            match stable_importer.import_files(&files, &beatmap_set) {
+            // Import to stable; a merge adds the missing files to the existing folder
+            // A replaced set only has its changed files rewritten
+            if let Some(existing) = replace {
+                let folder_name = existing
+                    .disk_folder_name()
+                    .map(OsStr::to_os_string)
+                    .unwrap_or_else(|| existing.generate_folder_name().into());
+                let patch = stable_importer
+                    .folder_hashes(&folder_name)
+                    .and_then(|hashes| {
+                        let incoming: Vec<(String, String)> = files
+                            .iter()
+                            .map(|(name, content)| {
+                                (name.clone(), blake3::hash(content).to_hex().to_string())
+                            })
+                            .collect();
+                        let diff = diff_set_files(&hashes, &incoming);
+                        if diff.is_empty() {
+                            return Ok(None);
+                        }
+                        stable_importer
+                            .apply_diff(&files, &folder_name, &diff, self.trash.as_ref())
+                            .map(Some)
+                    });
+                match patch {
+                    Ok(Some(written)) => {
+                        result.imported += 1;
+                        result.bytes_written += written;
+                        result.record_set_time(&set_name, set_start.elapsed());
+                        self.emit(SyncEvent::SetImported { name: set_name });
+                    }
+                    Ok(None) => {
+                        result.skipped += 1;
+                        self.emit(SyncEvent::SetSkipped {
+                            name: set_name,
+                            reason: "unchanged".to_string(),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to replace {}: {}", set_name, e);
+                        result.failed += 1;
+                        result.record_set_time(&set_name, set_start.elapsed());
+                        self.emit(SyncEvent::SetFailed {
+                            name: set_name.clone(),
+                            error: e.to_string(),
+                        });
+                        result
+                            .errors
+                            .push(SyncError::new(Some(set_name), e.to_string()));
+                    }
+                }
+                continue;
+            }
+
+            let (import, written) = match merge_into {
+                Some(existing) => {
+                    let merged = existing.merge(&beatmap_set);
+                    let files = merged_file_contents(&merged, Vec::new(), files);
+                    let folder_name = existing
+                        .disk_folder_name()
+                        .map(OsStr::to_os_string)
+                        .unwrap_or_else(|| existing.generate_folder_name().into());
+                    // merge_files leaves files the folder already has untouched
+                    let folder = songs_path.join(&folder_name);
+                    let written = files
+                        .iter()
+                        .filter(|(name, _)| !folder.join(name).exists())
+                        .map(|(_, content)| content.len() as u64)
+                        .sum();
+                    (stable_importer.merge_files(&files, &folder_name), written)
+                }
+                None => (
+                    stable_importer.import_files_with_progress(
+                        &files,
+                        &beatmap_set,
+                        |file, fraction| self.report_file_progress(&set_progress, file, fraction),
+                    ),
+                    files.iter().map(|(_, c)| c.len() as u64).sum(),
+                ),
+            };
+            match import {
                 Ok(import_result) => {
                     if import_result.success {
                         result.imported += 1;
+                        result.bytes_written += written;
+                        result.record_set_time(&set_name, set_start.elapsed());
+                        self.emit(SyncEvent::SetImported { name: set_name });
                     } else {
                         result.skipped += 1;
-                        if let Some(error) = import_result.error {
+                        if let Some(ref error) = import_result.error {
                             tracing::debug!("Skipped {}: {}", set_name, error);
                         }
+                        self.emit(SyncEvent::SetSkipped {
+                            name: set_name,
+                            reason: import_result
+                                .error
+                                .unwrap_or_else(|| "not imported".to_string()),
+                        });
                     }
                 }
                 Err(e) => {
                     tracing::error!("Failed to import {}: {}", set_name, e);
                     result.failed += 1;
+                    result.record_set_time(&set_name, set_start.elapsed());
+                    self.emit(SyncEvent::SetFailed {
+                        name: set_name.clone(),
+                        error: e.to_string(),
+                    });
                     result
                         .errors
                         .push(SyncError::new(Some(set_name), e.to_string()));
@@ -923,16 +1772,52 @@ impl SyncEngine {
         Ok(result)
     }
 
+    /// Paths of the files in a stable set's folder, as read by
+    /// [`collect_stable_files`](Self::collect_stable_files)
+    fn stable_set_file_paths(&self, beatmap_set: &BeatmapSet) -> Vec<PathBuf> {
+        let (Some(folder_name), Some(songs_path)) = (
+            beatmap_set.disk_folder_name(),
+            self.config.stable_songs_path(),
+        ) else {
+            return Vec::new();
+        };
+        std::fs::read_dir(songs_path.join(folder_name))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().map(|ft| ft.is_file()).unwrap_or(false))
+                    .map(|e| e.path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Fail with [`Error::InsufficientSpace`] if `needed` bytes won't fit on
+    /// the volume holding `target`
+    ///
+    /// Passes if the free space can't be determined.
+    fn check_free_space(&self, target: &Path, needed: u64) -> Result<()> {
+        match (self.free_space)(target) {
+            Some(available) if available < needed => {
+                tracing::error!(
+                    "{} needs {} bytes, only {} available",
+                    target.display(),
+                    needed,
+                    available
+                );
+                Err(Error::InsufficientSpace { needed, available })
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Collect files from a stable beatmap folder (parallel I/O for 2-3x speedup)
     fn collect_stable_files(&self, beatmap_set: &BeatmapSet) -> Result<Vec<(String, Vec<u8>)>> {
         let folder_name = beatmap_set
-            .folder_name
-            .as_ref()
+            .disk_folder_name()
             .ok_or_else(|| Error::Other("Beatmap set has no folder name".to_string()))?;
 
-        let songs_path = self.config.stable_songs_path().ok_or(Error::MissingPath {
-            path_type: "Stable",
-        })?;
+        let songs_path = self.config.require_stable_songs_path()?;
 
         let folder_path = songs_path.join(folder_name);
 
@@ -956,27 +1841,122 @@ impl SyncEngine {
         Ok(files)
     }
 
-    /// Collect files from the lazer file store (parallel I/O)
+    /// Delete the blobs of files a replaced lazer set dropped
+    ///
+    /// Blobs that another set or the new version still uses are kept.
+    /// lazer would eventually clean up unreferenced blobs itself, so failures
+    /// are only logged. Returns the number of blobs deleted.
+    fn drop_removed_blobs(
+        &self,
+        lazer_set: &LazerBeatmapSet,
+        diff: &SetDiff,
+        incoming: &[(String, String)],
+        blob_refs: &HashMap<String, usize>,
+    ) -> usize {
+        let removed: HashSet<String> = diff
+            .removed
+            .iter()
+            .map(|name| replace::normalize(name))
+            .collect();
+        let kept: HashSet<&str> = incoming.iter().map(|(_, hash)| hash.as_str()).collect();
+        let own_refs = |hash: &str| {
+            lazer_set
+                .files
+                .iter()
+                .filter(|f| f.hash.eq_ignore_ascii_case(hash))
+                .count()
+        };
+
+        let file_store = self.lazer_database.file_store();
+        let mut dropped = 0;
+        for named_file in &lazer_set.files {
+            let hash = named_file.hash.to_lowercase();
+            if !removed.contains(&replace::normalize(&named_file.filename))
+                || kept.contains(hash.as_str())
+                || blob_refs.get(&hash).copied().unwrap_or(0) > own_refs(&hash)
+            {
+                continue;
+            }
+            match file_store.remove(&hash) {
+                Ok(()) => dropped += 1,
+                Err(e) => tracing::warn!("Failed to drop {}: {}", named_file.filename, e),
+            }
+        }
+        dropped
+    }
+
+    /// Copy the files of a lazer set that `diff` overwrites or drops to the
+    /// trash
+    fn trash_lazer_files(
+        &self,
+        trash: &Trash,
+        lazer_set: &LazerBeatmapSet,
+        folder: &str,
+        diff: &SetDiff,
+    ) -> Result<()> {
+        let replaced: HashSet<String> = diff
+            .changed
+            .iter()
+            .chain(&diff.removed)
+            .map(|name| replace::normalize(name))
+            .collect();
+        let file_store = self.lazer_database.file_store();
+        let mut files = Vec::new();
+        for named_file in &lazer_set.files {
+            if replaced.contains(&replace::normalize(&named_file.filename)) {
+                files.push((
+                    named_file.filename.clone(),
+                    file_store.read(&named_file.hash)?,
+                ));
+            }
+        }
+        trash.store_files(TrashSource::Lazer, folder, &files)
+    }
+
+    /// Read a lazer set's files from the file store in parallel, reporting
+    /// progress through large files to the set described by `set_progress`
+    ///
+    /// Files are named after their original names with `/` separators.
+    /// Files whose name would escape the set folder, or that cannot be read,
+    /// are skipped with a warning.
     fn collect_lazer_files(
         &self,
         lazer_set: &crate::lazer::LazerBeatmapSet,
+        set_progress: &SyncProgress,
     ) -> Result<Vec<(String, Vec<u8>)>> {
         let file_store = self.lazer_database.file_store();
 
-        // Read files in parallel using rayon
         let files: Vec<_> = lazer_set
             .files
             .par_iter()
-            .filter_map(|named_file| match file_store.read(&named_file.hash) {
-                Ok(content) => Some((named_file.filename.clone(), content)),
-                Err(e) => {
+            .filter_map(|named_file| {
+                let Some(name) = original_name(&named_file.filename) else {
                     tracing::warn!(
-                        "Failed to read file {} ({}): {}",
-                        named_file.filename,
+                        "Skipping lazer file {} with unusable name '{}'",
                         named_file.hash,
-                        e
+                        named_file.filename
                     );
-                    None
+                    return None;
+                };
+                let size = file_store.size(&named_file.hash).unwrap_or(0);
+                let read = file_store.read_with_progress(&named_file.hash, |read| {
+                    // Files read in one chunk finish too quickly to show
+                    if size > LAZER_READ_PROGRESS_MIN_BYTES {
+                        let fraction = read as f32 / size as f32;
+                        self.report_file_progress(set_progress, &name, fraction);
+                    }
+                });
+                match read {
+                    Ok(content) => Some((name, content)),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to read file {} ({}): {}",
+                            named_file.filename,
+                            named_file.hash,
+                            e
+                        );
+                        None
+                    }
                 }
             })
             .collect();
@@ -985,6 +1965,163 @@ impl SyncEngine {
     }
 }
 
+/// Smallest lazer file whose read progress is reported
+const LAZER_READ_PROGRESS_MIN_BYTES: u64 = 1024 * 1024;
+
+/// Number of lazer sets' files that reference each blob, keyed by lowercase hash
+fn count_blob_refs(lazer_sets: &[LazerBeatmapSet]) -> HashMap<String, usize> {
+    let mut refs = HashMap::new();
+    for named_file in lazer_sets.iter().flat_map(|set| &set.files) {
+        *refs.entry(named_file.hash.to_lowercase()).or_insert(0) += 1;
+    }
+    refs
+}
+
+/// Bytes that importing `files` into lazer's content-addressed store adds
+///
+/// Content already in `store`, or seen earlier in `files`, adds nothing.
+/// Only files whose size matches a stored file or another file in `files`
+/// can share content, so only those are hashed (with SHA-256, as lazer
+/// stores them); the import hashes everything again anyway. Files that
+/// can't be read count at full size.
+fn estimate_import_size(
+    files: &[PathBuf],
+    store: &LazerFileStore,
+    cancellation: Option<&CancellationToken>,
+) -> Result<u64> {
+    let sizes: Vec<u64> = files
+        .iter()
+        .map(|path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+        .collect();
+    let mut size_counts: HashMap<u64, usize> = HashMap::new();
+    for &size in &sizes {
+        *size_counts.entry(size).or_insert(0) += 1;
+    }
+    let stored_sizes = store.file_sizes()?;
+
+    let hashed: Vec<(Option<String>, u64)> = files
+        .par_iter()
+        .zip(sizes.par_iter())
+        .map(|(path, &size)| {
+            if cancellation.is_some_and(|c| c.is_cancelled()) {
+                return (None, 0);
+            }
+            let may_share = size_counts[&size] > 1 || stored_sizes.contains(&size);
+            let hash = if may_share {
+                LazerFileStore::calculate_file_hash(path).ok()
+            } else {
+                None
+            };
+            (hash, size)
+        })
+        .collect();
+    if cancellation.is_some_and(|c| c.is_cancelled()) {
+        return Err(Error::Cancelled);
+    }
+
+    let mut seen = HashSet::new();
+    Ok(hashed
+        .into_iter()
+        .filter(|(hash, _)| match hash {
+            Some(hash) => seen.insert(hash.clone()) && !store.exists(hash),
+            None => true,
+        })
+        .map(|(_, size)| size)
+        .sum())
+}
+
+/// Find the set a [`DuplicateInfo`](crate::dedup::DuplicateInfo) refers to
+fn find_existing_set(sets: &[BeatmapSet], existing: &BeatmapSetRef) -> Option<usize> {
+    sets.iter().position(|set| {
+        set.id == existing.set_id
+            && set.beatmaps.first().map(|b| &b.md5_hash) == existing.hash.as_ref()
+    })
+}
+
+/// Pick file contents for each file of a merged set
+///
+/// `primary` wins when both lists have a file of the same name. Files that
+/// the merge dropped (e.g. a second copy of a difficulty) are left out.
+fn merged_file_contents(
+    merged: &BeatmapSet,
+    primary: Vec<(String, Vec<u8>)>,
+    secondary: Vec<(String, Vec<u8>)>,
+) -> Vec<(String, Vec<u8>)> {
+    let keep: HashSet<String> = merged
+        .files
+        .iter()
+        .map(|f| f.filename.to_lowercase())
+        .collect();
+    let mut seen = HashSet::new();
+    primary
+        .into_iter()
+        .chain(secondary)
+        .filter(|(name, _)| {
+            let name = name.to_lowercase();
+            (keep.is_empty() || keep.contains(&name)) && seen.insert(name)
+        })
+        .collect()
+}
+
+#[cfg(feature = "tokio")]
+impl SyncEngine {
+    /// Run a sync on tokio's blocking pool, see [`SyncEngine::run`]
+    ///
+    /// Progress is sent to `progress` if given, replacing any callback set
+    /// with [`SyncEngine::with_progress_callback`].
+    pub async fn sync_async(
+        mut self,
+        direction: SyncDirection,
+        progress: Option<tokio::sync::mpsc::UnboundedSender<SyncProgress>>,
+    ) -> Result<SyncResult> {
+        if let Some(tx) = progress {
+            self.progress_callback = Some(Box::new(move |update| {
+                let _ = tx.send(update);
+            }));
+        }
+        tokio::task::spawn_blocking(move || self.run(direction)).await?
+    }
+}
+
+/// Top-level Songs folder affected by a watcher event, if any
+fn new_set_folder(songs_path: &Path, event: &FileChangeEvent) -> Option<String> {
+    if matches!(event, FileChangeEvent::Deleted { .. }) {
+        return None;
+    }
+    let relative = event.path().strip_prefix(songs_path).ok()?;
+    let folder = relative.components().next()?;
+    Some(folder.as_os_str().to_string_lossy().to_string())
+}
+
+/// osu!stable's Favourites collection, matched by name in either spelling
+fn find_favorites(collections: &[Collection]) -> Option<&Collection> {
+    Collection::find(collections, "Favourites")
+}
+
+/// Indices of sets containing any of `md5s`, and the MD5s no set contains
+fn match_favorites(sets: &[BeatmapSet], md5s: &[String]) -> (Vec<usize>, Vec<String>) {
+    let wanted: HashSet<String> = md5s.iter().map(|h| h.to_lowercase()).collect();
+    let mut found = HashSet::new();
+    let mut indices = Vec::new();
+    for (idx, set) in sets.iter().enumerate() {
+        let mut matched = false;
+        for beatmap in &set.beatmaps {
+            let md5 = beatmap.md5_hash.to_lowercase();
+            if wanted.contains(&md5) {
+                found.insert(md5);
+                matched = true;
+            }
+        }
+        if matched {
+            indices.push(idx);
+        }
+    }
+
+    let mut missing: Vec<String> = wanted.difference(&found).cloned().collect();
+    missing.sort();
+    (indices, missing)
+}
+
 /// Builder for creating a SyncEngine with options
 pub struct SyncEngineBuilder {
     config: Option<Config>,
@@ -992,9 +2129,20 @@ pub struct SyncEngineBuilder {
     lazer_database: Option<LazerDatabase>,
     duplicate_strategy: DuplicateStrategy,
     progress_callback: Option<ProgressCallback>,
+    events: Option<Sender<SyncEvent>>,
     selected_set_ids: Option<HashSet<i32>>,
     selected_folders: Option<HashSet<String>>,
-    cancellation: Option<Arc<AtomicBool>>,
+    selected_difficulties: Option<HashMap<i32, Vec<String>>>,
+    cancellation: Option<CancellationToken>,
+    resolver: Option<Box<dyn ConflictResolver>>,
+    retry: Option<RetryPolicy>,
+    link_instead_of_copy: bool,
+    trash: Option<Trash>,
+    /// The default trash was requested, but there is no app data directory
+    trash_root_missing: bool,
+    trash_max_age: Duration,
+    throttle: Option<ThrottleConfig>,
+    game_guard: Option<GameGuard>,
 }
 
 impl SyncEngineBuilder {
@@ -1006,9 +2154,19 @@ impl SyncEngineBuilder {
             lazer_database: None,
             duplicate_strategy: DuplicateStrategy::default(),
             progress_callback: None,
+            events: None,
             selected_set_ids: None,
             selected_folders: None,
+            selected_difficulties: None,
             cancellation: None,
+            resolver: None,
+            retry: None,
+            link_instead_of_copy: false,
+            trash: None,
+            trash_root_missing: false,
+            trash_max_age: DEFAULT_TRASH_MAX_AGE,
+            throttle: None,
+            game_guard: None,
         }
     }
 
@@ -1042,6 +2200,12 @@ impl SyncEngineBuilder {
         self
     }
 
+    /// Set the channel that receives [`SyncEvent`]s
+    pub fn event_sender(mut self, sender: Sender<SyncEvent>) -> Self {
+        self.events = Some(sender);
+        self
+    }
+
     /// Set selected beatmap set IDs for user selection
     pub fn selected_set_ids(mut self, set_ids: HashSet<i32>) -> Self {
         if set_ids.is_empty() {
@@ -1062,14 +2226,93 @@ impl SyncEngineBuilder {
         self
     }
 
+    /// Only sync the given difficulty versions of each listed set, see
+    /// [`SyncEngine::with_selected_difficulties`]
+    pub fn selected_difficulties(mut self, selection: HashMap<i32, Vec<String>>) -> Self {
+        self.selected_difficulties = Some(selection);
+        self
+    }
+
     /// Set a cancellation token for aborting sync operations
-    pub fn cancellation(mut self, token: Arc<AtomicBool>) -> Self {
-        self.cancellation = Some(token);
+    pub fn cancellation(mut self, token: impl Into<CancellationToken>) -> Self {
+        self.cancellation = Some(token.into());
+        self
+    }
+
+    /// Set a custom conflict resolver, used by [`SyncEngine::run`]
+    pub fn with_resolver(mut self, resolver: Box<dyn ConflictResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Retry transient write errors up to `attempts` times in total,
+    /// waiting `backoff` before the first retry and doubling it each time
+    ///
+    /// Defaults to [`RetryPolicy::default`]; pass `1` to disable retries.
+    pub fn retry(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.retry = Some(RetryPolicy::new(attempts, backoff));
+        self
+    }
+
+    /// Hardlink stable files into the lazer store instead of copying them
+    /// when both are on the same filesystem, see
+    /// [`SyncEngine::with_link_instead_of_copy`]
+    pub fn link_instead_of_copy(mut self, link: bool) -> Self {
+        self.link_instead_of_copy = link;
+        self
+    }
+
+    /// Move files removed by a Replace to a timestamped folder under
+    /// [`Trash::default_root`] instead of deleting them, see
+    /// [`SyncEngine::with_trash`]
+    ///
+    /// [`build`](Self::build) fails if there is no app data directory to
+    /// hold the trash, rather than deleting replaced files.
+    pub fn trash_replaced(mut self, enabled: bool) -> Self {
+        let root = if enabled { Trash::default_root() } else { None };
+        self.trash_root_missing = enabled && root.is_none();
+        self.trash = root.map(Trash::new);
+        self
+    }
+
+    /// Keep replaced files in a custom trash
+    pub fn trash(mut self, trash: Trash) -> Self {
+        self.trash = Some(trash);
+        self.trash_root_missing = false;
+        self
+    }
+
+    /// How long trashed files are kept before the next sync deletes them
+    ///
+    /// Defaults to [`DEFAULT_TRASH_MAX_AGE`].
+    pub fn trash_max_age(mut self, max_age: Duration) -> Self {
+        self.trash_max_age = max_age;
+        self
+    }
+
+    /// Cap the write rate and optionally pause while a game runs, see
+    /// [`SyncEngine::with_throttle`]
+    pub fn throttle(mut self, throttle: ThrottleConfig) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Refuse to sync, or wait, while osu! is running, see
+    /// [`SyncEngine::with_game_guard`]
+    pub fn game_guard(mut self, guard: GameGuard) -> Self {
+        self.game_guard = Some(guard);
         self
     }
 
     /// Build the sync engine
     pub fn build(self) -> Result<SyncEngine> {
+        if self.trash_root_missing {
+            return Err(Error::Config(
+                "No app data directory to keep replaced files in; choose a trash folder"
+                    .to_string(),
+            ));
+        }
+
         let config = self.config.ok_or(Error::MissingComponent {
             component: "Config",
         })?;
@@ -1083,12 +2326,17 @@ impl SyncEngineBuilder {
         })?;
 
         let mut engine = SyncEngine::new(config, stable_scanner, lazer_database)
-            .with_duplicate_strategy(self.duplicate_strategy);
+            .with_duplicate_strategy(self.duplicate_strategy)
+            .with_link_instead_of_copy(self.link_instead_of_copy);
 
         if let Some(callback) = self.progress_callback {
             engine = engine.with_progress_callback(callback);
         }
 
+        if let Some(sender) = self.events {
+            engine = engine.with_event_sender(sender);
+        }
+
         if let Some(set_ids) = self.selected_set_ids {
             engine = engine.with_selected_set_ids(set_ids);
         }
@@ -1097,10 +2345,39 @@ impl SyncEngineBuilder {
             engine = engine.with_selected_folders(folders);
         }
 
+        if let Some(selection) = self.selected_difficulties {
+            engine = engine.with_selected_difficulties(selection);
+        }
+
         if let Some(token) = self.cancellation {
             engine = engine.with_cancellation(token);
         }
 
+        if let Some(resolver) = self.resolver {
+            engine = engine.with_resolver(resolver);
+        }
+
+        if let Some(retry) = self.retry {
+            engine = engine.with_retry_policy(retry);
+        }
+
+        if let Some(throttle) = self.throttle {
+            engine = engine.with_throttle(throttle);
+        }
+
+        if let Some(guard) = self.game_guard {
+            engine = engine.with_game_guard(guard);
+        }
+
+        if let Some(trash) = self.trash {
+            match trash.prune(self.trash_max_age) {
+                Ok(0) => {}
+                Ok(removed) => tracing::info!("Pruned {} old trash sessions", removed),
+                Err(e) => tracing::warn!("Failed to prune trash: {}", e),
+            }
+            engine = engine.with_trash(trash);
+        }
+
         Ok(engine)
     }
 }
@@ -1115,6 +2392,406 @@ impl Default for SyncEngineBuilder {
 mod tests {
     use super::*;
 
+    /// A stable install and a lazer data folder in a temp dir
+    ///
+    /// lazer's sets are not read from a database but passed to
+    /// [`engine`](Self::engine).
+    struct Installs {
+        _temp: tempfile::TempDir,
+        stable: std::path::PathBuf,
+        lazer: std::path::PathBuf,
+    }
+
+    impl Installs {
+        fn new() -> Self {
+            let temp = tempfile::tempdir().unwrap();
+            let stable = temp.path().join("stable");
+            let lazer = temp.path().join("lazer");
+            std::fs::create_dir_all(stable.join("Songs")).unwrap();
+            std::fs::create_dir_all(lazer.join("files")).unwrap();
+            Self {
+                _temp: temp,
+                stable,
+                lazer,
+            }
+        }
+
+        fn add_stable_set(&self, folder: &str, files: &[(&str, &[u8])]) {
+            let dir = self.stable.join("Songs").join(folder);
+            std::fs::create_dir_all(&dir).unwrap();
+            for (name, content) in files {
+                std::fs::write(dir.join(name), content).unwrap();
+            }
+        }
+
+        /// Put `files` into lazer's file store as a set with `online_id`
+        fn add_lazer_set(
+            &self,
+            online_id: Option<i32>,
+            files: &[(&str, &[u8])],
+        ) -> LazerBeatmapSet {
+            let store = LazerFileStore::new(&self.lazer);
+            let mut set = LazerBeatmapSet {
+                id: format!("set-{:?}", online_id),
+                online_id,
+                beatmaps: Vec::new(),
+                files: Vec::new(),
+            };
+            for (name, content) in files {
+                let hash = LazerFileStore::calculate_hash(content);
+                let path = store.hash_to_path(&hash);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, content).unwrap();
+                if name.ends_with(".osu") {
+                    let info = crate::parser::parse_osu_file(&path).unwrap();
+                    set.beatmaps.push(crate::lazer::LazerBeatmapInfo {
+                        id: format!("{}-{}", set.id, name),
+                        online_id: info.online_id(),
+                        hash: hash.clone(),
+                        md5_hash: info.md5_hash,
+                        metadata: info.metadata,
+                        difficulty: info.difficulty,
+                        version: info.version,
+                        mode: info.mode,
+                        length_ms: info.length_ms,
+                        bpm: info.bpm,
+                        star_rating: None,
+                        star_ratings: HashMap::new(),
+                        ranked_status: None,
+                    });
+                }
+                set.files.push(crate::lazer::LazerNamedFile {
+                    filename: name.to_string(),
+                    hash,
+                });
+            }
+            set
+        }
+
+        fn engine(&self, lazer_sets: Vec<LazerBeatmapSet>) -> SyncEngine {
+            let config = Config {
+                stable_path: Some(self.stable.clone()),
+                lazer_path: Some(self.lazer.clone()),
+                ..Config::default()
+            };
+            let engine = SyncEngine::new(
+                config,
+                StableScanner::new(self.stable.join("Songs")),
+                LazerDatabase::without_realm(&self.lazer),
+            );
+            engine.lazer_sets_cache.set(lazer_sets).unwrap();
+            engine
+        }
+
+        /// File names inside each .osz written to lazer's import folder
+        fn imported_osz_files(&self) -> Vec<Vec<String>> {
+            let mut archives: Vec<_> = std::fs::read_dir(self.lazer.join("import"))
+                .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+                .unwrap_or_default();
+            archives.sort();
+            archives
+                .iter()
+                .map(|path| {
+                    let file = std::fs::File::open(path).unwrap();
+                    let archive = zip::ZipArchive::new(file).unwrap();
+                    let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+                    names.sort();
+                    names
+                })
+                .collect()
+        }
+    }
+
+    fn osu_file(version: &str, set_id: i32) -> Vec<u8> {
+        format!(
+            "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\n\n[Metadata]\nTitle:Title\nArtist:Artist\nCreator:Mapper\nVersion:{}\nBeatmapSetID:{}\n\n[HitObjects]\n256,192,100,1,0,0:0:0:0:\n",
+            version, set_id
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_merge_imports_both_copies_under_the_online_id() {
+        let installs = Installs::new();
+        let hard = osu_file("Hard", 1);
+        let easy = osu_file("Easy", 1);
+        installs.add_stable_set(
+            "1 Artist - Title",
+            &[("hard.osu", &hard), ("audio.mp3", b"audio")],
+        );
+        let lazer_set =
+            installs.add_lazer_set(Some(1), &[("easy.osu", &easy), ("audio.mp3", b"audio")]);
+        let engine = installs.engine(vec![lazer_set]);
+
+        let result = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::merge_all())
+            .unwrap();
+
+        assert_eq!(result.imported, 1);
+        // The audio blob is already in the store, so only the maps are archived
+        assert_eq!(
+            installs.imported_osz_files(),
+            vec![vec!["easy.osu".to_string(), "hard.osu".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_sync_emits_set_events() {
+        let installs = Installs::new();
+        let duplicate = osu_file("Hard", 1);
+        let new = String::from_utf8(osu_file("Normal", 2))
+            .unwrap()
+            .replace("Title:Title", "Title:Other");
+        installs.add_stable_set("1 Artist - Title", &[("hard.osu", &duplicate)]);
+        installs.add_stable_set("2 Artist - Other", &[("normal.osu", new.as_bytes())]);
+        let lazer_set = installs.add_lazer_set(Some(1), &[("hard.osu", &duplicate)]);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let engine = installs.engine(vec![lazer_set]).with_event_sender(sender);
+
+        let result = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::skip_all())
+            .unwrap();
+        drop(engine);
+        let events: Vec<SyncEvent> = receiver.iter().collect();
+
+        assert_eq!((result.imported, result.skipped), (1, 1));
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            SyncEvent::Started {
+                direction: SyncDirection::StableToLazer
+            }
+        );
+        assert!(events.contains(&SyncEvent::SetImported {
+            name: "2 Artist - Other".to_string()
+        }));
+        assert!(events.contains(&SyncEvent::SetSkipped {
+            name: "1 Artist - Title".to_string(),
+            reason: "duplicate".to_string(),
+        }));
+        assert_eq!(
+            events[3],
+            SyncEvent::Finished {
+                imported: 1,
+                skipped: 1,
+                failed: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_game_guard_refuses_while_game_runs() {
+        let installs = Installs::new();
+        installs.add_stable_set("1 Artist - Title", &[("hard.osu", &osu_file("Hard", 1))]);
+        let mut engine = installs
+            .engine(Vec::new())
+            .with_game_guard(GameGuard::Refuse);
+        engine.detect_game = || Some(OsuGame::Stable);
+
+        let result = engine.sync(SyncDirection::StableToLazer, &AutoResolver::skip_all());
+        assert!(matches!(result, Err(Error::GameRunning { ref game }) if game == "osu! stable"));
+        assert!(installs.imported_osz_files().is_empty());
+    }
+
+    #[test]
+    fn test_game_guard_waits_for_game_exit() {
+        // lazer is running for the first two lookups, then exits
+        static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+        let installs = Installs::new();
+        installs.add_stable_set("1 Artist - Title", &[("hard.osu", &osu_file("Hard", 1))]);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut engine = installs
+            .engine(Vec::new())
+            .with_game_guard(GameGuard::Wait)
+            .with_event_sender(sender);
+        engine.detect_game =
+            || (LOOKUPS.fetch_add(1, Ordering::SeqCst) < 2).then_some(OsuGame::Lazer);
+
+        let result = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::skip_all())
+            .unwrap();
+        drop(engine);
+        let events: Vec<SyncEvent> = receiver.iter().collect();
+
+        assert_eq!(result.imported, 1);
+        assert!(LOOKUPS.load(Ordering::SeqCst) >= 3);
+        assert!(events.contains(&SyncEvent::WaitingForGameExit {
+            game: OsuGame::Lazer
+        }));
+        assert_eq!(installs.imported_osz_files().len(), 1);
+    }
+
+    #[test]
+    fn test_cancelled_sync_is_cancelled() {
+        let installs = Installs::new();
+        installs.add_stable_set("1 Artist - Title", &[("hard.osu", &osu_file("Hard", 1))]);
+        let token = CancellationToken::new();
+        token.cancel();
+        let engine = installs.engine(Vec::new()).with_cancellation(token);
+
+        let result = engine.sync(SyncDirection::StableToLazer, &AutoResolver::skip_all());
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(installs.imported_osz_files().is_empty());
+
+        let dry_run = engine.dry_run(SyncDirection::StableToLazer);
+        assert!(matches!(dry_run, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_lazer_export_keeps_files_inside_the_set_folder() {
+        let installs = Installs::new();
+        let hard = osu_file("Hard", 1);
+        let lazer_set = installs.add_lazer_set(
+            Some(1),
+            &[
+                ("hard.osu", &hard),
+                ("sb\\bg.png", b"background"),
+                ("../escape.png", b"escape"),
+            ],
+        );
+        let engine = installs.engine(vec![lazer_set]);
+
+        let result = engine
+            .sync(SyncDirection::LazerToStable, &AutoResolver::skip_all())
+            .unwrap();
+
+        assert_eq!(result.imported, 1);
+        let songs = installs.stable.join("Songs");
+        let folders: Vec<_> = std::fs::read_dir(&songs)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(folders.len(), 1);
+        assert!(folders[0].join("hard.osu").is_file());
+        assert!(folders[0].join("sb").join("bg.png").is_file());
+        assert!(!songs.join("escape.png").exists());
+    }
+
+    #[test]
+    fn test_merge_without_online_id_is_skipped() {
+        let installs = Installs::new();
+        let hard = osu_file("Hard", -1);
+        let easy = osu_file("Easy", -1);
+        installs.add_stable_set("Artist - Title", &[("hard.osu", &hard)]);
+        let lazer_set = installs.add_lazer_set(None, &[("easy.osu", &easy)]);
+        let engine = installs
+            .engine(vec![lazer_set])
+            .with_duplicate_strategy(DuplicateStrategy::ByMetadata);
+
+        let result = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::merge_all())
+            .unwrap();
+
+        assert_eq!(result.imported, 0);
+        assert_eq!(result.skipped, 1);
+        assert!(installs.imported_osz_files().is_empty());
+    }
+
+    #[test]
+    fn test_replace_archives_only_changed_files() {
+        let installs = Installs::new();
+        let old_hard = osu_file("Hard", 1);
+        let new_hard = [osu_file("Hard", 1), b"// edited\n".to_vec()].concat();
+        installs.add_stable_set(
+            "1 Artist - Title",
+            &[
+                ("hard.osu", &new_hard),
+                ("audio.mp3", b"audio"),
+                ("BG.jpg", b"background"),
+            ],
+        );
+        let lazer_set = installs.add_lazer_set(
+            Some(1),
+            &[
+                ("hard.osu", &old_hard),
+                ("audio.mp3", b"audio"),
+                ("bg.jpg", b"background"),
+                ("old.png", b"old"),
+                ("shared.png", b"shared"),
+            ],
+        );
+        let other_set = installs.add_lazer_set(Some(2), &[("shared.png", b"shared")]);
+        let engine = installs.engine(vec![lazer_set, other_set]);
+
+        let result = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::replace_all())
+            .unwrap();
+
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.bytes_written, new_hard.len() as u64);
+        // Unchanged audio stays out; the case-only rename is archived under
+        // its new name
+        assert_eq!(
+            installs.imported_osz_files(),
+            vec![vec!["BG.jpg".to_string(), "hard.osu".to_string()]]
+        );
+
+        let store = LazerFileStore::new(&installs.lazer);
+        let stored = |content: &[u8]| store.exists(&LazerFileStore::calculate_hash(content));
+        assert!(!stored(b"old"));
+        assert!(stored(b"shared"));
+        assert!(stored(b"audio"));
+        assert!(stored(&old_hard));
+    }
+
+    #[test]
+    fn test_replace_keeps_overwritten_lazer_files_in_trash() {
+        let installs = Installs::new();
+        let old_hard = osu_file("Hard", 1);
+        let new_hard = [osu_file("Hard", 1), b"// edited\n".to_vec()].concat();
+        installs.add_stable_set(
+            "1 Artist - Title",
+            &[("hard.osu", &new_hard), ("audio.mp3", b"audio")],
+        );
+        let lazer_set = installs.add_lazer_set(
+            Some(1),
+            &[
+                ("hard.osu", &old_hard),
+                ("audio.mp3", b"audio"),
+                ("old.png", b"old"),
+            ],
+        );
+        let trash = Trash::new(installs.lazer.with_file_name("trash"));
+        let engine = installs.engine(vec![lazer_set]).with_trash(trash.clone());
+
+        let result = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::replace_all())
+            .unwrap();
+        assert_eq!(result.imported, 1);
+
+        // Only the overwritten and dropped files are kept, as they were
+        let mut trashed: Vec<(String, Vec<u8>)> = walkdir::WalkDir::new(trash.session_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                (name, std::fs::read(e.path()).unwrap())
+            })
+            .collect();
+        trashed.sort();
+        assert_eq!(
+            trashed,
+            vec![
+                ("hard.osu".to_string(), old_hard),
+                ("old.png".to_string(), b"old".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_fails_without_a_default_trash_root() {
+        let mut builder = SyncEngineBuilder::new();
+        // What trash_replaced(true) leaves without an app data directory
+        builder.trash_root_missing = true;
+        assert!(matches!(builder.build(), Err(Error::Config(_))));
+
+        // A custom trash needs no app data directory
+        let builder = builder.trash(Trash::new("trash"));
+        assert!(!builder.trash_root_missing);
+    }
+
     #[test]
     fn test_sync_result() {
         let mut result = SyncResult::new(SyncDirection::StableToLazer);
@@ -1129,6 +2806,26 @@ mod tests {
         assert!(result.is_success());
     }
 
+    #[test]
+    fn test_record_set_time_keeps_slowest() {
+        let mut result = SyncResult::new(SyncDirection::StableToLazer);
+        for secs in 0..SLOWEST_SETS_TRACKED as u64 + 5 {
+            result.record_set_time(&format!("set {}", secs), Duration::from_secs(secs));
+        }
+        assert_eq!(result.slowest_sets.len(), SLOWEST_SETS_TRACKED);
+        assert_eq!(result.slowest_sets[0].1, Duration::from_secs(14));
+        assert_eq!(
+            result.slowest_sets.last().unwrap().1,
+            Duration::from_secs(5)
+        );
+
+        let mut other = SyncResult::new(SyncDirection::LazerToStable);
+        other.record_set_time("huge video", Duration::from_secs(60));
+        result.merge(other);
+        assert_eq!(result.slowest_sets[0].0, "huge video");
+        assert_eq!(result.slowest_sets.len(), SLOWEST_SETS_TRACKED);
+    }
+
     #[test]
     fn test_sync_result_merge() {
         let mut result1 = SyncResult::new(SyncDirection::StableToLazer);
@@ -1170,6 +2867,8 @@ mod tests {
             items_per_second: 25.0,
             elapsed_seconds: 2,
             estimated_remaining_seconds: Some(2),
+            current_file: Some("video.mp4".to_string()),
+            current_file_progress: 0.5,
         };
 
         assert_eq!(progress.current, 50);
@@ -1249,6 +2948,47 @@ mod tests {
         assert_eq!(result1.errors.len(), 3);
     }
 
+    #[test]
+    fn test_estimate_import_size_counts_stored_and_shared_files_once() {
+        let installs = Installs::new();
+        installs.add_stable_set("a", &[("audio.mp3", &[1; 100]), ("bg.jpg", &[2; 10])]);
+        installs.add_stable_set("b", &[("audio.mp3", &[1; 100]), ("stored.png", &[3; 40])]);
+        installs.add_lazer_set(None, &[("stored.png", &[3; 40])]);
+        let songs = installs.stable.join("Songs");
+        let files = vec![
+            songs.join("a").join("audio.mp3"),
+            songs.join("a").join("bg.jpg"),
+            songs.join("b").join("audio.mp3"),
+            songs.join("b").join("stored.png"),
+            songs.join("b").join("missing.osu"),
+        ];
+
+        let store = LazerFileStore::new(&installs.lazer);
+        assert_eq!(estimate_import_size(&files, &store, None).unwrap(), 110);
+    }
+
+    #[test]
+    fn test_sync_fails_when_lazer_volume_is_full() {
+        let installs = Installs::new();
+        let hard = osu_file("Hard", 1);
+        installs.add_stable_set(
+            "1 Artist - Title",
+            &[("hard.osu", &hard), ("audio.mp3", &[7; 4096])],
+        );
+        let mut engine = installs.engine(Vec::new());
+        engine.free_space = |_| Some(1024);
+
+        let err = engine
+            .sync(SyncDirection::StableToLazer, &AutoResolver::skip_all())
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::InsufficientSpace { needed, available: 1024 } if needed > 4096
+        ));
+        assert!(installs.imported_osz_files().is_empty());
+    }
+
     #[test]
     fn test_sync_result_empty_is_success() {
         let result = SyncResult::new(SyncDirection::Bidirectional);
@@ -1372,4 +3112,93 @@ mod tests {
             assert!(filenames.contains(&format!("file{}.txt", i)));
         }
     }
+
+    #[test]
+    fn test_new_set_folder_from_watch_event() {
+        let songs = Path::new("/osu/Songs");
+        let created = FileChangeEvent::Created {
+            path: songs.join("123 Artist - Title").join("map.osu"),
+            is_dir: false,
+        };
+        assert_eq!(
+            new_set_folder(songs, &created).as_deref(),
+            Some("123 Artist - Title")
+        );
+
+        let deleted = FileChangeEvent::Deleted {
+            path: songs.join("123 Artist - Title"),
+        };
+        assert_eq!(new_set_folder(songs, &deleted), None);
+
+        let outside = FileChangeEvent::Modified {
+            path: std::path::PathBuf::from("/osu/osu!.db"),
+        };
+        assert_eq!(new_set_folder(songs, &outside), None);
+    }
+
+    #[test]
+    fn test_watch_waits_for_new_set_sharing_file_names() {
+        use crate::unified::FolderCoalescer;
+
+        // A file deleted from one set while a new set with the same file
+        // names is extracted must not report the new set before it settles
+        let songs = Path::new("/osu/Songs");
+        let mut coalescer = FolderCoalescer::new(WATCH_SETTLE_TIME);
+        coalescer.set_roots(vec![songs.to_path_buf()]);
+        let start = Instant::now();
+        coalescer.push(
+            FileChangeEvent::Deleted {
+                path: songs.join("1 Old").join("audio.mp3"),
+            },
+            start,
+        );
+        for name in ["audio.mp3", "map.osu"] {
+            coalescer.push(
+                FileChangeEvent::Created {
+                    path: songs.join("2 New").join(name),
+                    is_dir: false,
+                },
+                start,
+            );
+        }
+
+        let folders = |events: Vec<FileChangeEvent>| -> Vec<String> {
+            events
+                .iter()
+                .filter_map(|event| new_set_folder(songs, event))
+                .collect()
+        };
+        assert!(folders(coalescer.drain_ready(start)).is_empty());
+        assert_eq!(
+            folders(coalescer.drain_ready(start + WATCH_SETTLE_TIME)),
+            vec!["1 Old".to_string(), "2 New".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_match_favorites() {
+        use crate::beatmap::BeatmapInfo;
+
+        let set = |md5s: &[&str]| {
+            let mut set = BeatmapSet::new();
+            for md5 in md5s {
+                set.beatmaps.push(BeatmapInfo {
+                    md5_hash: md5.to_string(),
+                    ..Default::default()
+                });
+            }
+            set
+        };
+        let sets = vec![set(&["aa", "bb"]), set(&["cc"]), set(&["DD"])];
+
+        let collections = vec![
+            Collection::with_hashes("Other", vec!["cc".into()]),
+            Collection::with_hashes("Favourites", vec!["bb".into(), "dd".into(), "ee".into()]),
+        ];
+
+        let favorites = find_favorites(&collections).unwrap();
+        let (indices, missing) = match_favorites(&sets, &favorites.beatmap_hashes);
+        assert_eq!(indices, vec![0, 2]);
+        assert_eq!(missing, vec!["ee".to_string()]);
+    }
 }
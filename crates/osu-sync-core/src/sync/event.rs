@@ -0,0 +1,83 @@
+//! Structured sync events
+
+use std::fmt;
+
+use super::direction::SyncDirection;
+use crate::unified::OsuGame;
+
+/// Something that happened during a sync
+///
+/// Unlike [`SyncProgress`](super::SyncProgress), which only carries counts
+/// for a progress bar, events say what happened to each beatmap set and why.
+/// They are sent through the channel given to
+/// [`SyncEngineBuilder::event_sender`](super::SyncEngineBuilder::event_sender).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncEvent {
+    /// A sync started
+    Started { direction: SyncDirection },
+    /// A beatmap set was imported
+    SetImported { name: String },
+    /// A beatmap set was not imported
+    SetSkipped { name: String, reason: String },
+    /// Importing a beatmap set failed
+    SetFailed { name: String, error: String },
+    /// The sync is paused until the game exits, see
+    /// [`GameGuard::Wait`](super::GameGuard::Wait)
+    WaitingForGameExit { game: OsuGame },
+    /// The sync finished; not sent if it was cancelled or aborted by an error
+    Finished {
+        imported: usize,
+        skipped: usize,
+        failed: usize,
+    },
+}
+
+impl fmt::Display for SyncEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Started { direction } => write!(f, "Started sync: {}", direction),
+            Self::SetImported { name } => write!(f, "Imported {}", name),
+            Self::SetSkipped { name, reason } => write!(f, "Skipped {} ({})", name, reason),
+            Self::SetFailed { name, error } => write!(f, "Failed {}: {}", name, error),
+            Self::WaitingForGameExit { game } => write!(f, "Waiting for {} to exit", game),
+            Self::Finished {
+                imported,
+                skipped,
+                failed,
+            } => write!(
+                f,
+                "Finished: {} imported, {} skipped, {} failed",
+                imported, skipped, failed
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_display() {
+        let skipped = SyncEvent::SetSkipped {
+            name: "1 Artist - Title".to_string(),
+            reason: "duplicate".to_string(),
+        };
+        assert_eq!(skipped.to_string(), "Skipped 1 Artist - Title (duplicate)");
+
+        let finished = SyncEvent::Finished {
+            imported: 3,
+            skipped: 1,
+            failed: 0,
+        };
+        assert_eq!(
+            finished.to_string(),
+            "Finished: 3 imported, 1 skipped, 0 failed"
+        );
+
+        let waiting = SyncEvent::WaitingForGameExit {
+            game: OsuGame::Lazer,
+        };
+        assert_eq!(waiting.to_string(), "Waiting for osu! lazer to exit");
+    }
+}
@@ -0,0 +1,66 @@
+//! Keeping syncs from writing into files a running osu! has open
+
+use std::time::Instant;
+
+use crate::unified::{GameLaunchDetector, OsuGame};
+
+/// What a sync does while osu!stable or osu!lazer is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameGuard {
+    /// Fail with [`Error::GameRunning`](crate::Error::GameRunning) instead
+    /// of starting
+    Refuse,
+    /// Wait for the game to exit before starting and between sets, sending
+    /// [`SyncEvent::WaitingForGameExit`](super::SyncEvent::WaitingForGameExit)
+    Wait,
+}
+
+/// Looks up which game is running, if any
+pub(crate) type DetectGame = fn() -> Option<OsuGame>;
+
+/// The running game according to the process list
+pub(crate) fn running_game() -> Option<OsuGame> {
+    let detector = GameLaunchDetector::new();
+    if detector.is_stable_running() {
+        Some(OsuGame::Stable)
+    } else if detector.is_lazer_running() {
+        Some(OsuGame::Lazer)
+    } else {
+        None
+    }
+}
+
+/// Looks up running games, at most once per poll interval
+pub(crate) struct GameWatch {
+    detect: DetectGame,
+    /// When the running games were last looked up; listing processes is
+    /// too slow to do for every set
+    last_check: Option<Instant>,
+}
+
+impl GameWatch {
+    pub(crate) fn new(detect: DetectGame) -> Self {
+        Self {
+            detect,
+            last_check: None,
+        }
+    }
+
+    /// The running game, or `None` if none is running or the last lookup
+    /// was less than a poll interval ago
+    pub(crate) fn poll(&mut self) -> Option<OsuGame> {
+        if matches!(
+            self.last_check,
+            Some(at) if at.elapsed() < GameLaunchDetector::DEFAULT_POLL_INTERVAL
+        ) {
+            return None;
+        }
+        self.last_check = Some(Instant::now());
+        self.running()
+    }
+
+    /// The running game, looked up now
+    pub(crate) fn running(&self) -> Option<OsuGame> {
+        (self.detect)()
+    }
+}
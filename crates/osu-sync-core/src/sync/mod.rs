@@ -1,17 +1,34 @@
 //! Beatmap synchronization between osu!stable and osu!lazer
 
 mod conflict;
+mod difficulty_selection;
 mod direction;
 mod dry_run;
 mod engine;
+mod event;
+mod game_guard;
+mod replace;
 pub mod skip_list;
+mod throttle;
+mod trash;
 
 pub use conflict::{
-    AutoResolver, ConfigBasedResolver, ConflictResolver, InteractiveResolver, SmartResolver,
+    summarize_decisions, AutoResolver, ConfigBasedResolver, ConflictResolver, DecisionLog,
+    DecisionSummary, InteractiveResolver, ObserveResolver, SmartResolver,
 };
+pub use difficulty_selection::select_difficulties;
 pub use direction::SyncDirection;
-pub use dry_run::{format_bytes, DryRunAction, DryRunItem, DryRunResult};
+pub use dry_run::{
+    format_bytes, ActionSummary, DryRunAction, DryRunItem, DryRunResult, DryRunSummary,
+};
 pub use engine::{
-    ProgressCallback, SyncEngine, SyncEngineBuilder, SyncError, SyncPhase, SyncProgress, SyncResult,
+    FavoritesSyncResult, ProgressCallback, SyncEngine, SyncEngineBuilder, SyncError, SyncPhase,
+    SyncProgress, SyncResult, SLOWEST_SETS_TRACKED,
 };
+pub use event::SyncEvent;
+pub use game_guard::GameGuard;
+pub use replace::{diff_set_files, SetDiff};
 pub use skip_list::SkipList;
+pub use throttle::ThrottleConfig;
+pub(crate) use throttle::{Throttle, ThrottledWriter};
+pub use trash::{restore_from_trash, Trash, TrashSource, DEFAULT_TRASH_MAX_AGE};
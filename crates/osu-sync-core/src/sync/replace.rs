@@ -0,0 +1,176 @@
+//! Replacing a beatmap set that already exists in the target
+//!
+//! When the resolver chooses [`Replace`](crate::dedup::DuplicateAction::Replace),
+//! usually only a difficulty or two changed. Comparing the files of both
+//! versions by content hash lets the engine write just those files instead
+//! of re-importing the whole set.
+
+use std::collections::{HashMap, HashSet};
+
+/// Files that differ between the target's copy of a set and the incoming one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetDiff {
+    /// Incoming files that are new or whose content changed
+    pub changed: Vec<String>,
+    /// Target files the incoming set no longer has, including the old
+    /// spelling of changed files whose name differs only in case
+    pub removed: Vec<String>,
+    /// Files with unchanged content whose name differs only in case or path
+    /// separator, as `(existing, incoming)` names
+    pub renamed: Vec<(String, String)>,
+    /// Number of files whose name and content already match
+    pub unchanged: usize,
+}
+
+impl SetDiff {
+    /// Whether both versions have exactly the same files
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+}
+
+/// Compare `(filename, hash)` pairs of the existing and incoming set
+///
+/// File names are matched case-insensitively with `\` and `/` treated
+/// alike, as osu! does on Windows. A match spelled differently is a rename
+/// (or, with new content, a removal plus a change), so patching a folder on
+/// a case-sensitive filesystem doesn't leave both spellings behind. Both
+/// sides must be hashed the same way.
+pub fn diff_set_files(existing: &[(String, String)], incoming: &[(String, String)]) -> SetDiff {
+    let existing_files: HashMap<String, (&str, &str)> = existing
+        .iter()
+        .map(|(name, hash)| (normalize(name), (name.as_str(), hash.as_str())))
+        .collect();
+
+    let mut diff = SetDiff::default();
+    let mut incoming_names = HashSet::new();
+    for (name, hash) in incoming {
+        let key = normalize(name);
+        match existing_files.get(&key) {
+            Some(&(existing_name, existing_hash)) if existing_hash.eq_ignore_ascii_case(hash) => {
+                if same_spelling(existing_name, name) {
+                    diff.unchanged += 1;
+                } else {
+                    diff.renamed.push((existing_name.to_string(), name.clone()));
+                }
+            }
+            Some(&(existing_name, _)) => {
+                if !same_spelling(existing_name, name) {
+                    diff.removed.push(existing_name.to_string());
+                }
+                diff.changed.push(name.clone());
+            }
+            None => diff.changed.push(name.clone()),
+        }
+        incoming_names.insert(key);
+    }
+
+    diff.removed.extend(
+        existing
+            .iter()
+            .filter(|(name, _)| !incoming_names.contains(&normalize(name)))
+            .map(|(name, _)| name.clone()),
+    );
+    diff
+}
+
+/// Whether two names refer to the same file on a case-sensitive filesystem
+fn same_spelling(a: &str, b: &str) -> bool {
+    a.replace('\\', "/") == b.replace('\\', "/")
+}
+
+/// Key two file names compare equal by
+pub(super) fn normalize(name: &str) -> String {
+    name.replace('\\', "/").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stable::StableImporter;
+    use std::ffi::OsStr;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn pairs(files: &[(&str, &str)]) -> Vec<(String, String)> {
+        files
+            .iter()
+            .map(|(name, hash)| (name.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_and_apply_to_stable_folder() {
+        let existing = pairs(&[
+            ("audio.mp3", "aa"),
+            ("Hard.osu", "bb"),
+            ("sb\\old.png", "cc"),
+        ]);
+        let incoming = pairs(&[("audio.mp3", "AA"), ("hard.osu", "b2"), ("new.osu", "dd")]);
+        let diff = diff_set_files(&existing, &incoming);
+        assert_eq!(diff.changed, vec!["hard.osu", "new.osu"]);
+        assert_eq!(diff.removed, vec!["Hard.osu", "sb\\old.png"]);
+        assert_eq!(diff.unchanged, 1);
+        assert!(diff_set_files(&existing, &existing).is_empty());
+
+        let songs = TempDir::new().unwrap();
+        let folder = songs.path().join("1 Artist - Title");
+        fs::create_dir_all(folder.join("sb")).unwrap();
+        fs::write(folder.join("audio.mp3"), b"audio").unwrap();
+        fs::write(folder.join("hard.osu"), b"old hard").unwrap();
+        fs::write(folder.join("sb").join("old.png"), b"png").unwrap();
+
+        let importer = StableImporter::new(songs.path().to_path_buf());
+        let name = OsStr::new("1 Artist - Title");
+        let files = vec![
+            ("audio.mp3".to_string(), b"audio".to_vec()),
+            ("hard.osu".to_string(), b"new hard".to_vec()),
+            ("new.osu".to_string(), b"new".to_vec()),
+        ];
+        let incoming: Vec<(String, String)> = files
+            .iter()
+            .map(|(name, content)| (name.clone(), blake3::hash(content).to_hex().to_string()))
+            .collect();
+        let diff = diff_set_files(&importer.folder_hashes(name).unwrap(), &incoming);
+        assert_eq!(diff.changed, vec!["hard.osu", "new.osu"]);
+        assert_eq!(diff.removed, vec!["sb/old.png"]);
+
+        let written = importer.apply_diff(&files, name, &diff, None).unwrap();
+        assert_eq!(written, b"new hard".len() as u64 + b"new".len() as u64);
+        assert_eq!(fs::read(folder.join("hard.osu")).unwrap(), b"new hard");
+        assert!(folder.join("new.osu").exists());
+        assert!(!folder.join("sb").join("old.png").exists());
+        assert!(diff_set_files(&importer.folder_hashes(name).unwrap(), &incoming).is_empty());
+
+        // Case-only renames replace the old spelling instead of adding a file
+        let renamed = vec![
+            ("Audio.mp3".to_string(), b"audio".to_vec()),
+            ("hard.osu".to_string(), b"new hard".to_vec()),
+            ("New.osu".to_string(), b"newer".to_vec()),
+        ];
+        let incoming: Vec<(String, String)> = renamed
+            .iter()
+            .map(|(name, content)| (name.clone(), blake3::hash(content).to_hex().to_string()))
+            .collect();
+        let diff = diff_set_files(&importer.folder_hashes(name).unwrap(), &incoming);
+        assert_eq!(
+            diff.renamed,
+            vec![("audio.mp3".to_string(), "Audio.mp3".to_string())]
+        );
+        assert_eq!(diff.changed, vec!["New.osu"]);
+        assert_eq!(diff.removed, vec!["new.osu"]);
+
+        let written = importer.apply_diff(&renamed, name, &diff, None).unwrap();
+        assert_eq!(written, b"newer".len() as u64);
+        let mut names: Vec<String> = fs::read_dir(&folder)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["Audio.mp3", "New.osu", "hard.osu"]);
+        assert_eq!(fs::read(folder.join("New.osu")).unwrap(), b"newer");
+        assert!(diff_set_files(&importer.folder_hashes(name).unwrap(), &incoming).is_empty());
+    }
+}
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::PathBuf;
 
+use crate::config::paths;
+
 /// A list of beatmaps to permanently skip during sync
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SkipList {
@@ -23,7 +25,7 @@ impl SkipList {
 
     /// Get the path to the skip list file
     fn file_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|p| p.join("osu-sync").join("skip_list.json"))
+        paths::config_dir().map(|p| p.join("skip_list.json"))
     }
 
     /// Load the skip list from disk
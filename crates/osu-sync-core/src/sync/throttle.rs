@@ -0,0 +1,217 @@
+//! Rate limiting of sync writes, for syncing in the background while playing
+
+use std::io::{self, Seek, SeekFrom, Write};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancellationToken;
+use crate::error::{Error, Result};
+
+/// Longest single sleep, so cancellation stays responsive
+const SLEEP_SLICE: Duration = Duration::from_millis(100);
+
+/// Shortest sleep worth taking; smaller debts carry over to the next write
+const MIN_SLEEP: Duration = Duration::from_millis(20);
+
+/// Limits on how hard a sync may hit the disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThrottleConfig {
+    /// Maximum bytes written per second; `None` for no cap
+    pub max_bytes_per_second: Option<u64>,
+    /// Wait between sets while osu!stable or osu!lazer is running, like
+    /// [`GameGuard::Wait`](super::GameGuard::Wait) does
+    pub pause_while_playing: bool,
+}
+
+impl ThrottleConfig {
+    /// Cap writes at `max_bytes_per_second`
+    pub fn new(max_bytes_per_second: u64) -> Self {
+        Self {
+            max_bytes_per_second: Some(max_bytes_per_second),
+            pause_while_playing: false,
+        }
+    }
+
+    /// Wait between sets while a game is running
+    pub fn with_pause_while_playing(mut self, pause: bool) -> Self {
+        self.pause_while_playing = pause;
+        self
+    }
+
+    /// Parse a rate such as `20MB`, `512k` or `1.5 GB/s` into bytes per
+    /// second
+    ///
+    /// Units are binary (`1KB` is 1024 bytes), matching
+    /// [`format_bytes`](crate::sync::format_bytes); a bare number is bytes.
+    pub fn parse_rate(rate: &str) -> Result<u64> {
+        let invalid = || Error::Config(format!("Invalid rate '{}'", rate));
+        let trimmed = rate.trim();
+        let trimmed = trimmed.strip_suffix("/s").unwrap_or(trimmed).trim_end();
+        let split = trimmed
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(trimmed.len());
+        let (number, unit) = trimmed.split_at(split);
+        let number: f64 = number.parse().map_err(|_| invalid())?;
+        let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" | "kib" => 1024,
+            "m" | "mb" | "mib" => 1024 * 1024,
+            "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+            _ => return Err(invalid()),
+        };
+        let bytes = (number * multiplier as f64) as u64;
+        if bytes == 0 {
+            return Err(invalid());
+        }
+        Ok(bytes)
+    }
+}
+
+/// Applies a [`ThrottleConfig`]'s write rate cap to the writes of one sync
+///
+/// The importers report every chunk they write through a
+/// [`ThrottledWriter`], so a single large set is paced too.
+pub(crate) struct Throttle {
+    max_bytes_per_second: Option<u64>,
+    cancellation: Option<CancellationToken>,
+    /// Start of the current measuring window and the bytes written since
+    window: Mutex<(Instant, u64)>,
+}
+
+impl Throttle {
+    pub(crate) fn new(config: ThrottleConfig, cancellation: Option<CancellationToken>) -> Self {
+        Self {
+            max_bytes_per_second: config.max_bytes_per_second,
+            cancellation,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Record `bytes` just written, sleeping until they fit under the cap
+    ///
+    /// Time spent idle does not build up credit: once writes are under the
+    /// cap, the window starts over. A cancelled sync stops waiting; the
+    /// engine notices the cancellation before the next set.
+    pub(crate) fn consume(&self, bytes: u64) {
+        let Some(rate) = self.max_bytes_per_second else {
+            return;
+        };
+        let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+        let (start, written) = &mut *window;
+        *written += bytes;
+
+        let due = Duration::from_secs_f64(*written as f64 / rate as f64);
+        let elapsed = start.elapsed();
+        if due > elapsed {
+            if due - elapsed < MIN_SLEEP {
+                return;
+            }
+            let _ = sleep_unless_cancelled(due - elapsed, self.cancellation.as_ref());
+        }
+        *start = Instant::now();
+        *written = 0;
+    }
+}
+
+/// Writer that reports every write to an optional [`Throttle`]
+pub(crate) struct ThrottledWriter<'a, W> {
+    inner: W,
+    throttle: Option<&'a Throttle>,
+}
+
+impl<'a, W> ThrottledWriter<'a, W> {
+    pub(crate) fn new(inner: W, throttle: Option<&'a Throttle>) -> Self {
+        Self { inner, throttle }
+    }
+}
+
+impl<W: Write> Write for ThrottledWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some(throttle) = self.throttle {
+            throttle.consume(written as u64);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek> Seek for ThrottledWriter<'_, W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Sleep for `duration`, failing with [`Error::Cancelled`] as soon as
+/// `cancellation` is cancelled
+pub(crate) fn sleep_unless_cancelled(
+    duration: Duration,
+    cancellation: Option<&CancellationToken>,
+) -> Result<()> {
+    let end = Instant::now() + duration;
+    loop {
+        if cancellation.is_some_and(CancellationToken::is_cancelled) {
+            return Err(Error::Cancelled);
+        }
+        let now = Instant::now();
+        if now >= end {
+            return Ok(());
+        }
+        thread::sleep((end - now).min(SLEEP_SLICE));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(
+            ThrottleConfig::parse_rate("20MB").unwrap(),
+            20 * 1024 * 1024
+        );
+        assert_eq!(ThrottleConfig::parse_rate("512k").unwrap(), 512 * 1024);
+        assert_eq!(
+            ThrottleConfig::parse_rate("1.5 GB/s").unwrap(),
+            3 * 512 * 1024 * 1024
+        );
+        assert_eq!(ThrottleConfig::parse_rate("4096").unwrap(), 4096);
+        assert!(ThrottleConfig::parse_rate("fast").is_err());
+        assert!(ThrottleConfig::parse_rate("20XB").is_err());
+        assert!(ThrottleConfig::parse_rate("0MB").is_err());
+    }
+
+    #[test]
+    fn test_throttle_sleeps_to_stay_under_cap() {
+        let throttle = Throttle::new(ThrottleConfig::new(1000), None);
+        let start = Instant::now();
+        throttle.consume(200);
+        assert!(start.elapsed() >= Duration::from_millis(190));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let throttle = Throttle::new(ThrottleConfig::new(1000), Some(token));
+        let start = Instant::now();
+        throttle.consume(10_000);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_throttled_writer_paces_one_large_write() {
+        let throttle = Throttle::new(ThrottleConfig::new(10_000), None);
+        let mut out = Vec::new();
+        let start = Instant::now();
+        // Written in chunks, like the importers' copy loops
+        let mut writer = ThrottledWriter::new(&mut out, Some(&throttle));
+        for chunk in vec![0u8; 3000].chunks(500) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(280));
+        assert_eq!(out.len(), 3000);
+    }
+}
@@ -0,0 +1,238 @@
+//! Trash for files a Replace would otherwise destroy
+//!
+//! With a [`Trash`] set on the engine, files that a
+//! [`Replace`](crate::dedup::DuplicateAction::Replace) overwrites or removes
+//! are kept under `<root>/<YYYYmmdd-HHMMSS>/<stable|lazer>/<set folder>`
+//! instead, where [`restore_from_trash`] can bring them back. Sessions older
+//! than a maximum age are removed by [`Trash::prune`].
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use walkdir::WalkDir;
+
+use crate::config::paths;
+use crate::error::{Error, Result};
+
+/// How long trashed files are kept by default
+pub const DEFAULT_TRASH_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Format of the per-session folder names
+const SESSION_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Which installation a trashed set was removed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashSource {
+    Stable,
+    Lazer,
+}
+
+impl TrashSource {
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Lazer => "lazer",
+        }
+    }
+}
+
+/// Timestamped folder that replaced beatmap files are moved into
+#[derive(Debug, Clone)]
+pub struct Trash {
+    root: PathBuf,
+    /// Folder of this sync session, named after the time it was created
+    session: PathBuf,
+}
+
+impl Trash {
+    /// Create a trash session under `root`
+    ///
+    /// Nothing is written until the first file is trashed.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let session = root.join(Local::now().format(SESSION_FORMAT).to_string());
+        Self { root, session }
+    }
+
+    /// Where trashed files are kept by default, under the app data directory
+    pub fn default_root() -> Option<PathBuf> {
+        paths::app_data_dir().map(|p| p.join("trash"))
+    }
+
+    /// Folder holding all trash sessions
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Folder of the current session
+    pub fn session_dir(&self) -> &Path {
+        &self.session
+    }
+
+    /// Folder a set's trashed files go to in the current session
+    pub fn entry_dir(&self, source: TrashSource, set_folder: &str) -> PathBuf {
+        self.session.join(source.dir_name()).join(set_folder)
+    }
+
+    /// Move `set_dir/relative` of a stable set into the trash, keeping its
+    /// path in the set
+    ///
+    /// Does nothing if the file doesn't exist.
+    pub fn trash_file(&self, set_dir: &Path, relative: &str) -> Result<()> {
+        let file_path = set_dir.join(relative);
+        if !file_path.exists() {
+            return Ok(());
+        }
+        let set_folder = set_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let target = self
+            .entry_dir(TrashSource::Stable, &set_folder)
+            .join(relative);
+        move_file(&file_path, &target)
+    }
+
+    /// Write copies of files that live elsewhere into the trash
+    ///
+    /// Used for lazer, whose files are shared blobs in its file store and
+    /// can't be moved out.
+    pub fn store_files(
+        &self,
+        source: TrashSource,
+        set_folder: &str,
+        files: &[(String, Vec<u8>)],
+    ) -> Result<()> {
+        let dir = self.entry_dir(source, set_folder);
+        for (filename, content) in files {
+            let path = dir.join(filename);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+            }
+            fs::write(&path, content).map_err(|e| Error::io_at(e, &path))?;
+        }
+        Ok(())
+    }
+
+    /// Delete trash sessions older than `max_age`, returning how many
+    ///
+    /// Age is taken from the session's folder name, or its modification time
+    /// when the name isn't a timestamp.
+    pub fn prune(&self, max_age: Duration) -> Result<usize> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(Error::io_at(e, &self.root)),
+        };
+
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() || path == self.session {
+                continue;
+            }
+            let created = session_time(&path).or_else(|| entry.metadata().ok()?.modified().ok());
+            let expired = created
+                .and_then(|created| now.duration_since(created).ok())
+                .is_some_and(|age| age > max_age);
+            if expired {
+                fs::remove_dir_all(&path).map_err(|e| Error::io_at(e, &path))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Move the files of a trashed set back to `destination`
+///
+/// `entry` is a set folder inside a session, e.g.
+/// `<root>/20250101-120000/stable/123 Artist - Title`, and `destination` is
+/// where the set should live again, usually the same folder name in the
+/// Songs folder. Files already at the destination are overwritten. A lazer
+/// entry can be restored to any folder and imported from there. The emptied
+/// entry is removed. Returns the number of restored files.
+pub fn restore_from_trash(entry: &Path, destination: &Path) -> Result<usize> {
+    if !entry.is_dir() {
+        return Err(Error::Other(format!(
+            "Trash entry not found: {}",
+            entry.display()
+        )));
+    }
+
+    let mut restored = 0;
+    for file in WalkDir::new(entry).into_iter().filter_map(|e| e.ok()) {
+        if !file.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = file.path().strip_prefix(entry) else {
+            continue;
+        };
+        move_file(file.path(), &destination.join(relative))?;
+        restored += 1;
+    }
+
+    fs::remove_dir_all(entry).map_err(|e| Error::io_at(e, entry))?;
+    Ok(restored)
+}
+
+/// Rename `from` to `to`, copying when they are on different filesystems
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io_at(e, parent))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| Error::io_at(e, from))?;
+    fs::remove_file(from).map_err(|e| Error::io_at(e, from))
+}
+
+/// Creation time encoded in a session folder name
+fn session_time(path: &Path) -> Option<SystemTime> {
+    let name = path.file_name()?.to_str()?;
+    let naive = NaiveDateTime::parse_from_str(name, SESSION_FORMAT).ok()?;
+    let local = Local.from_local_datetime(&naive).earliest()?;
+    Some(local.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_trash_restore_and_prune() {
+        let dir = TempDir::new().unwrap();
+        let set_dir = dir.path().join("Songs").join("1 Artist - Title");
+        fs::create_dir_all(set_dir.join("sb")).unwrap();
+        fs::write(set_dir.join("hard.osu"), b"custom hard").unwrap();
+        fs::write(set_dir.join("sb").join("bg.png"), b"png").unwrap();
+
+        let trash = Trash::new(dir.path().join("trash"));
+        trash.trash_file(&set_dir, "hard.osu").unwrap();
+        trash.trash_file(&set_dir, "sb/bg.png").unwrap();
+        trash.trash_file(&set_dir, "missing.osu").unwrap();
+        assert!(!set_dir.join("hard.osu").exists());
+
+        let entry = trash.entry_dir(TrashSource::Stable, "1 Artist - Title");
+        assert_eq!(restore_from_trash(&entry, &set_dir).unwrap(), 2);
+        assert_eq!(fs::read(set_dir.join("hard.osu")).unwrap(), b"custom hard");
+        assert!(set_dir.join("sb").join("bg.png").exists());
+        assert!(!entry.exists());
+
+        // Only sessions past the maximum age are pruned, never the current one
+        let old = trash.root().join("20000101-000000");
+        fs::create_dir_all(&old).unwrap();
+        trash
+            .store_files(TrashSource::Lazer, "2 Other", &[("a.osu".into(), vec![1])])
+            .unwrap();
+        assert_eq!(trash.prune(DEFAULT_TRASH_MAX_AGE).unwrap(), 1);
+        assert!(!old.exists());
+        assert!(trash.session_dir().exists());
+    }
+}
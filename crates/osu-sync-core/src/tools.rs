@@ -0,0 +1,190 @@
+//! Tools that let AI assistants query the user's osu! library
+//!
+//! [`OsuSyncTools`] describes each tool with a JSON Schema for its arguments
+//! and runs it from JSON arguments, returning JSON, which is the shape MCP
+//! servers register tools in.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::beatmap::GameMode;
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::filter::{BeatmapSearchHit, FilterCriteria, FilterEngine};
+use crate::stable::StableScanner;
+
+/// Results returned by `search_beatmaps` when no limit is given
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Most results `search_beatmaps` returns, whatever limit is asked for
+pub const MAX_SEARCH_LIMIT: usize = 200;
+
+/// Name, description and argument schema of one tool
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// JSON Schema of the tool's arguments
+    pub input_schema: Value,
+}
+
+/// Arguments of the `search_beatmaps` tool
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchBeatmapsArgs {
+    /// Text matched against title, artist, creator, source, tags and folder
+    /// name
+    pub query: String,
+    /// Maximum number of sets to return, capped at [`MAX_SEARCH_LIMIT`]
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Only sets with a difficulty of at least this many stars
+    #[serde(default)]
+    pub min_stars: Option<f32>,
+    /// Only sets with a difficulty of at most this many stars
+    #[serde(default)]
+    pub max_stars: Option<f32>,
+    /// Only sets with a difficulty in this mode
+    #[serde(default)]
+    pub mode: Option<GameMode>,
+}
+
+impl SearchBeatmapsArgs {
+    fn criteria(&self) -> FilterCriteria {
+        let mut criteria = FilterCriteria::new().with_search(self.query.clone());
+        if let Some(min) = self.min_stars {
+            criteria = criteria.with_min_stars(min);
+        }
+        if let Some(max) = self.max_stars {
+            criteria = criteria.with_max_stars(max);
+        }
+        if let Some(mode) = self.mode {
+            criteria = criteria.with_mode(mode);
+        }
+        criteria
+    }
+}
+
+/// Tools over an osu!stable Songs folder
+pub struct OsuSyncTools {
+    scanner: StableScanner,
+}
+
+impl OsuSyncTools {
+    /// Create tools for the given Songs folder
+    pub fn new(songs_path: PathBuf) -> Self {
+        Self {
+            scanner: StableScanner::new(songs_path),
+        }
+    }
+
+    /// Create tools for the Songs folder of the configured osu!stable
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new(config.require_stable_songs_path()?))
+    }
+
+    /// Every tool, for listing to the assistant
+    pub fn definitions() -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            name: "search_beatmaps",
+            description: "Search the user's osu!stable beatmap sets. Returns up to `limit` \
+                          sets with their online set ID, artist, title, creator, game modes, \
+                          star range (star_min/star_max), difficulty count and folder name.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Text matched against title, artist, creator, source, tags and folder name"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_SEARCH_LIMIT,
+                        "default": DEFAULT_SEARCH_LIMIT
+                    },
+                    "min_stars": { "type": "number" },
+                    "max_stars": { "type": "number" },
+                    "mode": { "enum": ["Osu", "Taiko", "Catch", "Mania"] }
+                },
+                "required": ["query"]
+            }),
+        }]
+    }
+
+    /// Run the tool `name` with JSON `arguments`
+    pub fn call(&self, name: &str, arguments: Value) -> Result<Value> {
+        match name {
+            "search_beatmaps" => {
+                let args: SearchBeatmapsArgs = serde_json::from_value(arguments).map_err(|e| {
+                    Error::Other(format!("Invalid search_beatmaps arguments: {}", e))
+                })?;
+                let hits = self.search_beatmaps(&args)?;
+                serde_json::to_value(hits).map_err(|e| Error::Other(e.to_string()))
+            }
+            _ => Err(Error::Other(format!("Unknown tool: {}", name))),
+        }
+    }
+
+    /// Find sets matching `args`, using the scan cache when it is current
+    pub fn search_beatmaps(&self, args: &SearchBeatmapsArgs) -> Result<Vec<BeatmapSearchHit>> {
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .min(MAX_SEARCH_LIMIT);
+        let sets = self.scanner.scan_parallel()?;
+        Ok(FilterEngine::search_stable(&sets, &args.criteria(), limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_beatmaps_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let songs = dir.path().join("Songs");
+        for (folder, artist, title) in [
+            ("1 Camellia - Ghost", "Camellia", "Ghost"),
+            (
+                "2 Camellia - Exit This Earth",
+                "Camellia",
+                "Exit This Earth",
+            ),
+            ("3 Someone - Other", "Someone", "Other"),
+        ] {
+            std::fs::create_dir_all(songs.join(folder)).unwrap();
+            let osu = format!(
+                "osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nMode: 0\n\n[Metadata]\nTitle:{}\nArtist:{}\nCreator:Mapper\nVersion:Hard\n\n[HitObjects]\n256,192,100,1,0,0:0:0:0:\n",
+                title, artist
+            );
+            std::fs::write(songs.join(folder).join("map.osu"), osu).unwrap();
+        }
+
+        let tools = OsuSyncTools::new(songs);
+        let hits = tools
+            .call(
+                "search_beatmaps",
+                json!({ "query": "camellia", "limit": 1 }),
+            )
+            .unwrap();
+        let hits = hits.as_array().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["artist"], "Camellia");
+        assert_eq!(hits[0]["modes"], json!(["Osu"]));
+
+        let none = tools
+            .call(
+                "search_beatmaps",
+                json!({ "query": "camellia", "mode": "Mania" }),
+            )
+            .unwrap();
+        assert_eq!(none, json!([]));
+
+        assert!(tools.call("search_beatmaps", json!({})).is_err());
+        assert!(tools.call("delete_everything", json!({})).is_err());
+        assert_eq!(OsuSyncTools::definitions()[0].name, "search_beatmaps");
+    }
+}
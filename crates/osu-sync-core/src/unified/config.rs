@@ -5,7 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Mode for unified storage - determines which installation is the "master".
 ///
@@ -171,6 +171,16 @@ pub struct UnifiedStorageConfig {
     pub use_junctions: bool,
     /// Track changes in a manifest file for efficient syncing.
     pub track_manifest: bool,
+    /// Paths that are never linked or migrated into shared storage.
+    ///
+    /// Relative paths are resolved against each installation (and the shared
+    /// location), e.g. `Skins/My Skin`; absolute paths match one location.
+    /// A resource folder containing exclusions is linked entry by entry, so
+    /// excluded entries stay real directories. An exclusion nested deeper
+    /// keeps its whole top-level entry unlinked. Excluded entries are never
+    /// moved into the `{folder}_backup` directory during setup.
+    #[serde(default)]
+    pub exclude_paths: Vec<PathBuf>,
 }
 
 impl Default for UnifiedStorageConfig {
@@ -191,6 +201,7 @@ impl Default for UnifiedStorageConfig {
             },
             use_junctions: true,
             track_manifest: true,
+            exclude_paths: Vec::new(),
         }
     }
 }
@@ -280,6 +291,41 @@ impl UnifiedStorageConfig {
         self.shared_resources.len()
     }
 
+    /// Excludes a path from linking and migration.
+    pub fn exclude_path(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        if !self.exclude_paths.contains(&path) {
+            self.exclude_paths.push(path);
+        }
+    }
+
+    /// Returns `true` if `path`, inside the location rooted at `root`, is
+    /// excluded or lies within an excluded path.
+    pub fn is_path_excluded(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).ok();
+        self.exclude_paths.iter().any(|excluded| {
+            if excluded.is_absolute() {
+                path.starts_with(excluded)
+            } else {
+                relative.is_some_and(|r| r.starts_with(excluded))
+            }
+        })
+    }
+
+    /// Returns `true` if an exclusion lies strictly inside `path`, meaning
+    /// `path` cannot be linked as a whole.
+    pub fn has_exclusions_within(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).ok();
+        self.exclude_paths.iter().any(|excluded| {
+            let inside = if excluded.is_absolute() {
+                Some(path)
+            } else {
+                relative
+            };
+            inside.is_some_and(|p| excluded.starts_with(p) && excluded.as_path() != p)
+        })
+    }
+
     /// Validates the configuration and returns any errors.
     ///
     /// # Errors
@@ -396,4 +442,18 @@ mod tests {
         assert_eq!(config.mode, UnifiedStorageMode::TrueUnified);
         assert_eq!(config.get_shared_path(), Some(&PathBuf::from("/shared")));
     }
+
+    #[test]
+    fn test_exclude_paths() {
+        let mut config = UnifiedStorageConfig::true_unified(PathBuf::from("/shared"));
+        config.exclude_path("Skins/Mine/Extra");
+        let root = Path::new("/osu");
+
+        assert!(config.is_path_excluded(root, Path::new("/osu/Skins/Mine/Extra/a.png")));
+        assert!(!config.is_path_excluded(root, Path::new("/osu/Skins/Mine")));
+        assert!(config.has_exclusions_within(root, Path::new("/osu/Skins")));
+        assert!(config.has_exclusions_within(root, Path::new("/osu/Skins/Mine")));
+        assert!(!config.has_exclusions_within(root, Path::new("/osu/Skins/Other")));
+        assert!(!config.has_exclusions_within(root, Path::new("/osu/Skins/Mine/Extra")));
+    }
 }
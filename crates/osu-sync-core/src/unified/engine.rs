@@ -10,14 +10,14 @@
 //! - Repair of broken links
 //! - Teardown and cleanup
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
 
 use super::config::{SharedResourceType, UnifiedStorageConfig, UnifiedStorageMode};
-use super::link::{copy_dir_recursive, LinkManager};
+use super::link::{copy_dir_recursive, LinkInfo, LinkManager, LinkType};
 use super::manifest::{LinkStatus, LinkedResource, UnifiedManifest};
 
 /// Result of a setup operation.
@@ -32,6 +32,8 @@ pub struct SetupResult {
     pub resources_linked: usize,
     /// Non-fatal warnings encountered during setup.
     pub warnings: Vec<String>,
+    /// Pre-flight scan of each shared resource in both installations.
+    pub resources: Vec<ResourceUsage>,
 }
 
 impl SetupResult {
@@ -49,6 +51,74 @@ impl SetupResult {
     pub fn add_warning(&mut self, warning: impl Into<String>) {
         self.warnings.push(warning.into());
     }
+
+    /// Returns the resources that have data in both installations.
+    pub fn conflicts(&self) -> impl Iterator<Item = &ResourceUsage> {
+        self.resources.iter().filter(|r| r.is_conflict())
+    }
+
+    /// Returns `true` if any resource has data in both installations.
+    pub fn has_conflicts(&self) -> bool {
+        self.conflicts().next().is_some()
+    }
+}
+
+/// How much data a shared resource holds in each installation.
+///
+/// Produced by [`UnifiedStorageEngine::preflight`] before anything is linked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The resource being described.
+    pub resource_type: SharedResourceType,
+    /// Number of files in the stable installation.
+    pub stable_files: usize,
+    /// Total size in bytes in the stable installation.
+    pub stable_bytes: u64,
+    /// Number of files in the lazer installation.
+    pub lazer_files: usize,
+    /// Total size in bytes in the lazer installation.
+    pub lazer_bytes: u64,
+    /// Whether either side is already a link.
+    pub already_linked: bool,
+}
+
+impl ResourceUsage {
+    /// Returns `true` if both installations hold their own data for this resource.
+    ///
+    /// Setting up a link for a conflicting resource backs up one side.
+    pub fn is_conflict(&self) -> bool {
+        !self.already_linked && self.stable_files > 0 && self.lazer_files > 0
+    }
+}
+
+/// Count files and bytes below `path`, treating a link as empty.
+///
+/// Returns `(files, bytes, is_link)`.
+fn scan_resource_dir(path: &Path) -> (usize, u64, bool) {
+    if LinkManager::is_link(path) {
+        return (0, 0, true);
+    }
+    if !path.is_dir() {
+        return (0, 0, false);
+    }
+
+    let (files, bytes) = measure_tree(path);
+    (files, bytes, false)
+}
+
+/// Counts regular files and their total size under `path`.
+///
+/// Links inside the tree are not followed, so nested links are not counted
+/// twice.
+fn measure_tree(path: &Path) -> (usize, u64) {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .fold((0usize, 0u64), |(files, bytes), entry| {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            (files + 1, bytes + size)
+        })
 }
 
 /// Result of a sync operation.
@@ -109,6 +179,11 @@ pub struct VerificationResult {
     pub broken: usize,
     /// Number of links that are stale (no longer needed).
     pub stale: usize,
+    /// Bytes that would be duplicated without unified storage.
+    ///
+    /// Measured from the on-disk size of each shared target, once for every
+    /// valid link beyond the copy that has to exist somewhere.
+    pub space_saved: u64,
 }
 
 impl VerificationResult {
@@ -161,6 +236,228 @@ impl RepairResult {
     }
 }
 
+/// A unified storage operation that can be planned without running it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnifiedOperation {
+    /// [`UnifiedStorageEngine::setup`]
+    Setup,
+    /// [`UnifiedStorageEngine::sync`]
+    Sync,
+    /// [`UnifiedStorageEngine::repair`]
+    Repair,
+    /// [`UnifiedStorageEngine::teardown`]
+    Teardown,
+}
+
+/// A filesystem change made, or planned, by the unified storage engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedAction {
+    /// Create a link at `link` pointing to `target`.
+    CreateLink { link: PathBuf, target: PathBuf },
+    /// Remove the link at `link`.
+    RemoveLink { link: PathBuf },
+    /// Move an existing directory aside as a backup.
+    Backup { from: PathBuf, to: PathBuf },
+    /// Delete a directory and everything in it.
+    Delete { path: PathBuf },
+    /// Create a directory.
+    CreateDir { path: PathBuf },
+    /// Copy directory contents into another directory.
+    MigrateContents { from: PathBuf, to: PathBuf },
+}
+
+impl std::fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CreateLink { link, target } => {
+                write!(f, "link {} -> {}", link.display(), target.display())
+            }
+            Self::RemoveLink { link } => write!(f, "remove link {}", link.display()),
+            Self::Backup { from, to } => {
+                write!(f, "back up {} to {}", from.display(), to.display())
+            }
+            Self::Delete { path } => write!(f, "delete {}", path.display()),
+            Self::CreateDir { path } => write!(f, "create {}", path.display()),
+            Self::MigrateContents { from, to } => {
+                write!(
+                    f,
+                    "copy contents of {} into {}",
+                    from.display(),
+                    to.display()
+                )
+            }
+        }
+    }
+}
+
+/// Every filesystem change the engine makes goes through here.
+///
+/// In dry-run mode changes are only recorded, so planning and execution
+/// share exactly the same decision logic. Paths the plan has already moved
+/// away or created are tracked so later existence checks see the planned
+/// state rather than the untouched disk.
+#[derive(Debug, Default)]
+struct FsEffects {
+    dry_run: bool,
+    prefer_junctions: bool,
+    recorded: Vec<PlannedAction>,
+    /// Paths removed or renamed away by the current plan.
+    vacated: HashSet<PathBuf>,
+    /// Paths created by the current plan.
+    created: HashSet<PathBuf>,
+}
+
+impl FsEffects {
+    /// Starts recording a new plan.
+    fn begin_plan(&mut self) {
+        self.dry_run = true;
+        self.recorded.clear();
+        self.vacated.clear();
+        self.created.clear();
+    }
+
+    /// Stops recording and returns the planned actions.
+    fn end_plan(&mut self) -> Vec<PlannedAction> {
+        self.dry_run = false;
+        self.vacated.clear();
+        self.created.clear();
+        std::mem::take(&mut self.recorded)
+    }
+
+    /// Whether `path` exists, taking the changes planned so far into account.
+    fn exists(&self, path: &Path) -> bool {
+        if self.dry_run {
+            if self.created.contains(path) {
+                return true;
+            }
+            if self.vacated.contains(path) {
+                return false;
+            }
+        }
+        path.exists()
+    }
+
+    /// Whether `path` is a link, taking the changes planned so far into account.
+    fn is_link(&self, path: &Path) -> bool {
+        if self.dry_run && (self.vacated.contains(path) || self.created.contains(path)) {
+            return false;
+        }
+        LinkManager::is_link(path)
+    }
+
+    fn plan_created(&mut self, path: &Path) {
+        if self.dry_run {
+            self.vacated.remove(path);
+            self.created.insert(path.to_path_buf());
+        }
+    }
+
+    fn plan_vacated(&mut self, path: &Path) {
+        if self.dry_run {
+            self.created.remove(path);
+            self.vacated.insert(path.to_path_buf());
+        }
+    }
+
+    fn link_directory(
+        &mut self,
+        manager: &LinkManager,
+        source: &Path,
+        link: &Path,
+    ) -> Result<LinkInfo> {
+        self.recorded.push(PlannedAction::CreateLink {
+            link: link.to_path_buf(),
+            target: source.to_path_buf(),
+        });
+        if self.dry_run {
+            self.plan_created(link);
+            return Ok(self.planned_link(source, link));
+        }
+        manager.link_directory(source, link)
+    }
+
+    fn create_link(
+        &mut self,
+        manager: &LinkManager,
+        link: &Path,
+        target: &Path,
+    ) -> Result<LinkInfo> {
+        self.recorded.push(PlannedAction::CreateLink {
+            link: link.to_path_buf(),
+            target: target.to_path_buf(),
+        });
+        if self.dry_run {
+            self.plan_created(link);
+            return Ok(self.planned_link(target, link));
+        }
+        manager.create_link(link, target)
+    }
+
+    fn planned_link(&self, source: &Path, link: &Path) -> LinkInfo {
+        let link_type = LinkManager::link_capabilities_at(link)
+            .directory_link_type(self.prefer_junctions)
+            .unwrap_or(LinkType::Copy);
+        LinkInfo::new(source.to_path_buf(), link.to_path_buf(), link_type)
+    }
+
+    fn remove_link(&mut self, link: &Path) -> Result<()> {
+        self.recorded.push(PlannedAction::RemoveLink {
+            link: link.to_path_buf(),
+        });
+        if self.dry_run {
+            self.plan_vacated(link);
+            return Ok(());
+        }
+        LinkManager::remove_link(link)
+    }
+
+    fn rename(&mut self, from: &Path, to: &Path) -> std::io::Result<()> {
+        self.recorded.push(PlannedAction::Backup {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        if self.dry_run {
+            self.plan_vacated(from);
+            self.plan_created(to);
+            return Ok(());
+        }
+        fs::rename(from, to)
+    }
+
+    fn remove_dir_all(&mut self, path: &Path) -> std::io::Result<()> {
+        self.recorded.push(PlannedAction::Delete {
+            path: path.to_path_buf(),
+        });
+        if self.dry_run {
+            self.plan_vacated(path);
+            return Ok(());
+        }
+        fs::remove_dir_all(path)
+    }
+
+    fn create_dir_all(&mut self, path: &Path) -> std::io::Result<()> {
+        self.recorded.push(PlannedAction::CreateDir {
+            path: path.to_path_buf(),
+        });
+        if self.dry_run {
+            self.plan_created(path);
+            return Ok(());
+        }
+        fs::create_dir_all(path)
+    }
+
+    fn migrate_contents(&mut self, from: &Path, to: &Path, skip: &[PathBuf]) -> Result<()> {
+        self.recorded.push(PlannedAction::MigrateContents {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        if self.dry_run {
+            return Ok(());
+        }
+        UnifiedStorageEngine::migrate_directory_contents(from, to, skip)
+    }
+}
+
 /// Main orchestration engine for unified storage operations.
 ///
 /// The `UnifiedStorageEngine` coordinates all unified storage operations,
@@ -201,6 +498,8 @@ pub struct UnifiedStorageEngine {
     manifest: UnifiedManifest,
     /// Manager for filesystem link operations.
     link_manager: LinkManager,
+    /// Filesystem changes, executed or only recorded in dry-run mode.
+    effects: FsEffects,
 }
 
 impl UnifiedStorageEngine {
@@ -236,12 +535,18 @@ impl UnifiedStorageEngine {
         // Initialize link manager with configuration options
         let link_manager = LinkManager::new(config.should_use_junctions());
 
+        let effects = FsEffects {
+            prefer_junctions: config.should_use_junctions(),
+            ..Default::default()
+        };
+
         Ok(Self {
             config,
             stable_path: stable,
             lazer_path: lazer,
             manifest,
             link_manager,
+            effects,
         })
     }
 
@@ -265,14 +570,47 @@ impl UnifiedStorageEngine {
         &self.manifest
     }
 
+    /// Computes the filesystem changes an operation would make, without making them.
+    ///
+    /// Runs the same code as the real operation with every link creation,
+    /// backup, and deletion only recorded. The manifest is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors the real operation would return before
+    /// touching the filesystem, such as unified storage being disabled.
+    pub fn plan(&mut self, operation: UnifiedOperation) -> Result<Vec<PlannedAction>> {
+        let manifest = self.manifest.clone();
+        self.effects.begin_plan();
+
+        let outcome = match operation {
+            UnifiedOperation::Setup => self.setup().map(|_| ()),
+            UnifiedOperation::Sync => self.sync().map(|_| ()),
+            UnifiedOperation::Repair => self.repair().map(|_| ()),
+            UnifiedOperation::Teardown => self.teardown(),
+        };
+
+        let planned = self.effects.end_plan();
+        self.manifest = manifest;
+        outcome.map(|()| planned)
+    }
+
     /// Performs initial setup of unified storage.
     ///
+    /// Run [`preflight`](Self::preflight) first and confirm with the user;
+    /// this method backs up and links directories immediately.
+    ///
     /// This operation:
     /// 1. Analyzes both installations for shared resources
     /// 2. Backs up existing data if necessary
     /// 3. Creates symbolic links or junctions as configured
     /// 4. Updates the manifest with tracked links
     ///
+    /// Resource folders containing `exclude_paths` are linked entry by entry
+    /// instead, so excluded entries stay real directories. Only entries that
+    /// are replaced by a link are moved into `{folder}_backup`; excluded
+    /// entries are left untouched and are never migrated or tracked.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
@@ -288,13 +626,8 @@ impl UnifiedStorageEngine {
 
         tracing::info!("Setting up unified storage in {:?} mode", self.config.mode);
 
-        let mut result = SetupResult::new();
-
-        // TODO: Implement setup logic
-        // 1. Scan shared resources in both installations
-        // 2. Determine which installation owns each resource
-        // 3. Create links from non-master to master
-        // 4. Update manifest
+        let mut result = self.preflight()?;
+        result.warnings.clear();
 
         match self.config.mode {
             UnifiedStorageMode::Disabled => {
@@ -321,6 +654,53 @@ impl UnifiedStorageEngine {
         Ok(result)
     }
 
+    /// Scans shared resources in both installations without changing anything.
+    ///
+    /// Reports how much data each shared resource holds per installation and
+    /// flags conflicts where both sides have their own data. Callers should
+    /// show this to the user and only call [`setup`](Self::setup), which
+    /// backs up and links directories, once they have confirmed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if unified storage is not enabled in the configuration.
+    pub fn preflight(&self) -> Result<SetupResult> {
+        if !self.config.is_enabled() {
+            return Err(Error::Config(
+                "Unified storage is not enabled in configuration".to_string(),
+            ));
+        }
+
+        let mut result = SetupResult::new();
+        for resource_type in self.config.shared_resources_iter() {
+            let folder_name = resource_type.folder_name();
+            let (stable_files, stable_bytes, stable_link) =
+                scan_resource_dir(&self.stable_path.join(folder_name));
+            let (lazer_files, lazer_bytes, lazer_link) =
+                scan_resource_dir(&self.lazer_path.join(folder_name));
+
+            let usage = ResourceUsage {
+                resource_type: *resource_type,
+                stable_files,
+                stable_bytes,
+                lazer_files,
+                lazer_bytes,
+                already_linked: stable_link || lazer_link,
+            };
+            if usage.is_conflict() {
+                result.add_warning(format!(
+                    "{} has data in both installations ({} files in stable, {} in lazer)",
+                    resource_type.display_name(),
+                    stable_files,
+                    lazer_files
+                ));
+            }
+            result.resources.push(usage);
+        }
+
+        Ok(result)
+    }
+
     /// Synchronizes changes between installations.
     ///
     /// This operation:
@@ -378,11 +758,20 @@ impl UnifiedStorageEngine {
         let mut result = VerificationResult::new();
 
         for resource in self.manifest.iter() {
+            // Excluded paths may linger in manifests written before the exclusion
+            if self.is_excluded(&resource.source_path) {
+                continue;
+            }
+
             // Check if this resource is marked as stale in the manifest
             let is_stale = resource.status == LinkStatus::Stale;
+            let mut deduplicated_links = 0u64;
 
             // Count and verify each link for this resource
             for link_path in &resource.link_paths {
+                if self.is_excluded(link_path) {
+                    continue;
+                }
                 result.total_links += 1;
 
                 // If the resource is marked as stale, count all its links as stale
@@ -391,11 +780,18 @@ impl UnifiedStorageEngine {
                     continue;
                 }
 
-                // Otherwise, verify the link's validity
-                match self.link_manager.check_link(link_path) {
+                // Otherwise, verify the link's validity using the mechanism it
+                // was created with
+                match self
+                    .link_manager
+                    .check_link_as(link_path, resource.link_type)
+                {
                     Ok(info) => {
                         if info.is_valid {
                             result.active += 1;
+                            if info.link_type != Some(LinkType::Copy) {
+                                deduplicated_links += 1;
+                            }
                         } else {
                             result.broken += 1;
                         }
@@ -405,19 +801,47 @@ impl UnifiedStorageEngine {
                     }
                 }
             }
+
+            if deduplicated_links > 0 {
+                // A source in the shared folder replaces one installation's
+                // copy, so only the links beyond the first save space there.
+                let in_shared = self
+                    .config
+                    .get_shared_path()
+                    .is_some_and(|shared| resource.source_path.starts_with(shared));
+                let saved_copies = if in_shared {
+                    deduplicated_links - 1
+                } else {
+                    deduplicated_links
+                };
+                if saved_copies > 0 {
+                    result.space_saved += saved_copies * Self::on_disk_size(&resource.source_path);
+                }
+            }
         }
 
         tracing::info!(
-            "Verification complete: {} total, {} active, {} broken, {} stale",
+            "Verification complete: {} total, {} active, {} broken, {} stale, {} bytes saved",
             result.total_links,
             result.active,
             result.broken,
-            result.stale
+            result.stale,
+            result.space_saved
         );
 
         Ok(result)
     }
 
+    /// Returns the on-disk size of a shared target, in bytes.
+    fn on_disk_size(path: &Path) -> u64 {
+        let target = LinkManager::read_link(path).unwrap_or_else(|_| path.to_path_buf());
+        if target.is_file() {
+            fs::metadata(&target).map(|m| m.len()).unwrap_or(0)
+        } else {
+            measure_tree(&target).1
+        }
+    }
+
     /// Repairs broken or stale links.
     ///
     /// This operation:
@@ -442,28 +866,41 @@ impl UnifiedStorageEngine {
             .manifest
             .find_needing_attention()
             .into_iter()
-            .map(|r| (r.source_path.clone(), r.link_paths.clone(), r.status))
+            .map(|r| {
+                (
+                    r.source_path.clone(),
+                    r.link_paths.clone(),
+                    r.status,
+                    r.link_type,
+                )
+            })
             .collect();
 
         // Track which resources were successfully repaired so we can update their status
-        let mut repaired_sources: Vec<PathBuf> = Vec::new();
+        let mut repaired_sources: Vec<(PathBuf, Option<LinkType>)> = Vec::new();
 
-        for (source_path, link_paths, status) in resources_to_repair {
+        for (source_path, link_paths, status, link_type) in resources_to_repair {
             // Skip stale resources here - they're handled separately below
             if status == LinkStatus::Stale {
                 continue;
             }
 
             let mut all_links_ok = true;
+            let mut repaired_type = link_type;
 
             for link_path in &link_paths {
-                match self.link_manager.check_link(link_path) {
+                match self.link_manager.check_link_as(link_path, link_type) {
                     Ok(info) => {
                         if !info.is_valid {
                             // Attempt to repair by recreating link
-                            match self.link_manager.create_link(link_path, &source_path) {
-                                Ok(_) => {
+                            match self.effects.create_link(
+                                &self.link_manager,
+                                link_path,
+                                &source_path,
+                            ) {
+                                Ok(info) => {
                                     result.repaired += 1;
+                                    repaired_type = Some(info.link_type);
                                 }
                                 Err(e) => {
                                     tracing::warn!(
@@ -479,9 +916,13 @@ impl UnifiedStorageEngine {
                     }
                     Err(_) => {
                         // Link doesn't exist, try to create it
-                        match self.link_manager.create_link(link_path, &source_path) {
-                            Ok(_) => {
+                        match self
+                            .effects
+                            .create_link(&self.link_manager, link_path, &source_path)
+                        {
+                            Ok(info) => {
                                 result.repaired += 1;
+                                repaired_type = Some(info.link_type);
                             }
                             Err(e) => {
                                 tracing::warn!(
@@ -499,14 +940,17 @@ impl UnifiedStorageEngine {
 
             // If all links for this resource were successfully repaired, mark for status update
             if all_links_ok && !link_paths.is_empty() {
-                repaired_sources.push(source_path);
+                repaired_sources.push((source_path, repaired_type));
             }
         }
 
         // Update status to Active for successfully repaired resources
-        for source_path in repaired_sources {
+        for (source_path, link_type) in repaired_sources {
             self.manifest
                 .update_status(&source_path, LinkStatus::Active);
+            if let Some(resource) = self.manifest.find_by_source_mut(&source_path) {
+                resource.link_type = link_type;
+            }
         }
 
         // Handle stale links - remove them from the manifest
@@ -562,7 +1006,7 @@ impl UnifiedStorageEngine {
         let mut removed_count = 0;
 
         for link_path in all_link_paths {
-            if let Err(e) = LinkManager::remove_link(&link_path) {
+            if let Err(e) = self.effects.remove_link(&link_path) {
                 tracing::warn!("Failed to remove link {}: {}", link_path.display(), e);
             } else {
                 removed_count += 1;
@@ -591,7 +1035,11 @@ impl UnifiedStorageEngine {
     fn setup_stable_master(&mut self, result: &mut SetupResult) -> Result<()> {
         tracing::debug!("Setting up StableMaster mode");
 
-        for resource_type in self.config.shared_resources_iter() {
+        // Collect resource types to avoid borrow issues
+        let resource_types: Vec<SharedResourceType> =
+            self.config.shared_resources_iter().cloned().collect();
+
+        for resource_type in resource_types {
             let folder_name = resource_type.folder_name();
 
             let stable_resource = self.stable_path.join(folder_name);
@@ -603,6 +1051,16 @@ impl UnifiedStorageEngine {
                 continue;
             }
 
+            if self.apply_exclusions(
+                resource_type,
+                &stable_resource,
+                std::slice::from_ref(&lazer_resource),
+                false,
+                result,
+            )? {
+                continue;
+            }
+
             // If lazer already has content at that path, back it up
             if lazer_resource.exists() {
                 // Check if it's already a link pointing to the right target
@@ -615,12 +1073,15 @@ impl UnifiedStorageEngine {
                             tracing::debug!("{} already linked correctly, skipping", folder_name);
                             // Still add to manifest if not already tracked
                             if self.manifest.find_by_source(&stable_resource).is_none() {
-                                self.manifest.add_resource(LinkedResource::active(
-                                    *resource_type,
-                                    stable_resource.clone(),
-                                    vec![lazer_resource.clone()],
-                                    None,
-                                ));
+                                self.manifest.add_resource(
+                                    LinkedResource::active(
+                                        resource_type,
+                                        stable_resource.clone(),
+                                        vec![lazer_resource.clone()],
+                                        None,
+                                    )
+                                    .with_link_type(LinkManager::detect_link_type(&lazer_resource)),
+                                );
                                 result.resources_linked += 1;
                             }
                             continue;
@@ -631,7 +1092,7 @@ impl UnifiedStorageEngine {
                         "Removing existing link at {} (points to wrong target)",
                         lazer_resource.display()
                     );
-                    if let Err(e) = LinkManager::remove_link(&lazer_resource) {
+                    if let Err(e) = self.effects.remove_link(&lazer_resource) {
                         result.add_warning(format!(
                             "Failed to remove existing link at {}: {}",
                             lazer_resource.display(),
@@ -650,7 +1111,7 @@ impl UnifiedStorageEngine {
 
                     // If backup already exists, remove it first
                     if backup_path.exists() {
-                        if let Err(e) = fs::remove_dir_all(&backup_path) {
+                        if let Err(e) = self.effects.remove_dir_all(&backup_path) {
                             result.add_warning(format!(
                                 "Failed to remove old backup at {}: {}",
                                 backup_path.display(),
@@ -660,7 +1121,7 @@ impl UnifiedStorageEngine {
                         }
                     }
 
-                    if let Err(e) = fs::rename(&lazer_resource, &backup_path) {
+                    if let Err(e) = self.effects.rename(&lazer_resource, &backup_path) {
                         result.add_warning(format!(
                             "Failed to backup {} to {}: {}",
                             lazer_resource.display(),
@@ -680,8 +1141,8 @@ impl UnifiedStorageEngine {
             );
 
             match self
-                .link_manager
-                .link_directory(&stable_resource, &lazer_resource)
+                .effects
+                .link_directory(&self.link_manager, &stable_resource, &lazer_resource)
             {
                 Ok(link_info) => {
                     tracing::info!(
@@ -692,12 +1153,15 @@ impl UnifiedStorageEngine {
                     );
 
                     // Add to manifest
-                    self.manifest.add_resource(LinkedResource::active(
-                        *resource_type,
-                        stable_resource.clone(),
-                        vec![lazer_resource.clone()],
-                        None,
-                    ));
+                    self.manifest.add_resource(
+                        LinkedResource::active(
+                            resource_type,
+                            stable_resource.clone(),
+                            vec![lazer_resource.clone()],
+                            None,
+                        )
+                        .with_link_type(Some(link_info.link_type)),
+                    );
 
                     result.links_created += 1;
                     result.resources_linked += 1;
@@ -718,7 +1182,11 @@ impl UnifiedStorageEngine {
     fn setup_lazer_master(&mut self, result: &mut SetupResult) -> Result<()> {
         tracing::debug!("Setting up LazerMaster mode");
 
-        for resource_type in self.config.shared_resources_iter() {
+        // Collect resource types to avoid borrow issues
+        let resource_types: Vec<SharedResourceType> =
+            self.config.shared_resources_iter().cloned().collect();
+
+        for resource_type in resource_types {
             let folder_name = resource_type.folder_name();
 
             let stable_resource = self.stable_path.join(folder_name);
@@ -730,6 +1198,16 @@ impl UnifiedStorageEngine {
                 continue;
             }
 
+            if self.apply_exclusions(
+                resource_type,
+                &lazer_resource,
+                std::slice::from_ref(&stable_resource),
+                false,
+                result,
+            )? {
+                continue;
+            }
+
             // If stable already has content at that path, handle it
             if stable_resource.exists() {
                 // Check if it's already a link pointing to the right target
@@ -742,12 +1220,17 @@ impl UnifiedStorageEngine {
                             tracing::debug!("{} already linked correctly, skipping", folder_name);
                             // Still add to manifest if not already tracked
                             if self.manifest.find_by_source(&lazer_resource).is_none() {
-                                self.manifest.add_resource(LinkedResource::active(
-                                    *resource_type,
-                                    lazer_resource.clone(),
-                                    vec![stable_resource.clone()],
-                                    None,
-                                ));
+                                self.manifest.add_resource(
+                                    LinkedResource::active(
+                                        resource_type,
+                                        lazer_resource.clone(),
+                                        vec![stable_resource.clone()],
+                                        None,
+                                    )
+                                    .with_link_type(
+                                        LinkManager::detect_link_type(&stable_resource),
+                                    ),
+                                );
                                 result.resources_linked += 1;
                             }
                             continue;
@@ -758,7 +1241,7 @@ impl UnifiedStorageEngine {
                         "Removing existing link at {} (points to wrong target)",
                         stable_resource.display()
                     );
-                    if let Err(e) = LinkManager::remove_link(&stable_resource) {
+                    if let Err(e) = self.effects.remove_link(&stable_resource) {
                         result.add_warning(format!(
                             "Failed to remove existing link at {}: {}",
                             stable_resource.display(),
@@ -777,7 +1260,7 @@ impl UnifiedStorageEngine {
 
                     // If backup already exists, remove it first
                     if backup_path.exists() {
-                        if let Err(e) = fs::remove_dir_all(&backup_path) {
+                        if let Err(e) = self.effects.remove_dir_all(&backup_path) {
                             result.add_warning(format!(
                                 "Failed to remove old backup at {}: {}",
                                 backup_path.display(),
@@ -787,7 +1270,7 @@ impl UnifiedStorageEngine {
                         }
                     }
 
-                    if let Err(e) = fs::rename(&stable_resource, &backup_path) {
+                    if let Err(e) = self.effects.rename(&stable_resource, &backup_path) {
                         result.add_warning(format!(
                             "Failed to backup {} to {}: {}",
                             stable_resource.display(),
@@ -807,8 +1290,8 @@ impl UnifiedStorageEngine {
             );
 
             match self
-                .link_manager
-                .create_link(&stable_resource, &lazer_resource)
+                .effects
+                .create_link(&self.link_manager, &stable_resource, &lazer_resource)
             {
                 Ok(link_info) => {
                     tracing::info!(
@@ -819,12 +1302,15 @@ impl UnifiedStorageEngine {
                     );
 
                     // Add to manifest (lazer is the source, stable is the link)
-                    self.manifest.add_resource(LinkedResource::active(
-                        *resource_type,
-                        lazer_resource.clone(),
-                        vec![stable_resource.clone()],
-                        None,
-                    ));
+                    self.manifest.add_resource(
+                        LinkedResource::active(
+                            resource_type,
+                            lazer_resource.clone(),
+                            vec![stable_resource.clone()],
+                            None,
+                        )
+                        .with_link_type(Some(link_info.link_type)),
+                    );
 
                     result.links_created += 1;
                     result.resources_linked += 1;
@@ -868,9 +1354,19 @@ impl UnifiedStorageEngine {
                 shared_resource.display()
             );
 
+            if self.apply_exclusions(
+                resource_type,
+                &shared_resource,
+                &[stable_resource.clone(), lazer_resource.clone()],
+                true,
+                result,
+            )? {
+                continue;
+            }
+
             // Step 1: Create the shared folder if it doesn't exist
-            if !shared_resource.exists() {
-                fs::create_dir_all(&shared_resource).map_err(|e| {
+            if !self.effects.exists(&shared_resource) {
+                self.effects.create_dir_all(&shared_resource).map_err(|e| {
                     Error::Other(format!(
                         "Failed to create shared directory {}: {}",
                         shared_resource.display(),
@@ -882,8 +1378,9 @@ impl UnifiedStorageEngine {
 
             // Step 2: Migrate content from BOTH installations to shared location
             // Prefer stable content first, then add unique lazer content
-            if stable_resource.exists() && !LinkManager::is_link(&stable_resource) {
-                Self::migrate_directory_contents(&stable_resource, &shared_resource)?;
+            if self.effects.exists(&stable_resource) && !self.effects.is_link(&stable_resource) {
+                self.effects
+                    .migrate_contents(&stable_resource, &shared_resource, &[])?;
                 tracing::debug!(
                     "Migrated stable content from {} to {}",
                     stable_resource.display(),
@@ -891,9 +1388,10 @@ impl UnifiedStorageEngine {
                 );
             }
 
-            if lazer_resource.exists() && !LinkManager::is_link(&lazer_resource) {
+            if self.effects.exists(&lazer_resource) && !self.effects.is_link(&lazer_resource) {
                 // Only copy unique content from lazer (files that don't exist in shared)
-                Self::migrate_directory_contents(&lazer_resource, &shared_resource)?;
+                self.effects
+                    .migrate_contents(&lazer_resource, &shared_resource, &[])?;
                 tracing::debug!(
                     "Migrated unique lazer content from {} to {}",
                     lazer_resource.display(),
@@ -903,14 +1401,15 @@ impl UnifiedStorageEngine {
 
             // Step 3: Back up both stable and lazer folders (rename to {folder}_backup)
             let mut links_created_for_resource = 0;
+            let mut link_type = None;
 
             // Back up stable folder if it exists and is not already a link
-            if stable_resource.exists() && !LinkManager::is_link(&stable_resource) {
+            if self.effects.exists(&stable_resource) && !self.effects.is_link(&stable_resource) {
                 let backup_path = self.stable_path.join(format!("{}_backup", folder_name));
 
                 // Remove old backup if it exists
                 if backup_path.exists() {
-                    if let Err(e) = fs::remove_dir_all(&backup_path) {
+                    if let Err(e) = self.effects.remove_dir_all(&backup_path) {
                         result.add_warning(format!(
                             "Failed to remove old stable backup at {}: {}",
                             backup_path.display(),
@@ -919,7 +1418,7 @@ impl UnifiedStorageEngine {
                     }
                 }
 
-                if let Err(e) = fs::rename(&stable_resource, &backup_path) {
+                if let Err(e) = self.effects.rename(&stable_resource, &backup_path) {
                     result.add_warning(format!(
                         "Failed to backup stable {} to {}: {}",
                         stable_resource.display(),
@@ -936,12 +1435,12 @@ impl UnifiedStorageEngine {
             }
 
             // Back up lazer folder if it exists and is not already a link
-            if lazer_resource.exists() && !LinkManager::is_link(&lazer_resource) {
+            if self.effects.exists(&lazer_resource) && !self.effects.is_link(&lazer_resource) {
                 let backup_path = self.lazer_path.join(format!("{}_backup", folder_name));
 
                 // Remove old backup if it exists
                 if backup_path.exists() {
-                    if let Err(e) = fs::remove_dir_all(&backup_path) {
+                    if let Err(e) = self.effects.remove_dir_all(&backup_path) {
                         result.add_warning(format!(
                             "Failed to remove old lazer backup at {}: {}",
                             backup_path.display(),
@@ -950,7 +1449,7 @@ impl UnifiedStorageEngine {
                     }
                 }
 
-                if let Err(e) = fs::rename(&lazer_resource, &backup_path) {
+                if let Err(e) = self.effects.rename(&lazer_resource, &backup_path) {
                     result.add_warning(format!(
                         "Failed to backup lazer {} to {}: {}",
                         lazer_resource.display(),
@@ -968,26 +1467,26 @@ impl UnifiedStorageEngine {
 
             // Step 4: Create links from BOTH stable and lazer to the shared location
             // Remove existing links that point to wrong target
-            if stable_resource.exists() && LinkManager::is_link(&stable_resource) {
+            if self.effects.exists(&stable_resource) && self.effects.is_link(&stable_resource) {
                 if let Ok(target) = LinkManager::read_link(&stable_resource) {
                     let shared_canonical = shared_resource.canonicalize().ok();
                     let target_canonical = target.canonicalize().ok();
                     if shared_canonical != target_canonical {
                         // Link points to wrong target, remove it
-                        if let Err(e) = LinkManager::remove_link(&stable_resource) {
+                        if let Err(e) = self.effects.remove_link(&stable_resource) {
                             result.add_warning(format!("Failed to remove old stable link: {}", e));
                         }
                     }
                 }
             }
 
-            if lazer_resource.exists() && LinkManager::is_link(&lazer_resource) {
+            if self.effects.exists(&lazer_resource) && self.effects.is_link(&lazer_resource) {
                 if let Ok(target) = LinkManager::read_link(&lazer_resource) {
                     let shared_canonical = shared_resource.canonicalize().ok();
                     let target_canonical = target.canonicalize().ok();
                     if shared_canonical != target_canonical {
                         // Link points to wrong target, remove it
-                        if let Err(e) = LinkManager::remove_link(&lazer_resource) {
+                        if let Err(e) = self.effects.remove_link(&lazer_resource) {
                             result.add_warning(format!("Failed to remove old lazer link: {}", e));
                         }
                     }
@@ -995,11 +1494,12 @@ impl UnifiedStorageEngine {
             }
 
             // Create stable link to shared location
-            if !stable_resource.exists() {
-                match self
-                    .link_manager
-                    .link_directory(&shared_resource, &stable_resource)
-                {
+            if !self.effects.exists(&stable_resource) {
+                match self.effects.link_directory(
+                    &self.link_manager,
+                    &shared_resource,
+                    &stable_resource,
+                ) {
                     Ok(link_info) => {
                         tracing::info!(
                             "Created {} link: {} -> {}",
@@ -1008,6 +1508,7 @@ impl UnifiedStorageEngine {
                             shared_resource.display()
                         );
                         links_created_for_resource += 1;
+                        link_type = Some(link_info.link_type);
                     }
                     Err(e) => {
                         result.add_warning(format!(
@@ -1016,17 +1517,19 @@ impl UnifiedStorageEngine {
                         ));
                     }
                 }
-            } else if LinkManager::is_link(&stable_resource) {
+            } else if self.effects.is_link(&stable_resource) {
                 // Link already exists and points to correct target
                 links_created_for_resource += 1;
+                link_type = link_type.or(LinkManager::detect_link_type(&stable_resource));
             }
 
             // Create lazer link to shared location
-            if !lazer_resource.exists() {
-                match self
-                    .link_manager
-                    .link_directory(&shared_resource, &lazer_resource)
-                {
+            if !self.effects.exists(&lazer_resource) {
+                match self.effects.link_directory(
+                    &self.link_manager,
+                    &shared_resource,
+                    &lazer_resource,
+                ) {
                     Ok(link_info) => {
                         tracing::info!(
                             "Created {} link: {} -> {}",
@@ -1035,6 +1538,7 @@ impl UnifiedStorageEngine {
                             shared_resource.display()
                         );
                         links_created_for_resource += 1;
+                        link_type = Some(link_info.link_type);
                     }
                     Err(e) => {
                         result.add_warning(format!(
@@ -1043,9 +1547,10 @@ impl UnifiedStorageEngine {
                         ));
                     }
                 }
-            } else if LinkManager::is_link(&lazer_resource) {
+            } else if self.effects.is_link(&lazer_resource) {
                 // Link already exists and points to correct target
                 links_created_for_resource += 1;
+                link_type = link_type.or(LinkManager::detect_link_type(&lazer_resource));
             }
 
             // Step 5: Track in manifest (source = shared path, link_paths = [stable_path, lazer_path])
@@ -1055,7 +1560,8 @@ impl UnifiedStorageEngine {
                     shared_resource.clone(),
                     vec![stable_resource.clone(), lazer_resource.clone()],
                     None,
-                );
+                )
+                .with_link_type(link_type);
                 self.manifest.add_resource(linked_resource);
 
                 result.links_created += links_created_for_resource;
@@ -1066,9 +1572,235 @@ impl UnifiedStorageEngine {
         Ok(())
     }
 
+    // -------------------------------------------------------------------------
+    // Exclusions
+    // -------------------------------------------------------------------------
+
+    /// Returns `true` if `path` is covered by `exclude_paths` in any location.
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclusion_roots()
+            .any(|root| self.config.is_path_excluded(root, path))
+    }
+
+    /// Returns `true` if `path` is excluded or cannot be linked as a whole
+    /// because an exclusion lies inside it.
+    fn is_entry_excluded(&self, path: &Path) -> bool {
+        self.exclusion_roots().any(|root| {
+            self.config.is_path_excluded(root, path)
+                || self.config.has_exclusions_within(root, path)
+        })
+    }
+
+    /// Returns the locations relative exclusions are resolved against.
+    fn exclusion_roots(&self) -> impl Iterator<Item = &Path> {
+        [
+            Some(&self.stable_path),
+            Some(&self.lazer_path),
+            self.config.get_shared_path(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(PathBuf::as_path)
+    }
+
+    /// Handles a resource folder affected by `exclude_paths`.
+    ///
+    /// Returns `false` if no exclusion applies and the caller should link the
+    /// folder as a whole. Otherwise the folder is either skipped entirely or
+    /// linked entry by entry.
+    fn apply_exclusions(
+        &mut self,
+        resource_type: SharedResourceType,
+        master: &Path,
+        followers: &[PathBuf],
+        migrate: bool,
+        result: &mut SetupResult,
+    ) -> Result<bool> {
+        let folders = || std::iter::once(master).chain(followers.iter().map(PathBuf::as_path));
+
+        if folders().any(|folder| self.is_excluded(folder)) {
+            tracing::debug!(
+                "{} is excluded, leaving it unlinked",
+                resource_type.folder_name()
+            );
+            return Ok(true);
+        }
+        if !folders().any(|folder| self.is_entry_excluded(folder)) {
+            return Ok(false);
+        }
+
+        self.link_entries(resource_type, master, followers, migrate, result)?;
+        Ok(true)
+    }
+
+    /// Links a resource folder entry by entry, leaving excluded entries alone.
+    ///
+    /// Every non-excluded entry of `master` is linked into each follower
+    /// folder, which stays a real directory. Follower entries in the way are
+    /// moved into `{folder}_backup` first. Excluded entries are not migrated,
+    /// backed up, linked, or tracked in the manifest. With `migrate` set,
+    /// follower contents are first copied into `master` (TrueUnified mode).
+    fn link_entries(
+        &mut self,
+        resource_type: SharedResourceType,
+        master: &Path,
+        followers: &[PathBuf],
+        migrate: bool,
+        result: &mut SetupResult,
+    ) -> Result<()> {
+        let folder_name = resource_type.folder_name();
+        tracing::debug!("Linking {} entry by entry to honor exclusions", folder_name);
+
+        let list_dir = |dir: &Path| -> Vec<PathBuf> {
+            fs::read_dir(dir)
+                .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+                .unwrap_or_default()
+        };
+
+        if migrate && !self.effects.exists(master) {
+            self.effects.create_dir_all(master).map_err(|e| {
+                Error::Other(format!(
+                    "Failed to create shared directory {}: {}",
+                    master.display(),
+                    e
+                ))
+            })?;
+        }
+
+        let mut entry_names: BTreeSet<std::ffi::OsString> = list_dir(master)
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_os_string()))
+            .collect();
+
+        for follower in followers {
+            // A whole-folder link from an earlier setup is replaced by a real directory
+            if self.effects.is_link(follower) {
+                if let Err(e) = self.effects.remove_link(follower) {
+                    result.add_warning(format!(
+                        "Failed to remove folder link {}: {}",
+                        follower.display(),
+                        e
+                    ));
+                    continue;
+                }
+            } else if migrate && follower.is_dir() {
+                let (excluded, included): (Vec<PathBuf>, Vec<PathBuf>) = list_dir(follower)
+                    .into_iter()
+                    .partition(|p| self.is_entry_excluded(p));
+                self.effects.migrate_contents(follower, master, &excluded)?;
+                entry_names.extend(
+                    included
+                        .iter()
+                        .filter_map(|p| p.file_name().map(|n| n.to_os_string())),
+                );
+            }
+
+            if !self.effects.exists(follower) {
+                if let Err(e) = self.effects.create_dir_all(follower) {
+                    result.add_warning(format!("Failed to create {}: {}", follower.display(), e));
+                }
+            }
+        }
+
+        for name in entry_names {
+            let source = master.join(&name);
+            if self.is_entry_excluded(&source) {
+                continue;
+            }
+
+            let mut links = Vec::new();
+            let mut link_type = None;
+
+            for follower in followers {
+                let link = follower.join(&name);
+                if self.is_entry_excluded(&link) {
+                    continue;
+                }
+
+                if LinkManager::is_link(&link) {
+                    let target = LinkManager::read_link(&link).ok();
+                    let target_canonical = target.and_then(|t| t.canonicalize().ok());
+                    if target_canonical.is_some() && target_canonical == source.canonicalize().ok()
+                    {
+                        link_type = link_type.or(LinkManager::detect_link_type(&link));
+                        links.push(link);
+                        continue;
+                    }
+                    if let Err(e) = self.effects.remove_link(&link) {
+                        result.add_warning(format!(
+                            "Failed to remove old link {}: {}",
+                            link.display(),
+                            e
+                        ));
+                        continue;
+                    }
+                } else if link.exists() {
+                    let install = follower.parent().unwrap_or(follower);
+                    let backup_dir = install.join(format!("{}_backup", folder_name));
+                    let backup_path = backup_dir.join(&name);
+                    let backed_up = self
+                        .effects
+                        .create_dir_all(&backup_dir)
+                        .and_then(|()| {
+                            if backup_path.exists() {
+                                self.effects.remove_dir_all(&backup_path)
+                            } else {
+                                Ok(())
+                            }
+                        })
+                        .and_then(|()| self.effects.rename(&link, &backup_path));
+                    if let Err(e) = backed_up {
+                        result.add_warning(format!(
+                            "Failed to backup {} to {}: {}",
+                            link.display(),
+                            backup_path.display(),
+                            e
+                        ));
+                        continue;
+                    }
+                }
+
+                match self.effects.create_link(&self.link_manager, &link, &source) {
+                    Ok(link_info) => {
+                        tracing::debug!(
+                            "Created {} link: {} -> {}",
+                            link_info.link_type,
+                            link.display(),
+                            source.display()
+                        );
+                        link_type = Some(link_info.link_type);
+                        links.push(link);
+                        result.links_created += 1;
+                    }
+                    Err(e) => {
+                        result.add_warning(format!(
+                            "Failed to create link {}: {}",
+                            link.display(),
+                            e
+                        ));
+                    }
+                }
+            }
+
+            if !links.is_empty() {
+                self.manifest.add_resource(
+                    LinkedResource::active(resource_type, source, links, None)
+                        .with_link_type(link_type),
+                );
+                result.resources_linked += 1;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Migrates all contents from source directory to destination.
-    /// Skips files that already exist in the destination.
-    fn migrate_directory_contents(src: &std::path::Path, dst: &std::path::Path) -> Result<()> {
+    /// Skips files that already exist in the destination and entries in `skip`.
+    fn migrate_directory_contents(
+        src: &std::path::Path,
+        dst: &std::path::Path,
+        skip: &[PathBuf],
+    ) -> Result<()> {
         if !src.is_dir() {
             return Ok(());
         }
@@ -1083,7 +1815,7 @@ impl UnifiedStorageEngine {
             let dst_path = dst.join(&file_name);
 
             // Skip if destination already exists (prefer existing content)
-            if dst_path.exists() {
+            if dst_path.exists() || skip.contains(&src_path) {
                 continue;
             }
 
@@ -1166,18 +1898,22 @@ impl UnifiedStorageEngine {
                             if beatmap_canonical.is_some() && beatmap_canonical == target_canonical
                             {
                                 // Link exists and is correct, just add to manifest
-                                self.manifest.add_resource(LinkedResource::active(
-                                    SharedResourceType::Beatmaps,
-                                    beatmap_path.clone(),
-                                    vec![link_path],
-                                    None,
-                                ));
+                                let link_type = LinkManager::detect_link_type(&link_path);
+                                self.manifest.add_resource(
+                                    LinkedResource::active(
+                                        SharedResourceType::Beatmaps,
+                                        beatmap_path.clone(),
+                                        vec![link_path],
+                                        None,
+                                    )
+                                    .with_link_type(link_type),
+                                );
                                 result.new_links += 1;
                                 continue;
                             }
                         }
                         // Link exists but points to wrong target - remove and recreate
-                        if let Err(e) = LinkManager::remove_link(&link_path) {
+                        if let Err(e) = self.effects.remove_link(&link_path) {
                             result.add_error(format!(
                                 "Failed to remove stale link {}: {}",
                                 link_path.display(),
@@ -1196,14 +1932,20 @@ impl UnifiedStorageEngine {
                 }
 
                 // Create the link
-                match self.link_manager.link_directory(beatmap_path, &link_path) {
-                    Ok(_) => {
-                        self.manifest.add_resource(LinkedResource::active(
-                            SharedResourceType::Beatmaps,
-                            beatmap_path.clone(),
-                            vec![link_path],
-                            None,
-                        ));
+                match self
+                    .effects
+                    .link_directory(&self.link_manager, beatmap_path, &link_path)
+                {
+                    Ok(link_info) => {
+                        self.manifest.add_resource(
+                            LinkedResource::active(
+                                SharedResourceType::Beatmaps,
+                                beatmap_path.clone(),
+                                vec![link_path],
+                                None,
+                            )
+                            .with_link_type(Some(link_info.link_type)),
+                        );
                         result.new_links += 1;
                     }
                     Err(e) => {
@@ -1251,7 +1993,11 @@ impl UnifiedStorageEngine {
                 if !LinkManager::is_link(&link_path) {
                     // Link is broken or missing - try to recreate
                     if source_path.exists() {
-                        match self.link_manager.link_directory(&source_path, &link_path) {
+                        match self.effects.link_directory(
+                            &self.link_manager,
+                            &source_path,
+                            &link_path,
+                        ) {
                             Ok(_) => {
                                 result.updated += 1;
                                 tracing::debug!("Recreated link: {}", link_path.display());
@@ -1336,18 +2082,22 @@ impl UnifiedStorageEngine {
                             if beatmap_canonical.is_some() && beatmap_canonical == target_canonical
                             {
                                 // Link exists and is correct, just add to manifest
-                                self.manifest.add_resource(LinkedResource::active(
-                                    SharedResourceType::Beatmaps,
-                                    beatmap_path.clone(),
-                                    vec![link_path],
-                                    None,
-                                ));
+                                let link_type = LinkManager::detect_link_type(&link_path);
+                                self.manifest.add_resource(
+                                    LinkedResource::active(
+                                        SharedResourceType::Beatmaps,
+                                        beatmap_path.clone(),
+                                        vec![link_path],
+                                        None,
+                                    )
+                                    .with_link_type(link_type),
+                                );
                                 result.new_links += 1;
                                 continue;
                             }
                         }
                         // Link exists but points to wrong target - remove and recreate
-                        if let Err(e) = LinkManager::remove_link(&link_path) {
+                        if let Err(e) = self.effects.remove_link(&link_path) {
                             result.add_error(format!(
                                 "Failed to remove stale link {}: {}",
                                 link_path.display(),
@@ -1366,14 +2116,20 @@ impl UnifiedStorageEngine {
                 }
 
                 // Create the link (stable link -> lazer source)
-                match self.link_manager.create_link(&link_path, beatmap_path) {
-                    Ok(_) => {
-                        self.manifest.add_resource(LinkedResource::active(
-                            SharedResourceType::Beatmaps,
-                            beatmap_path.clone(),
-                            vec![link_path],
-                            None,
-                        ));
+                match self
+                    .effects
+                    .create_link(&self.link_manager, &link_path, beatmap_path)
+                {
+                    Ok(link_info) => {
+                        self.manifest.add_resource(
+                            LinkedResource::active(
+                                SharedResourceType::Beatmaps,
+                                beatmap_path.clone(),
+                                vec![link_path],
+                                None,
+                            )
+                            .with_link_type(Some(link_info.link_type)),
+                        );
                         result.new_links += 1;
                     }
                     Err(e) => {
@@ -1421,7 +2177,10 @@ impl UnifiedStorageEngine {
                 if !LinkManager::is_link(&link_path) {
                     // Link is broken or missing - try to recreate
                     if source_path.exists() {
-                        match self.link_manager.create_link(&link_path, &source_path) {
+                        match self
+                            .effects
+                            .create_link(&self.link_manager, &link_path, &source_path)
+                        {
                             Ok(_) => {
                                 result.updated += 1;
                                 tracing::debug!("Recreated link: {}", link_path.display());
@@ -1546,7 +2305,7 @@ impl UnifiedStorageEngine {
             if !stable_link_ok {
                 // Remove existing path if it's a wrong link
                 if stable_resource.exists() && LinkManager::is_link(&stable_resource) {
-                    if let Err(e) = LinkManager::remove_link(&stable_resource) {
+                    if let Err(e) = self.effects.remove_link(&stable_resource) {
                         result.add_error(format!(
                             "Failed to remove broken stable link for {}: {}",
                             folder_name, e
@@ -1557,10 +2316,11 @@ impl UnifiedStorageEngine {
 
                 // Create link if path doesn't exist
                 if !stable_resource.exists() {
-                    match self
-                        .link_manager
-                        .link_directory(&shared_resource, &stable_resource)
-                    {
+                    match self.effects.link_directory(
+                        &self.link_manager,
+                        &shared_resource,
+                        &stable_resource,
+                    ) {
                         Ok(link_info) => {
                             tracing::info!(
                                 "Repaired stable {} link: {} -> {}",
@@ -1585,7 +2345,7 @@ impl UnifiedStorageEngine {
             if !lazer_link_ok {
                 // Remove existing path if it's a wrong link
                 if lazer_resource.exists() && LinkManager::is_link(&lazer_resource) {
-                    if let Err(e) = LinkManager::remove_link(&lazer_resource) {
+                    if let Err(e) = self.effects.remove_link(&lazer_resource) {
                         result.add_error(format!(
                             "Failed to remove broken lazer link for {}: {}",
                             folder_name, e
@@ -1596,10 +2356,11 @@ impl UnifiedStorageEngine {
 
                 // Create link if path doesn't exist
                 if !lazer_resource.exists() {
-                    match self
-                        .link_manager
-                        .link_directory(&shared_resource, &lazer_resource)
-                    {
+                    match self.effects.link_directory(
+                        &self.link_manager,
+                        &shared_resource,
+                        &lazer_resource,
+                    ) {
                         Ok(link_info) => {
                             tracing::info!(
                                 "Repaired lazer {} link: {} -> {}",
@@ -1688,6 +2449,114 @@ mod tests {
         assert_eq!(result.warnings.len(), 1);
     }
 
+    #[test]
+    fn test_preflight_reports_conflicts_without_linking() {
+        let temp = tempfile::tempdir().unwrap();
+        let stable = temp.path().join("stable");
+        let lazer = temp.path().join("lazer");
+        fs::create_dir_all(stable.join("Skins")).unwrap();
+        fs::create_dir_all(lazer.join("Skins")).unwrap();
+        fs::write(stable.join("Skins").join("a.ini"), b"stable").unwrap();
+        fs::write(lazer.join("Skins").join("b.ini"), b"lazer!").unwrap();
+
+        let mut config = UnifiedStorageConfig::stable_master();
+        config.unshare_all_resources();
+        config.share_resource(SharedResourceType::Skins);
+        let engine = UnifiedStorageEngine::new(config, stable.clone(), lazer.clone()).unwrap();
+
+        let result = engine.preflight().unwrap();
+        assert_eq!(result.resources.len(), 1);
+        assert_eq!(result.resources[0].stable_bytes, 6);
+        assert!(result.has_conflicts());
+        assert_eq!(result.links_created, 0);
+        assert!(!LinkManager::is_link(&lazer.join("Skins")));
+        assert!(!lazer.join("Skins_backup").exists());
+    }
+
+    #[test]
+    fn test_plan_setup_does_not_touch_filesystem() {
+        let temp = tempfile::tempdir().unwrap();
+        let stable = temp.path().join("stable");
+        let lazer = temp.path().join("lazer");
+        fs::create_dir_all(stable.join("Skins")).unwrap();
+        fs::create_dir_all(lazer.join("Skins")).unwrap();
+
+        let mut config = UnifiedStorageConfig::stable_master();
+        config.unshare_all_resources();
+        config.share_resource(SharedResourceType::Skins);
+        let mut engine = UnifiedStorageEngine::new(config, stable.clone(), lazer.clone()).unwrap();
+
+        let plan = engine.plan(UnifiedOperation::Setup).unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                PlannedAction::Backup {
+                    from: lazer.join("Skins"),
+                    to: lazer.join("Skins_backup"),
+                },
+                PlannedAction::CreateLink {
+                    link: lazer.join("Skins"),
+                    target: stable.join("Skins"),
+                },
+            ]
+        );
+        assert!(!lazer.join("Skins_backup").exists());
+        assert!(!LinkManager::is_link(&lazer.join("Skins")));
+        assert!(engine.manifest().iter().next().is_none());
+    }
+
+    #[test]
+    fn test_plan_true_unified_links_backed_up_folders() {
+        let temp = tempfile::tempdir().unwrap();
+        let stable = temp.path().join("stable");
+        let lazer = temp.path().join("lazer");
+        let shared = temp.path().join("shared");
+        fs::create_dir_all(stable.join("Skins")).unwrap();
+        fs::create_dir_all(lazer.join("Skins")).unwrap();
+
+        let mut config = UnifiedStorageConfig::true_unified(shared.clone());
+        config.unshare_all_resources();
+        config.share_resource(SharedResourceType::Skins);
+        let mut engine = UnifiedStorageEngine::new(config, stable.clone(), lazer.clone()).unwrap();
+
+        let plan = engine.plan(UnifiedOperation::Setup).unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                PlannedAction::CreateDir {
+                    path: shared.join("Skins"),
+                },
+                PlannedAction::MigrateContents {
+                    from: stable.join("Skins"),
+                    to: shared.join("Skins"),
+                },
+                PlannedAction::MigrateContents {
+                    from: lazer.join("Skins"),
+                    to: shared.join("Skins"),
+                },
+                PlannedAction::Backup {
+                    from: stable.join("Skins"),
+                    to: stable.join("Skins_backup"),
+                },
+                PlannedAction::Backup {
+                    from: lazer.join("Skins"),
+                    to: lazer.join("Skins_backup"),
+                },
+                PlannedAction::CreateLink {
+                    link: stable.join("Skins"),
+                    target: shared.join("Skins"),
+                },
+                PlannedAction::CreateLink {
+                    link: lazer.join("Skins"),
+                    target: shared.join("Skins"),
+                },
+            ]
+        );
+        assert!(!shared.exists());
+        assert!(!LinkManager::is_link(&stable.join("Skins")));
+        assert!(!LinkManager::is_link(&lazer.join("Skins")));
+    }
+
     #[test]
     fn test_sync_result() {
         let mut result = SyncResult::new();
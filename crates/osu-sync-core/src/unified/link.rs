@@ -16,11 +16,14 @@
 //! On Unix-like systems, symbolic links are used for both files and directories
 //! as they don't require special privileges.
 
+use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
 
 use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 /// Information about a created link.
@@ -64,7 +67,8 @@ impl LinkInfo {
 }
 
 /// The type of filesystem link.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LinkType {
     /// Windows NTFS junction (directories only, no admin required).
     Junction,
@@ -146,6 +150,57 @@ impl LinkCapability {
     }
 }
 
+/// Link types available to the current process.
+///
+/// Unlike [`LinkCapability`], this reports symlink and junction support
+/// independently, so callers can tell which mechanism a link will use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkCapabilities {
+    /// Whether symbolic links can be created.
+    pub symlinks: bool,
+    /// Whether NTFS junctions can be created (Windows only).
+    pub junctions: bool,
+}
+
+impl LinkCapabilities {
+    /// Returns `true` if at least one link type can be created.
+    pub fn any(&self) -> bool {
+        self.symlinks || self.junctions
+    }
+
+    /// Returns the link type used for directories, if any is available.
+    pub fn directory_link_type(&self, prefer_junctions: bool) -> Option<LinkType> {
+        match (self.symlinks, self.junctions) {
+            (_, true) if prefer_junctions || !self.symlinks => Some(LinkType::Junction),
+            (true, _) => Some(LinkType::Symlink),
+            _ => None,
+        }
+    }
+
+    /// Collapses these capabilities into a [`LinkCapability`] level.
+    pub fn capability(&self) -> LinkCapability {
+        if self.symlinks {
+            LinkCapability::Full
+        } else if self.junctions {
+            LinkCapability::JunctionsOnly
+        } else {
+            LinkCapability::None
+        }
+    }
+}
+
+impl std::fmt::Display for LinkCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let yes_no = |b: bool| if b { "available" } else { "unavailable" };
+        write!(
+            f,
+            "symlinks {}, junctions {}",
+            yes_no(self.symlinks),
+            yes_no(self.junctions)
+        )
+    }
+}
+
 /// Manager for creating and managing filesystem links.
 ///
 /// On Windows, this manager implements a cascading strategy:
@@ -199,19 +254,78 @@ impl LinkManager {
     /// On Windows, this checks whether the process has privileges to create
     /// symbolic links. On Unix, this always returns `Full`.
     pub fn check_capabilities() -> LinkCapability {
+        Self::link_capabilities().capability()
+    }
+
+    /// Reports which link types the current process can create.
+    ///
+    /// On Windows, junctions are always available while symlinks need
+    /// administrator privileges or Developer Mode. On Unix, only symlinks
+    /// exist. Probed in the temp directory, see [`Self::link_capabilities_at`].
+    pub fn link_capabilities() -> LinkCapabilities {
+        Self::link_capabilities_at(&std::env::temp_dir())
+    }
+
+    /// Reports which link types can be created at `path`.
+    ///
+    /// On Windows this creates a test symlink in the nearest existing
+    /// directory of `path`. The result is cached per volume root, so each
+    /// volume is probed once per process.
+    pub fn link_capabilities_at(path: &Path) -> LinkCapabilities {
+        static CACHE: OnceLock<Mutex<HashMap<PathBuf, LinkCapabilities>>> = OnceLock::new();
+        let mut cache = CACHE
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *cache
+            .entry(volume_root(path))
+            .or_insert_with(|| Self::probe_capabilities(path))
+    }
+
+    /// Probes the link types that can be created at `path`, uncached.
+    #[cfg_attr(not(windows), allow(unused_variables))]
+    fn probe_capabilities(path: &Path) -> LinkCapabilities {
         #[cfg(windows)]
         {
-            windows_impl::check_capabilities()
+            let dir = path
+                .ancestors()
+                .find(|p| p.is_dir())
+                .map(Path::to_path_buf)
+                .unwrap_or_else(std::env::temp_dir);
+            LinkCapabilities {
+                symlinks: windows_impl::can_create_symlinks(&dir),
+                junctions: true,
+            }
         }
 
         #[cfg(unix)]
         {
-            LinkCapability::Full
+            LinkCapabilities {
+                symlinks: true,
+                junctions: false,
+            }
         }
 
         #[cfg(not(any(windows, unix)))]
         {
-            LinkCapability::None
+            LinkCapabilities {
+                symlinks: false,
+                junctions: false,
+            }
+        }
+    }
+
+    /// Detects the type of link at `path`, if it is a symlink or junction.
+    ///
+    /// Hard links and copies are indistinguishable from regular files, so
+    /// this returns `None` for them.
+    pub fn detect_link_type(path: &Path) -> Option<LinkType> {
+        if Self::is_junction(path) {
+            Some(LinkType::Junction)
+        } else if Self::is_link(path) {
+            Some(LinkType::Symlink)
+        } else {
+            None
         }
     }
 
@@ -223,7 +337,7 @@ impl LinkManager {
     pub fn requires_elevation() -> bool {
         #[cfg(windows)]
         {
-            !windows_impl::can_create_symlinks()
+            !Self::link_capabilities().symlinks
         }
 
         #[cfg(unix)]
@@ -497,10 +611,27 @@ impl LinkManager {
     ///
     /// Returns a `LinkCheckInfo` containing information about the link status.
     pub fn check_link(&self, path: &Path) -> Result<LinkCheckInfo> {
-        // Check if the path exists as a link
-        let is_link = Self::is_link(path);
+        self.check_link_as(path, None)
+    }
 
-        if !is_link {
+    /// Checks a link using the mechanism it was created with.
+    ///
+    /// Junctions and symlinks are only valid if the path is still a link of
+    /// that type with a reachable target. Hard links and copies are not
+    /// detectable as links, so they are valid as long as the path exists.
+    /// With `expected` set to `None`, this behaves like [`check_link`](Self::check_link).
+    pub fn check_link_as(&self, path: &Path, expected: Option<LinkType>) -> Result<LinkCheckInfo> {
+        if let Some(expected @ (LinkType::Hardlink | LinkType::Copy)) = expected {
+            let exists = path.exists() && !Self::is_link(path);
+            return Ok(LinkCheckInfo {
+                is_valid: exists,
+                link_path: path.to_path_buf(),
+                target_path: None,
+                link_type: exists.then_some(expected),
+            });
+        }
+
+        let Some(link_type) = Self::detect_link_type(path) else {
             // Path is not a link - it might not exist or be a regular file/dir
             return Ok(LinkCheckInfo {
                 is_valid: false,
@@ -508,24 +639,18 @@ impl LinkManager {
                 target_path: None,
                 link_type: None,
             });
-        }
+        };
 
         // It's a link - try to read the target
         let target = Self::read_link(path).ok();
         let target_exists = target.as_ref().map(|t| t.exists()).unwrap_or(false);
-
-        // Determine link type
-        let link_type = if Self::is_junction(path) {
-            Some(LinkType::Junction)
-        } else {
-            Some(LinkType::Symlink)
-        };
+        let type_matches = !matches!(expected, Some(t) if t != link_type);
 
         Ok(LinkCheckInfo {
-            is_valid: target_exists,
+            is_valid: target_exists && type_matches,
             link_path: path.to_path_buf(),
             target_path: target,
-            link_type,
+            link_type: Some(link_type),
         })
     }
 
@@ -565,6 +690,8 @@ impl LinkManager {
 
     #[cfg(windows)]
     fn link_directory_windows(&self, source: &Path, link: &Path) -> Result<LinkInfo> {
+        let mut last_error = None;
+
         // Strategy 1: Try junction first (no admin needed)
         if self.prefer_junctions {
             debug!(
@@ -587,6 +714,7 @@ impl LinkManager {
                 }
                 Err(e) => {
                     warn!("Junction creation failed, trying symlink: {}", e);
+                    last_error = Some(e);
                 }
             }
         }
@@ -611,8 +739,7 @@ impl LinkManager {
                 ));
             }
             Err(e) => {
-                if e.raw_os_error() == Some(1314) {
-                    // ERROR_PRIVILEGE_NOT_HELD
+                if e.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD) {
                     warn!(
                         "Symlink creation failed due to missing privileges. \
                          Consider enabling Developer Mode or running as administrator."
@@ -620,6 +747,7 @@ impl LinkManager {
                 } else {
                     warn!("Symlink creation failed: {}", e);
                 }
+                last_error = Some(e);
             }
         }
 
@@ -638,15 +766,13 @@ impl LinkManager {
             ));
         }
 
-        Err(Error::Other(format!(
-            "Failed to create directory link and fallback is disabled: {} -> {}",
-            source.display(),
-            link.display()
-        )))
+        Err(link_creation_error(source, link, last_error.as_ref()))
     }
 
     #[cfg(windows)]
     fn link_file_windows(&self, source: &Path, link: &Path) -> Result<LinkInfo> {
+        let mut last_error = None;
+
         // Strategy 1: Try symbolic link first
         debug!(
             "Creating file symlink: {} -> {}",
@@ -667,12 +793,12 @@ impl LinkManager {
                 ));
             }
             Err(e) => {
-                if e.raw_os_error() == Some(1314) {
-                    // ERROR_PRIVILEGE_NOT_HELD
+                if e.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD) {
                     warn!("Symlink creation failed due to missing privileges.");
                 } else {
                     warn!("Symlink creation failed: {}", e);
                 }
+                last_error = Some(e);
             }
         }
 
@@ -697,6 +823,7 @@ impl LinkManager {
             }
             Err(e) => {
                 warn!("Hard link creation failed: {}", e);
+                last_error.get_or_insert(e);
             }
         }
 
@@ -715,11 +842,7 @@ impl LinkManager {
             ));
         }
 
-        Err(Error::Other(format!(
-            "Failed to create file link and fallback is disabled: {} -> {}",
-            source.display(),
-            link.display()
-        )))
+        Err(link_creation_error(source, link, last_error.as_ref()))
     }
 
     #[cfg(unix)]
@@ -760,7 +883,7 @@ impl LinkManager {
                         LinkType::Copy,
                     ))
                 } else {
-                    Err(Error::Io(e))
+                    Err(link_creation_error(source, link, Some(&e)))
                 }
             }
         }
@@ -804,7 +927,7 @@ impl LinkManager {
                         LinkType::Copy,
                     ))
                 } else {
-                    Err(Error::Io(e))
+                    Err(link_creation_error(source, link, Some(&e)))
                 }
             }
         }
@@ -825,9 +948,7 @@ impl LinkManager {
                 LinkType::Copy,
             ))
         } else {
-            Err(Error::Other(
-                "Links not supported on this platform".to_string(),
-            ))
+            Err(link_creation_error(source, link, None))
         }
     }
 
@@ -846,13 +967,52 @@ impl LinkManager {
                 LinkType::Copy,
             ))
         } else {
-            Err(Error::Other(
-                "Links not supported on this platform".to_string(),
-            ))
+            Err(link_creation_error(source, link, None))
         }
     }
 }
 
+/// The prefix and root of `path`, e.g. `C:\` or `/`; empty if relative.
+fn volume_root(path: &Path) -> PathBuf {
+    path.components()
+        .take_while(|c| matches!(c, Component::Prefix(_) | Component::RootDir))
+        .collect()
+}
+
+/// Windows error code for a missing symlink privilege.
+const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+
+/// Builds a descriptive error for a link that could not be created.
+///
+/// Raw OS errors such as `ERROR_PRIVILEGE_NOT_HELD` are translated into a
+/// message that says which link types this process can create.
+fn link_creation_error(source: &Path, link: &Path, err: Option<&io::Error>) -> Error {
+    let caps = LinkManager::link_capabilities_at(link);
+    let privilege_error = err.is_some_and(|e| {
+        (cfg!(windows) && e.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD))
+            || e.kind() == io::ErrorKind::PermissionDenied
+    });
+
+    let message = if !caps.any() {
+        "neither symbolic links nor junctions can be created on this system".to_string()
+    } else if privilege_error && !caps.symlinks {
+        format!(
+            "symbolic links need administrator privileges or Developer Mode ({})",
+            caps
+        )
+    } else if let Some(e) = err {
+        format!("{} ({})", e, caps)
+    } else {
+        format!("no usable link type ({})", caps)
+    };
+
+    Error::LinkCreation {
+        source_path: source.to_path_buf(),
+        link_path: link.to_path_buf(),
+        message,
+    }
+}
+
 /// Recursively copies a directory and its contents.
 ///
 /// This is a utility function used by the link manager for fallback copying
@@ -922,24 +1082,11 @@ mod windows_impl {
     // Reparse buffer size
     const MAXIMUM_REPARSE_DATA_BUFFER_SIZE: usize = 16384;
 
-    /// Check link creation capabilities on Windows.
-    pub fn check_capabilities() -> LinkCapability {
-        if can_create_symlinks() {
-            LinkCapability::Full
-        } else {
-            // Junctions don't require special privileges
-            LinkCapability::JunctionsOnly
-        }
-    }
-
-    /// Check if the current process can create symbolic links.
-    pub fn can_create_symlinks() -> bool {
-        // Try to create a symlink in a temp directory to check permissions
-        use std::env;
-
-        let temp = env::temp_dir();
-        let test_source = temp.join(".osu_sync_link_test_source");
-        let test_link = temp.join(".osu_sync_link_test_link");
+    /// Check if the current process can create symbolic links in `dir`.
+    pub fn can_create_symlinks(dir: &Path) -> bool {
+        // Try to create a symlink to check permissions and filesystem support
+        let test_source = dir.join(".osu_sync_link_test_source");
+        let test_link = dir.join(".osu_sync_link_test_link");
 
         // Clean up any previous test files
         let _ = fs::remove_file(&test_source);
@@ -1370,6 +1517,15 @@ mod tests {
             cap,
             LinkCapability::Full | LinkCapability::JunctionsOnly | LinkCapability::None
         ));
+        assert_eq!(cap, LinkManager::link_capabilities().capability());
+    }
+
+    #[test]
+    fn test_volume_root() {
+        assert_eq!(volume_root(Path::new("/home/user/osu")), Path::new("/"));
+        assert_eq!(volume_root(Path::new("relative/dir")), Path::new(""));
+        #[cfg(windows)]
+        assert_eq!(volume_root(Path::new(r"D:\Games\osu!")), Path::new(r"D:\"));
     }
 
     #[test]
@@ -1457,6 +1613,41 @@ mod tests {
             assert!(link.join("file.txt").exists());
         }
 
+        #[test]
+        fn test_check_link_as_uses_recorded_type() {
+            let temp = TempDir::new().unwrap();
+            let source = temp.path().join("source_dir");
+            let link = temp.path().join("link_dir");
+            fs::create_dir(&source).unwrap();
+
+            let manager = LinkManager::new(false);
+            let info = manager.link_directory(&source, &link).unwrap();
+            assert_eq!(LinkManager::detect_link_type(&link), Some(info.link_type));
+
+            assert!(
+                manager
+                    .check_link_as(&link, Some(LinkType::Symlink))
+                    .unwrap()
+                    .is_valid
+            );
+            let mismatch = manager
+                .check_link_as(&link, Some(LinkType::Junction))
+                .unwrap();
+            assert!(!mismatch.is_valid);
+            assert_eq!(mismatch.link_type, Some(LinkType::Symlink));
+
+            // Copies are not links but are still valid when recorded as such
+            let copy = temp.path().join("copy_dir");
+            copy_dir_recursive(&source, &copy).unwrap();
+            assert!(!manager.check_link(&copy).unwrap().is_valid);
+            assert!(
+                manager
+                    .check_link_as(&copy, Some(LinkType::Copy))
+                    .unwrap()
+                    .is_valid
+            );
+        }
+
         #[test]
         fn test_read_link_unix() {
             let temp = TempDir::new().unwrap();
@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
 use super::config::{SharedResourceType, UnifiedStorageMode};
+use super::link::LinkType;
+use crate::config::paths;
 use crate::error::{Error, Result};
 
 /// Current manifest format version.
@@ -74,6 +76,9 @@ pub struct LinkedResource {
     pub modified_at: DateTime<Utc>,
     /// Current status of the link.
     pub status: LinkStatus,
+    /// The kind of link that was actually created, if known.
+    #[serde(default)]
+    pub link_type: Option<LinkType>,
 }
 
 impl LinkedResource {
@@ -90,6 +95,7 @@ impl LinkedResource {
             content_hash: None,
             modified_at: Utc::now(),
             status: LinkStatus::Pending,
+            link_type: None,
         }
     }
 
@@ -107,9 +113,16 @@ impl LinkedResource {
             content_hash,
             modified_at: Utc::now(),
             status: LinkStatus::Active,
+            link_type: None,
         }
     }
 
+    /// Records the kind of link used for this resource.
+    pub fn with_link_type(mut self, link_type: Option<LinkType>) -> Self {
+        self.link_type = link_type;
+        self
+    }
+
     /// Returns `true` if this resource matches the given source path.
     pub fn matches_source(&self, path: &Path) -> bool {
         self.source_path == path
@@ -222,20 +235,13 @@ impl UnifiedManifest {
     /// - Windows: `%APPDATA%/osu-sync/unified-manifest.json`
     /// - Linux/macOS: `~/.config/osu-sync/unified-manifest.json`
     pub fn manifest_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir().ok_or_else(|| {
-            Error::ManifestError("Could not determine config directory".to_string())
-        })?;
-
-        Ok(config_dir.join("osu-sync").join(MANIFEST_FILENAME))
+        Ok(Self::manifest_dir()?.join(MANIFEST_FILENAME))
     }
 
     /// Returns the directory where the manifest is stored.
     pub fn manifest_dir() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir().ok_or_else(|| {
-            Error::ManifestError("Could not determine config directory".to_string())
-        })?;
-
-        Ok(config_dir.join("osu-sync"))
+        paths::config_dir()
+            .ok_or_else(|| Error::ManifestError("Could not determine config directory".to_string()))
     }
 
     /// Loads the manifest from the default location.
@@ -1352,38 +1352,8 @@ impl UnifiedMigration {
 
     /// Gets the available disk space at a path.
     fn get_available_disk_space(path: &Path) -> Result<u64> {
-        #[cfg(windows)]
-        {
-            // Get the root of the path
-            let _root = path.ancestors().last().unwrap_or(path);
-
-            // For simplicity, return a large value if we can't determine
-            // In production, use winapi GetDiskFreeSpaceExW
-            Ok(u64::MAX)
-        }
-
-        #[cfg(unix)]
-        {
-            use std::process::Command;
-
-            let output = Command::new("df")
-                .args(["-B1", &path.to_string_lossy()])
-                .output()?;
-
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let lines: Vec<&str> = output_str.lines().collect();
-
-            if lines.len() >= 2 {
-                let parts: Vec<&str> = lines[1].split_whitespace().collect();
-                if parts.len() >= 4 {
-                    if let Ok(available) = parts[3].parse::<u64>() {
-                        return Ok(available);
-                    }
-                }
-            }
-
-            Ok(u64::MAX)
-        }
+        // Treat an undeterminable volume as unlimited rather than blocking migration
+        Ok(crate::utils::available_space(path).unwrap_or(u64::MAX))
     }
 
     /// Checks if we can write to a path.
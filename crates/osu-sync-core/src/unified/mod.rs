@@ -31,6 +31,7 @@
 //!         FileChangeEvent::Modified { path } => println!("Modified: {:?}", path),
 //!         FileChangeEvent::Deleted { path } => println!("Deleted: {:?}", path),
 //!         FileChangeEvent::Renamed { from, to } => println!("Renamed: {:?} -> {:?}", from, to),
+//!         FileChangeEvent::FolderChanged { path } => println!("Folder changed: {:?}", path),
 //!     }
 //! }
 //! ```
@@ -50,16 +51,20 @@ pub use migration::{
     UnifiedMigration,
 };
 
-pub use watcher::{FileChangeEvent, UnifiedWatcher, WatcherEventHandler};
+pub use watcher::{FileChangeEvent, FolderCoalescer, UnifiedWatcher, WatcherEventHandler};
 
 pub use game_detect::{
     find_running_processes, is_process_running, GameEvent, GameLaunchDetector, OsuGame, ProcessInfo,
 };
 
-pub use engine::{RepairResult, SetupResult, SyncResult, UnifiedStorageEngine, VerificationResult};
+pub use engine::{
+    PlannedAction, RepairResult, ResourceUsage, SetupResult, SyncResult, UnifiedOperation,
+    UnifiedStorageEngine, VerificationResult,
+};
 
 pub use manifest::{LinkStatus, LinkedResource, ManifestSummary, UnifiedManifest};
 
 pub use link::{
-    copy_dir_recursive, LinkCapability, LinkCheckInfo, LinkInfo, LinkManager, LinkType,
+    copy_dir_recursive, LinkCapabilities, LinkCapability, LinkCheckInfo, LinkInfo, LinkManager,
+    LinkType,
 };
@@ -35,6 +35,9 @@
 //!         FileChangeEvent::Renamed { from, to } => {
 //!             println!("Renamed: {:?} -> {:?}", from, to);
 //!         }
+//!         FileChangeEvent::FolderChanged { path } => {
+//!             println!("Folder changed: {:?}", path);
+//!         }
 //!     }
 //! }
 //! ```
@@ -42,7 +45,8 @@
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use crate::error::{Error, Result};
@@ -107,6 +111,14 @@ pub enum FileChangeEvent {
         /// New path after renaming.
         to: PathBuf,
     },
+    /// Something inside a top-level folder of a watched directory changed.
+    ///
+    /// Only emitted by [`UnifiedWatcher::new_debounced`], which coalesces
+    /// all events under one folder into a single notification.
+    FolderChanged {
+        /// Path to the top-level folder.
+        path: PathBuf,
+    },
 }
 
 impl FileChangeEvent {
@@ -117,6 +129,7 @@ impl FileChangeEvent {
             Self::Modified { path } => path,
             Self::Deleted { path } => path,
             Self::Renamed { to, .. } => to,
+            Self::FolderChanged { path } => path,
         }
     }
 
@@ -124,6 +137,7 @@ impl FileChangeEvent {
     pub fn is_directory_event(&self) -> bool {
         match self {
             Self::Created { is_dir, .. } => *is_dir,
+            Self::FolderChanged { .. } => true,
             _ => false,
         }
     }
@@ -141,6 +155,8 @@ pub struct UnifiedWatcher {
     event_tx: Sender<FileChangeEvent>,
     /// List of currently watched paths.
     watched_paths: Vec<PathBuf>,
+    /// Watched paths shared with the folder-coalescing thread, if any.
+    shared_roots: Option<Arc<Mutex<Vec<PathBuf>>>>,
     /// Event handler for processing and filtering events.
     handler: WatcherEventHandler,
 }
@@ -294,12 +310,56 @@ impl UnifiedWatcher {
                 watcher,
                 event_tx,
                 watched_paths: Vec::new(),
+                shared_roots: None,
                 handler,
             },
             event_rx,
         ))
     }
 
+    /// Creates a watcher that coalesces events per top-level folder.
+    ///
+    /// All events under one folder of a watched directory (for example a
+    /// beatmap set inside Songs) are merged into a single
+    /// [`FileChangeEvent::FolderChanged`], emitted once the folder has been
+    /// quiet for `window`. A deleted entry followed by a created entry in the
+    /// same parent folder collapses into [`FileChangeEvent::Renamed`], which
+    /// is held for `window` as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying file system watcher cannot be created.
+    pub fn new_debounced(window: Duration) -> Result<(Self, Receiver<FileChangeEvent>)> {
+        let (mut watcher, raw_rx) = Self::new()?;
+        let roots = Arc::new(Mutex::new(Vec::new()));
+        watcher.shared_roots = Some(Arc::clone(&roots));
+
+        let (tx, rx) = channel();
+        std::thread::spawn(move || {
+            let mut coalescer = FolderCoalescer::new(window);
+            loop {
+                match raw_rx.recv_timeout(Duration::from_millis(50)) {
+                    Ok(event) => {
+                        if let Ok(roots) = roots.lock() {
+                            coalescer.set_roots(roots.clone());
+                        }
+                        coalescer.push(event, Instant::now());
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                for event in coalescer.drain_ready(Instant::now()) {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok((watcher, rx))
+    }
+
     /// Starts watching a directory for changes.
     ///
     /// The directory will be watched recursively, meaning all subdirectories
@@ -335,6 +395,11 @@ impl UnifiedWatcher {
             .map_err(|e| Error::Other(format!("Failed to watch path: {}", e)))?;
 
         self.watched_paths.push(path.to_path_buf());
+        if let Some(roots) = &self.shared_roots {
+            if let Ok(mut roots) = roots.lock() {
+                roots.push(path.to_path_buf());
+            }
+        }
         Ok(())
     }
 
@@ -353,6 +418,11 @@ impl UnifiedWatcher {
             .map_err(|e| Error::Other(format!("Failed to unwatch path: {}", e)))?;
 
         self.watched_paths.retain(|p| p != path);
+        if let Some(roots) = &self.shared_roots {
+            if let Ok(mut roots) = roots.lock() {
+                roots.retain(|p| p != path);
+            }
+        }
         Ok(())
     }
 
@@ -379,6 +449,153 @@ impl UnifiedWatcher {
     }
 }
 
+/// Coalesces file change events per top-level folder of the watched roots.
+///
+/// Used by [`UnifiedWatcher::new_debounced`]; events are pushed as they
+/// arrive and folders are drained once they have been quiet for the window.
+#[derive(Debug)]
+pub struct FolderCoalescer {
+    /// Quiet period required before a folder is reported.
+    window: Duration,
+    /// Watched root directories.
+    roots: Vec<PathBuf>,
+    /// Top-level folder -> (last event time, whether the folder itself was deleted).
+    folders: HashMap<PathBuf, (Instant, bool)>,
+    /// Deletions waiting for a matching creation to form a rename.
+    deletions: Vec<(PathBuf, Instant)>,
+    /// Renames waiting out the window, with the time they were detected.
+    renames: Vec<(FileChangeEvent, Instant)>,
+}
+
+impl FolderCoalescer {
+    /// Creates a coalescer with the given quiet window.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            roots: Vec::new(),
+            folders: HashMap::new(),
+            deletions: Vec::new(),
+            renames: Vec::new(),
+        }
+    }
+
+    /// Sets the watched root directories used to find top-level folders.
+    pub fn set_roots(&mut self, roots: Vec<PathBuf>) {
+        self.roots = roots;
+    }
+
+    /// Records an event observed at `now`.
+    pub fn push(&mut self, event: FileChangeEvent, now: Instant) {
+        match event {
+            FileChangeEvent::Deleted { path } => {
+                self.deletions.push((path, now));
+            }
+            FileChangeEvent::Created { path, .. } => match self.rename_source(&path) {
+                Some(index) => {
+                    let (from, _) = self.deletions.remove(index);
+                    self.renames
+                        .push((FileChangeEvent::Renamed { from, to: path }, now));
+                }
+                None => self.touch(&path, now, false),
+            },
+            FileChangeEvent::Renamed { from, to } => {
+                self.renames
+                    .push((FileChangeEvent::Renamed { from, to }, now));
+            }
+            FileChangeEvent::Modified { path } | FileChangeEvent::FolderChanged { path } => {
+                self.touch(&path, now, false);
+            }
+        }
+    }
+
+    /// Index of the deletion a creation at `path` renames, if any
+    ///
+    /// Only a deletion in the same parent folder pairs, and only when it is
+    /// the sole deletion waiting there; anything else stays separate events.
+    fn rename_source(&self, path: &Path) -> Option<usize> {
+        let parent = path.parent()?;
+        let mut candidates = self
+            .deletions
+            .iter()
+            .enumerate()
+            .filter(|(_, (deleted, _))| deleted != path && deleted.parent() == Some(parent))
+            .map(|(index, _)| index);
+        let index = candidates.next()?;
+        candidates.next().is_none().then_some(index)
+    }
+
+    /// Returns renames and folders that have been quiet for the whole window.
+    pub fn drain_ready(&mut self, now: Instant) -> Vec<FileChangeEvent> {
+        let window = self.window;
+
+        // Deletions that found no partner within the window count as folder changes
+        let (expired, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.deletions)
+            .into_iter()
+            .partition(|(_, at)| now.duration_since(*at) >= window);
+        self.deletions = waiting;
+        for (path, at) in expired {
+            self.touch(&path, at, true);
+        }
+
+        let mut settled: Vec<(PathBuf, bool)> = Vec::new();
+        self.folders.retain(|folder, (last, deleted)| {
+            if now.duration_since(*last) >= window {
+                settled.push((folder.clone(), *deleted));
+                false
+            } else {
+                true
+            }
+        });
+        settled.sort();
+
+        let (ready_renames, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.renames)
+            .into_iter()
+            .partition(|(_, at)| now.duration_since(*at) >= window);
+        self.renames = waiting;
+
+        let mut ready: Vec<FileChangeEvent> = ready_renames
+            .into_iter()
+            .map(|(rename, _)| rename)
+            .collect();
+        for (path, deleted) in settled {
+            if deleted && !path.exists() {
+                ready.push(FileChangeEvent::Deleted { path });
+            } else {
+                ready.push(FileChangeEvent::FolderChanged { path });
+            }
+        }
+        ready
+    }
+
+    /// Returns true if nothing is waiting to be reported.
+    pub fn is_idle(&self) -> bool {
+        self.folders.is_empty() && self.deletions.is_empty() && self.renames.is_empty()
+    }
+
+    fn touch(&mut self, path: &Path, at: Instant, deleted: bool) {
+        let folder = top_level_folder(&self.roots, path);
+        let is_folder_itself = folder == path;
+        let entry = self.folders.entry(folder).or_insert((at, false));
+        entry.0 = entry.0.max(at);
+        entry.1 = deleted && is_folder_itself;
+    }
+}
+
+/// Returns the top-level folder of `path` below the deepest matching root.
+///
+/// Paths outside every root are returned unchanged.
+fn top_level_folder(roots: &[PathBuf], path: &Path) -> PathBuf {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .and_then(|root| {
+            let first = path.strip_prefix(root).ok()?.components().next()?;
+            Some(root.join(first))
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
 /// Handler for processing and filtering file system events.
 ///
 /// This struct provides debouncing logic and filtering capabilities
@@ -760,4 +977,95 @@ mod tests {
         handler.clear_ignore_patterns();
         assert_eq!(handler.ignore_pattern_count(), 0);
     }
+
+    #[test]
+    fn test_folder_coalescer_merges_events_per_folder() {
+        let root = PathBuf::from("/osu/Songs");
+        let mut coalescer = FolderCoalescer::new(Duration::from_millis(500));
+        coalescer.set_roots(vec![root.clone()]);
+
+        let start = Instant::now();
+        for name in ["map.osu", "audio.mp3", "bg.jpg"] {
+            coalescer.push(
+                FileChangeEvent::Created {
+                    path: root.join("123 Set").join(name),
+                    is_dir: false,
+                },
+                start,
+            );
+        }
+
+        // Still inside the window: nothing is emitted yet
+        assert!(coalescer.drain_ready(start).is_empty());
+
+        let ready = coalescer.drain_ready(start + Duration::from_millis(600));
+        assert_eq!(
+            ready,
+            vec![FileChangeEvent::FolderChanged {
+                path: root.join("123 Set")
+            }]
+        );
+        assert!(coalescer.is_idle());
+    }
+
+    #[test]
+    fn test_folder_coalescer_collapses_renames() {
+        let root = PathBuf::from("/osu/Songs");
+        let mut coalescer = FolderCoalescer::new(Duration::from_millis(500));
+        coalescer.set_roots(vec![root.clone()]);
+
+        let now = Instant::now();
+        let from = root.join("123 Set").join("old.osu");
+        let to = root.join("123 Set").join("new.osu");
+        coalescer.push(FileChangeEvent::Deleted { path: from.clone() }, now);
+        coalescer.push(
+            FileChangeEvent::Created {
+                path: to.clone(),
+                is_dir: false,
+            },
+            now,
+        );
+
+        // Renames wait out the window like folder changes
+        assert!(coalescer.drain_ready(now).is_empty());
+        assert_eq!(
+            coalescer.drain_ready(now + Duration::from_millis(600)),
+            vec![FileChangeEvent::Renamed { from, to }]
+        );
+        assert!(coalescer.is_idle());
+    }
+
+    #[test]
+    fn test_folder_coalescer_keeps_same_names_in_other_folders_apart() {
+        let root = PathBuf::from("/osu/Songs");
+        let mut coalescer = FolderCoalescer::new(Duration::from_millis(500));
+        coalescer.set_roots(vec![root.clone()]);
+
+        let now = Instant::now();
+        coalescer.push(
+            FileChangeEvent::Deleted {
+                path: root.join("1 A").join("audio.mp3"),
+            },
+            now,
+        );
+        coalescer.push(
+            FileChangeEvent::Created {
+                path: root.join("2 B").join("audio.mp3"),
+                is_dir: false,
+            },
+            now,
+        );
+
+        assert_eq!(
+            coalescer.drain_ready(now + Duration::from_millis(600)),
+            vec![
+                FileChangeEvent::FolderChanged {
+                    path: root.join("1 A")
+                },
+                FileChangeEvent::FolderChanged {
+                    path: root.join("2 B")
+                },
+            ]
+        );
+    }
 }
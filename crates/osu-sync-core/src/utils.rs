@@ -1,5 +1,11 @@
 //! Utility functions shared across modules.
 
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
 /// Sanitize a string for use as a filename by replacing invalid characters.
 ///
 /// This function replaces the following characters with underscores:
@@ -18,20 +24,528 @@
 /// assert_eq!(sanitize_filename("  spaced  "), "spaced");
 /// ```
 pub fn sanitize_filename(name: &str) -> String {
-    name.chars()
-        .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-            _ => c,
-        })
-        .collect::<String>()
-        .trim()
-        .to_string()
+    SanitizeOptions::default().sanitize(name)
+}
+
+/// Characters invalid in file names on at least one common platform
+const PORTABLE_INVALID: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Device names Windows reserves regardless of extension
+const WINDOWS_RESERVED: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Platform whose file naming rules [`SanitizeOptions`] applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SanitizeTarget {
+    /// Replace characters invalid on any common platform
+    #[default]
+    Portable,
+    /// Portable rules, plus control characters, trailing dots and spaces,
+    /// and reserved device names such as `CON`
+    Windows,
+    /// Only replace `/` and NUL, the characters no Unix filesystem allows
+    Unix,
+}
+
+/// Rules for turning arbitrary text into a single file or folder name
+///
+/// The default matches [`sanitize_filename`]: characters invalid on any
+/// common platform become `_` and there is no length limit.
+///
+/// # Examples
+///
+/// ```
+/// use osu_sync_core::utils::{SanitizeOptions, SanitizeTarget};
+///
+/// let options = SanitizeOptions::new().with_target(SanitizeTarget::Windows);
+/// assert_eq!(options.sanitize("Title..."), "Title");
+/// assert_eq!(options.sanitize("con.txt"), "_con.txt");
+///
+/// let short = SanitizeOptions::new().with_max_length(16);
+/// let name = short.sanitize("a very long file name.mp3");
+/// assert!(name.len() <= 16 && name.ends_with(".mp3"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// Character substituted for invalid ones; must itself be valid
+    pub replacement: char,
+    /// Maximum length of the result in UTF-8 bytes, if any
+    pub max_length: Option<usize>,
+    /// Platform whose rules apply
+    pub target: SanitizeTarget,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            replacement: '_',
+            max_length: None,
+            target: SanitizeTarget::default(),
+        }
+    }
+}
+
+impl SanitizeOptions {
+    /// Create options matching [`sanitize_filename`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the character substituted for invalid ones
+    pub fn with_replacement(mut self, replacement: char) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Limit names to `max_length` UTF-8 bytes (255 on most filesystems)
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Set the platform whose rules apply
+    pub fn with_target(mut self, target: SanitizeTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sanitize `name` for use as a file or folder name
+    ///
+    /// Leading and trailing whitespace is trimmed. Names longer than
+    /// `max_length` are shortened to `{start}~{hash}.{ext}`, keeping the
+    /// extension; the hash is taken from the full name, so names sharing a
+    /// long prefix stay distinct after truncation.
+    pub fn sanitize(&self, name: &str) -> String {
+        let replaced: String = name
+            .chars()
+            .map(|c| {
+                if self.is_invalid(c) {
+                    self.replacement
+                } else {
+                    c
+                }
+            })
+            .collect();
+        let result = self.apply_target_rules(replaced.trim());
+
+        match self.max_length {
+            Some(max) if result.len() > max => {
+                self.apply_target_rules(&truncate_with_hash(&result, name, max))
+            }
+            _ => result,
+        }
+    }
+
+    fn is_invalid(&self, c: char) -> bool {
+        match self.target {
+            SanitizeTarget::Portable => PORTABLE_INVALID.contains(&c),
+            SanitizeTarget::Windows => PORTABLE_INVALID.contains(&c) || c.is_control(),
+            SanitizeTarget::Unix => c == '/' || c == '\0',
+        }
+    }
+
+    /// Windows silently drops trailing dots and spaces and can't open
+    /// files named after devices, even with an extension
+    fn apply_target_rules(&self, name: &str) -> String {
+        if self.target != SanitizeTarget::Windows {
+            return name.to_string();
+        }
+        let name = name.trim_end_matches(['.', ' ']);
+        let stem = name.split('.').next().unwrap_or_default().trim_end();
+        if WINDOWS_RESERVED
+            .iter()
+            .any(|r| r.eq_ignore_ascii_case(stem))
+        {
+            format!("{}{}", self.replacement, name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// Shorten `name` to at most `max` bytes as `{start}~{hash}.{ext}`
+fn truncate_with_hash(name: &str, original: &str, max: usize) -> String {
+    let hash = blake3::hash(original.as_bytes()).to_hex();
+    let (stem, ext) = split_extension(name);
+    let suffix = format!("~{}{}", &hash[..8], ext);
+    if suffix.len() >= max {
+        return truncate_to_char_boundary(name, max).to_string();
+    }
+    let stem = truncate_to_char_boundary(stem, max - suffix.len()).trim_end();
+    format!("{}{}", stem, suffix)
+}
+
+/// Split off a short alphanumeric extension, including its dot
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(idx)
+            if idx > 0
+                && (2..=9).contains(&(name.len() - idx))
+                && name[idx + 1..].chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            name.split_at(idx)
+        }
+        _ => (name, ""),
+    }
+}
+
+/// The longest prefix of `s` that is at most `max` bytes
+fn truncate_to_char_boundary(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Get the free space available to the current user on the volume containing `path`.
+///
+/// The path does not need to exist yet; the nearest existing ancestor is used.
+/// Returns `None` if the volume cannot be determined.
+pub fn available_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let canonical = existing
+        .canonicalize()
+        .unwrap_or_else(|_| existing.to_path_buf());
+
+    // Strip the Windows verbatim prefix so it compares against mount points
+    let canonical_str = canonical.to_string_lossy();
+    let normalized = PathBuf::from(
+        canonical_str
+            .strip_prefix(r"\\?\")
+            .unwrap_or(&canonical_str),
+    );
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| normalized.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+}
+
+/// Whether two paths live on the same filesystem, so one can be hardlinked
+/// to the other
+///
+/// Compares device IDs on Unix and volume prefixes (drive letter or UNC
+/// share) on Windows. Returns `false` if either path cannot be resolved.
+pub fn same_device(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (std::fs::metadata(a), std::fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev(),
+            _ => false,
+        }
+    }
+    #[cfg(windows)]
+    {
+        let prefix = |p: &Path| {
+            let canonical = p.canonicalize().ok()?;
+            match canonical.components().next()? {
+                std::path::Component::Prefix(prefix) => {
+                    Some(prefix.as_os_str().to_ascii_lowercase())
+                }
+                _ => None,
+            }
+        };
+        matches!((prefix(a), prefix(b)), (Some(a), Some(b)) if a == b)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (a, b);
+        false
+    }
+}
+
+/// Show `path` in the system file manager
+///
+/// Files are selected in their folder where the platform supports it
+/// (`explorer /select,` on Windows, `open -R` on macOS); elsewhere the
+/// containing folder is opened with `xdg-open`. Fails with
+/// [`Error::FileManagerUnavailable`] if the launcher cannot be started.
+pub fn reveal_in_file_manager(path: &Path) -> Result<(), Error> {
+    let metadata = std::fs::metadata(path).map_err(|e| Error::io_at(e, path))?;
+    // Launchers resolve relative paths against their own working directory
+    let absolute;
+    let path = if path.is_relative() {
+        absolute = std::env::current_dir()?.join(path);
+        &absolute
+    } else {
+        path
+    };
+    let mut command = reveal_command(path, metadata.is_dir());
+    let launcher = reveal_launcher();
+    let mut child = command.spawn().map_err(|e| Error::FileManagerUnavailable {
+        launcher,
+        reason: e.to_string(),
+    })?;
+
+    // The launcher may outlive the caller's interest; reap it in the background
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+fn reveal_launcher() -> &'static str {
+    if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    }
+}
+
+fn reveal_command(path: &Path, is_dir: bool) -> std::process::Command {
+    let mut command = std::process::Command::new(reveal_launcher());
+    if cfg!(windows) {
+        if is_dir {
+            command.arg(path);
+        } else {
+            // Explorer parses its own command line and wants only the path
+            // quoted, which `arg` can't produce for paths with spaces
+            #[cfg(windows)]
+            {
+                use std::os::windows::process::CommandExt;
+                let mut select = std::ffi::OsString::from("/select,\"");
+                select.push(path);
+                select.push("\"");
+                command.raw_arg(select);
+            }
+        }
+    } else if cfg!(target_os = "macos") {
+        if !is_dir {
+            command.arg("-R");
+        }
+        command.arg(path);
+    } else if is_dir {
+        command.arg(path);
+    } else {
+        command.arg(path.parent().unwrap_or(path));
+    }
+    command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    command
+}
+
+/// [`write_with_progress`] reports after at least this many bytes...
+pub const PROGRESS_INTERVAL_BYTES: u64 = 4 * 1024 * 1024;
+
+/// ...or after this much time, whichever comes first
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Size of each write issued by [`write_with_progress`]
+const PROGRESS_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Write `content` in chunks, reporting the fraction written (0.0 - 1.0)
+///
+/// Progress is reported every [`PROGRESS_INTERVAL_BYTES`] or
+/// [`PROGRESS_INTERVAL`], whichever comes first. A final `1.0` is only
+/// reported if earlier progress was, so small files cause no calls at all.
+pub fn write_with_progress<W: Write>(
+    writer: &mut W,
+    content: &[u8],
+    mut on_progress: impl FnMut(f32),
+) -> io::Result<()> {
+    let total = content.len() as u64;
+    let mut written = 0u64;
+    let mut reported_bytes = 0u64;
+    let mut reported_at = Instant::now();
+    let mut reported = false;
+
+    for chunk in content.chunks(PROGRESS_CHUNK_SIZE) {
+        writer.write_all(chunk)?;
+        written += chunk.len() as u64;
+        if written < total
+            && (written - reported_bytes >= PROGRESS_INTERVAL_BYTES
+                || reported_at.elapsed() >= PROGRESS_INTERVAL)
+        {
+            on_progress(written as f32 / total as f32);
+            reported_bytes = written;
+            reported_at = Instant::now();
+            reported = true;
+        }
+    }
+
+    if reported {
+        on_progress(1.0);
+    }
+    Ok(())
+}
+
+/// Set how many worker threads parallel scanning, hashing and syncing use
+///
+/// Parallel work runs on rayon's global pool, which defaults to one thread
+/// per logical CPU. On a spinning disk, 1 or 2 threads avoid seek thrashing.
+/// Must be called before any parallel operation starts; the pool cannot be
+/// resized afterwards.
+///
+/// Retried writes (see [`RetryPolicy`]) are unaffected: importers write one
+/// set at a time on the calling thread, not on the pool, so a backoff only
+/// delays that write and never ties up a worker thread.
+pub fn set_worker_threads(threads: usize) -> Result<(), Error> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .map_err(|e| Error::Other(format!("Failed to set worker threads: {}", e)))
+}
+
+/// How to retry filesystem writes that fail with a transient error
+///
+/// On Windows, antivirus scanners briefly lock freshly written files, which
+/// shows up as a sharing violation (os error 32). Such errors are retried
+/// after `backoff`, doubling the delay after each attempt. Permanent errors
+/// like `NotFound` fail immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first
+    pub attempts: u32,
+    /// Delay before the first retry
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 4,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy with the given number of attempts and initial backoff
+    pub fn new(attempts: u32, backoff: Duration) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            backoff,
+        }
+    }
+
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// Run `op`, retrying while it fails with a transient error
+    pub fn run<T, E: TransientError>(&self, mut op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        let mut delay = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match op() {
+                Err(e) if attempt < self.attempts && e.is_transient() => {
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Errors that may go away when the operation is retried
+pub trait TransientError {
+    /// Whether retrying the failed operation could succeed
+    fn is_transient(&self) -> bool;
+}
+
+impl TransientError for io::Error {
+    fn is_transient(&self) -> bool {
+        // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+        #[cfg(windows)]
+        {
+            if matches!(self.raw_os_error(), Some(32 | 33)) {
+                return true;
+            }
+        }
+        matches!(
+            self.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut
+        )
+    }
+}
+
+impl TransientError for Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            Error::Io(e) => e.is_transient(),
+            Error::Zip(zip::result::ZipError::Io(e)) => e.is_transient(),
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_same_device() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sub = temp_dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        assert!(same_device(temp_dir.path(), &sub));
+        assert!(!same_device(
+            temp_dir.path(),
+            &temp_dir.path().join("missing")
+        ));
+    }
+
+    #[test]
+    fn test_reveal_in_file_manager() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let missing = temp_dir.path().join("missing");
+        assert!(matches!(
+            reveal_in_file_manager(&missing),
+            Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let file = temp_dir.path().join("backup.zip");
+            std::fs::write(&file, b"zip").unwrap();
+            let command = reveal_command(&file, false);
+            assert_eq!(command.get_program(), "xdg-open");
+            let args: Vec<_> = command.get_args().collect();
+            assert_eq!(args, [temp_dir.path().as_os_str()]);
+        }
+
+        #[cfg(windows)]
+        {
+            let command = reveal_command(Path::new(r"C:\osu backups\backup.zip"), false);
+            let args: Vec<_> = command.get_args().collect();
+            assert_eq!(args, [r#"/select,"C:\osu backups\backup.zip""#]);
+        }
+    }
+
+    #[test]
+    fn test_write_with_progress() {
+        let content = vec![7u8; 10 * 1024 * 1024];
+        let mut out = Vec::new();
+        let mut reports = Vec::new();
+        write_with_progress(&mut out, &content, |f| reports.push(f)).unwrap();
+
+        assert_eq!(out, content);
+        assert!(reports.len() >= 3);
+        assert!(reports.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(reports.last(), Some(&1.0));
+
+        let mut reports = Vec::new();
+        write_with_progress(&mut Vec::new(), b"small", |f| reports.push(f)).unwrap();
+        assert!(reports.is_empty());
+    }
+
     #[test]
     fn test_sanitize_filename() {
         // Basic cases
@@ -56,4 +570,72 @@ mod tests {
         assert_eq!(sanitize_filename("  leading"), "leading");
         assert_eq!(sanitize_filename("trailing  "), "trailing");
     }
+
+    #[test]
+    fn test_sanitize_options() {
+        let windows = SanitizeOptions::new()
+            .with_target(SanitizeTarget::Windows)
+            .with_replacement('-');
+        assert_eq!(windows.sanitize("a:b\tc. . "), "a-b-c");
+        assert_eq!(windows.sanitize("LPT1"), "-LPT1");
+        assert_eq!(windows.sanitize("console"), "console");
+
+        let unix = SanitizeOptions::new().with_target(SanitizeTarget::Unix);
+        assert_eq!(unix.sanitize("a:b/c?"), "a:b_c?");
+
+        // Long names sharing a prefix keep their extension and stay distinct
+        let short = SanitizeOptions::new().with_max_length(24);
+        let a = short.sanitize("Artist - Very Long Title (TV Size).mp3");
+        let b = short.sanitize("Artist - Very Long Title (Full).mp3");
+        assert!(a.len() <= 24 && b.len() <= 24);
+        assert!(a.starts_with("Artist - ") && a.ends_with(".mp3"));
+        assert_ne!(a, b);
+        assert_eq!(short.sanitize("short.mp3"), "short.mp3");
+
+        // Multi-byte characters are never split
+        let jp = SanitizeOptions::new()
+            .with_max_length(16)
+            .sanitize("ぼくのりりっくのぼうよみ");
+        assert!(jp.len() <= 16);
+    }
+
+    #[test]
+    fn test_available_space_missing_path_uses_ancestor() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let missing = temp_dir.path().join("does/not/exist");
+
+        assert_eq!(available_space(&missing), available_space(temp_dir.path()));
+    }
+
+    #[test]
+    fn test_retry_policy_retries_only_transient_errors() {
+        let policy = RetryPolicy::new(3, Duration::ZERO);
+
+        let mut calls = 0;
+        let result: io::Result<()> = policy.run(|| {
+            calls += 1;
+            Err(io::Error::from(io::ErrorKind::WouldBlock))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result = policy.run(|| {
+            calls += 1;
+            if calls < 2 {
+                Err(io::Error::from(io::ErrorKind::Interrupted))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 2);
+
+        let mut calls = 0;
+        let result: io::Result<()> = policy.run(|| {
+            calls += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
 }
@@ -1,12 +1,14 @@
-//! Game window capture using Windows Graphics Capture API.
+//! Game window capture.
 //!
-//! This module provides functionality to capture screenshots of osu! game windows
-//! on Windows. Uses the `windows-capture` crate for high-performance capture.
+//! This module provides functionality to capture screenshots of osu! game windows.
+//! Windows uses the `windows-capture` crate for high-performance capture.
 //!
 //! # Platform Support
 //!
 //! - **Windows**: Full support using Windows Graphics Capture API
-//! - **Linux/macOS**: Not supported (returns error)
+//! - **Linux** (`linux-vision` feature): X11 (including XWayland) via `GetImage`,
+//!   falling back to the Wayland screenshot portal
+//! - **macOS**: Not supported (returns error)
 
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
@@ -33,6 +35,69 @@ impl CapturedFrame {
         std::fs::write(path, &self.png_bytes)
             .map_err(|e| Error::Other(format!("Failed to save capture: {}", e)))
     }
+
+    /// Return a new frame containing only the given pixel region
+    ///
+    /// Fails if the region is empty or extends past the frame.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<CapturedFrame> {
+        let fits =
+            |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+        if width == 0 || height == 0 || !fits(x, width, self.width) || !fits(y, height, self.height)
+        {
+            return Err(Error::Other(format!(
+                "Crop region {}x{} at ({}, {}) is outside the {}x{} frame",
+                width, height, x, y, self.width, self.height
+            )));
+        }
+
+        let image = self.decode()?.crop_imm(x, y, width, height);
+        self.with_image(&image)
+    }
+
+    /// Return a new frame scaled down so neither side exceeds `max_dim`
+    ///
+    /// The aspect ratio is preserved. Frames that already fit are returned
+    /// unchanged.
+    pub fn resize(&self, max_dim: u32) -> Result<CapturedFrame> {
+        if max_dim == 0 {
+            return Err(Error::Other(
+                "Resize target must be at least 1 pixel".into(),
+            ));
+        }
+        if self.width <= max_dim && self.height <= max_dim {
+            return Ok(self.clone());
+        }
+
+        let image = self
+            .decode()?
+            .resize(max_dim, max_dim, image::imageops::FilterType::Triangle);
+        self.with_image(&image)
+    }
+
+    /// Decode the PNG bytes into a pixel buffer
+    fn decode(&self) -> Result<image::DynamicImage> {
+        image::load_from_memory_with_format(&self.png_bytes, image::ImageFormat::Png)
+            .map_err(|e| Error::Other(format!("Failed to decode capture: {}", e)))
+    }
+
+    /// Build a frame from an image, keeping this frame's title and timestamp
+    fn with_image(&self, image: &image::DynamicImage) -> Result<CapturedFrame> {
+        let mut png_bytes = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut png_bytes),
+                image::ImageFormat::Png,
+            )
+            .map_err(|e| Error::Other(format!("Failed to encode capture: {}", e)))?;
+
+        Ok(CapturedFrame {
+            png_bytes,
+            width: image.width(),
+            height: image.height(),
+            window_title: self.window_title.clone(),
+            timestamp: self.timestamp.clone(),
+        })
+    }
 }
 
 /// Game variant to capture
@@ -311,21 +376,333 @@ mod windows_impl {
 }
 
 // ============================================================================
-// Non-Windows Stubs
+// Linux Implementation
 // ============================================================================
 
-#[cfg(not(windows))]
+#[cfg(all(target_os = "linux", feature = "linux-vision"))]
+mod linux_impl {
+    use super::*;
+
+    /// Capture a screenshot of an osu! game window
+    ///
+    /// Tries X11 first, which also sees games running under XWayland. If no X
+    /// server is reachable, the Wayland screenshot portal captures the whole
+    /// output, since Wayland does not expose other clients' windows.
+    pub fn capture_game_window(target: CaptureTarget) -> Result<CapturedFrame> {
+        match x11::capture(target) {
+            Ok(frame) => Ok(frame),
+            Err(x11::X11Error::NoDisplay(reason)) => portal::capture().map_err(|portal_error| {
+                Error::Other(format!(
+                    "No capture backend available: X11 unavailable ({}), \
+                         Wayland portal failed ({})",
+                    reason, portal_error
+                ))
+            }),
+            Err(x11::X11Error::Other(e)) => Err(e),
+        }
+    }
+
+    /// List all capturable osu! windows
+    ///
+    /// Only X11 windows can be enumerated; on pure Wayland this is empty.
+    pub fn list_osu_windows() -> Result<Vec<WindowInfo>> {
+        match x11::list_windows() {
+            Ok(windows) => Ok(windows.into_iter().map(|(_, info)| info).collect()),
+            Err(x11::X11Error::NoDisplay(_)) => Ok(Vec::new()),
+            Err(x11::X11Error::Other(e)) => Err(e),
+        }
+    }
+
+    /// WM_CLASS names of osu!lazer's window
+    const LAZER_CLASSES: &[&str] = &["osu!", "osu", "osu!lazer"];
+
+    /// WM_CLASS name of osu!stable's window; stable runs through Wine, which
+    /// names the class after the .exe
+    const STABLE_CLASS: &str = "osu!.exe";
+
+    /// Split a raw WM_CLASS value ("instance\0class\0") into its names
+    fn class_names(class: &str) -> impl Iterator<Item = &str> {
+        class.split('\0').filter(|name| !name.is_empty())
+    }
+
+    /// Check if a raw WM_CLASS value belongs to an osu! window
+    ///
+    /// Titles are ignored: browser tabs and editors often have "osu!" in theirs.
+    pub(super) fn is_osu_window(class: &str) -> bool {
+        class_names(class).any(|name| {
+            name.eq_ignore_ascii_case(STABLE_CLASS)
+                || LAZER_CLASSES.iter().any(|c| name.eq_ignore_ascii_case(c))
+        })
+    }
+
+    /// Check if a raw WM_CLASS value belongs to osu!lazer
+    pub(super) fn is_lazer_window(class: &str) -> bool {
+        class_names(class).any(|name| LAZER_CLASSES.iter().any(|c| name.eq_ignore_ascii_case(c)))
+            && !class_names(class).any(|name| name.eq_ignore_ascii_case(STABLE_CLASS))
+    }
+
+    /// Encode an RGBA buffer as a PNG frame
+    fn encode_frame(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        window_title: String,
+    ) -> Result<CapturedFrame> {
+        let image = image::RgbaImage::from_raw(width, height, rgba)
+            .ok_or_else(|| Error::Other("Captured image has an unexpected size".into()))?;
+        let mut png_bytes = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut png_bytes),
+                image::ImageFormat::Png,
+            )
+            .map_err(|e| Error::Other(format!("Failed to encode capture: {}", e)))?;
+
+        Ok(CapturedFrame {
+            png_bytes,
+            width,
+            height,
+            window_title,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    mod x11 {
+        use super::*;
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, ImageFormat, Window};
+        use x11rb::rust_connection::RustConnection;
+
+        /// X11 failures, split so callers can fall back when there is no X server
+        pub enum X11Error {
+            NoDisplay(String),
+            Other(Error),
+        }
+
+        fn other(context: &str, e: impl std::fmt::Display) -> X11Error {
+            X11Error::Other(Error::Other(format!("{}: {}", context, e)))
+        }
+
+        fn connect() -> std::result::Result<(RustConnection, usize), X11Error> {
+            x11rb::connect(None).map_err(|e| X11Error::NoDisplay(e.to_string()))
+        }
+
+        fn atom(conn: &RustConnection, name: &[u8]) -> std::result::Result<u32, X11Error> {
+            conn.intern_atom(false, name)
+                .map_err(|e| other("X11 request failed", e))?
+                .reply()
+                .map(|r| r.atom)
+                .map_err(|e| other("X11 request failed", e))
+        }
+
+        fn text_property(
+            conn: &RustConnection,
+            window: Window,
+            property: u32,
+            kind: u32,
+        ) -> String {
+            conn.get_property(false, window, property, kind, 0, 1024)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+                .unwrap_or_default()
+        }
+
+        /// Enumerate top-level osu! windows managed by the window manager
+        pub fn list_windows() -> std::result::Result<Vec<(Window, WindowInfo)>, X11Error> {
+            let (conn, screen_num) = connect()?;
+            list_with(&conn, screen_num)
+        }
+
+        fn list_with(
+            conn: &RustConnection,
+            screen_num: usize,
+        ) -> std::result::Result<Vec<(Window, WindowInfo)>, X11Error> {
+            let root = conn.setup().roots[screen_num].root;
+            let client_list = atom(conn, b"_NET_CLIENT_LIST")?;
+            let net_wm_name = atom(conn, b"_NET_WM_NAME")?;
+            let utf8_string = atom(conn, b"UTF8_STRING")?;
+
+            let clients: Vec<Window> = conn
+                .get_property(false, root, client_list, AtomEnum::WINDOW, 0, u32::MAX)
+                .map_err(|e| other("Failed to enumerate windows", e))?
+                .reply()
+                .map_err(|e| other("Failed to enumerate windows", e))?
+                .value32()
+                .map(|values| values.collect())
+                .unwrap_or_default();
+
+            let mut result = Vec::new();
+            for window in clients {
+                let mut title = text_property(conn, window, net_wm_name, utf8_string);
+                if title.is_empty() {
+                    title = text_property(
+                        conn,
+                        window,
+                        AtomEnum::WM_NAME.into(),
+                        AtomEnum::STRING.into(),
+                    );
+                }
+                // WM_CLASS holds "instance\0class\0"
+                let class = text_property(
+                    conn,
+                    window,
+                    AtomEnum::WM_CLASS.into(),
+                    AtomEnum::STRING.into(),
+                );
+
+                if !is_osu_window(&class) {
+                    continue;
+                }
+
+                let (width, height) = conn
+                    .get_geometry(window)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .map(|g| (u32::from(g.width), u32::from(g.height)))
+                    .unwrap_or((0, 0));
+                let class_name = class_names(&class).last().map(str::to_string);
+
+                result.push((
+                    window,
+                    WindowInfo {
+                        is_lazer: is_lazer_window(&class),
+                        title,
+                        width,
+                        height,
+                        process_name: class_name,
+                    },
+                ));
+            }
+            Ok(result)
+        }
+
+        /// Capture an osu! window with a core `GetImage` request
+        pub fn capture(target: CaptureTarget) -> std::result::Result<CapturedFrame, X11Error> {
+            let (conn, screen_num) = connect()?;
+            let (window, info) = list_with(&conn, screen_num)?
+                .into_iter()
+                .find(|(_, info)| match target {
+                    CaptureTarget::Stable => !info.is_lazer,
+                    CaptureTarget::Lazer => info.is_lazer,
+                    CaptureTarget::Any => true,
+                })
+                .ok_or_else(|| {
+                    X11Error::Other(Error::Other(format!(
+                        "No {} window found. Make sure the game is running.",
+                        target
+                    )))
+                })?;
+
+            let geometry = conn
+                .get_geometry(window)
+                .map_err(|e| other("Failed to query window size", e))?
+                .reply()
+                .map_err(|e| other("Failed to query window size", e))?;
+            let image = conn
+                .get_image(
+                    ImageFormat::Z_PIXMAP,
+                    window,
+                    0,
+                    0,
+                    geometry.width,
+                    geometry.height,
+                    u32::MAX,
+                )
+                .map_err(|e| other("Capture failed", e))?
+                .reply()
+                .map_err(|e| other("Capture failed (is the window minimized?)", e))?;
+
+            if image.depth != 24 && image.depth != 32 {
+                return Err(X11Error::Other(Error::Other(format!(
+                    "Unsupported X11 window depth: {}",
+                    image.depth
+                ))));
+            }
+
+            // 24/32-bit ZPixmap data is BGRX in little-endian byte order
+            let mut rgba = image.data;
+            for pixel in rgba.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+                pixel[3] = 255;
+            }
+
+            encode_frame(
+                rgba,
+                u32::from(geometry.width),
+                u32::from(geometry.height),
+                info.title,
+            )
+            .map_err(X11Error::Other)
+        }
+    }
+
+    mod portal {
+        use super::*;
+        use ashpd::desktop::screenshot::Screenshot;
+
+        /// Capture the whole output through the xdg-desktop-portal screenshot API
+        pub fn capture() -> Result<CapturedFrame> {
+            // Runs on its own thread so it also works when called from async code
+            let uri = std::thread::spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| e.to_string())?;
+                runtime.block_on(async {
+                    let response = Screenshot::request()
+                        .interactive(false)
+                        .modal(false)
+                        .send()
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .response()
+                        .map_err(|e| e.to_string())?;
+                    Ok::<_, String>(response.uri().clone())
+                })
+            })
+            .join()
+            .map_err(|_| Error::Other("Screenshot portal thread panicked".into()))?
+            .map_err(Error::Other)?;
+
+            let path = uri
+                .to_file_path()
+                .map_err(|_| Error::Other(format!("Unexpected screenshot location: {}", uri)))?;
+            let bytes = std::fs::read(&path)?;
+            let image = image::load_from_memory(&bytes)
+                .map_err(|e| Error::Other(format!("Failed to decode screenshot: {}", e)))?
+                .into_rgba8();
+            let _ = std::fs::remove_file(&path);
+
+            let (width, height) = image.dimensions();
+            encode_frame(
+                image.into_raw(),
+                width,
+                height,
+                "Screen (Wayland portal)".into(),
+            )
+        }
+    }
+}
+
+// ============================================================================
+// Stubs for Unsupported Platforms
+// ============================================================================
+
+#[cfg(not(any(windows, all(target_os = "linux", feature = "linux-vision"))))]
 mod stub_impl {
     use super::*;
 
-    /// Capture is not supported on non-Windows platforms
+    /// Capture is not supported on this platform
     pub fn capture_game_window(_target: CaptureTarget) -> Result<CapturedFrame> {
         Err(Error::Other(
-            "Game window capture is only supported on Windows".into(),
+            "Game window capture is only supported on Windows and on Linux with \
+             the `linux-vision` feature"
+                .into(),
         ))
     }
 
-    /// List is not supported on non-Windows platforms
+    /// List is not supported on this platform
     pub fn list_osu_windows() -> Result<Vec<WindowInfo>> {
         Ok(Vec::new())
     }
@@ -339,7 +716,8 @@ mod stub_impl {
 ///
 /// # Platform Support
 ///
-/// This function only works on Windows. On other platforms, it returns an error.
+/// Works on Windows, and on Linux when built with the `linux-vision` feature.
+/// On other platforms, it returns an error.
 ///
 /// # Example
 ///
@@ -352,14 +730,20 @@ mod stub_impl {
 #[cfg(windows)]
 pub use windows_impl::capture_game_window;
 
-#[cfg(not(windows))]
+#[cfg(all(target_os = "linux", feature = "linux-vision"))]
+pub use linux_impl::capture_game_window;
+
+#[cfg(not(any(windows, all(target_os = "linux", feature = "linux-vision"))))]
 pub use stub_impl::capture_game_window;
 
 /// List all capturable osu! windows.
 #[cfg(windows)]
 pub use windows_impl::list_osu_windows;
 
-#[cfg(not(windows))]
+#[cfg(all(target_os = "linux", feature = "linux-vision"))]
+pub use linux_impl::list_osu_windows;
+
+#[cfg(not(any(windows, all(target_os = "linux", feature = "linux-vision"))))]
 pub use stub_impl::list_osu_windows;
 
 #[cfg(test)]
@@ -373,6 +757,57 @@ mod tests {
         assert_eq!(CaptureTarget::Any.display_name(), "osu!");
     }
 
+    #[cfg(all(target_os = "linux", feature = "linux-vision"))]
+    #[test]
+    fn test_linux_window_matching() {
+        assert!(linux_impl::is_osu_window("osu!\0osu!\0"));
+        assert!(linux_impl::is_osu_window("osu!.exe\0osu!.exe\0"));
+        assert!(!linux_impl::is_osu_window("kitty\0kitty\0"));
+        // A browser tab titled "osu! - beatmap listing" is still a browser
+        assert!(!linux_impl::is_osu_window("Navigator\0firefox\0"));
+        assert!(!linux_impl::is_osu_window("osu-sync\0osu-sync\0"));
+        assert!(linux_impl::is_lazer_window("osu!\0osu!\0"));
+        assert!(!linux_impl::is_lazer_window("osu!.exe\0osu!.exe\0"));
+    }
+
+    fn test_frame(width: u32, height: u32) -> CapturedFrame {
+        let image = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            width,
+            height,
+            image::Rgba([255, 0, 0, 255]),
+        ));
+        let mut png_bytes = Vec::new();
+        image
+            .write_to(
+                &mut std::io::Cursor::new(&mut png_bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        CapturedFrame {
+            png_bytes,
+            width,
+            height,
+            window_title: "osu!".into(),
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_crop_and_resize() {
+        let frame = test_frame(100, 50);
+
+        let cropped = frame.crop(10, 10, 40, 20).unwrap();
+        assert_eq!((cropped.width, cropped.height), (40, 20));
+        assert_eq!(cropped.decode().unwrap().width(), 40);
+        assert!(frame.crop(90, 0, 20, 10).is_err());
+        assert!(frame.crop(0, 0, 0, 10).is_err());
+        assert!(frame.crop(u32::MAX, 0, 1, 1).is_err());
+
+        let resized = frame.resize(20).unwrap();
+        assert_eq!((resized.width, resized.height), (20, 10));
+        assert_eq!(frame.resize(200).unwrap().width, 100);
+    }
+
     #[test]
     fn test_list_windows_doesnt_crash() {
         // Should not panic even if no windows found
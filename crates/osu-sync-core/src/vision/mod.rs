@@ -12,7 +12,8 @@
 //! ## Platform Support
 //!
 //! - **Windows**: Full support using Windows Graphics Capture API
-//! - **Linux/macOS**: Not supported (stub implementations)
+//! - **Linux**: X11 and the Wayland screenshot portal (`linux-vision` feature)
+//! - **macOS**: Not supported (stub implementations)
 //!
 //! ## Usage
 //!
@@ -4,7 +4,7 @@
 //! across all three modes: StableMaster, LazerMaster, and TrueUnified.
 
 use osu_sync_core::unified::{
-    SharedResourceType, UnifiedStorageConfig, UnifiedStorageEngine, UnifiedStorageMode,
+    LinkManager, SharedResourceType, UnifiedStorageConfig, UnifiedStorageEngine, UnifiedStorageMode,
 };
 use std::fs::{self, File};
 use std::io::Write;
@@ -209,6 +209,40 @@ fn test_stable_master_verify_detects_healthy_links() {
     assert_eq!(verify_result.broken, 0);
 }
 
+#[test]
+fn test_stable_master_verify_reports_space_saved() {
+    let fixture = TestFixture::new();
+    fixture.create_stable_songs(3);
+
+    let config = UnifiedStorageConfig::stable_master();
+    let mut engine = UnifiedStorageEngine::new(
+        config,
+        fixture.stable_path.clone(),
+        fixture.lazer_path.clone(),
+    )
+    .expect("Failed to create engine");
+
+    engine.setup().expect("Setup failed");
+
+    let songs_size: u64 = (0..3)
+        .map(|i| {
+            let osu_file = fixture
+                .stable_path
+                .join("Songs")
+                .join(format!("beatmap_{}", i))
+                .join(format!("beatmap_{}.osu", i));
+            fs::metadata(osu_file).expect("Missing beatmap file").len()
+        })
+        .sum();
+
+    let verify_result = engine.verify().expect("Verify failed");
+    assert!(songs_size > 0);
+    assert_eq!(
+        verify_result.space_saved, songs_size,
+        "Songs linked into lazer should count once as saved"
+    );
+}
+
 #[test]
 fn test_stable_master_teardown_removes_links() {
     let fixture = TestFixture::new();
@@ -371,6 +405,46 @@ fn test_true_unified_both_installations_link_to_shared() {
     );
 }
 
+#[test]
+fn test_true_unified_leaves_excluded_skin_unlinked() {
+    let fixture = TestFixture::new();
+    fixture.create_stable_skins(2);
+    fixture.create_lazer_skins(1);
+
+    let mut config = UnifiedStorageConfig::true_unified(fixture.shared_path.clone());
+    config.exclude_path("Skins/lazer_skin_0");
+    let mut engine = UnifiedStorageEngine::new(
+        config,
+        fixture.stable_path.clone(),
+        fixture.lazer_path.clone(),
+    )
+    .expect("Failed to create engine");
+
+    engine.setup().expect("Setup failed");
+
+    let lazer_skins = fixture.lazer_path.join("Skins");
+    let excluded = lazer_skins.join("lazer_skin_0");
+    assert!(
+        !LinkManager::is_link(&lazer_skins),
+        "Skins should stay a real folder"
+    );
+    assert!(
+        excluded.join("skin.ini").exists(),
+        "Excluded skin should stay in place"
+    );
+    assert!(!LinkManager::is_link(&excluded));
+    assert!(!fixture
+        .shared_path
+        .join("Skins")
+        .join("lazer_skin_0")
+        .exists());
+    assert!(LinkManager::is_link(&lazer_skins.join("skin_0")));
+    assert!(engine.manifest().find_by_link_path(&excluded).is_none());
+
+    let verify_result = engine.verify().expect("Verify failed");
+    assert!(verify_result.is_healthy());
+}
+
 // =============================================================================
 // Verification and Repair Tests
 // =============================================================================
@@ -379,9 +453,9 @@ fn test_true_unified_both_installations_link_to_shared() {
 fn test_verify_empty_manifest_is_healthy() {
     let fixture = TestFixture::new();
 
-    // For disabled mode, engine creation should fail or verify should still work
-    // Let's test with an enabled config but no setup
-    let config = UnifiedStorageConfig::stable_master();
+    // For disabled mode, engine creation should fail or verify should still work
+    // Let's test with an enabled config but no setup
+    let config = UnifiedStorageConfig::stable_master();
     let engine = UnifiedStorageEngine::new(
         config,
         fixture.stable_path.clone(),
@@ -497,11 +571,11 @@ fn test_true_unified_requires_shared_path() {
     let _fixture = TestFixture::new();
 
     // Create TrueUnified config without shared path
-    let config = UnifiedStorageConfig {
-        mode: UnifiedStorageMode::TrueUnified,
-        shared_path: None,
-        ..UnifiedStorageConfig::default()
-    };
+    let config = UnifiedStorageConfig {
+        mode: UnifiedStorageMode::TrueUnified,
+        shared_path: None,
+        ..UnifiedStorageConfig::default()
+    };
 
     // Validation should fail
     assert!(
@@ -596,19 +670,19 @@ fn test_setup_is_idempotent() {
     )
     .expect("Failed to create engine");
 
-    // First setup
-    engine.setup().expect("First setup failed");
-    let verify1 = engine.verify().expect("Verify after first setup failed");
-
-    // Second setup should be safe (idempotent)
-    engine.setup().expect("Second setup failed");
-
-    // Verify state is still correct
-    let verify2 = engine.verify().expect("Verify failed");
-    assert!(verify2.is_healthy());
-    assert_eq!(verify1.total_links, verify2.total_links);
-    assert_eq!(verify1.active, verify2.active);
-    assert_eq!(verify1.broken, verify2.broken);
+    // First setup
+    engine.setup().expect("First setup failed");
+    let verify1 = engine.verify().expect("Verify after first setup failed");
+
+    // Second setup should be safe (idempotent)
+    engine.setup().expect("Second setup failed");
+
+    // Verify state is still correct
+    let verify2 = engine.verify().expect("Verify failed");
+    assert!(verify2.is_healthy());
+    assert_eq!(verify1.total_links, verify2.total_links);
+    assert_eq!(verify1.active, verify2.active);
+    assert_eq!(verify1.broken, verify2.broken);
 }
 
 #[test]
@@ -839,26 +913,26 @@ fn test_multiple_setup_teardown_cycles() {
         )
         .expect("Failed to create engine");
 
-        let setup_result = engine
-            .setup()
-            .unwrap_or_else(|_| panic!("Setup failed on cycle {}", cycle));
+        let setup_result = engine
+            .setup()
+            .unwrap_or_else(|_| panic!("Setup failed on cycle {}", cycle));
         assert!(
             setup_result.links_created >= 1 || cycle > 0,
             "Should create links on first cycle"
         );
 
-        let verify_result = engine
-            .verify()
-            .unwrap_or_else(|_| panic!("Verify failed on cycle {}", cycle));
+        let verify_result = engine
+            .verify()
+            .unwrap_or_else(|_| panic!("Verify failed on cycle {}", cycle));
         assert!(
             verify_result.is_healthy(),
             "Should be healthy on cycle {}",
             cycle
         );
 
-        engine
-            .teardown()
-            .unwrap_or_else(|_| panic!("Teardown failed on cycle {}", cycle));
+        engine
+            .teardown()
+            .unwrap_or_else(|_| panic!("Teardown failed on cycle {}", cycle));
     }
 }
 